@@ -87,6 +87,8 @@ public class RpcExecutionClient extends ExecutionClient<RpcChannel> {
                         .setBatchSize(PegasusConfig.PEGASUS_BATCH_SIZE.get(graphConfig))
                         .setMemoryLimit(PegasusConfig.PEGASUS_MEMORY_LIMIT.get(graphConfig))
                         .setBatchCapacity(PegasusConfig.PEGASUS_OUTPUT_CAPACITY.get(graphConfig))
+                        .setApplyBatchSize(
+                                PegasusConfig.PEGASUS_APPLY_BATCH_SIZE.get(graphConfig))
                         .setTimeLimit(timeoutConfig.getEngineTimeoutMS())
                         .setAll(
                                 com.alibaba.pegasus.service.protocol.PegasusClient.Empty
@@ -29,6 +29,10 @@ public class PegasusConfig {
     public static final Config<Integer> PEGASUS_MEMORY_LIMIT =
             Config.intConfig("pegasus.memory.limit", Integer.MAX_VALUE);
 
+    // 0 means to use the default of the server
+    public static final Config<Integer> PEGASUS_APPLY_BATCH_SIZE =
+            Config.intConfig("pegasus.apply.batch.size", 0);
+
     public static final Config<String> PEGASUS_HOSTS =
             Config.stringConfig("pegasus.hosts", "localhost:8080");
 }
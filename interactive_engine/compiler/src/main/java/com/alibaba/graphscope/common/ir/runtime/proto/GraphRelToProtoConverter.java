@@ -754,6 +754,12 @@ public class GraphRelToProtoConverter extends GraphShuttle {
 
                 orderingPairBuilder.setKey(var);
                 orderingPairBuilder.setOrder(Utils.protoOrderOpt(collations.get(i).getDirection()));
+                // keep the default, i.e., the nulls last in ascending order and first in descending order
+                RelFieldCollation collation = collations.get(i);
+                if (collation.nullDirection != collation.direction.defaultNullDirection()) {
+                    orderingPairBuilder.setNullOrder(
+                            Utils.protoNullOrderOpt(collation.nullDirection));
+                }
                 orderByBuilder.addPairs(orderingPairBuilder.build());
             }
             if (sort.offset != null || sort.fetch != null) {
@@ -467,6 +467,18 @@ public abstract class Utils {
         }
     }
 
+    public static final GraphAlgebra.OrderBy.OrderingPair.NullOrder protoNullOrderOpt(
+            RelFieldCollation.NullDirection nullDirection) {
+        switch (nullDirection) {
+            case FIRST:
+                return GraphAlgebra.OrderBy.OrderingPair.NullOrder.NULLS_FIRST;
+            case LAST:
+                return GraphAlgebra.OrderBy.OrderingPair.NullOrder.NULLS_LAST;
+            default:
+                return GraphAlgebra.OrderBy.OrderingPair.NullOrder.DEFAULT;
+        }
+    }
+
     public static final GraphAlgebraPhysical.EdgeExpand.Direction protoExpandDirOpt(
             GraphOpt.Expand opt) {
         switch (opt) {
@@ -44,6 +44,10 @@ public interface IrCoreLibrary extends Library {
 
     FfiData.ByValue buildPhysicalPlan(Pointer plan, int workers, int servers, int planId);
 
+    FfiResult.ByValue setAllowMissingTags(Pointer plan);
+
+    FfiResult.ByValue setColumnPruning(Pointer plan);
+
     Pointer initScanOperator(FfiScanOpt opt);
 
     FfiResult.ByValue appendScanOperator(
@@ -32,6 +32,7 @@ import org.checkerframework.checker.nullness.qual.Nullable;
 import org.slf4j.Logger;
 import org.slf4j.LoggerFactory;
 
+import java.math.BigInteger;
 import java.util.ArrayList;
 import java.util.List;
 import java.util.Map;
@@ -132,6 +133,22 @@ public class Utils {
         return graphPathType;
     }
 
+    /**
+     * parse the unsigned 128-bit integer, which is split into its high and low 64 bits
+     */
+    public static BigInteger parseU128(Common.U128 value) {
+        BigInteger hi = new BigInteger(Long.toUnsignedString(value.getHi()));
+        BigInteger lo = new BigInteger(Long.toUnsignedString(value.getLo()));
+        return hi.shiftLeft(64).or(lo);
+    }
+
+    /**
+     * parse the unsigned 64-bit integer, which is kept as a {@code Long} if it fits, otherwise as a {@code BigInteger}
+     */
+    public static Number parseU64(long value) {
+        return value >= 0 ? (Number) value : new BigInteger(Long.toUnsignedString(value));
+    }
+
     public static String parseLabelValue(Common.Value value, GraphLabelType type) {
         switch (value.getItemCase()) {
             case STR:
@@ -312,11 +312,17 @@ public class CypherRecordParser implements RecordParser<AnyValue> {
             case I32:
                 return Values.intValue(value.getI32());
             case U32:
-                return Values.intValue(value.getU32());
+                return Values.longValue(Integer.toUnsignedLong(value.getU32()));
             case I64:
                 return Values.longValue(value.getI64());
             case U64:
-                return Values.longValue(value.getU64());
+                // the value beyond the range of the 64-bit integer in cypher is kept as its decimal string
+                return value.getU64() >= 0
+                        ? Values.longValue(value.getU64())
+                        : Values.stringValue(Long.toUnsignedString(value.getU64()));
+            case U128:
+                // there is no 128-bit integer value in cypher, which is kept as its decimal string
+                return Values.stringValue(Utils.parseU128(value.getU128()).toString());
             case F32:
                 return Values.floatValue(value.getF32());
             case F64:
@@ -333,6 +339,18 @@ public class CypherRecordParser implements RecordParser<AnyValue> {
                         value.getI64Array().getItemList().stream()
                                 .mapToLong(k -> k.longValue())
                                 .toArray());
+            case U32_ARRAY:
+                return Values.longArray(
+                        value.getU32Array().getItemList().stream()
+                                .mapToLong(Integer::toUnsignedLong)
+                                .toArray());
+            case U64_ARRAY:
+                List<Long> u64Items = value.getU64Array().getItemList();
+                if (u64Items.stream().allMatch(k -> k >= 0)) {
+                    return Values.longArray(u64Items.stream().mapToLong(k -> k).toArray());
+                }
+                return Values.stringArray(
+                        u64Items.stream().map(Long::toUnsignedString).toArray(String[]::new));
             case F64_ARRAY:
                 return Values.doubleArray(
                         value.getF64Array().getItemList().stream()
@@ -448,6 +448,9 @@ public class IrStandardOpProcessor extends StandardOpProcessor {
                                             .setBatchCapacity(
                                                     PegasusConfig.PEGASUS_OUTPUT_CAPACITY.get(
                                                             queryConfigs))
+                                            .setApplyBatchSize(
+                                                    PegasusConfig.PEGASUS_APPLY_BATCH_SIZE.get(
+                                                            queryConfigs))
                                             .setTimeLimit(timeoutConfig.getEngineTimeoutMS())
                                             .setAll(PegasusClient.Empty.newBuilder().build())
                                             .build();
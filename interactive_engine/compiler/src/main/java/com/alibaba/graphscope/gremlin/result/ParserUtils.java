@@ -18,6 +18,7 @@ package com.alibaba.graphscope.gremlin.result;
 
 import com.alibaba.graphscope.common.jna.IrCoreLibrary;
 import com.alibaba.graphscope.common.jna.type.*;
+import com.alibaba.graphscope.common.result.Utils;
 import com.alibaba.graphscope.gaia.proto.Common;
 import com.alibaba.graphscope.gaia.proto.IrResult;
 import com.alibaba.graphscope.gremlin.exception.GremlinResultParserException;
@@ -90,6 +91,8 @@ public class ParserUtils {
                 return value.getI64();
             case U64:
                 return value.getU64();
+            case U128:
+                return Utils.parseU128(value.getU128());
             case F32:
                 return value.getF32();
             case F64:
@@ -154,6 +157,10 @@ public class ParserUtils {
                 return pairInMap;
             case STR_ARRAY:
                 return value.getStrArray().getItemList();
+            case VALUE_ARRAY:
+                return value.getValueArray().getItemList().stream()
+                        .map(k -> parseCommonValue(k))
+                        .collect(Collectors.toList());
             case NONE:
                 return EmptyValue.INSTANCE;
             default:
@@ -272,11 +272,13 @@ public class GremlinRecordParser implements RecordParser<Object> {
             case I32:
                 return value.getI32();
             case U32:
-                return value.getU32();
+                return Integer.toUnsignedLong(value.getU32());
             case I64:
                 return value.getI64();
             case U64:
-                return value.getU64();
+                return Utils.parseU64(value.getU64());
+            case U128:
+                return Utils.parseU128(value.getU128());
             case F32:
                 return value.getF32();
             case F64:
@@ -287,10 +289,23 @@ public class GremlinRecordParser implements RecordParser<Object> {
                 return value.getI32Array().getItemList();
             case I64_ARRAY:
                 return value.getI64Array().getItemList();
+            case U32_ARRAY:
+                return value.getU32Array().getItemList().stream()
+                        .map(Integer::toUnsignedLong)
+                        .collect(Collectors.toList());
+            case U64_ARRAY:
+                return value.getU64Array().getItemList().stream()
+                        .map(Utils::parseU64)
+                        .collect(Collectors.toList());
             case F64_ARRAY:
                 return value.getF64Array().getItemList();
             case STR_ARRAY:
                 return value.getStrArray().getItemList();
+            case VALUE_ARRAY:
+                RelDataType componentType = dataType == null ? null : dataType.getComponentType();
+                return value.getValueArray().getItemList().stream()
+                        .map(k -> parseValue(k, componentType))
+                        .collect(Collectors.toList());
             case PAIR_ARRAY:
                 if (dataType instanceof MapSqlType) {
                     return value.getPairArray().getItemList().stream()
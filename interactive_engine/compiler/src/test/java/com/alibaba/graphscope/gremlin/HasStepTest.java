@@ -244,6 +244,14 @@ public class HasStepTest {
         Assert.assertEquals("@.age > 20 && (@.age < 30)", op.getPredicate().get().applyArg());
     }
 
+    @Test
+    public void g_V_has_P_between_test() {
+        Traversal traversal = g.V().has("age", P.between(20, 30));
+        Step hasStep = traversal.asAdmin().getEndStep();
+        SelectOp op = (SelectOp) StepTransformFactory.HAS_STEP.apply(hasStep);
+        Assert.assertEquals("@.age >= 20 && (@.age < 30)", op.getPredicate().get().applyArg());
+    }
+
     @Test
     public void g_V_has_P_outside_test() {
         Traversal traversal = g.V().has("age", P.outside(20, 30));
@@ -21,9 +21,9 @@ use std::time::Duration;
 
 use gaia_pegasus::Configuration as GaiaConfig;
 use global_query::GlobalGraph;
-use graph_proxy::{apis::PegasusClusterInfo, create_gs_store, GrootMultiPartition};
+use graph_proxy::{apis::PegasusClusterInfo, create_gs_store_with_setup_timeout, GrootMultiPartition};
 use groot_store::api::PartitionId;
-use groot_store::db::api::{GraphConfig, GraphResult};
+use groot_store::db::api::{ErrorCode, GraphConfig, GraphError, GraphResult};
 use groot_store::db::graph::store::GraphStore;
 use pegasus_network::config::{NetworkConfig, ServerAddr};
 use pegasus_network::SimpleServerDetector;
@@ -73,19 +73,41 @@ impl GaiaServer {
         let gaia_config = make_gaia_config(self.config.clone());
         let gaia_rpc_config = make_gaia_rpc_config(self.config.clone());
         info!("Server config {:?}\nRPC config {:?}", gaia_config, gaia_rpc_config);
-        let (server_port, rpc_port) = self.rpc_runtime.block_on(async {
-            let column_filter_push_down = false;
-            #[cfg(feature = "column_filter_push_down")]
-            let column_filter_push_down = true;
-            let cluster_info = Arc::new(PegasusClusterInfo::default());
-            let gs_store = create_gs_store(
-                self.graph.clone(),
-                self.graph.clone(),
-                self.graph.get_process_partition_list(),
-                cluster_info.clone(),
-                true,
-                column_filter_push_down,
+        let column_filter_push_down = false;
+        #[cfg(feature = "column_filter_push_down")]
+        let column_filter_push_down = true;
+        let cluster_info = Arc::new(PegasusClusterInfo::default());
+        // the time limit of setting up a scan in the store, which is unbounded if not given
+        let store_setup_timeout_ms: Option<u64> = self
+            .config
+            .get_storage_option("gaia.store.setup.timeout.ms")
+            .map(|config_str| {
+                config_str
+                    .parse()
+                    .expect("parse gaia.store.setup.timeout.ms failed")
+            });
+        let gs_store = create_gs_store_with_setup_timeout(
+            self.graph.clone(),
+            self.graph.clone(),
+            self.graph.get_process_partition_list(),
+            cluster_info.clone(),
+            true,
+            column_filter_push_down,
+            store_setup_timeout_ms.map(Duration::from_millis),
+        )
+        .map_err(|e| GraphError::new(ErrorCode::UNSUPPORTED_OPERATION, format!("{}", e)))?;
+        // the max number of ids fetched by one call of the store, beyond which the ids are fetched in chunks
+        if let Some(max_batch_size) = self
+            .config
+            .get_storage_option("gaia.store.max.batch.size")
+        {
+            gs_store.set_max_batch_size(
+                max_batch_size
+                    .parse()
+                    .expect("parse gaia.store.max.batch.size failed"),
             );
+        }
+        let (server_port, rpc_port) = self.rpc_runtime.block_on(async {
             let partition_info = GrootMultiPartition::new(self.graph.clone());
             let job_compiler = initialize_job_assembly(gs_store, Arc::new(partition_info), cluster_info);
             let service_listener = GaiaServiceListener::default();
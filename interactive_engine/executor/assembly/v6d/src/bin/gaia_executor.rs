@@ -16,10 +16,12 @@
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use gaia_runtime::error::{StartServerError, StartServerResult};
 use global_query::{FFIGraphStore, GraphPartitionManager};
-use graph_proxy::{apis::PegasusClusterInfo, create_gs_store, VineyardMultiPartition};
+use graph_proxy::apis::{PegasusClusterInfo, TimeoutReadGraph};
+use graph_proxy::{create_gs_store_with_setup_timeout, VineyardMultiPartition};
 use log::info;
 #[cfg(feature = "mimalloc")]
 use mimalloc_rust::*;
@@ -72,6 +74,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .get("graph.vineyard.object.id")
         .ok_or_else(|| StartServerError::empty_config_error("graph.vineyard.object.id"))?
         .parse()?;
+    // the deadlines of the scans and the lookups on the store, which are unbounded if not given
+    let scan_timeout_ms: Option<u64> = config_map
+        .get("graph.store.scan.timeout.ms")
+        .map(|ms| ms.parse())
+        .transpose()?;
+    let lookup_timeout_ms: Option<u64> = config_map
+        .get("graph.store.lookup.timeout.ms")
+        .map(|ms| ms.parse())
+        .transpose()?;
+    // the time limit of setting up a scan in the store, which is unbounded if not given
+    let setup_timeout_ms: Option<u64> = config_map
+        .get("graph.store.setup.timeout.ms")
+        .map(|ms| ms.parse())
+        .transpose()?;
+    // the max number of ids fetched by one call of the store, beyond which the ids are fetched in chunks
+    let max_batch_size: Option<usize> = config_map
+        .get("graph.store.max.batch.size")
+        .map(|size| size.parse())
+        .transpose()?;
 
     assert_eq!(server_size, hosts.len());
 
@@ -111,16 +132,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("server_index: {:?}, partition_server_index_map: {:?}", server_index, partition_server_index_map);
 
     let cluster_info = Arc::new(PegasusClusterInfo::default());
-    let gs_store = create_gs_store(
+    let gs_store = create_gs_store_with_setup_timeout(
         Arc::new(ffi_store),
         partition_manager.clone(),
         computed_process_partition_list,
         cluster_info.clone(),
         false,
         false,
-    );
+        setup_timeout_ms.map(Duration::from_millis),
+    )?;
+    if let Some(max_batch_size) = max_batch_size {
+        gs_store.set_max_batch_size(max_batch_size);
+    }
     let partition_info = VineyardMultiPartition::new(partition_manager, partition_server_index_map.clone());
-    let job_assembly = initialize_job_assembly(gs_store, Arc::new(partition_info), cluster_info);
+    let job_assembly = if scan_timeout_ms.is_some() || lookup_timeout_ms.is_some() {
+        let timeout = |ms: Option<u64>| {
+            ms.map(Duration::from_millis)
+                .unwrap_or(Duration::MAX)
+        };
+        let graph = TimeoutReadGraph::new(gs_store, timeout(scan_timeout_ms), timeout(lookup_timeout_ms));
+        initialize_job_assembly(Arc::new(graph), Arc::new(partition_info), cluster_info)
+    } else {
+        initialize_job_assembly(gs_store, Arc::new(partition_info), cluster_info)
+    };
     start_rpc_server(server_id, rpc_config, job_assembly, GaiaServiceListener).await?;
     Ok(())
 }
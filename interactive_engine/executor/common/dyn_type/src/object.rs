@@ -1360,6 +1360,10 @@ macro_rules! eq {
                     false
                 }
             }
+            $crate::$ty::DateFormat(v) => $other
+                .as_date_format()
+                .map(|o| DateTimeFormats::eq(v, o))
+                .unwrap_or(false),
             _ => false,
         }
     };
@@ -1415,6 +1419,10 @@ macro_rules! partial_cmp {
                     None
                 }
             }
+            $crate::$ty::DateFormat(v) => $other
+                .as_date_format()
+                .map(|o| DateTimeFormats::partial_cmp(v, o))
+                .unwrap_or(None),
             _ => None,
         }
     };
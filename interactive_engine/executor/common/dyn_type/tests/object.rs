@@ -121,6 +121,16 @@ mod tests {
         assert!(object_kv3 > object_kv2);
         assert!(object_kv1 < object_kv4);
         assert!(object_kv4 > object_kv2);
+
+        // date format
+        let date1 = Object::from(chrono::NaiveDate::from_ymd_opt(2020, 10, 10).unwrap());
+        let date2 = Object::from(chrono::NaiveDate::from_ymd_opt(2020, 10, 10).unwrap());
+        let date3 = Object::from(chrono::NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+
+        assert_eq!(date1, date2);
+        assert_ne!(date1, date3);
+        assert!(date1 < date3);
+        assert!(date3.as_borrow() > date2.as_borrow());
     }
 
     #[test]
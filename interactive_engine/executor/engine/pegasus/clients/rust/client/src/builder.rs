@@ -541,6 +541,7 @@ impl JobBuilder {
             batch_capacity: self.conf.batch_capacity,
             memory_limit: self.conf.memory_limit,
             trace_enable: self.conf.trace_enable,
+            apply_batch_size: self.conf.apply_batch_size,
             servers: match self.conf.servers() {
                 ServerConf::Local => Some(pegasus_pb::job_config::Servers::Local(pegasus_pb::Empty {})),
                 ServerConf::Partial(servers) => {
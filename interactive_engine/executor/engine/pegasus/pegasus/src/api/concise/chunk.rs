@@ -0,0 +1,31 @@
+use crate::stream::Stream;
+use crate::{BuildJobError, Data};
+
+/// Group the data stream into chunks of a bounded size.
+pub trait Chunk<D: Data> {
+    /// Group the input data into chunks of at most `size` data each, which is useful to amortize
+    /// the overhead of handling each data alone, e.g., to [`apply`] a correlated subtask once per chunk.
+    /// Only the last chunk of each scope in a worker may be smaller than `size`. A `size` of 0 is
+    /// regarded as 1.
+    ///
+    /// [`apply`]: crate::api::CorrelatedSubTask::apply
+    ///
+    /// # Example
+    /// ```
+    /// #     use pegasus::JobConf;
+    /// #     use pegasus::api::{Sink, Chunk, Collect};
+    /// #     let conf = JobConf::new("chunk_example");
+    ///       let mut results = pegasus::run(conf, || {
+    ///         move |input, output| {
+    ///                 input.input_from(1_u32..6)?
+    ///                      .chunk(2)?
+    ///                      .collect::<Vec<Vec<u32>>>()?
+    ///                      .sink_into(output)
+    ///             }
+    ///         })
+    ///         .expect("run job failure;");
+    ///
+    ///     assert_eq!(results.next().unwrap().unwrap(), [vec![1, 2], vec![3, 4], vec![5]]);
+    /// ```
+    fn chunk(self, size: usize) -> Result<Stream<Vec<D>>, BuildJobError>;
+}
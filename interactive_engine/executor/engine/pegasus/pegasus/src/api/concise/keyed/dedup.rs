@@ -7,8 +7,12 @@ pub trait Dedup<D: Data + HasKey> {
     /// To apply deduplication on the input data stream. In order to do so, the input data must be
     /// [`Key`], so that a data structure like [`HashSet`] can be leveraged to eliminate duplication.
     ///
+    /// The keys seen in a scope of a worker are spilled to disk beyond
+    /// [`JobConf::dedup_spill_threshold`], and the deduplication remains exact after spilling.
+    ///
     /// [`Key`]: crate::api::keyed::Key
     /// [`HashSet`]: std::collections::HashSet
+    /// [`JobConf::dedup_spill_threshold`]: crate::JobConf::dedup_spill_threshold
     ///
     /// # Example
     /// ```
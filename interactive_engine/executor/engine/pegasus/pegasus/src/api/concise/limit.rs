@@ -73,7 +73,10 @@ pub trait SortLimit<D: Data + Ord> {
     fn sort_limit(self, size: u32) -> Result<Stream<D>, BuildJobError>;
 }
 
-/// An alternative of `SortLimit` but requires a comparator of the data.
+/// An alternative of `SortLimit` but requires a comparator of the data. The sort is stable, i.e., the
+/// data tied by the comparator are kept in the order they are input, as [`sort_by()`] does.
+///
+/// [`sort_by()`]: crate::api::order::SortBy::sort_by()
 pub trait SortLimitBy<D: Data> {
     fn sort_limit_by<F>(self, size: u32, cmp: F) -> Result<Stream<D>, BuildJobError>
     where
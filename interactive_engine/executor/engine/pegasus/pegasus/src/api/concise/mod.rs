@@ -14,6 +14,7 @@
 //! limitations under the License.
 
 pub use any::*;
+pub use chunk::*;
 pub use collect::*;
 pub use correlate::*;
 pub use count::*;
@@ -27,6 +28,7 @@ pub use order::*;
 pub use reduce::*;
 
 mod any;
+mod chunk;
 mod collect;
 mod correlate;
 mod count;
@@ -69,4 +69,12 @@ pub trait SortBy<D: Data> {
     fn sort_by<F>(self, cmp: F) -> Result<Stream<D>, BuildJobError>
     where
         F: Fn(&D, &D) -> Ordering + Send + 'static;
+
+    /// Sort the input data stream via a user-defined comparator `cmp`, but may not preserve
+    /// the order of equal elements, which is typically faster than [`sort_by`].
+    ///
+    /// [`sort_by`]: crate::api::SortBy::sort_by
+    fn sort_unstable_by<F>(self, cmp: F) -> Result<Stream<D>, BuildJobError>
+    where
+        F: Fn(&D, &D) -> Ordering + Send + 'static;
 }
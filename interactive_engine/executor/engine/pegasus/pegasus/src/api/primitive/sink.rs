@@ -19,6 +19,14 @@ use crate::BuildJobError;
 /// `FromStream` provides the capability to consume the data from the stream
 pub trait FromStream<D>: Send + 'static {
     fn on_next(&mut self, next: D) -> FnResult<()>;
+
+    /// Consume a batch of data from the stream, which consumes the data one by one by default.
+    fn on_batch(&mut self, batch: &mut dyn Iterator<Item = D>) -> FnResult<()> {
+        for next in batch {
+            self.on_next(next)?;
+        }
+        Ok(())
+    }
 }
 
 /// `Sink` the final results for further processing.  
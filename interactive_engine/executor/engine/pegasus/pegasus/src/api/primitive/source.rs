@@ -13,6 +13,9 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use crate::communication::output::OutputBuilderImpl;
 use crate::dataflow::DataflowBuilder;
 use crate::errors::BuildJobError;
@@ -26,11 +29,12 @@ pub trait IntoDataflow<D: Data> {
 pub struct Source<D: Data> {
     output: OutputBuilderImpl<D>,
     dfb: DataflowBuilder,
+    stopped: Arc<AtomicBool>,
 }
 
 impl<D: Data> Source<D> {
     pub(crate) fn new(output: OutputBuilderImpl<D>, dfb: &DataflowBuilder) -> Self {
-        Source { output, dfb: dfb.clone() }
+        Source { output, dfb: dfb.clone(), stopped: Arc::new(AtomicBool::new(false)) }
     }
 
     pub fn input_from<I>(&mut self, source: I) -> Result<Stream<D>, BuildJobError>
@@ -41,7 +45,23 @@ impl<D: Data> Source<D> {
         let output = self.output.copy_data();
         let output = std::mem::replace(&mut self.output, output);
         let stream = Stream::new(output, &self.dfb);
-        source.into_dataflow(stream)
+        let stopped = self.stopped.clone();
+        source
+            .into_iter()
+            .take_while(move |_| !stopped.load(Ordering::SeqCst))
+            .into_dataflow(stream)
+    }
+
+    /// Stop producing data from the source of this worker, while the data already emitted still
+    /// flow through the downstream operators until they finish, e.g., once enough data is obtained.
+    /// Unlike the cancellation of the job, it doesn't abort the job.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// Get the hook to [`Source::stop`] the source in execution, e.g., from a downstream operator.
+    pub fn get_stop_hook(&self) -> &Arc<AtomicBool> {
+        &self.stopped
     }
 
     pub fn get_worker_index(&self) -> u32 {
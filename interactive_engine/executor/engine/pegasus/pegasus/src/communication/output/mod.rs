@@ -70,6 +70,10 @@ pub trait OutputProxy: AsAny + Send {
 
     fn try_unblock(&self) -> IOResult<()>;
 
+    /// Check if this output is blocked on an iterator, e.g., of a source, which produces new data
+    /// rather than the data in flight once unblocked;
+    fn is_pulling(&self) -> bool;
+
     /// Notify this output that the scope with tag was closed, no more data of this scope will be send
     /// on this output;
     fn notify_end(&self, end: EndOfScope) -> IOResult<()>;
@@ -157,6 +161,11 @@ impl<D: Data> OutputProxy for RefWrapOutput<D> {
         self.output.borrow_mut().try_unblock()
     }
 
+    #[inline]
+    fn is_pulling(&self) -> bool {
+        self.output.borrow().is_pulling()
+    }
+
     #[inline]
     fn notify_end(&self, end: EndOfScope) -> IOResult<()> {
         self.output.borrow_mut().notify_end(end)
@@ -222,6 +222,13 @@ impl<D: Data> OutputHandle<D> {
         &self.blocks
     }
 
+    /// Check if this output is blocked on an iterator, which produces new data once unblocked;
+    pub(crate) fn is_pulling(&self) -> bool {
+        self.block_entries
+            .iter()
+            .any(|(_, entry)| matches!(entry, BlockEntry::DynIter(..)))
+    }
+
     pub(crate) fn cancel(&mut self, tag: &Tag) -> IOResult<()> {
         let level = tag.len() as u32;
         if level < self.scope_level {
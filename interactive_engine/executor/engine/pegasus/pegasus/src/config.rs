@@ -14,13 +14,15 @@
 //! limitations under the License.
 
 use std::hash::Hasher;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use ahash::AHasher;
 use pegasus_network::config::NetworkConfig;
 use serde::Deserialize;
 
 use crate::errors::StartupError;
+use crate::metrics::{JobMetrics, MetricsCallback};
 use crate::{get_servers, get_servers_len};
 use crate::{PROFILE_COMM_FLAG, PROFILE_TIME_FLAG};
 
@@ -37,6 +39,8 @@ macro_rules! configure_with_default {
 pub struct Configuration {
     pub network: Option<NetworkConfig>,
     pub max_pool_size: Option<u32>,
+    /// the default of [`JobConf::apply_batch_size`] for the jobs in this server;
+    pub apply_batch_size: Option<u32>,
 }
 
 impl Configuration {
@@ -45,11 +49,11 @@ impl Configuration {
     }
 
     pub fn singleton() -> Self {
-        Configuration { network: None, max_pool_size: None }
+        Configuration { network: None, max_pool_size: None, apply_batch_size: None }
     }
 
     pub fn with(network: NetworkConfig) -> Self {
-        Configuration { network: Some(network), max_pool_size: None }
+        Configuration { network: Some(network), max_pool_size: None, apply_batch_size: None }
     }
 
     pub fn server_id(&self) -> u64 {
@@ -73,6 +77,12 @@ impl Configuration {
     }
 }
 
+static DEFAULT_APPLY_BATCH_SIZE: AtomicU32 = AtomicU32::new(1);
+
+pub(crate) fn set_default_apply_batch_size(size: u32) {
+    DEFAULT_APPLY_BATCH_SIZE.store(size.max(1), Ordering::SeqCst);
+}
+
 pub fn read_from<P: AsRef<Path>>(path: P) -> Result<Configuration, StartupError> {
     let config_str = std::fs::read_to_string(path)?;
     Ok(Configuration::parse(&config_str)?)
@@ -122,12 +132,17 @@ pub struct JobConf {
     pub workers: u32,
     /// the most milliseconds the job can run;
     pub time_limit: u64,
+    /// set to capture what a worker is doing if it is stuck in execution beyond the time limit;
+    pub capture_on_timeout: bool,
     /// the size used to batching streaming data;
     pub batch_size: u32,
     /// the size used to limit each operator's output size per-schedule;
     pub batch_capacity: u32,
     /// the most memory(MB) this job can use in each server;
     pub memory_limit: u32,
+    /// the most tracked memory(bytes) this job can allocate, beyond which the sources of its workers are
+    /// held back until the memory is released, with the `mem` feature;
+    pub memory_limit_bytes: u64,
     /// set to print runtime dataflow plan before running;
     pub plan_print: bool,
     /// the id of servers this job will run on;
@@ -136,6 +151,16 @@ pub struct JobConf {
     pub trace_enable: bool,
     /// optimization factors of early-stop
     pub debug: bool,
+    /// the most keys a dedup operator keeps in memory per scope in each worker, beyond which
+    /// they are spilled to `spill_dir`;
+    pub dedup_spill_threshold: u64,
+    /// the directory to spill operator states to, which is the temp directory of the system if not set;
+    pub spill_dir: Option<PathBuf>,
+    /// the most input data a correlated subtask is applied to in a scope, if it can be applied to
+    /// the chunks of data instead of each of them, see [`crate::api::Chunk`];
+    pub apply_batch_size: u32,
+    /// the callback to deliver the custom metrics of the job, see [`crate::metrics`];
+    metrics_callback: Option<MetricsCallback>,
 }
 
 impl JobConf {
@@ -169,6 +194,26 @@ impl JobConf {
         self.servers = servers
     }
 
+    /// Set the callback to deliver the custom metrics of the job, which are merged from all workers
+    /// of the job in the current server, after the last of them finishes.
+    pub fn set_metrics_callback<F>(&mut self, func: F)
+    where
+        F: Fn(&JobMetrics) + Send + Sync + 'static,
+    {
+        self.metrics_callback = Some(MetricsCallback::new(func));
+    }
+
+    pub fn metrics_callback(&self) -> Option<&MetricsCallback> {
+        self.metrics_callback.as_ref()
+    }
+
+    /// The directory to spill operator states to, see [`JobConf::spill_dir`].
+    pub fn get_spill_dir(&self) -> PathBuf {
+        self.spill_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir)
+    }
+
     pub fn total_workers(&self) -> usize {
         let len = self.servers.len();
         if len == 0 {
@@ -191,13 +236,19 @@ impl Default for JobConf {
             job_name: "anonymity".to_owned(),
             workers: 1,
             time_limit: !0,
+            capture_on_timeout: false,
             batch_size: 1024,
             batch_capacity: 64,
             memory_limit: !0u32,
+            memory_limit_bytes: !0u64,
             plan_print,
             servers: ServerConf::Local,
             trace_enable: false,
             debug: false,
+            dedup_spill_threshold: !0u64,
+            spill_dir: None,
+            apply_batch_size: DEFAULT_APPLY_BATCH_SIZE.load(Ordering::SeqCst),
+            metrics_callback: None,
         }
     }
 }
@@ -29,7 +29,7 @@ use crate::errors::{BuildJobError, IOResult, JobExecError};
 use crate::event::emitter::EventEmitter;
 use crate::graph::{Dependency, DotGraph, Edge, Port};
 use crate::operator::{GeneralOperator, NotifiableOperator, Operator, OperatorBuilder, OperatorCore};
-use crate::schedule::Schedule;
+use crate::schedule::{Schedule, ScheduleProbe};
 use crate::{Data, JobConf, Tag, WorkerId};
 use crate::{PROFILE_COMM_FLAG, PROFILE_TIME_FLAG};
 
@@ -174,7 +174,8 @@ impl DataflowBuilder {
         if report {
             info!("create job[{}] with configuration : {:?}", self.config.job_id, self.config);
             info!("{}", plan_desc);
-            let dot_g = DotGraph::new(self.config.job_name.clone(), self.config.job_id, op_names, edges);
+            let dot_g =
+                DotGraph::new(self.config.job_name.clone(), self.config.job_id, op_names.clone(), edges);
             if let Ok(mut f) = File::create(format!("{}_{}.dot", self.config.job_name, self.config.job_id))
             {
                 if let Err(e) = dot::render(&dot_g, &mut f) {
@@ -183,11 +184,13 @@ impl DataflowBuilder {
             }
         }
 
+        sch.probe().set_op_names(op_names);
         Ok(Dataflow {
             worker_id: self.worker_id,
             operators: RefCell::new(operators),
             conf: self.config,
             depends,
+            probe: sch.probe().clone(),
         })
     }
 }
@@ -247,6 +250,7 @@ pub struct Dataflow {
     pub worker_id: WorkerId,
     operators: RefCell<Vec<Option<Operator>>>,
     depends: Dependency,
+    probe: Arc<ScheduleProbe>,
 }
 
 impl Dataflow {
@@ -263,7 +267,9 @@ impl Dataflow {
         if let Some(op_opt) = operators.get_mut(index) {
             if let Some(mut op) = op_opt.take() {
                 if !op.is_idle()? {
+                    self.probe.enter_fire(index);
                     let result = op.fire();
+                    self.probe.exit_fire();
                     if op.is_finished() {
                         op.close();
                         // debug_worker!("operator {:?} finished;", op.meta);
@@ -279,6 +285,16 @@ impl Dataflow {
         Ok(false)
     }
 
+    /// Check if the operator of `index` is pulling new data from an iterator, e.g., a source;
+    pub fn is_pulling(&self, index: usize) -> bool {
+        let operators = self.operators.borrow();
+        operators
+            .get(index)
+            .and_then(|op| op.as_ref())
+            .map(|op| op.is_pulling())
+            .unwrap_or(false)
+    }
+
     pub fn is_idle(&self) -> IOResult<bool> {
         let operators = self.operators.borrow();
         for op in operators.iter() {
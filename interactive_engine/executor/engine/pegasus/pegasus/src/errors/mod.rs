@@ -16,6 +16,7 @@
 use std::error::Error;
 use std::fmt::{self, Debug, Display};
 use std::io;
+use std::sync::Arc;
 
 use pegasus_network::NetError;
 
@@ -127,6 +128,35 @@ impl Error for JobExecError {
     }
 }
 
+/// The cause of the error that a job is failed with via [`crate::fail_job`], shared by the workers
+/// of the job, each of which reports it to the result sink.
+#[derive(Clone)]
+pub struct JobFailure(Arc<dyn Error + Send + Sync>);
+
+impl JobFailure {
+    pub(crate) fn new<E: Error + Send + Sync + 'static>(cause: E) -> Self {
+        JobFailure(Arc::new(cause))
+    }
+}
+
+impl Debug for JobFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for JobFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for JobFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
 impl From<IOError> for JobExecError {
     fn from(err: IOError) -> Self {
         if err.is_interrupted() {
@@ -44,6 +44,7 @@ pub mod communication;
 mod data_plane;
 pub mod dataflow;
 mod event;
+pub mod metrics;
 mod operator;
 pub(crate) mod progress;
 pub mod resource;
@@ -57,6 +58,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 pub use config::{read_from, Configuration, JobConf, ServerConf};
 pub use data::Data;
@@ -66,19 +68,23 @@ pub use pegasus_common::codec;
 pub use pegasus_memory::alloc::check_current_task_memory;
 pub use pegasus_network::ServerDetect;
 pub use tag::Tag;
-pub use worker::Worker;
+pub use worker::{PauseHook, Worker};
 pub use worker_id::{get_current_worker, get_current_worker_checked, set_current_worker, WorkerId};
 
 use crate::api::Source;
 pub use crate::errors::{BuildJobError, CancelError, JobSubmitError, SpawnJobError, StartupError};
+use crate::errors::{ErrorKind, JobExecError, JobFailure};
 use crate::resource::PartitionedResource;
 use crate::result::{ResultSink, ResultStream};
+use crate::worker::ParkableWorker;
 use crate::worker_id::WorkerIdIter;
 
 lazy_static! {
     static ref SERVER_ID: Mutex<Option<u64>> = Mutex::new(None);
     static ref SERVERS: RwLock<Vec<u64>> = RwLock::new(vec![]);
     static ref JOB_CANCEL_MAP: RwLock<HashMap<u64, Arc<AtomicBool>>> = RwLock::new(HashMap::new());
+    /// The errors that the jobs are failed with via `fail_job()`, indexed by the job id;
+    static ref JOB_FAILURES: Mutex<HashMap<u64, (ErrorKind, bool, JobFailure)>> = Mutex::new(HashMap::new());
     pub static ref PROFILE_TIME_FLAG: bool = configure_with_default!(bool, "PROFILE_TIME_FLAG", false);
     pub static ref PROFILE_COMM_FLAG: bool = configure_with_default!(bool, "PROFILE_COMM_FLAG", false);
 }
@@ -149,6 +155,9 @@ pub fn startup(conf: Configuration) -> Result<(), StartupError> {
     if let Some(pool_size) = conf.max_pool_size {
         pegasus_executor::set_core_pool_size(pool_size as usize);
     }
+    if let Some(batch_size) = conf.apply_batch_size {
+        config::set_default_apply_batch_size(batch_size);
+    }
     pegasus_executor::try_start_executor_async();
 
     let mut servers = HashSet::new();
@@ -190,6 +199,9 @@ pub fn startup_with<D: ServerDetect + 'static>(
     if let Some(pool_size) = conf.max_pool_size {
         pegasus_executor::set_core_pool_size(pool_size as usize);
     }
+    if let Some(batch_size) = conf.apply_batch_size {
+        config::set_default_apply_batch_size(batch_size);
+    }
     pegasus_executor::try_start_executor_async();
 
     let server_id = conf.server_id();
@@ -272,11 +284,18 @@ where
     init_env();
     let cancel_hook = sink.get_cancel_hook().clone();
     if let Ok(mut lock) = JOB_CANCEL_MAP.write() {
-        lock.insert(conf.job_id, cancel_hook);
+        lock.insert(conf.job_id, cancel_hook.clone());
     } else {
         return Err(BuildJobError::from("JOB_CANCEL_MAP is poisoned;"))?;
     }
+    let deadline = if conf.time_limit > 0 {
+        Instant::now().checked_add(Duration::from_millis(conf.time_limit))
+    } else {
+        None
+    };
+    let pause_hook = Arc::new(PauseHook::new(cancel_hook, deadline));
     let peer_guard = Arc::new(AtomicUsize::new(0));
+    let job_metrics = Arc::new(Mutex::new(metrics::JobMetrics::default()));
     let conf = Arc::new(conf);
     let workers = allocate_local_worker(&conf)?;
     if workers.is_none() {
@@ -297,7 +316,7 @@ where
             let span = tracer
                 .span_builder(format!("/worker-{}", worker_id.index))
                 .start_with_context(&tracer, &cx);
-            Worker::new(&conf, worker_id, &peer_guard, sink.clone(), span)
+            Worker::new(&conf, worker_id, &peer_guard, &pause_hook, &job_metrics, sink.clone(), span)
         });
         let _g = crate::worker_id::guard(worker.id);
         logic(&mut worker)?;
@@ -316,7 +335,7 @@ where
         workers.len()
     );
 
-    match pegasus_executor::spawn_batch(workers) {
+    match pegasus_executor::spawn_batch(workers.into_iter().map(ParkableWorker::new)) {
         Ok(_) => Ok(()),
         Err(e) => {
             if pegasus_executor::is_shutdown() {
@@ -341,6 +360,48 @@ pub fn cancel_job(job_id: u64) -> Result<(), CancelError> {
     Ok(())
 }
 
+/// Fail the job with the error, e.g., which is raised by an iterator consumed by an operator, and
+/// thus cannot be returned as the error of the operator. The job is canceled as by [`cancel_job`],
+/// while each worker of the job in this server reports the error to the result sink instead of the
+/// cancellation. Only the first error is kept if the job is failed more than once.
+pub fn fail_job<E: std::error::Error + Send + Sync + 'static>(
+    job_id: u64, kind: ErrorKind, is_system: bool, cause: E,
+) -> Result<(), CancelError> {
+    // kept before the cancellation, which the workers may observe right away;
+    if let Ok(mut failures) = JOB_FAILURES.lock() {
+        failures
+            .entry(job_id)
+            .or_insert_with(|| (kind, is_system, JobFailure::new(cause)));
+    } else {
+        return Err(CancelError::CancelMapPoisonedError);
+    }
+    let canceled = cancel_job(job_id);
+    if canceled.is_err() {
+        remove_job_failure(job_id);
+    }
+    canceled
+}
+
+/// The error that the job is failed with via [`fail_job`], if any.
+pub(crate) fn job_failure(job_id: u64) -> Option<JobExecError> {
+    let failures = JOB_FAILURES.lock().ok()?;
+    failures
+        .get(&job_id)
+        .map(|(kind, is_system, cause)| {
+            let mut err = JobExecError::new(kind.clone(), cause.clone());
+            if *is_system {
+                err.set_system();
+            }
+            err
+        })
+}
+
+pub(crate) fn remove_job_failure(job_id: u64) {
+    if let Ok(mut failures) = JOB_FAILURES.lock() {
+        failures.remove(&job_id);
+    }
+}
+
 pub fn remove_cancel_hook(job_id: u64) -> Result<(), CancelError> {
     if let Ok(mut hook) = JOB_CANCEL_MAP.write() {
         hook.remove(&job_id);
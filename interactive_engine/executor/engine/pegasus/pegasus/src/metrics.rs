@@ -0,0 +1,286 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+//! Custom metrics recorded by operators, e.g., the hit rate of a cache.
+//!
+//! Each worker installs a [`MetricsRegistry`] into its keyed resources under [`METRICS_REGISTRY_KEY`],
+//! of which the handles are available in operator closures via [`counter`], [`gauge`] and [`histogram`].
+//! The metrics of all workers are merged per job when the last worker finishes,
+//! and then delivered to the callback set by [`crate::JobConf::set_metrics_callback`].
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::resource::get_resource_mut_by_key;
+
+/// The key of the [`MetricsRegistry`] in the keyed resources of a worker.
+pub const METRICS_REGISTRY_KEY: &str = "pegasus.metrics";
+
+/// A counter that only increases, which is summed up among workers.
+#[derive(Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1)
+    }
+
+    pub fn add(&self, n: u64) {
+        // a worker is single-threaded, thus no stronger ordering is needed;
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A gauge that can be set to arbitrary values, which is summed up among workers.
+#[derive(Clone, Default)]
+pub struct Gauge(Arc<AtomicI64>);
+
+impl Gauge {
+    pub fn set(&self, value: i64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn add(&self, delta: i64) {
+        self.0.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The summary of the values recorded by a [`Histogram`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramSummary {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Default for HistogramSummary {
+    fn default() -> Self {
+        HistogramSummary { count: 0, sum: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+}
+
+impl HistogramSummary {
+    fn record(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn merge(&mut self, other: &HistogramSummary) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.sum / self.count as f64)
+        } else {
+            None
+        }
+    }
+}
+
+/// A histogram that summarizes the recorded values, which is merged among workers.
+#[derive(Clone, Default)]
+pub struct Histogram(Arc<Mutex<HistogramSummary>>);
+
+impl Histogram {
+    pub fn record(&self, value: f64) {
+        if let Ok(mut summary) = self.0.lock() {
+            summary.record(value);
+        }
+    }
+
+    pub fn summary(&self) -> HistogramSummary {
+        self.0
+            .lock()
+            .map(|summary| *summary)
+            .unwrap_or_default()
+    }
+}
+
+/// The metrics registered in a worker, of which the handles are cheap to clone into operator closures.
+#[derive(Default)]
+pub struct MetricsRegistry {
+    counters: HashMap<String, Counter>,
+    gauges: HashMap<String, Gauge>,
+    histograms: HashMap<String, Histogram>,
+}
+
+impl MetricsRegistry {
+    /// Get the counter of the given name, which is registered if not yet.
+    pub fn counter(&mut self, name: &str) -> Counter {
+        self.counters
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Get the gauge of the given name, which is registered if not yet.
+    pub fn gauge(&mut self, name: &str) -> Gauge {
+        self.gauges
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Get the histogram of the given name, which is registered if not yet.
+    pub fn histogram(&mut self, name: &str) -> Histogram {
+        self.histograms
+            .entry(name.to_string())
+            .or_default()
+            .clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.gauges.is_empty() && self.histograms.is_empty()
+    }
+
+    /// Take a snapshot of the current values of the registered metrics.
+    pub fn snapshot(&self) -> JobMetrics {
+        JobMetrics {
+            counters: self
+                .counters
+                .iter()
+                .map(|(name, counter)| (name.clone(), counter.get()))
+                .collect(),
+            gauges: self
+                .gauges
+                .iter()
+                .map(|(name, gauge)| (name.clone(), gauge.get()))
+                .collect(),
+            histograms: self
+                .histograms
+                .iter()
+                .map(|(name, histogram)| (name.clone(), histogram.summary()))
+                .collect(),
+        }
+    }
+}
+
+/// The values of the metrics, of a worker or merged from all workers of a job.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobMetrics {
+    pub counters: HashMap<String, u64>,
+    pub gauges: HashMap<String, i64>,
+    pub histograms: HashMap<String, HistogramSummary>,
+}
+
+impl JobMetrics {
+    pub fn merge(&mut self, other: &JobMetrics) {
+        for (name, value) in &other.counters {
+            *self.counters.entry(name.clone()).or_default() += *value;
+        }
+        for (name, value) in &other.gauges {
+            *self.gauges.entry(name.clone()).or_default() += *value;
+        }
+        for (name, summary) in &other.histograms {
+            self.histograms
+                .entry(name.clone())
+                .or_default()
+                .merge(summary);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.gauges.is_empty() && self.histograms.is_empty()
+    }
+}
+
+/// The callback to deliver the merged metrics of a job, which is invoked on the thread of the last
+/// finished worker.
+#[derive(Clone)]
+pub struct MetricsCallback(Arc<dyn Fn(&JobMetrics) + Send + Sync>);
+
+impl MetricsCallback {
+    pub fn new<F>(func: F) -> Self
+    where
+        F: Fn(&JobMetrics) + Send + Sync + 'static,
+    {
+        MetricsCallback(Arc::new(func))
+    }
+
+    pub(crate) fn call(&self, metrics: &JobMetrics) {
+        (self.0)(metrics)
+    }
+}
+
+impl Debug for MetricsCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MetricsCallback")
+    }
+}
+
+fn with_registry<R, F: FnOnce(&mut MetricsRegistry) -> R>(func: F) -> Option<R> {
+    get_resource_mut_by_key::<MetricsRegistry>(METRICS_REGISTRY_KEY).map(|mut registry| func(&mut registry))
+}
+
+/// Get the counter of the given name in the registry of the current worker,
+/// which is `None` if not called by a worker in execution.
+pub fn counter(name: &str) -> Option<Counter> {
+    with_registry(|registry| registry.counter(name))
+}
+
+/// Get the gauge of the given name in the registry of the current worker,
+/// which is `None` if not called by a worker in execution.
+pub fn gauge(name: &str) -> Option<Gauge> {
+    with_registry(|registry| registry.gauge(name))
+}
+
+/// Get the histogram of the given name in the registry of the current worker,
+/// which is `None` if not called by a worker in execution.
+pub fn histogram(name: &str) -> Option<Histogram> {
+    with_registry(|registry| registry.histogram(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_metrics_test() {
+        let mut registry1 = MetricsRegistry::default();
+        registry1.counter("hits").add(3);
+        registry1.gauge("size").set(10);
+        registry1.histogram("latency").record(1.0);
+        let mut registry2 = MetricsRegistry::default();
+        registry2.counter("hits").inc();
+        registry2.counter("misses").inc();
+        registry2.histogram("latency").record(3.0);
+
+        let mut merged = registry1.snapshot();
+        merged.merge(&registry2.snapshot());
+        assert_eq!(merged.counters["hits"], 4);
+        assert_eq!(merged.counters["misses"], 1);
+        assert_eq!(merged.gauges["size"], 10);
+        let latency = merged.histograms["latency"];
+        assert_eq!((latency.count, latency.min, latency.max), (2, 1.0, 3.0));
+        assert_eq!(latency.mean(), Some(2.0));
+    }
+}
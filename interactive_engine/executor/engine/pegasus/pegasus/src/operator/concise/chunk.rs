@@ -0,0 +1,39 @@
+use crate::api::{Chunk, Unary};
+use crate::communication::output::OutputProxy;
+use crate::stream::Stream;
+use crate::tag::tools::map::TidyTagMap;
+use crate::{BuildJobError, Data};
+
+impl<D: Data> Chunk<D> for Stream<D> {
+    fn chunk(self, size: usize) -> Result<Stream<Vec<D>>, BuildJobError> {
+        let size = std::cmp::max(size, 1);
+        self.unary("chunk", |info| {
+            let mut table = TidyTagMap::<Vec<D>>::new(info.scope_level);
+            move |input, output| {
+                input.for_each_batch(|batch| {
+                    if !batch.is_empty() {
+                        let mut session = output.new_session(&batch.tag)?;
+                        let chunk = table.get_mut_or_insert_with(&batch.tag, || Vec::with_capacity(size));
+                        for d in batch.drain() {
+                            chunk.push(d);
+                            if chunk.len() == size {
+                                session.give(std::mem::replace(chunk, Vec::with_capacity(size)))?;
+                            }
+                        }
+                    }
+
+                    if let Some(end) = batch.take_end() {
+                        match table.remove(&batch.tag) {
+                            Some(chunk) if !chunk.is_empty() => {
+                                let mut session = output.new_session(&batch.tag)?;
+                                session.give_last(chunk, end)?;
+                            }
+                            _ => output.notify_end(end)?,
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
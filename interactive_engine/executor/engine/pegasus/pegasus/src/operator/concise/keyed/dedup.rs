@@ -1,22 +1,33 @@
-use ahash::AHashSet;
+use pegasus_common::codec::{Decode, Encode};
 
 use crate::api::{Dedup, HasKey, PartitionByKey, Unary};
+use crate::errors::IOError;
+use crate::operator::concise::keyed::spill::{SpillConf, SpillableSet};
 use crate::stream::Stream;
 use crate::tag::tools::map::TidyTagMap;
 use crate::{BuildJobError, Data};
 
-impl<D: Data + HasKey> Dedup<D> for Stream<D> {
+impl<D: Data + HasKey> Dedup<D> for Stream<D>
+where
+    D::Target: Encode + Decode,
+{
     fn dedup(self) -> Result<Stream<D>, BuildJobError> {
+        let conf = self.get_job_conf();
+        let spill_conf = SpillConf {
+            threshold: conf.dedup_spill_threshold as usize,
+            dir: conf.get_spill_dir(),
+            job_id: conf.job_id,
+        };
         self.partition_by_key().unary("dedup", |info| {
-            let mut table = TidyTagMap::<AHashSet<D::Target>>::new(info.scope_level);
+            let mut table = TidyTagMap::<SpillableSet<D::Target>>::new(info.scope_level);
             move |input, output| {
                 input.for_each_batch(|batch| {
                     if !batch.is_empty() {
                         let mut session = output.new_session(&batch.tag)?;
-                        let set = table.get_mut_or_insert(&batch.tag);
+                        let set = table
+                            .get_mut_or_insert_with(&batch.tag, || SpillableSet::new(spill_conf.clone()));
                         for d in batch.drain() {
-                            if !set.contains(d.get_key()) {
-                                set.insert(d.get_key().clone());
+                            if set.insert(d.get_key()).map_err(IOError::from)? {
                                 session.give(d)?;
                             }
                         }
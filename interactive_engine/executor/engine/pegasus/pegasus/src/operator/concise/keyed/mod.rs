@@ -67,6 +67,7 @@ mod dedup;
 mod fold;
 mod join;
 mod reduce;
+mod spill;
 
 #[cfg(test)]
 mod test {
@@ -0,0 +1,272 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::fs::{File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ahash::AHashSet;
+use pegasus_common::codec::{Decode, Encode};
+
+use crate::api::Key;
+
+/// The number of keys in a block of a spilled run, which is the unit to read on probing a run.
+const RUN_BLOCK_SIZE: usize = 64;
+/// The bits of the bloom filter per spilled key, with which the false positive rate is about 1%.
+const BLOOM_BITS_PER_KEY: usize = 10;
+const BLOOM_HASHES: u64 = 7;
+
+static SPILL_DIR_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// Where and when a [`SpillableSet`] spills its keys to disk.
+#[derive(Clone, Debug)]
+pub(crate) struct SpillConf {
+    /// the most keys kept in memory, beyond which they are spilled as a sorted run;
+    pub threshold: usize,
+    /// the directory under which the runs are spilled;
+    pub dir: PathBuf,
+    pub job_id: u64,
+}
+
+/// A set of keys that spills to disk once it holds more than `threshold` keys in memory.
+///
+/// The keys spilled at a time form a run, which is sorted by the hash of the keys and indexed
+/// by a bloom filter over the hashes. A key is looked up in the recent keys in memory first,
+/// then in the runs that the bloom filters cannot rule out, by reading the blocks of the runs
+/// holding its hash. Thus the set is always exact, and the bloom filters only save the reading.
+///
+/// The spilled files are removed once the set is dropped, e.g., the scope is finished or the
+/// job is cancelled.
+pub(crate) struct SpillableSet<K> {
+    conf: SpillConf,
+    hash_builder: ahash::RandomState,
+    recent: AHashSet<K>,
+    runs: Vec<SpilledRun>,
+    dir: Option<SpillDir>,
+}
+
+impl<K: Key + Encode + Decode> SpillableSet<K> {
+    pub fn new(conf: SpillConf) -> Self {
+        SpillableSet {
+            conf,
+            hash_builder: ahash::RandomState::with_seeds(17, 31, 67, 131),
+            recent: AHashSet::new(),
+            runs: vec![],
+            dir: None,
+        }
+    }
+
+    /// Insert the key if it is not in the set, and return whether it is inserted.
+    pub fn insert(&mut self, key: &K) -> io::Result<bool> {
+        if self.recent.contains(key) {
+            return Ok(false);
+        }
+        if !self.runs.is_empty() {
+            let hash = self.hash(key);
+            for run in self.runs.iter_mut() {
+                if run.bloom.may_contain(hash) && run.contains(hash, key)? {
+                    return Ok(false);
+                }
+            }
+        }
+        self.recent.insert(key.clone());
+        if self.recent.len() >= self.conf.threshold {
+            self.spill()?;
+        }
+        Ok(true)
+    }
+
+    #[cfg(test)]
+    fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    fn hash(&self, key: &K) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        let mut keys: Vec<(u64, K)> = std::mem::take(&mut self.recent)
+            .into_iter()
+            .map(|key| (self.hash(&key), key))
+            .collect();
+        keys.sort_by_key(|(hash, _)| *hash);
+
+        if self.dir.is_none() {
+            self.dir = Some(SpillDir::create(&self.conf)?);
+        }
+        let path = self
+            .dir
+            .as_ref()
+            .expect("spill dir created")
+            .path
+            .join(format!("run_{}", self.runs.len()));
+        let mut file = OpenOptions::new()
+            .create_new(true)
+            .read(true)
+            .write(true)
+            .open(&path)?;
+
+        let mut bloom = BloomFilter::new(keys.len());
+        let mut index = Vec::with_capacity(keys.len() / RUN_BLOCK_SIZE + 1);
+        let mut offset = 0;
+        let mut buf = vec![];
+        for block in keys.chunks(RUN_BLOCK_SIZE) {
+            index.push((block[0].0, offset));
+            buf.clear();
+            for (hash, key) in block {
+                bloom.insert(*hash);
+                buf.write_all(&hash.to_le_bytes())?;
+                key.write_to(&mut buf)?;
+            }
+            file.write_all(&buf)?;
+            offset += buf.len() as u64;
+        }
+        file.flush()?;
+        debug_worker!("dedup spilled {} keys to {:?}", keys.len(), path);
+        self.runs
+            .push(SpilledRun { file, len: offset, index, bloom });
+        Ok(())
+    }
+}
+
+/// The directory that the runs of a [`SpillableSet`] are spilled to, which is removed on drop.
+struct SpillDir {
+    path: PathBuf,
+}
+
+impl SpillDir {
+    fn create(conf: &SpillConf) -> io::Result<Self> {
+        let seq = SPILL_DIR_SEQ.fetch_add(1, Ordering::Relaxed);
+        let path = conf
+            .dir
+            .join(format!("pegasus_dedup_{}_{}_{}", conf.job_id, std::process::id(), seq));
+        std::fs::create_dir_all(&path)?;
+        Ok(SpillDir { path })
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.path) {
+            warn!("fail to remove spilled files in {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// A file of keys sorted by their hashes, of which every `RUN_BLOCK_SIZE` keys form a block.
+struct SpilledRun {
+    file: File,
+    len: u64,
+    /// the first hash and the offset of each block;
+    index: Vec<(u64, u64)>,
+    bloom: BloomFilter,
+}
+
+impl SpilledRun {
+    fn contains<K: Key + Decode>(&mut self, hash: u64, key: &K) -> io::Result<bool> {
+        // the keys of the same hash may start in the last block of smaller first hashes;
+        let mut block = self
+            .index
+            .partition_point(|(first, _)| *first < hash)
+            .saturating_sub(1);
+        while block < self.index.len() {
+            let (first, start) = self.index[block];
+            if first > hash {
+                return Ok(false);
+            }
+            let end = self
+                .index
+                .get(block + 1)
+                .map(|(_, offset)| *offset)
+                .unwrap_or(self.len);
+            let mut buf = vec![0u8; (end - start) as usize];
+            self.file.seek(SeekFrom::Start(start))?;
+            self.file.read_exact(&mut buf)?;
+            let mut reader = &buf[..];
+            while !reader.is_empty() {
+                let mut hash_bytes = [0u8; 8];
+                reader.read_exact(&mut hash_bytes)?;
+                let spilled_hash = u64::from_le_bytes(hash_bytes);
+                let spilled_key = K::read_from(&mut reader)?;
+                if spilled_hash > hash {
+                    return Ok(false);
+                }
+                if spilled_hash == hash && spilled_key == *key {
+                    return Ok(true);
+                }
+            }
+            block += 1;
+        }
+        Ok(false)
+    }
+}
+
+struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    fn new(keys: usize) -> Self {
+        let words = (keys * BLOOM_BITS_PER_KEY + 63) / 64;
+        BloomFilter { bits: vec![0; words.max(1)] }
+    }
+
+    fn positions(&self, hash: u64) -> impl Iterator<Item = usize> {
+        let total = self.bits.len() as u64 * 64;
+        let delta = hash.rotate_left(32) | 1;
+        (0..BLOOM_HASHES).map(move |i| (hash.wrapping_add(i.wrapping_mul(delta)) % total) as usize)
+    }
+
+    fn insert(&mut self, hash: u64) {
+        for pos in self.positions(hash).collect::<Vec<_>>() {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+    }
+
+    fn may_contain(&self, hash: u64) -> bool {
+        self.positions(hash)
+            .all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spillable_set_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf = SpillConf { threshold: 100, dir: dir.path().to_path_buf(), job_id: 0 };
+        let mut set = SpillableSet::<u64>::new(conf);
+        for i in 0..1000u64 {
+            assert!(set.insert(&i).unwrap());
+        }
+        assert_eq!(set.spilled_runs(), 10);
+        for i in 0..1000u64 {
+            assert!(!set.insert(&i).unwrap(), "{} is spilled", i);
+        }
+        for i in 1000..1050u64 {
+            assert!(set.insert(&i).unwrap());
+        }
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        drop(set);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -86,6 +86,9 @@ impl<D: Data> SortLimitBy<D> for Stream<D> {
     }
 }
 
+/// The most data preallocated for the heap of `sort_limit_by`.
+const HEAP_INIT_CAPACITY: usize = 1024;
+
 type Cmp<D> = Arc<dyn Fn(&D, &D) -> Ordering + Send + 'static>;
 
 struct ShadeCmp<C> {
@@ -100,8 +103,11 @@ impl<C> Clone for ShadeCmp<C> {
     }
 }
 
+/// The data in the heap of `sort_limit_by`, where the ties are ordered by `seq`, i.e., the order
+/// in which they are input, so that the top-k is the same as that of a stable sort.
 struct Item<D> {
     inner: D,
+    seq: u64,
     cmp: Cmp<D>,
 }
 
@@ -109,19 +115,19 @@ impl<D> Eq for Item<D> {}
 
 impl<D> PartialEq<Self> for Item<D> {
     fn eq(&self, other: &Self) -> bool {
-        (*(self.cmp))(&self.inner, &other.inner) == Ordering::Equal
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl<D> PartialOrd<Self> for Item<D> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some((*(self.cmp))(&self.inner, &other.inner))
+        Some(self.cmp(other))
     }
 }
 
 impl<D> Ord for Item<D> {
     fn cmp(&self, other: &Self) -> Ordering {
-        (*(self.cmp))(&self.inner, &other.inner)
+        (*(self.cmp))(&self.inner, &other.inner).then(self.seq.cmp(&other.seq))
     }
 }
 
@@ -176,28 +182,35 @@ where
         })
     } else {
         stream.unary(name, |info| {
-            let mut table = TidyTagMap::<BinaryHeap<Item<D>>>::new(info.scope_level);
+            // the heap of each scope, along with the number of data input to the scope
+            let mut table = TidyTagMap::<(BinaryHeap<Item<D>>, u64)>::new(info.scope_level);
             move |input, output| {
                 input.for_each_batch(|dataset| {
                     let cmp_clone = cmp.cmp.clone();
                     if !dataset.is_empty() {
-                        let heap = table
-                            .get_mut_or_else(&dataset.tag, || BinaryHeap::with_capacity(size as usize));
+                        // the heap grows with the input, as `size` may be far beyond the number of data;
+                        let (heap, seq) = table.get_mut_or_else(&dataset.tag, || {
+                            let heap =
+                                BinaryHeap::with_capacity(std::cmp::min(size as usize, HEAP_INIT_CAPACITY));
+                            (heap, 0)
+                        });
                         for d in dataset.drain() {
+                            // the data tied with the top of a full heap is input later, so it is not kept
                             if heap.len() < size as usize {
-                                heap.push(Item { inner: d, cmp: cmp_clone.clone() });
+                                heap.push(Item { inner: d, seq: *seq, cmp: cmp_clone.clone() });
                             } else {
                                 if (*cmp_clone)(&d, &heap.peek().unwrap().inner) == Ordering::Less {
                                     heap.pop();
-                                    heap.push(Item { inner: d, cmp: cmp_clone.clone() });
+                                    heap.push(Item { inner: d, seq: *seq, cmp: cmp_clone.clone() });
                                 }
                             }
+                            *seq += 1;
                         }
                     }
 
                     if dataset.is_last() {
                         let mut session = output.new_session(&dataset.tag)?;
-                        if let Some(heap) = table.remove(&dataset.tag) {
+                        if let Some((heap, _)) = table.remove(&dataset.tag) {
                             session.give_iterator(
                                 heap.into_sorted_vec()
                                     .into_iter()
@@ -23,6 +23,7 @@ use pegasus_common::io::{ReadExt, WriteExt};
 use crate::codec::{Decode, Encode};
 
 mod any;
+mod chunk;
 mod collect;
 mod correlate;
 mod count;
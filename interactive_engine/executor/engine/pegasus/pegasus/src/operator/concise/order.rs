@@ -52,27 +52,41 @@ impl<D: Data> SortBy<D> for Stream<D> {
     where
         F: Fn(&D, &D) -> Ordering + Send + 'static,
     {
-        self.aggregate().unary("sort_by", |info| {
-            let mut map = TidyTagMap::new(info.scope_level);
-            move |input, output| {
-                input.for_each_batch(|dataset| {
-                    if !dataset.is_empty() {
-                        let vec = map.get_mut_or_else(&dataset.tag, Vec::new);
-                        for d in dataset.drain() {
-                            vec.push(d);
-                        }
+        sort_by_impl(self, "sort_by", move |vec| vec.sort_by(|x, y| cmp(x, y)))
+    }
+
+    fn sort_unstable_by<F>(self, cmp: F) -> Result<Stream<D>, BuildJobError>
+    where
+        F: Fn(&D, &D) -> Ordering + Send + 'static,
+    {
+        sort_by_impl(self, "sort_unstable_by", move |vec| vec.sort_unstable_by(|x, y| cmp(x, y)))
+    }
+}
+
+fn sort_by_impl<D: Data, S>(stream: Stream<D>, name: &str, sort: S) -> Result<Stream<D>, BuildJobError>
+where
+    S: Fn(&mut Vec<D>) + Send + 'static,
+{
+    stream.aggregate().unary(name, |info| {
+        let mut map = TidyTagMap::new(info.scope_level);
+        move |input, output| {
+            input.for_each_batch(|dataset| {
+                if !dataset.is_empty() {
+                    let vec = map.get_mut_or_else(&dataset.tag, Vec::new);
+                    for d in dataset.drain() {
+                        vec.push(d);
                     }
+                }
 
-                    if dataset.is_last() {
-                        let mut session = output.new_session(&dataset.tag)?;
-                        if let Some(mut vec) = map.remove(&dataset.tag) {
-                            vec.sort_by(|x, y| cmp(x, y));
-                            session.give_iterator(vec.into_iter())?;
-                        }
+                if dataset.is_last() {
+                    let mut session = output.new_session(&dataset.tag)?;
+                    if let Some(mut vec) = map.remove(&dataset.tag) {
+                        sort(&mut vec);
+                        session.give_iterator(vec.into_iter())?;
                     }
-                    Ok(())
-                })
-            }
-        })
-    }
+                }
+                Ok(())
+            })
+        }
+    })
 }
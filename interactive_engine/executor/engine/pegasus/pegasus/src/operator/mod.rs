@@ -307,6 +307,13 @@ impl Operator {
         self.inputs.is_empty() || self.inputs.iter().all(|i| i.is_exhaust())
     }
 
+    /// Check if this operator is pulling new data from an iterator, e.g., a source, see [`OutputProxy::is_pulling`];
+    pub fn is_pulling(&self) -> bool {
+        self.outputs
+            .iter()
+            .any(|output| output.is_pulling())
+    }
+
     pub fn is_idle(&self) -> IOResult<bool> {
         for output in self.outputs.iter() {
             if !output.get_blocks().is_empty() {
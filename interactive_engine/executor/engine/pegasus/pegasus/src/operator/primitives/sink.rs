@@ -45,8 +45,8 @@ where
     ) -> Result<(), JobExecError> {
         let mut input = new_input_session::<D>(&inputs[0]);
         input.for_each_batch(|dataset| {
-            for d in dataset.drain() {
-                self.collector.on_next(d)?;
+            if !dataset.is_empty() {
+                self.collector.on_batch(&mut dataset.drain())?;
             }
             Ok(())
         })
@@ -74,8 +74,9 @@ where
     ) -> Result<(), JobExecError> {
         let mut input = new_input_session::<Single<D>>(&inputs[0]);
         input.for_each_batch(|dataset| {
-            for d in dataset.drain() {
-                self.sender.on_next(d.0)?;
+            if !dataset.is_empty() {
+                self.sender
+                    .on_batch(&mut dataset.drain().map(|d| d.0))?;
             }
             Ok(())
         })
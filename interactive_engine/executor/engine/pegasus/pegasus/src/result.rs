@@ -8,16 +8,22 @@ use dyn_clonable::*;
 
 use crate::api::function::FnResult;
 use crate::api::FromStream;
-use crate::errors::JobExecError;
+use crate::errors::{JobExecError, JobFailure};
 
 #[clonable]
 pub trait FromStreamExt<T>: FromStream<T> + Clone {
     fn on_error(&mut self, error: Box<dyn Error + Send>);
 }
 
+/// The hook to observe each batch of results right before they are sunk
+pub type BatchHook<T> = Arc<dyn Fn(&[T]) + Send + Sync>;
+
 pub struct ResultSink<T> {
     cancel: Arc<AtomicBool>,
     kind: ResultSinkKind<T>,
+    batch_hook: Option<BatchHook<T>>,
+    /// whether the end of results, i.e., an error or the cancellation, has been signaled via this sink;
+    closed: bool,
 }
 
 pub enum ResultSinkKind<T> {
@@ -30,6 +36,8 @@ impl<T: 'static> ResultSink<T> {
         ResultSink {
             cancel: Arc::new(AtomicBool::new(false)),
             kind: ResultSinkKind::Default(DefaultResultSink::new(tx)),
+            batch_hook: None,
+            closed: false,
         }
     }
 
@@ -40,9 +48,21 @@ impl<T: 'static> ResultSink<T> {
         ResultSink {
             cancel: Arc::new(AtomicBool::new(false)),
             kind: ResultSinkKind::Customized(Box::new(sink)),
+            batch_hook: None,
+            closed: false,
         }
     }
 
+    /// Set the hook to be invoked with each non-empty batch of results, before the results are sunk.
+    /// The hook is invoked on the worker thread that produces the batch, thus it is expected to be
+    /// lightweight, e.g., handing over the results to another thread, to avoid blocking the worker.
+    pub fn set_batch_hook<F>(&mut self, hook: F)
+    where
+        F: Fn(&[T]) + Send + Sync + 'static,
+    {
+        self.batch_hook = Some(Arc::new(hook));
+    }
+
     pub fn get_cancel_hook(&self) -> &Arc<AtomicBool> {
         &self.cancel
     }
@@ -50,6 +70,7 @@ impl<T: 'static> ResultSink<T> {
     pub fn set_cancel_hook(&mut self, is_canceled: bool) {
         self.cancel.store(is_canceled, Ordering::SeqCst);
         if is_canceled {
+            self.closed = true;
             match &mut self.kind {
                 ResultSinkKind::Customized(tx) => {
                     let msg = "Job is canceled".to_string();
@@ -63,6 +84,7 @@ impl<T: 'static> ResultSink<T> {
     }
 
     pub fn on_error<E: std::error::Error + Send + 'static>(&mut self, error: E) {
+        self.closed = true;
         match &mut self.kind {
             ResultSinkKind::Default(tx) => {
                 tx.tx.send(Err(Box::new(error))).ok();
@@ -72,6 +94,17 @@ impl<T: 'static> ResultSink<T> {
             }
         }
     }
+
+    /// Signal the end of results to the consumers, if neither an error nor the cancellation has been
+    /// signaled yet via this sink. It is for a worker that is released before it finishes, e.g., on panic,
+    /// of which the consumers would otherwise wait for the results forever. The job is canceled as well,
+    /// as the peers of the worker would also wait for its data forever.
+    pub fn close(&mut self) {
+        if !self.closed {
+            self.cancel.store(true, Ordering::SeqCst);
+            self.on_error(JobExecError::from("worker is released before finished".to_string()));
+        }
+    }
 }
 
 impl<T: Send + Debug + 'static> FromStream<T> for ResultSink<T> {
@@ -81,6 +114,21 @@ impl<T: Send + Debug + 'static> FromStream<T> for ResultSink<T> {
             ResultSinkKind::Customized(tx) => tx.on_next(next),
         }
     }
+
+    fn on_batch(&mut self, batch: &mut dyn Iterator<Item = T>) -> FnResult<()> {
+        if let Some(hook) = self.batch_hook.clone() {
+            let batch = batch.collect::<Vec<_>>();
+            hook(&batch);
+            for next in batch {
+                self.on_next(next)?;
+            }
+        } else {
+            for next in batch {
+                self.on_next(next)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T> Clone for ResultSink<T> {
@@ -89,7 +137,7 @@ impl<T> Clone for ResultSink<T> {
             ResultSinkKind::Default(tx) => ResultSinkKind::Default(tx.clone()),
             ResultSinkKind::Customized(tx) => ResultSinkKind::Customized(tx.clone()),
         };
-        ResultSink { cancel: self.cancel.clone(), kind }
+        ResultSink { cancel: self.cancel.clone(), kind, batch_hook: self.batch_hook.clone(), closed: false }
     }
 }
 
@@ -180,7 +228,8 @@ impl<T> ResultStream<T> {
             Ok(Ok(res)) => Some(Ok(res)),
             Ok(Err(e)) => {
                 self.is_poison.store(true, Ordering::SeqCst);
-                if self.is_cancel() {
+                // the error that the job is failed with, rather than one caused by the cancellation;
+                if self.is_cancel() && !is_job_failure(&e) {
                     self.report_cancel()
                 } else {
                     Some(Err(e))
@@ -227,6 +276,13 @@ impl<T> ResultStream<T> {
     }
 }
 
+/// Whether the error is the one that the job is failed with via [`crate::fail_job`];
+fn is_job_failure(err: &Box<dyn Error + Send>) -> bool {
+    err.downcast_ref::<JobExecError>()
+        .map(|err| err.as_ref::<JobFailure>().is_some())
+        .unwrap_or(false)
+}
+
 /// blocking iterator which will block on `next` if message is not available;
 impl<T> Iterator for ResultStream<T> {
     type Item = Result<T, Box<dyn Error + Send>>;
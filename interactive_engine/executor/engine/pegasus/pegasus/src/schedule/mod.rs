@@ -13,7 +13,9 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
-use std::time::Instant;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use crate::data_plane::GeneralPull;
 use crate::dataflow::Dataflow;
@@ -31,6 +33,107 @@ pub mod strategies;
 
 pub trait StepStrategy: Send + 'static {
     fn make_step(&mut self, task: &Dataflow) -> Result<(), JobExecError>;
+
+    /// Hold back the operators pulling new data from iterators, e.g., sources, in the following steps
+    /// if `throttled`, while the others keep consuming the data in flight;
+    fn set_throttled(&mut self, _throttled: bool) {}
+}
+
+/// The progress of a worker's scheduling, which is updated by the scheduler while stepping the
+/// dataflow, and can be inspected by other threads, e.g., to diagnose a worker hanging in a step.
+pub struct ScheduleProbe {
+    since: Instant,
+    /// the milliseconds since `since` when the worker begins to execute, or `!0` if not executing;
+    executing_since: AtomicU64,
+    /// the index of the operator being fired, or `!0` if none;
+    firing: AtomicUsize,
+    op_names: Mutex<Vec<String>>,
+    step_count: AtomicUsize,
+    /// the number of events received in the latest step;
+    received_events: AtomicUsize,
+}
+
+/// A snapshot of the `ScheduleProbe`
+#[derive(Debug, Clone)]
+pub struct ProbeSnapshot {
+    /// how long the worker has been executing, if it is;
+    pub executing: Option<Duration>,
+    /// the index and name of the operator being fired, if any;
+    pub firing: Option<(usize, String)>,
+    pub step_count: usize,
+    pub received_events: usize,
+}
+
+impl Default for ScheduleProbe {
+    fn default() -> Self {
+        ScheduleProbe {
+            since: Instant::now(),
+            executing_since: AtomicU64::new(!0),
+            firing: AtomicUsize::new(!0),
+            op_names: Mutex::new(vec![]),
+            step_count: AtomicUsize::new(0),
+            received_events: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ScheduleProbe {
+    pub(crate) fn set_op_names(&self, op_names: Vec<String>) {
+        if let Ok(mut names) = self.op_names.lock() {
+            *names = op_names;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn enter_execute(&self) {
+        let now = self.since.elapsed().as_millis() as u64;
+        self.executing_since
+            .store(now, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn exit_execute(&self) {
+        self.executing_since
+            .store(!0, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn enter_fire(&self, index: usize) {
+        self.firing.store(index, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn exit_fire(&self) {
+        self.firing.store(!0, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ProbeSnapshot {
+        let executing_since = self.executing_since.load(Ordering::Relaxed);
+        let executing = if executing_since == !0 {
+            None
+        } else {
+            let now = self.since.elapsed().as_millis() as u64;
+            Some(Duration::from_millis(now.saturating_sub(executing_since)))
+        };
+        let firing = self.firing.load(Ordering::Relaxed);
+        let firing = if firing == !0 {
+            None
+        } else {
+            let name = self
+                .op_names
+                .lock()
+                .ok()
+                .and_then(|names| names.get(firing).cloned())
+                .unwrap_or_default();
+            Some((firing, name))
+        };
+        ProbeSnapshot {
+            executing,
+            firing,
+            step_count: self.step_count.load(Ordering::Relaxed),
+            received_events: self.received_events.load(Ordering::Relaxed),
+        }
+    }
 }
 
 pub struct Schedule {
@@ -39,6 +142,7 @@ pub struct Schedule {
     event_collector: EventCollector,
     sch_ops: Vec<OperatorScheduler>,
     strategy: Box<dyn StepStrategy>,
+    probe: Arc<ScheduleProbe>,
 }
 
 impl Schedule {
@@ -50,15 +154,26 @@ impl Schedule {
             event_collector,
             sch_ops: vec![],
             strategy: Box::new(strategies::WaterfallStrategy::default()),
+            probe: Arc::new(ScheduleProbe::default()),
         }
     }
 
+    pub fn probe(&self) -> &Arc<ScheduleProbe> {
+        &self.probe
+    }
+
     #[allow(dead_code)]
     #[inline]
     pub fn reset_step_strategy<S: StepStrategy>(&mut self, strategy: S) {
         self.strategy = Box::new(strategy);
     }
 
+    /// See [`StepStrategy::set_throttled`];
+    #[inline]
+    pub fn set_throttled(&mut self, throttled: bool) {
+        self.strategy.set_throttled(throttled);
+    }
+
     pub fn add_schedule_op(
         &mut self, worker_id: WorkerId, index: usize, scope_level: u32,
         inputs_notify: Vec<Option<Box<dyn InputEndNotify>>>,
@@ -71,6 +186,9 @@ impl Schedule {
     pub fn try_notify(&mut self) -> Result<(), JobExecError> {
         self.event_collector.collect()?;
         let updates = self.event_collector.get_updates();
+        self.probe
+            .received_events
+            .store(updates.len(), Ordering::Relaxed);
         if !updates.is_empty() {
             for event in updates.drain(..) {
                 let index = event.target_port.index;
@@ -105,6 +223,9 @@ impl Schedule {
         self.event_emitter.flush()?;
         debug_worker!("========== finish step {} cost: {:?} ==========", self.step_count, start.elapsed(),);
         self.step_count += 1;
+        self.probe
+            .step_count
+            .store(self.step_count, Ordering::Relaxed);
         Ok(())
     }
 
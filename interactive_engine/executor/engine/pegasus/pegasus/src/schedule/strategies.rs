@@ -5,6 +5,8 @@ use crate::schedule::StepStrategy;
 #[derive(Default)]
 pub(super) struct WaterfallStrategy {
     markers: Vec<bool>,
+    /// don't fire the operators pulling new data from iterators if throttled;
+    throttled: bool,
 }
 
 impl WaterfallStrategy {
@@ -26,6 +28,10 @@ impl WaterfallStrategy {
     }
 
     fn fire(&self, task: &Dataflow, index: usize) -> Result<bool, JobExecError> {
+        if self.throttled && task.is_pulling(index) {
+            trace_worker!("operator {} is throttled;", index);
+            return Ok(false);
+        }
         loop {
             match task.try_fire(index) {
                 Ok(fired) => return Ok(fired),
@@ -72,6 +78,10 @@ impl StepStrategy for WaterfallStrategy {
         self.fire_follows(0, task)?;
         Ok(())
     }
+
+    fn set_throttled(&mut self, throttled: bool) {
+        self.throttled = throttled;
+    }
 }
 
 #[allow(dead_code)]
@@ -161,19 +161,25 @@ impl<V> TidyTagMap<V> {
     }
 }
 
-impl<V: Default> TidyTagMap<V> {
-    pub fn get_mut_or_insert(&mut self, tag: &Tag) -> &mut V {
+impl<V> TidyTagMap<V> {
+    pub fn get_mut_or_insert_with<F: FnOnce() -> V>(&mut self, tag: &Tag, func: F) -> &mut V {
         match tag {
-            Tag::Root => self.root.get_or_insert_with(V::default),
-            Tag::One(v) => self.first.entry(*v).or_insert_with(V::default),
+            Tag::Root => self.root.get_or_insert_with(func),
+            Tag::One(v) => self.first.entry(*v).or_insert_with(func),
             _ => self
                 .others
                 .entry(tag.clone())
-                .or_insert_with(V::default),
+                .or_insert_with(func),
         }
     }
 }
 
+impl<V: Default> TidyTagMap<V> {
+    pub fn get_mut_or_insert(&mut self, tag: &Tag) -> &mut V {
+        self.get_mut_or_insert_with(tag, V::default)
+    }
+}
+
 pub enum Iter<'a, V> {
     Root(std::option::Iter<'a, V>),
     First(std::collections::hash_map::Iter<'a, u32, V>),
@@ -13,11 +13,12 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
-use std::any::TypeId;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use opentelemetry::global::BoxedSpan;
 use opentelemetry::{trace, trace::Span, KeyValue};
@@ -32,46 +33,61 @@ use crate::errors::{BuildJobError, JobExecError};
 use crate::event::emitter::EventEmitter;
 use crate::event::Event;
 use crate::graph::Port;
+use crate::metrics::{JobMetrics, MetricsRegistry, METRICS_REGISTRY_KEY};
 use crate::progress::DynPeers;
 use crate::progress::EndOfScope;
 use crate::resource::{KeyedResources, ResourceMap};
 use crate::result::ResultSink;
-use crate::schedule::Schedule;
+use crate::schedule::{ProbeSnapshot, Schedule, ScheduleProbe};
 use crate::{Data, JobConf, Tag, WorkerId};
 
 pub struct Worker<D: Data, T: Debug + Send + 'static> {
     pub conf: Arc<JobConf>,
     pub id: WorkerId,
     task: WorkerTask,
+    memory_guard: MemoryGuard,
     peer_guard: Arc<AtomicUsize>,
+    pause_hook: Arc<PauseHook>,
+    /// the custom metrics merged from the finished workers of the job;
+    job_metrics: Arc<Mutex<JobMetrics>>,
+    /// whether the custom metrics of this worker have been merged into those of the job;
+    is_metrics_collected: bool,
     start: Instant,
     sink: ResultSink<T>,
     resources: ResourceMap,
     keyed_resources: KeyedResources,
     is_finished: bool,
     span: BoxedSpan,
+    watchdog: Option<Watchdog>,
     _ph: std::marker::PhantomData<D>,
 }
 
 impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
     pub(crate) fn new(
-        conf: &Arc<JobConf>, id: WorkerId, peer_guard: &Arc<AtomicUsize>, sink: ResultSink<T>,
-        span: BoxedSpan,
+        conf: &Arc<JobConf>, id: WorkerId, peer_guard: &Arc<AtomicUsize>, pause_hook: &Arc<PauseHook>,
+        job_metrics: &Arc<Mutex<JobMetrics>>, sink: ResultSink<T>, span: BoxedSpan,
     ) -> Self {
         if peer_guard.fetch_add(1, Ordering::SeqCst) == 0 {
             pegasus_memory::alloc::new_task(conf.job_id as usize);
         }
+        let mut keyed_resources = KeyedResources::default();
+        keyed_resources.insert(METRICS_REGISTRY_KEY.to_string(), Box::new(MetricsRegistry::default()));
         Worker {
             conf: conf.clone(),
             id,
             task: WorkerTask::Empty,
+            memory_guard: MemoryGuard::new(conf.job_id, conf.memory_limit_bytes),
             peer_guard: peer_guard.clone(),
+            pause_hook: pause_hook.clone(),
+            job_metrics: job_metrics.clone(),
+            is_metrics_collected: false,
             start: Instant::now(),
             sink,
             resources: ResourceMap::default(),
-            keyed_resources: KeyedResources::default(),
+            keyed_resources,
             is_finished: false,
             span: span,
+            watchdog: None,
             _ph: std::marker::PhantomData,
         }
     }
@@ -115,6 +131,14 @@ impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
         func(&mut input, output)?;
         let mut sch = Schedule::new(event_emitter, rx);
         let df = dfb.build(&mut sch)?;
+        if self.conf.capture_on_timeout && self.conf.time_limit > 0 && self.conf.time_limit != !0 {
+            let time_limit = Duration::from_millis(self.conf.time_limit);
+            let cancel_hook = self.sink.get_cancel_hook().clone();
+            match Watchdog::spawn(self.id, time_limit, self.start, sch.probe().clone(), cancel_hook) {
+                Ok(watchdog) => self.watchdog = Some(watchdog),
+                Err(e) => warn_worker!("fail to spawn watchdog: {}", e),
+            }
+        }
         self.task = WorkerTask::Dataflow(df, sch);
         let root = Box::new(root_builder)
             .build()
@@ -125,6 +149,23 @@ impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
         Ok(())
     }
 
+    /// Observe each batch of results produced by this worker, for the host to deliver the results
+    /// incrementally. It must be called before [`Worker::dataflow`] to take effect, and the hook is
+    /// invoked on the worker thread, see [`ResultSink::set_batch_hook`].
+    pub fn on_batch<F>(&mut self, hook: F)
+    where
+        F: Fn(&[T]) + Send + Sync + 'static,
+    {
+        self.sink.set_batch_hook(hook);
+    }
+
+    /// The hook to pause and resume the job of this worker, which is shared by all the workers of the
+    /// job in this server, like the cancel hook of the job, see [`PauseHook`]. Hold a clone of it before
+    /// the worker is spawned, to pause the job from anywhere afterwards.
+    pub fn get_pause_hook(&self) -> &Arc<PauseHook> {
+        &self.pause_hook
+    }
+
     pub fn add_resource<R: Send + 'static>(&mut self, resource: R) {
         let type_id = TypeId::of::<R>();
         self.resources
@@ -136,6 +177,27 @@ impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
             .insert(key, Box::new(resource));
     }
 
+    /// Install the keyed resources collected by the host at once, which can be got by
+    /// [`crate::resource::get_resource_by_key`] in the operators. If any of the keys has been
+    /// taken, none of the resources are installed, and all the colliding keys are reported.
+    pub fn add_keyed_resources(
+        &mut self, resources: HashMap<String, Box<dyn Any + Send>>,
+    ) -> Result<(), BuildJobError> {
+        let mut collisions: Vec<&String> = resources
+            .keys()
+            .filter(|key| self.keyed_resources.contains_key(*key))
+            .collect();
+        if !collisions.is_empty() {
+            collisions.sort();
+            return Err(BuildJobError::InternalError(format!(
+                "keyed resources {:?} already exist in worker {:?}",
+                collisions, self.id
+            )));
+        }
+        self.keyed_resources.extend(resources);
+        Ok(())
+    }
+
     fn check_cancel(&self) -> bool {
         if self.conf.time_limit > 0 {
             let elapsed = self.start.elapsed().as_millis() as u64;
@@ -148,13 +210,363 @@ impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
             .load(Ordering::SeqCst)
     }
 
+    /// Collect the custom metrics of the worker which is canceled or failed, and deliver the metrics
+    /// of the job if it is the last worker, as are delivered when the job finishes.
+    fn abort_metrics(&mut self) {
+        if self.is_metrics_collected {
+            return;
+        }
+        self.is_metrics_collected = true;
+        collect_metrics(&mut self.span, &self.job_metrics, kept_metrics(&self.keyed_resources));
+        if self.peer_guard.fetch_sub(1, Ordering::SeqCst) == 1 {
+            deliver_metrics(&self.conf, &self.job_metrics);
+        }
+    }
+
+    /// Signal the cancellation to the result sink, or the error that the job is failed with via
+    /// [`crate::fail_job`] if any.
+    fn sink_canceled(&mut self) {
+        match crate::job_failure(self.id.job_id) {
+            Some(err) => self.sink.on_error(err),
+            None => self.sink.set_cancel_hook(true),
+        }
+    }
+
     fn release(&mut self) {
+        if let Some(watchdog) = self.watchdog.take() {
+            watchdog.stop();
+        }
         if self.peer_guard.load(Ordering::SeqCst) == 0 {
             pegasus_memory::alloc::remove_task(self.conf.job_id as usize);
+            crate::remove_job_failure(self.conf.job_id);
         }
         if !crate::remove_cancel_hook(self.conf.job_id).is_ok() {
             error!("JOB_CANCEL_MAP is poisoned!");
         }
+        if !self.is_finished {
+            self.sink.close();
+        }
+    }
+}
+
+/// How often the parked workers of a paused job check whether the job is canceled or beyond its time limit.
+const PAUSE_WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Pause and resume all the workers of a job in this server, e.g., for external rate limiting, which is
+/// shared by the workers like the cancel hook of the job, see [`Worker::get_pause_hook`].
+///
+/// Once the job is paused, each of its workers is parked out of the executor the next time it is
+/// scheduled, which keeps its dataflow and executes no step, instead of being polled. The parked workers
+/// are spawned again when the job is resumed, or when it is canceled or beyond its time limit, which are
+/// watched by a thread of the job while any worker is parked.
+///
+/// The pause is not a `Paused` state of `WorkerTask` switched by `Worker::pause()` and `Worker::resume()`,
+/// as a worker is owned by the executor once spawned, out of reach of the one to pause it, and a paused
+/// task returning `TaskState::NotReady` would still be polled by the executor without any progress.
+pub struct PauseHook {
+    paused: AtomicBool,
+    cancel: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    parked: Mutex<ParkedWorkers>,
+}
+
+#[derive(Default)]
+struct ParkedWorkers {
+    workers: Vec<Box<dyn Task>>,
+    /// whether a thread is watching the cancellation and the time limit of the job;
+    is_watched: bool,
+}
+
+impl PauseHook {
+    pub(crate) fn new(cancel: Arc<AtomicBool>, deadline: Option<Instant>) -> Self {
+        PauseHook {
+            paused: AtomicBool::new(false),
+            cancel,
+            deadline,
+            parked: Mutex::new(ParkedWorkers::default()),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Pause the job, whose workers are parked once they are scheduled.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resume the job, and spawn the parked workers of it again.
+    pub fn resume(self: &Arc<Self>) {
+        // must be stored before taking the parked workers, see `PauseHook::park`;
+        self.paused.store(false, Ordering::SeqCst);
+        let workers = match self.parked.lock() {
+            Ok(mut parked) => std::mem::take(&mut parked.workers),
+            Err(_) => {
+                error!("parked workers are poisoned;");
+                return;
+            }
+        };
+        self.spawn(workers);
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+            || self
+                .deadline
+                .map(|deadline| Instant::now() >= deadline)
+                .unwrap_or(false)
+    }
+
+    /// Park the worker if the job is paused, or give it back otherwise.
+    fn park(self: &Arc<Self>, worker: Box<dyn Task>) -> Result<(), Box<dyn Task>> {
+        let mut parked = match self.parked.lock() {
+            Ok(parked) => parked,
+            Err(_) => return Err(worker),
+        };
+        // checked with the lock held, so that the worker won't be parked after the job is resumed;
+        if !self.is_paused() || self.is_terminated() {
+            return Err(worker);
+        }
+        parked.workers.push(worker);
+        if !parked.is_watched {
+            parked.is_watched = true;
+            let hook = self.clone();
+            let watch = std::thread::Builder::new()
+                .name("pause-watchdog".to_owned())
+                .spawn(move || hook.watch());
+            if let Err(e) = watch {
+                // the workers will be parked until the job is resumed;
+                warn!("fail to watch the parked workers: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn watch(self: &Arc<Self>) {
+        loop {
+            std::thread::sleep(PAUSE_WATCH_INTERVAL);
+            let workers = match self.parked.lock() {
+                Ok(mut parked) => {
+                    if parked.workers.is_empty() {
+                        parked.is_watched = false;
+                        return;
+                    } else if self.is_terminated() {
+                        parked.is_watched = false;
+                        std::mem::take(&mut parked.workers)
+                    } else {
+                        continue;
+                    }
+                }
+                Err(_) => return,
+            };
+            // spawned to finish themselves, as they are no longer parked once the job is terminated;
+            self.spawn(workers);
+            return;
+        }
+    }
+
+    fn spawn(self: &Arc<Self>, workers: Vec<Box<dyn Task>>) {
+        for worker in workers {
+            let task = ParkableWorker { worker: Some(worker), pause_hook: self.clone() };
+            if pegasus_executor::spawn(task).is_err() {
+                error!("fail to spawn the parked worker, as the executor has shutdown;");
+            }
+        }
+    }
+}
+
+/// The task of a worker in the executor, which parks the worker while its job is paused, see [`PauseHook`].
+pub(crate) struct ParkableWorker {
+    worker: Option<Box<dyn Task>>,
+    pause_hook: Arc<PauseHook>,
+}
+
+impl ParkableWorker {
+    pub(crate) fn new<D: Data, T: Debug + Send + 'static>(worker: Worker<D, T>) -> Self {
+        let pause_hook = worker.get_pause_hook().clone();
+        ParkableWorker { worker: Some(Box::new(worker)), pause_hook }
+    }
+
+    fn run<F: FnOnce(&mut dyn Task) -> TaskState>(&mut self, func: F) -> TaskState {
+        if self.pause_hook.is_paused() {
+            if let Some(worker) = self.worker.take() {
+                match self.pause_hook.park(worker) {
+                    // the worker is owned by the hook, and this task is done;
+                    Ok(()) => return TaskState::Finished,
+                    Err(worker) => self.worker = Some(worker),
+                }
+            }
+        }
+        match self.worker.as_mut() {
+            Some(worker) => func(worker.as_mut()),
+            None => TaskState::Finished,
+        }
+    }
+}
+
+impl Task for ParkableWorker {
+    fn execute(&mut self) -> TaskState {
+        self.run(|worker| worker.execute())
+    }
+
+    fn check_ready(&mut self) -> TaskState {
+        self.run(|worker| worker.check_ready())
+    }
+}
+
+/// The longest time a worker can stay in execution beyond the time limit of its job,
+/// before the watchdog captures what it is doing.
+const WATCHDOG_GRACE_PERIOD: Duration = Duration::from_millis(1000);
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Watch the worker that is stuck in execution beyond the time limit, e.g., inside a storage iterator
+/// that never returns the control to check the cancellation. Once the worker stays in execution for
+/// more than `WATCHDOG_GRACE_PERIOD`, it logs and records a snapshot of the worker's scheduling,
+/// and then forces the cancel hook of the job.
+struct Watchdog {
+    probe: Arc<ScheduleProbe>,
+    stopped: Arc<AtomicBool>,
+    /// the elapsed time of the job and the snapshot of the worker, when the worker is stuck;
+    captured: Arc<Mutex<Option<(Duration, ProbeSnapshot)>>>,
+}
+
+impl Watchdog {
+    fn spawn(
+        id: WorkerId, time_limit: Duration, start: Instant, probe: Arc<ScheduleProbe>,
+        cancel_hook: Arc<AtomicBool>,
+    ) -> std::io::Result<Self> {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let captured = Arc::new(Mutex::new(None));
+        let watchdog =
+            Watchdog { probe: probe.clone(), stopped: stopped.clone(), captured: captured.clone() };
+        std::thread::Builder::new()
+            .name(format!("watchdog-{:?}", id))
+            .spawn(move || {
+                while !stopped.load(Ordering::SeqCst) {
+                    std::thread::sleep(WATCHDOG_INTERVAL);
+                    let elapsed = start.elapsed();
+                    if elapsed < time_limit {
+                        continue;
+                    }
+                    let snapshot = probe.snapshot();
+                    match snapshot.executing {
+                        Some(executing) if executing > WATCHDOG_GRACE_PERIOD => {
+                            warn!(
+                                "worker {:?} is stuck in execution for {:?}, beyond the time limit {:?} with elapsed {:?}; snapshot: {:?}",
+                                id, executing, time_limit, elapsed, snapshot
+                            );
+                            if let Ok(mut captured) = captured.lock() {
+                                captured.replace((elapsed, snapshot));
+                            }
+                            cancel_hook.store(true, Ordering::SeqCst);
+                            break;
+                        }
+                        Some(_) => (),
+                        // the worker will be canceled once it is scheduled to execute again;
+                        None => break,
+                    }
+                }
+            })?;
+        Ok(watchdog)
+    }
+
+    fn take_captured(&self) -> Option<(Duration, ProbeSnapshot)> {
+        self.captured
+            .lock()
+            .ok()
+            .and_then(|mut captured| captured.take())
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+fn snapshot_attributes(elapsed: Duration, snapshot: &ProbeSnapshot) -> Vec<KeyValue> {
+    let mut attributes = vec![
+        KeyValue::new("elapsed_ms", elapsed.as_millis() as i64),
+        KeyValue::new("step_count", snapshot.step_count as i64),
+        KeyValue::new("received_events", snapshot.received_events as i64),
+    ];
+    if let Some(executing) = snapshot.executing {
+        attributes.push(KeyValue::new("executing_ms", executing.as_millis() as i64));
+    }
+    if let Some((index, name)) = snapshot.firing.as_ref() {
+        attributes.push(KeyValue::new("firing_operator", format!("{}({})", name, index)));
+    }
+    attributes
+}
+
+/// The longest time a job can stay beyond its memory limit before being aborted.
+const MEMORY_RECOVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Apply backpressure to the job whose tracked memory exceeds `JobConf::memory_limit_bytes`, by holding
+/// back the operators pulling new data from iterators, e.g., the sources, see [`Schedule::set_throttled`].
+/// The memory is tracked by the allocator of `pegasus_memory`, which is installed with the `mem` feature.
+struct MemoryGuard {
+    job_id: usize,
+    limit: u64,
+    recover_timeout: Duration,
+    /// since when the job has been beyond the memory limit;
+    exceeded_since: Option<Instant>,
+}
+
+impl MemoryGuard {
+    fn new(job_id: u64, limit: u64) -> Self {
+        if limit != !0u64 && !cfg!(feature = "mem") {
+            warn!("the memory limit of job({}) takes no effect without the `mem` feature;", job_id);
+        }
+        MemoryGuard {
+            job_id: job_id as usize,
+            limit,
+            recover_timeout: MEMORY_RECOVER_TIMEOUT,
+            exceeded_since: None,
+        }
+    }
+
+    #[inline]
+    fn is_enabled(&self) -> bool {
+        self.limit != !0u64
+    }
+
+    /// Return `Ok(true)` if the job uses more memory than the limit, and the worker should back off
+    /// until some memory is released; Return error if the job can't recover from it in time.
+    fn check(&mut self, used: Option<usize>) -> Result<bool, JobExecError> {
+        match used {
+            Some(used) if self.is_enabled() && used as u64 > self.limit => {
+                if let Some(since) = self.exceeded_since {
+                    if since.elapsed() > self.recover_timeout {
+                        return Err(JobExecError::from(format!(
+                            "job({}) uses {} bytes memory, beyond the limit {} bytes for {:?};",
+                            self.job_id, used, self.limit, self.recover_timeout
+                        )));
+                    }
+                } else {
+                    warn_worker!(
+                        "job({}) uses {} bytes memory, beyond the limit {} bytes, apply backpressure;",
+                        self.job_id,
+                        used,
+                        self.limit
+                    );
+                    self.exceeded_since = Some(Instant::now());
+                }
+                Ok(true)
+            }
+            _ => {
+                self.exceeded_since = None;
+                Ok(false)
+            }
+        }
+    }
+
+    fn check_job_memory(&mut self) -> Result<bool, JobExecError> {
+        if self.is_enabled() {
+            let used = pegasus_memory::alloc::check_task_memory(self.job_id);
+            self.check(used)
+        } else {
+            Ok(false)
+        }
     }
 }
 
@@ -164,10 +576,13 @@ enum WorkerTask {
 }
 
 impl WorkerTask {
-    pub fn execute(&mut self) -> Result<TaskState, JobExecError> {
+    pub fn execute(&mut self, memory_guard: &mut MemoryGuard) -> Result<TaskState, JobExecError> {
         match self {
             WorkerTask::Empty => Ok(TaskState::Finished),
             WorkerTask::Dataflow(df, sch) => {
+                // only hold back the sources beyond the memory limit, while the data in flight keep
+                // being consumed, which releases the memory;
+                sch.set_throttled(memory_guard.check_job_memory()?);
                 sch.step(df)?;
                 if df.check_finish() {
                     sch.close()?;
@@ -237,15 +652,76 @@ impl<'a> Drop for WorkerContext<'a> {
     }
 }
 
+/// Take the custom metrics of a worker in execution, whose registry is accessible from its thread.
+fn current_metrics() -> Option<JobMetrics> {
+    match crate::resource::get_resource_by_key::<MetricsRegistry>(METRICS_REGISTRY_KEY) {
+        Some(registry) if !registry.is_empty() => Some(registry.snapshot()),
+        _ => None,
+    }
+}
+
+/// Take the custom metrics of a worker out of execution, whose registry is kept in its keyed resources.
+fn kept_metrics(keyed_resources: &KeyedResources) -> Option<JobMetrics> {
+    keyed_resources
+        .get(METRICS_REGISTRY_KEY)
+        .and_then(|registry| registry.downcast_ref::<MetricsRegistry>())
+        .filter(|registry| !registry.is_empty())
+        .map(|registry| registry.snapshot())
+}
+
+/// Record the custom metrics of a worker into its span, and merge them into the metrics of the job.
+fn collect_metrics(span: &mut BoxedSpan, job_metrics: &Mutex<JobMetrics>, metrics: Option<JobMetrics>) {
+    let metrics = match metrics {
+        Some(metrics) => metrics,
+        None => return,
+    };
+    for (name, value) in &metrics.counters {
+        span.set_attribute(KeyValue::new(format!("metrics.counter.{}", name), *value as i64));
+    }
+    for (name, value) in &metrics.gauges {
+        span.set_attribute(KeyValue::new(format!("metrics.gauge.{}", name), *value));
+    }
+    for (name, summary) in &metrics.histograms {
+        span.set_attribute(KeyValue::new(
+            format!("metrics.histogram.{}.count", name),
+            summary.count as i64,
+        ));
+        span.set_attribute(KeyValue::new(format!("metrics.histogram.{}.sum", name), summary.sum));
+    }
+    if let Ok(mut job_metrics) = job_metrics.lock() {
+        job_metrics.merge(&metrics);
+    }
+}
+
+/// Deliver the merged metrics of the job to its callback, if any, when the last worker finishes.
+fn deliver_metrics(conf: &JobConf, job_metrics: &Mutex<JobMetrics>) {
+    if let Some(callback) = conf.metrics_callback() {
+        let metrics = job_metrics
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_default();
+        callback.call(&metrics);
+    }
+}
+
 impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
     fn execute(&mut self) -> TaskState {
         let _g = crate::worker_id::guard(self.id);
         if self.check_cancel() {
+            if let Some((elapsed, snapshot)) = self
+                .watchdog
+                .as_ref()
+                .and_then(|w| w.take_captured())
+            {
+                self.span
+                    .add_event("timeout snapshot", snapshot_attributes(elapsed, &snapshot));
+            }
+            self.abort_metrics();
             self.span
                 .set_status(trace::Status::error("Job is canceled"));
             self.span.end();
 
-            self.sink.set_cancel_hook(true);
+            self.sink_canceled();
             return TaskState::Finished;
         }
 
@@ -253,7 +729,21 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
         let trace_id = self.span.span_context().trace_id();
         let trace_id_hex = format!("{:x}", trace_id);
 
-        match self.task.execute() {
+        // trace the memory allocated by this worker if the job is limited in memory;
+        let _mem_trace = if self.memory_guard.is_enabled() {
+            pegasus_memory::alloc::reset_current_task(Some(self.conf.job_id as usize));
+            Some(pegasus_memory::alloc::trace_memory_alloc())
+        } else {
+            None
+        };
+        if let Some(watchdog) = self.watchdog.as_ref() {
+            watchdog.probe.enter_execute();
+        }
+        let result = self.task.execute(&mut self.memory_guard);
+        if let Some(watchdog) = self.watchdog.as_ref() {
+            watchdog.probe.exit_execute();
+        }
+        match result {
             Ok(state) => {
                 if TaskState::Finished == state {
                     let elapsed = self.start.elapsed().as_millis();
@@ -265,12 +755,15 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
                         elapsed
                     );
                     self.is_finished = true;
+                    self.is_metrics_collected = true;
+                    collect_metrics(&mut self.span, &self.job_metrics, current_metrics());
                     self.span
                         .set_attribute(KeyValue::new("used_ms", elapsed.to_string()));
                     self.span.set_status(trace::Status::Ok);
                     self.span.end();
                     // if this is last worker, return Finished
                     if self.peer_guard.fetch_sub(1, Ordering::SeqCst) == 1 {
+                        deliver_metrics(&self.conf, &self.job_metrics);
                         state
                     } else {
                         // if other workers are not finished, return NotReady until all workers finished
@@ -282,6 +775,9 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
             }
             Err(e) => {
                 error_worker!("trace_id:{}, job({}) execute error: {}", trace_id_hex, self.id.job_id, e);
+                // put the resources back to the worker, to collect its metrics;
+                drop(_ctx);
+                self.abort_metrics();
                 self.span
                     .set_status(trace::Status::error(format!("Execution error: {}", e)));
                 self.span.end();
@@ -297,7 +793,8 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
             return TaskState::Finished;
         }
         if self.check_cancel() {
-            self.sink.set_cancel_hook(true);
+            self.abort_metrics();
+            self.sink_canceled();
             return TaskState::Finished;
         }
         if !self.is_finished {
@@ -312,12 +809,14 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
                                 self.conf.job_name,
                                 elapsed
                             );
+                            self.is_finished = true;
                         }
                     }
                     state
                 }
                 Err(e) => {
                     error_worker!("job({}) execute error: {}", self.id.job_id, e);
+                    self.abort_metrics();
                     self.sink.on_error(e);
                     TaskState::Finished
                 }
@@ -338,3 +837,130 @@ impl<D: Data, T: Debug + Send + 'static> Drop for Worker<D, T> {
         self.release();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn memory_guard_backpressure_test() {
+        let mut guard = MemoryGuard::new(0, 1024);
+        guard.recover_timeout = Duration::from_millis(100);
+        assert!(!guard.check(Some(512)).unwrap());
+        // back off instead of allocating more memory;
+        assert!(guard.check(Some(4096)).unwrap());
+        assert!(guard.check(Some(4096)).unwrap());
+        // recover after some memory is released;
+        assert!(!guard.check(Some(512)).unwrap());
+        assert!(guard.exceeded_since.is_none());
+        // abort if it can't recover in time;
+        assert!(guard.check(Some(4096)).unwrap());
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(guard.check(Some(4096)).is_err());
+    }
+
+    fn run_paused_job(
+        job_name: &str, steps: &Arc<AtomicUsize>,
+    ) -> (Arc<PauseHook>, crate::result::ResultStream<u32>) {
+        use crate::api::{Map, Sink};
+
+        let mut conf = JobConf::new(job_name);
+        conf.set_workers(2);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sink = ResultSink::new(tx);
+        let results = crate::result::ResultStream::new(conf.job_id, sink.get_cancel_hook().clone(), rx);
+        let mut pause_hook = None;
+        crate::run_opt(conf, sink, |worker| {
+            // paused before spawned, thus the workers are parked once they are scheduled;
+            pause_hook
+                .get_or_insert_with(|| worker.get_pause_hook().clone())
+                .pause();
+            let steps = steps.clone();
+            worker.dataflow(move |input, output| {
+                input
+                    .input_from(0..1000u32)?
+                    .map(move |d| {
+                        steps.fetch_add(1, Ordering::SeqCst);
+                        Ok(d)
+                    })?
+                    .sink_into(output)
+            })
+        })
+        .unwrap();
+        (pause_hook.unwrap(), results)
+    }
+
+    fn count_parked(pause_hook: &PauseHook) -> usize {
+        pause_hook.parked.lock().unwrap().workers.len()
+    }
+
+    #[test]
+    fn pause_job_test() {
+        let steps = Arc::new(AtomicUsize::new(0));
+        let (pause_hook, results) = run_paused_job("pause_job_test", &steps);
+        std::thread::sleep(Duration::from_millis(200));
+        // parked out of the executor instead of being polled;
+        assert_eq!(count_parked(&pause_hook), 2);
+        assert_eq!(steps.load(Ordering::SeqCst), 0);
+
+        pause_hook.resume();
+        assert_eq!(count_parked(&pause_hook), 0);
+        assert_eq!(results.map(|r| r.unwrap()).count(), 2000);
+        assert_eq!(steps.load(Ordering::SeqCst), 2000);
+    }
+
+    #[test]
+    fn cancel_paused_job_test() {
+        let steps = Arc::new(AtomicUsize::new(0));
+        let (pause_hook, mut results) = run_paused_job("cancel_paused_job_test", &steps);
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(count_parked(&pause_hook), 2);
+
+        crate::cancel_job(results.job_id).unwrap();
+        // the parked workers are spawned again to finish themselves, without being resumed;
+        // no result but the cancellation is reported;
+        assert!(results.next().unwrap().is_err());
+        assert!(pause_hook.is_paused());
+        assert_eq!(count_parked(&pause_hook), 0);
+        assert_eq!(steps.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn fail_job_test() {
+        use crate::api::{Map, Sink};
+        use crate::errors::ErrorKind;
+
+        let mut conf = JobConf::new("fail_job_test");
+        conf.set_workers(2);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sink = ResultSink::new(tx);
+        let mut results = crate::result::ResultStream::new(conf.job_id, sink.get_cancel_hook().clone(), rx);
+        crate::run_opt(conf, sink, |worker| {
+            worker.dataflow(|input, output| {
+                input
+                    .input_from(0..100_000u32)?
+                    .map(|d| {
+                        if d == 10 {
+                            let job_id = crate::get_current_worker().job_id;
+                            let cause =
+                                std::io::Error::new(std::io::ErrorKind::TimedOut, "store timed out");
+                            crate::fail_job(job_id, ErrorKind::IOError, true, cause).unwrap();
+                        }
+                        Ok(d)
+                    })?
+                    .sink_into(output)
+            })
+        })
+        .unwrap();
+        // the error that the job is failed with is reported, rather than the cancellation;
+        let err = results.find_map(|r| r.err()).unwrap();
+        assert!(err.to_string().contains("store timed out"), "{}", err);
+    }
+
+    #[test]
+    fn memory_guard_disabled_test() {
+        let mut guard = MemoryGuard::new(0, !0u64);
+        assert!(!guard.check(Some(usize::MAX)).unwrap());
+        assert!(!guard.check_job_memory().unwrap());
+    }
+}
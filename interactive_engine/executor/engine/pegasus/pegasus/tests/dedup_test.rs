@@ -40,3 +40,34 @@ fn map_dedup_flatmap_test() {
     }
     assert_eq!(10_000 * 2, count);
 }
+
+#[test]
+fn dedup_spill_test() {
+    let spill_dir = tempfile::tempdir().unwrap();
+    let mut conf = JobConf::new("dedup_spill_test");
+    conf.set_workers(2);
+    conf.dedup_spill_threshold = 100;
+    conf.spill_dir = Some(spill_dir.path().to_path_buf());
+    let mut res = pegasus::run(conf, || {
+        move |source, sink| {
+            source
+                .input_from(0..20_000u64)?
+                .map(|x| Ok(x % 3_000))?
+                .dedup()?
+                .sink_into(sink)
+        }
+    })
+    .expect("submit job failure");
+
+    let mut results = vec![];
+    while let Some(Ok(x)) = res.next() {
+        results.push(x);
+    }
+    results.sort();
+    assert_eq!(results, (0..3_000u64).collect::<Vec<_>>());
+    // the spilled files are removed on the end of the job
+    let spilled = std::fs::read_dir(spill_dir.path())
+        .unwrap()
+        .count();
+    assert_eq!(spilled, 0);
+}
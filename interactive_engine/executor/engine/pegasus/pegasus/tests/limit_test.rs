@@ -353,6 +353,33 @@ fn multi_scope_sort_limit_test() {
     assert_eq!(results, vec![(4, vec![0, 1, 2]), (3, vec![0, 1, 2]), (2, vec![0, 1])]);
 }
 
+#[test]
+fn sort_limit_by_stable_test() {
+    let mut conf = JobConf::new("sort_limit_by_stable_test");
+    conf.set_workers(1);
+    conf.batch_size = 4;
+
+    let result_stream = pegasus::run(conf, || {
+        move |input, output| {
+            // the keys are 0 to 4, each of which is tied by 20 data, input in the order of their seconds
+            input
+                .input_from((0_u32..100).map(|x| (x % 5, x)))?
+                .sort_limit_by(30, |x, y| x.0.cmp(&y.0))?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure");
+
+    let results: Vec<(u32, u32)> = result_stream.map(|x| x.unwrap()).collect();
+    // all the 20 data of key 0, followed by the first 10 of key 1, as they are input
+    let mut expected: Vec<(u32, u32)> = (0_u32..100)
+        .map(|x| (x % 5, x))
+        .filter(|(k, x)| *k == 0 || (*k == 1 && *x < 50))
+        .collect();
+    expected.sort_by(|x, y| x.0.cmp(&y.0));
+    assert_eq!(results, expected);
+}
+
 #[test]
 fn sort_limit_1_test() {
     let mut conf = JobConf::new("sort_limit_1_test");
@@ -452,3 +479,92 @@ fn modern_graph_sort_limit_1_by_test() {
 
     assert_eq!(results, vec![1.0]);
 }
+
+#[test]
+fn sort_limit_by_more_than_partition_test() {
+    let mut conf = JobConf::new("sort_limit_by_more_than_partition_test");
+    let num_workers = 2;
+    conf.set_workers(num_workers);
+
+    let result_stream = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from((0_u32..6).filter(move |x| *x % num_workers == index))?
+                // each worker has only 3 data, which is less than the limit;
+                .sort_limit_by(5, |x, y| y.cmp(x))?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure");
+
+    let results: Vec<u32> = result_stream.map(|x| x.unwrap()).collect();
+
+    assert_eq!(results, vec![5_u32, 4, 3, 2, 1]);
+}
+
+#[test]
+fn sort_limit_by_duplicated_keys_test() {
+    let mut conf = JobConf::new("sort_limit_by_duplicated_keys_test");
+    let num_workers = 2;
+    conf.set_workers(num_workers);
+
+    let result_stream = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(
+                    (0_u32..100)
+                        .map(|x| (x % 3, x % 5))
+                        .filter(move |x| x.1 % num_workers == index),
+                )?
+                // order by the first key ascending, then by the second key descending;
+                .sort_limit_by(8, |x, y| x.0.cmp(&y.0).then(y.1.cmp(&x.1)))?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure");
+
+    let results: Vec<(u32, u32)> = result_stream.map(|x| x.unwrap()).collect();
+
+    assert_eq!(results, vec![(0, 4), (0, 4), (0, 4), (0, 4), (0, 4), (0, 4), (0, 4), (0, 3)]);
+}
+
+// the source is stopped once enough data are emitted, and the data already emitted are not lost;
+#[test]
+fn source_stop_test() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut conf = JobConf::new("source_stop_test");
+    conf.batch_size = 16;
+    conf.batch_capacity = 1;
+    let emitted = Arc::new(AtomicUsize::new(0));
+    let emitted_in_job = emitted.clone();
+    let mut result = pegasus::run(conf, move || {
+        let emitted = emitted_in_job.clone();
+        move |input, output| {
+            let stop = input.get_stop_hook().clone();
+            let emitted = emitted.clone();
+            input
+                .input_from(0..u32::MAX)?
+                .map(move |d| {
+                    if emitted.fetch_add(1, Ordering::SeqCst) + 1 == 100 {
+                        stop.store(true, Ordering::SeqCst);
+                    }
+                    Ok(d)
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut results = vec![];
+    while let Some(Ok(d)) = result.next() {
+        results.push(d);
+    }
+    results.sort();
+    assert!(results.len() >= 100 && results.len() < 1000, "{} results", results.len());
+    assert_eq!(results.len(), emitted.load(Ordering::SeqCst));
+    assert_eq!(results, (0..results.len() as u32).collect::<Vec<u32>>());
+}
@@ -0,0 +1,64 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+// the memory of jobs is only tracked by the allocator installed with the `mem` feature;
+#![cfg(feature = "mem")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use pegasus::api::{Count, Map, Sink};
+use pegasus::JobConf;
+
+const RECORD_BYTES: usize = 16 * 1024;
+
+/// Each batch of records in flight takes more memory than the limit of the job. The sources are held
+/// back until the records are consumed by the downstream operators, instead of stalling all the workers.
+#[test]
+fn memory_limit_drain_in_flight_test() {
+    let mut conf = JobConf::new("memory_limit_drain_in_flight_test");
+    conf.set_workers(2);
+    conf.batch_size = 64;
+    conf.memory_limit_bytes = 512 * 1024;
+    let peak = Arc::new(AtomicUsize::new(0));
+    let peak_in_job = peak.clone();
+    let mut result = pegasus::run(conf, move || {
+        let peak = peak_in_job.clone();
+        move |input, output| {
+            let peak = peak.clone();
+            input
+                .input_from(0..1024u32)?
+                .map(|i| Ok(vec![i as u8; RECORD_BYTES]))?
+                .repartition(|bytes| Ok(bytes[0] as u64))
+                .map(move |bytes| {
+                    if let Some(used) = pegasus_memory::alloc::check_current_task_memory() {
+                        peak.fetch_max(used, Ordering::SeqCst);
+                    }
+                    Ok(bytes.len() as u64)
+                })?
+                .count()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    while let Some(next) = result.next() {
+        count += next.expect("the job should not be aborted for memory");
+    }
+    assert_eq!(count, 2 * 1024);
+    // the memory is really allocated beyond the limit;
+    assert!(peak.load(Ordering::SeqCst) > 512 * 1024);
+}
@@ -0,0 +1,90 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::time::Duration;
+
+use pegasus::api::{Map, Sink};
+use pegasus::JobConf;
+
+#[test]
+fn merge_custom_metrics_test() {
+    let mut conf = JobConf::new("merge_custom_metrics_test");
+    conf.set_workers(2);
+    let (tx, rx) = crossbeam_channel::unbounded();
+    conf.set_metrics_callback(move |metrics| tx.send(metrics.clone()).unwrap());
+    let results = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(0..10u32)?
+                .map(|x| {
+                    pegasus::metrics::counter("seen")
+                        .expect("metrics registry not found")
+                        .inc();
+                    pegasus::metrics::histogram("value")
+                        .expect("metrics registry not found")
+                        .record(x as f64);
+                    Ok(x)
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("run job fail;");
+
+    assert_eq!(results.map(|r| r.unwrap()).count(), 20);
+    let metrics = rx
+        .recv_timeout(Duration::from_secs(5))
+        .expect("metrics not delivered");
+    assert_eq!(metrics.counters["seen"], 20);
+    let value = metrics.histograms["value"];
+    assert_eq!((value.count, value.min, value.max), (20, 0.0, 9.0));
+    // metrics are not available out of a worker;
+    assert!(pegasus::metrics::counter("seen").is_none());
+}
+
+#[test]
+fn deliver_metrics_on_cancel_test() {
+    let mut conf = JobConf::new("deliver_metrics_on_cancel_test");
+    conf.time_limit = 1000;
+    // check the cancellation in between the small batches;
+    conf.batch_size = 1;
+    conf.set_workers(2);
+    let (tx, rx) = crossbeam_channel::unbounded();
+    conf.set_metrics_callback(move |metrics| tx.send(metrics.clone()).unwrap());
+    let mut results = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(0..100u32)?
+                .map(|x| {
+                    pegasus::metrics::counter("seen")
+                        .expect("metrics registry not found")
+                        .inc();
+                    std::thread::sleep(Duration::from_millis(100));
+                    Ok(x)
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("run job fail;");
+
+    while let Some(Ok(_)) = results.next() {}
+    assert!(results.is_cancel());
+    // the metrics collected before the job is canceled are still delivered;
+    let metrics = rx
+        .recv_timeout(Duration::from_secs(5))
+        .expect("metrics not delivered");
+    assert!(metrics.counters["seen"] > 0);
+    assert!(metrics.counters["seen"] < 200);
+    assert!(rx.try_recv().is_err());
+}
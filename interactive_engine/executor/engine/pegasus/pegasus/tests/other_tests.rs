@@ -1,4 +1,9 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
 use pegasus::api::{Map, Sink};
+use pegasus::result::{ResultSink, ResultStream};
 use pegasus::JobConf;
 
 #[test]
@@ -25,3 +30,73 @@ fn flatmap_x_repartition_x_filtermap_x_broadcast_x_test() {
         println!("{}", n);
     }
 }
+
+#[test]
+fn on_batch_hook_test() {
+    let mut conf = JobConf::new("on_batch_hook_test");
+    conf.batch_size = 4;
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let sink = ResultSink::new(tx);
+    let results = ResultStream::new(conf.job_id, sink.get_cancel_hook().clone(), rx);
+    let batches = Arc::new(Mutex::new(vec![]));
+    let batches_clone = batches.clone();
+    pegasus::run_opt(conf, sink, move |worker| {
+        let batches = batches_clone.clone();
+        worker.on_batch(move |batch: &[u64]| batches.lock().unwrap().push(batch.to_vec()));
+        worker.dataflow(|input, output| input.input_from(0..10_u64)?.sink_into(output))
+    })
+    .expect("run job fail;");
+
+    let results: Vec<u64> = results.map(|r| r.unwrap()).collect();
+    assert_eq!(results, (0..10).collect::<Vec<u64>>());
+    // the hook is invoked once for each batch of at most `batch_size` results
+    let batches = batches.lock().unwrap();
+    assert_eq!(batches.len(), 3);
+    assert!(batches.iter().all(|batch| batch.len() <= 4));
+    assert_eq!(batches.concat(), results);
+}
+
+#[test]
+fn add_keyed_resources_test() {
+    let mut conf = JobConf::new("add_keyed_resources_test");
+    conf.set_workers(2);
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let sink = ResultSink::new(tx);
+    let results = ResultStream::new(conf.job_id, sink.get_cancel_hook().clone(), rx);
+    pegasus::run_opt(conf, sink, |worker| {
+        let mut resources: HashMap<String, Box<dyn Any + Send>> = HashMap::new();
+        resources.insert("offset".to_string(), Box::new(100_u64));
+        resources.insert("name".to_string(), Box::new("pegasus".to_string()));
+        worker.add_keyed_resources(resources)?;
+
+        // colliding with the metrics registry installed by the worker
+        let mut resources: HashMap<String, Box<dyn Any + Send>> = HashMap::new();
+        resources.insert(pegasus::metrics::METRICS_REGISTRY_KEY.to_string(), Box::new(0_u64));
+        resources.insert("other".to_string(), Box::new(0_u64));
+        let err = worker
+            .add_keyed_resources(resources)
+            .unwrap_err();
+        assert!(format!("{}", err).contains(pegasus::metrics::METRICS_REGISTRY_KEY));
+
+        worker.dataflow(|input, output| {
+            input
+                .input_from(0..2_u64)?
+                .map(|x| {
+                    let offset =
+                        pegasus::resource::get_resource_by_key::<u64>("offset").expect("offset not found");
+                    let name =
+                        pegasus::resource::get_resource_by_key::<String>("name").expect("name not found");
+                    assert_eq!(name.as_str(), "pegasus");
+                    // not installed along with the colliding resources
+                    assert!(pegasus::resource::get_resource_by_key::<u64>("other").is_none());
+                    Ok(x + *offset)
+                })?
+                .sink_into(output)
+        })
+    })
+    .expect("run job fail;");
+
+    let mut results: Vec<u64> = results.map(|r| r.unwrap()).collect();
+    results.sort();
+    assert_eq!(results, vec![100, 100, 101, 101]);
+}
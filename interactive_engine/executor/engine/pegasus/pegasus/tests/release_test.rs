@@ -0,0 +1,58 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::time::Duration;
+
+use pegasus::api::{Collect, Map, Sink};
+use pegasus::JobConf;
+
+/// a worker panics in execution, of which the results are waited by its peer and the consumer;
+#[test]
+fn worker_panic_release_test() {
+    let mut conf = JobConf::new("worker_panic_release_test");
+    conf.set_workers(2);
+    let mut results = pegasus::run(conf, || {
+        |input, output| {
+            let worker_id = input.get_worker_index();
+            input
+                .input_from(0..100u32)?
+                .map(move |d| {
+                    if worker_id == 1 {
+                        panic!("worker 1 is killed");
+                    }
+                    Ok(d)
+                })?
+                .collect::<Vec<u32>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure;");
+
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    std::thread::spawn(move || {
+        let mut errors = 0;
+        while let Some(result) = results.next() {
+            if result.is_err() {
+                errors += 1;
+                break;
+            }
+        }
+        tx.send(errors).ok();
+    });
+    let errors = rx
+        .recv_timeout(Duration::from_secs(10))
+        .expect("consumer is blocked");
+    assert_eq!(errors, 1);
+}
@@ -58,3 +58,29 @@ fn modern_graph_sort_by_test() {
 
     assert_eq!(results, vec![1.0, 1.0, 0.5, 0.4, 0.4, 0.2]);
 }
+
+#[test]
+fn modern_graph_sort_unstable_by_test() {
+    use pegasus::api::SortBy;
+
+    let mut conf = JobConf::new("modern_graph_sort_unstable_by_test");
+    let num_workers = 2;
+    conf.set_workers(num_workers);
+
+    let result_stream = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from((1..7).filter(move |x| *x % num_workers == index))?
+                .flat_map(|v| Ok(MAP.get(&v).unwrap().iter().cloned()))?
+                .sort_unstable_by(|x, y| y.1.partial_cmp(&x.1).unwrap())?
+                .map(|x| Ok(x.1))?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure");
+
+    let results: Vec<f32> = result_stream.map(|x| x.unwrap()).collect();
+
+    assert_eq!(results, vec![1.0, 1.0, 0.5, 0.4, 0.4, 0.2]);
+}
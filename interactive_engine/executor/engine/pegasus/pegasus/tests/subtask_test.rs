@@ -13,7 +13,10 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
-use pegasus::api::{Collect, CorrelatedSubTask, Count, Filter, HasAny, Map, Sink};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use pegasus::api::{Chunk, Collect, CorrelatedSubTask, Count, Filter, HasAny, Map, Sink};
 use pegasus::JobConf;
 
 #[test]
@@ -241,6 +244,54 @@ fn apply_collect_empty_stream() {
     assert_eq!(count, 1000);
 }
 
+fn apply_chunk_x_flatmap_count_x(chunk_size: usize, scopes: Arc<AtomicUsize>) -> Vec<(u32, u64)> {
+    let name = format!("apply_chunk_{}_x_flatmap_count_x_test", chunk_size);
+    let mut conf = JobConf::new(name);
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        let scopes = scopes.clone();
+        move |input, output| {
+            input
+                .input_from((index * 500)..(index + 1) * 500)?
+                .chunk(chunk_size)?
+                .apply(|sub| {
+                    sub.flat_map(move |chunk: Vec<u32>| {
+                        scopes.fetch_add(1, Ordering::SeqCst);
+                        Ok(chunk.into_iter())
+                    })?
+                    .repartition(|x| Ok(*x as u64))
+                    .map(|i| Ok((i, (0..i).flat_map(|j| 0..j).count() as u64)))?
+                    .collect::<Vec<(u32, u64)>>()
+                })?
+                .flat_map(|(_, sub)| Ok(sub.into_iter()))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut results = vec![];
+    while let Some(Ok(d)) = result.next() {
+        results.push(d);
+    }
+    results.sort();
+    results
+}
+
+#[test]
+fn apply_chunk_x_flatmap_count_x_test() {
+    let row_scopes = Arc::new(AtomicUsize::new(0));
+    let row_results = apply_chunk_x_flatmap_count_x(1, row_scopes.clone());
+    let chunk_scopes = Arc::new(AtomicUsize::new(0));
+    let chunk_results = apply_chunk_x_flatmap_count_x(100, chunk_scopes.clone());
+
+    assert_eq!(row_results.len(), 1000);
+    assert_eq!(row_results, chunk_results);
+    // each subtask is applied to a chunk of 100 data, instead of each of them
+    assert_eq!(row_scopes.load(Ordering::SeqCst), 1000);
+    assert_eq!(chunk_scopes.load(Ordering::SeqCst), 10);
+}
+
 //#[test]
 // fn apply_x_flatmap_flatmap_any_x_test() {
 //     let mut conf = JobConf::new("apply_x_flatmap_flatmap_any_x_test");
@@ -0,0 +1,91 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{Level, Log, Metadata, Record};
+use pegasus::api::{Map, Sink};
+use pegasus::JobConf;
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Capture the warnings, where the watchdog reports the snapshot of a stuck worker
+struct WarningCapture;
+
+impl Log for WarningCapture {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: WarningCapture = WarningCapture;
+
+/// Return if the job is canceled
+fn run_blocking_job(name: &str, capture_on_timeout: bool) -> bool {
+    let mut conf = JobConf::new(name);
+    conf.time_limit = 300;
+    conf.capture_on_timeout = capture_on_timeout;
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(vec![0u32])?
+                .map(|input| {
+                    // simulate a storage iterator that never returns the control in time
+                    std::thread::sleep(Duration::from_millis(2500));
+                    Ok(input + 1)
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure;");
+    while let Some(Ok(_)) = result.next() {}
+    result.is_cancel()
+}
+
+#[test]
+fn watchdog_capture_on_timeout_test() {
+    log::set_logger(&LOGGER).expect("set logger failure;");
+    log::set_max_level(log::LevelFilter::Warn);
+
+    run_blocking_job("watchdog_disabled", false);
+    assert!(WARNINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|w| !w.contains("snapshot")));
+
+    // the watchdog forces the cancellation
+    assert!(run_blocking_job("watchdog_enabled", true));
+    let warnings = WARNINGS.lock().unwrap();
+    let snapshots: Vec<&String> = warnings
+        .iter()
+        .filter(|w| w.contains("snapshot"))
+        .collect();
+    assert_eq!(snapshots.len(), 1, "{:?}", warnings);
+    // the snapshot tells the operator being fired
+    assert!(snapshots[0].contains("\"map\"))"), "{}", snapshots[0]);
+}
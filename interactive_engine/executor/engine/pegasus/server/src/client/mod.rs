@@ -159,6 +159,7 @@ impl RPCJobClient {
             batch_capacity: config.batch_capacity,
             memory_limit: config.memory_limit,
             trace_enable: config.trace_enable,
+            apply_batch_size: config.apply_batch_size,
             servers: Some(servers),
         };
         let req = JobRequest { conf: Some(conf), source: input, plan, resource };
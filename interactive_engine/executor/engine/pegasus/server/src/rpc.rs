@@ -539,6 +539,10 @@ fn parse_conf_req(mut req: pb::JobConfig) -> JobConf {
         conf.batch_capacity = req.batch_capacity;
     }
 
+    if req.apply_batch_size != 0 {
+        conf.apply_batch_size = req.apply_batch_size;
+    }
+
     if req.trace_enable {
         conf.trace_enable = true;
         conf.plan_print = true;
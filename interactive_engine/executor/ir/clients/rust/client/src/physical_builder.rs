@@ -13,10 +13,12 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 
+use ir_common::error::ParsePbResult;
 use ir_common::generated::algebra as algebra_pb;
 use ir_common::generated::common as common_pb;
 use ir_common::generated::physical as pb;
@@ -31,11 +33,12 @@ const DEFAULT_PLAN_ID: i32 = i32::MAX;
 pub struct PlanBuilder {
     id: i32,
     plan: Vec<pb::PhysicalOpr>,
+    params: HashMap<String, common_pb::Value>,
 }
 
 impl Default for PlanBuilder {
     fn default() -> Self {
-        PlanBuilder { id: DEFAULT_PLAN_ID, plan: vec![] }
+        PlanBuilder { id: DEFAULT_PLAN_ID, plan: vec![], params: HashMap::new() }
     }
 }
 
@@ -55,7 +58,7 @@ impl DerefMut for PlanBuilder {
 
 impl PlanBuilder {
     pub fn new(plan_id: i32) -> Self {
-        PlanBuilder { id: plan_id, plan: vec![] }
+        PlanBuilder { id: plan_id, plan: vec![], params: HashMap::new() }
     }
 
     pub fn add_dummy_source(&mut self) -> &mut Self {
@@ -67,14 +70,8 @@ impl PlanBuilder {
     /// Scan as the source, when the data come from the scan operator.
     /// If the plan is single source, scan would be the root op;
     /// Otherwise, the root is the dummy node, while the real sources are multiple scans.
-    pub fn add_scan_source(&mut self, mut scan: algebra_pb::Scan) -> &mut Self {
-        let meta_data = scan
-            .meta_data
-            .take()
-            .map(|meta| vec![meta.into()])
-            .unwrap_or_default();
-        let op = pb::physical_opr::operator::OpKind::Scan(scan.into());
-        self.plan.push((op, meta_data).into());
+    pub fn add_scan_source(&mut self, scan: algebra_pb::Scan) -> &mut Self {
+        self.plan.push(scan.into());
         self
     }
 
@@ -99,14 +96,7 @@ impl PlanBuilder {
     }
 
     pub fn project(&mut self, project: algebra_pb::Project) -> &mut Self {
-        let meta_data = project
-            .meta_data
-            .clone()
-            .into_iter()
-            .map(|meta| meta.into())
-            .collect();
-        let op = pb::physical_opr::operator::OpKind::Project(project.into());
-        self.plan.push((op, meta_data).into());
+        self.plan.push(project.into());
         self
     }
 
@@ -117,44 +107,31 @@ impl PlanBuilder {
     }
 
     pub fn group(&mut self, group: algebra_pb::GroupBy) -> &mut Self {
-        let meta_data = group
-            .meta_data
-            .clone()
-            .into_iter()
-            .map(|meta| meta.into())
-            .collect();
-        let op = pb::physical_opr::operator::OpKind::GroupBy(group.into());
-        self.plan.push((op, meta_data).into());
+        self.plan.push(group.into());
         self
     }
 
-    pub fn order(&mut self, order: algebra_pb::OrderBy) -> &mut Self {
-        let op = pb::physical_opr::operator::OpKind::OrderBy(order);
-        self.plan.push(op.into());
-        self
+    /// The order, which fails if it is invalid, e.g., with a key missing or an empty limit,
+    /// as validated by its conversion to the physical operator, the same for `dedup()`,
+    /// `limit()` and `sample()`.
+    pub fn order(&mut self, order: algebra_pb::OrderBy) -> ParsePbResult<&mut Self> {
+        self.plan.push(order.try_into()?);
+        Ok(self)
     }
 
-    pub fn dedup(&mut self, dedup: algebra_pb::Dedup) -> &mut Self {
-        let op = pb::physical_opr::operator::OpKind::Dedup(dedup);
-        self.plan.push(op.into());
-        self
+    pub fn dedup(&mut self, dedup: algebra_pb::Dedup) -> ParsePbResult<&mut Self> {
+        self.plan.push(dedup.try_into()?);
+        Ok(self)
     }
 
-    pub fn unfold(&mut self, mut unfold: algebra_pb::Unfold) -> &mut Self {
-        let meta_data = unfold
-            .meta_data
-            .take()
-            .map(|meta| vec![meta.into()])
-            .unwrap_or_default();
-        let op = pb::physical_opr::operator::OpKind::Unfold(unfold.into());
-        self.plan.push((op, meta_data).into());
+    pub fn unfold(&mut self, unfold: algebra_pb::Unfold) -> &mut Self {
+        self.plan.push(unfold.into());
         self
     }
 
-    pub fn limit(&mut self, limit: algebra_pb::Limit) -> &mut Self {
-        let op = pb::physical_opr::operator::OpKind::Limit(limit);
-        self.plan.push(op.into());
-        self
+    pub fn limit(&mut self, limit: algebra_pb::Limit) -> ParsePbResult<&mut Self> {
+        self.plan.push(limit.try_into()?);
+        Ok(self)
     }
 
     pub fn apply(
@@ -165,7 +142,11 @@ impl PlanBuilder {
         let apply = pb::Apply {
             join_kind: unsafe { ::std::mem::transmute(join_kind) },
             keys: vec![],
-            sub_plan: Some(pb::PhysicalPlan { plan: sub_plan.take(), plan_id: DEFAULT_PLAN_ID }),
+            sub_plan: Some(pb::PhysicalPlan {
+                plan: sub_plan.take(),
+                plan_id: DEFAULT_PLAN_ID,
+                params: HashMap::new(),
+            }),
             alias,
         };
         let op = pb::physical_opr::operator::OpKind::Apply(apply);
@@ -193,7 +174,11 @@ impl PlanBuilder {
         let apply = pb::Apply {
             join_kind: unsafe { ::std::mem::transmute(join_kind) },
             keys,
-            sub_plan: Some(pb::PhysicalPlan { plan: sub_plan.take(), plan_id: DEFAULT_PLAN_ID }),
+            sub_plan: Some(pb::PhysicalPlan {
+                plan: sub_plan.take(),
+                plan_id: DEFAULT_PLAN_ID,
+                params: HashMap::new(),
+            }),
             alias,
         };
         let op = pb::physical_opr::operator::OpKind::Apply(apply);
@@ -216,13 +201,37 @@ impl PlanBuilder {
     pub fn join(
         &mut self, join_kind: algebra_pb::join::JoinKind, left_plan: PlanBuilder, right_plan: PlanBuilder,
         left_keys: Vec<common_pb::Variable>, right_keys: Vec<common_pb::Variable>,
+    ) -> &mut Self {
+        self.join_with_null_policy(
+            join_kind,
+            left_plan,
+            right_plan,
+            left_keys,
+            right_keys,
+            pb::join::NullPolicy::NullSafe,
+        )
+    }
+
+    pub fn join_with_null_policy(
+        &mut self, join_kind: algebra_pb::join::JoinKind, left_plan: PlanBuilder, right_plan: PlanBuilder,
+        left_keys: Vec<common_pb::Variable>, right_keys: Vec<common_pb::Variable>,
+        null_policy: pb::join::NullPolicy,
     ) -> &mut Self {
         let join = pb::Join {
             left_keys,
             right_keys,
             join_kind: unsafe { ::std::mem::transmute(join_kind) },
-            left_plan: Some(pb::PhysicalPlan { plan: left_plan.take(), plan_id: DEFAULT_PLAN_ID }),
-            right_plan: Some(pb::PhysicalPlan { plan: right_plan.take(), plan_id: DEFAULT_PLAN_ID }),
+            left_plan: Some(pb::PhysicalPlan {
+                plan: left_plan.take(),
+                plan_id: DEFAULT_PLAN_ID,
+                params: HashMap::new(),
+            }),
+            right_plan: Some(pb::PhysicalPlan {
+                plan: right_plan.take(),
+                plan_id: DEFAULT_PLAN_ID,
+                params: HashMap::new(),
+            }),
+            null_policy: null_policy as i32,
         };
         let op = pb::physical_opr::operator::OpKind::Join(join);
         self.plan.push(op.into());
@@ -247,7 +256,11 @@ impl PlanBuilder {
     pub fn union(&mut self, mut plans: Vec<PlanBuilder>) -> &mut Self {
         let mut sub_plans = vec![];
         for plan in plans.drain(..) {
-            sub_plans.push(pb::PhysicalPlan { plan: plan.take(), plan_id: DEFAULT_PLAN_ID });
+            sub_plans.push(pb::PhysicalPlan {
+                plan: plan.take(),
+                plan_id: DEFAULT_PLAN_ID,
+                params: HashMap::new(),
+            });
         }
         let union = pb::Union { sub_plans };
         let op = pb::physical_opr::operator::OpKind::Union(union);
@@ -259,7 +272,11 @@ impl PlanBuilder {
         let key = key.try_into().unwrap();
         let mut sub_plans = vec![];
         for plan in plans.drain(..) {
-            sub_plans.push(pb::PhysicalPlan { plan: plan.take(), plan_id: DEFAULT_PLAN_ID });
+            sub_plans.push(pb::PhysicalPlan {
+                plan: plan.take(),
+                plan_id: DEFAULT_PLAN_ID,
+                params: HashMap::new(),
+            });
         }
         let intersect = pb::Intersect { sub_plans, key };
         let op = pb::physical_opr::operator::OpKind::Intersect(intersect);
@@ -267,54 +284,25 @@ impl PlanBuilder {
         self
     }
 
-    pub fn get_v(&mut self, mut get_v: algebra_pb::GetV) -> &mut Self {
-        let meta_data = get_v
-            .meta_data
-            .take()
-            .map(|meta| vec![meta.into()])
-            .unwrap_or_default();
-        let op = pb::physical_opr::operator::OpKind::Vertex(get_v.into());
-        self.plan.push((op, meta_data).into());
+    pub fn get_v(&mut self, get_v: algebra_pb::GetV) -> &mut Self {
+        self.plan.push(get_v.into());
         self
     }
 
-    pub fn edge_expand(&mut self, mut edge: algebra_pb::EdgeExpand) -> &mut Self {
-        let meta_data = edge
-            .meta_data
-            .take()
-            .map(|meta| vec![meta.into()])
-            .unwrap_or_default();
-        let op = pb::physical_opr::operator::OpKind::Edge(edge.into());
-        self.plan.push((op, meta_data).into());
+    pub fn edge_expand(&mut self, edge: algebra_pb::EdgeExpand) -> &mut Self {
+        self.plan.push(edge.into());
         self
     }
 
     pub fn path_expand(&mut self, path: algebra_pb::PathExpand) -> &mut Self {
-        let mut edge_expand = None;
-        if let Some(base) = &path.base {
-            if let Some(expd) = &base.edge_expand {
-                edge_expand = Some(expd);
-            }
-        }
-        // Set the Metadata of PathExpand to the Metadata of EdgeExpand
-        let meta_data = if let Some(edge_expand) = edge_expand {
-            edge_expand
-                .meta_data
-                .clone()
-                .map(|meta| vec![meta.into()])
-                .unwrap_or_default()
-        } else {
-            vec![]
-        };
-
-        let op = pb::physical_opr::operator::OpKind::Path(path.into());
-        self.plan.push((op, meta_data).into());
+        // the Metadata of PathExpand is set to the Metadata of its EdgeExpand
+        self.plan.push(path.into());
         self
     }
 
-    pub fn sample(&mut self, sample: algebra_pb::Sample) {
-        let op = pb::physical_opr::operator::OpKind::Sample(sample);
-        self.plan.push(op.into());
+    pub fn sample(&mut self, sample: algebra_pb::Sample) -> ParsePbResult<&mut Self> {
+        self.plan.push(sample.try_into()?);
+        Ok(self)
     }
 
     pub fn sink(&mut self, sink: algebra_pb::Sink) {
@@ -330,8 +318,13 @@ impl PlanBuilder {
         self.plan.last_mut()
     }
 
+    /// Bind the value to the dynamic param of the given name, which is resolved before the plan is executed
+    pub fn bind_param(&mut self, name: &str, value: common_pb::Value) {
+        self.params.insert(name.to_string(), value);
+    }
+
     pub fn build(self) -> pb::PhysicalPlan {
-        pb::PhysicalPlan { plan: self.plan, plan_id: self.id }
+        pb::PhysicalPlan { plan: self.plan, plan_id: self.id, params: self.params }
     }
 }
 
@@ -409,14 +402,15 @@ impl JobBuilder {
         self
     }
 
-    pub fn order(&mut self, order: algebra_pb::OrderBy) -> &mut Self {
-        self.plan.order(order);
-        self
+    /// The same as `PlanBuilder::order()`, which fails if the order is invalid.
+    pub fn order(&mut self, order: algebra_pb::OrderBy) -> ParsePbResult<&mut Self> {
+        self.plan.order(order)?;
+        Ok(self)
     }
 
-    pub fn dedup(&mut self, dedup: algebra_pb::Dedup) -> &mut Self {
-        self.plan.dedup(dedup);
-        self
+    pub fn dedup(&mut self, dedup: algebra_pb::Dedup) -> ParsePbResult<&mut Self> {
+        self.plan.dedup(dedup)?;
+        Ok(self)
     }
 
     pub fn unfold(&mut self, unfold: algebra_pb::Unfold) -> &mut Self {
@@ -424,8 +418,15 @@ impl JobBuilder {
         self
     }
 
-    pub fn limit(&mut self, limit: algebra_pb::Limit) -> &mut Self {
-        self.plan.limit(limit);
+    pub fn limit(&mut self, limit: algebra_pb::Limit) -> ParsePbResult<&mut Self> {
+        self.plan.limit(limit)?;
+        Ok(self)
+    }
+
+    /// Push the physical operator as is, e.g., the one converted from `OrderBy`, `Dedup`, `Limit`
+    /// or `Sample`, which is validated by the conversion.
+    pub fn push(&mut self, opr: pb::PhysicalOpr) -> &mut Self {
+        self.plan.push(opr);
         self
     }
 
@@ -477,6 +478,22 @@ impl JobBuilder {
         self
     }
 
+    pub fn join_with_null_policy(
+        &mut self, join_kind: algebra_pb::join::JoinKind, left_plan: PlanBuilder, right_plan: PlanBuilder,
+        left_keys: Vec<common_pb::Variable>, right_keys: Vec<common_pb::Variable>,
+        null_policy: pb::join::NullPolicy,
+    ) -> &mut Self {
+        self.plan.join_with_null_policy(
+            join_kind,
+            left_plan,
+            right_plan,
+            left_keys,
+            right_keys,
+            null_policy,
+        );
+        self
+    }
+
     pub fn join_func<FL, FR>(
         &mut self, join_kind: algebra_pb::join::JoinKind, left_task: FL, right_task: FR,
         left_keys: Vec<common_pb::Variable>, right_keys: Vec<common_pb::Variable>,
@@ -515,8 +532,9 @@ impl JobBuilder {
         self
     }
 
-    pub fn sample(&mut self, sample: algebra_pb::Sample) {
-        self.plan.sample(sample);
+    pub fn sample(&mut self, sample: algebra_pb::Sample) -> ParsePbResult<&mut Self> {
+        self.plan.sample(sample)?;
+        Ok(self)
     }
 
     pub fn sink(&mut self, sink: algebra_pb::Sink) {
@@ -537,6 +555,7 @@ impl JobBuilder {
             batch_capacity: self.conf.batch_capacity,
             memory_limit: self.conf.memory_limit,
             trace_enable: self.conf.trace_enable,
+            apply_batch_size: self.conf.apply_batch_size,
             servers: match self.conf.servers() {
                 ServerConf::Local => Some(pegasus_pb::job_config::Servers::Local(pegasus_pb::Empty {})),
                 ServerConf::Partial(servers) => {
@@ -574,7 +593,7 @@ mod test {
             is_count_only: false,
             meta_data: None,
         };
-        let sink_pb = algebra_pb::Sink { tags: vec![], sink_target: None };
+        let sink_pb = algebra_pb::Sink { tags: vec![], sink_target: None, tagged_targets: vec![] };
         builder
             .add_scan_source(source_pb.clone())
             .select(algebra_pb::Select { predicate: None })
@@ -600,7 +619,7 @@ mod test {
         };
         let scan2_pb = scan1_pb.clone();
         let project_pb = algebra_pb::Project { mappings: vec![], is_append: false, meta_data: vec![] };
-        let sink_pb = algebra_pb::Sink { tags: vec![], sink_target: None };
+        let sink_pb = algebra_pb::Sink { tags: vec![], sink_target: None, tagged_targets: vec![] };
 
         builder
             .add_dummy_source()
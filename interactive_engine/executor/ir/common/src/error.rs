@@ -32,16 +32,16 @@ impl std::fmt::Display for ParsePbError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ParsePbError::ParseError(e) => {
-                write!(f, "invalid protobuf: {}", e)
+                write!(f, "parse error: {}", e)
             }
             ParsePbError::SerdeError(e) => {
                 write!(f, "serde error: {}", e)
             }
             ParsePbError::EmptyFieldError(e) => {
-                write!(f, "empty protobuf field: {}", e)
+                write!(f, "missing required field: {}", e)
             }
             ParsePbError::Unsupported(e) => {
-                write!(f, "Not supported: {}", e)
+                write!(f, "unsupported feature: {}", e)
             }
         }
     }
@@ -60,3 +60,38 @@ impl From<&str> for ParsePbError {
         desc.to_string().into()
     }
 }
+
+impl From<serde_json::Error> for ParsePbError {
+    fn from(err: serde_json::Error) -> Self {
+        ParsePbError::SerdeError(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_empty_field_error() {
+        let err = ParsePbError::EmptyFieldError("opr".to_string());
+        assert_eq!(err.to_string(), "missing required field: opr");
+    }
+
+    #[test]
+    fn test_display_parse_error() {
+        let err = ParsePbError::ParseError("invalid variable".to_string());
+        assert_eq!(err.to_string(), "parse error: invalid variable");
+    }
+
+    #[test]
+    fn test_display_serde_error() {
+        let err = ParsePbError::SerdeError("EOF while parsing".to_string());
+        assert_eq!(err.to_string(), "serde error: EOF while parsing");
+    }
+
+    #[test]
+    fn test_display_unsupported() {
+        let err = ParsePbError::Unsupported("subtask".to_string());
+        assert_eq!(err.to_string(), "unsupported feature: subtask");
+    }
+}
@@ -22,6 +22,7 @@ use std::convert::{TryFrom, TryInto};
 use crate::expr_parse::error::{ExprError, ExprResult};
 use crate::expr_parse::token::{tokenize, Token};
 use crate::generated::common as pb;
+use crate::utils::parse_variable;
 use crate::VAR_PREFIX;
 
 fn idents_to_vars(idents: Vec<String>) -> ExprResult<pb::VariableKeys> {
@@ -32,8 +33,7 @@ fn idents_to_vars(idents: Vec<String>) -> ExprResult<pb::VariableKeys> {
                 .as_str()
                 .into());
         } else {
-            let var: pb::Variable = ident.into();
-            vars.push(var)
+            vars.push(parse_variable(&ident)?)
         }
     }
 
@@ -84,13 +84,17 @@ impl TryFrom<Token> for pb::ExprOpr {
                         .as_str()
                         .into())
                 } else {
-                    let var: pb::Variable = ident.into();
-                    Ok(var.into())
+                    Ok(parse_variable(&ident)?.into())
                 }
             }
             Token::IdentArray(idents) => Ok((idents_to_vars(idents)?, false).into()),
             Token::IdentMap(idents) => Ok((idents_to_vars(idents)?, true).into()),
             Token::IsNull => Ok(pb::Logical::Isnull.into()),
+            Token::Comma | Token::Function(_) => {
+                Err(format!("invalid token: {:?} out of a function call", token)
+                    .as_str()
+                    .into())
+            }
         }
     }
 }
@@ -112,6 +116,7 @@ impl ExprToken for pb::ExprOpr {
         match self.item {
             Some(pb::expr_opr::Item::Const(_)) => true,
             Some(pb::expr_opr::Item::Var(_)) => true,
+            Some(pb::expr_opr::Item::UdfFunc(_)) => true,
             _ => false,
         }
     }
@@ -224,15 +229,75 @@ pub fn to_suffix_expr<E: ExprToken + std::fmt::Debug>(expr: Vec<E>) -> ExprResul
     Ok(results)
 }
 
-pub fn str_to_expr_pb(expr_str: String) -> ExprResult<pb::Expression> {
+/// Parse the arguments of a function call, i.e., `name(arg1, arg2, ...)`, from the tokens right
+/// after the function name, in which each argument is a sub-expression.
+fn tokens_to_function_call<I: Iterator<Item = Token>>(
+    name: String, tokens: &mut I,
+) -> ExprResult<pb::ExprOpr> {
+    if tokens.next() != Some(Token::LBrace) {
+        return Err(format!("invalid function call of {:?}", name)
+            .as_str()
+            .into());
+    }
+    let mut parameters = vec![];
+    let mut arg = vec![];
+    let mut depth = 0;
+    loop {
+        match tokens.next() {
+            Some(Token::RBrace) if depth == 0 => {
+                if !arg.is_empty() {
+                    parameters.push(tokens_to_expr_pb(std::mem::take(&mut arg))?);
+                } else if !parameters.is_empty() {
+                    return Err(format!("empty argument in function call of {:?}", name)
+                        .as_str()
+                        .into());
+                }
+                break;
+            }
+            Some(Token::Comma) if depth == 0 => {
+                if arg.is_empty() {
+                    return Err(format!("empty argument in function call of {:?}", name)
+                        .as_str()
+                        .into());
+                }
+                parameters.push(tokens_to_expr_pb(std::mem::take(&mut arg))?);
+            }
+            Some(token) => {
+                if token == Token::LBrace {
+                    depth += 1;
+                } else if token == Token::RBrace {
+                    depth -= 1;
+                }
+                arg.push(token);
+            }
+            None => return Err(ExprError::UnmatchedLRBraces),
+        }
+    }
+
+    Ok(pb::ExprOpr {
+        node_type: None,
+        item: Some(pb::expr_opr::Item::UdfFunc(pb::UserDefinedFunction { name, parameters })),
+    })
+}
+
+fn tokens_to_expr_pb(tokens: Vec<Token>) -> ExprResult<pb::Expression> {
     let mut operators = vec![];
-    for token in tokenize(&expr_str)? {
-        operators.push(token.try_into()?);
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        if let Token::Function(name) = token {
+            operators.push(tokens_to_function_call(name, &mut tokens)?);
+        } else {
+            operators.push(token.try_into()?);
+        }
     }
 
     Ok(pb::Expression { operators })
 }
 
+pub fn str_to_expr_pb(expr_str: String) -> ExprResult<pb::Expression> {
+    tokens_to_expr_pb(tokenize(&expr_str)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -303,4 +368,58 @@ mod tests {
         ];
         assert_eq!(to_suffix_expr(case7).unwrap(), expected_case7);
     }
+    #[test]
+    fn test_str_to_function_call() {
+        let call = |name: &str, parameters: Vec<pb::Expression>| pb::ExprOpr {
+            node_type: None,
+            item: Some(pb::expr_opr::Item::UdfFunc(pb::UserDefinedFunction {
+                name: name.to_string(),
+                parameters,
+            })),
+        };
+        let name: pb::ExprOpr = pb::Variable::from("@.name".to_string()).into();
+
+        let case1 = str_to_expr_pb("substring(@.name, 1 + 1, 3)".to_string()).unwrap();
+        let expected_case1 = pb::Expression {
+            operators: vec![call(
+                "substring",
+                vec![
+                    pb::Expression { operators: vec![name.clone()] },
+                    pb::Expression {
+                        operators: vec![
+                            pb::Value::from(1i64).into(),
+                            pb::Arithmetic::Add.into(),
+                            pb::Value::from(1i64).into(),
+                        ],
+                    },
+                    pb::Expression { operators: vec![pb::Value::from(3i64).into()] },
+                ],
+            )],
+        };
+        assert_eq!(case1, expected_case1);
+
+        let case2 = str_to_expr_pb("length(toUpper((@.name))) > 3".to_string()).unwrap();
+        let to_upper = call(
+            "toUpper",
+            vec![pb::Expression {
+                operators: vec![
+                    pb::ExprOpr { node_type: None, item: Some(pb::expr_opr::Item::Brace(0)) },
+                    name.clone(),
+                    pb::ExprOpr { node_type: None, item: Some(pb::expr_opr::Item::Brace(1)) },
+                ],
+            }],
+        );
+        let expected_case2 = pb::Expression {
+            operators: vec![
+                call("length", vec![pb::Expression { operators: vec![to_upper] }]),
+                pb::Logical::Gt.into(),
+                pb::Value::from(3i64).into(),
+            ],
+        };
+        assert_eq!(case2, expected_case2);
+
+        assert!(str_to_expr_pb("concat(@.name, )".to_string()).is_err());
+        assert!(str_to_expr_pb("concat(@.name".to_string()).is_err());
+        assert!(str_to_expr_pb("@.name, 3".to_string()).is_err());
+    }
 }
@@ -50,6 +50,10 @@ pub enum Token {
     // Precedence
     LBrace, // (
     RBrace, // )
+    Comma,  // , e.g., to separate the arguments of a function call
+
+    // Function call
+    Function(String), // the name of a function, e.g., `substring` in `substring(@.name, 0, 3)`
 
     // Values and Variables
     Identifier(String),      // a string-identifier
@@ -160,6 +164,7 @@ fn char_to_partial_token(c: char) -> PartialToken {
         '%' => PartialToken::Token(Token::Percent),
         '(' => PartialToken::Token(Token::LBrace),
         ')' => PartialToken::Token(Token::RBrace),
+        ',' => PartialToken::Token(Token::Comma),
         c => {
             if c.is_whitespace() {
                 PartialToken::Whitespace
@@ -361,6 +366,9 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> ExprResult<Vec<Token
                     Some(Token::EndsWith)
                 } else if literal.to_lowercase().as_str() == "isnull" {
                     Some(Token::IsNull)
+                } else if second == Some(PartialToken::Token(Token::LBrace)) {
+                    // a literal that is directly followed by a left brace, e.g., `toUpper(`
+                    Some(Token::Function(literal))
                 } else {
                     // To parse the float of the form `<coefficient>e{+,-}<exponent>`,
                     // for example [Literal("10e"), Minus, Literal("3")] => "1e-3".parse().
@@ -708,4 +716,33 @@ mod tests {
             ExprError::unsupported("array of various type unsupported".to_string())
         );
     }
+    #[test]
+    fn test_tokenize_function() {
+        let case1 = tokenize("substring(@.name, 0, -3)").unwrap();
+        let expected_case1 = vec![
+            Token::Function("substring".to_string()),
+            Token::LBrace,
+            Token::Identifier("@.name".to_string()),
+            Token::Comma,
+            Token::Int(0),
+            Token::Comma,
+            Token::Int(-3),
+            Token::RBrace,
+        ];
+        assert_eq!(case1, expected_case1);
+
+        let case2 = tokenize("length(toUpper(@a.name)) > 3").unwrap();
+        let expected_case2 = vec![
+            Token::Function("length".to_string()),
+            Token::LBrace,
+            Token::Function("toUpper".to_string()),
+            Token::LBrace,
+            Token::Identifier("@a.name".to_string()),
+            Token::RBrace,
+            Token::RBrace,
+            Token::Gt,
+            Token::Int(3),
+        ];
+        assert_eq!(case2, expected_case2);
+    }
 }
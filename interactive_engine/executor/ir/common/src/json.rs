@@ -0,0 +1,162 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+//! The JSON mapping of the physical plan, for the clients that can hardly produce binary protobuf.
+//!
+//! The mapping follows the serde derivation of the generated pb structures: a message is a JSON
+//! object keyed by its field names in snake case, an enum field is its integer value, and a `oneof`
+//! is a JSON object with a single entry keyed by the variant name in upper camel case, e.g.,
+//! `{"op_kind": {"Scan": {...}}}`.
+
+use serde_json::Value;
+
+use crate::error::ParsePbResult;
+use crate::generated::physical as physical_pb;
+
+/// Encode the physical plan into JSON
+pub fn physical_plan_to_json(plan: &physical_pb::PhysicalPlan) -> Value {
+    // the serialization of a pb structure never fails, as it contains no maps of non-string keys
+    serde_json::to_value(plan).expect("serialize physical plan into json")
+}
+
+/// Decode the physical plan from JSON, where all the fields except the optional ones must be present
+pub fn json_to_physical_plan(json: Value) -> ParsePbResult<physical_pb::PhysicalPlan> {
+    Ok(serde_json::from_value(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::generated::algebra as algebra_pb;
+    use crate::generated::common as common_pb;
+    use crate::generated::physical::physical_opr::operator::OpKind;
+
+    fn to_physical_opr(op_kind: OpKind) -> physical_pb::PhysicalOpr {
+        physical_pb::PhysicalOpr {
+            opr: Some(physical_pb::physical_opr::Operator { op_kind: Some(op_kind) }),
+            meta_data: vec![],
+        }
+    }
+
+    #[test]
+    fn physical_plan_json_roundtrip() {
+        let op_kinds = vec![
+            OpKind::Project(Default::default()),
+            OpKind::Select(Default::default()),
+            OpKind::GroupBy(Default::default()),
+            OpKind::OrderBy(Default::default()),
+            OpKind::Dedup(Default::default()),
+            OpKind::Unfold(Default::default()),
+            OpKind::Limit(Default::default()),
+            OpKind::Scan(Default::default()),
+            OpKind::Sink(Default::default()),
+            OpKind::Apply(Default::default()),
+            OpKind::Join(Default::default()),
+            OpKind::Union(Default::default()),
+            OpKind::Intersect(Default::default()),
+            OpKind::Repartition(Default::default()),
+            OpKind::Root(Default::default()),
+            OpKind::Sample(Default::default()),
+            OpKind::Vertex(Default::default()),
+            OpKind::Edge(Default::default()),
+            OpKind::Path(Default::default()),
+            OpKind::ProcedureCall(Default::default()),
+        ];
+        for op_kind in op_kinds {
+            let plan = physical_pb::PhysicalPlan {
+                plan_id: 1,
+                plan: vec![to_physical_opr(op_kind)],
+                params: Default::default(),
+            };
+            let json = physical_plan_to_json(&plan);
+            // the json can be transferred as text
+            let json: Value = serde_json::from_str(&json.to_string()).unwrap();
+            assert_eq!(json_to_physical_plan(json).unwrap(), plan);
+        }
+    }
+
+    #[test]
+    fn physical_plan_json_stable() {
+        let plan = physical_pb::PhysicalPlan {
+            plan_id: 2,
+            plan: vec![
+                to_physical_opr(OpKind::Scan(physical_pb::Scan {
+                    scan_opt: 0,
+                    alias: Some(1),
+                    params: Some(algebra_pb::QueryParams {
+                        tables: vec![common_pb::NameOrId::from(0)],
+                        ..Default::default()
+                    }),
+                    idx_predicate: None,
+                    is_count_only: false,
+                })),
+                to_physical_opr(OpKind::Limit(algebra_pb::Limit {
+                    range: Some(algebra_pb::Range { lower: 0, upper: 10 }),
+                })),
+            ],
+            params: Default::default(),
+        };
+        let expected = json!({
+            "plan_id": 2,
+            "plan": [
+                {
+                    "opr": {
+                        "op_kind": {
+                            "Scan": {
+                                "scan_opt": 0,
+                                "alias": 1,
+                                "params": {
+                                    "tables": [{ "item": { "Id": 0 } }],
+                                    "columns": [],
+                                    "is_all_columns": false,
+                                    "limit": null,
+                                    "predicate": null,
+                                    "sample_ratio": 0.0,
+                                    "extra": {}
+                                },
+                                "idx_predicate": null,
+                                "is_count_only": false
+                            }
+                        }
+                    },
+                    "meta_data": []
+                },
+                {
+                    "opr": { "op_kind": { "Limit": { "range": { "lower": 0, "upper": 10 } } } },
+                    "meta_data": []
+                }
+            ],
+            "params": {}
+        });
+        assert_eq!(physical_plan_to_json(&plan), expected);
+        assert_eq!(json_to_physical_plan(expected).unwrap(), plan);
+    }
+
+    #[test]
+    fn json_to_physical_plan_error() {
+        // unknown operator
+        let json =
+            json!({ "plan_id": 1, "plan": [{ "opr": { "op_kind": { "Unknown": {} } }, "meta_data": [] }] });
+        assert!(matches!(json_to_physical_plan(json), Err(crate::error::ParsePbError::SerdeError(_))));
+        // mistyped field
+        let json = json!({ "plan_id": "1", "plan": [] });
+        assert!(matches!(json_to_physical_plan(json), Err(crate::error::ParsePbError::SerdeError(_))));
+        // missing field
+        let json = json!({ "plan_id": 1 });
+        assert!(matches!(json_to_physical_plan(json), Err(crate::error::ParsePbError::SerdeError(_))));
+    }
+}
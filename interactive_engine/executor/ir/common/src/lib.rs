@@ -26,6 +26,7 @@ use crate::generated::results as result_pb;
 
 pub mod error;
 pub mod expr_parse;
+pub mod json;
 pub mod utils;
 
 pub use utils::*;
@@ -13,7 +13,7 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::ops::Deref;
@@ -21,7 +21,8 @@ use std::ops::Deref;
 use chrono::Timelike;
 use dyn_type::{DateTimeFormats, Object, Primitives};
 
-use crate::error::ParsePbError;
+use crate::error::{ParsePbError, ParsePbResult};
+use crate::expr_parse::{to_suffix_expr, ExprToken};
 use crate::generated::algebra as pb;
 use crate::generated::common as common_pb;
 use crate::generated::physical as physical_pb;
@@ -178,6 +179,18 @@ impl From<Vec<i64>> for common_pb::Value {
     }
 }
 
+impl From<Vec<u32>> for common_pb::Value {
+    fn from(item: Vec<u32>) -> Self {
+        common_pb::Value { item: Some(common_pb::value::Item::U32Array(common_pb::U32Array { item })) }
+    }
+}
+
+impl From<Vec<u64>> for common_pb::Value {
+    fn from(item: Vec<u64>) -> Self {
+        common_pb::Value { item: Some(common_pb::value::Item::U64Array(common_pb::U64Array { item })) }
+    }
+}
+
 impl From<Vec<f64>> for common_pb::Value {
     fn from(item: Vec<f64>) -> Self {
         common_pb::Value { item: Some(common_pb::value::Item::F64Array(common_pb::DoubleArray { item })) }
@@ -190,6 +203,12 @@ impl From<Vec<String>> for common_pb::Value {
     }
 }
 
+impl From<Vec<common_pb::Value>> for common_pb::Value {
+    fn from(item: Vec<common_pb::Value>) -> Self {
+        common_pb::Value { item: Some(common_pb::value::Item::ValueArray(common_pb::ValueArray { item })) }
+    }
+}
+
 impl From<i32> for common_pb::NameOrId {
     fn from(i: i32) -> Self {
         common_pb::NameOrId { item: Some(common_pb::name_or_id::Item::Id(i)) }
@@ -213,19 +232,56 @@ pub const LABEL_KEY: &'static str = "~label";
 pub const LENGTH_KEY: &'static str = "~len";
 pub const ALL_KEY: &'static str = "~all";
 
+/// Parse a path element key in the form of `~[index]` or `~[index].property`, e.g., `~[-1].name`
+fn str_as_path_elem(str: &str) -> Option<common_pb::PathElementKey> {
+    let rest = str.strip_prefix("~[")?;
+    let (index, rest) = rest.split_once(']')?;
+    let index = index.parse::<i32>().ok()?;
+    let property = if rest.is_empty() {
+        None
+    } else {
+        Some(Box::new(parse_property_key(rest.strip_prefix(SPLITTER)?)))
+    };
+    Some(common_pb::PathElementKey { index, property })
+}
+
+/// Parse a property key, where the magic keys below refer to the special properties of an element,
+/// and any other key refers to the property of that name:
+///
+/// | Key             | Property                                  |
+/// |-----------------|-------------------------------------------|
+/// | `~id`           | the id of a vertex/edge                   |
+/// | `~label`        | the label of a vertex/edge                |
+/// | `~len`          | the length of a path                      |
+/// | `~all`          | all the properties of a vertex/edge       |
+/// | `~[index]`      | the element at `index` of a path          |
+/// | `~[index].prop` | the property of the element of a path     |
+pub fn parse_property_key(s: &str) -> common_pb::Property {
+    let item = if let Some(path_elem) = str_as_path_elem(s) {
+        common_pb::property::Item::PathElem(Box::new(path_elem))
+    } else if s == ID_KEY {
+        common_pb::property::Item::Id(common_pb::IdKey {})
+    } else if s == LABEL_KEY {
+        common_pb::property::Item::Label(common_pb::LabelKey {})
+    } else if s == LENGTH_KEY {
+        common_pb::property::Item::Len(common_pb::LengthKey {})
+    } else if s == ALL_KEY {
+        common_pb::property::Item::All(common_pb::AllKey {})
+    } else {
+        common_pb::property::Item::Key(s.into())
+    };
+    common_pb::Property { item: Some(item) }
+}
+
 impl From<String> for common_pb::Property {
     fn from(str: String) -> Self {
-        if str == ID_KEY {
-            common_pb::Property { item: Some(common_pb::property::Item::Id(common_pb::IdKey {})) }
-        } else if str == LABEL_KEY {
-            common_pb::Property { item: Some(common_pb::property::Item::Label(common_pb::LabelKey {})) }
-        } else if str == LENGTH_KEY {
-            common_pb::Property { item: Some(common_pb::property::Item::Len(common_pb::LengthKey {})) }
-        } else if str == ALL_KEY {
-            common_pb::Property { item: Some(common_pb::property::Item::All(common_pb::AllKey {})) }
-        } else {
-            common_pb::Property { item: Some(common_pb::property::Item::Key(str.into())) }
-        }
+        parse_property_key(&str)
+    }
+}
+
+impl From<&str> for common_pb::Property {
+    fn from(str: &str) -> Self {
+        parse_property_key(str)
     }
 }
 
@@ -237,27 +293,327 @@ fn str_as_tag(str: String) -> Option<common_pb::NameOrId> {
     }
 }
 
-// When translate String to Variable, the type is not considered.
+/// Parse a variable in the form of `@tag`, `@tag.property` or `@.property`, where the type of the
+/// variable is not considered. A tag that can be parsed as an integer is regarded as a tag id.
+pub fn parse_variable(s: &str) -> Result<common_pb::Variable, ParsePbError> {
+    let str = s.strip_prefix(VAR_PREFIX).ok_or_else(|| {
+        ParsePbError::ParseError(format!("variable must start with '{}', got {:?}", VAR_PREFIX, s))
+    })?;
+    if !str.contains(SPLITTER) {
+        Ok(common_pb::Variable {
+            // If the tag is represented as an integer
+            tag: str_as_tag(str.to_string()),
+            property: None,
+            node_type: None,
+        })
+    } else {
+        // the property may further contain the splitter, e.g., `@p.~[-1].name`
+        let mut splitter = str.splitn(2, SPLITTER);
+        let tag: Option<common_pb::NameOrId> =
+            if let Some(first) = splitter.next() { str_as_tag(first.to_string()) } else { None };
+        let property: Option<common_pb::Property> = splitter.next().map(parse_property_key);
+        Ok(common_pb::Variable { tag, property, node_type: None })
+    }
+}
+
+/// Deprecated: panics on a malformed variable, use [`parse_variable`] instead.
+/// Note that rustc rejects `#[deprecated]` on a trait impl (`useless_deprecated` is deny-by-default),
+/// so the deprecation can only be noted here.
 impl From<String> for common_pb::Variable {
     fn from(str: String) -> Self {
-        assert!(str.starts_with(VAR_PREFIX));
-        // skip the var variable
-        let str: String = str.chars().skip(1).collect();
-        if !str.contains(SPLITTER) {
-            common_pb::Variable {
-                // If the tag is represented as an integer
-                tag: str_as_tag(str),
-                property: None,
-                node_type: None,
+        parse_variable(&str).unwrap()
+    }
+}
+
+impl common_pb::Variable {
+    /// The variable referring to a property of the current entry, i.e., `@.prop`.
+    pub fn property<P: Into<common_pb::Property>>(prop: P) -> Self {
+        common_pb::Variable { tag: None, property: Some(prop.into()), node_type: None }
+    }
+
+    /// The variable referring to a property of the tagged entry, i.e., `@tag.prop`.
+    pub fn tagged_property<T: Into<common_pb::NameOrId>, P: Into<common_pb::Property>>(
+        tag: T, prop: P,
+    ) -> Self {
+        common_pb::Variable { tag: Some(tag.into()), property: Some(prop.into()), node_type: None }
+    }
+}
+
+impl common_pb::VariableKeys {
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+fn fmt_name_or_id(name_or_id: &common_pb::NameOrId, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match name_or_id.item.as_ref() {
+        Some(common_pb::name_or_id::Item::Name(name)) => write!(f, "{}", name),
+        Some(common_pb::name_or_id::Item::Id(id)) => write!(f, "{}", id),
+        None => Ok(()),
+    }
+}
+
+fn fmt_property(property: &common_pb::Property, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match property.item.as_ref() {
+        Some(common_pb::property::Item::Id(_)) => write!(f, "{}", ID_KEY),
+        Some(common_pb::property::Item::Label(_)) => write!(f, "{}", LABEL_KEY),
+        Some(common_pb::property::Item::Len(_)) => write!(f, "{}", LENGTH_KEY),
+        Some(common_pb::property::Item::All(_)) => write!(f, "{}", ALL_KEY),
+        Some(common_pb::property::Item::Key(key)) => fmt_name_or_id(key, f),
+        Some(common_pb::property::Item::PathElem(path_elem)) => {
+            write!(f, "~[{}]", path_elem.index)?;
+            if let Some(property) = path_elem.property.as_ref() {
+                write!(f, "{}", SPLITTER)?;
+                fmt_property(property, f)?;
             }
-        } else {
-            let mut splitter = str.split(SPLITTER);
-            let tag: Option<common_pb::NameOrId> =
-                if let Some(first) = splitter.next() { str_as_tag(first.to_string()) } else { None };
-            let property: Option<common_pb::Property> =
-                if let Some(second) = splitter.next() { Some(second.to_string().into()) } else { None };
-            common_pb::Variable { tag, property, node_type: None }
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Display the variable in the form of `@tag.property`, which is the inverse of `From<String>`.
+impl fmt::Display for common_pb::Variable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", VAR_PREFIX)?;
+        if let Some(tag) = self.tag.as_ref() {
+            fmt_name_or_id(tag, f)?;
+        }
+        if let Some(property) = self.property.as_ref() {
+            write!(f, "{}", SPLITTER)?;
+            fmt_property(property, f)?;
+        }
+        Ok(())
+    }
+}
+
+fn fmt_str(s: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn fmt_list<T, F>(items: &[T], f: &mut fmt::Formatter<'_>, mut fmt_item: F) -> fmt::Result
+where
+    F: FnMut(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+{
+    write!(f, "[")?;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
         }
+        fmt_item(item, f)?;
+    }
+    write!(f, "]")
+}
+
+/// Display the value as a literal of the expression, e.g., `18`, `1.5`, `"marko"` or `[1, 2]`.
+impl fmt::Display for common_pb::Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use common_pb::value::Item;
+        match self.item.as_ref() {
+            Some(Item::Boolean(b)) => write!(f, "{}", b),
+            Some(Item::I32(i)) => write!(f, "{}", i),
+            Some(Item::I64(i)) => write!(f, "{}", i),
+            Some(Item::U32(u)) => write!(f, "{}", u),
+            Some(Item::U64(u)) => write!(f, "{}", u),
+            Some(Item::U128(u)) => write!(f, "{}", u128::from(u.clone())),
+            // `Debug` keeps the decimal point of a float, e.g., `1.0` rather than `1`
+            Some(Item::F32(d)) => write!(f, "{:?}", d),
+            Some(Item::F64(d)) => write!(f, "{:?}", d),
+            Some(Item::Str(s)) => fmt_str(s, f),
+            Some(Item::I32Array(array)) => fmt_list(&array.item, f, |i, f| write!(f, "{}", i)),
+            Some(Item::I64Array(array)) => fmt_list(&array.item, f, |i, f| write!(f, "{}", i)),
+            Some(Item::U32Array(array)) => fmt_list(&array.item, f, |u, f| write!(f, "{}", u)),
+            Some(Item::U64Array(array)) => fmt_list(&array.item, f, |u, f| write!(f, "{}", u)),
+            Some(Item::F64Array(array)) => fmt_list(&array.item, f, |d, f| write!(f, "{:?}", d)),
+            Some(Item::StrArray(array)) => fmt_list(&array.item, f, |s, f| fmt_str(s, f)),
+            Some(Item::ValueArray(array)) => fmt_list(&array.item, f, |v, f| write!(f, "{}", v)),
+            Some(Item::None(_)) | None => write!(f, "null"),
+            Some(item) => write!(f, "{:?}", item),
+        }
+    }
+}
+
+impl common_pb::Logical {
+    fn as_str(&self) -> &'static str {
+        match self {
+            common_pb::Logical::Eq => "==",
+            common_pb::Logical::Ne => "!=",
+            common_pb::Logical::Lt => "<",
+            common_pb::Logical::Le => "<=",
+            common_pb::Logical::Gt => ">",
+            common_pb::Logical::Ge => ">=",
+            common_pb::Logical::Within => "within",
+            common_pb::Logical::Without => "without",
+            common_pb::Logical::Startswith => "startsWith",
+            common_pb::Logical::Endswith => "endsWith",
+            common_pb::Logical::And => "&&",
+            common_pb::Logical::Or => "||",
+            common_pb::Logical::Not => "!",
+            common_pb::Logical::Isnull => "isNull",
+            common_pb::Logical::Regex => "regex",
+        }
+    }
+}
+
+impl common_pb::Arithmetic {
+    fn as_str(&self) -> &'static str {
+        match self {
+            common_pb::Arithmetic::Add => "+",
+            common_pb::Arithmetic::Sub => "-",
+            common_pb::Arithmetic::Mul => "*",
+            common_pb::Arithmetic::Div => "/",
+            common_pb::Arithmetic::Mod => "%",
+            common_pb::Arithmetic::Exp => "^^",
+            common_pb::Arithmetic::Bitand => "&",
+            common_pb::Arithmetic::Bitor => "|",
+            common_pb::Arithmetic::Bitxor => "^",
+            common_pb::Arithmetic::Bitlshift => "<<",
+            common_pb::Arithmetic::Bitrshift => ">>",
+        }
+    }
+}
+
+fn operand_to_string(item: &common_pb::expr_opr::Item) -> String {
+    use common_pb::expr_opr::Item;
+    let join = |items: Vec<String>| items.join(", ");
+    match item {
+        Item::Const(value) => value.to_string(),
+        Item::Var(var) => var.to_string(),
+        Item::Vars(vars) => format!(
+            "[{}]",
+            join(
+                vars.keys
+                    .iter()
+                    .map(|var| var.to_string())
+                    .collect()
+            )
+        ),
+        Item::VarMap(vars) => format!(
+            "{{{}}}",
+            join(
+                vars.keys
+                    .iter()
+                    .map(|var| var.to_string())
+                    .collect()
+            )
+        ),
+        Item::Param(param) => format!("${}", param.name),
+        Item::UdfFunc(func) => format!(
+            "{}({})",
+            func.name,
+            join(
+                func.parameters
+                    .iter()
+                    .map(|param| param.to_string())
+                    .collect()
+            )
+        ),
+        _ => format!("{:?}", item),
+    }
+}
+
+fn parenthesize(expr: String, is_parenthesized: bool) -> String {
+    if is_parenthesized {
+        format!("({})", expr)
+    } else {
+        expr
+    }
+}
+
+/// Renders the operators of an expression, which are in the suffix order, into the infix form,
+/// in which a sub-expression is parenthesized only if its operator has a lower precedence than
+/// the operator applied to it, or for readability, if it is the operand of a unary operator, e.g.,
+/// `!(@.age > 18)`. Returns `None` if the operators do not form a valid expression.
+fn suffix_expr_to_infix(operators: Vec<common_pb::ExprOpr>) -> Option<String> {
+    use common_pb::expr_opr::Item;
+    // each entry is a rendered sub-expression, with the precedence of its outermost operator, or
+    // `None` if it is an operand that needs no parentheses
+    let mut stack: Vec<(String, Option<i32>)> = vec![];
+    let is_lower = |operand_precedence: Option<i32>, precedence: i32| matches!(operand_precedence, Some(p) if p < precedence);
+    for opr in operators {
+        let precedence = opr.precedence();
+        match opr.item.as_ref()? {
+            Item::Logical(l) if common_pb::Logical::from_i32(*l)?.is_unary() => {
+                let logical = common_pb::Logical::from_i32(*l)?;
+                let (operand, operand_precedence) = stack.pop()?;
+                let sep = if logical == common_pb::Logical::Not { "" } else { " " };
+                let expr = format!(
+                    "{}{}{}",
+                    logical.as_str(),
+                    sep,
+                    parenthesize(operand, operand_precedence.is_some())
+                );
+                stack.push((expr, Some(precedence)));
+            }
+            Item::Logical(_) | Item::Arith(_) => {
+                let op = match opr.item.as_ref()? {
+                    Item::Logical(l) => common_pb::Logical::from_i32(*l)?.as_str(),
+                    Item::Arith(a) => common_pb::Arithmetic::from_i32(*a)?.as_str(),
+                    _ => unreachable!(),
+                };
+                let (right, right_precedence) = stack.pop()?;
+                let (left, left_precedence) = stack.pop()?;
+                // the binary operators are left-associative, and thus a right operand of the same
+                // precedence is parenthesized
+                let expr = format!(
+                    "{} {} {}",
+                    parenthesize(left, is_lower(left_precedence, precedence)),
+                    op,
+                    parenthesize(right, is_lower(right_precedence, precedence + 1))
+                );
+                stack.push((expr, Some(precedence)));
+            }
+            Item::Extract(extract) => {
+                let interval = common_pb::extract::Interval::from_i32(extract.interval)?;
+                let (operand, _) = stack.pop()?;
+                stack.push((format!("extract({:?} from {})", interval, operand), None));
+            }
+            Item::Brace(_) => return None,
+            item => stack.push((operand_to_string(item), None)),
+        }
+    }
+    if stack.len() == 1 {
+        stack.pop().map(|(expr, _)| expr)
+    } else {
+        None
+    }
+}
+
+/// Display the expression in the infix form, e.g., `@.age > 18 && @.name startsWith "m"`.
+/// An expression that is not well-formed, e.g., with unmatched braces, is displayed as its
+/// operators in the given order.
+impl fmt::Display for common_pb::Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let infix = to_suffix_expr(self.operators.clone())
+            .ok()
+            .and_then(suffix_expr_to_infix);
+        if let Some(infix) = infix {
+            return write!(f, "{}", infix);
+        }
+        for (i, opr) in self.operators.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match opr.item.as_ref() {
+                Some(common_pb::expr_opr::Item::Logical(l)) => match common_pb::Logical::from_i32(*l) {
+                    Some(logical) => write!(f, "{}", logical.as_str())?,
+                    None => write!(f, "{:?}", opr)?,
+                },
+                Some(common_pb::expr_opr::Item::Arith(a)) => match common_pb::Arithmetic::from_i32(*a) {
+                    Some(arith) => write!(f, "{}", arith.as_str())?,
+                    None => write!(f, "{:?}", opr)?,
+                },
+                Some(common_pb::expr_opr::Item::Brace(0)) => write!(f, "(")?,
+                Some(common_pb::expr_opr::Item::Brace(_)) => write!(f, ")")?,
+                Some(item) => write!(f, "{}", operand_to_string(item))?,
+                None => write!(f, "{:?}", opr)?,
+            }
+        }
+        Ok(())
     }
 }
 
@@ -342,6 +698,18 @@ impl From<Vec<String>> for pb::IndexPredicate {
     }
 }
 
+impl From<u128> for common_pb::U128 {
+    fn from(u: u128) -> Self {
+        common_pb::U128 { hi: (u >> 64) as u64, lo: u as u64 }
+    }
+}
+
+impl From<common_pb::U128> for u128 {
+    fn from(u: common_pb::U128) -> Self {
+        ((u.hi as u128) << 64) | (u.lo as u128)
+    }
+}
+
 impl TryFrom<common_pb::Value> for Object {
     type Error = ParsePbError;
 
@@ -354,6 +722,7 @@ impl TryFrom<common_pb::Value> for Object {
                 U32(i) => Ok((*i).into()),
                 I64(i) => Ok((*i).into()),
                 U64(i) => Ok((*i).into()),
+                U128(u) => Ok(u128::from(u.clone()).into()),
                 F32(f) => Ok((*f).into()),
                 F64(f) => Ok((*f).into()),
                 Str(s) => Ok(s.clone().into()),
@@ -361,8 +730,18 @@ impl TryFrom<common_pb::Value> for Object {
                 None(_) => Ok(Object::None),
                 I32Array(v) => Ok(v.item.clone().into()),
                 I64Array(v) => Ok(v.item.clone().into()),
+                U32Array(v) => Ok(v.item.clone().into()),
+                U64Array(v) => Ok(v.item.clone().into()),
                 F64Array(v) => Ok(v.item.clone().into()),
                 StrArray(v) => Ok(v.item.clone().into()),
+                ValueArray(v) => {
+                    let vec = v
+                        .item
+                        .iter()
+                        .map(|val| Object::try_from(val.clone()))
+                        .collect::<Result<Vec<Object>, _>>()?;
+                    Ok(vec.into())
+                }
                 PairArray(pairs) => {
                     let mut vec = Vec::<(Object, Object)>::with_capacity(pairs.item.len());
                     for item in pairs.item.clone().into_iter() {
@@ -388,12 +767,177 @@ impl TryFrom<common_pb::Value> for Object {
     }
 }
 
+impl common_pb::Expression {
+    /// Substitute the dynamic params among the operators with the values bound to their names in `params`,
+    /// e.g., `@.~id without $ids` with `ids` bound to the ids given by a subquery, so that the params are
+    /// evaluated as constants.
+    pub fn resolve_params(
+        &mut self, params: &HashMap<String, common_pb::Value>,
+    ) -> Result<(), ParsePbError> {
+        for opr in self.operators.iter_mut() {
+            if let Some(common_pb::expr_opr::Item::Param(param)) = opr.item.as_ref() {
+                let value = params.get(&param.name).ok_or_else(|| {
+                    ParsePbError::ParseError(format!("dynamic param {:?} is not bound", param.name))
+                })?;
+                opr.item = Some(common_pb::expr_opr::Item::Const(value.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl pb::IndexPredicate {
+    /// Substitute the dynamic params in the triplets with the values bound to their names in `params`,
+    /// so that a parameterized predicate can be further converted, e.g., into the global ids to scan.
+    pub fn resolve_params(
+        &mut self, params: &HashMap<String, common_pb::Value>,
+    ) -> Result<(), ParsePbError> {
+        for and_predicate in self.or_predicates.iter_mut() {
+            for predicate in and_predicate.predicates.iter_mut() {
+                if let Some(pb::index_predicate::triplet::Value::Param(param)) = predicate.value.as_ref() {
+                    let value = params.get(&param.name).ok_or_else(|| {
+                        ParsePbError::ParseError(format!("dynamic param {:?} is not bound", param.name))
+                    })?;
+                    predicate.value = Some(pb::index_predicate::triplet::Value::Const(value.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the predicate of `lo <= key < hi`, i.e., `P.between(lo, hi)` in Gremlin, as a single
+    /// `AndPredicate` that consists of a `Ge` triplet and a `Lt` triplet on the key.
+    pub fn between(
+        key: common_pb::Property, lo: common_pb::Value, hi: common_pb::Value,
+    ) -> pb::IndexPredicate {
+        let triplet = |cmp: common_pb::Logical, value: common_pb::Value| pb::index_predicate::Triplet {
+            key: Some(key.clone()),
+            value: Some(value.into()),
+            cmp: cmp as i32,
+        };
+        pb::IndexPredicate {
+            or_predicates: vec![pb::index_predicate::AndPredicate {
+                predicates: vec![triplet(common_pb::Logical::Ge, lo), triplet(common_pb::Logical::Lt, hi)],
+            }],
+        }
+    }
+
+    /// Whether each `AndPredicate` of the predicate is a range, see `AndPredicate::is_range()`.
+    pub fn is_range(&self) -> bool {
+        !self.or_predicates.is_empty()
+            && self
+                .or_predicates
+                .iter()
+                .all(|and_predicate| and_predicate.is_range())
+    }
+}
+
+impl pb::index_predicate::AndPredicate {
+    /// Whether all the triplets compare their keys by `Gt`, `Ge`, `Lt` or `Le`, e.g., a `between` predicate.
+    /// Such a range predicate does not refer to any primary key value to look up.
+    pub fn is_range(&self) -> bool {
+        !self.predicates.is_empty()
+            && self.predicates.iter().all(|triplet| {
+                matches!(
+                    common_pb::Logical::from_i32(triplet.cmp),
+                    Some(
+                        common_pb::Logical::Gt
+                            | common_pb::Logical::Ge
+                            | common_pb::Logical::Lt
+                            | common_pb::Logical::Le
+                    )
+                )
+            })
+    }
+
+    /// The number of triplets in the conjunction.
+    pub fn len(&self) -> usize {
+        self.predicates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+}
+
+/// Transform the `IndexPredicate` into the equivalent expression, e.g., to evaluate a range predicate
+/// that cannot be looked up by the index as a filter, where each `AndPredicate` is braced and joined by `||`.
+impl TryFrom<pb::IndexPredicate> for common_pb::Expression {
+    type Error = ParsePbError;
+
+    fn try_from(value: pb::IndexPredicate) -> Result<Self, Self::Error> {
+        let brace = |brace: common_pb::expr_opr::Brace| common_pb::ExprOpr {
+            node_type: None,
+            item: Some(common_pb::expr_opr::Item::Brace(brace as i32)),
+        };
+        let mut operators = vec![];
+        for and_predicate in value.or_predicates {
+            if !operators.is_empty() {
+                operators.push(common_pb::Logical::Or.into());
+            }
+            operators.push(brace(common_pb::expr_opr::Brace::LeftBrace));
+            for (idx, triplet) in and_predicate.predicates.into_iter().enumerate() {
+                if idx > 0 {
+                    operators.push(common_pb::Logical::And.into());
+                }
+                let cmp = common_pb::Logical::from_i32(triplet.cmp).ok_or_else(|| {
+                    ParsePbError::ParseError(format!("invalid indexed predicate cmp {:?}", triplet.cmp))
+                })?;
+                let key = triplet.key.ok_or_else(|| {
+                    ParsePbError::EmptyFieldError("key is empty in indexed predicate".to_string())
+                })?;
+                let value = match triplet.value {
+                    Some(pb::index_predicate::triplet::Value::Const(value)) => value,
+                    Some(pb::index_predicate::triplet::Value::Param(param)) => {
+                        Err(ParsePbError::Unsupported(format!(
+                            "unresolved dynamic param {:?} in indexed predicate",
+                            param.name
+                        )))?
+                    }
+                    None => Err(ParsePbError::EmptyFieldError(
+                        "value is empty in indexed predicate".to_string(),
+                    ))?,
+                };
+                operators
+                    .push(common_pb::Variable { tag: None, property: Some(key), node_type: None }.into());
+                operators.push(cmp.into());
+                operators.push(value.into());
+            }
+            operators.push(brace(common_pb::expr_opr::Brace::RightBrace));
+        }
+        if operators.is_empty() {
+            Err(ParsePbError::EmptyFieldError("empty indexed predicate".to_string()))
+        } else {
+            Ok(common_pb::Expression { operators })
+        }
+    }
+}
+
 impl TryFrom<pb::IndexPredicate> for Vec<i64> {
     type Error = ParsePbError;
 
     fn try_from(value: pb::IndexPredicate) -> Result<Self, Self::Error> {
         let mut global_ids = vec![];
         for and_predicate in value.or_predicates {
+            // an id lookup expects only a single id predicate, while the primary keys of
+            // multiple columns, which are not looked up by id, can be conjunctive
+            let is_id_lookup = and_predicate
+                .predicates
+                .iter()
+                .any(|predicate| {
+                    matches!(
+                        predicate
+                            .key
+                            .as_ref()
+                            .and_then(|key| key.item.as_ref()),
+                        Some(common_pb::property::Item::Id(_))
+                    )
+                });
+            if is_id_lookup && and_predicate.len() > 1 {
+                Err(ParsePbError::ParseError(
+                    "expected single-predicate AndPredicate for ID lookup".to_string(),
+                ))?
+            }
             let predicate = and_predicate
                 .predicates
                 .get(0)
@@ -426,8 +970,8 @@ impl TryFrom<pb::IndexPredicate> for Vec<i64> {
                             "indexed value other than integer (I32, I64) and integer array".to_string(),
                         ))?,
                     },
-                    pb::index_predicate::triplet::Value::Param(_) => Err(ParsePbError::Unsupported(
-                        "indexed value other than integer (I32, I64) and integer array".to_string(),
+                    pb::index_predicate::triplet::Value::Param(param) => Err(ParsePbError::Unsupported(
+                        format!("unresolved dynamic param {:?} in indexed value", param.name),
                     ))?,
                 }
             }
@@ -447,8 +991,13 @@ impl TryFrom<pb::IndexPredicate> for Vec<Vec<(NameOrId, Object)>> {
         // a IndexPredicate can be: name within ["marko", "josh"], which is a single AndCondition, but with "OR" semantics,
         // then the result should be: [[("name", "marko")], [("name", "josh")]].
         // But if the IndexPredicate mix up with "within" and other conditions, unexpected result will be returned.
+        // Besides, a range, e.g., age >= 20 && age < 30 built by `between()`, refers to no primary key values,
+        // and is skipped here, which is left to be evaluated as a filter instead.
         let mut primary_key_values = Vec::with_capacity(value.or_predicates.len());
         for and_predicates in value.or_predicates {
+            if and_predicates.is_range() {
+                continue;
+            }
             // PkValue can be one-column or multi-columns, which is a set of and_conditions.
             let mut primary_key_value = Vec::with_capacity(and_predicates.predicates.len());
             for predicate in &and_predicates.predicates {
@@ -485,6 +1034,16 @@ impl TryFrom<pb::IndexPredicate> for Vec<Vec<(NameOrId, Object)>> {
                                         primary_key_values.push(vec![(key.clone(), (*v).into())]);
                                     }
                                 }
+                                common_pb::value::Item::U32Array(array) => {
+                                    for v in array.item.iter() {
+                                        primary_key_values.push(vec![(key.clone(), (*v).into())]);
+                                    }
+                                }
+                                common_pb::value::Item::U64Array(array) => {
+                                    for v in array.item.iter() {
+                                        primary_key_values.push(vec![(key.clone(), (*v).into())]);
+                                    }
+                                }
                                 common_pb::value::Item::F64Array(array) => {
                                     for v in array.item.iter() {
                                         primary_key_values.push(vec![(key.clone(), (*v).into())]);
@@ -667,15 +1226,17 @@ impl From<pb::Sample> for pb::logical_plan::Operator {
     }
 }
 
-impl From<Object> for common_pb::Value {
-    fn from(value: Object) -> Self {
+impl TryFrom<Object> for common_pb::Value {
+    type Error = ParsePbError;
+
+    fn try_from(value: Object) -> Result<Self, Self::Error> {
         let item = match value {
             Object::Primitive(v) => match v {
                 // TODO: It seems that Byte is only used for bool for now
                 Primitives::Byte(v) => common_pb::value::Item::Boolean(!(v == 0)),
                 Primitives::Integer(v) => common_pb::value::Item::I32(v),
                 Primitives::Long(v) => common_pb::value::Item::I64(v),
-                Primitives::ULLong(v) => common_pb::value::Item::Str(v.to_string()),
+                Primitives::ULLong(v) => common_pb::value::Item::U128(v.into()),
                 Primitives::Float(v) => common_pb::value::Item::F32(v),
                 Primitives::UInteger(v) => common_pb::value::Item::U32(v),
                 Primitives::ULong(v) => common_pb::value::Item::U64(v),
@@ -683,51 +1244,95 @@ impl From<Object> for common_pb::Value {
             },
             Object::String(s) => common_pb::value::Item::Str(s),
             Object::Blob(b) => common_pb::value::Item::Blob(b.to_vec()),
-            Object::Vector(v) => common_pb::value::Item::StrArray(common_pb::StringArray {
-                item: v
-                    .into_iter()
-                    .map(|obj| obj.to_string())
-                    .collect(),
-            }),
+            Object::Vector(v) => {
+                if v.iter().any(is_nested_object) {
+                    // nested collections, e.g., a list of lists, are encoded recursively
+                    common_pb::value::Item::ValueArray(vec_to_value_array(v)?)
+                } else {
+                    common_pb::value::Item::StrArray(common_pb::StringArray {
+                        item: v
+                            .into_iter()
+                            .map(|obj| obj.to_string())
+                            .collect(),
+                    })
+                }
+            }
             Object::KV(kv) => {
                 let mut pairs: Vec<common_pb::Pair> = Vec::with_capacity(kv.len());
                 for (key, val) in kv {
-                    let key_pb: common_pb::Value = key.into();
-                    let val_pb: common_pb::Value = val.into();
+                    let key_pb = common_pb::Value::try_from(key)?;
+                    let val_pb = common_pb::Value::try_from(val)?;
                     pairs.push(common_pb::Pair { key: Some(key_pb), val: Some(val_pb) })
                 }
                 common_pb::value::Item::PairArray(common_pb::PairArray { item: pairs })
             }
             Object::None => common_pb::value::Item::None(common_pb::None {}),
-            Object::DateFormat(datetime_formats) => match datetime_formats {
-                DateTimeFormats::Date(date) => common_pb::value::Item::Date(common_pb::Date32 {
-                    // convert to days since from 1970-01-01
-                    item: (date
-                        .and_hms_opt(0, 0, 0)
-                        .unwrap() // can safely unwrap since it is valid hour/min/sec
-                        .timestamp()
-                        / 86400) as i32,
-                }),
-                DateTimeFormats::Time(time) => common_pb::value::Item::Time(common_pb::Time32 {
-                    // convert to milliseconds past midnight
-                    item: (time.hour() as i32 * 3600 + time.minute() as i32 * 60 + time.second() as i32)
-                        * 1000
-                        + time.nanosecond() as i32 / 1000_000,
-                }),
-                DateTimeFormats::DateTime(dt) => {
-                    common_pb::value::Item::Timestamp(common_pb::Timestamp { item: dt.timestamp_millis() })
-                }
-                DateTimeFormats::DateTimeWithTz(dt) => {
-                    common_pb::value::Item::Timestamp(common_pb::Timestamp { item: dt.timestamp_millis() })
-                }
-            },
-            _ => unimplemented!(),
+            Object::DateFormat(datetime_formats) => return common_pb::Value::try_from(datetime_formats),
+            other => Err(ParsePbError::Unsupported(format!("object {:?} to pb value", other)))?,
         };
 
-        common_pb::Value { item: Some(item) }
+        Ok(common_pb::Value { item: Some(item) })
     }
 }
 
+impl TryFrom<DateTimeFormats> for common_pb::Value {
+    type Error = ParsePbError;
+
+    fn try_from(datetime_formats: DateTimeFormats) -> Result<Self, Self::Error> {
+        let item = match datetime_formats {
+            DateTimeFormats::Date(date) => common_pb::value::Item::Date(common_pb::Date32 {
+                // convert to days since from 1970-01-01
+                item: (date
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap() // can safely unwrap since it is valid hour/min/sec
+                    .timestamp()
+                    / 86400) as i32,
+            }),
+            DateTimeFormats::Time(time) => {
+                // convert to milliseconds past midnight, where the nanoseconds may exceed one second for a leap second
+                let secs = time.num_seconds_from_midnight() as i32;
+                let millis = i32::try_from(time.nanosecond() / 1000_000)
+                    .ok()
+                    .and_then(|millis| secs.checked_mul(1000)?.checked_add(millis))
+                    .ok_or_else(|| {
+                        ParsePbError::ParseError(format!("time {:?} overflows `Time32`", time))
+                    })?;
+                common_pb::value::Item::Time(common_pb::Time32 { item: millis })
+            }
+            DateTimeFormats::DateTime(dt) => {
+                common_pb::value::Item::Timestamp(common_pb::Timestamp { item: dt.timestamp_millis() })
+            }
+            DateTimeFormats::DateTimeWithTz(dt) => {
+                common_pb::value::Item::Timestamp(common_pb::Timestamp { item: dt.timestamp_millis() })
+            }
+        };
+        Ok(common_pb::Value { item: Some(item) })
+    }
+}
+
+#[inline]
+fn is_nested_object(obj: &Object) -> bool {
+    match obj {
+        Object::Vector(_) | Object::KV(_) => true,
+        _ => false,
+    }
+}
+
+// Within a `ValueArray`, the inner vectors are always encoded as `ValueArray`s as well,
+// so that the types of their elements are preserved.
+fn vec_to_value_array(vec: Vec<Object>) -> ParsePbResult<common_pb::ValueArray> {
+    let item = vec
+        .into_iter()
+        .map(|obj| match obj {
+            Object::Vector(inner) => Ok(common_pb::Value {
+                item: Some(common_pb::value::Item::ValueArray(vec_to_value_array(inner)?)),
+            }),
+            _ => common_pb::Value::try_from(obj),
+        })
+        .collect::<ParsePbResult<Vec<_>>>()?;
+    Ok(common_pb::ValueArray { item })
+}
+
 impl From<pb::EdgeExpand> for pb::path_expand::ExpandBase {
     fn from(opr: pb::EdgeExpand) -> Self {
         pb::path_expand::ExpandBase { edge_expand: Some(opr), get_v: None }
@@ -774,6 +1379,120 @@ impl pb::QueryParams {
     }
 }
 
+impl pb::Scan {
+    /// Whether the scan looks up the vertices (or edges) by an index, rather than scanning all of them
+    pub fn is_index_scan(&self) -> bool {
+        self.idx_predicate.is_some()
+    }
+
+    /// Whether the scan only counts the vertices (or edges), without returning them
+    pub fn is_count_only_scan(&self) -> bool {
+        self.is_count_only
+    }
+}
+
+impl pb::OrderBy {
+    /// Whether the order is total, i.e., the records are ordered by the pairs alone, regardless of the
+    /// sort algorithm and of their input order. It is the case if no pair is ordered randomly, and some
+    /// pair orders by the ids of the elements, e.g., `order().by('name').by(id)`, which leaves only the
+    /// records of the same elements tied. The physical plan carries the `OrderBy` as is.
+    pub fn is_stable(&self) -> bool {
+        use pb::order_by::ordering_pair::Order;
+        let is_by_id = |pair: &pb::order_by::OrderingPair| {
+            pair.key
+                .as_ref()
+                .and_then(|key| key.property.as_ref())
+                .map_or(false, |property| matches!(property.item, Some(common_pb::property::Item::Id(_))))
+        };
+        self.pairs
+            .iter()
+            .all(|pair| pair.order != Order::Shuffle as i32)
+            && self.pairs.iter().any(is_by_id)
+    }
+
+    /// Whether the records tied on all the pairs must be kept in their relative order, which requires
+    /// a stable sort. It is not the case if the order is total or random, or if `unstable` is opted in.
+    pub fn requires_stable_sort(&self) -> bool {
+        use pb::order_by::ordering_pair::Order;
+        !self.unstable
+            && !self.is_stable()
+            && self
+                .pairs
+                .iter()
+                .all(|pair| pair.order != Order::Shuffle as i32)
+    }
+}
+
+impl pb::order_by::OrderingPair {
+    /// Whether the records with a null key are placed before the others, where `NullOrder::Default`
+    /// resolves to nulls last in ascending order and nulls first in descending order, as in SQL.
+    pub fn is_nulls_first(&self) -> Result<bool, ParsePbError> {
+        use pb::order_by::ordering_pair::{NullOrder, Order};
+        match NullOrder::from_i32(self.null_order) {
+            Some(NullOrder::NullsFirst) => Ok(true),
+            Some(NullOrder::NullsLast) => Ok(false),
+            Some(NullOrder::Default) => Ok(self.order == Order::Desc as i32),
+            None => Err(ParsePbError::ParseError(format!(
+                "invalid `OrderingPair::null_order`: {}",
+                self.null_order
+            ))),
+        }
+    }
+}
+
+impl pb::GetV {
+    /// Check that `opt` is a known `VOpt`. Note that `Itself`, which is only defined in the physical
+    /// `GetV`, is also accepted, as the planner uses it for the `GetV` to filter on the vertices.
+    pub fn validate(&self) -> Result<(), ParsePbError> {
+        if physical_pb::get_v::VOpt::from_i32(self.opt).is_none() {
+            return Err(ParsePbError::ParseError(format!("invalid `GetV::opt`: {}", self.opt)));
+        }
+        Ok(())
+    }
+
+    /// Whether to get the vertex from an edge or a path, rather than the vertex itself.
+    pub fn is_adjacent(&self) -> bool {
+        self.opt != physical_pb::get_v::VOpt::Itself as i32
+    }
+}
+
+impl pb::Pattern {
+    /// Check that every vertex tag referred by the binders of the sentences is declared in the
+    /// pattern, i.e., as the start or end tag of a sentence, or the alias of a `GetV` binder.
+    pub fn validate(&self) -> Result<(), ParsePbError> {
+        use pb::pattern::binder::Item;
+
+        let mut declared = vec![];
+        for sentence in &self.sentences {
+            declared.extend(sentence.start.iter().chain(sentence.end.iter()));
+            for binder in &sentence.binders {
+                if let Some(Item::Vertex(getv)) = binder.item.as_ref() {
+                    declared.extend(getv.alias.iter());
+                }
+            }
+        }
+        for sentence in &self.sentences {
+            for binder in &sentence.binders {
+                let tag = match binder.item.as_ref() {
+                    Some(Item::Edge(edge)) => edge.v_tag.as_ref(),
+                    Some(Item::Path(path)) => path.start_tag.as_ref(),
+                    Some(Item::Vertex(getv)) => getv.tag.as_ref(),
+                    _ => None,
+                };
+                if let Some(tag) = tag {
+                    if !declared.contains(&tag) {
+                        return Err(ParsePbError::ParseError(format!(
+                            "undeclared vertex alias {:?} in `Pattern`",
+                            tag
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl pb::edge_expand::Direction {
     pub fn reverse(&self) -> pb::edge_expand::Direction {
         match self {
@@ -784,6 +1503,21 @@ impl pb::edge_expand::Direction {
     }
 }
 
+impl pb::EdgeExpand {
+    /// Split an expansion of `Both` direction into an `Out` and an `In` expansion,
+    /// with all other fields preserved. Return `None` if the direction is not `Both`.
+    pub fn split_both(self) -> Option<(pb::EdgeExpand, pb::EdgeExpand)> {
+        if self.direction != pb::edge_expand::Direction::Both as i32 {
+            return None;
+        }
+        let mut out_expand = self.clone();
+        out_expand.direction = pb::edge_expand::Direction::Out as i32;
+        let mut in_expand = self;
+        in_expand.direction = pb::edge_expand::Direction::In as i32;
+        Some((out_expand, in_expand))
+    }
+}
+
 impl From<physical_pb::physical_opr::operator::OpKind> for physical_pb::PhysicalOpr {
     fn from(op_kind: physical_pb::physical_opr::operator::OpKind) -> Self {
         let opr = physical_pb::physical_opr::Operator { op_kind: Some(op_kind) };
@@ -812,6 +1546,80 @@ impl From<pb::MetaData> for physical_pb::physical_opr::MetaData {
     }
 }
 
+impl From<(physical_pb::physical_opr::operator::OpKind, Vec<pb::MetaData>)> for physical_pb::PhysicalOpr {
+    fn from(op_kind_with_meta: (physical_pb::physical_opr::operator::OpKind, Vec<pb::MetaData>)) -> Self {
+        let (op_kind, meta_data) = op_kind_with_meta;
+        let meta_data: Vec<physical_pb::physical_opr::MetaData> = meta_data
+            .into_iter()
+            .map(|meta| meta.into())
+            .collect();
+        (op_kind, meta_data).into()
+    }
+}
+
+impl From<pb::Project> for physical_pb::PhysicalOpr {
+    fn from(mut project: pb::Project) -> Self {
+        let meta_data = std::mem::take(&mut project.meta_data);
+        let op_kind = physical_pb::physical_opr::operator::OpKind::Project(project.into());
+        (op_kind, meta_data).into()
+    }
+}
+
+impl From<pb::GroupBy> for physical_pb::PhysicalOpr {
+    fn from(mut group: pb::GroupBy) -> Self {
+        let meta_data = std::mem::take(&mut group.meta_data);
+        let op_kind = physical_pb::physical_opr::operator::OpKind::GroupBy(group.into());
+        (op_kind, meta_data).into()
+    }
+}
+
+impl From<pb::Unfold> for physical_pb::PhysicalOpr {
+    fn from(mut unfold: pb::Unfold) -> Self {
+        let meta_data: Vec<pb::MetaData> = unfold.meta_data.take().into_iter().collect();
+        let op_kind = physical_pb::physical_opr::operator::OpKind::Unfold(unfold.into());
+        (op_kind, meta_data).into()
+    }
+}
+
+impl From<pb::Scan> for physical_pb::PhysicalOpr {
+    fn from(mut scan: pb::Scan) -> Self {
+        let meta_data: Vec<pb::MetaData> = scan.meta_data.take().into_iter().collect();
+        let op_kind = physical_pb::physical_opr::operator::OpKind::Scan(scan.into());
+        (op_kind, meta_data).into()
+    }
+}
+
+impl From<pb::GetV> for physical_pb::PhysicalOpr {
+    fn from(mut getv: pb::GetV) -> Self {
+        let meta_data: Vec<pb::MetaData> = getv.meta_data.take().into_iter().collect();
+        let op_kind = physical_pb::physical_opr::operator::OpKind::Vertex(getv.into());
+        (op_kind, meta_data).into()
+    }
+}
+
+impl From<pb::EdgeExpand> for physical_pb::PhysicalOpr {
+    fn from(mut expand: pb::EdgeExpand) -> Self {
+        let meta_data: Vec<pb::MetaData> = expand.meta_data.take().into_iter().collect();
+        let op_kind = physical_pb::physical_opr::operator::OpKind::Edge(expand.into());
+        (op_kind, meta_data).into()
+    }
+}
+
+/// The `PathExpand` takes the meta data of its base `EdgeExpand`.
+impl From<pb::PathExpand> for physical_pb::PhysicalOpr {
+    fn from(path: pb::PathExpand) -> Self {
+        let meta_data: Vec<pb::MetaData> = path
+            .base
+            .as_ref()
+            .and_then(|base| base.edge_expand.as_ref())
+            .and_then(|expand| expand.meta_data.clone())
+            .into_iter()
+            .collect();
+        let op_kind = physical_pb::physical_opr::operator::OpKind::Path(path.into());
+        (op_kind, meta_data).into()
+    }
+}
+
 impl From<physical_pb::Repartition> for physical_pb::PhysicalOpr {
     fn from(repartition: physical_pb::Repartition) -> Self {
         let op_kind = physical_pb::physical_opr::operator::OpKind::Repartition(repartition);
@@ -840,7 +1648,17 @@ impl From<physical_pb::Scan> for physical_pb::PhysicalOpr {
     }
 }
 
-impl From<physical_pb::PathExpand> for physical_pb::PhysicalOpr {
+impl physical_pb::Scan {
+    pub fn is_index_scan(&self) -> bool {
+        self.idx_predicate.is_some()
+    }
+
+    pub fn is_count_only_scan(&self) -> bool {
+        self.is_count_only
+    }
+}
+
+impl From<physical_pb::PathExpand> for physical_pb::PhysicalOpr {
     fn from(path: physical_pb::PathExpand) -> Self {
         let op_kind = physical_pb::physical_opr::operator::OpKind::Path(path);
         op_kind.into()
@@ -900,6 +1718,7 @@ impl From<pb::Unfold> for physical_pb::Unfold {
         physical_pb::Unfold {
             tag: unfold.tag.map(|tag| tag.try_into().unwrap()),
             alias: unfold.alias.map(|tag| tag.try_into().unwrap()),
+            keep_empty: unfold.keep_empty,
         }
     }
 }
@@ -947,6 +1766,7 @@ impl From<pb::PathExpand> for physical_pb::PathExpand {
             result_opt: path.result_opt,
             condition: path.condition,
             is_optional: path.is_optional,
+            is_condition_emit_only: path.is_condition_emit_only,
         }
     }
 }
@@ -963,19 +1783,112 @@ impl From<pb::Scan> for physical_pb::Scan {
     }
 }
 
+fn to_physical_sink_tags(tags: Vec<common_pb::NameOrIdKey>) -> Vec<physical_pb::sink::OptTag> {
+    tags.into_iter()
+        .map(|tag| physical_pb::sink::OptTag { tag: tag.key.map(|tag| tag.try_into().unwrap()) })
+        .collect()
+}
+
 impl From<pb::Sink> for physical_pb::Sink {
     fn from(sink: pb::Sink) -> Self {
         physical_pb::Sink {
-            tags: sink
-                .tags
+            tags: to_physical_sink_tags(sink.tags),
+            sink_target: sink.sink_target,
+            tagged_targets: sink
+                .tagged_targets
                 .into_iter()
-                .map(|tag| physical_pb::sink::OptTag { tag: tag.key.map(|tag| tag.try_into().unwrap()) })
+                .map(|target| physical_pb::sink::TaggedSinkTarget {
+                    tags: to_physical_sink_tags(target.tags),
+                    sink_target: target.sink_target,
+                })
                 .collect(),
-            sink_target: sink.sink_target,
         }
     }
 }
 
+/// A valid range must be non-empty and non-negative, i.e., `0 <= lower < upper`.
+fn check_range(range: &pb::Range, opr: &str) -> Result<(), ParsePbError> {
+    if range.lower < 0 || range.upper <= range.lower {
+        Err(ParsePbError::ParseError(format!(
+            "invalid range [{}, {}) in {}",
+            range.lower, range.upper, opr
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+impl TryFrom<pb::OrderBy> for physical_pb::PhysicalOpr {
+    type Error = ParsePbError;
+
+    fn try_from(order: pb::OrderBy) -> Result<Self, Self::Error> {
+        if order
+            .pairs
+            .iter()
+            .any(|pair| pair.key.is_none())
+        {
+            Err(ParsePbError::EmptyFieldError("OrderBy::pairs::key".to_string()))?
+        }
+        for pair in &order.pairs {
+            pair.is_nulls_first()?;
+        }
+        if let Some(range) = order.limit.as_ref() {
+            check_range(range, "OrderBy::limit")?;
+        }
+        Ok(physical_pb::physical_opr::operator::OpKind::OrderBy(order).into())
+    }
+}
+
+impl TryFrom<pb::Dedup> for physical_pb::PhysicalOpr {
+    type Error = ParsePbError;
+
+    /// Dedup with empty keys is allowed, as the keys may be appended after the operator is created.
+    fn try_from(dedup: pb::Dedup) -> Result<Self, Self::Error> {
+        Ok(physical_pb::physical_opr::operator::OpKind::Dedup(dedup).into())
+    }
+}
+
+impl TryFrom<pb::Limit> for physical_pb::PhysicalOpr {
+    type Error = ParsePbError;
+
+    fn try_from(limit: pb::Limit) -> Result<Self, Self::Error> {
+        let range = limit
+            .range
+            .as_ref()
+            .ok_or_else(|| ParsePbError::EmptyFieldError("Limit::range".to_string()))?;
+        check_range(range, "Limit")?;
+        Ok(physical_pb::physical_opr::operator::OpKind::Limit(limit).into())
+    }
+}
+
+impl TryFrom<pb::Sample> for physical_pb::PhysicalOpr {
+    type Error = ParsePbError;
+
+    fn try_from(sample: pb::Sample) -> Result<Self, Self::Error> {
+        if let Some(sample_type) = sample.sample_type.as_ref() {
+            let inner = sample_type
+                .inner
+                .as_ref()
+                .ok_or_else(|| ParsePbError::EmptyFieldError("Sample::sample_type".to_string()))?;
+            match inner {
+                pb::sample::sample_type::Inner::SampleByNum(num) => {
+                    if num.num <= 0 {
+                        Err(ParsePbError::ParseError("SampleByNum num should be > 0".to_string()))?
+                    }
+                }
+                pb::sample::sample_type::Inner::SampleByRatio(ratio) => {
+                    if !(0.0..=1.0).contains(&ratio.ratio) {
+                        Err(ParsePbError::ParseError(
+                            "SampleByRatio ratio should be in [0, 1]".to_string(),
+                        ))?
+                    }
+                }
+            }
+        }
+        Ok(physical_pb::physical_opr::operator::OpKind::Sample(sample).into())
+    }
+}
+
 impl TryFrom<&physical_pb::PhysicalOpr> for physical_pb::physical_opr::operator::OpKind {
     type Error = ParsePbError;
 
@@ -997,6 +1910,201 @@ impl TryFrom<physical_pb::PhysicalOpr> for physical_pb::physical_opr::operator::
     }
 }
 
+/// The aliases that an operator defines, and whether the aliases defined before are discarded,
+/// as the operator, e.g., a `GroupBy`, outputs the records of new aliases only.
+pub fn aliases_of(opr: &pb::logical_plan::operator::Opr) -> (Vec<&common_pb::NameOrId>, bool) {
+    use pb::logical_plan::operator::Opr;
+    match opr {
+        Opr::Project(project) => (
+            project
+                .mappings
+                .iter()
+                .filter_map(|mapping| mapping.alias.as_ref())
+                .collect(),
+            !project.is_append,
+        ),
+        Opr::GroupBy(group) => (
+            group
+                .mappings
+                .iter()
+                .filter_map(|mapping| mapping.alias.as_ref())
+                .chain(
+                    group
+                        .functions
+                        .iter()
+                        .filter_map(|func| func.alias.as_ref()),
+                )
+                .collect(),
+            true,
+        ),
+        Opr::Unfold(unfold) => (unfold.alias.iter().collect(), false),
+        Opr::Apply(apply) => (apply.alias.iter().collect(), false),
+        Opr::Scan(scan) => (scan.alias.iter().collect(), false),
+        Opr::As(as_opr) => (as_opr.alias.iter().collect(), false),
+        Opr::Vertex(getv) => (getv.alias.iter().collect(), false),
+        Opr::Edge(expand) => (expand.alias.iter().collect(), false),
+        Opr::Path(path) => (path.alias.iter().collect(), false),
+        _ => (vec![], false),
+    }
+}
+
+/// Validate that no operator of the plan reuses an alias that is already defined upstream,
+/// which would otherwise silently overwrite the data tagged by the alias.
+///
+/// The operators in different branches, e.g., the sub-plans of a `Union` or an `Intersect`,
+/// may define the same alias, as they tag different records.
+pub fn validate_aliases(plan: &pb::LogicalPlan) -> Result<(), ParsePbError> {
+    let num_nodes = plan.nodes.len();
+    let mut in_degrees = vec![0; num_nodes];
+    for node in &plan.nodes {
+        for child in &node.children {
+            let child = *child as usize;
+            if child >= num_nodes {
+                return Err(ParsePbError::ParseError(format!("child node {} not found", child)));
+            }
+            in_degrees[child] += 1;
+        }
+    }
+    // the aliases defined upstream of each node, visited in a topological order
+    let mut defined: Vec<Vec<NameOrId>> = vec![vec![]; num_nodes];
+    let mut queue: Vec<usize> = (0..num_nodes)
+        .filter(|idx| in_degrees[*idx] == 0)
+        .collect();
+    let mut visited = 0;
+    while let Some(idx) = queue.pop() {
+        visited += 1;
+        let mut aliases = std::mem::take(&mut defined[idx]);
+        if let Some(opr) = plan.nodes[idx]
+            .opr
+            .as_ref()
+            .and_then(|opr| opr.opr.as_ref())
+        {
+            let (new_aliases, discard) = aliases_of(opr);
+            if discard {
+                aliases.clear();
+            }
+            for alias in new_aliases {
+                let alias = NameOrId::try_from(alias.clone())?;
+                if aliases.contains(&alias) {
+                    return Err(ParsePbError::ParseError(format!(
+                        "alias {:?} of node {} is already defined",
+                        alias, idx
+                    )));
+                }
+                aliases.push(alias);
+            }
+        }
+        for child in &plan.nodes[idx].children {
+            let child = *child as usize;
+            for alias in &aliases {
+                if !defined[child].contains(alias) {
+                    defined[child].push(alias.clone());
+                }
+            }
+            in_degrees[child] -= 1;
+            if in_degrees[child] == 0 {
+                queue.push(child);
+            }
+        }
+    }
+    if visited < num_nodes {
+        return Err(ParsePbError::ParseError("the logical plan has a cycle".to_string()));
+    }
+    Ok(())
+}
+
+/// A difference between two logical plans, as found by `pb::LogicalPlan::diff()`, where the nodes are
+/// identified by their array indices in the plans, and an edge is from a node to one of its children.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlanDiff {
+    AddedNode {
+        index: usize,
+        op: Option<pb::logical_plan::Operator>,
+    },
+    RemovedNode {
+        index: usize,
+        op: Option<pb::logical_plan::Operator>,
+    },
+    ModifiedNode {
+        index: usize,
+        old_op: Option<pb::logical_plan::Operator>,
+        new_op: Option<pb::logical_plan::Operator>,
+    },
+    AddedEdge {
+        from: usize,
+        to: usize,
+    },
+    RemovedEdge {
+        from: usize,
+        to: usize,
+    },
+    AddedRoot {
+        index: usize,
+    },
+    RemovedRoot {
+        index: usize,
+    },
+}
+
+impl pb::LogicalPlan {
+    /// The differences from this plan to `other`, e.g., to detect the accidental plan changes in the
+    /// regression tests, as `assert!(expected.diff(&actual).is_empty())`. The operators are compared
+    /// structurally, node by node of the same index, while the order of the children (and of the roots)
+    /// does not matter.
+    pub fn diff(&self, other: &pb::LogicalPlan) -> Vec<PlanDiff> {
+        let children_of = |node: &pb::logical_plan::Node| -> BTreeSet<usize> {
+            node.children
+                .iter()
+                .map(|child| *child as usize)
+                .collect()
+        };
+        let roots_of = |plan: &pb::LogicalPlan| -> BTreeSet<usize> {
+            plan.roots
+                .iter()
+                .map(|root| *root as usize)
+                .collect()
+        };
+        let mut diffs = vec![];
+        let (old_roots, new_roots) = (roots_of(self), roots_of(other));
+        for &index in old_roots.difference(&new_roots) {
+            diffs.push(PlanDiff::RemovedRoot { index });
+        }
+        for &index in new_roots.difference(&old_roots) {
+            diffs.push(PlanDiff::AddedRoot { index });
+        }
+        for index in 0..self.nodes.len().max(other.nodes.len()) {
+            let (old_children, new_children) = match (self.nodes.get(index), other.nodes.get(index)) {
+                (Some(old), Some(new)) => {
+                    if old.opr != new.opr {
+                        diffs.push(PlanDiff::ModifiedNode {
+                            index,
+                            old_op: old.opr.clone(),
+                            new_op: new.opr.clone(),
+                        });
+                    }
+                    (children_of(old), children_of(new))
+                }
+                (Some(old), None) => {
+                    diffs.push(PlanDiff::RemovedNode { index, op: old.opr.clone() });
+                    (children_of(old), BTreeSet::new())
+                }
+                (None, Some(new)) => {
+                    diffs.push(PlanDiff::AddedNode { index, op: new.opr.clone() });
+                    (BTreeSet::new(), children_of(new))
+                }
+                (None, None) => unreachable!(),
+            };
+            for &to in old_children.difference(&new_children) {
+                diffs.push(PlanDiff::RemovedEdge { from: index, to });
+            }
+            for &to in new_children.difference(&old_children) {
+                diffs.push(PlanDiff::AddedEdge { from: index, to });
+            }
+        }
+        diffs
+    }
+}
+
 impl common_pb::Logical {
     pub fn is_unary(&self) -> bool {
         match self {
@@ -1025,6 +2133,54 @@ impl common_pb::Logical {
     }
 }
 
+impl common_pb::Value {
+    /// Coerce the value into the `target` type, which only supports the widening conversions
+    /// that never lose information, i.e., from a narrower integer into a wider integer, and from
+    /// a float into a double; additionally, an integer can be coerced into a double, as the usual
+    /// numeric promotion, which is exact for integers of no more than 53 bits.
+    /// A null value, or a target type of `DT_ANY`, leaves the value as it is.
+    pub fn coerce_to(&self, target: common_pb::DataType) -> Result<common_pb::Value, ParsePbError> {
+        use common_pb::value::Item;
+        use common_pb::PrimitiveType;
+
+        let target_type = match target.item {
+            Some(common_pb::data_type::Item::PrimitiveType(t)) => PrimitiveType::from_i32(t)
+                .ok_or_else(|| ParsePbError::ParseError(format!("invalid primitive type {:?}", t)))?,
+            _ => {
+                return Err(ParsePbError::Unsupported(format!(
+                    "coerce value {:?} to non-primitive type {:?}",
+                    self, target
+                )))
+            }
+        };
+        let item = match (self.item.as_ref(), target_type) {
+            (None, _) | (Some(Item::None(_)), _) | (_, PrimitiveType::DtAny) => return Ok(self.clone()),
+            (Some(Item::Boolean(b)), PrimitiveType::DtBool) => Item::Boolean(*b),
+            (Some(Item::I32(i)), PrimitiveType::DtSignedInt32) => Item::I32(*i),
+            (Some(Item::I32(i)), PrimitiveType::DtSignedInt64) => Item::I64(*i as i64),
+            (Some(Item::I32(i)), PrimitiveType::DtDouble) => Item::F64(*i as f64),
+            (Some(Item::U32(u)), PrimitiveType::DtUnsignedInt32) => Item::U32(*u),
+            (Some(Item::U32(u)), PrimitiveType::DtSignedInt64) => Item::I64(*u as i64),
+            (Some(Item::U32(u)), PrimitiveType::DtUnsignedInt64) => Item::U64(*u as u64),
+            (Some(Item::U32(u)), PrimitiveType::DtDouble) => Item::F64(*u as f64),
+            (Some(Item::I64(i)), PrimitiveType::DtSignedInt64) => Item::I64(*i),
+            (Some(Item::I64(i)), PrimitiveType::DtDouble) => Item::F64(*i as f64),
+            (Some(Item::U64(u)), PrimitiveType::DtUnsignedInt64) => Item::U64(*u),
+            (Some(Item::U64(u)), PrimitiveType::DtDouble) => Item::F64(*u as f64),
+            (Some(Item::F32(f)), PrimitiveType::DtFloat) => Item::F32(*f),
+            (Some(Item::F32(f)), PrimitiveType::DtDouble) => Item::F64(*f as f64),
+            (Some(Item::F64(f)), PrimitiveType::DtDouble) => Item::F64(*f),
+            _ => {
+                return Err(ParsePbError::Unsupported(format!(
+                    "lossy coercion of value {:?} to type {:?}",
+                    self, target_type
+                )))
+            }
+        };
+        Ok(common_pb::Value { item: Some(item) })
+    }
+}
+
 impl physical_pb::PhysicalOpr {
     pub fn is_repartition(&self) -> bool {
         match self {
@@ -1038,11 +2194,114 @@ impl physical_pb::PhysicalOpr {
             _ => false,
         }
     }
+
+    /// Duplicate the operator, including its `meta_data`, e.g., to reuse a plan fragment in
+    /// another branch of the plan. The duplicate shares nothing with the original.
+    pub fn duplicate_opr(&self) -> physical_pb::PhysicalOpr {
+        self.clone()
+    }
+}
+
+impl physical_pb::PhysicalPlan {
+    /// Substitute the dynamic params in the predicates, projections and index predicates of the
+    /// operators, including those of the nested plans, with the values bound to them in `params`
+    /// of the plan, e.g., the ids given by a prior subquery to `@.~id without $ids`.
+    pub fn resolve_params(&mut self) -> Result<(), ParsePbError> {
+        if self.params.is_empty() {
+            return Ok(());
+        }
+        let params = self.params.clone();
+        resolve_plan_params(&mut self.plan, &params)
+    }
+}
+
+fn resolve_plan_params(
+    plan: &mut [physical_pb::PhysicalOpr], params: &HashMap<String, common_pb::Value>,
+) -> Result<(), ParsePbError> {
+    use physical_pb::physical_opr::operator::OpKind;
+
+    let resolve_expr = |expr: Option<&mut common_pb::Expression>| -> Result<(), ParsePbError> {
+        if let Some(expr) = expr {
+            expr.resolve_params(params)?;
+        }
+        Ok(())
+    };
+    let resolve_query_params = |query_params: Option<&mut pb::QueryParams>| {
+        resolve_expr(query_params.and_then(|query_params| query_params.predicate.as_mut()))
+    };
+    for opr in plan.iter_mut() {
+        let op_kind = match opr
+            .opr
+            .as_mut()
+            .and_then(|opr| opr.op_kind.as_mut())
+        {
+            Some(op_kind) => op_kind,
+            None => continue,
+        };
+        match op_kind {
+            OpKind::Select(select) => resolve_expr(select.predicate.as_mut())?,
+            OpKind::Project(project) => {
+                for mapping in project.mappings.iter_mut() {
+                    resolve_expr(mapping.expr.as_mut())?;
+                }
+            }
+            OpKind::Scan(scan) => {
+                resolve_query_params(scan.params.as_mut())?;
+                if let Some(idx_predicate) = scan.idx_predicate.as_mut() {
+                    idx_predicate.resolve_params(params)?;
+                }
+            }
+            OpKind::Vertex(get_v) => resolve_query_params(get_v.params.as_mut())?,
+            OpKind::Edge(edge) => resolve_query_params(edge.params.as_mut())?,
+            OpKind::Path(path) => {
+                if let Some(base) = path.base.as_mut() {
+                    resolve_query_params(
+                        base.edge_expand
+                            .as_mut()
+                            .and_then(|edge| edge.params.as_mut()),
+                    )?;
+                    resolve_query_params(
+                        base.get_v
+                            .as_mut()
+                            .and_then(|get_v| get_v.params.as_mut()),
+                    )?;
+                }
+                resolve_expr(path.condition.as_mut())?;
+            }
+            OpKind::Apply(apply) => {
+                if let Some(sub_plan) = apply.sub_plan.as_mut() {
+                    resolve_plan_params(&mut sub_plan.plan, params)?;
+                }
+            }
+            OpKind::Join(join) => {
+                for sub_plan in join
+                    .left_plan
+                    .iter_mut()
+                    .chain(join.right_plan.iter_mut())
+                {
+                    resolve_plan_params(&mut sub_plan.plan, params)?;
+                }
+            }
+            OpKind::Union(union) => {
+                for sub_plan in union.sub_plans.iter_mut() {
+                    resolve_plan_params(&mut sub_plan.plan, params)?;
+                }
+            }
+            OpKind::Intersect(intersect) => {
+                for sub_plan in intersect.sub_plans.iter_mut() {
+                    resolve_plan_params(&mut sub_plan.plan, params)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::expr_parse::str_to_expr_pb;
 
     #[test]
     fn test_str_to_variable() {
@@ -1119,5 +2378,1068 @@ mod test {
             common_pb::Variable { tag: None, property: None, node_type: None },
             common_pb::Variable::from(case7.to_string())
         );
+
+        let case8 = "@p.~[-1].name";
+        assert_eq!(
+            common_pb::Variable {
+                tag: Some(common_pb::NameOrId::from("p".to_string())),
+                property: Some(common_pb::Property {
+                    item: Some(common_pb::property::Item::PathElem(Box::new(common_pb::PathElementKey {
+                        index: -1,
+                        property: Some(Box::new("name".to_string().into())),
+                    })))
+                }),
+                node_type: None
+            },
+            common_pb::Variable::from(case8.to_string())
+        );
+    }
+
+    #[test]
+    fn test_variable_to_str() {
+        let cases = vec![
+            "@1",
+            "@a",
+            "@1.~id",
+            "@1.~label",
+            "@1.~len",
+            "@1.~all",
+            "@1.name",
+            "@a.name",
+            "@.name",
+            "@.~id",
+            "@",
+            "@p.~[0]",
+            "@p.~[-1].name",
+            "@p.~[1].~id",
+        ];
+        for case in cases {
+            let var = common_pb::Variable::from(case.to_string());
+            assert_eq!(var.to_string(), case);
+            assert_eq!(common_pb::Variable::from(var.to_string()), var);
+        }
+    }
+
+    #[test]
+    fn test_variable_property_constructors() {
+        let var = common_pb::Variable::property("name");
+        assert_eq!(var, common_pb::Variable::from("@.name".to_string()));
+        assert_eq!(var.to_string(), "@.name");
+
+        let var = common_pb::Variable::tagged_property(1, "age");
+        assert_eq!(var, common_pb::Variable::from("@1.age".to_string()));
+        assert_eq!(var.to_string(), "@1.age");
+
+        let var = common_pb::Variable::tagged_property("a", "~id");
+        assert_eq!(var, common_pb::Variable::from("@a.~id".to_string()));
+
+        let expr = common_pb::Expression {
+            operators: vec![
+                common_pb::Variable::tagged_property(1, "age").into(),
+                common_pb::Logical::Gt.into(),
+                common_pb::Value::from(27i64).into(),
+            ],
+        };
+        assert_eq!(expr, str_to_expr_pb("@1.age > 27".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_parse_property_key() {
+        assert_eq!(
+            parse_property_key("~id"),
+            common_pb::Property { item: Some(common_pb::property::Item::Id(common_pb::IdKey {})) }
+        );
+        assert_eq!(
+            parse_property_key("~label"),
+            common_pb::Property { item: Some(common_pb::property::Item::Label(common_pb::LabelKey {})) }
+        );
+        assert_eq!(
+            parse_property_key("~len"),
+            common_pb::Property { item: Some(common_pb::property::Item::Len(common_pb::LengthKey {})) }
+        );
+        assert_eq!(
+            parse_property_key("~all"),
+            common_pb::Property { item: Some(common_pb::property::Item::All(common_pb::AllKey {})) }
+        );
+        assert_eq!(
+            parse_property_key("name"),
+            common_pb::Property { item: Some(common_pb::property::Item::Key("name".into())) }
+        );
+        // the `From` impls delegate to `parse_property_key`
+        assert_eq!(common_pb::Property::from("~id".to_string()), parse_property_key("~id"));
+        assert_eq!(common_pb::Property::from("name"), parse_property_key("name"));
+    }
+
+    #[test]
+    fn test_validate_pattern() {
+        let sentence = |start: &str, v_tag: Option<&str>, end: &str| pb::pattern::Sentence {
+            start: Some(start.into()),
+            binders: vec![pb::pattern::Binder {
+                item: Some(pb::pattern::binder::Item::Edge(pb::EdgeExpand {
+                    v_tag: v_tag.map(|tag| tag.into()),
+                    direction: 0,
+                    params: None,
+                    alias: None,
+                    expand_opt: 0,
+                    meta_data: None,
+                    is_optional: false,
+                })),
+            }],
+            end: Some(end.into()),
+            join_kind: 0,
+        };
+
+        let pattern = pb::Pattern {
+            sentences: vec![sentence("a", None, "b"), sentence("b", Some("a"), "c")],
+            meta_data: vec![],
+        };
+        assert!(pattern.validate().is_ok());
+
+        let pattern = pb::Pattern {
+            sentences: vec![sentence("a", None, "b"), sentence("b", Some("d"), "c")],
+            meta_data: vec![],
+        };
+        match pattern.validate() {
+            Err(ParsePbError::ParseError(msg)) => assert!(msg.contains("\"d\"")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_variable_keys_len() {
+        let vars = common_pb::VariableKeys { keys: vec![] };
+        assert_eq!(vars.len(), 0);
+        assert!(vars.is_empty());
+
+        let vars = common_pb::VariableKeys {
+            keys: vec![common_pb::Variable::property("name"), common_pb::Variable::property("age")],
+        };
+        assert_eq!(vars.len(), 2);
+        assert!(!vars.is_empty());
+    }
+
+    #[test]
+    fn test_parse_variable() {
+        assert_eq!(
+            parse_variable("@a").unwrap(),
+            common_pb::Variable { tag: Some("a".into()), property: None, node_type: None }
+        );
+        assert_eq!(
+            parse_variable("@1.name").unwrap(),
+            common_pb::Variable {
+                tag: Some(1.into()),
+                property: Some(parse_property_key("name")),
+                node_type: None
+            }
+        );
+        assert_eq!(
+            parse_variable("@.~id").unwrap(),
+            common_pb::Variable { tag: None, property: Some(parse_property_key("~id")), node_type: None }
+        );
+        assert_eq!(
+            parse_variable("a.name"),
+            Err(ParsePbError::ParseError("variable must start with '@', got \"a.name\"".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_nested_vector_to_value() {
+        // [[1, 2], [3]]
+        let nested: Object = vec![Object::from(vec![1, 2]), Object::from(vec![3])].into();
+        let value_pb = common_pb::Value::try_from(nested.clone()).unwrap();
+        assert_eq!(
+            value_pb,
+            common_pb::Value::from(vec![
+                common_pb::Value::from(vec![common_pb::Value::from(1), common_pb::Value::from(2)]),
+                common_pb::Value::from(vec![common_pb::Value::from(3)]),
+            ])
+        );
+        assert_eq!(Object::try_from(value_pb).unwrap(), nested);
+
+        // [{"name": "marko", "age": 29}, {"name": "vadas"}]
+        let maps: Object = vec![
+            Object::from(vec![(Object::from("name"), Object::from("marko")), ("age".into(), 29.into())]),
+            Object::from(vec![(Object::from("name"), Object::from("vadas"))]),
+        ]
+        .into();
+        let value_pb = common_pb::Value::try_from(maps.clone()).unwrap();
+        match value_pb.item.as_ref() {
+            Some(common_pb::value::Item::ValueArray(array)) => {
+                assert_eq!(array.item.len(), 2);
+                for val in &array.item {
+                    assert!(matches!(val.item, Some(common_pb::value::Item::PairArray(_))));
+                }
+            }
+            _ => panic!("a vector of maps should be encoded as `ValueArray`"),
+        }
+        assert_eq!(Object::try_from(value_pb).unwrap(), maps);
+
+        // vectors of primitives are encoded as before
+        let flat: Object = vec![1, 2].into();
+        assert_eq!(
+            common_pb::Value::try_from(flat).unwrap(),
+            common_pb::Value::from(vec!["1".to_string(), "2".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_unsigned_array_to_object() {
+        let array = vec![0, i64::MAX as u64 + 1, u64::MAX];
+        let value_pb = common_pb::Value::from(array.clone());
+        let decoded = Object::try_from(value_pb)
+            .unwrap()
+            .take_vector()
+            .unwrap()
+            .into_iter()
+            .map(|obj| obj.as_u64().unwrap())
+            .collect::<Vec<u64>>();
+        assert_eq!(decoded, array);
+
+        let array = vec![0, i32::MAX as u32 + 1, u32::MAX];
+        let value_pb = common_pb::Value::from(array.clone());
+        assert_eq!(Object::try_from(value_pb).unwrap(), Object::from(array));
+    }
+
+    #[test]
+    fn test_ullong_to_value() {
+        let large = u128::MAX;
+        let small = u64::MAX as u128 + 1;
+        let large_pb = common_pb::Value::try_from(Object::from(large)).unwrap();
+        let small_pb = common_pb::Value::try_from(Object::from(small)).unwrap();
+        assert_eq!(
+            large_pb.item,
+            Some(common_pb::value::Item::U128(common_pb::U128 { hi: u64::MAX, lo: u64::MAX }))
+        );
+        assert_eq!(small_pb.item, Some(common_pb::value::Item::U128(common_pb::U128 { hi: 1, lo: 0 })));
+
+        let large_obj = Object::try_from(large_pb).unwrap();
+        let small_obj = Object::try_from(small_pb).unwrap();
+        assert_eq!(large_obj, Object::from(large));
+        assert_eq!(small_obj, Object::from(small));
+        assert!(large_obj > small_obj);
+
+        // the values encoded as strings before are still decoded as they were
+        let legacy_pb = common_pb::Value::from(large.to_string());
+        assert_eq!(Object::try_from(legacy_pb).unwrap(), Object::from(large.to_string()));
+    }
+
+    #[test]
+    fn test_unfold_keep_empty_to_physical() {
+        let unfold =
+            pb::Unfold { tag: Some(1.into()), alias: Some(2.into()), meta_data: None, keep_empty: true };
+        let physical_unfold = physical_pb::Unfold::from(unfold);
+        assert_eq!(physical_unfold.tag, Some(1));
+        assert_eq!(physical_unfold.alias, Some(2));
+        assert!(physical_unfold.keep_empty);
+    }
+
+    fn op_kind_of(opr: physical_pb::PhysicalOpr) -> physical_pb::physical_opr::operator::OpKind {
+        opr.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_order_to_physical() {
+        let order = pb::OrderBy {
+            pairs: vec![pb::order_by::OrderingPair {
+                key: Some(common_pb::Variable::from("@a".to_string())),
+                order: 1,
+                null_order: 0,
+            }],
+            limit: Some(pb::Range { lower: 0, upper: 10 }),
+            unstable: false,
+        };
+        assert_eq!(
+            op_kind_of(physical_pb::PhysicalOpr::try_from(order.clone()).unwrap()),
+            physical_pb::physical_opr::operator::OpKind::OrderBy(order.clone())
+        );
+
+        let mut no_key = order.clone();
+        no_key
+            .pairs
+            .push(pb::order_by::OrderingPair { key: None, order: 2, null_order: 0 });
+        assert!(matches!(
+            physical_pb::PhysicalOpr::try_from(no_key),
+            Err(ParsePbError::EmptyFieldError(_))
+        ));
+
+        let mut bad_limit = order.clone();
+        bad_limit.limit = Some(pb::Range { lower: 10, upper: 5 });
+        assert!(matches!(physical_pb::PhysicalOpr::try_from(bad_limit), Err(ParsePbError::ParseError(_))));
+
+        let mut bad_null_order = order.clone();
+        bad_null_order.pairs[0].null_order = 3;
+        assert!(matches!(
+            physical_pb::PhysicalOpr::try_from(bad_null_order),
+            Err(ParsePbError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_order_nulls_first() {
+        use pb::order_by::ordering_pair::{NullOrder, Order};
+        let pair = |order: Order, null_order: NullOrder| pb::order_by::OrderingPair {
+            key: Some(common_pb::Variable::from("@.age".to_string())),
+            order: order as i32,
+            null_order: null_order as i32,
+        };
+        // by default, as in SQL
+        assert!(!pair(Order::Asc, NullOrder::Default)
+            .is_nulls_first()
+            .unwrap());
+        assert!(pair(Order::Desc, NullOrder::Default)
+            .is_nulls_first()
+            .unwrap());
+        // explicitly, regardless of the direction
+        assert!(pair(Order::Asc, NullOrder::NullsFirst)
+            .is_nulls_first()
+            .unwrap());
+        assert!(!pair(Order::Desc, NullOrder::NullsLast)
+            .is_nulls_first()
+            .unwrap());
+    }
+
+    #[test]
+    fn test_order_directions_to_physical() {
+        use pb::order_by::ordering_pair::{NullOrder, Order};
+        let order_by = |pairs: Vec<(&str, Order, NullOrder)>, unstable| pb::OrderBy {
+            pairs: pairs
+                .into_iter()
+                .map(|(key, order, null_order)| pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from(key.to_string())),
+                    order: order as i32,
+                    null_order: null_order as i32,
+                })
+                .collect(),
+            limit: None,
+            unstable,
+        };
+        // (order, is total, requires a stable sort)
+        let cases = vec![
+            (order_by(vec![("@.age", Order::Asc, NullOrder::Default)], true), false, false),
+            (order_by(vec![("@.age", Order::Desc, NullOrder::NullsLast)], false), false, true),
+            (
+                order_by(
+                    vec![
+                        ("@a.name", Order::Asc, NullOrder::NullsFirst),
+                        ("@.age", Order::Desc, NullOrder::Default),
+                        ("@.~id", Order::Asc, NullOrder::Default),
+                    ],
+                    false,
+                ),
+                true,
+                false,
+            ),
+        ];
+        for (order, is_total, requires_stable_sort) in cases {
+            match op_kind_of(physical_pb::PhysicalOpr::try_from(order.clone()).unwrap()) {
+                physical_pb::physical_opr::operator::OpKind::OrderBy(physical) => {
+                    // each key keeps its direction and null order, in the order of the pairs
+                    assert_eq!(physical.pairs, order.pairs);
+                    assert_eq!(physical.is_stable(), is_total);
+                    assert_eq!(physical.requires_stable_sort(), requires_stable_sort);
+                }
+                op_kind => panic!("unexpected op kind {:?}", op_kind),
+            }
+        }
+        // ordering randomly is never total, and needs no stable sort
+        let random = order_by(
+            vec![("@.~id", Order::Asc, NullOrder::Default), ("@.name", Order::Shuffle, NullOrder::Default)],
+            false,
+        );
+        assert!(!random.is_stable());
+        assert!(!random.requires_stable_sort());
+        // an invalid null order is rejected
+        let mut invalid = order_by(vec![("@.age", Order::Asc, NullOrder::Default)], false);
+        invalid.pairs[0].null_order = 3;
+        assert!(physical_pb::PhysicalOpr::try_from(invalid).is_err());
+    }
+
+    #[test]
+    fn test_expression_to_string() {
+        let to_string = |expr: &str| {
+            str_to_expr_pb(expr.to_string())
+                .unwrap()
+                .to_string()
+        };
+        let cases = vec![
+            // (the expression to parse, the expected string)
+            ("@.age > 18 && @.name startsWith \"m\"", "@.age > 18 && @.name startsWith \"m\""),
+            (
+                "(@.age > 18 || @a.age < 10) && !(@.name == \"marko\")",
+                "(@.age > 18 || @a.age < 10) && !(@.name == \"marko\")",
+            ),
+            (
+                "@.age > 18 || (@.age < 10 && @.name endsWith \"o\")",
+                "@.age > 18 || @.age < 10 && @.name endsWith \"o\"",
+            ),
+            ("((@.age > 18))", "@.age > 18"),
+            ("(1 + 2) * 3 - (4 - 5)", "(1 + 2) * 3 - (4 - 5)"),
+            ("1 + 2 * 3", "1 + 2 * 3"),
+            ("isNull @.age || @.id within [1, 2]", "isNull @.age || @.id within [1, 2]"),
+            ("@.score == 1.0 && @.name == \"a\\\"b\"", "@.score == 1.0 && @.name == \"a\\\"b\""),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(to_string(expr), expected);
+            // the string can be parsed back into the same expression, up to the braces
+            assert_eq!(to_string(&to_string(expr)), expected);
+        }
+
+        // an ill-formed expression is displayed as its operators in the given order
+        let mut expr = str_to_expr_pb("(@.age > 18".to_string()).unwrap();
+        assert_eq!(expr.to_string(), "( @.age > 18");
+        expr.operators.truncate(3);
+        expr.operators.remove(0);
+        assert_eq!(expr.to_string(), "@.age >");
+    }
+
+    #[test]
+    fn test_get_v_validate() {
+        let get_v = |opt| pb::GetV { tag: None, opt, params: None, alias: Some(1.into()), meta_data: None };
+        // Start, End, Other, Both, and Itself
+        for opt in 0..=4 {
+            assert!(get_v(opt).validate().is_ok());
+        }
+        for opt in vec![-1, 5, i32::MAX] {
+            assert!(matches!(get_v(opt).validate(), Err(ParsePbError::ParseError(_))));
+        }
+        assert!(get_v(2).is_adjacent());
+        assert!(!get_v(4).is_adjacent());
+    }
+
+    #[test]
+    fn test_dedup_to_physical() {
+        let dedup = pb::Dedup { keys: vec![common_pb::Variable::from("@a".to_string())], dedup_key: None };
+        assert_eq!(
+            op_kind_of(physical_pb::PhysicalOpr::try_from(dedup.clone()).unwrap()),
+            physical_pb::physical_opr::operator::OpKind::Dedup(dedup)
+        );
+        // the keys may be added after the dedup operator is created
+        assert!(physical_pb::PhysicalOpr::try_from(pb::Dedup { keys: vec![], dedup_key: None }).is_ok());
+    }
+
+    #[test]
+    fn test_limit_to_physical() {
+        let limit = pb::Limit { range: Some(pb::Range { lower: 1, upper: 2 }) };
+        assert_eq!(
+            op_kind_of(physical_pb::PhysicalOpr::try_from(limit.clone()).unwrap()),
+            physical_pb::physical_opr::operator::OpKind::Limit(limit)
+        );
+
+        assert!(matches!(
+            physical_pb::PhysicalOpr::try_from(pb::Limit { range: None }),
+            Err(ParsePbError::EmptyFieldError(_))
+        ));
+        for (lower, upper) in vec![(-1, 10), (10, 10), (10, 5)] {
+            let limit = pb::Limit { range: Some(pb::Range { lower, upper }) };
+            assert!(matches!(physical_pb::PhysicalOpr::try_from(limit), Err(ParsePbError::ParseError(_))));
+        }
+    }
+
+    #[test]
+    fn test_sample_to_physical() {
+        let sample_by = |inner| pb::Sample {
+            sample_type: Some(pb::sample::SampleType { inner }),
+            seed: None,
+            sample_weight: None,
+        };
+        let by_num =
+            sample_by(Some(pb::sample::sample_type::Inner::SampleByNum(pb::sample::SampleByNum {
+                num: 10,
+            })));
+        assert_eq!(
+            op_kind_of(physical_pb::PhysicalOpr::try_from(by_num.clone()).unwrap()),
+            physical_pb::physical_opr::operator::OpKind::Sample(by_num)
+        );
+        let by_ratio =
+            sample_by(Some(pb::sample::sample_type::Inner::SampleByRatio(pb::sample::SampleByRatio {
+                ratio: 1.0,
+            })));
+        assert!(physical_pb::PhysicalOpr::try_from(by_ratio).is_ok());
+
+        let zero_num =
+            sample_by(Some(pb::sample::sample_type::Inner::SampleByNum(pb::sample::SampleByNum {
+                num: 0,
+            })));
+        assert!(matches!(physical_pb::PhysicalOpr::try_from(zero_num), Err(ParsePbError::ParseError(_))));
+        for ratio in vec![-0.1, 1.1, f64::NAN] {
+            let bad_ratio =
+                sample_by(Some(pb::sample::sample_type::Inner::SampleByRatio(pb::sample::SampleByRatio {
+                    ratio,
+                })));
+            assert!(matches!(
+                physical_pb::PhysicalOpr::try_from(bad_ratio),
+                Err(ParsePbError::ParseError(_))
+            ));
+        }
+        assert!(matches!(
+            physical_pb::PhysicalOpr::try_from(sample_by(None)),
+            Err(ParsePbError::EmptyFieldError(_))
+        ));
+    }
+
+    #[test]
+    fn test_sample_physical_round_trip() {
+        // the physical plan carries the logical `Sample` as is, including the seed and the weight
+        let sample = pb::Sample {
+            sample_type: Some(pb::sample::SampleType {
+                inner: Some(pb::sample::sample_type::Inner::SampleByRatio(pb::sample::SampleByRatio {
+                    ratio: 0.5,
+                })),
+            }),
+            seed: Some(42),
+            sample_weight: Some(common_pb::Variable::property("weight")),
+        };
+        let physical = physical_pb::PhysicalOpr::try_from(sample.clone()).unwrap();
+        match op_kind_of(physical) {
+            physical_pb::physical_opr::operator::OpKind::Sample(round_trip) => {
+                assert_eq!(round_trip, sample)
+            }
+            op_kind => panic!("unexpected op kind {:?}", op_kind),
+        }
+    }
+
+    fn plan_of(nodes: Vec<(pb::logical_plan::Operator, Vec<i32>)>) -> pb::LogicalPlan {
+        pb::LogicalPlan {
+            nodes: nodes
+                .into_iter()
+                .map(|(opr, children)| pb::logical_plan::Node { opr: Some(opr), children })
+                .collect(),
+            roots: vec![0],
+        }
+    }
+
+    fn as_opr(alias: &str) -> pb::logical_plan::Operator {
+        pb::As { alias: Some(alias.into()) }.into()
+    }
+
+    #[test]
+    fn test_validate_aliases() {
+        // g.V().as("a").out().as("b")
+        let plan = plan_of(vec![(as_opr("a"), vec![1]), (as_opr("b"), vec![])]);
+        assert!(validate_aliases(&plan).is_ok());
+
+        // g.V().as("a").out().as("a")
+        let plan = plan_of(vec![(as_opr("a"), vec![1]), (as_opr("a"), vec![])]);
+        assert!(matches!(validate_aliases(&plan), Err(ParsePbError::ParseError(_))));
+
+        // the branches of a union tag different records with the same alias,
+        // while the alias is then defined for the records after the union
+        let union: pb::logical_plan::Operator = pb::Union { parents: vec![1, 2] }.into();
+        let plan = plan_of(vec![
+            (as_opr("a"), vec![1, 2]),
+            (as_opr("b"), vec![3]),
+            (as_opr("b"), vec![3]),
+            (union.clone(), vec![]),
+        ]);
+        assert!(validate_aliases(&plan).is_ok());
+        let plan = plan_of(vec![
+            (as_opr("a"), vec![1, 2]),
+            (as_opr("b"), vec![3]),
+            (as_opr("c"), vec![3]),
+            (union, vec![4]),
+            (as_opr("b"), vec![]),
+        ]);
+        assert!(validate_aliases(&plan).is_err());
+
+        // a group discards the aliases defined before
+        let group: pb::logical_plan::Operator = pb::GroupBy {
+            mappings: vec![pb::group_by::KeyAlias {
+                key: Some(common_pb::Variable::from("@a".to_string())),
+                alias: Some("a".into()),
+            }],
+            functions: vec![],
+            meta_data: vec![],
+        }
+        .into();
+        let plan = plan_of(vec![(as_opr("a"), vec![1]), (group, vec![])]);
+        assert!(validate_aliases(&plan).is_ok());
+    }
+
+    #[test]
+    fn test_plan_diff() {
+        let plan = plan_of(vec![(as_opr("a"), vec![1]), (as_opr("b"), vec![2]), (as_opr("c"), vec![])]);
+        assert!(plan.diff(&plan).is_empty());
+
+        // a single operator changed
+        let changed = plan_of(vec![(as_opr("a"), vec![1]), (as_opr("d"), vec![2]), (as_opr("c"), vec![])]);
+        let diffs = plan.diff(&changed);
+        assert_eq!(
+            diffs,
+            vec![PlanDiff::ModifiedNode { index: 1, old_op: Some(as_opr("b")), new_op: Some(as_opr("d")) }],
+            "{:?}",
+            diffs
+        );
+
+        // an operator appended, or removed the other way round
+        let appended = plan_of(vec![
+            (as_opr("a"), vec![1]),
+            (as_opr("b"), vec![2]),
+            (as_opr("c"), vec![3]),
+            (as_opr("d"), vec![]),
+        ]);
+        assert_eq!(
+            plan.diff(&appended),
+            vec![
+                PlanDiff::AddedEdge { from: 2, to: 3 },
+                PlanDiff::AddedNode { index: 3, op: Some(as_opr("d")) }
+            ]
+        );
+        assert_eq!(
+            appended.diff(&plan),
+            vec![
+                PlanDiff::RemovedEdge { from: 2, to: 3 },
+                PlanDiff::RemovedNode { index: 3, op: Some(as_opr("d")) }
+            ]
+        );
+        // the order of the children does not matter
+        let branches =
+            plan_of(vec![(as_opr("a"), vec![1, 2]), (as_opr("b"), vec![]), (as_opr("c"), vec![])]);
+        let mut reordered = branches.clone();
+        reordered.nodes[0].children = vec![2, 1];
+        assert!(branches.diff(&reordered).is_empty());
+
+        // a root changed, with the operators kept as is
+        let mut rerooted = branches.clone();
+        rerooted.roots = vec![1];
+        assert_eq!(
+            branches.diff(&rerooted),
+            vec![PlanDiff::RemovedRoot { index: 0 }, PlanDiff::AddedRoot { index: 1 }]
+        );
+        rerooted.roots = vec![1, 0];
+        assert_eq!(branches.diff(&rerooted), vec![PlanDiff::AddedRoot { index: 1 }]);
+    }
+
+    #[test]
+    fn test_resolve_params_of_index_predicate() {
+        let id_param = |name: &str| pb::index_predicate::Triplet {
+            key: Some(common_pb::Property::from(ID_KEY.to_string())),
+            value: Some(
+                common_pb::DynamicParam { name: name.to_string(), index: 0, data_type: None }.into(),
+            ),
+            cmp: common_pb::Logical::Eq as i32,
+        };
+        let mut predicate = pb::IndexPredicate {
+            or_predicates: vec![
+                pb::index_predicate::AndPredicate { predicates: vec![id_param("id1")] },
+                pb::index_predicate::AndPredicate { predicates: vec![id_param("ids")] },
+            ],
+        };
+        // the dynamic params cannot be converted before resolving
+        assert!(<Vec<i64>>::try_from(predicate.clone()).is_err());
+
+        let mut params = HashMap::new();
+        params.insert("id1".to_string(), common_pb::Value::from(1_i64));
+        assert!(predicate
+            .clone()
+            .resolve_params(&params)
+            .is_err());
+
+        params.insert("ids".to_string(), common_pb::Value::from(vec![2_i64, 3]));
+        predicate.resolve_params(&params).unwrap();
+        assert_eq!(<Vec<i64>>::try_from(predicate).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_resolve_physical_plan_params() {
+        use crate::generated::physical::physical_opr::operator::OpKind;
+
+        let ids_param = common_pb::ExprOpr {
+            node_type: None,
+            item: Some(common_pb::expr_opr::Item::Param(common_pb::DynamicParam {
+                name: "ids".to_string(),
+                index: 0,
+                data_type: None,
+            })),
+        };
+        let mut predicate = str_to_expr_pb("@.~id without [1, 2]".to_string()).unwrap();
+        let mut expected = predicate.clone();
+        predicate.operators[2] = ids_param;
+        expected.operators[2] = common_pb::ExprOpr {
+            node_type: None,
+            item: Some(common_pb::expr_opr::Item::Const(common_pb::Value::from(vec![1_i64, 2]))),
+        };
+        let select: PhysicalOpr = OpKind::Select(pb::Select { predicate: Some(predicate.clone()) }).into();
+        let sub_plan =
+            physical_pb::PhysicalPlan { plan_id: 0, plan: vec![select.clone()], params: HashMap::new() };
+        let mut plan = physical_pb::PhysicalPlan {
+            plan_id: 0,
+            plan: vec![
+                select,
+                OpKind::Apply(physical_pb::Apply { sub_plan: Some(sub_plan), ..Default::default() }).into(),
+            ],
+            params: HashMap::new(),
+        };
+        // nothing to resolve without the params
+        plan.clone().resolve_params().unwrap();
+
+        plan.params
+            .insert("ids".to_string(), common_pb::Value::from(vec![1_i64, 2]));
+        plan.resolve_params().unwrap();
+        let selects: Vec<_> = vec![&plan.plan[0], &plan.plan[1]]
+            .into_iter()
+            .map(|opr| {
+                match opr
+                    .opr
+                    .as_ref()
+                    .and_then(|opr| opr.op_kind.as_ref())
+                {
+                    Some(OpKind::Select(select)) => select.predicate.clone(),
+                    Some(OpKind::Apply(apply)) => match apply.sub_plan.as_ref().unwrap().plan[0]
+                        .opr
+                        .as_ref()
+                        .and_then(|opr| opr.op_kind.as_ref())
+                    {
+                        Some(OpKind::Select(select)) => select.predicate.clone(),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            })
+            .collect();
+        assert_eq!(selects, vec![Some(expected.clone()), Some(expected)]);
+    }
+
+    #[test]
+    fn test_index_predicate_multi_predicates_for_id() {
+        let id_triplet = |id: i64| pb::index_predicate::Triplet {
+            key: Some(common_pb::Property {
+                item: Some(common_pb::property::Item::Id(common_pb::IdKey {})),
+            }),
+            value: Some(common_pb::Value::from(id).into()),
+            cmp: common_pb::Logical::Eq as i32,
+        };
+        let single = pb::IndexPredicate {
+            or_predicates: vec![pb::index_predicate::AndPredicate { predicates: vec![id_triplet(1)] }],
+        };
+        assert_eq!(single.or_predicates[0].len(), 1);
+        assert_eq!(<Vec<i64>>::try_from(single).unwrap(), vec![1]);
+
+        let multi = pb::IndexPredicate {
+            or_predicates: vec![pb::index_predicate::AndPredicate {
+                predicates: vec![id_triplet(1), id_triplet(2)],
+            }],
+        };
+        assert_eq!(multi.or_predicates[0].len(), 2);
+        match <Vec<i64>>::try_from(multi) {
+            Err(ParsePbError::ParseError(msg)) => {
+                assert_eq!(msg, "expected single-predicate AndPredicate for ID lookup")
+            }
+            r => panic!("unexpected result {:?} of multiple predicates for id lookup", r),
+        }
+
+        // the primary keys of multiple columns are not regarded as an id lookup
+        let pk = pb::IndexPredicate {
+            or_predicates: vec![pb::index_predicate::AndPredicate {
+                predicates: vec![
+                    pb::index_predicate::Triplet {
+                        key: Some(common_pb::Property::from("name".to_string())),
+                        value: Some(common_pb::Value::from("marko".to_string()).into()),
+                        cmp: common_pb::Logical::Eq as i32,
+                    },
+                    pb::index_predicate::Triplet {
+                        key: Some(common_pb::Property::from("age".to_string())),
+                        value: Some(common_pb::Value::from(29_i64).into()),
+                        cmp: common_pb::Logical::Eq as i32,
+                    },
+                ],
+            }],
+        };
+        assert!(<Vec<i64>>::try_from(pk).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_index_predicate_between() {
+        let between = pb::IndexPredicate::between(
+            common_pb::Property::from("age".to_string()),
+            common_pb::Value::from(20_i64),
+            common_pb::Value::from(30_i64),
+        );
+        assert!(between.is_range());
+        assert_eq!(
+            common_pb::Expression::try_from(between.clone()).unwrap(),
+            str_to_expr_pb("(@.age >= 20 && @.age < 30)".to_string()).unwrap()
+        );
+
+        // the range is not regarded as the primary key values, while the other `AndPredicate` is
+        let mut predicate = between.clone();
+        predicate
+            .or_predicates
+            .push(pb::index_predicate::AndPredicate {
+                predicates: vec![pb::index_predicate::Triplet {
+                    key: Some(common_pb::Property::from("name".to_string())),
+                    value: Some(common_pb::Value::from("marko".to_string()).into()),
+                    cmp: common_pb::Logical::Eq as i32,
+                }],
+            });
+        assert!(!predicate.is_range());
+        assert!(<Vec<Vec<(NameOrId, Object)>>>::try_from(between)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            <Vec<Vec<(NameOrId, Object)>>>::try_from(predicate).unwrap(),
+            vec![vec![(NameOrId::from("name".to_string()), Object::from("marko"))]]
+        );
+    }
+
+    #[test]
+    fn test_split_both_edge_expand() {
+        let expand = pb::EdgeExpand {
+            v_tag: Some("a".into()),
+            direction: pb::edge_expand::Direction::Both as i32,
+            params: Some(pb::QueryParams {
+                tables: vec!["knows".into()],
+                columns: vec!["weight".into()],
+                is_all_columns: false,
+                limit: None,
+                predicate: str_to_expr_pb("@.weight > 0.5".to_string()).ok(),
+                sample_ratio: 1.0,
+                extra: HashMap::new(),
+            }),
+            alias: Some("b".into()),
+            expand_opt: pb::edge_expand::ExpandOpt::Edge as i32,
+            meta_data: None,
+            is_optional: true,
+        };
+        let (out_expand, in_expand) = expand.clone().split_both().unwrap();
+        assert_eq!(out_expand.direction, pb::edge_expand::Direction::Out as i32);
+        assert_eq!(in_expand.direction, pb::edge_expand::Direction::In as i32);
+        for split in vec![out_expand, in_expand] {
+            assert_eq!(split.params, expand.params);
+            assert_eq!(split.alias, expand.alias);
+            assert_eq!(split.v_tag, expand.v_tag);
+            assert_eq!(split.expand_opt, expand.expand_opt);
+            assert_eq!(split.is_optional, expand.is_optional);
+        }
+
+        let mut out_expand = expand;
+        out_expand.direction = pb::edge_expand::Direction::Out as i32;
+        assert!(out_expand.clone().split_both().is_none());
+        out_expand.direction = pb::edge_expand::Direction::In as i32;
+        assert!(out_expand.split_both().is_none());
+    }
+
+    #[test]
+    fn test_time_to_pb_value() {
+        let time_value = |h, m, s, milli| {
+            let time = chrono::NaiveTime::from_hms_milli_opt(h, m, s, milli).unwrap();
+            common_pb::Value::try_from(DateTimeFormats::Time(time)).unwrap()
+        };
+        let time32 = |item| common_pb::Value {
+            item: Some(common_pb::value::Item::Time(common_pb::Time32 { item })),
+        };
+        assert_eq!(time_value(0, 0, 0, 0), time32(0));
+        // the end of the day
+        assert_eq!(time_value(23, 59, 59, 999), time32(86_399_999));
+        // a leap second
+        assert_eq!(time_value(23, 59, 59, 1_500), time32(86_400_500));
+        // the same as the conversion from the object
+        let time = chrono::NaiveTime::from_hms_milli_opt(23, 59, 59, 999).unwrap();
+        assert_eq!(
+            common_pb::Value::try_from(Object::DateFormat(DateTimeFormats::Time(time))).unwrap(),
+            time32(86_399_999)
+        );
+    }
+
+    #[test]
+    fn test_meta_data_into_physical_opr() {
+        let meta = |t: common_pb::PrimitiveType, alias: i32| pb::MetaData {
+            r#type: Some(common_pb::IrDataType {
+                r#type: Some(common_pb::ir_data_type::Type::DataType(primitive_type(t))),
+            }),
+            alias,
+        };
+        let expected_meta = |meta_data: &[pb::MetaData]| -> Vec<physical_pb::physical_opr::MetaData> {
+            meta_data
+                .iter()
+                .map(|meta| physical_pb::physical_opr::MetaData {
+                    r#type: meta.r#type.clone(),
+                    alias: meta.alias,
+                })
+                .collect()
+        };
+
+        let project_meta = vec![
+            meta(common_pb::PrimitiveType::DtSignedInt64, 0),
+            meta(common_pb::PrimitiveType::DtBool, 1),
+        ];
+        let project = pb::Project { mappings: vec![], is_append: true, meta_data: project_meta.clone() };
+        let opr: physical_pb::PhysicalOpr = project.clone().into();
+        assert_eq!(opr.meta_data, expected_meta(&project_meta));
+        let op_kind: physical_pb::physical_opr::operator::OpKind = opr.try_into().unwrap();
+        assert_eq!(op_kind, physical_pb::physical_opr::operator::OpKind::Project(project.into()));
+
+        let scan_meta = meta(common_pb::PrimitiveType::DtSignedInt32, 2);
+        let scan = pb::Scan {
+            scan_opt: 0,
+            alias: Some(2.into()),
+            params: None,
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: Some(scan_meta.clone()),
+        };
+        let opr: physical_pb::PhysicalOpr = scan.into();
+        assert_eq!(opr.meta_data, expected_meta(&[scan_meta.clone()]));
+
+        // the path expand takes the meta data of its edge expand
+        let expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: None,
+            alias: None,
+            expand_opt: pb::edge_expand::ExpandOpt::Vertex as i32,
+            meta_data: Some(scan_meta.clone()),
+            is_optional: false,
+        };
+        let path = pb::PathExpand {
+            base: Some(pb::path_expand::ExpandBase { edge_expand: Some(expand.clone()), get_v: None }),
+            start_tag: None,
+            alias: None,
+            hop_range: Some(pb::Range { lower: 1, upper: 2 }),
+            path_opt: 0,
+            result_opt: 0,
+            condition: None,
+            is_optional: false,
+            is_condition_emit_only: false,
+        };
+        let opr: physical_pb::PhysicalOpr = expand.into();
+        assert_eq!(opr.meta_data, expected_meta(&[scan_meta.clone()]));
+        let opr: physical_pb::PhysicalOpr = path.into();
+        assert_eq!(opr.meta_data, expected_meta(&[scan_meta]));
+
+        // no meta data
+        let group = pb::GroupBy { mappings: vec![], functions: vec![], meta_data: vec![] };
+        let opr: physical_pb::PhysicalOpr = group.into();
+        assert!(opr.meta_data.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_physical_opr() {
+        let scan = pb::Scan {
+            scan_opt: 0,
+            alias: Some(1.into()),
+            params: Some(pb::QueryParams::default()),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: Some(pb::MetaData {
+                r#type: Some(common_pb::IrDataType {
+                    r#type: Some(common_pb::ir_data_type::Type::DataType(primitive_type(
+                        common_pb::PrimitiveType::DtSignedInt64,
+                    ))),
+                }),
+                alias: 1,
+            }),
+        };
+        let origin: physical_pb::PhysicalOpr = scan.into();
+        let mut duplicate = origin.duplicate_opr();
+        assert_eq!(duplicate, origin);
+
+        duplicate.meta_data[0].alias = 2;
+        if let Some(physical_pb::physical_opr::Operator {
+            op_kind: Some(physical_pb::physical_opr::operator::OpKind::Scan(scan)),
+        }) = duplicate.opr.as_mut()
+        {
+            scan.alias = Some(2);
+            scan.is_count_only = true;
+        } else {
+            panic!("expected a scan operator");
+        }
+        assert_ne!(duplicate, origin);
+        assert_eq!(origin.meta_data[0].alias, 1);
+        match origin
+            .opr
+            .as_ref()
+            .and_then(|opr| opr.op_kind.as_ref())
+        {
+            Some(physical_pb::physical_opr::operator::OpKind::Scan(scan)) => {
+                assert_eq!(scan.alias, Some(1));
+                assert!(!scan.is_count_only);
+            }
+            _ => panic!("expected a scan operator"),
+        }
+    }
+
+    fn primitive_type(t: common_pb::PrimitiveType) -> common_pb::DataType {
+        common_pb::DataType { item: Some(common_pb::data_type::Item::PrimitiveType(t as i32)) }
+    }
+
+    #[test]
+    fn test_value_coerce_to() {
+        use common_pb::value::Item;
+        use common_pb::PrimitiveType::*;
+
+        let valid_cases = vec![
+            (Item::I32(-7), DtSignedInt64, Item::I64(-7)),
+            (Item::I32(-7), DtDouble, Item::F64(-7.0)),
+            (Item::U32(7), DtUnsignedInt64, Item::U64(7)),
+            (Item::U32(u32::MAX), DtSignedInt64, Item::I64(u32::MAX as i64)),
+            (Item::U32(7), DtDouble, Item::F64(7.0)),
+            (Item::I64(1 << 40), DtDouble, Item::F64((1i64 << 40) as f64)),
+            (Item::U64(1 << 40), DtDouble, Item::F64((1u64 << 40) as f64)),
+            (Item::F32(0.5), DtDouble, Item::F64(0.5)),
+            // identical types
+            (Item::I32(7), DtSignedInt32, Item::I32(7)),
+            (Item::F64(0.5), DtDouble, Item::F64(0.5)),
+            (Item::Boolean(true), DtBool, Item::Boolean(true)),
+            // any type, or null value
+            (Item::Str("a".to_string()), DtAny, Item::Str("a".to_string())),
+            (Item::None(common_pb::None {}), DtSignedInt32, Item::None(common_pb::None {})),
+        ];
+        for (from, target, expected) in valid_cases {
+            let value = common_pb::Value { item: Some(from) };
+            assert_eq!(
+                value.coerce_to(primitive_type(target)).unwrap(),
+                common_pb::Value { item: Some(expected) }
+            );
+        }
+
+        let lossy_cases = vec![
+            (Item::I64(7), DtSignedInt32),
+            (Item::U64(7), DtSignedInt64),
+            (Item::I32(-7), DtUnsignedInt64),
+            (Item::I32(7), DtFloat),
+            (Item::F64(0.5), DtFloat),
+            (Item::F64(7.0), DtSignedInt64),
+            (Item::F32(7.0), DtSignedInt32),
+            (Item::Boolean(true), DtSignedInt32),
+            (Item::Str("7".to_string()), DtSignedInt32),
+        ];
+        for (from, target) in lossy_cases {
+            let value = common_pb::Value { item: Some(from) };
+            assert!(matches!(value.coerce_to(primitive_type(target)), Err(ParsePbError::Unsupported(_))));
+        }
+
+        let string_type = common_pb::DataType {
+            item: Some(common_pb::data_type::Item::String(common_pb::String {
+                item: Some(common_pb::string::Item::LongText(common_pb::string::LongText {})),
+            })),
+        };
+        assert!(matches!(
+            common_pb::Value::from(7).coerce_to(string_type),
+            Err(ParsePbError::Unsupported(_))
+        ));
+    }
+    #[test]
+    fn test_scan_kinds() {
+        for (idx_predicate, is_count_only) in [
+            (None, false),
+            (None, true),
+            (Some(pb::IndexPredicate::from(vec!["software".to_string()])), false),
+            (Some(pb::IndexPredicate::from(vec!["software".to_string()])), true),
+        ] {
+            let is_index = idx_predicate.is_some();
+            let scan = pb::Scan {
+                scan_opt: 0,
+                alias: None,
+                params: None,
+                idx_predicate,
+                is_count_only,
+                meta_data: None,
+            };
+            assert_eq!(scan.is_index_scan(), is_index);
+            assert_eq!(scan.is_count_only_scan(), is_count_only);
+            let physical_scan: physical_pb::Scan = scan.into();
+            assert_eq!(physical_scan.is_index_scan(), is_index);
+            assert_eq!(physical_scan.is_count_only_scan(), is_count_only);
+        }
     }
 }
@@ -0,0 +1,752 @@
+//
+//! Copyright 2021 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+//! The column pruning pass populates the columns of the query params of the graph operators, i.e.,
+//! `Scan`, `EdgeExpand`, `GetV` and `PathExpand`, with the properties that are actually referred to
+//! downstream, e.g., in filters, projections, group keys, order keys and join keys, such that the
+//! storage fetches no more properties than needed, instead of all of them with `is_all_columns`.
+//!
+//! The pass is conservative, which keeps the plan as is whenever a property cannot be resolved to
+//! the operator producing the graph element, and keeps all the columns of an operator if all of its
+//! properties are referred to, e.g., by `@a.~all`, or if it is sent back by a `Sink`, either as the
+//! head or as a tag. Besides, the operators that are visible to the subtask of an `Apply` are left
+//! as they are.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+
+use ir_common::generated::algebra as pb;
+use ir_common::generated::common as common_pb;
+use ir_common::NameOrId;
+
+use crate::error::{IrError, IrResult};
+
+type Opr = pb::logical_plan::operator::Opr;
+
+/// The columns of a graph element that are referred to downstream
+#[derive(Clone, Debug, PartialEq)]
+enum Columns {
+    All,
+    Exact(BTreeSet<NameOrId>),
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Columns::Exact(BTreeSet::new())
+    }
+}
+
+/// The property of a graph element that a variable refers to
+enum PropRef {
+    /// Only the element itself, or its id, label, etc, which needs no column
+    None,
+    Key(NameOrId),
+    All,
+}
+
+/// A variable referring to the property of an element, which is the head if `tag` is `None`
+struct VarRef {
+    tag: Option<common_pb::NameOrId>,
+    prop: PropRef,
+}
+
+impl VarRef {
+    fn from_var(var: &common_pb::Variable) -> Self {
+        use common_pb::property::Item;
+        let prop = match var
+            .property
+            .as_ref()
+            .and_then(|prop| prop.item.as_ref())
+        {
+            Some(Item::Key(key)) => match NameOrId::try_from(key.clone()) {
+                Ok(key) => PropRef::Key(key),
+                Err(_) => PropRef::All,
+            },
+            // a vertex or an edge in the path may refer to any property
+            Some(Item::All(_)) | Some(Item::PathElem(_)) => PropRef::All,
+            _ => PropRef::None,
+        };
+        VarRef { tag: var.tag.clone(), prop }
+    }
+}
+
+/// Populate the columns of the graph operators of the plan with the properties referred to downstream.
+/// Return whether the plan is rewritten, which is not if the plan is beyond the analysis.
+pub fn prune_columns(plan: &mut pb::LogicalPlan) -> IrResult<bool> {
+    let parents = parents_of(plan)?;
+    let order = topological_order(plan, &parents)?;
+
+    // the graph operators producing the head of each node, with `None` indicating no graph element,
+    // and the graph operators producing the element of each tag
+    let mut heads: Vec<Option<BTreeSet<usize>>> = vec![None; plan.nodes.len()];
+    let mut input_heads: Vec<Option<BTreeSet<usize>>> = vec![None; plan.nodes.len()];
+    let mut tags: HashMap<NameOrId, BTreeSet<usize>> = HashMap::new();
+    for &index in &order {
+        let input_head = merge_heads(
+            parents[index]
+                .iter()
+                .map(|parent| &heads[*parent]),
+        );
+        let itself = Some(single(index));
+        let head = match opr_of(plan, index) {
+            Some(Opr::Scan(scan)) => {
+                bind(&mut tags, scan.alias.as_ref(), itself.clone())?;
+                itself
+            }
+            Some(Opr::Edge(edge)) => {
+                bind(&mut tags, edge.alias.as_ref(), itself.clone())?;
+                itself
+            }
+            Some(Opr::Vertex(getv)) if getv.is_adjacent() => {
+                bind(&mut tags, getv.alias.as_ref(), itself.clone())?;
+                itself
+            }
+            // the vertex itself, which is only filtered
+            Some(Opr::Vertex(getv)) => {
+                bind(&mut tags, getv.alias.as_ref(), input_head.clone())?;
+                input_head.clone()
+            }
+            Some(Opr::Path(path)) => {
+                bind(&mut tags, path.alias.as_ref(), itself.clone())?;
+                itself
+            }
+            Some(Opr::As(as_opr)) => {
+                bind(&mut tags, as_opr.alias.as_ref(), input_head.clone())?;
+                input_head.clone()
+            }
+            Some(Opr::Select(_))
+            | Some(Opr::Dedup(_))
+            | Some(Opr::OrderBy(_))
+            | Some(Opr::Limit(_))
+            | Some(Opr::Sample(_))
+            | Some(Opr::Union(_))
+            | Some(Opr::Intersect(_))
+            | Some(Opr::Root(_))
+            | Some(Opr::Sink(_)) => input_head.clone(),
+            Some(Opr::Project(project)) => {
+                let mut elements = vec![];
+                for mapping in &project.mappings {
+                    let element = element_of(mapping.expr.as_ref(), input_head.as_ref(), &tags)?;
+                    elements.push((mapping.alias.as_ref(), element));
+                }
+                let head = match elements.as_slice() {
+                    [(_, element)] if !project.is_append => element.clone(),
+                    _ => None,
+                };
+                for (alias, element) in elements {
+                    bind(&mut tags, alias, element)?;
+                }
+                head
+            }
+            Some(Opr::GroupBy(group)) => {
+                let mut elements = vec![];
+                for mapping in &group.mappings {
+                    let element = match mapping.key.as_ref() {
+                        Some(key) => element_of_var(key, input_head.as_ref(), &tags)?,
+                        None => None,
+                    };
+                    elements.push((mapping.alias.as_ref(), element));
+                }
+                for (alias, element) in elements {
+                    bind(&mut tags, alias, element)?;
+                }
+                None
+            }
+            _ => None,
+        };
+        heads[index] = head;
+        input_heads[index] = input_head;
+    }
+
+    // the columns of the graph operators referred to downstream, including by their own predicates
+    let mut required: Vec<Columns> = vec![Columns::default(); plan.nodes.len()];
+    for index in 0..plan.nodes.len() {
+        let mut own_refs = vec![];
+        let mut input_refs = vec![];
+        match opr_of(plan, index) {
+            Some(Opr::Scan(scan)) => collect_params(scan.params.as_ref(), &mut own_refs),
+            Some(Opr::Edge(edge)) => collect_params(edge.params.as_ref(), &mut own_refs),
+            Some(Opr::Vertex(getv)) if getv.is_adjacent() => {
+                collect_params(getv.params.as_ref(), &mut own_refs)
+            }
+            Some(Opr::Vertex(getv)) => collect_params(getv.params.as_ref(), &mut input_refs),
+            Some(Opr::Path(path)) => collect_expr(path.condition.as_ref(), &mut own_refs),
+            Some(Opr::Select(select)) => collect_expr(select.predicate.as_ref(), &mut input_refs),
+            Some(Opr::Project(project)) => project
+                .mappings
+                .iter()
+                .for_each(|mapping| collect_expr(mapping.expr.as_ref(), &mut input_refs)),
+            Some(Opr::Dedup(dedup)) => {
+                collect_vars(dedup.keys.iter(), &mut input_refs);
+                collect_expr(dedup.dedup_key.as_ref(), &mut input_refs);
+            }
+            Some(Opr::OrderBy(order)) => collect_vars(
+                order
+                    .pairs
+                    .iter()
+                    .filter_map(|pair| pair.key.as_ref()),
+                &mut input_refs,
+            ),
+            Some(Opr::GroupBy(group)) => {
+                collect_vars(
+                    group
+                        .mappings
+                        .iter()
+                        .filter_map(|mapping| mapping.key.as_ref()),
+                    &mut input_refs,
+                );
+                collect_vars(
+                    group
+                        .functions
+                        .iter()
+                        .flat_map(|func| func.vars.iter()),
+                    &mut input_refs,
+                );
+            }
+            Some(Opr::Join(join)) => collect_vars(
+                join.left_keys
+                    .iter()
+                    .chain(join.right_keys.iter()),
+                &mut input_refs,
+            ),
+            Some(Opr::Sample(sample)) => collect_vars(sample.sample_weight.iter(), &mut input_refs),
+            // the elements sent back are with all their properties
+            Some(Opr::Sink(sink)) => {
+                for producers in sunk_elements(sink, input_heads[index].as_ref(), &tags)? {
+                    for producer in producers {
+                        required[*producer] = Columns::All;
+                    }
+                }
+            }
+            _ => {}
+        }
+        let itself = Some(single(index));
+        for (refs, head) in [(own_refs, itself.as_ref()), (input_refs, input_heads[index].as_ref())] {
+            for var_ref in refs {
+                if !require(&var_ref, head, &tags, &mut required)? {
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    let frozen = frozen_nodes(plan, &parents);
+    for index in 0..plan.nodes.len() {
+        if frozen.contains(&index) {
+            continue;
+        }
+        if let Some(params) = params_mut(plan, index) {
+            match &required[index] {
+                Columns::All => {
+                    let params = params.get_or_insert_with(Default::default);
+                    params.columns.clear();
+                    params.is_all_columns = true;
+                }
+                Columns::Exact(columns) => {
+                    if columns.is_empty() && params.is_none() {
+                        continue;
+                    }
+                    let params = params.get_or_insert_with(Default::default);
+                    params.columns = columns
+                        .iter()
+                        .cloned()
+                        .map(Into::into)
+                        .collect();
+                    params.is_all_columns = false;
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Bind the alias to the element produced by `producers`, if it is an element
+fn bind(
+    tags: &mut HashMap<NameOrId, BTreeSet<usize>>, alias: Option<&common_pb::NameOrId>,
+    producers: Option<BTreeSet<usize>>,
+) -> IrResult<()> {
+    if let (Some(alias), Some(producers)) = (alias, producers) {
+        tags.entry(NameOrId::try_from(alias.clone())?)
+            .or_default()
+            .extend(producers);
+    }
+    Ok(())
+}
+
+fn single(index: usize) -> BTreeSet<usize> {
+    std::iter::once(index).collect()
+}
+
+fn opr_of(plan: &pb::LogicalPlan, index: usize) -> Option<&Opr> {
+    plan.nodes[index]
+        .opr
+        .as_ref()
+        .and_then(|opr| opr.opr.as_ref())
+}
+
+/// The query params of the graph operator whose columns are to be rewritten, if any.
+/// Note that the edges of an `EdgeExpand` other than `Edge` are not the output, and a `PathExpand`
+/// fetches the properties of the vertices on the path by its `GetV`.
+fn params_mut(plan: &mut pb::LogicalPlan, index: usize) -> Option<&mut Option<pb::QueryParams>> {
+    match plan.nodes[index]
+        .opr
+        .as_mut()
+        .and_then(|opr| opr.opr.as_mut())
+    {
+        Some(Opr::Scan(scan)) => Some(&mut scan.params),
+        Some(Opr::Edge(edge)) if edge.expand_opt == pb::edge_expand::ExpandOpt::Edge as i32 => {
+            Some(&mut edge.params)
+        }
+        Some(Opr::Vertex(getv)) if getv.is_adjacent() => Some(&mut getv.params),
+        Some(Opr::Path(path)) => path
+            .base
+            .as_mut()
+            .and_then(|base| base.get_v.as_mut())
+            .map(|getv| &mut getv.params),
+        _ => None,
+    }
+}
+
+fn parents_of(plan: &pb::LogicalPlan) -> IrResult<Vec<Vec<usize>>> {
+    let mut parents = vec![vec![]; plan.nodes.len()];
+    for (index, node) in plan.nodes.iter().enumerate() {
+        for &child in &node.children {
+            if child < 0 || child as usize >= plan.nodes.len() {
+                return Err(IrError::MissingData(format!("child node {} of node {}", child, index)));
+            }
+            parents[child as usize].push(index);
+        }
+    }
+    Ok(parents)
+}
+
+fn topological_order(plan: &pb::LogicalPlan, parents: &[Vec<usize>]) -> IrResult<Vec<usize>> {
+    let mut in_degrees: Vec<usize> = parents
+        .iter()
+        .map(|parents| parents.len())
+        .collect();
+    let mut queue: VecDeque<usize> = (0..plan.nodes.len())
+        .filter(|index| in_degrees[*index] == 0)
+        .collect();
+    let mut order = Vec::with_capacity(plan.nodes.len());
+    while let Some(index) = queue.pop_front() {
+        order.push(index);
+        for &child in &plan.nodes[index].children {
+            in_degrees[child as usize] -= 1;
+            if in_degrees[child as usize] == 0 {
+                queue.push_back(child as usize);
+            }
+        }
+    }
+    if order.len() < plan.nodes.len() {
+        return Err(IrError::Unsupported("column pruning of a plan with cycles".to_string()));
+    }
+    Ok(order)
+}
+
+/// The nodes that are upstream of an `Apply`, or in its subtask, which may be referred to in the subtask.
+fn frozen_nodes(plan: &pb::LogicalPlan, parents: &[Vec<usize>]) -> HashSet<usize> {
+    let mut frozen = HashSet::new();
+    let mut upstream = vec![];
+    let mut downstream = vec![];
+    for index in 0..plan.nodes.len() {
+        let subtask = match opr_of(plan, index) {
+            Some(Opr::Apply(apply)) => Some(apply.subtask),
+            Some(Opr::SegApply(seg_apply)) => seg_apply
+                .apply_subtask
+                .as_ref()
+                .map(|apply| apply.subtask),
+            _ => None,
+        };
+        if let Some(subtask) = subtask {
+            upstream.push(index);
+            if subtask >= 0 && (subtask as usize) < plan.nodes.len() {
+                downstream.push(subtask as usize);
+            }
+        }
+    }
+    while let Some(index) = upstream.pop() {
+        if frozen.insert(index) {
+            upstream.extend(parents[index].iter().cloned());
+        }
+    }
+    let mut visited = HashSet::new();
+    while let Some(index) = downstream.pop() {
+        if visited.insert(index) {
+            frozen.insert(index);
+            downstream.extend(
+                plan.nodes[index]
+                    .children
+                    .iter()
+                    .map(|child| *child as usize),
+            );
+        }
+    }
+    frozen
+}
+
+/// The head is an element only if the heads of all the parents are, e.g., of a `Union`
+fn merge_heads<'a, I>(heads: I) -> Option<BTreeSet<usize>>
+where
+    I: Iterator<Item = &'a Option<BTreeSet<usize>>>,
+{
+    let mut merged: Option<BTreeSet<usize>> = None;
+    for head in heads {
+        merged
+            .get_or_insert_with(BTreeSet::new)
+            .extend(head.as_ref()?.iter().cloned());
+    }
+    merged
+}
+
+/// The element that an expression evaluates to, if it is a sole variable of an element
+fn element_of(
+    expr: Option<&common_pb::Expression>, head: Option<&BTreeSet<usize>>,
+    tags: &HashMap<NameOrId, BTreeSet<usize>>,
+) -> IrResult<Option<BTreeSet<usize>>> {
+    match expr.map(|expr| expr.operators.as_slice()) {
+        Some([common_pb::ExprOpr { item: Some(common_pb::expr_opr::Item::Var(var)), .. }]) => {
+            element_of_var(var, head, tags)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn element_of_var(
+    var: &common_pb::Variable, head: Option<&BTreeSet<usize>>, tags: &HashMap<NameOrId, BTreeSet<usize>>,
+) -> IrResult<Option<BTreeSet<usize>>> {
+    if var.property.is_some() {
+        return Ok(None);
+    }
+    match var.tag.as_ref() {
+        Some(tag) => Ok(tags
+            .get(&NameOrId::try_from(tag.clone())?)
+            .cloned()),
+        None => Ok(head.cloned()),
+    }
+}
+
+/// The producers of the elements that a sink sends back, i.e., of the given tags, or of all the
+/// tags if none is given. A tag that is not an element, e.g., a projected property, needs no column.
+fn sunk_elements<'a>(
+    sink: &pb::Sink, head: Option<&'a BTreeSet<usize>>, tags: &'a HashMap<NameOrId, BTreeSet<usize>>,
+) -> IrResult<Vec<&'a BTreeSet<usize>>> {
+    if sink.tags.is_empty() {
+        return Ok(tags.values().collect());
+    }
+    let mut elements = vec![];
+    let tagged_keys = sink
+        .tagged_targets
+        .iter()
+        .flat_map(|target| target.tags.iter());
+    for key in sink.tags.iter().chain(tagged_keys) {
+        match key.key.as_ref() {
+            Some(tag) => elements.extend(tags.get(&NameOrId::try_from(tag.clone())?)),
+            None => elements.extend(head),
+        }
+    }
+    Ok(elements)
+}
+
+/// Require the property that `var_ref` refers to of the producers of the element, and return false
+/// if the element cannot be resolved while a property of it is referred to.
+fn require(
+    var_ref: &VarRef, head: Option<&BTreeSet<usize>>, tags: &HashMap<NameOrId, BTreeSet<usize>>,
+    required: &mut [Columns],
+) -> IrResult<bool> {
+    if let PropRef::None = var_ref.prop {
+        return Ok(true);
+    }
+    let producers = match var_ref.tag.as_ref() {
+        Some(tag) => tags.get(&NameOrId::try_from(tag.clone())?),
+        None => head,
+    };
+    if let Some(producers) = producers {
+        for producer in producers {
+            match (&var_ref.prop, &mut required[*producer]) {
+                (PropRef::Key(key), Columns::Exact(columns)) => {
+                    columns.insert(key.clone());
+                }
+                (PropRef::All, columns) => *columns = Columns::All,
+                _ => {}
+            }
+        }
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+fn collect_params(params: Option<&pb::QueryParams>, refs: &mut Vec<VarRef>) {
+    collect_expr(params.and_then(|params| params.predicate.as_ref()), refs);
+}
+
+fn collect_vars<'a, I: Iterator<Item = &'a common_pb::Variable>>(vars: I, refs: &mut Vec<VarRef>) {
+    refs.extend(vars.map(VarRef::from_var));
+}
+
+fn collect_expr(expr: Option<&common_pb::Expression>, refs: &mut Vec<VarRef>) {
+    use common_pb::expr_opr::Item;
+    for opr in expr
+        .iter()
+        .flat_map(|expr| expr.operators.iter())
+    {
+        match opr.item.as_ref() {
+            Some(Item::Var(var)) => refs.push(VarRef::from_var(var)),
+            Some(Item::Vars(vars)) | Some(Item::VarMap(vars)) => collect_vars(vars.keys.iter(), refs),
+            Some(Item::Map(key_values)) => collect_key_values(key_values, refs),
+            Some(Item::PathFunc(path_func)) => {
+                refs.push(VarRef { tag: path_func.tag.clone(), prop: PropRef::All })
+            }
+            Some(Item::Case(case)) => {
+                for when_then in &case.when_then_expressions {
+                    collect_expr(when_then.when_expression.as_ref(), refs);
+                    collect_expr(when_then.then_result_expression.as_ref(), refs);
+                }
+                collect_expr(case.else_result_expression.as_ref(), refs);
+            }
+            Some(Item::UdfFunc(udf)) => udf
+                .parameters
+                .iter()
+                .for_each(|param| collect_expr(Some(param), refs)),
+            _ => {}
+        }
+    }
+}
+
+fn collect_key_values(key_values: &common_pb::VariableKeyValues, refs: &mut Vec<VarRef>) {
+    use common_pb::variable_key_value::Value;
+    for key_value in &key_values.key_vals {
+        match key_value.value.as_ref() {
+            Some(Value::Val(var)) => refs.push(VarRef::from_var(var)),
+            Some(Value::PathFunc(path_func)) => {
+                refs.push(VarRef { tag: path_func.tag.clone(), prop: PropRef::All })
+            }
+            Some(Value::Nested(nested)) => collect_key_values(nested, refs),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ir_common::expr_parse::str_to_expr_pb;
+
+    use super::*;
+
+    fn all_columns() -> Option<pb::QueryParams> {
+        Some(pb::QueryParams { is_all_columns: true, ..Default::default() })
+    }
+
+    fn expr(expr: &str) -> Option<common_pb::Expression> {
+        Some(str_to_expr_pb(expr.to_string()).unwrap())
+    }
+
+    fn var(var: &str) -> common_pb::Variable {
+        var.to_string().into()
+    }
+
+    /// A chain of operators, each of which is the child of the previous one
+    fn chain_of(oprs: Vec<Opr>) -> pb::LogicalPlan {
+        let len = oprs.len();
+        pb::LogicalPlan {
+            nodes: oprs
+                .into_iter()
+                .enumerate()
+                .map(|(index, opr)| pb::logical_plan::Node {
+                    opr: Some(pb::logical_plan::Operator { opr: Some(opr) }),
+                    children: if index + 1 < len { vec![index as i32 + 1] } else { vec![] },
+                })
+                .collect(),
+            roots: vec![0],
+        }
+    }
+
+    fn scan(alias: &str, predicate: Option<&str>) -> Opr {
+        let mut params = all_columns().unwrap();
+        params.predicate = predicate.and_then(expr);
+        Opr::Scan(pb::Scan { alias: Some(alias.into()), params: Some(params), ..Default::default() })
+    }
+
+    fn expand_edge(alias: &str) -> Opr {
+        Opr::Edge(pb::EdgeExpand {
+            alias: Some(alias.into()),
+            expand_opt: pb::edge_expand::ExpandOpt::Edge as i32,
+            params: all_columns(),
+            ..Default::default()
+        })
+    }
+
+    fn get_end_v(alias: &str) -> Opr {
+        Opr::Vertex(pb::GetV {
+            alias: Some(alias.into()),
+            opt: pb::get_v::VOpt::End as i32,
+            params: all_columns(),
+            ..Default::default()
+        })
+    }
+
+    fn project(mappings: Vec<(&str, &str)>) -> Opr {
+        Opr::Project(pb::Project {
+            mappings: mappings
+                .into_iter()
+                .map(|(mapping, alias)| pb::project::ExprAlias {
+                    expr: expr(mapping),
+                    alias: Some(alias.into()),
+                })
+                .collect(),
+            is_append: false,
+            ..Default::default()
+        })
+    }
+
+    fn sink(tags: Vec<Option<&str>>) -> Opr {
+        Opr::Sink(pb::Sink {
+            tags: tags
+                .into_iter()
+                .map(|tag| common_pb::NameOrIdKey { key: tag.map(Into::into) })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn params_of(plan: &pb::LogicalPlan, index: usize) -> pb::QueryParams {
+        match opr_of(plan, index) {
+            Some(Opr::Scan(scan)) => scan.params.clone().unwrap(),
+            Some(Opr::Edge(edge)) => edge.params.clone().unwrap(),
+            Some(Opr::Vertex(getv)) => getv.params.clone().unwrap(),
+            opr => panic!("unexpected operator {:?}", opr),
+        }
+    }
+
+    fn columns_of(plan: &pb::LogicalPlan, index: usize) -> Vec<common_pb::NameOrId> {
+        let params = params_of(plan, index);
+        assert!(!params.is_all_columns);
+        params.columns
+    }
+
+    #[test]
+    fn prune_columns_of_chain() {
+        // g.V().as("a").has("age", gt(10)).outE().as("e").inV().as("b").where(b.lang == "java")
+        //  .order().by(b.name).select(a.name, e.weight, b).limit(10)
+        let mut plan = chain_of(vec![
+            scan("a", None),
+            Opr::Select(pb::Select { predicate: expr("@.age > 10") }),
+            expand_edge("e"),
+            get_end_v("b"),
+            Opr::Select(pb::Select { predicate: expr("@b.lang == \"java\"") }),
+            Opr::OrderBy(pb::OrderBy {
+                pairs: vec![pb::order_by::OrderingPair { key: Some(var("@b.name")), ..Default::default() }],
+                ..Default::default()
+            }),
+            project(vec![("@a.name", "x"), ("@e.weight", "w"), ("@b", "y")]),
+            Opr::Limit(pb::Limit { range: Some(pb::Range { lower: 0, upper: 10 }) }),
+            sink(vec![Some("x"), Some("w"), Some("y")]),
+        ]);
+        assert!(prune_columns(&mut plan).unwrap());
+        assert_eq!(columns_of(&plan, 0), vec!["age".into(), "name".into()]);
+        assert_eq!(columns_of(&plan, 2), vec!["weight".into()]);
+        // `@b` is sent back as `y`
+        assert!(params_of(&plan, 3).is_all_columns);
+    }
+
+    #[test]
+    fn keep_all_columns_of_sunk_elements() {
+        // the head is sent back
+        let mut plan = chain_of(vec![
+            scan("a", Some("@.age > 10")),
+            expand_edge("e"),
+            get_end_v("b"),
+            sink(vec![None]),
+        ]);
+        assert!(prune_columns(&mut plan).unwrap());
+        assert_eq!(columns_of(&plan, 0), vec!["age".into()]);
+        assert!(columns_of(&plan, 1).is_empty());
+        assert!(params_of(&plan, 2).is_all_columns);
+
+        // the tags are sent back, either bare or through a bare-tag projection
+        let mut plan = chain_of(vec![
+            scan("a", None),
+            expand_edge("e"),
+            get_end_v("b"),
+            project(vec![("@b", "c"), ("@a.name", "d")]),
+            sink(vec![Some("c"), Some("d")]),
+        ]);
+        assert!(prune_columns(&mut plan).unwrap());
+        assert_eq!(columns_of(&plan, 0), vec!["name".into()]);
+        assert!(columns_of(&plan, 1).is_empty());
+        assert!(params_of(&plan, 2).is_all_columns);
+
+        // all the tags are sent back if none is given
+        let mut plan = chain_of(vec![scan("a", None), expand_edge("e"), get_end_v("b"), sink(vec![])]);
+        assert!(prune_columns(&mut plan).unwrap());
+        assert!(params_of(&plan, 0).is_all_columns);
+        assert!(params_of(&plan, 1).is_all_columns);
+        assert!(params_of(&plan, 2).is_all_columns);
+    }
+
+    #[test]
+    fn prune_columns_through_aliases() {
+        // the vertex projected as a tag is still the scanned one, as well as the head of `As`
+        let mut plan = chain_of(vec![
+            scan("a", Some("@.name == \"marko\"")),
+            Opr::As(pb::As { alias: Some("c".into()) }),
+            project(vec![("@c", "d")]),
+            Opr::Select(pb::Select { predicate: expr("@.age > 10 && @d.id > 1") }),
+        ]);
+        assert!(prune_columns(&mut plan).unwrap());
+        assert_eq!(columns_of(&plan, 0), vec!["age".into(), "id".into(), "name".into()]);
+    }
+
+    #[test]
+    fn keep_all_columns_conservatively() {
+        // all the properties referred to
+        let mut plan = chain_of(vec![scan("a", None), expand_edge("e"), project(vec![("@a.~all", "x")])]);
+        assert!(prune_columns(&mut plan).unwrap());
+        assert!(params_of(&plan, 0).is_all_columns);
+        assert!(columns_of(&plan, 1).is_empty());
+
+        // a property of an element that cannot be resolved, e.g., the count of a group
+        let mut plan = chain_of(vec![
+            scan("a", None),
+            Opr::GroupBy(pb::GroupBy {
+                functions: vec![pb::group_by::AggFunc {
+                    vars: vec![var("@a")],
+                    alias: Some("c".into()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            Opr::Select(pb::Select { predicate: expr("@c.name == \"marko\"") }),
+        ]);
+        let expected = plan.clone();
+        assert!(!prune_columns(&mut plan).unwrap());
+        assert_eq!(plan, expected);
+
+        // the operators upstream of an apply, with the subtask [3, 4]
+        let mut plan = chain_of(vec![
+            scan("a", None),
+            Opr::Apply(pb::Apply { subtask: 3, ..Default::default() }),
+            project(vec![("@a.name", "x")]),
+            expand_edge("e"),
+            get_end_v("b"),
+        ]);
+        plan.nodes[2].children.clear();
+        assert!(prune_columns(&mut plan).unwrap());
+        assert!(params_of(&plan, 0).is_all_columns);
+        assert!(params_of(&plan, 3).is_all_columns);
+        assert!(params_of(&plan, 4).is_all_columns);
+    }
+}
@@ -663,7 +663,9 @@ pub extern "C" fn build_physical_plan(
     let mut plan_meta = plan.meta.clone();
     let mut builder = PlanBuilder::new(plan_id);
     // let mut builder = PlanBuilder::default();
-    let build_result = plan.add_job_builder(&mut builder, &mut plan_meta);
+    let prune_result =
+        if plan.meta.is_column_pruning() { plan.prune_columns().map(|_| ()) } else { Ok(()) };
+    let build_result = prune_result.and_then(|_| plan.add_job_builder(&mut builder, &mut plan_meta));
     let result = match build_result {
         Ok(_) => {
             let physical_plan = builder.build();
@@ -685,6 +687,28 @@ pub extern "C" fn build_physical_plan(
     result
 }
 
+/// To pad the tags that are missing in some branches of a `Union` as `None` while building the
+/// physical plan, rather than rejecting the plan.
+#[no_mangle]
+pub extern "C" fn set_allow_missing_tags(ptr_plan: *const c_void) -> FfiResult {
+    let mut plan = unsafe { Box::from_raw(ptr_plan as *mut LogicalPlan) };
+    plan.meta = plan.meta.clone().with_allow_missing_tags();
+    std::mem::forget(plan);
+
+    FfiResult::success()
+}
+
+/// To populate the columns of the graph operators with only the properties referred to downstream
+/// while building the physical plan, rather than fetching the columns as given.
+#[no_mangle]
+pub extern "C" fn set_column_pruning(ptr_plan: *const c_void) -> FfiResult {
+    let mut plan = unsafe { Box::from_raw(ptr_plan as *mut LogicalPlan) };
+    plan.meta = plan.meta.clone().with_column_pruning();
+    std::mem::forget(plan);
+
+    FfiResult::success()
+}
+
 fn append_operator(
     ptr_plan: *const c_void, operator: pb::logical_plan::Operator, parent_ids: Vec<i32>, id: *mut i32,
 ) -> FfiResult {
@@ -1239,7 +1263,7 @@ mod join {
     #[no_mangle]
     pub extern "C" fn init_join_operator(join_kind: FfiJoinKind) -> *const c_void {
         let kind = unsafe { std::mem::transmute(join_kind) };
-        let join = Box::new(pb::Join { left_keys: vec![], right_keys: vec![], kind });
+        let join = Box::new(pb::Join { left_keys: vec![], right_keys: vec![], kind, null_policy: 0 });
         Box::into_raw(join) as *const c_void
     }
 
@@ -1558,7 +1582,7 @@ mod orderby {
     /// To initialize an orderby operator
     #[no_mangle]
     pub extern "C" fn init_orderby_operator() -> *const c_void {
-        let order = Box::new(pb::OrderBy { pairs: vec![], limit: None });
+        let order = Box::new(pb::OrderBy { pairs: vec![], limit: None, unstable: false });
         Box::into_raw(order) as *const c_void
     }
 
@@ -1578,7 +1602,7 @@ mod orderby {
             };
             orderby
                 .pairs
-                .push(pb::order_by::OrderingPair { key: key_result.ok(), order });
+                .push(pb::order_by::OrderingPair { key: key_result.ok(), order, null_order: 0 });
         } else {
             result = key_result.err().unwrap();
         }
@@ -1604,7 +1628,7 @@ mod orderby {
             };
             orderby
                 .pairs
-                .push(pb::order_by::OrderingPair { key: key_result.ok(), order });
+                .push(pb::order_by::OrderingPair { key: key_result.ok(), order, null_order: 0 });
         } else {
             result = key_result.err().unwrap();
         }
@@ -1619,6 +1643,17 @@ mod orderby {
         set_range(ptr_orderby, lower, upper, InnerOpt::OrderBy)
     }
 
+    /// Set whether the orderby operator may put the equal records out of their original order,
+    /// which is kept by default
+    #[no_mangle]
+    pub extern "C" fn set_orderby_unstable(ptr_orderby: *const c_void, unstable: bool) -> FfiResult {
+        let mut orderby = unsafe { Box::from_raw(ptr_orderby as *mut pb::OrderBy) };
+        orderby.unstable = unstable;
+        std::mem::forget(orderby);
+
+        FfiResult::success()
+    }
+
     /// Append an orderby operator to the logical plan
     #[no_mangle]
     pub extern "C" fn append_orderby_operator(
@@ -1640,7 +1675,7 @@ mod dedup {
     /// To initialize a dedup operator
     #[no_mangle]
     pub extern "C" fn init_dedup_operator() -> *const c_void {
-        let dedup = Box::new(pb::Dedup { keys: vec![] });
+        let dedup = Box::new(pb::Dedup { keys: vec![], dedup_key: None });
         Box::into_raw(dedup) as *const c_void
     }
 
@@ -1675,6 +1710,20 @@ mod dedup {
         result
     }
 
+    /// Set an expression whose result is used as the key for de-duplicating, instead of the keys.
+    #[no_mangle]
+    pub extern "C" fn set_dedup_key_expr(ptr_dedup: *const c_void, cstr_expr: *const c_char) -> FfiResult {
+        let mut result = FfiResult::success();
+        let mut dedup = unsafe { Box::from_raw(ptr_dedup as *mut pb::Dedup) };
+        match cstr_to_expr_pb(cstr_expr) {
+            Ok(expr) => dedup.dedup_key = Some(expr),
+            Err(e) => result = e,
+        }
+        std::mem::forget(dedup);
+
+        result
+    }
+
     /// Append a dedup operator to the logical plan
     #[no_mangle]
     pub extern "C" fn append_dedup_operator(
@@ -1696,7 +1745,7 @@ mod unfold {
     /// To initialize an unfold operator
     #[no_mangle]
     pub extern "C" fn init_unfold_operator() -> *const c_void {
-        let unfold = Box::new(pb::Unfold { tag: None, alias: None, meta_data: None });
+        let unfold = Box::new(pb::Unfold { tag: None, alias: None, meta_data: None, keep_empty: false });
         Box::into_raw(unfold) as *const c_void
     }
 
@@ -1724,6 +1773,17 @@ mod unfold {
         result
     }
 
+    /// Set whether the unfold operator emits one null element for an empty collection,
+    /// which emits nothing by default
+    #[no_mangle]
+    pub extern "C" fn set_unfold_keep_empty(ptr_unfold: *const c_void, keep_empty: bool) -> FfiResult {
+        let mut unfold = unsafe { Box::from_raw(ptr_unfold as *mut pb::Unfold) };
+        unfold.keep_empty = keep_empty;
+        std::mem::forget(unfold);
+
+        FfiResult::success()
+    }
+
     /// To set the meta_data for the unfold operator
     #[no_mangle]
     pub extern "C" fn set_unfold_meta(ptr_unfold: *const c_void, ptr_meta: FfiPbPointer) -> FfiResult {
@@ -2079,6 +2139,7 @@ mod sink {
                     id_name_mappings: vec![],
                 })),
             }),
+            tagged_targets: vec![],
         });
         Box::into_raw(sink_opr) as *const c_void
     }
@@ -2094,8 +2155,10 @@ mod sink {
                 inner: Some(pb::sink::sink_target::Inner::SinkVineyard(pb::SinkVineyard {
                     graph_name,
                     graph_schema: None,
+                    extra: Default::default(),
                 })),
             }),
+            tagged_targets: vec![],
         });
         Box::into_raw(sink_opr) as *const c_void
     }
@@ -2342,6 +2405,7 @@ mod graph {
             result_opt: unsafe { std::mem::transmute::<PathResultOpt, i32>(result_opt) },
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         });
 
         Box::into_raw(pathxpd) as *const c_void
@@ -2366,6 +2430,7 @@ mod graph {
             result_opt: unsafe { std::mem::transmute::<PathResultOpt, i32>(result_opt) },
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         });
 
         Box::into_raw(pathxpd) as *const c_void
@@ -2564,6 +2629,7 @@ mod subtask {
             tags: vec![],
             subtask: subtask_root,
             alias: None,
+            lateral: false,
         });
 
         Box::into_raw(apply) as *const c_void
@@ -2586,6 +2652,17 @@ mod subtask {
         result
     }
 
+    /// Set the apply to be lateral, such that all the tags bound in the input relation are in the
+    /// scope of the subtask.
+    #[no_mangle]
+    pub extern "C" fn set_apply_lateral(ptr_apply: *const c_void, lateral: bool) -> FfiResult {
+        let mut apply = unsafe { Box::from_raw(ptr_apply as *mut pb::Apply) };
+        apply.lateral = lateral;
+        std::mem::forget(apply);
+
+        FfiResult::success()
+    }
+
     #[no_mangle]
     pub extern "C" fn set_apply_alias(ptr_apply: *const c_void, alias: FfiAlias) -> FfiResult {
         set_alias(ptr_apply, alias, InnerOpt::Apply)
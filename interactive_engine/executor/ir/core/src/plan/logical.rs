@@ -25,11 +25,12 @@ use ir_common::error::ParsePbError;
 use ir_common::generated::algebra as pb;
 use ir_common::generated::algebra::pattern::binder::Item;
 use ir_common::generated::common as common_pb;
-use ir_common::{KeyId, LabelId, NameOrId};
+use ir_common::{validate_aliases, KeyId, LabelId, NameOrId};
 use vec_map::VecMap;
 
 use crate::error::{IrError, IrResult};
 use crate::glogue::error::IrPatternError;
+use crate::plan::column_pruning::prune_columns;
 use crate::plan::meta::{ColumnsOpt, PlanMeta, Schema, StoreMeta, TagId, INVALID_META_ID, STORE_META};
 use crate::plan::patmat::{ExtendStrategy, MatchingStrategy, NaiveStrategy};
 
@@ -129,6 +130,7 @@ impl TryFrom<pb::LogicalPlan> for LogicalPlan {
     type Error = ParsePbError;
 
     fn try_from(pb: pb::LogicalPlan) -> Result<Self, Self::Error> {
+        validate_aliases(&pb)?;
         let nodes_pb = pb.nodes;
         let mut plan = LogicalPlan::with_root();
         let mut id_map = HashMap::<NodeId, NodeId>::new();
@@ -229,6 +231,65 @@ fn clone_node(node: NodeType) -> Node {
 // Implement some private functions
 #[allow(dead_code)]
 impl LogicalPlan {
+    /// Validate that the tags referred to in the subtask of a lateral apply are all in scope,
+    /// namely, bound either in the input relation of the apply, i.e., at the ancestors of `parent_ids`,
+    /// or within the subtask itself, including all its branches and the subtasks nested in it.
+    fn validate_lateral_apply(&self, apply: &pb::Apply, parent_ids: &[NodeId]) -> IrResult<()> {
+        let subtask_nodes = self.collect_nodes(&[apply.subtask as NodeId], |node| {
+            let mut next: Vec<NodeId> = node.children.iter().cloned().collect();
+            if let Some(pb::logical_plan::operator::Opr::Apply(nested)) = node.opr.opr.as_ref() {
+                next.push(nested.subtask as NodeId);
+            }
+            next
+        });
+        let mut scope_nodes = self.collect_nodes(parent_ids, |node| node.parents.iter().cloned().collect());
+        scope_nodes.extend(subtask_nodes.iter().cloned());
+
+        let mut tags = apply.tags.clone();
+        for node_id in &subtask_nodes {
+            if let Some(node) = self.get_node(*node_id) {
+                collect_referred_tags(&node.borrow().opr, &mut tags);
+            }
+        }
+        for tag in tags {
+            let tag_id = match tag.item {
+                Some(common_pb::name_or_id::Item::Id(id)) => Some(id as TagId),
+                Some(common_pb::name_or_id::Item::Name(ref name)) => self.meta.get_tag_id(name),
+                None => None,
+            };
+            let is_in_scope = tag_id
+                .map(|tag_id| {
+                    self.meta
+                        .get_tag_nodes(tag_id)
+                        .iter()
+                        .any(|node_id| scope_nodes.contains(node_id))
+                })
+                .unwrap_or(false);
+            if !is_in_scope {
+                return Err(IrError::TagNotExist(tag.try_into()?));
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the nodes reachable from `start_ids`, including themselves, where `next` gives the
+    /// nodes to visit after a node.
+    fn collect_nodes<F: Fn(&Node) -> Vec<NodeId>>(
+        &self, start_ids: &[NodeId], next: F,
+    ) -> BTreeSet<NodeId> {
+        let mut visited = BTreeSet::new();
+        let mut queue: VecDeque<NodeId> = start_ids.iter().cloned().collect();
+        while let Some(node_id) = queue.pop_front() {
+            if !visited.insert(node_id) {
+                continue;
+            }
+            if let Some(node) = self.get_node(node_id) {
+                queue.extend(next(&node.borrow()));
+            }
+        }
+        visited
+    }
+
     /// Get the corresponding merge node of the given branch node.
     fn get_merge_node(&self, branch_node: NodeType) -> Option<NodeType> {
         if branch_node.borrow().children.len() > 1 {
@@ -494,9 +555,15 @@ impl LogicalPlan {
         if let Ok(store_meta) = STORE_META.read() {
             opr.preprocess(&store_meta, &mut self.meta)?;
         }
+        if let Some(Opr::Apply(apply)) = opr.opr.as_ref() {
+            if apply.lateral {
+                self.validate_lateral_apply(apply, &parent_ids)?;
+            }
+        }
 
         let new_curr_node_rst = match opr.opr.as_ref() {
             Some(Opr::Pattern(pattern)) => {
+                pattern.validate()?;
                 if parent_ids.len() == 1 {
                     // We try to match via ExtendStrategy. If not supported, match via NaiveStrategy.
 
@@ -612,6 +679,30 @@ impl LogicalPlan {
         node
     }
 
+    /// Populate the columns of the graph operators with the properties referred to downstream,
+    /// as `column_pruning::prune_columns()` does, and return whether the plan is rewritten.
+    pub fn prune_columns(&mut self) -> IrResult<bool> {
+        use pb::logical_plan::operator::Opr;
+
+        // the nodes of the pb plan are in the order of the nodes of this plan
+        let mut plan_pb = pb::LogicalPlan::from(self.clone());
+        if !prune_columns(&mut plan_pb)? {
+            return Ok(false);
+        }
+        for ((_, node), node_pb) in self.nodes.iter().zip(plan_pb.nodes.into_iter()) {
+            // only the query params of the graph operators are rewritten
+            if let Some(opr) = node_pb.opr.filter(|opr| {
+                matches!(
+                    opr.opr,
+                    Some(Opr::Scan(_)) | Some(Opr::Edge(_)) | Some(Opr::Vertex(_)) | Some(Opr::Path(_))
+                )
+            }) {
+                node.borrow_mut().opr = opr;
+            }
+        }
+        Ok(true)
+    }
+
     /// Append branch plans to a certain node which has **no** children in this logical plan.
     fn append_branch_plans(&mut self, connect_node: NodeType, branch_plans: Vec<LogicalPlan>) {
         if !connect_node.borrow().children.is_empty() {
@@ -993,7 +1084,14 @@ fn preprocess_var(
     let tag =
         if let Some(tag) = var.tag.as_mut() { Some(get_or_set_tag_id(tag, plan_meta)?) } else { None };
     let mut node_meta = plan_meta.curr_node_meta_mut();
-    if let Some(property) = var.property.as_mut() {
+    // the property of a vertex in a path, e.g., `@p.~[-1].name`, is a column of the path
+    let property = match var.property.as_mut() {
+        Some(common_pb::Property { item: Some(common_pb::property::Item::PathElem(path_elem)) }) => {
+            path_elem.property.as_deref_mut()
+        }
+        property => property,
+    };
+    if let Some(property) = property {
         if let Some(key) = property.item.as_mut() {
             match key {
                 common_pb::property::Item::Key(key) => {
@@ -1125,6 +1223,12 @@ fn preprocess_expression(
                     }
                     count = 0;
                 }
+                common_pb::expr_opr::Item::UdfFunc(udf) => {
+                    for param in &mut udf.parameters {
+                        preprocess_expression(param, meta, plan_meta, is_predicate)?;
+                    }
+                    count = 0;
+                }
                 _ => count = 0,
             }
         }
@@ -1191,6 +1295,110 @@ fn get_or_set_tag_id(tag_pb: &mut common_pb::NameOrId, plan_meta: &mut PlanMeta)
     }
 }
 
+/// Collect the tags that the operator refers to, e.g., the tags of the variables in its expressions.
+pub(crate) fn collect_referred_tags(opr: &pb::logical_plan::Operator, tags: &mut Vec<common_pb::NameOrId>) {
+    use pb::logical_plan::operator::Opr;
+
+    fn collect_var(var: &common_pb::Variable, tags: &mut Vec<common_pb::NameOrId>) {
+        if let Some(tag) = var.tag.as_ref() {
+            tags.push(tag.clone());
+        }
+    }
+
+    fn collect_expr(expr: &common_pb::Expression, tags: &mut Vec<common_pb::NameOrId>) {
+        for opr in &expr.operators {
+            match opr.item.as_ref() {
+                Some(common_pb::expr_opr::Item::Var(var)) => collect_var(var, tags),
+                Some(common_pb::expr_opr::Item::Vars(vars))
+                | Some(common_pb::expr_opr::Item::VarMap(vars)) => {
+                    vars.keys
+                        .iter()
+                        .for_each(|var| collect_var(var, tags));
+                }
+                Some(common_pb::expr_opr::Item::Map(key_values)) => {
+                    for key_val in &key_values.key_vals {
+                        if let Some(common_pb::variable_key_value::Value::Val(var)) = key_val.value.as_ref()
+                        {
+                            collect_var(var, tags);
+                        }
+                    }
+                }
+                Some(common_pb::expr_opr::Item::UdfFunc(udf)) => {
+                    udf.parameters
+                        .iter()
+                        .for_each(|param| collect_expr(param, tags));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_params(params: Option<&pb::QueryParams>, tags: &mut Vec<common_pb::NameOrId>) {
+        if let Some(pred) = params.and_then(|params| params.predicate.as_ref()) {
+            collect_expr(pred, tags);
+        }
+    }
+
+    match opr.opr.as_ref() {
+        Some(Opr::Scan(scan)) => collect_params(scan.params.as_ref(), tags),
+        Some(Opr::Edge(edge)) => {
+            tags.extend(edge.v_tag.clone());
+            collect_params(edge.params.as_ref(), tags);
+        }
+        Some(Opr::Vertex(getv)) => {
+            tags.extend(getv.tag.clone());
+            collect_params(getv.params.as_ref(), tags);
+        }
+        Some(Opr::Path(path)) => {
+            tags.extend(path.start_tag.clone());
+            if let Some(cond) = path.condition.as_ref() {
+                collect_expr(cond, tags);
+            }
+        }
+        Some(Opr::Project(project)) => {
+            for mapping in &project.mappings {
+                if let Some(expr) = mapping.expr.as_ref() {
+                    collect_expr(expr, tags);
+                }
+            }
+        }
+        Some(Opr::Select(select)) => {
+            if let Some(pred) = select.predicate.as_ref() {
+                collect_expr(pred, tags);
+            }
+        }
+        Some(Opr::Dedup(dedup)) => dedup
+            .keys
+            .iter()
+            .for_each(|var| collect_var(var, tags)),
+        Some(Opr::OrderBy(order)) => order
+            .pairs
+            .iter()
+            .filter_map(|pair| pair.key.as_ref())
+            .for_each(|var| collect_var(var, tags)),
+        Some(Opr::GroupBy(group)) => {
+            group
+                .mappings
+                .iter()
+                .filter_map(|mapping| mapping.key.as_ref())
+                .for_each(|var| collect_var(var, tags));
+            group
+                .functions
+                .iter()
+                .flat_map(|func| func.vars.iter())
+                .for_each(|var| collect_var(var, tags));
+        }
+        Some(Opr::Unfold(unfold)) => tags.extend(unfold.tag.clone()),
+        Some(Opr::Apply(apply)) => tags.extend(apply.tags.iter().cloned()),
+        Some(Opr::Sink(sink)) => tags.extend(
+            sink.tags
+                .iter()
+                .filter_map(|tag| tag.key.clone()),
+        ),
+        _ => {}
+    }
+}
+
 /// Process the columns' meta in `plan_meta` such that the columns can be added to
 /// corresponding nodes.
 fn process_columns_meta(plan_meta: &mut PlanMeta, is_late_project: bool) -> IrResult<()> {
@@ -1302,8 +1510,9 @@ impl AsLogical for pb::Scan {
             let tag_id = get_or_set_tag_id(alias, plan_meta)?;
             plan_meta.set_tag_nodes(tag_id, vec![plan_meta.get_curr_node()]);
         }
+        let is_index_scan = self.is_index_scan();
         if let Some(params) = self.params.as_mut() {
-            if self.idx_predicate.is_none() {
+            if !is_index_scan {
                 if let Some(expr) = params.predicate.as_mut() {
                     let idx_pred =
                         triplet_to_index_predicate(expr, &params.tables, self.scan_opt != 1, meta)?;
@@ -1396,6 +1605,9 @@ impl AsLogical for pb::Dedup {
         for var in self.keys.iter_mut() {
             preprocess_var(var, meta, plan_meta, false)?;
         }
+        if let Some(dedup_key) = self.dedup_key.as_mut() {
+            preprocess_expression(dedup_key, meta, plan_meta, false)?;
+        }
         process_columns_meta(plan_meta, false)?;
 
         Ok(())
@@ -1489,7 +1701,7 @@ fn is_whole_graph(operator: &pb::logical_plan::Operator) -> bool {
     if let Some(opr) = &operator.opr {
         match opr {
             pb::logical_plan::operator::Opr::Scan(scan) => {
-                scan.idx_predicate.is_none()
+                !scan.is_index_scan()
                     && scan.alias.is_none()
                     && scan
                         .params
@@ -1598,7 +1810,11 @@ impl AsLogical for pb::Sample {
 
 impl AsLogical for pb::Sink {
     fn preprocess(&mut self, _meta: &StoreMeta, plan_meta: &mut PlanMeta) -> IrResult<()> {
-        for tag_key in self.tags.iter_mut() {
+        let tagged_targets_tags = self
+            .tagged_targets
+            .iter_mut()
+            .flat_map(|target| target.tags.iter_mut());
+        for tag_key in self.tags.iter_mut().chain(tagged_targets_tags) {
             if let Some(tag) = tag_key.key.as_mut() {
                 get_or_set_tag_id(tag, plan_meta)?;
             }
@@ -1867,6 +2083,22 @@ mod test {
         assert_eq!(parents, vec![1]);
     }
 
+    #[test]
+    fn logical_plan_from_pb_with_duplicated_aliases() {
+        let as_opr = |alias: i32| pb::logical_plan::Operator {
+            opr: Some(pb::logical_plan::operator::Opr::As(pb::As { alias: Some(alias.into()) })),
+        };
+        let plan_pb = |alias: i32| pb::LogicalPlan {
+            nodes: vec![
+                pb::logical_plan::Node { opr: Some(as_opr(0)), children: vec![1] },
+                pb::logical_plan::Node { opr: Some(as_opr(alias)), children: vec![] },
+            ],
+            roots: vec![0],
+        };
+        assert!(LogicalPlan::try_from(plan_pb(1)).is_ok());
+        assert!(LogicalPlan::try_from(plan_pb(0)).is_err());
+    }
+
     #[test]
     fn logical_plan_from_pb() {
         let opr = pb::logical_plan::Operator {
@@ -1969,6 +2201,42 @@ mod test {
         assert!(node1.children.is_empty());
     }
 
+    #[test]
+    fn logical_plan_prune_columns() {
+        let mut plan = LogicalPlan::with_root();
+        let scan = pb::Scan {
+            params: Some(pb::QueryParams { is_all_columns: true, ..Default::default() }),
+            ..Default::default()
+        };
+        let project = pb::Project {
+            mappings: vec![pb::project::ExprAlias {
+                expr: str_to_expr_pb("@.name".to_string()).ok(),
+                alias: Some("a".into()),
+            }],
+            is_append: false,
+            ..Default::default()
+        };
+        plan.append_operator_as_node(scan.into(), vec![0])
+            .unwrap();
+        plan.append_operator_as_node(project.into(), vec![1])
+            .unwrap();
+
+        assert!(plan.prune_columns().unwrap());
+        let plan_pb = pb::LogicalPlan::from(plan);
+        match plan_pb.nodes[1]
+            .opr
+            .as_ref()
+            .and_then(|opr| opr.opr.as_ref())
+        {
+            Some(pb::logical_plan::operator::Opr::Scan(scan)) => {
+                let params = scan.params.as_ref().unwrap();
+                assert!(!params.is_all_columns);
+                assert_eq!(params.columns.len(), 1);
+            }
+            opr => panic!("unexpected operator {:?}", opr),
+        }
+    }
+
     #[test]
     fn preprocess_expr() {
         let mut plan_meta = PlanMeta::default();
@@ -2120,6 +2388,40 @@ mod test {
             // node2 with tag b has a new column "id", which is mapped to 0
             vec![0.into()]
         );
+
+        // the variables in the arguments of a function call are also preprocessed
+        let mut expression = str_to_expr_pb("toUpper(@b.name)".to_string()).unwrap();
+        preprocess_expression(&mut expression, &meta, &mut plan_meta, false).unwrap();
+        let opr = expression.operators.get(0).unwrap().clone();
+        match opr.item.unwrap() {
+            common_pb::expr_opr::Item::UdfFunc(udf) => {
+                match udf.parameters[0].operators[0]
+                    .item
+                    .clone()
+                    .unwrap()
+                {
+                    common_pb::expr_opr::Item::Var(var) => {
+                        match var.property.unwrap().item.unwrap() {
+                            Item::Key(key) => assert_eq!(key, 1.into()),
+                            _ => panic!(),
+                        }
+                        assert_eq!(var.tag.unwrap(), (b_id as i32).into());
+                    }
+                    _ => panic!(),
+                }
+            }
+            _ => panic!(),
+        }
+        assert_eq!(
+            plan_meta
+                .get_curr_node_meta()
+                .unwrap()
+                .get_tag_columns()
+                .get(&Some(b_id))
+                .unwrap()
+                .get(),
+            vec![0.into(), 1.into()]
+        );
     }
 
     #[test]
@@ -2742,8 +3044,10 @@ mod test {
                     node_type: None,
                 }),
                 order: 1,
+                null_order: 0,
             }],
             limit: None,
+            unstable: false,
         };
         plan.append_operator_as_node(orderby.into(), vec![4])
             .unwrap();
@@ -3118,6 +3422,7 @@ mod test {
             tags: vec![],
             subtask: oprid as PbNodeId,
             alias: None,
+            lateral: false,
         };
         let oprid = plan
             .append_operator_as_node(apply.into(), vec![1])
@@ -3144,6 +3449,111 @@ mod test {
         );
     }
 
+    // g.V().as("a").where(out().as("b").where(@b.age == @a.age)), where `@a` refers to the outer scope
+    fn lateral_apply_plan(predicate: &str, lateral: bool) -> IrResult<NodeId> {
+        let mut plan = LogicalPlan::with_root();
+        let scan = pb::Scan {
+            scan_opt: 0,
+            alias: Some("a".into()),
+            params: Some(query_params(vec![], vec![])),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let scan_id = plan.append_operator_as_node(scan.into(), vec![0])?;
+
+        let expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec![], vec![])),
+            expand_opt: 0,
+            alias: Some("b".into()),
+            meta_data: None,
+            is_optional: false,
+        };
+        let subtask = plan.append_operator_as_node(expand.into(), vec![])?;
+        let select = pb::Select { predicate: str_to_expr_pb(predicate.to_string()).ok() };
+        plan.append_operator_as_node(select.into(), vec![subtask])?;
+
+        let apply = pb::Apply {
+            join_kind: 4, // semi join
+            tags: vec![],
+            subtask: subtask as PbNodeId,
+            alias: None,
+            lateral,
+        };
+        plan.append_operator_as_node(apply.into(), vec![scan_id])
+    }
+
+    #[test]
+    fn lateral_apply_tags_in_scope() {
+        // refer to a tag of the outer scope
+        assert!(lateral_apply_plan("@.age == @a.age", true).is_ok());
+        // refer to both the tags of the outer scope and the subtask itself
+        assert!(lateral_apply_plan("@b.age == @a.age", true).is_ok());
+        // `@c` is never bound
+        match lateral_apply_plan("@b.age == @c.age", true) {
+            Err(IrError::TagNotExist(_)) => {}
+            other => panic!("expect `TagNotExist`, while got {:?}", other),
+        }
+        // a non-lateral apply is not validated
+        assert!(lateral_apply_plan("@b.age == @c.age", false).is_ok());
+    }
+
+    // g.V().as("c"), and g.V().as("a").where(out().as("b").union(select(@.age > 0), select(predicate))),
+    // where `@c` is bound in another branch of the plan
+    fn lateral_apply_branched_plan(predicate: &str) -> IrResult<NodeId> {
+        let mut plan = LogicalPlan::with_root();
+        let scan = |alias: &str| pb::Scan {
+            scan_opt: 0,
+            alias: Some(alias.into()),
+            params: Some(query_params(vec![], vec![])),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        plan.append_operator_as_node(scan("c").into(), vec![0])?;
+        let scan_id = plan.append_operator_as_node(scan("a").into(), vec![0])?;
+
+        let expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec![], vec![])),
+            expand_opt: 0,
+            alias: Some("b".into()),
+            meta_data: None,
+            is_optional: false,
+        };
+        let subtask = plan.append_operator_as_node(expand.into(), vec![])?;
+        let select = |predicate: &str| pb::Select { predicate: str_to_expr_pb(predicate.to_string()).ok() };
+        plan.append_operator_as_node(select("@.age > 0").into(), vec![subtask])?;
+        plan.append_operator_as_node(select(predicate).into(), vec![subtask])?;
+
+        let apply = pb::Apply {
+            join_kind: 4, // semi join
+            tags: vec![],
+            subtask: subtask as PbNodeId,
+            alias: None,
+            lateral: true,
+        };
+        plan.append_operator_as_node(apply.into(), vec![scan_id])
+    }
+
+    #[test]
+    fn lateral_apply_tags_out_of_scope() {
+        assert!(lateral_apply_branched_plan("@b.age == @a.age").is_ok());
+        // `@c` is bound, but not in the input relation of the apply
+        match lateral_apply_branched_plan("@b.age == @c.age") {
+            Err(IrError::TagNotExist(_)) => {}
+            other => panic!("expect `TagNotExist`, while got {:?}", other),
+        }
+        // `@d` is never bound, referred to in a branch other than the first one of the subtask
+        match lateral_apply_branched_plan("@b.age == @d.age") {
+            Err(IrError::TagNotExist(_)) => {}
+            other => panic!("expect `TagNotExist`, while got {:?}", other),
+        }
+    }
+
     #[test]
     fn column_maintain_groupby_case1() {
         // groupBy contains tagging a keys that is further a vertex
@@ -3190,8 +3600,10 @@ mod test {
                     node_type: None,
                 }),
                 order: 0,
+                null_order: 0,
             }],
             limit: None,
+            unstable: false,
         };
         plan.append_operator_as_node(order.into(), vec![2])
             .unwrap();
@@ -3364,6 +3776,7 @@ mod test {
             tags: vec![],
             subtask: subtask as PbNodeId,
             alias: Some("~apply".into()),
+            lateral: false,
         };
         plan.append_operator_as_node(apply.into(), vec![1])
             .unwrap();
@@ -3445,8 +3858,10 @@ mod test {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(common_pb::Variable { tag: Some("a".into()), property: None, node_type: None }),
                 order: 0,
+                null_order: 0,
             }],
             limit: None,
+            unstable: false,
         };
         plan.append_operator_as_node(order.into(), vec![2])
             .unwrap();
@@ -3589,7 +4004,13 @@ mod test {
         plan.append_operator_as_node(select.into(), vec![root_id])
             .unwrap();
 
-        let apply = pb::Apply { join_kind: 4, tags: vec![], subtask: root_id as PbNodeId, alias: None };
+        let apply = pb::Apply {
+            join_kind: 4,
+            tags: vec![],
+            subtask: root_id as PbNodeId,
+            alias: None,
+            lateral: false,
+        };
         let opr_id = plan
             .append_operator_as_node(apply.into(), vec![opr_id])
             .unwrap();
@@ -3662,7 +4083,13 @@ mod test {
             .append_operator_as_node(expand.into(), vec![])
             .unwrap();
 
-        let apply = pb::Apply { join_kind: 5, tags: vec![], subtask: root_id as PbNodeId, alias: None };
+        let apply = pb::Apply {
+            join_kind: 5,
+            tags: vec![],
+            subtask: root_id as PbNodeId,
+            alias: None,
+            lateral: false,
+        };
         plan.append_operator_as_node(apply.into(), vec![1])
             .unwrap();
 
@@ -3684,6 +4111,7 @@ mod test {
                     id_name_mappings: vec![],
                 })),
             }),
+            tagged_targets: vec![],
         };
         plan.append_operator_as_node(sink.into(), vec![4])
             .unwrap();
@@ -782,6 +782,12 @@ pub struct PlanMeta {
     max_tag_id: TagId,
     /// Whether to partition the task
     is_partition: bool,
+    /// Whether to pad the tags that are missing in some branches of a `Union` as `None`,
+    /// rather than rejecting the plan
+    allow_missing_tags: bool,
+    /// Whether to populate the columns of the graph operators with the properties referred to
+    /// downstream, before building the physical plan
+    column_pruning: bool,
 }
 
 // Some constructors
@@ -797,6 +803,16 @@ impl PlanMeta {
         self.is_partition = true;
         self
     }
+
+    pub fn with_allow_missing_tags(mut self) -> Self {
+        self.allow_missing_tags = true;
+        self
+    }
+
+    pub fn with_column_pruning(mut self) -> Self {
+        self.column_pruning = true;
+        self
+    }
 }
 
 impl PlanMeta {
@@ -971,4 +987,12 @@ impl PlanMeta {
     pub fn is_partition(&self) -> bool {
         self.is_partition
     }
+
+    pub fn allow_missing_tags(&self) -> bool {
+        self.allow_missing_tags
+    }
+
+    pub fn is_column_pruning(&self) -> bool {
+        self.column_pruning
+    }
 }
@@ -13,6 +13,7 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+pub mod column_pruning;
 pub mod ffi;
 pub mod logical;
 pub mod meta;
@@ -893,6 +893,7 @@ impl MatchingStrategy for JoinSentence {
                         left_keys: keys.clone(),
                         right_keys: keys,
                         kind: unsafe { std::mem::transmute(self.join_kind) },
+                        null_policy: 0,
                     }
                     .into(),
                 ),
@@ -1422,7 +1423,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("b".into()), property: None, node_type: None }
                 ],
-                kind: 0
+                kind: 0,
+                null_policy: 0
             }
             .into()
         );
@@ -1495,7 +1497,8 @@ mod test {
                     common_pb::Variable { tag: Some("b".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("c".into()), property: None, node_type: None }
                 ],
-                kind: 0
+                kind: 0,
+                null_policy: 0
             }
             .into()
         );
@@ -1535,7 +1538,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("c".into()), property: None, node_type: None }
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1587,7 +1591,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("d".into()), property: None, node_type: None },
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1604,7 +1609,8 @@ mod test {
                     common_pb::Variable { tag: Some("b".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("d".into()), property: None, node_type: None }
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1643,7 +1649,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("c".into()), property: None, node_type: None },
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1660,7 +1667,8 @@ mod test {
                     common_pb::Variable { tag: Some("b".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("d".into()), property: None, node_type: None }
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1691,7 +1699,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("b".into()), property: None, node_type: None },
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1724,7 +1733,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("b".into()), property: None, node_type: None },
                 ],
-                kind: 5 // anti join
+                kind: 5 // anti join,
+                null_policy: 0
             }
             .into()
         );
@@ -1769,7 +1779,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("c".into()), property: None, node_type: None },
                 ],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1809,7 +1820,8 @@ mod test {
                     property: None,
                     node_type: None
                 },],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1829,7 +1841,8 @@ mod test {
                     property: None,
                     node_type: None
                 },],
-                kind: 0 // inner join
+                kind: 0 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -1863,7 +1876,8 @@ mod test {
                     common_pb::Variable { tag: Some("a".into()), property: None, node_type: None },
                     common_pb::Variable { tag: Some("c".into()), property: None, node_type: None }
                 ],
-                kind: 5 // inner join
+                kind: 5 // inner join,
+                null_policy: 0
             }
             .into()
         );
@@ -19,18 +19,19 @@
 //! protobuf structure.
 //!
 
-use std::convert::TryInto;
+use std::collections::{BTreeSet, VecDeque};
+use std::convert::{TryFrom, TryInto};
 
 use ir_common::expr_parse::str_to_expr_pb;
 use ir_common::generated::algebra as pb;
 use ir_common::generated::common as common_pb;
 use ir_common::generated::common::expr_opr::Item;
 use ir_common::generated::physical as physical_pb;
-use ir_common::KeyId;
+use ir_common::{aliases_of, KeyId, NameOrId};
 use ir_physical_client::physical_builder::PlanBuilder;
 
 use crate::error::{IrError, IrResult};
-use crate::plan::logical::{LogicalPlan, NodeType};
+use crate::plan::logical::{collect_referred_tags, LogicalPlan, NodeType};
 use crate::plan::meta::PlanMeta;
 
 /// A trait for building physical plan (pegasus) from the logical plan
@@ -44,6 +45,21 @@ pub trait AsPhysical {
     }
 }
 
+/// To build the physical plan of a logical plan in pb, in the same way as the ffi `build_physical_plan()`
+/// does, e.g., for `graph_proxy::apis::register_physical_planner()` to explain the plan.
+pub fn build_physical_plan(
+    logical_plan: &pb::LogicalPlan, plan_id: i32,
+) -> IrResult<physical_pb::PhysicalPlan> {
+    let mut plan = LogicalPlan::try_from(logical_plan.clone())?;
+    let mut plan_meta = plan.meta.clone();
+    let mut builder = PlanBuilder::new(plan_id);
+    if plan.meta.is_column_pruning() {
+        plan.prune_columns()?;
+    }
+    plan.add_job_builder(&mut builder, &mut plan_meta)?;
+    Ok(builder.build())
+}
+
 // Fetch properties before used in Project, Select, Order, Dedup, Group, Join, and Apply.
 // This is used when the storage is distributed. In case, we may not able to fetch properties of vertices directly as it may locate on a remote server.
 // e.g.,
@@ -231,9 +247,7 @@ impl AsPhysical for pb::PathExpand {
             .hop_range
             .as_ref()
             .ok_or_else(|| IrError::MissingData("PathExpand::hop_range".to_string()))?;
-        if range.upper <= range.lower || range.lower < 0 || range.upper <= 0 {
-            Err(IrError::InvalidRange(range.lower, range.upper))?
-        }
+        check_range(range)?;
         // post_process for path_expand, including add repartition, and the properties need to cache, if necessary.
         let mut path_expand = self.clone();
         path_expand.post_process(builder, plan_meta)?;
@@ -434,11 +448,110 @@ impl AsPhysical for pb::PathExpand {
                     }
                 }
             }
+            // The condition is tested on the end vertex of the path at each hop, which thus needs to carry
+            // the properties used in the condition, fetched right after shuffling to where it locates.
+            let columns = self
+                .condition
+                .as_ref()
+                .map(condition_columns)
+                .unwrap_or_default();
+            if !columns.is_empty() {
+                let expand_base = self
+                    .base
+                    .as_mut()
+                    .ok_or_else(|| IrError::MissingData("PathExpand::base".to_string()))?;
+                if let Some(getv) = expand_base.get_v.as_mut() {
+                    if let Some(params) = getv.params.as_mut() {
+                        if !params.is_all_columns {
+                            for column in columns {
+                                if !params.columns.contains(&column) {
+                                    params.columns.push(column);
+                                }
+                            }
+                        }
+                    } else {
+                        getv.params = Some(columns_params(columns));
+                    }
+                } else {
+                    let edge_expand = expand_base
+                        .edge_expand
+                        .as_ref()
+                        .ok_or_else(|| IrError::MissingData("PathExpand::base.edge_expand".to_string()))?;
+                    expand_base.get_v = Some(pb::GetV {
+                        tag: None,
+                        opt: 4, //ItSelf
+                        params: Some(columns_params(columns)),
+                        alias: edge_expand.alias.clone(),
+                        meta_data: edge_expand.meta_data.clone(),
+                    });
+                }
+            }
         }
         Ok(())
     }
 }
 
+// The properties of the head used in the condition of `PathExpand`, e.g., "name" in `@.name == "marko"`.
+fn condition_columns(condition: &common_pb::Expression) -> Vec<common_pb::NameOrId> {
+    let mut columns = vec![];
+    for opr in &condition.operators {
+        match opr.item.as_ref() {
+            Some(common_pb::expr_opr::Item::Var(var)) if var.tag.is_none() => {
+                if let Some(common_pb::property::Item::Key(key)) = var
+                    .property
+                    .as_ref()
+                    .and_then(|prop| prop.item.as_ref())
+                {
+                    if !columns.contains(key) {
+                        columns.push(key.clone());
+                    }
+                }
+            }
+            Some(common_pb::expr_opr::Item::UdfFunc(udf)) => {
+                for column in udf
+                    .parameters
+                    .iter()
+                    .flat_map(condition_columns)
+                {
+                    if !columns.contains(&column) {
+                        columns.push(column);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    columns
+}
+
+fn columns_params(columns: Vec<common_pb::NameOrId>) -> pb::QueryParams {
+    pb::QueryParams {
+        tables: vec![],
+        columns,
+        is_all_columns: false,
+        limit: None,
+        predicate: None,
+        sample_ratio: 1.0,
+        extra: Default::default(),
+    }
+}
+
+// Whether the head of the records is known to be a vertex, as the output of the last operator,
+// e.g., a vertex scan, an `EdgeExpand(ExpandV)` or a `GetV`.
+fn is_vertex_head(builder: &mut PlanBuilder) -> bool {
+    use physical_pb::physical_opr::operator::OpKind;
+    let op_kind = builder
+        .get_last_op_mut()
+        .and_then(|op| op.opr.as_ref())
+        .and_then(|opr| opr.op_kind.as_ref());
+    match op_kind {
+        Some(OpKind::Scan(scan)) => scan.scan_opt == physical_pb::scan::ScanOpt::Vertex as i32,
+        Some(OpKind::Edge(edge)) => edge.expand_opt == physical_pb::edge_expand::ExpandOpt::Vertex as i32,
+        Some(OpKind::Vertex(_)) => true,
+        _ => false,
+    }
+}
+
 // Try to apply the optimize rule: ExpandE + GetV = ExpandV, it it satisfies:
 // 1. the previous op is ExpandE, and with no alias (which means that the edges won't be accessed later).
 // 2. `GetV` is GetV(Adj) (i.e., opt=Start/End/Other) without any filters or further query semantics.
@@ -500,6 +613,13 @@ impl AsPhysical for pb::GetV {
         //          where GetV(Self) is used to filter on the adj vertex itself.
         //    or 2. if GetV without filter, directly
         //         `Source + EdgeExpand(ExpandE) + GetV(GetAdj)`
+        self.validate()?;
+        if self.tag.is_none() && self.is_adjacent() && is_vertex_head(builder) {
+            return Err(IrError::InvalidPattern(format!(
+                "`GetV` to get the adjacent vertex, while the head is a vertex rather than an edge or a path: {:?}",
+                self
+            )));
+        }
         let mut getv = self.clone();
         // If GetV(Adj) with filter, translate GetV into GetV(GetAdj) + Shuffle (if on distributed storage) + GetV(Self)
         if let Some(params) = getv.params.as_mut() {
@@ -553,10 +673,8 @@ impl AsPhysical for pb::Limit {
             .range
             .as_ref()
             .ok_or_else(|| IrError::MissingData("Limit::range".to_string()))?;
-        if range.upper <= range.lower || range.lower < 0 || range.upper <= 0 {
-            Err(IrError::InvalidRange(range.lower, range.upper))?
-        }
-        builder.limit(self.clone());
+        check_range(range)?;
+        builder.push(self.clone().try_into()?);
         Ok(())
     }
 }
@@ -564,13 +682,11 @@ impl AsPhysical for pb::Limit {
 impl AsPhysical for pb::OrderBy {
     fn add_job_builder(&self, builder: &mut PlanBuilder, plan_meta: &mut PlanMeta) -> IrResult<()> {
         if let Some(range) = &self.limit {
-            if range.upper <= range.lower || range.lower < 0 || range.upper <= 0 {
-                Err(IrError::InvalidRange(range.lower, range.upper))?
-            }
+            check_range(range)?;
         }
         let mut order = self.clone();
         order.post_process(builder, plan_meta)?;
-        builder.order(order);
+        builder.push(order.try_into()?);
         Ok(())
     }
 
@@ -580,11 +696,21 @@ impl AsPhysical for pb::OrderBy {
     }
 }
 
+/// Report an invalid range as `IrError::InvalidRange`, ahead of the validation of the conversion
+/// to the physical operator, which would report it as a `ParsePbError` instead.
+fn check_range(range: &pb::Range) -> IrResult<()> {
+    if range.upper <= range.lower || range.lower < 0 || range.upper <= 0 {
+        Err(IrError::InvalidRange(range.lower, range.upper))
+    } else {
+        Ok(())
+    }
+}
+
 impl AsPhysical for pb::Dedup {
     fn add_job_builder(&self, builder: &mut PlanBuilder, plan_meta: &mut PlanMeta) -> IrResult<()> {
         let mut dedup = self.clone();
         dedup.post_process(builder, plan_meta)?;
-        builder.dedup(dedup);
+        builder.push(dedup.try_into()?);
         Ok(())
     }
 
@@ -616,27 +742,9 @@ impl AsPhysical for pb::Unfold {
 
 impl AsPhysical for pb::Sample {
     fn add_job_builder(&self, builder: &mut PlanBuilder, plan_meta: &mut PlanMeta) -> IrResult<()> {
-        if let Some(sample_type) = &self.sample_type {
-            let sample_type = sample_type
-                .inner
-                .as_ref()
-                .ok_or_else(|| IrError::MissingData("Sample::sample_type".to_string()))?;
-            match sample_type {
-                pb::sample::sample_type::Inner::SampleByNum(num) => {
-                    if num.num <= 0 {
-                        Err(IrError::ParsePbError("SampleByNum num should be > 0".into()))?
-                    }
-                }
-                pb::sample::sample_type::Inner::SampleByRatio(ratio) => {
-                    if ratio.ratio < 0.0 || ratio.ratio > 1.0 {
-                        Err(IrError::ParsePbError("SampleByRatio ratio should be in [0, 1]".into()))?
-                    }
-                }
-            }
-        }
         let mut sample = self.clone();
         sample.post_process(builder, plan_meta)?;
-        builder.sample(self.clone());
+        builder.push(sample.try_into()?);
         Ok(())
     }
     fn post_process(&mut self, builder: &mut PlanBuilder, plan_meta: &mut PlanMeta) -> IrResult<()> {
@@ -645,54 +753,104 @@ impl AsPhysical for pb::Sample {
     }
 }
 
+/// Complete the sink target with the metadata, e.g., the mapping of tag names for the default
+/// target, and the schema of the graph for the Vineyard target.
+fn complete_sink_target(
+    target: Option<&pb::sink::SinkTarget>, plan_meta: &PlanMeta,
+) -> IrResult<pb::sink::SinkTarget> {
+    let target = target.ok_or_else(|| IrError::MissingData("Sink::sink_target".to_string()))?;
+    match target
+        .inner
+        .as_ref()
+        .ok_or_else(|| IrError::MissingData("Sink::sink_target::Inner".to_string()))?
+    {
+        pb::sink::sink_target::Inner::SinkDefault(_) => Ok(pb::sink::SinkTarget {
+            inner: Some(pb::sink::sink_target::Inner::SinkDefault(pb::SinkDefault {
+                id_name_mappings: get_tag_name_mappings(plan_meta),
+            })),
+        }),
+        pb::sink::sink_target::Inner::SinkArrow(sink_arrow) => Ok(pb::sink::SinkTarget {
+            inner: Some(pb::sink::sink_target::Inner::SinkArrow(pb::SinkArrow {
+                batch_size: sink_arrow.batch_size,
+                id_name_mappings: get_tag_name_mappings(plan_meta),
+                column_types: sink_arrow.column_types.clone(),
+            })),
+        }),
+        pb::sink::sink_target::Inner::SinkVineyard(sink_vineyard) => {
+            use crate::plan::meta::STORE_META;
+            let graph_name = sink_vineyard.graph_name.clone();
+            loop {
+                if let Ok(meta) = STORE_META.try_read() {
+                    return Ok(pb::sink::SinkTarget {
+                        inner: Some(pb::sink::sink_target::Inner::SinkVineyard(pb::SinkVineyard {
+                            graph_name,
+                            graph_schema: meta.schema.clone().map(|schema| schema.into()),
+                            extra: sink_vineyard.extra.clone(),
+                        })),
+                    });
+                }
+            }
+        }
+        pb::sink::sink_target::Inner::SinkCsv(_) => Ok(target.clone()),
+    }
+}
+
+/// The mappings from the ids of the tags to their names, for the columns to be named by the tags.
+fn get_tag_name_mappings(plan_meta: &PlanMeta) -> Vec<pb::sink_default::IdNameMapping> {
+    plan_meta
+        .get_tag_id_mappings()
+        .iter()
+        .map(|(tag, id)| pb::sink_default::IdNameMapping {
+            id: *id as KeyId,
+            name: tag.clone(),
+            meta_type: 3,
+        })
+        .collect()
+}
+
+/// In the presence of the tagged targets, each tag must be sinked into exactly one target,
+/// thus the tags of all targets must be given explicitly and be disjoint from each other.
+fn check_sink_tags(sink: &pb::Sink) -> IrResult<()> {
+    if sink.tagged_targets.is_empty() {
+        return Ok(());
+    }
+    let mut all_tags: Vec<&Option<common_pb::NameOrId>> = vec![];
+    let targets_tags = std::iter::once(&sink.tags).chain(
+        sink.tagged_targets
+            .iter()
+            .map(|target| &target.tags),
+    );
+    for tags in targets_tags {
+        if tags.is_empty() {
+            return Err(IrError::ParsePbError(
+                "the tags of each target must be given in a sink of multiple targets"
+                    .to_string()
+                    .into(),
+            ));
+        }
+        for tag in tags {
+            if all_tags.contains(&&tag.key) {
+                return Err(IrError::ParsePbError(
+                    format!("the tag {:?} is sinked into more than one target", tag.key).into(),
+                ));
+            }
+            all_tags.push(&tag.key);
+        }
+    }
+    Ok(())
+}
+
 impl AsPhysical for pb::Sink {
     fn add_job_builder(&self, builder: &mut PlanBuilder, plan_meta: &mut PlanMeta) -> IrResult<()> {
+        check_sink_tags(self)?;
         let mut sink_opr = self.clone();
-        let target = self
-            .sink_target
-            .as_ref()
-            .ok_or_else(|| IrError::MissingData("Sink::sink_target".to_string()))?;
-        match target
-            .inner
-            .as_ref()
-            .ok_or_else(|| IrError::MissingData("Sink::sink_target::Inner".to_string()))?
-        {
-            pb::sink::sink_target::Inner::SinkDefault(_) => {
-                let tag_id_mapping = plan_meta
-                    .get_tag_id_mappings()
-                    .iter()
-                    .map(|(tag, id)| pb::sink_default::IdNameMapping {
-                        id: *id as KeyId,
-                        name: tag.clone(),
-                        meta_type: 3,
-                    })
-                    .collect();
-                let sink_target = pb::sink::SinkTarget {
-                    inner: Some(pb::sink::sink_target::Inner::SinkDefault(pb::SinkDefault {
-                        id_name_mappings: tag_id_mapping,
-                    })),
-                };
-                sink_opr.sink_target = Some(sink_target);
-            }
-            pb::sink::sink_target::Inner::SinkVineyard(sink_vineyard) => {
-                use crate::plan::meta::STORE_META;
-                let graph_name = sink_vineyard.graph_name.clone();
-                loop {
-                    if let Ok(meta) = STORE_META.try_read() {
-                        let sink_target = pb::sink::SinkTarget {
-                            inner: Some(pb::sink::sink_target::Inner::SinkVineyard(pb::SinkVineyard {
-                                graph_name,
-                                graph_schema: meta.schema.clone().map(|schema| schema.into()),
-                            })),
-                        };
-                        sink_opr.sink_target = Some(sink_target);
-                        break;
-                    }
-                }
-            }
-        };
+        sink_opr.sink_target = Some(complete_sink_target(self.sink_target.as_ref(), plan_meta)?);
+        for tagged_target in sink_opr.tagged_targets.iter_mut() {
+            tagged_target.sink_target =
+                Some(complete_sink_target(tagged_target.sink_target.as_ref(), plan_meta)?);
+        }
 
-        builder.sink(sink_opr.clone());
+        builder.sink(sink_opr);
         Ok(())
     }
 }
@@ -740,6 +898,171 @@ impl AsPhysical for NodeType {
     }
 }
 
+/// The tags that the operators of a branch define, and whether the branch discards the tags
+/// defined before it, e.g., by a `GroupBy`.
+fn branch_tags(branch: &LogicalPlan) -> IrResult<(bool, BTreeSet<NameOrId>)> {
+    let mut discards = false;
+    let mut tags = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut queue: VecDeque<NodeType> = branch.get_first_node().into_iter().collect();
+    while let Some(node) = queue.pop_front() {
+        let node = node.borrow();
+        if !visited.insert(node.id) {
+            continue;
+        }
+        if let Some(opr) = node.opr.opr.as_ref() {
+            let (aliases, discard) = aliases_of(opr);
+            if discard {
+                discards = true;
+                tags.clear();
+            }
+            for alias in aliases {
+                tags.insert(alias.clone().try_into()?);
+            }
+        }
+        queue.extend(
+            node.children
+                .iter()
+                .filter_map(|child| branch.get_node(*child)),
+        );
+    }
+    Ok((discards, tags))
+}
+
+/// The tags defined by the given node and its ancestors, up to the nodes that discard the tags
+/// defined before them.
+fn upstream_tags(plan: &LogicalPlan, node: NodeType) -> IrResult<BTreeSet<NameOrId>> {
+    let mut tags = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut queue = VecDeque::from(vec![node]);
+    while let Some(node) = queue.pop_front() {
+        let node = node.borrow();
+        if !visited.insert(node.id) {
+            continue;
+        }
+        let mut discard = false;
+        if let Some(opr) = node.opr.opr.as_ref() {
+            let (aliases, discards) = aliases_of(opr);
+            for alias in aliases {
+                tags.insert(alias.clone().try_into()?);
+            }
+            discard = discards;
+        }
+        if !discard {
+            queue.extend(
+                node.parents
+                    .iter()
+                    .filter_map(|parent| plan.get_node(*parent)),
+            );
+        }
+    }
+    Ok(tags)
+}
+
+/// The tags that the given node and its descendants refer to before defining them, down to the
+/// nodes that discard the tags defined before them.
+fn downstream_referred_tags(plan: &LogicalPlan, node: NodeType) -> IrResult<BTreeSet<NameOrId>> {
+    let mut referred = BTreeSet::new();
+    let mut defined = BTreeSet::new();
+    let mut visited = BTreeSet::new();
+    let mut queue = VecDeque::from(vec![node]);
+    while let Some(node) = queue.pop_front() {
+        let node = node.borrow();
+        if !visited.insert(node.id) {
+            continue;
+        }
+        let mut tags = vec![];
+        collect_referred_tags(&node.opr, &mut tags);
+        for tag in tags {
+            let tag: NameOrId = tag.try_into()?;
+            if !defined.contains(&tag) {
+                referred.insert(tag);
+            }
+        }
+        let mut discard = false;
+        if let Some(opr) = node.opr.opr.as_ref() {
+            let (aliases, discards) = aliases_of(opr);
+            for alias in aliases {
+                defined.insert(alias.clone().try_into()?);
+            }
+            discard = discards;
+        }
+        if !discard {
+            queue.extend(
+                node.children
+                    .iter()
+                    .filter_map(|child| plan.get_node(*child)),
+            );
+        }
+    }
+    Ok(referred)
+}
+
+/// Check that the tags referred to after a `Union` are defined in all of its branches, as a tag
+/// defined in some of the branches would be missing in the records of the others. A tag defined
+/// before the `Union` is also available in the branches that do not discard it. If
+/// `PlanMeta::allow_missing_tags()`, the missing tags are instead padded as `None` at the end of
+/// the branches. Note that `Join` and `Intersect` are not checked, as the tags of their branches are merged.
+fn reconcile_union_tags(
+    plans: &mut [PlanBuilder], branches: &[LogicalPlan], upstream: &BTreeSet<NameOrId>,
+    referred: &BTreeSet<NameOrId>, plan_meta: &PlanMeta,
+) -> IrResult<()> {
+    let branch_tags = branches
+        .iter()
+        .map(branch_tags)
+        .collect::<IrResult<Vec<_>>>()?;
+    let is_available = |(discards, tags): &(bool, BTreeSet<NameOrId>), tag: &NameOrId| {
+        tags.contains(tag) || (!*discards && upstream.contains(tag))
+    };
+    // the tags missing in all the branches are not defined by the union, and are left to the
+    // operators referring to them
+    let union_tags: Vec<&NameOrId> = referred
+        .iter()
+        .filter(|tag| {
+            branch_tags
+                .iter()
+                .any(|branch| is_available(branch, tag))
+        })
+        .collect();
+    let missing_tags: Vec<Vec<&NameOrId>> = branch_tags
+        .iter()
+        .map(|branch| {
+            union_tags
+                .iter()
+                .filter(|tag| !is_available(branch, tag))
+                .cloned()
+                .collect()
+        })
+        .collect();
+    if let Some(tag) = missing_tags.iter().flatten().next() {
+        if !plan_meta.allow_missing_tags() {
+            return Err(IrError::TagNotExist((*tag).clone()));
+        }
+    }
+    for (plan, missing) in plans.iter_mut().zip(missing_tags) {
+        if !missing.is_empty() {
+            let null_expr = common_pb::Expression {
+                operators: vec![common_pb::Value {
+                    item: Some(common_pb::value::Item::None(common_pb::None {})),
+                }
+                .into()],
+            };
+            plan.project(pb::Project {
+                mappings: missing
+                    .into_iter()
+                    .map(|tag| pb::project::ExprAlias {
+                        expr: Some(null_expr.clone()),
+                        alias: Some(tag.clone().into()),
+                    })
+                    .collect(),
+                is_append: true,
+                meta_data: vec![],
+            });
+        }
+    }
+    Ok(())
+}
+
 fn extract_expand_degree(node: NodeType) -> Option<pb::EdgeExpand> {
     if let Some(pb::logical_plan::operator::Opr::Edge(edgexpd)) = &node.borrow().opr.opr {
         if edgexpd.expand_opt == 2 {
@@ -887,6 +1210,9 @@ impl AsPhysical for LogicalPlan {
 
                 match &merge_node.borrow().opr.opr {
                     Some(Union(_)) => {
+                        let upstream = upstream_tags(self, curr_node.clone())?;
+                        let referred = downstream_referred_tags(self, merge_node.clone())?;
+                        reconcile_union_tags(&mut plans, &subplans, &upstream, &referred, plan_meta)?;
                         builder.union(plans);
                     }
                     Some(Intersect(intersect)) => {
@@ -902,12 +1228,13 @@ impl AsPhysical for LogicalPlan {
 
                         post_process_vars(builder, plan_meta, false)?;
 
-                        builder.join(
+                        builder.join_with_null_policy(
                             unsafe { std::mem::transmute(join_opr.kind) },
                             left_plan,
                             right_plan,
                             join_opr.left_keys.clone(),
                             join_opr.right_keys.clone(),
+                            unsafe { std::mem::transmute(join_opr.null_policy) },
                         );
                     }
                     None => {
@@ -1090,6 +1417,7 @@ mod test {
                     id_name_mappings: vec![],
                 })),
             }),
+            tagged_targets: vec![],
         }
     }
 
@@ -1133,6 +1461,45 @@ mod test {
         assert_eq!(job_builder, expected_builder);
     }
 
+    #[test]
+    fn get_v_validate_as_physical() {
+        let plan_of = |expand_opt: Option<i32>, get_v: pb::GetV| {
+            let mut plan = LogicalPlan::with_root();
+            plan.append_operator_as_node(build_scan(vec![]).into(), vec![0])
+                .unwrap();
+            if let Some(expand_opt) = expand_opt {
+                plan.append_operator_as_node(build_edgexpd(expand_opt, vec![], None).into(), vec![1])
+                    .unwrap();
+            }
+            let parent = plan.get_max_node_id() - 1;
+            plan.append_operator_as_node(get_v.into(), vec![parent])
+                .unwrap();
+            plan.clean_redundant_nodes();
+            let mut job_builder = PlanBuilder::default();
+            let mut plan_meta = plan.meta.clone();
+            plan.add_job_builder(&mut job_builder, &mut plan_meta)
+        };
+        let get_v = |opt| pb::GetV { opt, ..build_getv(None) };
+
+        // g.V().outE().otherV()
+        assert!(plan_of(Some(1), get_v(2)).is_ok());
+        // g.V().otherV(), where there is no edge to get the other vertex from
+        match plan_of(None, get_v(2)) {
+            Err(IrError::InvalidPattern(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        // g.V().out().inV()
+        match plan_of(Some(0), get_v(1)) {
+            Err(IrError::InvalidPattern(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+        // an unknown opt
+        match plan_of(Some(1), get_v(7)) {
+            Err(IrError::ParsePbError(_)) => {}
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
     #[test]
     fn post_process_edgexpd_property_filter_as_auxilia() {
         // g.V().out().has("birthday", 20220101)
@@ -1565,7 +1932,7 @@ mod test {
         expected_builder.add_scan_source(source_opr);
         expected_builder.get_v(auxilia);
         expected_builder.edge_expand(expand_opr);
-        expected_builder.limit(limit_opr);
+        expected_builder.push(limit_opr.try_into().unwrap());
         expected_builder.sink(build_sink());
 
         assert_eq!(builder, expected_builder);
@@ -1640,6 +2007,7 @@ mod test {
             result_opt: 0,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let mut logical_plan = LogicalPlan::with_node(Node::new(0, source_opr.clone().into()));
@@ -1676,6 +2044,88 @@ mod test {
         assert_eq!(builder, expected_builder);
     }
 
+    #[test]
+    fn path_expand_with_condition_as_physical() {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec!["person".into()], vec![])),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+
+        let edge_expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec!["knows".into()], vec![])),
+            expand_opt: 0,
+            alias: None,
+            meta_data: None,
+            is_optional: false,
+        };
+
+        let path_opr = pb::PathExpand {
+            base: Some(edge_expand.clone().into()),
+            start_tag: None,
+            alias: None,
+            hop_range: Some(pb::Range { lower: 1, upper: 4 }),
+            path_opt: 0,
+            result_opt: 0,
+            condition: str_to_expr_pb(
+                "@.name == \"marko\" || @.age > 27 && @.name != \"josh\"".to_string(),
+            )
+            .ok(),
+            is_optional: false,
+            is_condition_emit_only: false,
+        };
+
+        let mut logical_plan = LogicalPlan::with_node(Node::new(0, source_opr.clone().into()));
+        logical_plan
+            .append_operator_as_node(path_opr.clone().into(), vec![0])
+            .unwrap(); // node 1
+
+        // Case without partition
+        let mut builder = PlanBuilder::default();
+        let mut plan_meta = PlanMeta::default();
+        logical_plan
+            .add_job_builder(&mut builder, &mut plan_meta)
+            .unwrap();
+
+        let mut expected_builder = PlanBuilder::default();
+        expected_builder.add_scan_source(source_opr.clone());
+        expected_builder.path_expand(path_opr.clone());
+
+        assert_eq!(builder, expected_builder);
+
+        // Case with partition, where the end vertex of each hop carries the properties used in the
+        // condition, i.e., "name" and "age"
+        let mut builder = PlanBuilder::default();
+        let mut plan_meta = PlanMeta::default();
+        plan_meta = plan_meta.with_partition();
+        logical_plan
+            .add_job_builder(&mut builder, &mut plan_meta)
+            .unwrap();
+
+        let mut expected_path_opr = path_opr;
+        expected_path_opr.base = Some(pb::path_expand::ExpandBase {
+            edge_expand: Some(edge_expand),
+            get_v: Some(pb::GetV {
+                tag: None,
+                opt: 4,
+                params: Some(query_params(vec![], vec!["name".into(), "age".into()])),
+                alias: None,
+                meta_data: None,
+            }),
+        });
+        let mut expected_builder = PlanBuilder::default();
+        expected_builder.add_scan_source(source_opr);
+        expected_builder.shuffle(None);
+        expected_builder.path_expand(expected_path_opr);
+
+        assert_eq!(builder, expected_builder);
+    }
+
     #[test]
     fn path_expand_as_physical_with_getv() {
         let source_opr = pb::Scan {
@@ -1714,6 +2164,7 @@ mod test {
             result_opt: 0,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let fused_edge_expand = pb::EdgeExpand {
@@ -1734,6 +2185,7 @@ mod test {
             result_opt: 0,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let mut logical_plan = LogicalPlan::with_node(Node::new(0, source_opr.clone().into()));
@@ -1816,6 +2268,7 @@ mod test {
             result_opt: 0,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let fused_edge_expand = pb::EdgeExpand {
@@ -1851,6 +2304,7 @@ mod test {
             result_opt: 0,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let mut logical_plan = LogicalPlan::with_node(Node::new(0, source_opr.clone().into()));
@@ -1898,7 +2352,8 @@ mod test {
             meta_data: None,
         };
 
-        let topby_opr = pb::OrderBy { pairs: vec![], limit: Some(pb::Range { lower: 10, upper: 11 }) };
+        let topby_opr =
+            pb::OrderBy { pairs: vec![], limit: Some(pb::Range { lower: 10, upper: 11 }), unstable: false };
 
         let mut logical_plan = LogicalPlan::with_node(Node::new(0, source_opr.clone().into()));
         logical_plan
@@ -1910,7 +2365,7 @@ mod test {
 
         let mut expected_builder = PlanBuilder::default();
         expected_builder.add_scan_source(source_opr);
-        expected_builder.order(topby_opr);
+        expected_builder.push(topby_opr.try_into().unwrap());
 
         assert_eq!(builder, expected_builder);
     }
@@ -1955,7 +2410,8 @@ mod test {
         plan.append_operator_as_node(select.clone().into(), vec![root_id])
             .unwrap();
 
-        let apply = pb::Apply { join_kind: 4, tags: vec![], subtask: root_id as i32, alias: None };
+        let apply =
+            pb::Apply { join_kind: 4, tags: vec![], subtask: root_id as i32, alias: None, lateral: false };
         let opr_id = plan
             .append_operator_as_node(apply.clone().into(), vec![opr_id])
             .unwrap();
@@ -2033,8 +2489,13 @@ mod test {
             .unwrap();
 
         // Select("0").by()
-        let apply =
-            pb::Apply { join_kind: 4, tags: vec![], subtask: subplan_id as i32, alias: Some(1.into()) };
+        let apply = pb::Apply {
+            join_kind: 4,
+            tags: vec![],
+            subtask: subplan_id as i32,
+            alias: Some(1.into()),
+            lateral: false,
+        };
         plan.append_operator_as_node(apply.clone().into(), vec![opr_id])
             .unwrap();
 
@@ -2109,9 +2570,14 @@ mod test {
             .unwrap();
 
         // Select().by()
-        let apply: pb::logical_plan::Operator =
-            pb::Apply { join_kind: 4, tags: vec![], subtask: subplan_id as i32, alias: Some(1.into()) }
-                .into();
+        let apply: pb::logical_plan::Operator = pb::Apply {
+            join_kind: 4,
+            tags: vec![],
+            subtask: subplan_id as i32,
+            alias: Some(1.into()),
+            lateral: false,
+        }
+        .into();
         plan.append_operator_as_node(apply.clone(), vec![opr_id])
             .unwrap();
 
@@ -2167,7 +2633,7 @@ mod test {
             meta_data: None,
             is_optional: false,
         };
-        let join_opr = pb::Join { left_keys: vec![], right_keys: vec![], kind: 0 };
+        let join_opr = pb::Join { left_keys: vec![], right_keys: vec![], kind: 0, null_policy: 0 };
         let limit_opr = pb::Limit { range: Some(pb::Range { lower: 10, upper: 11 }) };
 
         let mut logical_plan = LogicalPlan::with_node(Node::new(0, source_opr.clone().into()));
@@ -2204,7 +2670,7 @@ mod test {
             vec![],
             vec![],
         );
-        expected_builder.limit(limit_opr);
+        expected_builder.push(limit_opr.try_into().unwrap());
 
         assert_eq!(builder, expected_builder);
     }
@@ -2953,6 +3419,159 @@ mod test {
         assert_eq!(builder, expected_builder);
     }
 
+    // The plan looks like:
+    //       root(1)
+    //      /   \
+    //     2     3
+    //      \   /
+    //        4
+    //        |
+    //        5
+    // 2: out as 0
+    // 3: out, or count if `discards`
+    // 4: union
+    // 5: project @0, if `refers`
+    // other: out
+    fn create_union_of_different_tags(discards: bool, refers: bool) -> LogicalPlan {
+        let mut plan = LogicalPlan::with_root();
+        plan.append_operator_as_node(build_edgexpd(0, vec![], None).into(), vec![0])
+            .unwrap(); // root(1)
+        plan.append_operator_as_node(build_edgexpd(0, vec![], Some(0.into())).into(), vec![1])
+            .unwrap(); // node 2
+        if discards {
+            plan.append_operator_as_node(count_by_group().into(), vec![1])
+                .unwrap(); // node 3
+        } else {
+            plan.append_operator_as_node(build_edgexpd(0, vec![], None).into(), vec![1])
+                .unwrap(); // node 3
+        }
+        plan.append_operator_as_node(pb::Union { parents: vec![2, 3] }.into(), vec![2, 3])
+            .unwrap(); // node 4
+        if refers {
+            plan.append_operator_as_node(project_tag(0).into(), vec![4])
+                .unwrap(); // node 5
+        }
+
+        plan.clean_redundant_nodes();
+
+        plan
+    }
+
+    fn count_by_group() -> pb::GroupBy {
+        pb::GroupBy {
+            mappings: vec![],
+            functions: vec![pb::group_by::AggFunc {
+                vars: vec![],
+                aggregate: 3, // count
+                alias: None,
+            }],
+            meta_data: vec![],
+        }
+    }
+
+    fn project_tag(tag: KeyId) -> pb::Project {
+        pb::Project {
+            mappings: vec![pb::project::ExprAlias {
+                expr: Some(common_pb::Expression {
+                    operators: vec![common_pb::Variable {
+                        tag: Some(tag.into()),
+                        property: None,
+                        node_type: None,
+                    }
+                    .into()],
+                }),
+                alias: None,
+            }],
+            is_append: true,
+            meta_data: vec![],
+        }
+    }
+
+    #[test]
+    fn test_union_of_different_tags_to_physical() {
+        let plan = create_union_of_different_tags(false, true);
+        let mut builder = PlanBuilder::default();
+        let mut plan_meta = PlanMeta::default();
+        let err = plan
+            .add_job_builder(&mut builder, &mut plan_meta)
+            .unwrap_err();
+        match err {
+            IrError::TagNotExist(tag) => assert_eq!(tag, NameOrId::Id(0)),
+            _ => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_union_of_unreferred_tags_to_physical() {
+        // e.g., `union(out().as('x'), in())`, which does not refer to `x` after the union
+        for discards in [false, true] {
+            let plan = create_union_of_different_tags(discards, false);
+            let mut builder = PlanBuilder::default();
+            let mut plan_meta = PlanMeta::default();
+            plan.add_job_builder(&mut builder, &mut plan_meta)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_union_of_discarded_tags_to_physical() {
+        let mut plan = LogicalPlan::with_root();
+        plan.append_operator_as_node(build_edgexpd(0, vec![], Some(0.into())).into(), vec![0])
+            .unwrap(); // root(1), which defines the tag before the union
+        plan.append_operator_as_node(build_edgexpd(0, vec![], None).into(), vec![1])
+            .unwrap(); // node 2
+        plan.append_operator_as_node(count_by_group().into(), vec![1])
+            .unwrap(); // node 3, which discards the tag
+        plan.append_operator_as_node(pb::Union { parents: vec![2, 3] }.into(), vec![2, 3])
+            .unwrap(); // node 4
+        plan.append_operator_as_node(project_tag(0).into(), vec![4])
+            .unwrap(); // node 5
+        plan.clean_redundant_nodes();
+
+        let mut builder = PlanBuilder::default();
+        let mut plan_meta = PlanMeta::default();
+        let err = plan
+            .add_job_builder(&mut builder, &mut plan_meta)
+            .unwrap_err();
+        match err {
+            IrError::TagNotExist(tag) => assert_eq!(tag, NameOrId::Id(0)),
+            _ => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_union_of_different_tags_padded_to_physical() {
+        let plan = create_union_of_different_tags(false, true);
+        let mut builder = PlanBuilder::default();
+        let mut plan_meta = PlanMeta::default().with_allow_missing_tags();
+        plan.add_job_builder(&mut builder, &mut plan_meta)
+            .unwrap();
+
+        let mut expected_builder = PlanBuilder::default();
+        expected_builder.edge_expand(build_edgexpd(0, vec![], None));
+        let mut sub_builder_1 = PlanBuilder::default();
+        sub_builder_1.edge_expand(build_edgexpd(0, vec![], Some(0.into())));
+        let mut sub_builder_2 = PlanBuilder::default();
+        sub_builder_2.edge_expand(build_edgexpd(0, vec![], None));
+        sub_builder_2.project(pb::Project {
+            mappings: vec![pb::project::ExprAlias {
+                expr: Some(common_pb::Expression {
+                    operators: vec![common_pb::Value {
+                        item: Some(common_pb::value::Item::None(common_pb::None {})),
+                    }
+                    .into()],
+                }),
+                alias: Some(0.into()),
+            }],
+            is_append: true,
+            meta_data: vec![],
+        });
+        expected_builder.union(vec![sub_builder_1, sub_builder_2]);
+        expected_builder.project(project_tag(0));
+
+        assert_eq!(builder, expected_builder);
+    }
+
     #[test]
     fn path_expand_project_as_physical() {
         let source_opr = pb::Scan {
@@ -2983,6 +3602,7 @@ mod test {
             result_opt: 1, // ALL_V
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let project_opr = pb::Project {
@@ -3040,4 +3660,62 @@ mod test {
         expected_builder.project(project_opr);
         assert_eq!(builder, expected_builder);
     }
+
+    #[test]
+    fn sink_tagged_targets_validation() {
+        let csv_target = pb::sink::SinkTarget {
+            inner: Some(pb::sink::sink_target::Inner::SinkCsv(pb::SinkCsv {
+                extra: vec![("path".to_string(), "/tmp/sink.csv".to_string())]
+                    .into_iter()
+                    .collect(),
+            })),
+        };
+        let tag_keys = |tags: Vec<i32>| -> Vec<common_pb::NameOrIdKey> {
+            tags.into_iter()
+                .map(|tag| common_pb::NameOrIdKey { key: Some(tag.into()) })
+                .collect()
+        };
+        let build_multi_sink = |tags: Vec<i32>, csv_tags: Vec<i32>| {
+            let mut sink = build_sink();
+            sink.tags = tag_keys(tags);
+            sink.tagged_targets = vec![pb::sink::TaggedSinkTarget {
+                tags: tag_keys(csv_tags),
+                sink_target: Some(csv_target.clone()),
+            }];
+            sink
+        };
+        let add_sink = |sink: pb::Sink| {
+            let mut builder = PlanBuilder::default();
+            let mut plan_meta = PlanMeta::default();
+            sink.add_job_builder(&mut builder, &mut plan_meta)
+                .map(|_| builder)
+        };
+
+        // each tag is sinked into exactly one target
+        let builder = add_sink(build_multi_sink(vec![0], vec![1, 2])).unwrap();
+        let mut expected_builder = PlanBuilder::default();
+        expected_builder.sink(build_multi_sink(vec![0], vec![1, 2]));
+        assert_eq!(builder, expected_builder);
+        // a tag sinked into two targets
+        assert!(add_sink(build_multi_sink(vec![0, 1], vec![1])).is_err());
+        // the tags to sink into a target are not given
+        assert!(add_sink(build_multi_sink(vec![], vec![1])).is_err());
+        assert!(add_sink(build_multi_sink(vec![0], vec![])).is_err());
+    }
+
+    #[test]
+    fn invalid_range_error() {
+        let add_opr = |opr: &dyn AsPhysical| {
+            let mut builder = PlanBuilder::default();
+            let mut plan_meta = PlanMeta::default();
+            opr.add_job_builder(&mut builder, &mut plan_meta)
+        };
+        let range = |lower: i32, upper: i32| Some(pb::Range { lower, upper });
+
+        assert!(add_opr(&pb::Limit { range: range(0, 10) }).is_ok());
+        assert!(matches!(add_opr(&pb::Limit { range: range(5, 1) }), Err(IrError::InvalidRange(5, 1))));
+        assert!(matches!(add_opr(&pb::Limit { range: None }), Err(IrError::MissingData(_))));
+        let order = pb::OrderBy { limit: range(-1, 1), ..Default::default() };
+        assert!(matches!(add_opr(&order), Err(IrError::InvalidRange(-1, 1))));
+    }
 }
@@ -34,6 +34,7 @@ mod tests {
                     id_name_mappings: vec![],
                 })),
             }),
+            tagged_targets: vec![],
         }
     }
 
@@ -94,6 +95,7 @@ mod tests {
                 property: None,
                 node_type: None,
             }],
+            null_policy: 0,
         };
 
         let sink = default_sink_pb();
@@ -174,6 +176,7 @@ mod tests {
                 property: None,
                 node_type: None,
             }],
+            null_policy: 0,
         };
 
         let sink = default_sink_pb();
@@ -269,6 +272,7 @@ mod tests {
                 property: None,
                 node_type: None,
             }],
+            null_policy: 0,
         };
 
         let sink = default_sink_pb();
@@ -377,6 +381,7 @@ mod tests {
             kind: 0,
             left_keys: vec![common_pb::Variable { tag: None, property: None, node_type: None }],
             right_keys: vec![common_pb::Variable { tag: None, property: None, node_type: None }],
+            null_policy: 0,
         };
 
         let sink = default_sink_pb();
@@ -454,6 +459,7 @@ mod tests {
             kind: 0,
             left_keys: vec![common_pb::Variable { tag: None, property: None, node_type: None }],
             right_keys: vec![common_pb::Variable { tag: None, property: None, node_type: None }],
+            null_policy: 0,
         };
 
         let sink = default_sink_pb();
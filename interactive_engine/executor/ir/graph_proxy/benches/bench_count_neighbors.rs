@@ -0,0 +1,106 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use graph_proxy::apis::{ClusterInfo, Direction, QueryParams, ReadGraph, ID};
+use graph_proxy::{create_exp_store_with_graph, GraphProxyResult};
+use graph_store::config::JsonConf;
+use graph_store::ldbc::LDBCVertexParser;
+use graph_store::prelude::{
+    DefaultId, GlobalStoreUpdate, GraphDBConfig, InternalId, LDBCGraphSchema, LargeGraphDB, MutableGraphDB,
+    INVALID_LABEL_ID,
+};
+
+const HUB_DEGREE: usize = 1 << 20;
+
+struct SingleWorker;
+
+impl ClusterInfo for SingleWorker {
+    fn get_server_num(&self) -> GraphProxyResult<u32> {
+        Ok(1)
+    }
+
+    fn get_server_index(&self) -> GraphProxyResult<u32> {
+        Ok(0)
+    }
+
+    fn get_local_worker_num(&self) -> GraphProxyResult<u32> {
+        Ok(1)
+    }
+
+    fn get_worker_index(&self) -> GraphProxyResult<u32> {
+        Ok(0)
+    }
+}
+
+/// A star graph, where the hub vertex `0` links to each of the other `HUB_DEGREE` vertices
+fn prepare_hub_graph() -> LargeGraphDB<DefaultId, InternalId> {
+    let mut mut_graph: MutableGraphDB<DefaultId, InternalId> = GraphDBConfig::default().new();
+    let hub: DefaultId = LDBCVertexParser::to_global_id(0, 0);
+    mut_graph.add_vertex(hub, [0, INVALID_LABEL_ID]);
+    for i in 1..=HUB_DEGREE {
+        let v: DefaultId = LDBCVertexParser::to_global_id(i, 0);
+        mut_graph.add_vertex(v, [0, INVALID_LABEL_ID]);
+        assert!(mut_graph.add_edge(hub, v, 0));
+    }
+    let schema = r#"
+    {
+      "vertex_type_map": { "node": 0 },
+      "edge_type_map": { "link": 0 },
+      "vertex_prop": { "node": [] },
+      "edge_prop": { "link": [] }
+    }
+    "#;
+    mut_graph.into_graph(LDBCGraphSchema::from_json(schema.to_string()).expect("Parse schema error!"))
+}
+
+fn bench_count_neighbors(c: &mut Criterion) {
+    let graph: &'static _ = Box::leak(Box::new(prepare_hub_graph()));
+    let store = create_exp_store_with_graph(graph, Arc::new(SingleWorker));
+    let hub = LDBCVertexParser::<DefaultId>::to_global_id(0, 0) as ID;
+    let explore_count = || {
+        store
+            .prepare_explore_vertex(Direction::Out, &QueryParams::default())
+            .unwrap()
+            .exec(hub)
+            .unwrap()
+            .count() as u64
+    };
+    assert_eq!(explore_count(), HUB_DEGREE as u64);
+    assert_eq!(
+        store
+            .count_neighbors(hub, Direction::Out, &QueryParams::default())
+            .unwrap(),
+        HUB_DEGREE as u64
+    );
+
+    let mut group = c.benchmark_group("count_neighbors_of_hub");
+    group.sample_size(10);
+    group.bench_function("explore", |b| b.iter(|| explore_count()));
+    group.bench_function("degree", |b| {
+        b.iter(|| {
+            store
+                .count_neighbors(hub, Direction::Out, &QueryParams::default())
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_count_neighbors);
+criterion_main!(benches);
@@ -0,0 +1,152 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+
+use std::convert::TryFrom;
+
+use ahash::HashMap;
+use criterion::{criterion_group, criterion_main, Criterion};
+use dyn_type::Object;
+use graph_proxy::apis::{DynDetails, GraphElement, Vertex, ID};
+use graph_proxy::utils::expr::eval::Evaluator;
+use graph_proxy::utils::expr::eval_batch::{BatchFilterIter, ColumnPredicate, DEFAULT_FILTER_BATCH_SIZE};
+use graph_proxy::utils::expr::eval_pred::{EvalPred, PEvaluator};
+use ir_common::expr_parse::str_to_expr_pb;
+use ir_common::generated::common as common_pb;
+use ir_common::NameOrId;
+
+const SCAN_SIZE: i64 = 1_000_000;
+
+/// A synthetic store vertex, whose properties are copied out on access
+struct StoreVertex {
+    id: ID,
+    props: Vec<(NameOrId, Object)>,
+}
+
+impl StoreVertex {
+    fn get_property(&self, key: &NameOrId) -> Option<Object> {
+        self.props
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    }
+
+    fn to_runtime_vertex(&self) -> Vertex {
+        let props: HashMap<NameOrId, Object> = self.props.iter().cloned().collect();
+        Vertex::new(self.id, Some(1.into()), DynDetails::new(props))
+    }
+}
+
+fn prepare_store() -> Vec<StoreVertex> {
+    (0..SCAN_SIZE)
+        .map(|i| StoreVertex {
+            id: i,
+            props: vec![
+                ("age".into(), ((i * 7919) % 100).into()),
+                ("weight".into(), ((i % 1000) as f64 / 10.0).into()),
+                ("name".into(), format!("name_{}", i % 97).into()),
+            ],
+        })
+        .collect()
+}
+
+// selectivity of 1%
+const PREDICATE: &str = "@.age == 42 && @.weight >= 0.0";
+
+fn bench_scan_filter(c: &mut Criterion) {
+    let evaluator = PEvaluator::try_from(str_to_expr_pb(PREDICATE.to_string()).unwrap()).unwrap();
+    let column_pred = ColumnPredicate::compile(&evaluator).unwrap();
+    let store = prepare_store();
+
+    let expected: Vec<ID> = store
+        .iter()
+        .map(|v| v.to_runtime_vertex())
+        .filter(|v| evaluator.eval_bool(Some(v)).unwrap_or(false))
+        .map(|v| v.id())
+        .collect();
+    let results: Vec<ID> = BatchFilterIter::new(
+        store.iter(),
+        column_pred.clone(),
+        |v: &&StoreVertex, key: &NameOrId| v.get_property(key),
+        |v| v.to_runtime_vertex().id(),
+        DEFAULT_FILTER_BATCH_SIZE,
+    )
+    .collect();
+    assert_eq!(expected, results);
+    assert_eq!(results.len() as i64, SCAN_SIZE / 100);
+
+    let mut group = c.benchmark_group("scan_filter_1m_1%");
+    group.sample_size(10);
+    group.bench_function("row_wise", |b| {
+        b.iter(|| {
+            store
+                .iter()
+                .map(|v| v.to_runtime_vertex())
+                .filter(|v| evaluator.eval_bool(Some(v)).unwrap_or(false))
+                .count()
+        })
+    });
+    group.bench_function("vectorized", |b| {
+        b.iter(|| {
+            BatchFilterIter::new(
+                store.iter(),
+                column_pred.clone(),
+                |v: &&StoreVertex, key: &NameOrId| v.get_property(key),
+                |v| v.to_runtime_vertex(),
+                DEFAULT_FILTER_BATCH_SIZE,
+            )
+            .count()
+        })
+    });
+    group.finish();
+}
+
+const WITHOUT_LIST_SIZE: i64 = 100_000;
+const WITHOUT_SCAN_SIZE: i64 = 1_000;
+
+fn bench_without_list(c: &mut Criterion) {
+    // @.~id without [0, 1, ..., 99999], e.g., the ids given by a subquery
+    let mut expr = str_to_expr_pb("@.~id without 0".to_string()).unwrap();
+    expr.operators.last_mut().unwrap().item = Some(common_pb::expr_opr::Item::Const(
+        common_pb::Value::from((0..WITHOUT_LIST_SIZE).collect::<Vec<i64>>()),
+    ));
+    let hashed = PEvaluator::try_from(expr.clone()).unwrap();
+    // the general evaluator searches the list linearly
+    let linear = Evaluator::try_from(expr).unwrap();
+    // half of the vertices are in the list
+    let vertices: Vec<Vertex> = (0..WITHOUT_SCAN_SIZE)
+        .map(|i| {
+            Vertex::new(
+                i * WITHOUT_LIST_SIZE * 2 / WITHOUT_SCAN_SIZE,
+                Some(1.into()),
+                DynDetails::default(),
+            )
+        })
+        .collect();
+
+    let count = |eval: &dyn Fn(&Vertex) -> bool| vertices.iter().filter(|v| eval(v)).count();
+    let hashed_count = count(&|v| hashed.eval_bool(Some(v)).unwrap());
+    assert_eq!(hashed_count, count(&|v| linear.eval_bool(Some(v)).unwrap()));
+    assert_eq!(hashed_count as i64, WITHOUT_SCAN_SIZE / 2);
+
+    let mut group = c.benchmark_group("without_list_100k");
+    group.sample_size(10);
+    group.bench_function("linear", |b| b.iter(|| count(&|v| linear.eval_bool(Some(v)).unwrap())));
+    group.bench_function("hashed", |b| b.iter(|| count(&|v| hashed.eval_bool(Some(v)).unwrap())));
+    group.finish();
+}
+
+criterion_group!(benches, bench_scan_filter, bench_without_list);
+criterion_main!(benches);
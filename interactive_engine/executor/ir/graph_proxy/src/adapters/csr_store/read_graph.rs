@@ -32,11 +32,14 @@ use pegasus_common::impl_as_any;
 
 use crate::apis::graph::PKV;
 use crate::apis::{
-    from_fn, ClusterInfo, Details, Direction, DynDetails, Edge, PropertyValue, QueryParams, ReadGraph,
-    Statement, Vertex, ID,
+    from_fn, ClusterInfo, Details, Direction, DynDetails, Edge, GraphElement, PropertyValue, QueryParams,
+    ReadGraph, Statement, Vertex, ID,
 };
 use crate::errors::{GraphProxyError, GraphProxyResult};
-use crate::{filter_limit, filter_sample_limit, limit_n, sample_limit};
+use crate::{
+    filter_limit, filter_reservoir_sample_limit, filter_sample_limit, limit_n, reservoir_sample_limit,
+    sample_limit,
+};
 
 lazy_static! {
     pub static ref CSR_PATH: String = configure_with_default!(String, "CSR_PATH", "".to_string());
@@ -68,6 +71,7 @@ impl ReadGraph for CSRStore {
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
         let label_ids = encode_storage_label(&params.labels);
         let props = params.columns.clone();
+        let sample_seed = params.get_sample_seed()?;
 
         let worker_index = self.cluster_info.get_worker_index()?;
         let workers_num = self.cluster_info.get_local_worker_num()?;
@@ -76,7 +80,27 @@ impl ReadGraph for CSRStore {
             .store
             .get_partitioned_vertices(label_ids.as_ref(), worker_index, workers_num)
             .map(move |v| to_runtime_vertex(v, props.clone()));
-        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, params.limit))
+        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, sample_seed, params.limit))
+    }
+
+    fn scan_vertex_ids(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = ID> + Send>> {
+        if params.filter.is_some() {
+            // the filter is evaluated on the vertices
+            return Ok(Box::new(self.scan_vertex(params)?.map(|v| v.id())));
+        }
+        let label_ids = encode_storage_label(&params.labels);
+        let sample_seed = params.get_sample_seed()?;
+
+        let worker_index = self.cluster_info.get_worker_index()?;
+        let workers_num = self.cluster_info.get_local_worker_num()?;
+
+        let result = self
+            .store
+            .get_partitioned_vertices(label_ids.as_ref(), worker_index, workers_num)
+            .map(|v| v.get_id() as ID);
+        Ok(sample_limit!(result, params.sample_ratio, sample_seed, params.limit))
     }
 
     fn index_scan_vertex(
@@ -90,6 +114,7 @@ impl ReadGraph for CSRStore {
     fn scan_edge(&self, params: &QueryParams) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
         let label_ids = encode_storage_label(&params.labels);
         let props = params.columns.clone();
+        let sample_seed = params.get_sample_seed()?;
 
         let worker_index = self.cluster_info.get_worker_index()?;
         let workers_num = self.cluster_info.get_local_worker_num()?;
@@ -99,7 +124,7 @@ impl ReadGraph for CSRStore {
             .store
             .get_partitioned_edges(label_ids.as_ref(), worker_index, workers_num)
             .map(move |e| to_runtime_edge(e, None, props.clone(), partition_id));
-        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, params.limit))
+        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, sample_seed, params.limit))
     }
 
     fn get_vertex(
@@ -118,9 +143,7 @@ impl ReadGraph for CSRStore {
     fn get_edge(
         &self, _ids: &[ID], _params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
-        Err(GraphProxyError::unsupported_error(
-            "Experiment storage does not support index_scan_vertex for now",
-        ))?
+        Err(GraphProxyError::unsupported_error("Experiment storage does not support get_edge for now"))?
     }
 
     fn prepare_explore_vertex(
@@ -151,6 +174,8 @@ impl ReadGraph for CSRStore {
         let limit = params.limit.clone();
         let graph = self.store;
         let props = params.columns.clone();
+        let per_vertex_sample = params.per_vertex_sample;
+        let sample_seed = params.get_sample_seed()?;
 
         let stmt = from_fn(move |v: ID| {
             let props = props.clone();
@@ -161,7 +186,9 @@ impl ReadGraph for CSRStore {
                 Direction::Both => graph.get_both_edges(v as DefaultId, edge_label_ids.as_ref()),
             }
             .map(move |e| to_runtime_edge(e, Some(v), props.clone(), partition_id));
-            Ok(filter_limit!(iter, filter, limit))
+            // sampled independently per source vertex, even with the same seed
+            let seed = sample_seed.map(|seed| seed ^ v as u64);
+            Ok(filter_reservoir_sample_limit!(iter, filter, per_vertex_sample, seed, limit))
         });
         Ok(stmt)
     }
@@ -175,7 +202,8 @@ impl ReadGraph for CSRStore {
     fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64> {
         if params.filter.is_some() {
             // the filter cannot be pushed down to store,
-            // so we need to scan all vertices with filter and then count
+            // so we need to scan all vertices with filter and then count,
+            // where the scan stops once `params.limit` vertices are found
             Ok(self.scan_vertex(params)?.count() as u64)
         } else {
             let worker_index = self.cluster_info.get_worker_index()?;
@@ -185,7 +213,7 @@ impl ReadGraph for CSRStore {
                 let count = self
                     .store
                     .count_all_vertices(label_ids.as_ref());
-                Ok(count as u64)
+                Ok(params.limit_count(count as u64))
             } else {
                 Ok(0)
             }
@@ -201,7 +229,7 @@ impl ReadGraph for CSRStore {
             if worker_index % workers_num == 0 {
                 let label_ids = encode_storage_label(&params.labels);
                 let count = self.store.count_all_edges(label_ids.as_ref());
-                Ok(count as u64)
+                Ok(params.limit_count(count as u64))
             } else {
                 Ok(0)
             }
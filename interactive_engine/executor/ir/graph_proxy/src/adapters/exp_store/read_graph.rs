@@ -33,11 +33,15 @@ use pegasus_common::impl_as_any;
 
 use crate::apis::graph::PKV;
 use crate::apis::{
-    from_fn, ClusterInfo, Details, Direction, DynDetails, Edge, PropertyValue, QueryParams, ReadGraph,
-    Statement, Vertex, ID,
+    from_fn, ClusterInfo, Details, Direction, DynDetails, Edge, GraphElement, PropertyValue, QueryParams,
+    ReadGraph, Statement, Vertex, ID,
 };
 use crate::errors::GraphProxyResult;
-use crate::{filter_limit, filter_sample_limit, limit_n, sample_limit, GraphProxyError};
+use crate::utils::expr::eval_pred::{extract_prop_names, EvalPred, PEvaluator};
+use crate::{
+    filter_limit, filter_reservoir_sample_limit, filter_sample_limit, limit_n, reservoir_sample_limit,
+    sample_limit, GraphProxyError,
+};
 
 const EXP_STORE_PK: KeyId = 0;
 
@@ -50,7 +54,14 @@ lazy_static! {
 #[allow(dead_code)]
 pub fn create_exp_store(cluster_info: Arc<dyn ClusterInfo>) -> Arc<ExpStore> {
     lazy_static::initialize(&GRAPH);
-    Arc::new(ExpStore { store: &GRAPH, cluster_info })
+    create_exp_store_with_graph(&GRAPH, cluster_info)
+}
+
+/// Create the exp_store over the given graph, instead of the one loaded from `DATA_PATH`.
+pub fn create_exp_store_with_graph(
+    graph: &'static LargeGraphDB<DefaultId, InternalId>, cluster_info: Arc<dyn ClusterInfo>,
+) -> Arc<ExpStore> {
+    Arc::new(ExpStore { store: graph, cluster_info })
 }
 
 pub struct ExpStore {
@@ -223,16 +234,15 @@ fn _init_modern_graph() -> LargeGraphDB<DefaultId, InternalId> {
     mut_graph.into_graph(schema)
 }
 
-impl ReadGraph for ExpStore {
-    fn scan_vertex(
+impl ExpStore {
+    /// Scan the vertices of the given labels in the part of the current worker.
+    fn scan_local_vertices(
         &self, params: &QueryParams,
-    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+    ) -> GraphProxyResult<impl Iterator<Item = LocalVertex<'static, DefaultId>> + Send> {
         // DemoGraph contains a single graph partition on each server,
         // therefore, there's no need to use the specific partition id for query.
         // Besides, workers will scan the vertices in a parallel way
         let label_ids = encode_storage_label(&params.labels);
-        let props = params.columns.clone();
-
         let worker_idx = self.cluster_info.get_worker_index()?;
         let workers_num = self.cluster_info.get_local_worker_num()?;
         let count = self
@@ -246,14 +256,64 @@ impl ReadGraph for ExpStore {
             partial_count
         };
 
-        let result = self
+        Ok(self
             .store
             .get_all_vertices(label_ids.as_ref())
             .skip((worker_idx % workers_num) as usize * partial_count)
-            .take(take_count)
-            .map(move |v| to_runtime_vertex(v, props.clone()));
+            .take(take_count))
+    }
+}
+
+impl ReadGraph for ExpStore {
+    fn scan_vertex(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        let props = extract_needed_prop_keys(params.filter.as_ref(), params.columns.as_ref());
+        let projection = params
+            .projection
+            .clone()
+            .map(|keys| Arc::new(VertexProjection { keys, store: self.store }));
+        let sample_seed = params.get_sample_seed()?;
+        let result = self.scan_local_vertices(params)?;
+
+        match (params.filter.clone(), projection) {
+            (Some(filter), Some(projection)) => {
+                // the filter may refer to the properties out of the projection,
+                // so it is evaluated on the vertex before the projection
+                let result = result.filter_map(move |v| {
+                    let passed = filter
+                        .eval_bool(Some(&to_runtime_vertex(v.clone(), None, None)))
+                        .unwrap_or(false);
+                    passed.then(|| to_runtime_vertex(v, props.clone(), Some(projection.clone())))
+                });
+                Ok(sample_limit!(result, params.sample_ratio, sample_seed, params.limit))
+            }
+            (_, projection) => {
+                let result = result.map(move |v| to_runtime_vertex(v, props.clone(), projection.clone()));
+                Ok(filter_sample_limit!(
+                    result,
+                    params.filter,
+                    params.sample_ratio,
+                    sample_seed,
+                    params.limit
+                ))
+            }
+        }
+    }
+
+    fn scan_vertex_ids(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = ID> + Send>> {
+        if params.filter.is_some() {
+            // the filter is evaluated on the vertices
+            return Ok(Box::new(self.scan_vertex(params)?.map(|v| v.id())));
+        }
+        let sample_seed = params.get_sample_seed()?;
+        let result = self
+            .scan_local_vertices(params)?
+            .map(|v| v.get_id() as ID);
 
-        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, params.limit))
+        Ok(sample_limit!(result, params.sample_ratio, sample_seed, params.limit))
     }
 
     fn index_scan_vertex(
@@ -290,6 +350,7 @@ impl ReadGraph for ExpStore {
         // Besides, workers will scan the edges in a parallel way
         let label_ids = encode_storage_label(&params.labels);
         let props = params.columns.clone();
+        let sample_seed = params.get_sample_seed()?;
 
         let worker_idx = self.cluster_info.get_worker_index()?;
         let workers_num = self.cluster_info.get_local_worker_num()?;
@@ -308,16 +369,17 @@ impl ReadGraph for ExpStore {
             .take(take_count)
             .map(move |v| to_runtime_edge(v, props.clone()));
 
-        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, params.limit))
+        Ok(filter_sample_limit!(result, params.filter, params.sample_ratio, sample_seed, params.limit))
     }
 
     fn get_vertex(
         &self, ids: &[ID], params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        let props = extract_needed_prop_keys(params.filter.as_ref(), params.columns.as_ref());
         let mut result = Vec::with_capacity(ids.len());
         for id in ids {
             if let Some(local_vertex) = self.store.get_vertex(*id as DefaultId) {
-                let v = to_runtime_vertex(local_vertex, params.columns.clone());
+                let v = to_runtime_vertex(local_vertex, props.clone(), None);
                 result.push(v);
             }
         }
@@ -366,6 +428,8 @@ impl ReadGraph for ExpStore {
         let limit = params.limit.clone();
         let graph = self.store;
         let props = params.columns.clone();
+        let per_vertex_sample = params.per_vertex_sample;
+        let sample_seed = params.get_sample_seed()?;
 
         let stmt = from_fn(move |v: ID| {
             let props = props.clone();
@@ -375,11 +439,30 @@ impl ReadGraph for ExpStore {
                 Direction::Both => graph.get_both_edges(v as DefaultId, edge_label_ids.as_ref()),
             }
             .map(move |e| to_runtime_edge(e, props.clone()));
-            Ok(filter_limit!(iter, filter, limit))
+            // sampled independently per source vertex, even with the same seed
+            let seed = sample_seed.map(|seed| seed ^ v as u64);
+            Ok(filter_reservoir_sample_limit!(iter, filter, per_vertex_sample, seed, limit))
         });
         Ok(stmt)
     }
 
+    fn count_neighbors(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        // the degrees maintained by the store are of all the edge labels
+        if !params.labels.is_empty() {
+            let params = QueryParams { labels: params.labels.clone(), ..Default::default() };
+            return Ok(self
+                .prepare_explore_vertex(direction, &params)?
+                .exec(id)?
+                .count() as u64);
+        }
+        let degree = match direction {
+            Direction::Out => self.store.out_degree(id as DefaultId),
+            Direction::In => self.store.in_degree(id as DefaultId),
+            Direction::Both => self.store.degree(id as DefaultId),
+        };
+        Ok(degree as u64)
+    }
+
     fn get_primary_key(&self, id: &ID) -> GraphProxyResult<Option<PKV>> {
         let outer_id = (*id << LABEL_SHIFT_BITS) >> LABEL_SHIFT_BITS;
         let pk_val = Object::from(outer_id);
@@ -389,16 +472,18 @@ impl ReadGraph for ExpStore {
     fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64> {
         if params.filter.is_some() {
             // the filter cannot be pushed down to exp_store,
-            // so we need to scan all vertices with filter and then count
+            // so we need to scan all vertices with filter and then count,
+            // where the scan stops once `params.limit` vertices are found
             Ok(self.scan_vertex(params)?.count() as u64)
         } else {
             let worker_idx = self.cluster_info.get_worker_index()?;
             let workers_num = self.cluster_info.get_local_worker_num()?;
             if worker_idx % workers_num == 0 {
                 let label_ids = encode_storage_label(&params.labels);
-                Ok(self
+                let count = self
                     .store
-                    .count_all_vertices(label_ids.as_ref()) as u64)
+                    .count_all_vertices(label_ids.as_ref());
+                Ok(params.limit_count(count as u64))
             } else {
                 Ok(0)
             }
@@ -413,20 +498,89 @@ impl ReadGraph for ExpStore {
             let workers_num = self.cluster_info.get_local_worker_num()?;
             if worker_idx % workers_num == 0 {
                 let label_ids = encode_storage_label(&params.labels);
-                Ok(self.store.count_all_edges(label_ids.as_ref()) as u64)
+                let count = self.store.count_all_edges(label_ids.as_ref());
+                Ok(params.limit_count(count as u64))
             } else {
                 Ok(0)
             }
         }
     }
+
+    fn count_vertex_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        if params.filter.is_some() {
+            Ok(params.limit_count(self.get_vertex(ids, params)?.count() as u64))
+        } else {
+            // only check the existence of the ids, without building the runtime vertices
+            let count = ids
+                .iter()
+                .filter(|id| {
+                    self.store
+                        .get_vertex(**id as DefaultId)
+                        .is_some()
+                })
+                .count();
+            Ok(params.limit_count(count as u64))
+        }
+    }
+
+    fn count_edge_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        if params.filter.is_some() {
+            Ok(params.limit_count(self.get_edge(ids, params)?.count() as u64))
+        } else {
+            let count = ids
+                .iter()
+                .filter(|id| {
+                    self.store
+                        .get_edge(encode_store_e_id(id))
+                        .is_some()
+                })
+                .count();
+            Ok(params.limit_count(count as u64))
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    // The number of vertices built by `to_runtime_vertex()` in the current thread.
+    static NUM_RUNTIME_VERTICES: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// Get the keys of the properties used in the filter and output, as `extract_needed_columns()` of
+/// gs_store, while the properties are accessed by names rather than ids in exp_store.
+#[inline]
+fn extract_needed_prop_keys(
+    filter: Option<&Arc<PEvaluator>>, out_columns: Option<&Vec<NameOrId>>,
+) -> Option<Vec<NameOrId>> {
+    // Some(vec[]) means need all props, so can't merge it with props needed in filter
+    if let Some(out_columns) = out_columns {
+        if out_columns.is_empty() {
+            return Some(Vec::with_capacity(0));
+        }
+    }
+    let mut columns = filter.and_then(extract_prop_names);
+    for column in out_columns.into_iter().flatten() {
+        let columns = columns.get_or_insert_with(Vec::new);
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+    columns
 }
 
+/// Build the runtime vertex, whose properties are fetched lazily, and restricted to the
+/// `projection` if it is given.
 #[inline]
-fn to_runtime_vertex(v: LocalVertex<'static, DefaultId>, prop_keys: Option<Vec<NameOrId>>) -> Vertex {
+fn to_runtime_vertex(
+    v: LocalVertex<'static, DefaultId>, prop_keys: Option<Vec<NameOrId>>,
+    projection: Option<Arc<VertexProjection>>,
+) -> Vertex {
+    #[cfg(test)]
+    NUM_RUNTIME_VERTICES.with(|num| num.set(num.get() + 1));
     // For vertices, we query properties via vid
     let id = v.get_id() as ID;
     let label = encode_runtime_v_label(&v);
-    let details = LazyVertexDetails::new(v, prop_keys);
+    let details = LazyVertexDetails::new(v, prop_keys).with_projection(projection);
     Vertex::new(id, Some(label), DynDetails::lazy(details))
 }
 
@@ -464,6 +618,36 @@ fn to_runtime_edge(e: LocalEdge<'static, DefaultId, InternalId>, prop_keys: Opti
     e
 }
 
+/// The properties that the scanned vertices are projected to, which is shared by the vertices of a scan.
+/// A property of id is the property at the position of the id in the vertex header of the store.
+struct VertexProjection {
+    keys: Vec<NameOrId>,
+    store: &'static LargeGraphDB<DefaultId, InternalId>,
+}
+
+impl VertexProjection {
+    fn contains(&self, label: StoreLabelId, name: &str) -> bool {
+        self.keys.iter().any(|key| match key {
+            NameOrId::Str(key) => key == name,
+            NameOrId::Id(id) => self
+                .store
+                .get_schema()
+                .get_vertex_header(label)
+                .and_then(|header| header.get(*id as usize))
+                .map(|(key, _)| key == name)
+                .unwrap_or(false),
+        })
+    }
+}
+
+impl fmt::Debug for VertexProjection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.keys.iter())
+            .finish()
+    }
+}
+
 /// LazyVertexDetails is used for local property fetching optimization.
 /// That is, the required properties will not be materialized until LazyVertexDetails need to be shuffled.
 #[allow(dead_code)]
@@ -474,13 +658,27 @@ struct LazyVertexDetails {
     // and None indicates we do not need any property
     prop_keys: Option<Vec<NameOrId>>,
     inner: LocalVertex<'static, DefaultId>,
+    // the properties that are accessible, where None indicates all of them
+    projection: Option<Arc<VertexProjection>>,
 }
 
 impl_as_any!(LazyVertexDetails);
 
 impl LazyVertexDetails {
     pub fn new(v: LocalVertex<'static, DefaultId>, prop_keys: Option<Vec<NameOrId>>) -> Self {
-        LazyVertexDetails { prop_keys, inner: v }
+        LazyVertexDetails { prop_keys, inner: v, projection: None }
+    }
+
+    fn with_projection(mut self, projection: Option<Arc<VertexProjection>>) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    fn is_projected(&self, key: &str) -> bool {
+        self.projection
+            .as_ref()
+            .map(|projection| projection.contains(self.inner.get_label()[0], key))
+            .unwrap_or(true)
     }
 }
 
@@ -489,6 +687,7 @@ impl fmt::Debug for LazyVertexDetails {
         f.debug_struct("LazyVertexDetails")
             .field("properties", &self.prop_keys)
             .field("inner", &self.inner)
+            .field("projection", &self.projection)
             .finish()
     }
 }
@@ -496,6 +695,9 @@ impl fmt::Debug for LazyVertexDetails {
 impl Details for LazyVertexDetails {
     fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
         if let NameOrId::Str(key) = key {
+            if !self.is_projected(key) {
+                return None;
+            }
             self.inner
                 .get_property(key)
                 .map(|prop| PropertyValue::Borrowed(prop))
@@ -511,6 +713,7 @@ impl Details for LazyVertexDetails {
             .map(|prop_key_vals| {
                 prop_key_vals
                     .into_iter()
+                    .filter(|(prop_key, _)| self.is_projected(prop_key))
                     .map(|(prop_key, prop_val)| (prop_key.into(), prop_val as Object))
                     .collect()
             })
@@ -650,11 +853,50 @@ fn encode_store_pk_prop_val(val: Object) -> GraphProxyResult<DefaultId> {
 
 #[cfg(test)]
 mod tests {
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+
     use graph_store::common::LabelId;
+    use graph_store::config::JsonConf;
     use graph_store::ldbc::{LDBCVertexParser, LABEL_SHIFT_BITS};
-    use graph_store::prelude::{DefaultId, GlobalStoreTrait};
+    use graph_store::prelude::{
+        DefaultId, GlobalStoreTrait, GlobalStoreUpdate, GraphDBConfig, InternalId, LDBCGraphSchema,
+        LargeGraphDB, MutableGraphDB, INVALID_LABEL_ID,
+    };
+    use ir_common::expr_parse::str_to_expr_pb;
+    use ir_common::generated::algebra as pb;
+    use ir_common::generated::physical as physical_pb;
+    use ir_common::NameOrId;
+
+    use super::{create_exp_store, create_exp_store_with_graph, GRAPH, NUM_RUNTIME_VERTICES};
+    use crate::apis::graph::SAMPLE_SEED_KEY;
+    use crate::apis::partitioner::PartitionId;
+    use crate::apis::{
+        register_physical_planner, ClusterInfo, Details, Direction, Edge, GraphElement, QueryParams,
+        ReadGraph, Statement, Vertex, ID,
+    };
+    use crate::utils::expr::eval_pred::PEvaluator;
+    use crate::GraphProxyResult;
+
+    struct SingleWorker;
+
+    impl ClusterInfo for SingleWorker {
+        fn get_server_num(&self) -> GraphProxyResult<u32> {
+            Ok(1)
+        }
+
+        fn get_server_index(&self) -> GraphProxyResult<u32> {
+            Ok(0)
+        }
+
+        fn get_local_worker_num(&self) -> GraphProxyResult<u32> {
+            Ok(1)
+        }
 
-    use super::GRAPH;
+        fn get_worker_index(&self) -> GraphProxyResult<u32> {
+            Ok(0)
+        }
+    }
 
     #[test]
     fn it_works() {
@@ -680,4 +922,387 @@ mod tests {
         assert_eq!(v1_label, 0);
         assert_eq!(v2_label, 1);
     }
+    // g.V().hasLabel("person").out("knows")
+    #[test]
+    fn explain_scan_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let scan = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(pb::QueryParams { tables: vec![0.into()], ..Default::default() }),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(pb::QueryParams { tables: vec![0.into()], ..Default::default() }),
+            expand_opt: 0,
+            alias: None,
+            meta_data: None,
+            is_optional: false,
+        };
+        let plan = pb::LogicalPlan {
+            nodes: vec![
+                pb::logical_plan::Node { opr: Some(scan.into()), children: vec![1] },
+                pb::logical_plan::Node { opr: Some(expand.into()), children: vec![] },
+            ],
+            roots: vec![0],
+        };
+
+        // a mock planner, as the physical plans are built by ir_core
+        register_physical_planner(|plan| {
+            Ok(physical_pb::PhysicalPlan { plan_id: plan.nodes.len() as i32, ..Default::default() })
+        });
+        let query_plan = graph
+            .explain(&serde_json::to_string(&plan).unwrap())
+            .unwrap();
+        assert_eq!(query_plan.logical_plan, plan);
+        assert_eq!(
+            query_plan
+                .physical_plan
+                .map(|plan| plan.plan_id),
+            Some(2)
+        );
+        // the modern graph has 4 persons
+        let scan_cost = query_plan.get_cost(0).unwrap();
+        assert_eq!(scan_cost.estimated_rows, 4.0);
+        assert_eq!(scan_cost.estimated_io_cost, 4.0);
+        // and 2 knows edges out of 6 vertices
+        let expand_cost = query_plan.get_cost(1).unwrap();
+        assert!((expand_cost.estimated_rows - 4.0 * 2.0 / 6.0).abs() < 1e-6);
+        assert!(query_plan.total_cost() > 0.0);
+
+        assert!(graph.explain("not a plan").is_err());
+    }
+    #[test]
+    fn scan_vertex_by_partition_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let groups: Vec<(PartitionId, usize)> = graph
+            .scan_vertex_by_partition(&QueryParams::default())
+            .unwrap()
+            .map(|(partition_id, vertices)| (partition_id, vertices.count()))
+            .collect();
+        // the modern graph is regarded as a single partition of 6 vertices
+        assert_eq!(groups, vec![(0, 6)]);
+    }
+
+    #[test]
+    fn scan_vertex_ids_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let num_runtime_vertices = || NUM_RUNTIME_VERTICES.with(|num| num.get());
+        let scan_ids = |params: &QueryParams| {
+            let mut ids: Vec<ID> = graph.scan_vertex_ids(params).unwrap().collect();
+            ids.sort();
+            ids
+        };
+        let scan_vertex_ids = |params: &QueryParams| {
+            let mut ids: Vec<ID> = graph
+                .scan_vertex(params)
+                .unwrap()
+                .map(|v| v.id())
+                .collect();
+            ids.sort();
+            ids
+        };
+
+        let params = QueryParams::default();
+        let expected = scan_vertex_ids(&params);
+        assert_eq!(expected.len(), 6);
+        let num_before = num_runtime_vertices();
+        assert_eq!(scan_ids(&params), expected);
+        // no vertex is built, and thus neither the label nor the details
+        assert_eq!(num_runtime_vertices(), num_before);
+
+        // the persons only, where the limit also applies
+        let mut params = QueryParams::default();
+        params.labels = vec![0];
+        let expected = scan_vertex_ids(&params);
+        assert_eq!(expected.len(), 4);
+        assert_eq!(scan_ids(&params), expected);
+        params.limit = Some(2);
+        assert_eq!(scan_ids(&params).len(), 2);
+        assert_eq!(num_runtime_vertices(), num_before + 4);
+
+        // the vertices are built to evaluate the filter
+        let mut params = QueryParams::default();
+        params.filter = Some(Arc::new(
+            PEvaluator::try_from(str_to_expr_pb("@.name == \"marko\"".to_string()).unwrap()).unwrap(),
+        ));
+        assert_eq!(scan_ids(&params), vec![1]);
+    }
+
+    #[test]
+    fn count_neighbors_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let marko = LDBCVertexParser::<DefaultId>::to_global_id(1, 0) as ID;
+        let lop = LDBCVertexParser::<DefaultId>::to_global_id(3, 1) as ID;
+        let explore_count = |id: ID, direction: Direction, edge_labels: &[ir_common::LabelId]| {
+            let params = QueryParams { labels: edge_labels.to_vec(), ..Default::default() };
+            graph
+                .prepare_explore_vertex(direction, &params)
+                .unwrap()
+                .exec(id)
+                .unwrap()
+                .count() as u64
+        };
+        let cases: Vec<(ID, Direction, Vec<ir_common::LabelId>, u64)> = vec![
+            // read from the degree of the store
+            (marko, Direction::Out, vec![], 3),
+            (marko, Direction::In, vec![], 0),
+            (lop, Direction::In, vec![], 3),
+            (lop, Direction::Both, vec![], 3),
+            // counted by exploring the neighbors of the given labels
+            (marko, Direction::Out, vec![0], 2),
+            (marko, Direction::Both, vec![1], 1),
+            (lop, Direction::In, vec![0], 0),
+        ];
+        for (id, direction, edge_labels, expected) in cases {
+            let params = QueryParams { labels: edge_labels.clone(), ..Default::default() };
+            assert_eq!(
+                graph
+                    .count_neighbors(id, direction, &params)
+                    .unwrap(),
+                expected
+            );
+            assert_eq!(explore_count(id, direction, &edge_labels), expected);
+        }
+        // a vertex absent from the graph has no neighbors
+        assert_eq!(
+            graph
+                .count_neighbors(100, Direction::Both, &QueryParams::default())
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn get_degree_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let marko = LDBCVertexParser::<DefaultId>::to_global_id(1, 0) as ID;
+        let lop = LDBCVertexParser::<DefaultId>::to_global_id(3, 1) as ID;
+        let explore_count = |id: ID, direction: Direction, params: &QueryParams| {
+            graph
+                .prepare_explore_edge(direction, params)
+                .unwrap()
+                .exec(id)
+                .unwrap()
+                .count() as u64
+        };
+        let weight_filter =
+            Arc::new(PEvaluator::try_from(str_to_expr_pb("@.weight > 0.45".to_string()).unwrap()).unwrap());
+        let cases: Vec<(ID, Direction, QueryParams, u64)> = vec![
+            (marko, Direction::Out, QueryParams::default(), 3),
+            (marko, Direction::In, QueryParams::default(), 0),
+            (lop, Direction::Both, QueryParams::default(), 3),
+            (marko, Direction::Out, QueryParams { labels: vec![0], ..Default::default() }, 2),
+            (marko, Direction::Out, QueryParams { limit: Some(1), ..Default::default() }, 1),
+            // counted by exploring the edges passing the filter, i.e., marko knows vadas and josh
+            (marko, Direction::Out, QueryParams { filter: Some(weight_filter), ..Default::default() }, 2),
+        ];
+        for (id, direction, params, expected) in cases {
+            assert_eq!(
+                graph
+                    .get_degree(id, direction, &params)
+                    .unwrap(),
+                expected
+            );
+            assert_eq!(explore_count(id, direction, &params), expected);
+        }
+    }
+
+    /// A graph of `num_vertices` isolated vertices, all of the label `0`
+    fn prepare_vertex_set(num_vertices: usize) -> LargeGraphDB<DefaultId, InternalId> {
+        let mut mut_graph: MutableGraphDB<DefaultId, InternalId> = GraphDBConfig::default().new();
+        for i in 0..num_vertices {
+            let v: DefaultId = LDBCVertexParser::to_global_id(i, 0);
+            mut_graph.add_vertex(v, [0, INVALID_LABEL_ID]);
+        }
+        let schema = r#"
+        {
+          "vertex_type_map": { "node": 0 },
+          "edge_type_map": {},
+          "vertex_prop": { "node": [] },
+          "edge_prop": {}
+        }
+        "#;
+        mut_graph.into_graph(LDBCGraphSchema::from_json(schema.to_string()).expect("Parse schema error!"))
+    }
+
+    #[test]
+    fn count_vertex_with_limit_test() {
+        let graph: &'static _ = Box::leak(Box::new(prepare_vertex_set(1000)));
+        let graph = create_exp_store_with_graph(graph, Arc::new(SingleWorker));
+        let num_runtime_vertices = || NUM_RUNTIME_VERTICES.with(|num| num.get());
+
+        let mut params = QueryParams::default();
+        assert_eq!(graph.count_vertex(&params).unwrap(), 1000);
+        params.limit = Some(10);
+        assert_eq!(graph.count_vertex(&params).unwrap(), 10);
+
+        // the filtered scan stops once the limit is reached
+        params.filter = Some(Arc::new(
+            PEvaluator::try_from(str_to_expr_pb("@.~label == 0".to_string()).unwrap()).unwrap(),
+        ));
+        let num_before = num_runtime_vertices();
+        assert_eq!(graph.count_vertex(&params).unwrap(), 10);
+        assert_eq!(num_runtime_vertices(), num_before + 10);
+    }
+
+    #[test]
+    fn count_vertex_without_runtime_vertices_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let num_runtime_vertices = || NUM_RUNTIME_VERTICES.with(|num| num.get());
+        let num_before = num_runtime_vertices();
+
+        // g.V().hasLabel('person').count()
+        let params = QueryParams { labels: vec![0], ..Default::default() };
+        assert_eq!(graph.count_vertex(&params).unwrap(), 4);
+
+        // g.V(v1, v2, v_absent).count()
+        let v1 = LDBCVertexParser::to_global_id(1, 0) as ID;
+        let v2 = LDBCVertexParser::to_global_id(2, 0) as ID;
+        let v_absent = LDBCVertexParser::to_global_id(100, 0) as ID;
+        let params = QueryParams::default();
+        assert_eq!(
+            graph
+                .count_vertex_by_ids(&[v1, v2, v_absent], &params)
+                .unwrap(),
+            2
+        );
+        assert_eq!(num_runtime_vertices(), num_before);
+
+        // the filter still requires the vertices to be built
+        let params = QueryParams {
+            filter: Some(Arc::new(
+                PEvaluator::try_from(str_to_expr_pb("@.id == 1".to_string()).unwrap()).unwrap(),
+            )),
+            ..Default::default()
+        };
+        assert_eq!(
+            graph
+                .count_vertex_by_ids(&[v1, v2, v_absent], &params)
+                .unwrap(),
+            1
+        );
+        assert_eq!(num_runtime_vertices(), num_before + 2);
+
+        // the count of the filtered vertices is capped at the limit as well
+        let params = QueryParams {
+            filter: Some(Arc::new(
+                PEvaluator::try_from(str_to_expr_pb("@.id < 3".to_string()).unwrap()).unwrap(),
+            )),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            graph
+                .count_vertex_by_ids(&[v1, v2, v_absent], &params)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn explore_edge_with_per_vertex_sample_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let all_edges = graph
+            .prepare_explore_edge(Direction::Both, &QueryParams::default())
+            .unwrap();
+        let mut params = QueryParams::default();
+        params.per_vertex_sample = Some(2);
+        params.extra_params = Some(
+            vec![(SAMPLE_SEED_KEY.to_string(), "7".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        let sampled_edges = graph
+            .prepare_explore_edge(Direction::Both, &params)
+            .unwrap();
+
+        let edge_ids = |stmt: &Box<dyn Statement<ID, Edge>>, v: ID| -> Vec<ID> {
+            stmt.exec(v).unwrap().map(|e| e.id()).collect()
+        };
+        let mut num_sampled_vertices = 0;
+        for v in graph
+            .scan_vertex(&QueryParams::default())
+            .unwrap()
+        {
+            let all = edge_ids(&all_edges, v.id());
+            let sampled = edge_ids(&sampled_edges, v.id());
+            // each source vertex yields at most 2 of its edges
+            assert_eq!(sampled.len(), all.len().min(2));
+            assert!(sampled.iter().all(|e| all.contains(e)));
+            if all.len() > 2 {
+                num_sampled_vertices += 1;
+            }
+        }
+        // v1, v3 and v4 of the modern graph have 3 edges each
+        assert_eq!(num_sampled_vertices, 3);
+    }
+
+    #[test]
+    fn scan_vertex_with_projection_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let name: NameOrId = "name".into();
+        let age: NameOrId = "age".into();
+        let id: NameOrId = "id".into();
+
+        // `age` is projected by its position in the vertex header of `person`
+        let mut params = QueryParams { labels: vec![0], ..Default::default() };
+        params.projection = Some(vec![name.clone(), NameOrId::Id(2)]);
+        let vertices: Vec<Vertex> = graph.scan_vertex(&params).unwrap().collect();
+        assert_eq!(vertices.len(), 4);
+        for v in vertices {
+            assert!(v.get_property(&name).is_some());
+            assert!(v.get_property(&age).is_some());
+            // the non-projected property is dropped after scan
+            assert!(v.get_property(&id).is_none());
+        }
+
+        // the filter is evaluated before the projection
+        params.filter =
+            Some(Arc::new(PEvaluator::try_from(str_to_expr_pb("@.id == 1".to_string()).unwrap()).unwrap()));
+        let vertices: Vec<Vertex> = graph.scan_vertex(&params).unwrap().collect();
+        assert_eq!(vertices.len(), 1);
+        assert!(vertices[0].get_property(&name).is_some());
+        assert!(vertices[0].get_property(&id).is_none());
+    }
+
+    #[test]
+    fn get_vertex_with_filter_prop_keys_test() {
+        let graph = create_exp_store(Arc::new(SingleWorker));
+        let marko = LDBCVertexParser::<DefaultId>::to_global_id(1, 0) as ID;
+        let name: NameOrId = "name".into();
+        let age: NameOrId = "age".into();
+
+        // the properties referred by names in the filter are kept in the vertex
+        let mut params = QueryParams::default();
+        params.filter = Some(Arc::new(
+            PEvaluator::try_from(str_to_expr_pb("@.name == \"marko\"".to_string()).unwrap()).unwrap(),
+        ));
+        let vertices: Vec<Vertex> = graph
+            .get_vertex(&[marko], &params)
+            .unwrap()
+            .collect();
+        assert_eq!(vertices.len(), 1);
+        assert_eq!(vertices[0].get_details().get_property_keys(), Some(vec![name.clone()]));
+
+        // together with the output columns
+        params.columns = Some(vec![age.clone(), name.clone()]);
+        let vertices: Vec<Vertex> = graph
+            .get_vertex(&[marko], &params)
+            .unwrap()
+            .collect();
+        assert_eq!(vertices[0].get_details().get_property_keys(), Some(vec![name, age]));
+
+        // while all the properties are kept if required
+        params.columns = Some(vec![]);
+        let vertices: Vec<Vertex> = graph
+            .get_vertex(&[marko], &params)
+            .unwrap()
+            .collect();
+        assert_eq!(vertices[0].get_details().get_property_keys(), Some(vec![]));
+    }
 }
@@ -14,6 +14,7 @@
 //! limitations under the License.
 
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 use ahash::HashMap;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
@@ -28,6 +29,56 @@ use pegasus_common::downcast::*;
 
 use crate::apis::Details;
 use crate::apis::PropertyValue;
+use crate::GraphProxyResult;
+
+/// Fetch the properties of the graph elements of type `T` from the storage on demand, other than the ones
+/// fetched along with the elements, as the columns pushed down to the storage are trimmed. It is shared by
+/// all the elements of a scan or an expansion, and only called on the first access to a property not fetched.
+pub(crate) trait PropertyFetcher<T>: Send + Sync {
+    /// The properties fetched along with the elements
+    fn fetched_prop_ids(&self) -> &[PropId];
+
+    /// Fetch all the properties of `element` from the storage at once, on the access to `prop_id`, so that
+    /// the accesses to the other properties not fetched along with the element do not go to the storage again.
+    fn fetch(&self, element: &T, prop_id: PropId) -> GraphProxyResult<Vec<(PropId, Property)>>;
+}
+
+/// The properties decoded of a graph element, each at most once.
+#[derive(Default)]
+struct DecodedProperties {
+    // the properties that have been decoded, where None means the property does not exist
+    props: HashMap<PropId, Option<Object>>,
+    // whether the properties not fetched along with the element have been fetched by the fetcher
+    fetched: bool,
+}
+
+impl DecodedProperties {
+    /// Get the property of `element`, by `get` if it is fetched along with the element (all of them if
+    /// there is no `fetcher`), or by `fetcher` otherwise.
+    fn get_or_decode<T, F: FnOnce(PropId) -> Option<Property>>(
+        &mut self, element: &T, prop_id: PropId, get: F, fetcher: Option<&Arc<dyn PropertyFetcher<T>>>,
+    ) -> GraphProxyResult<Option<Object>> {
+        if let Some(prop) = self.props.get(&prop_id) {
+            return Ok(prop.clone());
+        }
+        let prop = match fetcher {
+            Some(fetcher) if !fetcher.fetched_prop_ids().contains(&prop_id) => {
+                if !self.fetched {
+                    for (fetched_id, fetched_val) in fetcher.fetch(element, prop_id)? {
+                        self.props
+                            .entry(fetched_id)
+                            .or_insert_with(|| Some(encode_runtime_prop_val(fetched_val)));
+                    }
+                    self.fetched = true;
+                }
+                self.props.get(&prop_id).cloned().flatten()
+            }
+            _ => get(prop_id).map(encode_runtime_prop_val),
+        };
+        self.props.insert(prop_id, prop.clone());
+        Ok(prop)
+    }
+}
 
 #[inline]
 fn encode_runtime_property(prop_id: PropId, prop_val: Property) -> (NameOrId, Object) {
@@ -37,7 +88,7 @@ fn encode_runtime_property(prop_id: PropId, prop_val: Property) -> (NameOrId, Ob
 }
 
 #[inline]
-fn encode_runtime_prop_val(prop_val: Property) -> Object {
+pub(crate) fn encode_runtime_prop_val(prop_val: Property) -> Object {
     match prop_val {
         Property::Bool(b) => b.into(),
         Property::Char(c) => {
@@ -72,6 +123,8 @@ fn encode_runtime_prop_val(prop_val: Property) -> Object {
 
 /// LazyVertexDetails is used for local property fetching optimization.
 /// That is, the required properties will not be materialized until LazyVertexDetails need to be shuffled.
+/// Even if the store returns all the properties of the vertex, e.g., when the column filter is not pushed down,
+/// only the properties that are accessed are decoded, each at most once.
 #[allow(dead_code)]
 pub struct LazyVertexDetails<V>
 where
@@ -83,6 +136,9 @@ where
     // and Some(vec![]) means we need all properties
     prop_keys: Option<Vec<NameOrId>>,
     inner: V,
+    decoded: Mutex<DecodedProperties>,
+    // the fetcher of the properties not fetched along with the vertex, if they are trimmed
+    fetcher: Option<Arc<dyn PropertyFetcher<V>>>,
 }
 
 impl<V> LazyVertexDetails<V>
@@ -90,7 +146,34 @@ where
     V: StoreVertex + 'static,
 {
     pub fn new(v: V, prop_keys: Option<Vec<NameOrId>>) -> Self {
-        LazyVertexDetails { prop_keys, inner: v }
+        LazyVertexDetails { prop_keys, inner: v, decoded: Mutex::default(), fetcher: None }
+    }
+
+    /// Only the properties of `fetcher.fetched_prop_ids()` are fetched along with the vertex, e.g., as the
+    /// columns pushed down to the storage are trimmed, and the others are fetched by `fetcher` on demand.
+    pub(crate) fn with_fetcher(mut self, fetcher: Arc<dyn PropertyFetcher<V>>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    fn try_get_decoded_property(&self, prop_id: PropId) -> GraphProxyResult<Option<Object>> {
+        self.decoded
+            .lock()
+            .expect("lock poisoned")
+            .get_or_decode(
+                &self.inner,
+                prop_id,
+                |prop_id| self.inner.get_property(prop_id),
+                self.fetcher.as_ref(),
+            )
+    }
+
+    fn get_decoded_property(&self, prop_id: PropId) -> Option<Object> {
+        self.try_get_decoded_property(prop_id)
+            .unwrap_or_else(|e| {
+                warn!("{}", e);
+                None
+            })
     }
 }
 
@@ -112,20 +195,37 @@ where
 {
     fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
         if let NameOrId::Id(key) = key {
-            self.inner
-                .get_property(*key as PropId)
-                .map(|prop| PropertyValue::Owned(encode_runtime_prop_val(prop)))
+            self.get_decoded_property(*key as PropId)
+                .map(PropertyValue::Owned)
         } else {
             info!("Have not support getting property by prop_name in gs_store yet");
             None
         }
     }
 
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        if let NameOrId::Id(key) = key {
+            Ok(self
+                .try_get_decoded_property(*key as PropId)?
+                .map(PropertyValue::Owned))
+        } else {
+            Ok(self.get_property(key))
+        }
+    }
+
     fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
+        // all the properties are decoded, and kept for the later accesses
+        let mut decoded = self.decoded.lock().expect("lock poisoned");
         Some(
             self.inner
                 .get_properties()
-                .map(|(prop_id, prop_val)| encode_runtime_property(prop_id, prop_val))
+                .map(|(prop_id, prop_val)| {
+                    let (key, prop) = encode_runtime_property(prop_id, prop_val);
+                    decoded
+                        .props
+                        .insert(prop_id, Some(prop.clone()));
+                    (key, prop)
+                })
                 .collect(),
         )
     }
@@ -150,6 +250,7 @@ where
 
 /// LazyEdgeDetails is used for local property fetching optimization.
 /// That is, the required properties will not be materialized until LazyEdgeDetails need to be shuffled.
+/// The same as `LazyVertexDetails`, the properties accessed are decoded, or fetched, each at most once.
 #[allow(dead_code)]
 pub struct LazyEdgeDetails<E>
 where
@@ -161,6 +262,9 @@ where
     // and Some(vec![]) means we need all properties
     prop_keys: Option<Vec<NameOrId>>,
     inner: E,
+    decoded: Mutex<DecodedProperties>,
+    // the fetcher of the properties not fetched along with the edge, if they are trimmed
+    fetcher: Option<Arc<dyn PropertyFetcher<E>>>,
 }
 
 impl<E> LazyEdgeDetails<E>
@@ -168,7 +272,25 @@ where
     E: StoreEdge + 'static,
 {
     pub fn new(e: E, prop_keys: Option<Vec<NameOrId>>) -> Self {
-        LazyEdgeDetails { prop_keys, inner: e }
+        LazyEdgeDetails { prop_keys, inner: e, decoded: Mutex::default(), fetcher: None }
+    }
+
+    /// The same as `LazyVertexDetails::with_fetcher()`
+    pub(crate) fn with_fetcher(mut self, fetcher: Arc<dyn PropertyFetcher<E>>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    fn try_get_prop(&self, prop_id: PropId) -> GraphProxyResult<Option<Object>> {
+        self.decoded
+            .lock()
+            .expect("lock poisoned")
+            .get_or_decode(
+                &self.inner,
+                prop_id,
+                |prop_id| self.inner.get_property(prop_id),
+                self.fetcher.as_ref(),
+            )
     }
 }
 
@@ -188,22 +310,36 @@ where
     E: StoreEdge + 'static,
 {
     fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
+        self.try_get_property(key).unwrap_or_else(|e| {
+            warn!("{}", e);
+            None
+        })
+    }
+
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
         if let NameOrId::Id(key) = key {
-            self.inner
-                .get_property(*key as PropId)
-                .map(|prop| PropertyValue::Owned(encode_runtime_prop_val(prop)))
+            Ok(self
+                .try_get_prop(*key as PropId)?
+                .map(PropertyValue::Owned))
         } else {
             info!("Have not support getting property by prop_name in gs_store yet");
-            None
+            Ok(None)
         }
     }
 
     fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
         // the case of get_all_properties from vertex;
+        let mut decoded = self.decoded.lock().expect("lock poisoned");
         Some(
             self.inner
                 .get_properties()
-                .map(|(prop_id, prop_val)| encode_runtime_property(prop_id, prop_val))
+                .map(|(prop_id, prop_val)| {
+                    let (key, prop) = encode_runtime_property(prop_id, prop_val);
+                    decoded
+                        .props
+                        .insert(prop_id, Some(prop.clone()));
+                    (key, prop)
+                })
                 .collect(),
         )
     }
@@ -225,3 +361,216 @@ where
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use global_query::store_api::{LabelId as StoreLabelId, VertexId};
+
+    use pegasus_common::codec::{Decode, Encode};
+
+    use super::*;
+    use crate::apis::DynDetails;
+    use crate::GraphProxyError;
+
+    const NUM_PROPS: PropId = 20;
+
+    /// A store vertex that returns all of its properties, and counts the properties decoded (or looked up)
+    struct TestVertex {
+        decode_count: Arc<AtomicUsize>,
+    }
+
+    impl TestVertex {
+        fn decode(&self, prop_id: PropId) -> Option<Property> {
+            self.decode_count.fetch_add(1, Ordering::SeqCst);
+            if prop_id < NUM_PROPS {
+                Some(Property::Long(prop_id as i64))
+            } else {
+                None
+            }
+        }
+    }
+
+    impl StoreVertex for TestVertex {
+        type PI = std::vec::IntoIter<(PropId, Property)>;
+
+        fn get_id(&self) -> VertexId {
+            1
+        }
+
+        fn get_label_id(&self) -> StoreLabelId {
+            0
+        }
+
+        fn get_property(&self, prop_id: PropId) -> Option<Property> {
+            self.decode(prop_id)
+        }
+
+        fn get_properties(&self) -> Self::PI {
+            (0..NUM_PROPS)
+                .filter_map(|prop_id| self.decode(prop_id).map(|prop| (prop_id, prop)))
+                .collect::<Vec<_>>()
+                .into_iter()
+        }
+    }
+
+    fn lazy_vertex(prop_keys: Option<Vec<NameOrId>>) -> (LazyVertexDetails<TestVertex>, Arc<AtomicUsize>) {
+        let decode_count = Arc::new(AtomicUsize::new(0));
+        let vertex = TestVertex { decode_count: decode_count.clone() };
+        (LazyVertexDetails::new(vertex, prop_keys), decode_count)
+    }
+
+    #[test]
+    fn decode_only_accessed_properties() {
+        let prop_keys = vec![NameOrId::Id(3), NameOrId::Id(17)];
+        let (details, decode_count) = lazy_vertex(Some(prop_keys.clone()));
+        for _ in 0..3 {
+            for (key, expected) in prop_keys.iter().zip(vec![3_i64, 17_i64]) {
+                let prop = details
+                    .get_property(key)
+                    .and_then(|prop| prop.try_to_owned());
+                assert_eq!(prop, Some(Object::from(expected)));
+            }
+        }
+        // 2 of the 20 properties are decoded, each once
+        assert_eq!(decode_count.load(Ordering::SeqCst), 2);
+
+        // absent properties are not looked up repeatedly either
+        assert!(details
+            .get_property(&NameOrId::Id(NUM_PROPS as KeyId))
+            .is_none());
+        assert!(details
+            .get_property(&NameOrId::Id(NUM_PROPS as KeyId))
+            .is_none());
+        assert_eq!(decode_count.load(Ordering::SeqCst), 3);
+
+        // all the properties are materialized, not only the required ones
+        let all_props = details.get_all_properties().unwrap();
+        assert_eq!(all_props.len(), NUM_PROPS as usize);
+        assert_eq!(all_props.get(&NameOrId::Id(17)), Some(&Object::from(17_i64)));
+        assert_eq!(decode_count.load(Ordering::SeqCst), 3 + NUM_PROPS as usize);
+        // and are not decoded again on access
+        assert!(details.get_property(&NameOrId::Id(5)).is_some());
+        assert_eq!(decode_count.load(Ordering::SeqCst), 3 + NUM_PROPS as usize);
+    }
+
+    #[test]
+    fn decode_all_properties_if_all_required() {
+        let (details, decode_count) = lazy_vertex(Some(vec![]));
+        assert_eq!(details.get_all_properties().unwrap().len(), NUM_PROPS as usize);
+        assert_eq!(decode_count.load(Ordering::SeqCst), NUM_PROPS as usize);
+    }
+
+    /// Fetch the properties of the vertex by `fetch`, other than 3 fetched along with it, and count the fetches
+    struct TestFetcher<F> {
+        fetch: F,
+        fetch_count: Arc<AtomicUsize>,
+    }
+
+    impl<F> TestFetcher<F>
+    where
+        F: Fn(PropId) -> GraphProxyResult<Vec<(PropId, Property)>> + Send + Sync + 'static,
+    {
+        fn new(fetch: F) -> (Arc<dyn PropertyFetcher<TestVertex>>, Arc<AtomicUsize>) {
+            let fetch_count = Arc::new(AtomicUsize::new(0));
+            (Arc::new(TestFetcher { fetch, fetch_count: fetch_count.clone() }), fetch_count)
+        }
+    }
+
+    impl<F> PropertyFetcher<TestVertex> for TestFetcher<F>
+    where
+        F: Fn(PropId) -> GraphProxyResult<Vec<(PropId, Property)>> + Send + Sync,
+    {
+        fn fetched_prop_ids(&self) -> &[PropId] {
+            &[3]
+        }
+
+        fn fetch(
+            &self, _element: &TestVertex, prop_id: PropId,
+        ) -> GraphProxyResult<Vec<(PropId, Property)>> {
+            self.fetch_count.fetch_add(1, Ordering::SeqCst);
+            (self.fetch)(prop_id)
+        }
+    }
+
+    #[test]
+    fn fetch_properties_not_fetched_on_demand() {
+        let (fetcher, fetch_count) = TestFetcher::new(|_| {
+            Ok((0..NUM_PROPS)
+                .map(|prop_id| (prop_id, Property::Long(prop_id as i64 * 10)))
+                .collect())
+        });
+        let (details, decode_count) = lazy_vertex(Some(vec![]));
+        let details = details.with_fetcher(fetcher);
+        for _ in 0..2 {
+            // fetched along with the vertex
+            let prop = details
+                .get_property(&NameOrId::Id(3))
+                .and_then(|prop| prop.try_to_owned());
+            assert_eq!(prop, Some(Object::from(3_i64)));
+            // fetched on demand, all at once, and cached afterwards
+            for prop_id in [5, 7] {
+                let prop = details
+                    .try_get_property(&NameOrId::Id(prop_id))
+                    .unwrap()
+                    .and_then(|prop| prop.try_to_owned());
+                assert_eq!(prop, Some(Object::from(prop_id as i64 * 10)));
+            }
+            // absent in the storage either
+            assert!(details
+                .try_get_property(&NameOrId::Id(NUM_PROPS as KeyId))
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(decode_count.load(Ordering::SeqCst), 1);
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn fail_to_fetch_properties_on_demand() {
+        let (fetcher, _) = TestFetcher::new(|prop_id| {
+            Err(GraphProxyError::property_not_fetched(&NameOrId::Id(prop_id as KeyId), "for test"))
+        });
+        let (details, _) = lazy_vertex(Some(vec![]));
+        let details = details.with_fetcher(fetcher);
+        match details.try_get_property(&NameOrId::Id(5)) {
+            Err(GraphProxyError::PropertyNotFetched(_)) => {}
+            _ => panic!("the property is expected not fetched"),
+        }
+        assert!(details.get_property(&NameOrId::Id(5)).is_none());
+
+        // while the property absent in the storage is not an error
+        let (fetcher, _) = TestFetcher::new(|_| Ok(vec![]));
+        let (details, _) = lazy_vertex(Some(vec![]));
+        let details = details.with_fetcher(fetcher);
+        assert!(details
+            .try_get_property(&NameOrId::Id(5))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn trimmed_properties_not_fetched_after_shuffle() {
+        let (details, _) = lazy_vertex(Some(vec![NameOrId::Id(3)]));
+        let details = DynDetails::lazy(details);
+        let mut bytes = vec![];
+        details.write_to(&mut bytes).unwrap();
+        let shuffled = DynDetails::read_from(&mut bytes.as_slice()).unwrap();
+
+        let prop = shuffled
+            .try_get_property(&NameOrId::Id(3))
+            .unwrap()
+            .and_then(|prop| prop.try_to_owned());
+        assert_eq!(prop, Some(Object::from(3_i64)));
+        // the property trimmed before shuffled is told apart from the absent one
+        match shuffled.try_get_property(&NameOrId::Id(5)) {
+            Err(GraphProxyError::PropertyNotFetched(_)) => {}
+            _ => panic!("the property is expected not fetched"),
+        }
+        assert!(shuffled
+            .get_property(&NameOrId::Id(5))
+            .is_none());
+    }
+}
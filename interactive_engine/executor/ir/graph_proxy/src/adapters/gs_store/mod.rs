@@ -16,7 +16,10 @@
 mod details;
 mod partitioner;
 mod read_graph;
+mod store_setup;
 mod translation;
 
 pub use partitioner::*;
-pub use read_graph::{create_gs_store, GraphScopeStore};
+pub use read_graph::{
+    create_gs_store, create_gs_store_with_setup_timeout, GraphScopeStore, PartitionStatistics,
+};
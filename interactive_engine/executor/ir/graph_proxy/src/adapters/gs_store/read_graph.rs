@@ -13,28 +13,45 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::any::Any;
+use std::collections::VecDeque;
 use std::convert::TryInto;
-use std::sync::Arc;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use ahash::{HashMap, HashMapExt};
+use ahash::{HashMap, HashMapExt, HashSet};
 use dyn_type::{Object, Primitives};
 use global_query::store_api::prelude::{Condition, Property};
 use global_query::store_api::{
-    Edge as StoreEdge, LabelId as StoreLabelId, PartitionId, Vertex as StoreVertex, VertexId,
+    Edge as StoreEdge, EdgeId, LabelId as StoreLabelId, PartitionId, Vertex as StoreVertex, VertexId,
 };
 use global_query::store_api::{PropId, SnapshotId};
 use global_query::{
-    GlobalGraphQuery, GraphPartitionManager, PartitionLabeledVertexIds, PartitionVertexIds,
+    GlobalGraphQuery, GraphPartitionManager, PartitionLabeledVertexIds, PartitionVertexIds, StoreCaps,
 };
 use graph_store::utils::IterList;
 use ir_common::{KeyId, LabelId, NameOrId, OneOrMany};
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
 
-use crate::adapters::gs_store::details::{LazyEdgeDetails, LazyVertexDetails};
-use crate::apis::graph::PKV;
+use crate::adapters::gs_store::details::{
+    encode_runtime_prop_val, LazyEdgeDetails, LazyVertexDetails, PropertyFetcher,
+};
+use crate::adapters::gs_store::store_setup::StoreSetupPool;
+use crate::apis::graph::{PKV, SKIPPED_PARTITIONS_METRIC};
 use crate::apis::ClusterInfo;
-use crate::apis::{from_fn, Direction, DynDetails, Edge, QueryParams, ReadGraph, Statement, Vertex, ID};
+use crate::apis::{
+    from_fn, Direction, DynDetails, Edge, GraphElement, QueryParams, ReadConsistency, ReadGraph, Statement,
+    Vertex, ID,
+};
+use crate::utils::expr::eval_batch::{BatchFilterIter, ColumnPredicate, DEFAULT_FILTER_BATCH_SIZE};
 use crate::utils::expr::eval_pred::PEvaluator;
-use crate::{filter_limit, filter_sample_limit, limit_n, sample_limit};
+use crate::{
+    filter_limit, filter_reservoir_sample_limit, filter_sample_limit, limit_n, reservoir_sample_limit,
+    sample_limit,
+};
 use crate::{GraphProxyError, GraphProxyResult};
 
 // Should be identical to the param_name given by compiler
@@ -43,6 +60,41 @@ const SNAPSHOT_ID: &str = "SID";
 const DEFAULT_SNAPSHOT_ID: SnapshotId = SnapshotId::MAX - 1;
 // This represents the primary key of GraphScopeStore
 const GS_STORE_PK: KeyId = 0;
+// The time that the statistics of partitions are cached by default
+const DEFAULT_STATISTICS_TTL: Duration = Duration::from_secs(60);
+// The max number of ids fetched by one call of the storage in `get_vertex` by default
+const DEFAULT_MAX_BATCH_SIZE: usize = 1024;
+// The number of threads that set up the scans in the storage within a time limit
+const DEFAULT_STORE_SETUP_THREADS: usize = 8;
+// The max number of scans waiting for a thread to set up, beyond which the scans fail right away
+const DEFAULT_STORE_SETUP_QUEUE_SIZE: usize = 64;
+// The min interval between the warnings of reading an unavailable snapshot in best effort
+const STALE_SNAPSHOT_WARN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The number of vertices and edges residing in each partition of a server,
+/// e.g., for load balancing and cost-based optimization.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartitionStatistics {
+    pub partition_vertex_counts: HashMap<PartitionId, u64>,
+    pub partition_edge_counts: HashMap<PartitionId, u64>,
+    pub total_vertex_count: u64,
+    pub total_edge_count: u64,
+}
+
+/// The statistics of partitions, and the selectivities of filters, cached for `ttl` since they are computed.
+struct StatisticsCache {
+    ttl: Duration,
+    cached: Option<(Instant, PartitionStatistics)>,
+    /// the selectivities keyed by the labels and the filter pushed down to the storage,
+    /// see `GraphScopeStore::collect_filter_selectivity()`
+    selectivities: HashMap<String, (Instant, f64)>,
+}
+
+impl Default for StatisticsCache {
+    fn default() -> Self {
+        StatisticsCache { ttl: DEFAULT_STATISTICS_TTL, cached: None, selectivities: HashMap::new() }
+    }
+}
 
 pub struct GraphScopeStore<V, VI, E, EI>
 where
@@ -57,20 +109,64 @@ where
     cluster_info: Arc<dyn ClusterInfo>,
     row_filter_pushdown: bool,
     column_filter_pushdown: bool,
+    /// Whether the `Regex` comparisons in the row filter are pushed down, as supported by the storage,
+    /// or else the row filter is evaluated out of the storage as a whole
+    regex_pushdown: bool,
+    /// The pool to set up the scans in the storage within a time limit, which is unbounded if not given
+    store_setup_pool: Option<StoreSetupPool>,
+    statistics_cache: Mutex<StatisticsCache>,
+    /// The max number of ids fetched by one call of the storage in `get_vertex`, beyond which
+    /// the ids are fetched in chunks, to keep each request within the size limit of the storage
+    max_batch_size: AtomicUsize,
+    /// When an unavailable snapshot was last warned of, with which the warnings are rate-limited
+    stale_snapshot_warned_at: Mutex<Option<Instant>>,
 }
 
+/// Create a `GraphScopeStore`, where the filter pushdown flags are validated against the
+/// capabilities of the `store`, and an `UnsupportedOperation` error is returned if any is not supported.
 #[allow(dead_code)]
 pub fn create_gs_store<V, VI, E, EI>(
     store: Arc<dyn GlobalGraphQuery<V = V, E = E, VI = VI, EI = EI>>,
     partition_manager: Arc<dyn GraphPartitionManager>, server_partitions: Vec<PartitionId>,
     cluster_info: Arc<dyn ClusterInfo>, row_filter_push_down: bool, column_filter_push_down: bool,
-) -> Arc<GraphScopeStore<V, VI, E, EI>>
+) -> GraphProxyResult<Arc<GraphScopeStore<V, VI, E, EI>>>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    create_gs_store_with_setup_timeout(
+        store,
+        partition_manager,
+        server_partitions,
+        cluster_info,
+        row_filter_push_down,
+        column_filter_push_down,
+        None,
+    )
+}
+
+/// Create a `GraphScopeStore` as `create_gs_store`, that further fails a scan with a `Timeout` error,
+/// if the storage takes longer than `store_setup_timeout` to set up the scan, e.g., due to a slow
+/// partition manager; and the setup is unbounded if `store_setup_timeout` is not given.
+/// Notice that this is irrelevant to the time limit of the job, which is governed by pegasus.
+/// The scans are set up on a bounded pool of threads shared by the store, see `StoreSetupPool`.
+#[allow(dead_code)]
+pub fn create_gs_store_with_setup_timeout<V, VI, E, EI>(
+    store: Arc<dyn GlobalGraphQuery<V = V, E = E, VI = VI, EI = EI>>,
+    partition_manager: Arc<dyn GraphPartitionManager>, server_partitions: Vec<PartitionId>,
+    cluster_info: Arc<dyn ClusterInfo>, row_filter_push_down: bool, column_filter_push_down: bool,
+    store_setup_timeout: Option<Duration>,
+) -> GraphProxyResult<Arc<GraphScopeStore<V, VI, E, EI>>>
 where
     V: StoreVertex + 'static,
     VI: Iterator<Item = V> + Send + 'static,
     E: StoreEdge + 'static,
     EI: Iterator<Item = E> + Send + 'static,
 {
+    let caps = store.get_store_caps();
+    validate_store_caps(caps, row_filter_push_down, column_filter_push_down)?;
     let graph = GraphScopeStore {
         store,
         partition_manager,
@@ -78,8 +174,46 @@ where
         cluster_info,
         row_filter_pushdown: row_filter_push_down,
         column_filter_pushdown: column_filter_push_down,
+        regex_pushdown: caps.supports_regex,
+        store_setup_pool: store_setup_timeout.map(|timeout| {
+            StoreSetupPool::new(timeout, DEFAULT_STORE_SETUP_THREADS, DEFAULT_STORE_SETUP_QUEUE_SIZE)
+        }),
+        statistics_cache: Mutex::new(StatisticsCache::default()),
+        max_batch_size: AtomicUsize::new(DEFAULT_MAX_BATCH_SIZE),
+        stale_snapshot_warned_at: Mutex::new(None),
     };
-    Arc::new(graph)
+    Ok(Arc::new(graph))
+}
+
+impl<V, VI, E, EI> GraphScopeStore<V, VI, E, EI>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    /// Set the max number of ids fetched by one call of the storage in `get_vertex`, which can be
+    /// done on the shared store, e.g., from the configuration of the server.
+    pub fn set_max_batch_size(&self, max_batch_size: usize) {
+        self.max_batch_size
+            .store(max_batch_size.max(1), Ordering::Relaxed);
+    }
+
+    fn max_batch_size(&self) -> usize {
+        self.max_batch_size.load(Ordering::Relaxed)
+    }
+}
+
+fn validate_store_caps(
+    caps: StoreCaps, row_filter_push_down: bool, column_filter_push_down: bool,
+) -> GraphProxyResult<()> {
+    if row_filter_push_down && !caps.supports_row_filter {
+        Err(GraphProxyError::unsupported_error("row filter pushdown"))
+    } else if column_filter_push_down && !caps.supports_column_filter {
+        Err(GraphProxyError::unsupported_error("column filter pushdown"))
+    } else {
+        Ok(())
+    }
 }
 
 impl<V, VI, E, EI> ReadGraph for GraphScopeStore<V, VI, E, EI>
@@ -93,60 +227,28 @@ where
         &self, params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
         let worker_partitions = assign_worker_partitions(&self.server_partitions, &self.cluster_info)?;
+        let worker_partitions = self.prune_partitions_by_pks(worker_partitions, params)?;
         debug!("scan_vertex worker_partitions: {:?}", worker_partitions);
-        if !worker_partitions.is_empty() {
-            let store = self.store.clone();
-            let si = get_snapshot_id(params);
-            let label_ids = encode_storage_labels(params.labels.as_ref())?;
-            let row_filter = params.filter.clone();
-
-            let (condition, row_filter_exists_but_not_pushdown) =
-                encode_storage_row_filter_condition(row_filter.as_ref(), self.row_filter_pushdown);
-
-            let column_filter_pushdown = self.column_filter_pushdown;
-            // props that will be returned by storage layer
-            let prop_ids = if column_filter_pushdown {
-                // props that will be used in further computations
-                let cache_prop_ids = encode_storage_prop_keys(params.columns.as_ref())?;
-                if row_filter_exists_but_not_pushdown {
-                    // need to call filter_limit!, so get columns in row_filter and params.columns
-                    extract_needed_columns(row_filter.as_ref(), cache_prop_ids.as_ref())?
-                } else {
-                    // row_filter pushdown success, only need params.columns
-                    cache_prop_ids.clone()
-                }
-            } else {
-                // now, ir assume that it can get all properties from a vertex/edge locally
-                // just column filter is not pushdown.
-                get_all_storage_props()
-            };
-
-            let columns = params.columns.clone();
-            let result = store
-                .get_all_vertices(
-                    si,
-                    label_ids.as_ref(),
-                    // None means no filter condition pushed down to storage as not supported yet. Same as follows.
-                    condition.as_ref(),
-                    // None means no need to dedup by properties. Same as follows.
-                    None,
-                    prop_ids.as_ref(),
-                    // Zero limit means no limit. Same as follows.
-                    0,
-                    // Each worker will scan the partitions returned by assign_worker_partitions(). Same as follows.
-                    worker_partitions.as_ref(),
-                )
-                .map(move |v| to_runtime_vertex(v, columns.clone()));
-
-            if row_filter_exists_but_not_pushdown {
-                // fall back to call filter_limit! to do row filter
-                Ok(filter_sample_limit!(result, row_filter, params.sample_ratio, params.limit))
-            } else {
-                Ok(sample_limit!(result, params.sample_ratio, params.limit))
-            }
+        if params.skip_failed_partitions()? {
+            Ok(Box::new(self.scan_vertex_skipping_failed_partitions(params, worker_partitions)?))
         } else {
-            Ok(Box::new(std::iter::empty()))
+            self.scan_vertex_in_partitions(params, worker_partitions)
+        }
+    }
+
+    fn scan_vertex_by_partition(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<
+        Box<dyn Iterator<Item = (PartitionId, Box<dyn Iterator<Item = Vertex> + Send>)> + Send>,
+    > {
+        let worker_partitions = assign_worker_partitions(&self.server_partitions, &self.cluster_info)?;
+        debug!("scan_vertex_by_partition worker_partitions: {:?}", worker_partitions);
+        let mut groups = Vec::with_capacity(worker_partitions.len());
+        for partition_id in worker_partitions {
+            // the scan of each partition is lazy, until its group is iterated
+            groups.push((partition_id, self.scan_vertex_in_partitions(params, vec![partition_id])?));
         }
+        Ok(Box::new(groups.into_iter()))
     }
 
     fn index_scan_vertex(
@@ -158,15 +260,7 @@ where
         // 1. all workers are going to search for gid, and compute  which partition this vertex belongs;
         // 2. the worker assigned for this partition will further confirm the result by calling get_vertex() to see if this vertex exists
         let store_label_id = encode_storage_label(label_id)?;
-        let store_indexed_values = match primary_key {
-            OneOrMany::One(pkv) => {
-                vec![encode_store_prop_val(pkv[0].1.clone())]
-            }
-            OneOrMany::Many(pkvs) => pkvs
-                .iter()
-                .map(|(_pk, value)| encode_store_prop_val(value.clone()))
-                .collect(),
-        };
+        let store_indexed_values = encode_store_pk_vals(primary_key);
         debug!("index_scan_vertex store_indexed_values {:?}", store_indexed_values);
         if let Some(vid) = self
             .partition_manager
@@ -187,17 +281,47 @@ where
         }
     }
 
+    fn index_scan_vertex_local(
+        &self, partition_id: PartitionId, label_id: LabelId, primary_key: &PKV, params: &QueryParams,
+    ) -> GraphProxyResult<Option<Vertex>> {
+        // Different from index_scan_vertex(), only the worker assigned for the given partition
+        // probes the index of this partition, and there is no need to search for gid globally.
+        let worker_partitions = assign_worker_partitions(&self.server_partitions, &self.cluster_info)?;
+        if !worker_partitions.contains(&partition_id) {
+            return Ok(None);
+        }
+        let store_label_id = encode_storage_label(label_id)?;
+        let store_indexed_values = encode_store_pk_vals(primary_key);
+        if let Some(vid) = self
+            .partition_manager
+            .get_vertex_id_by_primary_keys_in_partition(
+                partition_id,
+                store_label_id,
+                store_indexed_values.as_ref(),
+            )
+        {
+            debug!("index_scan_vertex_local vid {:?} in partition {:?}", vid, partition_id);
+            Ok(self.get_vertex(&[vid as ID], params)?.next())
+        } else {
+            Ok(None)
+        }
+    }
+
     fn scan_edge(&self, params: &QueryParams) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
         let worker_partitions = assign_worker_partitions(&self.server_partitions, &self.cluster_info)?;
         if !worker_partitions.is_empty() {
             let store = self.store.clone();
-            let si = get_snapshot_id(params);
+            let si = self.get_snapshot_id(params)?;
+            let sample_seed = params.get_sample_seed()?;
             let label_ids = encode_storage_labels(params.labels.as_ref())?;
             let row_filter = params.filter.clone();
 
             // the same as above
-            let (condition, row_filter_exists_but_not_pushdown) =
-                encode_storage_row_filter_condition(row_filter.as_ref(), self.row_filter_pushdown);
+            let (condition, row_filter_exists_but_not_pushdown) = encode_storage_row_filter_condition(
+                row_filter.as_ref(),
+                self.row_filter_pushdown,
+                self.regex_pushdown,
+            );
 
             let column_filter_pushdown = self.column_filter_pushdown;
             let prop_ids = if column_filter_pushdown {
@@ -210,22 +334,27 @@ where
             } else {
                 get_all_storage_props()
             };
+            let dedup_prop_ids = params.dedup_props.clone();
 
             let result = store.get_all_edges(
                 si,
                 label_ids.as_ref(),
                 condition.as_ref(),
-                None,
+                dedup_prop_ids.as_ref(),
                 prop_ids.as_ref(),
                 0,
                 worker_partitions.as_ref(),
             );
-            let iter = RuntimeEdgeIter::new(result, true, params.columns.clone());
+            let edge_fetcher = self
+                .property_fetch_context(si, prop_ids.as_ref())
+                .map(|ctx| ctx.edge_fetcher(true));
+            let iter =
+                RuntimeEdgeIter::new(result, true, params.columns.clone()).with_fetcher(edge_fetcher);
 
             if row_filter_exists_but_not_pushdown {
-                Ok(filter_sample_limit!(iter, row_filter, params.sample_ratio, params.limit))
+                Ok(filter_sample_limit!(iter, row_filter, params.sample_ratio, sample_seed, params.limit))
             } else {
-                Ok(sample_limit!(iter, params.sample_ratio, params.limit))
+                Ok(sample_limit!(iter, params.sample_ratio, sample_seed, params.limit))
             }
         } else {
             Ok(Box::new(std::iter::empty()))
@@ -236,7 +365,7 @@ where
         &self, ids: &[ID], params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
         let store = self.store.clone();
-        let si = get_snapshot_id(params);
+        let si = self.get_snapshot_id(params)?;
 
         let column_filter_pushdown = self.column_filter_pushdown;
         // also need props in filter, because `filter_limit!`
@@ -250,22 +379,72 @@ where
         };
 
         let filter = params.filter.clone();
+        let fetch_ctx = self.property_fetch_context(si, prop_ids.as_ref());
+        let max_batch_size = self.max_batch_size();
+        let vertices: Box<dyn Iterator<Item = V> + Send> = if ids.len() <= max_batch_size {
+            let partition_label_vertex_ids =
+                get_partition_label_vertex_ids(ids, self.partition_manager.clone());
+            Box::new(store.get_vertex_properties(si, partition_label_vertex_ids, prop_ids.as_ref()))
+        } else {
+            // fetch the ids in chunks, with the store called lazily per chunk
+            let partition_manager = self.partition_manager.clone();
+            let chunks: Vec<Vec<ID>> = ids
+                .chunks(max_batch_size)
+                .map(|chunk| chunk.to_vec())
+                .collect();
+            Box::new(chunks.into_iter().flat_map(move |chunk| {
+                let partition_label_vertex_ids =
+                    get_partition_label_vertex_ids(&chunk, partition_manager.clone());
+                store.get_vertex_properties(si, partition_label_vertex_ids, prop_ids.as_ref())
+            }))
+        };
+
+        let columns = params.columns.clone();
+        let result = vertices.map(move |v| to_runtime_vertex(v, columns.clone(), fetch_ctx.as_ref()));
+
+        Ok(filter_limit!(result, filter, None))
+    }
+
+    fn get_vertex_with_misses(
+        &self, ids: &[ID], params: &QueryParams,
+    ) -> GraphProxyResult<(Box<dyn Iterator<Item = Vertex> + Send>, Vec<ID>)> {
+        let si = self.get_snapshot_id(params)?;
+        let prop_ids = if self.column_filter_pushdown {
+            let cache_prop_ids = encode_storage_prop_keys(params.columns.as_ref())?;
+            extract_needed_columns(params.filter.as_ref(), cache_prop_ids.as_ref())?
+        } else {
+            get_all_storage_props()
+        };
+
         let partition_label_vertex_ids =
             get_partition_label_vertex_ids(ids, self.partition_manager.clone());
-
         let columns = params.columns.clone();
-        let result = store
+        let fetch_ctx = self.property_fetch_context(si, prop_ids.as_ref());
+        // the ids may reside in remote partitions, which are fetched in the same round trip,
+        // and the misses are the requested ids of each partition that the store does not return.
+        let found: Vec<Vertex> = self
+            .store
             .get_vertex_properties(si, partition_label_vertex_ids.clone(), prop_ids.as_ref())
-            .map(move |v| to_runtime_vertex(v, columns.clone()));
+            .map(move |v| to_runtime_vertex(v, columns.clone(), fetch_ctx.as_ref()))
+            .collect();
+        let found_ids: HashSet<ID> = found.iter().map(|v| v.id()).collect();
+        let misses = partition_label_vertex_ids
+            .into_iter()
+            .flat_map(|(_pid, label_vertex_ids)| label_vertex_ids)
+            .flat_map(|(_label, vertex_ids)| vertex_ids)
+            .map(|vid| vid as ID)
+            .filter(|id| !found_ids.contains(id))
+            .collect();
 
-        Ok(filter_limit!(result, filter, None))
+        let filter = params.filter.clone();
+        Ok((filter_limit!(found.into_iter(), filter, None), misses))
     }
 
     fn get_edge(
         &self, _ids: &[ID], _params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
         // TODO(bingqing): adapt get_edge when graphscope support this
-        Err(GraphProxyError::query_store_error("GraphScope storage does not support get_edge for now"))?
+        Err(GraphProxyError::unsupported_error("get_edge in GraphScope storage"))?
     }
 
     fn prepare_explore_vertex(
@@ -273,14 +452,27 @@ where
     ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
         let row_filter = params.filter.clone();
 
-        let (condition, row_filter_exists_but_not_push_down) =
-            encode_storage_row_filter_condition(row_filter.as_ref(), self.row_filter_pushdown);
+        let (condition, row_filter_exists_but_not_push_down) = encode_storage_row_filter_condition(
+            row_filter.as_ref(),
+            self.row_filter_pushdown,
+            self.regex_pushdown,
+        );
 
-        let limit = params.limit.clone();
         let store = self.store.clone();
         let partition_manager = self.partition_manager.clone();
-        let si = get_snapshot_id(params);
+        let si = self.get_snapshot_id(params)?;
         let edge_label_ids = encode_storage_labels(params.labels.as_ref())?;
+        let end_v_label_ids = params
+            .end_v_labels
+            .as_ref()
+            .map(encode_storage_labels)
+            .transpose()?;
+        // the limit has to be applied after filtering by the end vertex labels, if any
+        let (limit, end_v_limit) = if end_v_label_ids.is_some() {
+            (None, params.limit.clone())
+        } else {
+            (params.limit.clone(), None)
+        };
 
         let stmt = from_fn(move |v: ID| {
             let src_id = get_partition_vertex_id(v, partition_manager.clone());
@@ -325,12 +517,15 @@ where
                 }
             };
             let iters = iter.map(|(_src, vi)| vi).collect();
-            let iter_list = IterList::new(iters).map(move |v| to_empty_vertex(&v));
+            let end_v_label_ids = end_v_label_ids.clone();
+            let iter_list = IterList::new(iters)
+                .filter(move |v| is_expected_label(end_v_label_ids.as_ref(), v.get_label_id()))
+                .map(move |v| to_empty_vertex(&v));
 
             if row_filter_exists_but_not_push_down {
-                Ok(filter_limit!(iter_list, row_filter, None))
+                Ok(filter_limit!(iter_list, row_filter, end_v_limit))
             } else {
-                Ok(Box::new(iter_list))
+                Ok(limit_n!(iter_list, end_v_limit))
             }
         });
         Ok(stmt)
@@ -340,14 +535,17 @@ where
         &self, direction: Direction, params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
         let store = self.store.clone();
-        let si = get_snapshot_id(params);
+        let si = self.get_snapshot_id(params)?;
 
         let partition_manager = self.partition_manager.clone();
         let row_filter = params.filter.clone();
 
         // the same as above
-        let (condition, row_filter_exists_but_not_pushdown) =
-            encode_storage_row_filter_condition(row_filter.as_ref(), self.row_filter_pushdown);
+        let (condition, row_filter_exists_but_not_pushdown) = encode_storage_row_filter_condition(
+            row_filter.as_ref(),
+            self.row_filter_pushdown,
+            self.regex_pushdown,
+        );
 
         let column_filter_pushdown = self.column_filter_pushdown;
         let prop_ids = if column_filter_pushdown {
@@ -361,9 +559,28 @@ where
             get_all_storage_props()
         };
 
-        let limit = params.limit.clone();
         let columns = params.columns.clone();
+        let fetch_ctx = self.property_fetch_context(si, prop_ids.as_ref());
+        let out_edge_fetcher = fetch_ctx
+            .clone()
+            .map(|ctx| ctx.edge_fetcher(true));
+        let in_edge_fetcher = fetch_ctx.map(|ctx| ctx.edge_fetcher(false));
         let edge_label_ids = encode_storage_labels(params.labels.as_ref())?;
+        // the store does not support triplet-filtered adjacency yet,
+        // so the edges are filtered by the end vertex labels before turned into runtime edges
+        let end_v_label_ids = params
+            .end_v_labels
+            .as_ref()
+            .map(encode_storage_labels)
+            .transpose()?;
+        let per_vertex_sample = params.per_vertex_sample;
+        let sample_seed = params.get_sample_seed()?;
+        // the limit has to be applied after filtering by the end vertex labels, or sampling, if any
+        let (limit, end_v_limit) = if end_v_label_ids.is_some() || per_vertex_sample.is_some() {
+            (None, params.limit.clone())
+        } else {
+            (params.limit.clone(), None)
+        };
 
         let stmt = from_fn(move |v: ID| {
             let src_id = get_partition_vertex_id(v, partition_manager.clone());
@@ -379,7 +596,9 @@ where
                         limit.unwrap_or(0),
                     );
                     if let Some(ei) = res_iter.next().map(|(_src, ei)| ei) {
-                        let iter = RuntimeEdgeIter::new(ei, true, columns.clone());
+                        let iter = RuntimeEdgeIter::new(ei, true, columns.clone())
+                            .with_end_v_labels(end_v_label_ids.clone())
+                            .with_fetcher(out_edge_fetcher.clone());
                         IterList::new(vec![iter])
                     } else {
                         IterList::new(vec![])
@@ -396,7 +615,9 @@ where
                         limit.unwrap_or(0),
                     );
                     if let Some(ei) = res_iter.next().map(|(_dst, ei)| ei) {
-                        let iter = RuntimeEdgeIter::new(ei, false, columns.clone());
+                        let iter = RuntimeEdgeIter::new(ei, false, columns.clone())
+                            .with_end_v_labels(end_v_label_ids.clone())
+                            .with_fetcher(in_edge_fetcher.clone());
                         IterList::new(vec![iter])
                     } else {
                         IterList::new(vec![])
@@ -423,26 +644,83 @@ where
                     );
                     let mut iters = vec![];
                     if let Some(ei) = res_out_iter.next().map(|(_src, ei)| ei) {
-                        let iter = RuntimeEdgeIter::new(ei, true, columns.clone());
+                        let iter = RuntimeEdgeIter::new(ei, true, columns.clone())
+                            .with_end_v_labels(end_v_label_ids.clone())
+                            .with_fetcher(out_edge_fetcher.clone());
                         iters.push(iter);
                     }
                     if let Some(ei) = res_in_iter.next().map(|(_dst, ei)| ei) {
-                        let iter = RuntimeEdgeIter::new(ei, false, columns.clone());
+                        let iter = RuntimeEdgeIter::new(ei, false, columns.clone())
+                            .with_end_v_labels(end_v_label_ids.clone())
+                            .with_fetcher(in_edge_fetcher.clone());
                         iters.push(iter);
                     }
                     IterList::new(iters)
                 }
             };
+            // sampled independently per source vertex, even with the same seed
+            let seed = sample_seed.map(|seed| seed ^ v as u64);
             if row_filter_exists_but_not_pushdown {
-                Ok(filter_limit!(iter_list, row_filter, None))
+                Ok(filter_reservoir_sample_limit!(
+                    iter_list,
+                    row_filter,
+                    per_vertex_sample,
+                    seed,
+                    end_v_limit
+                ))
             } else {
-                Ok(Box::new(iter_list))
+                Ok(reservoir_sample_limit!(iter_list, per_vertex_sample, seed, end_v_limit))
             }
         });
         Ok(stmt)
     }
 
+    fn count_neighbors(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        let si = self.get_snapshot_id(params)?;
+        if let Some(degree) = self.count_edges_by_store(si, id, direction, &params.labels, None)? {
+            Ok(degree)
+        } else {
+            // the degree is not reported by the store, e.g., the vertex is not found in the partition,
+            // so count by iterating the neighbors instead
+            let params = QueryParams {
+                labels: params.labels.clone(),
+                extra_params: params.extra_params.clone(),
+                ..Default::default()
+            };
+            Ok(self
+                .prepare_explore_vertex(direction, &params)?
+                .exec(id)?
+                .count() as u64)
+        }
+    }
+
+    fn get_degree(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        let (condition, row_filter_exists_but_not_pushdown) = encode_storage_row_filter_condition(
+            params.filter.as_ref(),
+            self.row_filter_pushdown,
+            self.regex_pushdown,
+        );
+        if !row_filter_exists_but_not_pushdown
+            && params.sample_ratio.is_none()
+            && params.per_vertex_sample.is_none()
+            && params.end_v_labels.is_none()
+        {
+            // the filter, if any, is counted by the store as well
+            let si = self.get_snapshot_id(params)?;
+            if let Some(degree) =
+                self.count_edges_by_store(si, id, direction, &params.labels, condition.as_ref())?
+            {
+                return Ok(params.limit_count(degree));
+            }
+        }
+        Ok(self
+            .prepare_explore_edge(direction, params)?
+            .exec(id)?
+            .count() as u64)
+    }
+
     fn get_primary_key(&self, id: &ID) -> GraphProxyResult<Option<PKV>> {
+        // the primary key is regarded as the outer id of the vertex, as its label is unknown
         let store = self.store.clone();
         let outer_id = store.translate_vertex_id(*id as VertexId);
         let pk_val = Object::from(outer_id);
@@ -450,20 +728,32 @@ where
         Ok(Some((GS_STORE_PK.into(), pk_val).into()))
     }
 
+    fn get_primary_key_with_label(
+        &self, id: &ID, label: &LabelId, params: &QueryParams,
+    ) -> GraphProxyResult<Option<PKV>> {
+        // the primary key, of possibly multiple columns, is read from the properties declared by the schema
+        let si = self.get_snapshot_id(params)?;
+        if let Some(pkv) = self.get_primary_key_by_schema(si, *id, *label as StoreLabelId)? {
+            return Ok(Some(pkv));
+        }
+        self.get_primary_key(id)
+    }
+
     fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64> {
         if params.filter.is_some() {
             // the filter cannot be pushed down to store,
-            // so we need to scan all vertices with filter and then count
+            // so we need to scan all vertices with filter and then count,
+            // where the scan stops once `params.limit` vertices are found
             Ok(self.scan_vertex(params)?.count() as u64)
         } else {
             let worker_partitions = assign_worker_partitions(&self.server_partitions, &self.cluster_info)?;
             if !worker_partitions.is_empty() {
                 let store = self.store.clone();
-                let si = get_snapshot_id(params);
+                let si = self.get_snapshot_id(params)?;
                 let label_ids = encode_storage_labels(params.labels.as_ref())?;
                 let count =
                     store.count_all_vertices(si, label_ids.as_ref(), None, worker_partitions.as_ref());
-                Ok(count)
+                Ok(params.limit_count(count))
             } else {
                 Ok(0)
             }
@@ -477,322 +767,2737 @@ where
             let worker_partitions = assign_worker_partitions(&self.server_partitions, &self.cluster_info)?;
             if !worker_partitions.is_empty() {
                 let store = self.store.clone();
-                let si = get_snapshot_id(params);
+                let si = self.get_snapshot_id(params)?;
                 let label_ids = encode_storage_labels(params.labels.as_ref())?;
                 let count = store.count_all_edges(si, label_ids.as_ref(), None, worker_partitions.as_ref());
-                Ok(count)
+                Ok(params.limit_count(count))
             } else {
                 Ok(0)
             }
         }
     }
-}
-
-fn get_snapshot_id(params: &QueryParams) -> SnapshotId {
-    let si = params
-        .get_extra_param(SNAPSHOT_ID)
-        .map(|s| {
-            s.parse::<SnapshotId>()
-                .unwrap_or(DEFAULT_SNAPSHOT_ID)
-        })
-        .unwrap_or(DEFAULT_SNAPSHOT_ID);
-    si
-}
-
-#[inline]
-fn to_runtime_vertex<V>(v: V, prop_keys: Option<Vec<NameOrId>>) -> Vertex
-where
-    V: 'static + StoreVertex,
-{
-    let id = v.get_id() as ID;
-    let label = encode_runtime_v_label(&v);
-    let details = LazyVertexDetails::new(v, prop_keys);
-    Vertex::new(id, Some(label), DynDetails::lazy(details))
-}
 
-#[inline]
-fn to_empty_vertex<V: StoreVertex>(v: &V) -> Vertex {
-    let id = v.get_id() as ID;
-    let label = encode_runtime_v_label(v);
-    Vertex::new(id, Some(label), DynDetails::default())
+    fn count_vertex_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        if params.filter.is_some() {
+            Ok(params.limit_count(self.get_vertex(ids, params)?.count() as u64))
+        } else {
+            // only check the existence of the ids, without building the runtime vertices
+            let si = self.get_snapshot_id(params)?;
+            let count = ids
+                .chunks(self.max_batch_size())
+                .map(|chunk| {
+                    let partition_label_vertex_ids =
+                        get_partition_label_vertex_ids(chunk, self.partition_manager.clone());
+                    self.store
+                        .count_vertices_by_ids(si, partition_label_vertex_ids)
+                })
+                .sum();
+            Ok(params.limit_count(count))
+        }
+    }
 }
 
-pub struct RuntimeEdgeIter<E, EI>
+impl<V, VI, E, EI> GraphScopeStore<V, VI, E, EI>
 where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
     E: StoreEdge + 'static,
-    EI: Iterator<Item = E> + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
 {
-    iter: EI,
-    from_src: bool,
-    prop_keys: Option<Vec<NameOrId>>,
-}
+    /// The number of vertices and edges in each partition of this server, of the latest graph.
+    /// The statistics are cached for the ttl set by `set_statistics_ttl()`, 60 seconds by default.
+    pub fn statistics(&self) -> GraphProxyResult<PartitionStatistics> {
+        let mut cache = self
+            .statistics_cache
+            .lock()
+            .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?;
+        if let Some((computed_at, statistics)) = cache.cached.as_ref() {
+            if computed_at.elapsed() < cache.ttl {
+                return Ok(statistics.clone());
+            }
+        }
+        let mut statistics = PartitionStatistics::default();
+        let all_labels = vec![];
+        for partition in &self.server_partitions {
+            let partitions = vec![*partition];
+            let vertex_count =
+                self.store
+                    .count_all_vertices(DEFAULT_SNAPSHOT_ID, &all_labels, None, &partitions);
+            let edge_count =
+                self.store
+                    .count_all_edges(DEFAULT_SNAPSHOT_ID, &all_labels, None, &partitions);
+            statistics
+                .partition_vertex_counts
+                .insert(*partition, vertex_count);
+            statistics
+                .partition_edge_counts
+                .insert(*partition, edge_count);
+            statistics.total_vertex_count += vertex_count;
+            statistics.total_edge_count += edge_count;
+        }
+        cache.cached = Some((Instant::now(), statistics.clone()));
+        Ok(statistics)
+    }
 
-impl<E, EI> RuntimeEdgeIter<E, EI>
-where
-    E: StoreEdge + 'static,
-    EI: Iterator<Item = E> + 'static,
-{
-    pub fn new(iter: EI, from_src: bool, prop_keys: Option<Vec<NameOrId>>) -> Self {
-        RuntimeEdgeIter { iter, from_src, prop_keys }
+    /// Set the time to cache the statistics of partitions, which also invalidates the cached ones.
+    pub fn set_statistics_ttl(&self, ttl: Duration) -> GraphProxyResult<()> {
+        let mut cache = self
+            .statistics_cache
+            .lock()
+            .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?;
+        cache.ttl = ttl;
+        cache.cached = None;
+        cache.selectivities.clear();
+        Ok(())
     }
-}
 
-impl<E, EI> Iterator for RuntimeEdgeIter<E, EI>
-where
-    E: StoreEdge + 'static,
-    EI: Iterator<Item = E> + 'static,
-{
-    type Item = Edge;
+    /// Estimate the fraction of the vertices of `params.labels` in the partitions of this server
+    /// that pass `params.filter`, e.g., for ordering the operators in cost-based planning.
+    /// It never scans the storage, but returns the selectivity cached by `collect_filter_selectivity()`
+    /// within the statistics ttl, or `None` if there is none.
+    pub fn estimate_filter_selectivity(&self, params: &QueryParams) -> Option<f64> {
+        if params.filter.is_none() {
+            return Some(1.0);
+        }
+        let key = self.selectivity_key(params)?;
+        let cache = self.statistics_cache.lock().ok()?;
+        cache
+            .selectivities
+            .get(&key)
+            .filter(|(computed_at, _)| computed_at.elapsed() < cache.ttl)
+            .map(|(_, selectivity)| *selectivity)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(next) = self.iter.next() {
-            Some(to_runtime_edge(next, self.prop_keys.clone(), self.from_src))
-        } else {
-            None
+    /// Compute the selectivity of `params.filter` to be estimated by `estimate_filter_selectivity()`.
+    /// As the storage keeps no statistics of the property values, such as min/max or histograms,
+    /// it counts the vertices with the filter pushed down to the storage, which takes two scans of
+    /// the partitions of this server, thus it is meant to be called off the planning, e.g., periodically
+    /// for the frequent filters. Return `None` if the filter cannot be pushed down, or there is no vertex.
+    pub fn collect_filter_selectivity(&self, params: &QueryParams) -> GraphProxyResult<Option<f64>> {
+        let key = match self.selectivity_key(params) {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        let (condition, _) = encode_storage_row_filter_condition(
+            params.filter.as_ref(),
+            self.row_filter_pushdown,
+            self.regex_pushdown,
+        );
+        let si = self.get_snapshot_id(params)?;
+        let label_ids = encode_storage_labels(params.labels.as_ref())?;
+        let total_count = self
+            .store
+            .count_all_vertices(si, &label_ids, None, &self.server_partitions);
+        if total_count == 0 {
+            return Ok(None);
         }
+        let count = match condition.as_ref() {
+            Some(condition) => {
+                self.store
+                    .count_all_vertices(si, &label_ids, Some(condition), &self.server_partitions)
+            }
+            None => total_count,
+        };
+        let selectivity = count as f64 / total_count as f64;
+        self.statistics_cache
+            .lock()
+            .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?
+            .selectivities
+            .insert(key, (Instant::now(), selectivity));
+        Ok(Some(selectivity))
     }
-}
 
-#[inline]
-fn to_runtime_edge<E>(e: E, prop_keys: Option<Vec<NameOrId>>, from_src: bool) -> Edge
-where
-    E: 'static + StoreEdge,
-{
-    let id = e.get_edge_id() as ID;
-    let label = encode_runtime_e_label(&e);
-    let src_id = e.get_src_id() as ID;
-    let dst_id = e.get_dst_id() as ID;
-    let src_label_id = e.get_src_label_id() as LabelId;
-    let dst_label_id = e.get_dst_label_id() as LabelId;
-    let details = LazyEdgeDetails::new(e, prop_keys);
+    /// The key of the selectivity of `params.filter` on `params.labels`, or `None` if the filter
+    /// cannot be pushed down to the storage.
+    fn selectivity_key(&self, params: &QueryParams) -> Option<String> {
+        let (condition, row_filter_exists_but_not_pushdown) = encode_storage_row_filter_condition(
+            params.filter.as_ref(),
+            self.row_filter_pushdown,
+            self.regex_pushdown,
+        );
+        if row_filter_exists_but_not_pushdown {
+            return None;
+        }
+        let label_ids = encode_storage_labels(params.labels.as_ref()).ok()?;
+        Some(format!("{:?}/{:?}", label_ids, condition))
+    }
 
-    let mut edge =
-        Edge::with_from_src(id, Some(label), src_id, dst_id, from_src, DynDetails::lazy(details));
-    edge.set_src_label(src_label_id);
-    edge.set_dst_label(dst_label_id);
-    edge
-}
+    /// The context to fetch the properties on demand, if the properties fetched along with the vertices
+    /// or edges are trimmed to `prop_ids` by the column filter pushdown, where `None` means no property
+    /// is fetched, and `Some(vec![])` means all of them.
+    fn property_fetch_context(
+        &self, si: SnapshotId, prop_ids: Option<&Vec<PropId>>,
+    ) -> Option<Arc<PropertyFetchContext<V, VI, E, EI>>> {
+        if !self.column_filter_pushdown || prop_ids.map_or(false, |prop_ids| prop_ids.is_empty()) {
+            return None;
+        }
+        Some(Arc::new(PropertyFetchContext {
+            store: self.store.clone(),
+            si,
+            partition_manager: self.partition_manager.clone(),
+            prop_ids: prop_ids.cloned().unwrap_or_default(),
+        }))
+    }
 
-/// In ir, None means we do not need any properties,
-/// and Some means we need given properties (and Some(vec![]) means we need all properties)
-#[inline]
-fn encode_storage_prop_keys(prop_names: Option<&Vec<NameOrId>>) -> GraphProxyResult<Option<Vec<PropId>>> {
-    if let Some(prop_names) = prop_names {
-        let ids = prop_names
-            .iter()
-            .map(|prop_key| match prop_key {
-                NameOrId::Str(_) => Err(GraphProxyError::FilterPushDownError(
-                    "encode storage prop key error, should provide prop_id".to_owned(),
-                )),
-                NameOrId::Id(prop_id) => Ok(*prop_id as PropId),
-            })
-            .collect::<Result<Vec<PropId>, _>>()?;
-        Ok(Some(ids))
-    } else {
-        Ok(None)
+    /// Count the adjacent edges of the given vertex in the given direction by the count APIs of the store,
+    /// without building the edges. Return `None` if the count is not reported by the store.
+    fn count_edges_by_store(
+        &self, si: SnapshotId, id: ID, direction: Direction, edge_labels: &[LabelId],
+        condition: Option<&Condition>,
+    ) -> GraphProxyResult<Option<u64>> {
+        let store = self.store.clone();
+        let edge_label_ids = encode_storage_labels(&edge_labels.to_vec())?;
+        let src_id = get_partition_vertex_id(id, self.partition_manager.clone());
+        let count_of = |counts: Box<dyn Iterator<Item = (VertexId, usize)>>| {
+            counts
+                .filter(|(v, _)| *v == id as VertexId)
+                .map(|(_, count)| count as u64)
+                .reduce(|a, b| a + b)
+        };
+        let degree = match direction {
+            Direction::Out => count_of(store.count_out_edges(si, vec![src_id], &edge_label_ids, condition)),
+            Direction::In => count_of(store.count_in_edges(si, vec![src_id], &edge_label_ids, condition)),
+            Direction::Both => {
+                count_of(store.count_out_edges(si, vec![src_id.clone()], &edge_label_ids, condition))
+                    .zip(count_of(store.count_in_edges(si, vec![src_id], &edge_label_ids, condition)))
+                    .map(|(out_degree, in_degree)| out_degree + in_degree)
+            }
+        };
+        Ok(degree)
     }
-}
 
-/// convert filter in `QueryParams` to storage `Condition` filter
-/// and return a flag that represents row filter exists but not push down
-/// caused by `row_filter_pushdown == false` or  convert error
-#[inline]
-fn encode_storage_row_filter_condition(
-    row_filter: Option<&Arc<PEvaluator>>, row_filter_pushdown: bool,
-) -> (Option<Condition>, bool) {
-    if row_filter_pushdown {
-        let condition = if let Some(filter) = row_filter { filter.as_ref().try_into() } else { Ok(None) };
-        // gremlin test in ci will compile use debug mode
-        // panic so that developer will know convert failed
-        debug_assert!(condition.is_ok());
-        match condition {
-            Ok(cond) => (cond, false),
-            Err(e) => {
-                error!("convert ir filter to store condition error {}", e);
-                (None, row_filter.is_some())
+    /// Get the snapshot to query, which is checked against the oldest snapshot that the storage serves
+    /// according to the read consistency of the query, as the storage may silently fall back to
+    /// the oldest one if the given one has been compacted away.
+    fn get_snapshot_id(&self, params: &QueryParams) -> GraphProxyResult<SnapshotId> {
+        let si = params
+            .get_extra_i64(SNAPSHOT_ID)?
+            .unwrap_or(DEFAULT_SNAPSHOT_ID);
+        if let Some(oldest_si) = self.store.get_oldest_snapshot_id() {
+            if si < oldest_si {
+                match params.get_read_consistency()? {
+                    ReadConsistency::Strict => Err(GraphProxyError::snapshot_unavailable(si, oldest_si))?,
+                    ReadConsistency::BestEffort => self.warn_stale_snapshot(si, oldest_si),
+                }
             }
         }
-    } else {
-        (None, row_filter.is_some())
+        Ok(si)
     }
-}
 
-/// get columns used in filter and output
-#[inline]
-fn extract_needed_columns(
-    filter: Option<&Arc<PEvaluator>>, out_columns: Option<&Vec<PropId>>,
-) -> GraphProxyResult<Option<Vec<PropId>>> {
-    use ahash::HashSet;
+    /// Warn of reading an unavailable snapshot at most once per `STALE_SNAPSHOT_WARN_INTERVAL`,
+    /// as every operation of the queries on the snapshot would warn otherwise.
+    fn warn_stale_snapshot(&self, si: SnapshotId, oldest_si: SnapshotId) {
+        if let Ok(mut warned_at) = self.stale_snapshot_warned_at.lock() {
+            let should_warn = warned_at
+                .map(|at| at.elapsed() >= STALE_SNAPSHOT_WARN_INTERVAL)
+                .unwrap_or(true);
+            if should_warn {
+                *warned_at = Some(Instant::now());
+                warn!(
+                    "snapshot {} is unavailable, and the oldest available snapshot {} may be read instead",
+                    si, oldest_si
+                );
+            }
+        }
+    }
 
-    use crate::adapters::gs_store::translation::zip_option_vecs;
+    /// Get the primary key of the vertex of the label as the values of the properties that the schema
+    /// declares as the primary key of the label, which are of the same order as `Schema::get_primary_keys()`.
+    /// A single-column primary key is given as one value. `None` is returned if the schema, or the primary
+    /// key of the label, is not available.
+    fn get_primary_key_by_schema(
+        &self, si: SnapshotId, id: ID, label: StoreLabelId,
+    ) -> GraphProxyResult<Option<PKV>> {
+        let pk_ids = match self
+            .store
+            .get_schema(si)
+            .and_then(|schema| schema.get_primary_keys(label))
+        {
+            Some(pk_ids) if !pk_ids.is_empty() => pk_ids,
+            _ => return Ok(None),
+        };
+        let partition_id = self
+            .partition_manager
+            .get_partition_id(id as VertexId) as PartitionId;
+        let vertex = self
+            .store
+            .get_vertex_properties(
+                si,
+                vec![(partition_id, vec![(Some(label), vec![id as VertexId])])],
+                Some(&pk_ids),
+            )
+            .next()
+            .ok_or_else(|| GraphProxyError::internal_error(&format!("vertex {} is not found", id)))?;
+        let mut pkvs = Vec::with_capacity(pk_ids.len());
+        for pk_id in pk_ids {
+            let pk_val = vertex.get_property(pk_id).ok_or_else(|| {
+                GraphProxyError::schema_error(&format!(
+                    "primary key {} of label {} is missing in vertex {}",
+                    pk_id, label, id
+                ))
+            })?;
+            pkvs.push((NameOrId::Id(pk_id as KeyId), encode_runtime_prop_val(pk_val)));
+        }
+        if pkvs.len() == 1 {
+            Ok(pkvs.pop().map(PKV::from))
+        } else {
+            Ok(Some(pkvs.into()))
+        }
+    }
 
-    // Some(vec[]) means need all props, so can't merge it with props needed in filter
-    if let Some(out_columns) = out_columns {
-        if out_columns.is_empty() {
-            return Ok(Some(Vec::with_capacity(0)));
+    /// Set up a scan in the storage on `store_setup_pool` within its time limit if given.
+    /// On expiry, the setup is left to finish in the background, and its result is dropped.
+    fn setup_store<T, F>(&self, setup: F) -> GraphProxyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        if let Some(pool) = self.store_setup_pool.as_ref() {
+            pool.setup(setup)
+        } else {
+            Ok(setup())
         }
     }
 
-    let filter_needed = if let Some(filter) = filter { filter.as_ref().extract_prop_ids() } else { None };
-    let columns = zip_option_vecs(filter_needed, out_columns.cloned());
-    // remove duplicated prop ids
-    Ok(columns.map(|v| {
-        v.into_iter()
-            .collect::<HashSet<PropId>>()
-            .into_iter()
-            .collect::<Vec<PropId>>()
-    }))
-}
+    /// Scan the vertices in the given partitions one by one, where a partition whose store call fails,
+    /// or panics, in either the setup or the iteration of its scan, is skipped with a warning, instead
+    /// of failing the whole scan, unless all of their setups fail. The skipped partitions are counted
+    /// by the worker's metric of `SKIPPED_PARTITIONS_METRIC`.
+    /// The sample and the limit of the `params` apply to the vertices of all the partitions as a whole.
+    pub fn scan_vertex_skipping_failed_partitions(
+        &self, params: &QueryParams, worker_partitions: Vec<PartitionId>,
+    ) -> GraphProxyResult<SkippedPartitionsIter<Vertex>> {
+        let sample_seed = params.get_sample_seed()?;
+        let mut partition_params = params.clone();
+        partition_params.sample_ratio = None;
+        partition_params.limit = None;
+        let num_partitions = worker_partitions.len();
+        let mut scans = VecDeque::with_capacity(num_partitions);
+        let mut skipped = vec![];
+        let mut last_error = None;
+        for partition_id in worker_partitions {
+            let scan = panic::catch_unwind(AssertUnwindSafe(|| {
+                self.scan_vertex_in_partitions(&partition_params, vec![partition_id])
+            }))
+            .unwrap_or_else(|e| Err(GraphProxyError::storage_unavailable(&panic_message(&e))));
+            match scan {
+                Ok(vertices) => scans.push_back((partition_id, vertices)),
+                Err(e) => {
+                    skip_failed_partition(partition_id, &e);
+                    skipped.push(partition_id);
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            // nothing partial to return if all the partitions fail, e.g., due to the invalid `params`
+            Some(e) if skipped.len() == num_partitions => Err(e),
+            _ => {
+                let sampler = params.sample_ratio.map(|ratio| {
+                    let rng = match sample_seed {
+                        Some(seed) => StdRng::seed_from_u64(seed),
+                        None => StdRng::from_entropy(),
+                    };
+                    (ratio, rng)
+                });
+                Ok(SkippedPartitionsIter { scans, sampler, limit: params.limit, skipped, warned: false })
+            }
+        }
+    }
 
-/// Some(vec![]) means need all properties
-#[inline]
-fn get_all_storage_props() -> Option<Vec<PropId>> {
-    Some(Vec::with_capacity(0))
-}
+    /// Scan the vertices in the given partitions, where the sample and limit of the `params`
+    /// apply to the vertices of these partitions as a whole.
+    fn scan_vertex_in_partitions(
+        &self, params: &QueryParams, worker_partitions: Vec<PartitionId>,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        if !worker_partitions.is_empty() {
+            let store = self.store.clone();
+            let si = self.get_snapshot_id(params)?;
+            let sample_seed = params.get_sample_seed()?;
+            let label_ids = encode_storage_labels(params.labels.as_ref())?;
+            let row_filter = params.filter.clone();
 
-#[inline]
-fn encode_storage_labels(labels: &Vec<LabelId>) -> GraphProxyResult<Vec<StoreLabelId>> {
-    labels
-        .iter()
-        .map(|label| encode_storage_label(*label))
-        .collect::<Result<Vec<StoreLabelId>, _>>()
-}
+            let (condition, row_filter_exists_but_not_pushdown) = encode_storage_row_filter_condition(
+                row_filter.as_ref(),
+                self.row_filter_pushdown,
+                self.regex_pushdown,
+            );
+            // a lone pk equality is pushed down instead, if it is not pushed down as the row filter
+            let condition = match condition {
+                Some(condition) => Some(condition),
+                None => self.encode_pk_equality_condition(params)?,
+            };
 
-#[inline]
-fn encode_storage_label(label: LabelId) -> GraphProxyResult<StoreLabelId> {
-    Ok(label as StoreLabelId)
-}
+            let column_filter_pushdown = self.column_filter_pushdown;
+            // the scanned vertices are immediately projected, so only the projected props are needed later
+            let projection = params
+                .projection
+                .clone()
+                .filter(|projection| !projection.is_empty());
+            let columns = if projection.is_some() { projection.clone() } else { params.columns.clone() };
+            // props that will be returned by storage layer
+            let prop_ids = if column_filter_pushdown || projection.is_some() {
+                // props that will be used in further computations
+                let cache_prop_ids = encode_storage_prop_keys(columns.as_ref())?;
+                if row_filter_exists_but_not_pushdown {
+                    // need to call filter_limit!, so get columns in row_filter and params.columns
+                    extract_needed_columns(row_filter.as_ref(), cache_prop_ids.as_ref())?
+                } else {
+                    // row_filter pushdown success, only need params.columns
+                    cache_prop_ids.clone()
+                }
+            } else {
+                // now, ir assume that it can get all properties from a vertex/edge locally
+                // just column filter is not pushdown.
+                get_all_storage_props()
+            };
 
-#[inline]
-fn encode_runtime_v_label<V: StoreVertex>(v: &V) -> LabelId {
-    v.get_label_id() as LabelId
-}
+            // props that storage dedups the scanned vertices by
+            let dedup_prop_ids = params.dedup_props.clone();
 
-#[inline]
-fn encode_runtime_e_label<E: StoreEdge>(e: &E) -> LabelId {
-    e.get_label_id() as LabelId
-}
+            // the props out of the projection are not fetched on demand
+            let fetch_ctx = if projection.is_none() {
+                self.property_fetch_context(si, prop_ids.as_ref())
+            } else {
+                None
+            };
+            let result = self.setup_store(move || {
+                store.get_all_vertices(
+                    si,
+                    label_ids.as_ref(),
+                    // None means no filter condition pushed down to storage as not supported yet. Same as follows.
+                    condition.as_ref(),
+                    // None means no need to dedup by properties. Same as follows.
+                    dedup_prop_ids.as_ref(),
+                    prop_ids.as_ref(),
+                    // Zero limit means no limit. Same as follows.
+                    0,
+                    // Each worker will scan the partitions returned by assign_worker_partitions(). Same as follows.
+                    worker_partitions.as_ref(),
+                )
+            })?;
 
-#[inline]
-fn encode_store_prop_val(prop_val: Object) -> Property {
-    match prop_val {
-        Object::Primitive(p) => match p {
-            Primitives::Byte(b) => Property::Char(b as u8),
-            Primitives::Integer(i) => Property::Int(i),
-            // will support u32 in groot soon.
-            Primitives::UInteger(i) => Property::Int(i as i32),
-            Primitives::Long(i) => Property::Long(i),
-            // will support u64 in groot soon.
-            Primitives::ULong(i) => Property::Long(i as i64),
-            Primitives::ULLong(i) => Property::Long(i as i64),
-            Primitives::Float(f) => Property::Float(f),
-            Primitives::Double(f) => Property::Double(f),
-        },
-        Object::String(s) => Property::String(s),
-        Object::Vector(vec) => {
-            if let Some(probe) = vec.get(0) {
-                match probe {
-                    Object::Primitive(p) => match p {
-                        Primitives::Byte(_) | Primitives::Integer(_) | Primitives::UInteger(_) => {
-                            Property::ListInt(
-                                vec.into_iter()
-                                    .map(|i| i.as_i32().unwrap())
-                                    .collect(),
-                            )
-                        }
-                        Primitives::Long(_) | Primitives::ULong(_) => Property::ListLong(
-                            vec.into_iter()
-                                .map(|i| i.as_i64().unwrap())
-                                .collect(),
-                        ),
-                        Primitives::ULLong(_) => Property::ListLong(
-                            vec.into_iter()
-                                .map(|i| i.as_u128().unwrap() as i64)
-                                .collect(),
-                        ),
-                        Primitives::Float(_) => Property::ListFloat(
-                            vec.into_iter()
-                                .map(|i| i.as_f32().unwrap())
-                                .collect(),
-                        ),
-                        Primitives::Double(_) => Property::ListDouble(
-                            vec.into_iter()
-                                .map(|i| i.as_f64().unwrap())
-                                .collect(),
-                        ),
-                    },
-                    Object::String(_) => Property::ListString(
-                        vec.into_iter()
-                            .map(|i| i.as_str().unwrap().into_owned())
-                            .collect(),
-                    ),
-                    Object::Blob(_) => Property::ListBytes(
-                        vec.into_iter()
-                            .map(|i| i.as_bytes().unwrap().to_vec())
-                            .collect(),
-                    ),
-                    Object::None => Property::Null,
-                    _ => Property::Unknown,
+            if row_filter_exists_but_not_pushdown {
+                let column_pred = row_filter
+                    .as_ref()
+                    .and_then(|filter| ColumnPredicate::compile(filter));
+                if let Some(column_pred) = column_pred {
+                    // evaluate the row filter over batches of property columns,
+                    // and only the vertices passing the filter are turned into runtime vertices
+                    let result = BatchFilterIter::new(
+                        result,
+                        column_pred,
+                        get_store_vertex_property,
+                        move |v| to_runtime_vertex(v, columns.clone(), fetch_ctx.as_ref()),
+                        DEFAULT_FILTER_BATCH_SIZE,
+                    );
+                    Ok(sample_limit!(result, params.sample_ratio, sample_seed, params.limit))
+                } else {
+                    // fall back to call filter_limit! to do row filter
+                    let result =
+                        result.map(move |v| to_runtime_vertex(v, columns.clone(), fetch_ctx.as_ref()));
+                    Ok(filter_sample_limit!(
+                        result,
+                        row_filter,
+                        params.sample_ratio,
+                        sample_seed,
+                        params.limit
+                    ))
                 }
             } else {
-                Property::Null
+                let result = result.map(move |v| to_runtime_vertex(v, columns.clone(), fetch_ctx.as_ref()));
+                Ok(sample_limit!(result, params.sample_ratio, sample_seed, params.limit))
             }
+        } else {
+            Ok(Box::new(std::iter::empty()))
         }
-        Object::Blob(b) => Property::Bytes(b.to_vec()),
-        Object::None => Property::Null,
-        _ => Property::Unknown,
     }
-}
 
-/// Given all the partitions,
-/// return the partition_list that current worker is going to scan.
-#[inline]
-fn assign_worker_partitions(
-    query_partitions: &Vec<u32>, cluster_info: &Arc<dyn ClusterInfo>,
-) -> GraphProxyResult<Vec<PartitionId>> {
-    let workers_num = cluster_info.get_local_worker_num()?;
-    let worker_idx = cluster_info.get_worker_index()?;
-    let mut worker_partition_list = vec![];
-    for pid in query_partitions {
-        if *pid % workers_num == worker_idx % workers_num {
-            worker_partition_list.push(*pid as PartitionId)
+    /// Encode the lone primary-key equality of `params.primary_keys` as a condition to push down
+    /// to the scan, if the vertex of the pk cannot be located by the pk-to-vertex mapping of the
+    /// `GraphPartitionManager`, i.e., for stores without an index of the primary keys.
+    /// The `SourceOperator` hints the equality out of the conjuncts of `params.filter`, so that the
+    /// condition never drops any vertex that the filter keeps, even if it is not on the primary key.
+    fn encode_pk_equality_condition(&self, params: &QueryParams) -> GraphProxyResult<Option<Condition>> {
+        let pk = match params
+            .primary_keys
+            .as_ref()
+            .map(|pks| pks.as_slice())
+        {
+            Some([pk @ OneOrMany::One(_)]) => pk,
+            _ => return Ok(None),
+        };
+        let si = self.get_snapshot_id(params)?;
+        for label in &params.labels {
+            let store_label_id = encode_storage_label(*label)?;
+            if let Some(store_indexed_values) = self.encode_label_pk_vals(si, store_label_id, pk) {
+                if self
+                    .partition_manager
+                    .get_vertex_id_by_primary_keys(store_label_id, store_indexed_values.as_ref())
+                    .is_some()
+                {
+                    // the pk can be looked up in the index
+                    return Ok(None);
+                }
+            }
+        }
+        let (key, value) = match pk {
+            OneOrMany::One([(key, value)]) => (key, value),
+            OneOrMany::Many(_) => unreachable!(),
+        };
+        let prop_id = match key {
+            NameOrId::Id(id) => Some(*id as PropId),
+            NameOrId::Str(name) => self
+                .store
+                .get_schema(si)
+                .and_then(|schema| schema.get_prop_id(name)),
+        };
+        if let Some(prop_id) = prop_id {
+            let condition = Condition::from_pk_equality(prop_id, value)
+                .map_err(|e| GraphProxyError::filter_push_down_error(&format!("{:?}", e)))?;
+            debug!("push down pk equality {:?} as condition {:?}", pk, condition);
+            Ok(Some(condition))
+        } else {
+            Ok(None)
         }
     }
-    debug!(
-        "workers_num {:?}, worker_idx: {:?},  worker_partition_list {:?}",
-        workers_num, worker_idx, worker_partition_list
-    );
-    Ok(worker_partition_list)
+
+    /// Encode the values of the primary key of the label out of the given property values, in the
+    /// order of the primary key, which is declared by the schema, or is the outer id otherwise, as
+    /// `get_primary_key_with_label()` reads it. `None` is returned if any column of the primary key is missing.
+    fn encode_label_pk_vals(&self, si: SnapshotId, label: StoreLabelId, pk: &PKV) -> Option<Vec<Property>> {
+        let schema = self.store.get_schema(si);
+        let pk_ids = schema
+            .as_ref()
+            .and_then(|schema| schema.get_primary_keys(label))
+            .unwrap_or_else(|| vec![GS_STORE_PK as PropId]);
+        pk_ids
+            .into_iter()
+            .map(|pk_id| {
+                pk.iter()
+                    .find(|(key, _)| match key {
+                        NameOrId::Id(id) => *id as PropId == pk_id,
+                        NameOrId::Str(name) => {
+                            schema
+                                .as_ref()
+                                .and_then(|schema| schema.get_prop_id(name))
+                                == Some(pk_id)
+                        }
+                    })
+                    .map(|(_, value)| encode_store_prop_val(value.clone()))
+            })
+            .collect()
+    }
+
+    /// Prune the given partitions to those that may contain the vertices of `params.primary_keys`,
+    /// which are located by the pk-to-vertex mapping of the `GraphPartitionManager`.
+    /// If the values given do not make up the primary key of any label to scan, or the mapping is
+    /// not available for any of them, or no labels are given, all the partitions are kept.
+    fn prune_partitions_by_pks(
+        &self, partitions: Vec<PartitionId>, params: &QueryParams,
+    ) -> GraphProxyResult<Vec<PartitionId>> {
+        let primary_keys = match params.primary_keys.as_ref() {
+            Some(pks) if !pks.is_empty() && !params.labels.is_empty() => pks,
+            _ => return Ok(partitions),
+        };
+        let si = self.get_snapshot_id(params)?;
+        let mut candidates = HashSet::default();
+        for label in &params.labels {
+            let store_label_id = encode_storage_label(*label)?;
+            for pk in primary_keys {
+                let vid = self
+                    .encode_label_pk_vals(si, store_label_id, pk)
+                    .and_then(|store_indexed_values| {
+                        self.partition_manager
+                            .get_vertex_id_by_primary_keys(store_label_id, store_indexed_values.as_ref())
+                    });
+                match vid {
+                    Some(vid) => {
+                        candidates.insert(self.partition_manager.get_partition_id(vid) as PartitionId);
+                    }
+                    None => return Ok(partitions),
+                }
+            }
+        }
+        let pruned: Vec<PartitionId> = partitions
+            .into_iter()
+            .filter(|pid| candidates.contains(pid))
+            .collect();
+        debug!(
+            "prune partitions by pks {:?}, candidates {:?}, pruned {:?}",
+            primary_keys, candidates, pruned
+        );
+        Ok(pruned)
+    }
 }
 
-/// Transform type of ids to PartitionLabeledVertexIds as required by graphscope store,
-/// which consists of (PartitionId, Vec<(Option<StoreLabelId>, Vec<VertexId>)>)
-fn get_partition_label_vertex_ids(
-    ids: &[ID], graph_partition_manager: Arc<dyn GraphPartitionManager>,
-) -> Vec<PartitionLabeledVertexIds> {
-    let mut partition_label_vid_map = HashMap::new();
-    for vid in ids {
-        let partition_id = graph_partition_manager.get_partition_id(*vid as VertexId) as PartitionId;
-        let label_vid_list = partition_label_vid_map
-            .entry(partition_id)
-            .or_insert(HashMap::new());
-        label_vid_list
-            .entry(None)
-            .or_insert(vec![])
-            .push(*vid as VertexId);
+/// The context to fetch the properties of the vertices and edges from the storage on demand, other than
+/// `prop_ids` that are fetched along with them, as the columns pushed down to the storage are trimmed.
+struct PropertyFetchContext<V, VI, E, EI>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    store: Arc<dyn GlobalGraphQuery<V = V, E = E, VI = VI, EI = EI>>,
+    si: SnapshotId,
+    partition_manager: Arc<dyn GraphPartitionManager>,
+    prop_ids: Vec<PropId>,
+}
+
+impl<V, VI, E, EI> PropertyFetchContext<V, VI, E, EI>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    /// The fetcher of the properties of the edges expanded from the source vertices if `from_src`,
+    /// or from the destination vertices otherwise.
+    fn edge_fetcher(self: &Arc<Self>, from_src: bool) -> Arc<dyn PropertyFetcher<E>> {
+        Arc::new(EdgePropertyFetcher { ctx: self.clone(), from_src, adjacency: Mutex::new(None) })
     }
+}
 
-    partition_label_vid_map
-        .into_iter()
-        .map(|(pid, label_vid_map)| (pid, label_vid_map.into_iter().collect()))
-        .collect()
+/// The vertex is looked up by its id and label again, with all of its properties.
+impl<V, VI, E, EI> PropertyFetcher<V> for PropertyFetchContext<V, VI, E, EI>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    fn fetched_prop_ids(&self) -> &[PropId] {
+        &self.prop_ids
+    }
+
+    fn fetch(&self, v: &V, prop_id: PropId) -> GraphProxyResult<Vec<(PropId, Property)>> {
+        let (vid, label) = (v.get_id(), v.get_label_id());
+        let (partition_id, _) = get_partition_vertex_id(vid as ID, self.partition_manager.clone());
+        let ids = vec![(partition_id, vec![(Some(label), vec![vid])])];
+        // `Some(vec![])` fetches all the properties
+        self.store
+            .get_vertex_properties(self.si, ids, Some(&vec![]))
+            .next()
+            .map(|v| v.get_properties().collect())
+            .ok_or_else(|| {
+                GraphProxyError::property_not_fetched(
+                    &NameOrId::Id(prop_id as KeyId),
+                    &format!("as vertex {} is not found in the storage", vid),
+                )
+            })
+    }
 }
 
-/// Transform type of ids to PartitionVertexIds as required by graphscope store,
-/// which consists of (PartitionId,Vec<VertexId>)
-fn get_partition_vertex_id(
-    id: ID, graph_partition_manager: Arc<dyn GraphPartitionManager>,
-) -> PartitionVertexIds {
+/// The edge is looked up among the adjacent edges of the vertex that it is expanded from,
+/// i.e., the source vertex if `from_src`, or the destination vertex otherwise. As the store cannot
+/// look up an edge by its id, the properties of all the adjacent edges are fetched by one read of
+/// the adjacency, and kept for the other edges expanded from the same vertex.
+struct EdgePropertyFetcher<V, VI, E, EI>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    ctx: Arc<PropertyFetchContext<V, VI, E, EI>>,
+    from_src: bool,
+    // the properties of the edges adjacent to the vertex of the last fetch, by the id and edge label of
+    // the vertex, as the edges expanded from a vertex are accessed one after another
+    adjacency: Mutex<Option<((VertexId, StoreLabelId), HashMap<EdgeId, Vec<(PropId, Property)>>)>>,
+}
+
+impl<V, VI, E, EI> PropertyFetcher<E> for EdgePropertyFetcher<V, VI, E, EI>
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    fn fetched_prop_ids(&self) -> &[PropId] {
+        &self.ctx.prop_ids
+    }
+
+    fn fetch(&self, e: &E, prop_id: PropId) -> GraphProxyResult<Vec<(PropId, Property)>> {
+        let ctx = &self.ctx;
+        let (eid, label) = (e.get_edge_id(), e.get_label_id());
+        let vid = if self.from_src { e.get_src_id() } else { e.get_dst_id() };
+        let mut adjacency = self.adjacency.lock().expect("lock poisoned");
+        if !matches!(adjacency.as_ref(), Some((key, _)) if *key == (vid, label)) {
+            let ids = vec![get_partition_vertex_id(vid as ID, ctx.partition_manager.clone())];
+            // `Some(vec![])` fetches all the properties
+            let (labels, prop_ids) = (vec![label], vec![]);
+            let edges = if self.from_src {
+                ctx.store
+                    .get_out_edges(ctx.si, ids, &labels, None, None, Some(&prop_ids), 0)
+            } else {
+                ctx.store
+                    .get_in_edges(ctx.si, ids, &labels, None, None, Some(&prop_ids), 0)
+            };
+            let props = edges
+                .flat_map(|(_, edges)| edges)
+                .map(|e| (e.get_edge_id(), e.get_properties().collect()))
+                .collect();
+            *adjacency = Some(((vid, label), props));
+        }
+        adjacency
+            .as_ref()
+            .and_then(|(_, props)| props.get(&eid).cloned())
+            .ok_or_else(|| {
+                GraphProxyError::property_not_fetched(
+                    &NameOrId::Id(prop_id as KeyId),
+                    &format!("as edge {} is not found in the storage", eid),
+                )
+            })
+    }
+}
+
+#[inline]
+fn to_runtime_vertex<V, VI, E, EI>(
+    v: V, prop_keys: Option<Vec<NameOrId>>, fetch_ctx: Option<&Arc<PropertyFetchContext<V, VI, E, EI>>>,
+) -> Vertex
+where
+    V: StoreVertex + 'static,
+    VI: Iterator<Item = V> + Send + 'static,
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + Send + 'static,
+{
+    let id = v.get_id() as ID;
+    let label = encode_runtime_v_label(&v);
+    let mut details = LazyVertexDetails::new(v, prop_keys);
+    if let Some(ctx) = fetch_ctx {
+        details = details.with_fetcher(ctx.clone());
+    }
+    Vertex::new(id, Some(label), DynDetails::lazy(details))
+}
+
+#[inline]
+fn get_store_vertex_property<V: StoreVertex>(v: &V, key: &NameOrId) -> Option<Object> {
+    // consistent with `LazyVertexDetails::get_property()`
+    if let NameOrId::Id(key) = key {
+        v.get_property(*key as PropId)
+            .map(encode_runtime_prop_val)
+    } else {
+        None
+    }
+}
+
+#[inline]
+fn to_empty_vertex<V: StoreVertex>(v: &V) -> Vertex {
+    let id = v.get_id() as ID;
+    let label = encode_runtime_v_label(v);
+    Vertex::new(id, Some(label), DynDetails::default())
+}
+
+/// Warn of the failed partition that is skipped by a scan, and count it by the worker's metric.
+fn skip_failed_partition(partition_id: PartitionId, e: &GraphProxyError) {
+    warn!("skip the failed partition {} in scan_vertex: {}", partition_id, e);
+    if let Some(counter) = pegasus::metrics::counter(SKIPPED_PARTITIONS_METRIC) {
+        counter.inc();
+    }
+}
+
+fn panic_message(e: &Box<dyn Any + Send>) -> String {
+    let msg = if let Some(msg) = e.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = e.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    };
+    format!("store panicked: {}", msg)
+}
+
+/// The results of a scan over the partitions one by one, where the failed partitions are skipped,
+/// see `skipped_partitions()`, whose number is warned as a trailing warning once the results are
+/// exhausted. A partition panicking in its iteration is skipped from then on, with the results
+/// that it has returned kept.
+pub struct SkippedPartitionsIter<T> {
+    scans: VecDeque<(PartitionId, Box<dyn Iterator<Item = T> + Send>)>,
+    // the sample ratio with the rng, applying to the results of all the partitions
+    sampler: Option<(f64, StdRng)>,
+    // the remaining number of the results to return
+    limit: Option<usize>,
+    skipped: Vec<PartitionId>,
+    warned: bool,
+}
+
+impl<T> SkippedPartitionsIter<T> {
+    /// The partitions that are skipped as they fail
+    pub fn skipped_partitions(&self) -> &[PartitionId] {
+        &self.skipped
+    }
+
+    fn next_in_partitions(&mut self) -> Option<T> {
+        while let Some((partition_id, scan)) = self.scans.front_mut() {
+            let sampler = &mut self.sampler;
+            let next = panic::catch_unwind(AssertUnwindSafe(|| match sampler {
+                Some((ratio, rng)) => scan.find(|_| rng.gen_bool(*ratio)),
+                None => scan.next(),
+            }));
+            match next {
+                Ok(Some(item)) => return Some(item),
+                Ok(None) => {}
+                Err(e) => {
+                    let partition_id = *partition_id;
+                    skip_failed_partition(
+                        partition_id,
+                        &GraphProxyError::storage_unavailable(&panic_message(&e)),
+                    );
+                    self.skipped.push(partition_id);
+                }
+            }
+            self.scans.pop_front();
+        }
+        None
+    }
+}
+
+impl<T> Iterator for SkippedPartitionsIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = if self.limit == Some(0) { None } else { self.next_in_partitions() };
+        match next {
+            Some(_) => {
+                if let Some(limit) = self.limit.as_mut() {
+                    *limit -= 1;
+                }
+            }
+            None => {
+                if !self.warned && !self.skipped.is_empty() {
+                    warn!(
+                        "the results are partial, with {} failed partitions {:?} skipped",
+                        self.skipped.len(),
+                        self.skipped
+                    );
+                    self.warned = true;
+                }
+            }
+        }
+        next
+    }
+}
+
+pub struct RuntimeEdgeIter<E, EI>
+where
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + 'static,
+{
+    iter: EI,
+    from_src: bool,
+    prop_keys: Option<Vec<NameOrId>>,
+    end_v_labels: Option<Vec<StoreLabelId>>,
+    fetcher: Option<Arc<dyn PropertyFetcher<E>>>,
+}
+
+impl<E, EI> RuntimeEdgeIter<E, EI>
+where
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + 'static,
+{
+    pub fn new(iter: EI, from_src: bool, prop_keys: Option<Vec<NameOrId>>) -> Self {
+        RuntimeEdgeIter { iter, from_src, prop_keys, end_v_labels: None, fetcher: None }
+    }
+
+    /// Only the edges whose other end vertex is of the given labels are returned
+    pub fn with_end_v_labels(mut self, end_v_labels: Option<Vec<StoreLabelId>>) -> Self {
+        self.end_v_labels = end_v_labels;
+        self
+    }
+
+    /// The properties of the edges that are not fetched along with them are fetched on demand
+    /// by `fetcher`, if any
+    fn with_fetcher(mut self, fetcher: Option<Arc<dyn PropertyFetcher<E>>>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+}
+
+impl<E, EI> Iterator for RuntimeEdgeIter<E, EI>
+where
+    E: StoreEdge + 'static,
+    EI: Iterator<Item = E> + 'static,
+{
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(next) = self.iter.next() {
+            let end_v_label = if self.from_src { next.get_dst_label_id() } else { next.get_src_label_id() };
+            if is_expected_label(self.end_v_labels.as_ref(), end_v_label) {
+                return Some(to_runtime_edge(
+                    next,
+                    self.prop_keys.clone(),
+                    self.from_src,
+                    self.fetcher.clone(),
+                ));
+            }
+        }
+        None
+    }
+}
+
+#[inline]
+fn is_expected_label(expected_labels: Option<&Vec<StoreLabelId>>, label: StoreLabelId) -> bool {
+    expected_labels
+        .map(|labels| labels.contains(&label))
+        .unwrap_or(true)
+}
+
+#[inline]
+fn to_runtime_edge<E>(
+    e: E, prop_keys: Option<Vec<NameOrId>>, from_src: bool, fetcher: Option<Arc<dyn PropertyFetcher<E>>>,
+) -> Edge
+where
+    E: 'static + StoreEdge,
+{
+    let id = e.get_edge_id() as ID;
+    let label = encode_runtime_e_label(&e);
+    let src_id = e.get_src_id() as ID;
+    let dst_id = e.get_dst_id() as ID;
+    let src_label_id = e.get_src_label_id() as LabelId;
+    let dst_label_id = e.get_dst_label_id() as LabelId;
+    let mut details = LazyEdgeDetails::new(e, prop_keys);
+    if let Some(fetcher) = fetcher {
+        details = details.with_fetcher(fetcher);
+    }
+
+    let mut edge =
+        Edge::with_from_src(id, Some(label), src_id, dst_id, from_src, DynDetails::lazy(details));
+    edge.set_src_label(src_label_id);
+    edge.set_dst_label(dst_label_id);
+    edge
+}
+
+/// In ir, None means we do not need any properties,
+/// and Some means we need given properties (and Some(vec![]) means we need all properties)
+#[inline]
+fn encode_storage_prop_keys(prop_names: Option<&Vec<NameOrId>>) -> GraphProxyResult<Option<Vec<PropId>>> {
+    if let Some(prop_names) = prop_names {
+        let ids = prop_names
+            .iter()
+            .map(|prop_key| match prop_key {
+                NameOrId::Str(name) => Err(GraphProxyError::schema_resolution_error("property", name)),
+                NameOrId::Id(prop_id) => Ok(*prop_id as PropId),
+            })
+            .collect::<Result<Vec<PropId>, _>>()?;
+        Ok(Some(ids))
+    } else {
+        Ok(None)
+    }
+}
+
+/// convert filter in `QueryParams` to storage `Condition` filter
+/// and return a flag that represents row filter exists but not push down
+/// caused by `row_filter_pushdown == false` or  convert error
+#[inline]
+fn encode_storage_row_filter_condition(
+    row_filter: Option<&Arc<PEvaluator>>, row_filter_pushdown: bool, regex_pushdown: bool,
+) -> (Option<Condition>, bool) {
+    if row_filter_pushdown {
+        if let Some(PEvaluator::General(eval)) = row_filter.map(|filter| filter.as_ref()) {
+            if eval.has_function_call() {
+                // the function calls, e.g., `year(@.creationDate)`, are not supported by the storage,
+                // so the filter is declined to push down, and is evaluated in runtime instead
+                return (None, true);
+            }
+        }
+        if !regex_pushdown
+            && row_filter
+                .map(|filter| filter.has_regex())
+                .unwrap_or(false)
+        {
+            // the same as above, for the storage without the support of regex
+            return (None, true);
+        }
+        let condition = if let Some(filter) = row_filter { filter.as_ref().try_into() } else { Ok(None) };
+        // gremlin test in ci will compile use debug mode
+        // panic so that developer will know convert failed
+        debug_assert!(condition.is_ok());
+        match condition {
+            Ok(cond) => (cond, false),
+            Err(e) => {
+                error!("convert ir filter to store condition error {}", e);
+                (None, row_filter.is_some())
+            }
+        }
+    } else {
+        (None, row_filter.is_some())
+    }
+}
+
+/// get columns used in filter and output
+#[inline]
+fn extract_needed_columns(
+    filter: Option<&Arc<PEvaluator>>, out_columns: Option<&Vec<PropId>>,
+) -> GraphProxyResult<Option<Vec<PropId>>> {
+    use crate::adapters::gs_store::translation::zip_option_vecs;
+
+    // Some(vec[]) means need all props, so can't merge it with props needed in filter
+    if let Some(out_columns) = out_columns {
+        if out_columns.is_empty() {
+            return Ok(Some(Vec::with_capacity(0)));
+        }
+    }
+
+    let filter_needed = if let Some(filter) = filter { filter.as_ref().extract_prop_ids() } else { None };
+    let columns = zip_option_vecs(filter_needed, out_columns.cloned());
+    // remove duplicated prop ids
+    Ok(columns.map(|v| {
+        v.into_iter()
+            .collect::<HashSet<PropId>>()
+            .into_iter()
+            .collect::<Vec<PropId>>()
+    }))
+}
+
+/// Some(vec![]) means need all properties
+#[inline]
+fn get_all_storage_props() -> Option<Vec<PropId>> {
+    Some(Vec::with_capacity(0))
+}
+
+#[inline]
+fn encode_storage_labels(labels: &Vec<LabelId>) -> GraphProxyResult<Vec<StoreLabelId>> {
+    labels
+        .iter()
+        .map(|label| encode_storage_label(*label))
+        .collect::<Result<Vec<StoreLabelId>, _>>()
+}
+
+#[inline]
+fn encode_storage_label(label: LabelId) -> GraphProxyResult<StoreLabelId> {
+    Ok(label as StoreLabelId)
+}
+
+#[inline]
+fn encode_runtime_v_label<V: StoreVertex>(v: &V) -> LabelId {
+    v.get_label_id() as LabelId
+}
+
+#[inline]
+fn encode_runtime_e_label<E: StoreEdge>(e: &E) -> LabelId {
+    e.get_label_id() as LabelId
+}
+
+#[inline]
+fn encode_store_pk_vals(primary_key: &PKV) -> Vec<Property> {
+    match primary_key {
+        OneOrMany::One(pkv) => {
+            vec![encode_store_prop_val(pkv[0].1.clone())]
+        }
+        OneOrMany::Many(pkvs) => pkvs
+            .iter()
+            .map(|(_pk, value)| encode_store_prop_val(value.clone()))
+            .collect(),
+    }
+}
+
+#[inline]
+fn encode_store_prop_val(prop_val: Object) -> Property {
+    match prop_val {
+        Object::Primitive(p) => match p {
+            Primitives::Byte(b) => Property::Char(b as u8),
+            Primitives::Integer(i) => Property::Int(i),
+            // will support u32 in groot soon.
+            Primitives::UInteger(i) => Property::Int(i as i32),
+            Primitives::Long(i) => Property::Long(i),
+            // will support u64 in groot soon.
+            Primitives::ULong(i) => Property::Long(i as i64),
+            Primitives::ULLong(i) => Property::Long(i as i64),
+            Primitives::Float(f) => Property::Float(f),
+            Primitives::Double(f) => Property::Double(f),
+        },
+        Object::String(s) => Property::String(s),
+        Object::Vector(vec) => {
+            if let Some(probe) = vec.get(0) {
+                match probe {
+                    Object::Primitive(p) => match p {
+                        Primitives::Byte(_) | Primitives::Integer(_) | Primitives::UInteger(_) => {
+                            Property::ListInt(
+                                vec.into_iter()
+                                    .map(|i| i.as_i32().unwrap())
+                                    .collect(),
+                            )
+                        }
+                        Primitives::Long(_) | Primitives::ULong(_) => Property::ListLong(
+                            vec.into_iter()
+                                .map(|i| i.as_i64().unwrap())
+                                .collect(),
+                        ),
+                        Primitives::ULLong(_) => Property::ListLong(
+                            vec.into_iter()
+                                .map(|i| i.as_u128().unwrap() as i64)
+                                .collect(),
+                        ),
+                        Primitives::Float(_) => Property::ListFloat(
+                            vec.into_iter()
+                                .map(|i| i.as_f32().unwrap())
+                                .collect(),
+                        ),
+                        Primitives::Double(_) => Property::ListDouble(
+                            vec.into_iter()
+                                .map(|i| i.as_f64().unwrap())
+                                .collect(),
+                        ),
+                    },
+                    Object::String(_) => Property::ListString(
+                        vec.into_iter()
+                            .map(|i| i.as_str().unwrap().into_owned())
+                            .collect(),
+                    ),
+                    Object::Blob(_) => Property::ListBytes(
+                        vec.into_iter()
+                            .map(|i| i.as_bytes().unwrap().to_vec())
+                            .collect(),
+                    ),
+                    Object::None => Property::Null,
+                    _ => Property::Unknown,
+                }
+            } else {
+                Property::Null
+            }
+        }
+        Object::Blob(b) => Property::Bytes(b.to_vec()),
+        Object::None => Property::Null,
+        _ => Property::Unknown,
+    }
+}
+
+/// Given all the partitions,
+/// return the partition_list that current worker is going to scan.
+#[inline]
+fn assign_worker_partitions(
+    query_partitions: &Vec<u32>, cluster_info: &Arc<dyn ClusterInfo>,
+) -> GraphProxyResult<Vec<PartitionId>> {
+    let workers_num = cluster_info.get_local_worker_num()?;
+    if workers_num == 0 {
+        // e.g., a stale view of the cluster membership, which may be refreshed on retry
+        return Err(GraphProxyError::storage_unavailable("worker count cannot be zero"));
+    }
+    let worker_idx = cluster_info.get_worker_index()?;
+    let mut worker_partition_list = vec![];
+    for pid in query_partitions {
+        if *pid % workers_num == worker_idx % workers_num {
+            worker_partition_list.push(*pid as PartitionId)
+        }
+    }
+    debug!(
+        "workers_num {:?}, worker_idx: {:?},  worker_partition_list {:?}",
+        workers_num, worker_idx, worker_partition_list
+    );
+    Ok(worker_partition_list)
+}
+
+/// Transform type of ids to PartitionLabeledVertexIds as required by graphscope store,
+/// which consists of (PartitionId, Vec<(Option<StoreLabelId>, Vec<VertexId>)>)
+fn get_partition_label_vertex_ids(
+    ids: &[ID], graph_partition_manager: Arc<dyn GraphPartitionManager>,
+) -> Vec<PartitionLabeledVertexIds> {
+    let mut partition_label_vid_map = HashMap::new();
+    for vid in ids {
+        let partition_id = graph_partition_manager.get_partition_id(*vid as VertexId) as PartitionId;
+        let label_vid_list = partition_label_vid_map
+            .entry(partition_id)
+            .or_insert(HashMap::new());
+        label_vid_list
+            .entry(None)
+            .or_insert(vec![])
+            .push(*vid as VertexId);
+    }
+
+    partition_label_vid_map
+        .into_iter()
+        .map(|(pid, label_vid_map)| (pid, label_vid_map.into_iter().collect()))
+        .collect()
+}
+
+/// Transform type of ids to PartitionVertexIds as required by graphscope store,
+/// which consists of (PartitionId,Vec<VertexId>)
+fn get_partition_vertex_id(
+    id: ID, graph_partition_manager: Arc<dyn GraphPartitionManager>,
+) -> PartitionVertexIds {
     let partition_id = graph_partition_manager.get_partition_id(id as VertexId) as PartitionId;
     (partition_id, vec![id as VertexId])
 }
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::sync::Mutex;
+
+    use dyn_type::object;
+    use global_query::store_api::{DataType, EdgeId, ElemFilter};
+    use global_query::Schema;
+    use ir_common::expr_parse::str_to_expr_pb;
+
+    use super::*;
+    use crate::apis::graph::{READ_CONSISTENCY_KEY, SAMPLE_SEED_KEY, SKIP_FAILED_PARTITIONS_KEY};
+
+    /// The id of a vertex that panics once it is read, to mimic a store failing in the iteration
+    const BROKEN_VERTEX_ID: VertexId = VertexId::MIN;
+
+    struct TestVertex(VertexId, StoreLabelId);
+
+    impl StoreVertex for TestVertex {
+        type PI = std::vec::IntoIter<(PropId, Property)>;
+
+        fn get_id(&self) -> VertexId {
+            assert_ne!(self.0, BROKEN_VERTEX_ID, "the vertex is broken in the store");
+            self.0
+        }
+
+        fn get_label_id(&self) -> StoreLabelId {
+            self.1
+        }
+
+        // an order is keyed by its customer (prop 1) and its number (prop 2), and a comment by its number
+        fn get_property(&self, prop_id: PropId) -> Option<Property> {
+            match (self.1, prop_id) {
+                (ORDER, 1) => Some(Property::Long(self.0 as i64 * 100)),
+                (ORDER, 2) | (COMMENT, 2) => Some(Property::Int(self.0 as i32)),
+                _ => None,
+            }
+        }
+
+        fn get_properties(&self) -> Self::PI {
+            (1..=2)
+                .filter_map(|prop_id| {
+                    self.get_property(prop_id)
+                        .map(|prop| (prop_id, prop))
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+        }
+    }
+
+    struct TestEdge {
+        src: TestVertex,
+        dst: TestVertex,
+    }
+
+    impl StoreEdge for TestEdge {
+        type PI = std::vec::IntoIter<(PropId, Property)>;
+
+        fn get_label_id(&self) -> StoreLabelId {
+            LIKES
+        }
+
+        fn get_src_label_id(&self) -> StoreLabelId {
+            self.src.1
+        }
+
+        fn get_dst_label_id(&self) -> StoreLabelId {
+            self.dst.1
+        }
+
+        fn get_src_id(&self) -> VertexId {
+            self.src.0
+        }
+
+        fn get_dst_id(&self) -> VertexId {
+            self.dst.0
+        }
+
+        // the edges of a source vertex are told apart by their destinations
+        fn get_edge_id(&self) -> EdgeId {
+            self.dst.0
+        }
+
+        // a like is weighted by its destination (prop 1), and numbered by it (prop 2)
+        fn get_property(&self, prop_id: PropId) -> Option<Property> {
+            match prop_id {
+                1 => Some(Property::Long(self.dst.0 as i64 * 10)),
+                2 => Some(Property::Int(self.dst.0 as i32)),
+                _ => None,
+            }
+        }
+
+        fn get_properties(&self) -> Self::PI {
+            (1..=2)
+                .filter_map(|prop_id| {
+                    self.get_property(prop_id)
+                        .map(|prop| (prop_id, prop))
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+        }
+    }
+
+    type VertexIter = std::vec::IntoIter<TestVertex>;
+    type EdgeIter = std::vec::IntoIter<TestEdge>;
+
+    const PERSON: StoreLabelId = 0;
+    const POST: StoreLabelId = 1;
+    const COMMENT: StoreLabelId = 2;
+    const ORDER: StoreLabelId = 3;
+    const LIKES: StoreLabelId = 10;
+
+    // The `likes` edges of a mixed destination, i.e., person -> post and person -> comment,
+    // where person 0 likes post 1, comment 2 and post 3, which are liked by person 0 and 4.
+    fn likes_out(src: VertexId) -> Vec<TestEdge> {
+        if src == 0 {
+            vec![(1, POST), (2, COMMENT), (3, POST)]
+                .into_iter()
+                .map(|(dst, dst_label)| TestEdge {
+                    src: TestVertex(src, PERSON),
+                    dst: TestVertex(dst, dst_label),
+                })
+                .collect()
+        } else {
+            vec![]
+        }
+    }
+
+    fn likes_in(dst: VertexId) -> Vec<TestEdge> {
+        let dst_label = match dst {
+            1 | 3 => POST,
+            2 => COMMENT,
+            _ => return vec![],
+        };
+        vec![0, 4]
+            .into_iter()
+            .map(|src| TestEdge { src: TestVertex(src, PERSON), dst: TestVertex(dst, dst_label) })
+            .collect()
+    }
+
+    fn adjacency<T, F: Fn(VertexId) -> Vec<T>>(
+        ids: Vec<PartitionVertexIds>, f: F,
+    ) -> Box<dyn Iterator<Item = (VertexId, std::vec::IntoIter<T>)>>
+    where
+        T: 'static,
+    {
+        let adj: Vec<_> = ids
+            .into_iter()
+            .flat_map(|(_pid, ids)| ids)
+            .map(|id| (id, f(id).into_iter()))
+            .collect();
+        Box::new(adj.into_iter())
+    }
+
+    // The vertices of the store, i.e., person 0 and 4, post 1 and 3, comment 2, and order 8.
+    fn vertex_label(vid: VertexId) -> Option<StoreLabelId> {
+        match vid {
+            0 | 4 => Some(PERSON),
+            1 | 3 => Some(POST),
+            2 => Some(COMMENT),
+            8 => Some(ORDER),
+            _ => None,
+        }
+    }
+
+    /// A store that records the dedup and output props it is asked to scan with,
+    /// and the partitions it is asked to get or scan vertices from.
+    /// It further delays the setup of a scan by `setup_delay`, to mimic a slow storage.
+    #[derive(Default)]
+    struct TestStore {
+        dedup_prop_ids: Mutex<Vec<Option<Vec<PropId>>>>,
+        output_prop_ids: Mutex<Vec<Option<Vec<PropId>>>>,
+        vertex_partitions: Mutex<Vec<PartitionId>>,
+        scan_partitions: Mutex<Vec<Vec<PartitionId>>>,
+        setup_delay: Duration,
+        count_calls: Mutex<usize>,
+        conditions: Mutex<Vec<Option<Condition>>>,
+        // the number of the ids in each call of `get_vertex_properties`
+        vertex_batches: Mutex<Vec<usize>>,
+        // the number of the calls of `get_out_edges`
+        out_edge_reads: Mutex<usize>,
+        // the capabilities of the store, where None indicates that all are supported
+        caps: Option<StoreCaps>,
+        // the oldest snapshot that the store serves, where None indicates that it is unknown
+        oldest_snapshot_id: Option<SnapshotId>,
+        // the partitions that hold no vertices
+        empty_partitions: Vec<PartitionId>,
+        // the partitions whose scans are delayed by `setup_delay`, where empty indicates all of them
+        slow_partitions: Vec<PartitionId>,
+        // the partitions whose scans panic in the setup
+        crashed_partitions: Vec<PartitionId>,
+        // the partitions whose scans panic in the iteration, after a vertex is returned
+        broken_partitions: Vec<PartitionId>,
+        schema: Option<Arc<dyn Schema>>,
+    }
+
+    /// A schema that only knows the primary keys, where an order is keyed by two columns,
+    /// and a comment by one.
+    struct TestSchema;
+
+    impl Schema for TestSchema {
+        fn get_prop_id(&self, _name: &str) -> Option<PropId> {
+            None
+        }
+
+        fn get_prop_type(&self, _label: StoreLabelId, _prop_id: PropId) -> Option<DataType> {
+            None
+        }
+
+        fn get_prop_name(&self, _prop_id: PropId) -> Option<String> {
+            None
+        }
+
+        fn get_label_id(&self, _name: &str) -> Option<StoreLabelId> {
+            None
+        }
+
+        fn get_label_name(&self, _label: StoreLabelId) -> Option<String> {
+            None
+        }
+
+        fn get_primary_keys(&self, label: StoreLabelId) -> Option<Vec<PropId>> {
+            match label {
+                ORDER => Some(vec![1, 2]),
+                COMMENT => Some(vec![2]),
+                _ => None,
+            }
+        }
+
+        fn to_proto(&self) -> Vec<u8> {
+            unimplemented!()
+        }
+    }
+
+    impl GlobalGraphQuery for TestStore {
+        type V = TestVertex;
+        type E = TestEdge;
+        type VI = VertexIter;
+        type EI = EdgeIter;
+
+        fn get_out_vertex_ids(
+            &self, _si: SnapshotId, src_ids: Vec<PartitionVertexIds>, _edge_labels: &Vec<StoreLabelId>,
+            _condition: Option<&Condition>, _dedup_prop_ids: Option<&Vec<PropId>>, _limit: usize,
+        ) -> Box<dyn Iterator<Item = (VertexId, Self::VI)>> {
+            adjacency(src_ids, |src| {
+                likes_out(src)
+                    .into_iter()
+                    .map(|e| e.dst)
+                    .collect()
+            })
+        }
+
+        fn get_out_edges(
+            &self, _si: SnapshotId, src_ids: Vec<PartitionVertexIds>, _edge_labels: &Vec<StoreLabelId>,
+            _condition: Option<&Condition>, _dedup_prop_ids: Option<&Vec<PropId>>,
+            _output_prop_ids: Option<&Vec<PropId>>, _limit: usize,
+        ) -> Box<dyn Iterator<Item = (VertexId, Self::EI)>> {
+            *self.out_edge_reads.lock().unwrap() += 1;
+            adjacency(src_ids, likes_out)
+        }
+
+        fn get_in_vertex_ids(
+            &self, _si: SnapshotId, dst_ids: Vec<PartitionVertexIds>, _edge_labels: &Vec<StoreLabelId>,
+            _condition: Option<&Condition>, _dedup_prop_ids: Option<&Vec<PropId>>, _limit: usize,
+        ) -> Box<dyn Iterator<Item = (VertexId, Self::VI)>> {
+            adjacency(dst_ids, |dst| {
+                likes_in(dst)
+                    .into_iter()
+                    .map(|e| e.src)
+                    .collect()
+            })
+        }
+
+        fn get_in_edges(
+            &self, _si: SnapshotId, dst_ids: Vec<PartitionVertexIds>, _edge_labels: &Vec<StoreLabelId>,
+            _condition: Option<&Condition>, _dedup_prop_ids: Option<&Vec<PropId>>,
+            _output_prop_ids: Option<&Vec<PropId>>, _limit: usize,
+        ) -> Box<dyn Iterator<Item = (VertexId, Self::EI)>> {
+            adjacency(dst_ids, likes_in)
+        }
+
+        fn count_out_edges(
+            &self, _si: SnapshotId, src_ids: Vec<PartitionVertexIds>, _edge_labels: &Vec<StoreLabelId>,
+            _condition: Option<&Condition>,
+        ) -> Box<dyn Iterator<Item = (VertexId, usize)>> {
+            Box::new(adjacency(src_ids, likes_out).map(|(src, edges)| (src, edges.len())))
+        }
+
+        fn count_in_edges(
+            &self, _si: SnapshotId, dst_ids: Vec<PartitionVertexIds>, _edge_labels: &Vec<StoreLabelId>,
+            _condition: Option<&Condition>,
+        ) -> Box<dyn Iterator<Item = (VertexId, usize)>> {
+            Box::new(adjacency(dst_ids, likes_in).map(|(dst, edges)| (dst, edges.len())))
+        }
+
+        fn get_vertex_properties(
+            &self, _si: SnapshotId, ids: Vec<PartitionLabeledVertexIds>,
+            _output_prop_ids: Option<&Vec<PropId>>,
+        ) -> Self::VI {
+            let num_ids = ids
+                .iter()
+                .flat_map(|(_pid, label_vertex_ids)| label_vertex_ids)
+                .map(|(_label, vids)| vids.len())
+                .sum();
+            self.vertex_batches
+                .lock()
+                .unwrap()
+                .push(num_ids);
+            let mut vertices = vec![];
+            for (pid, label_vertex_ids) in ids {
+                self.vertex_partitions.lock().unwrap().push(pid);
+                for vid in label_vertex_ids
+                    .into_iter()
+                    .flat_map(|(_label, vids)| vids)
+                {
+                    if let Some(label) = vertex_label(vid) {
+                        vertices.push(TestVertex(vid, label));
+                    }
+                }
+            }
+            vertices.into_iter()
+        }
+
+        fn get_edge_properties(
+            &self, _si: SnapshotId, _ids: Vec<PartitionLabeledVertexIds>,
+            _output_prop_ids: Option<&Vec<PropId>>,
+        ) -> Self::EI {
+            unimplemented!()
+        }
+
+        fn get_all_vertices(
+            &self, _si: SnapshotId, _labels: &Vec<StoreLabelId>, condition: Option<&Condition>,
+            dedup_prop_ids: Option<&Vec<PropId>>, output_prop_ids: Option<&Vec<PropId>>, _limit: usize,
+            partition_ids: &Vec<PartitionId>,
+        ) -> Self::VI {
+            if self.slow_partitions.is_empty()
+                || partition_ids
+                    .iter()
+                    .any(|pid| self.slow_partitions.contains(pid))
+            {
+                std::thread::sleep(self.setup_delay);
+            }
+            if partition_ids
+                .iter()
+                .any(|pid| self.crashed_partitions.contains(pid))
+            {
+                panic!("the store crashed in partitions {:?}", partition_ids);
+            }
+            self.conditions
+                .lock()
+                .unwrap()
+                .push(condition.cloned());
+            self.dedup_prop_ids
+                .lock()
+                .unwrap()
+                .push(dedup_prop_ids.cloned());
+            self.output_prop_ids
+                .lock()
+                .unwrap()
+                .push(output_prop_ids.cloned());
+            self.scan_partitions
+                .lock()
+                .unwrap()
+                .push(partition_ids.clone());
+            if partition_ids
+                .iter()
+                .all(|pid| self.empty_partitions.contains(pid))
+            {
+                vec![].into_iter()
+            } else if partition_ids
+                .iter()
+                .any(|pid| self.broken_partitions.contains(pid))
+            {
+                vec![TestVertex(1, PERSON), TestVertex(BROKEN_VERTEX_ID, PERSON)].into_iter()
+            } else {
+                vec![TestVertex(1, PERSON)].into_iter()
+            }
+        }
+
+        fn get_all_edges(
+            &self, _si: SnapshotId, _labels: &Vec<StoreLabelId>, _condition: Option<&Condition>,
+            dedup_prop_ids: Option<&Vec<PropId>>, _output_prop_ids: Option<&Vec<PropId>>, _limit: usize,
+            _partition_ids: &Vec<PartitionId>,
+        ) -> Self::EI {
+            self.dedup_prop_ids
+                .lock()
+                .unwrap()
+                .push(dedup_prop_ids.cloned());
+            vec![].into_iter()
+        }
+
+        // there are `10 * (p + 1)` vertices and `p` edges in partition `p`
+        fn count_all_vertices(
+            &self, _si: SnapshotId, _labels: &Vec<StoreLabelId>, condition: Option<&Condition>,
+            partition_ids: &Vec<PartitionId>,
+        ) -> u64 {
+            *self.count_calls.lock().unwrap() += 1;
+            // partition `p` holds the vertices of ids `[100 * p, 100 * p + 10 * (p + 1))`
+            partition_ids
+                .iter()
+                .map(|p| {
+                    let start = 100 * (*p as VertexId);
+                    (start..start + 10 * (*p as VertexId + 1))
+                        .filter(|vid| {
+                            condition
+                                .map(|cond| {
+                                    cond.filter_vertex(&TestVertex(*vid, PERSON))
+                                        .unwrap_or(false)
+                                })
+                                .unwrap_or(true)
+                        })
+                        .count() as u64
+                })
+                .sum()
+        }
+
+        fn count_all_edges(
+            &self, _si: SnapshotId, _labels: &Vec<StoreLabelId>, _condition: Option<&Condition>,
+            partition_ids: &Vec<PartitionId>,
+        ) -> u64 {
+            *self.count_calls.lock().unwrap() += 1;
+            partition_ids.iter().map(|p| *p as u64).sum()
+        }
+
+        fn translate_vertex_id(&self, vertex_id: VertexId) -> VertexId {
+            vertex_id
+        }
+
+        fn get_schema(&self, _si: SnapshotId) -> Option<Arc<dyn Schema>> {
+            self.schema.clone()
+        }
+
+        fn get_store_caps(&self) -> StoreCaps {
+            self.caps.unwrap_or(StoreCaps {
+                supports_row_filter: true,
+                supports_column_filter: true,
+                supports_regex: true,
+            })
+        }
+
+        fn get_oldest_snapshot_id(&self) -> Option<SnapshotId> {
+            self.oldest_snapshot_id
+        }
+    }
+
+    struct TestPartitionManager;
+
+    // The vertices of odd ids reside in the remote partition 1.
+    impl GraphPartitionManager for TestPartitionManager {
+        fn get_partition_id(&self, vid: VertexId) -> i32 {
+            (vid % 2) as i32
+        }
+
+        fn get_server_id(&self, _pid: PartitionId) -> Option<u32> {
+            Some(0)
+        }
+
+        fn get_process_partition_list(&self) -> Vec<PartitionId> {
+            vec![0]
+        }
+
+        fn get_vertex_id_by_primary_key(
+            &self, _label_id: StoreLabelId, _key: &String,
+        ) -> Option<(PartitionId, VertexId)> {
+            None
+        }
+
+        fn get_vertex_id_by_primary_keys(
+            &self, _label_id: StoreLabelId, _pks: &[Property],
+        ) -> Option<VertexId> {
+            None
+        }
+    }
+
+    /// A hash-partitioned manager, where the vertex of a long pk takes the pk as its id,
+    /// and the pk-to-vertex mapping is not available for pks of other types.
+    /// It records the number of global pk lookups, and the partitions whose local index is probed.
+    struct HashPartitionManager {
+        partition_num: u32,
+        global_lookups: Mutex<usize>,
+        local_probes: Mutex<Vec<PartitionId>>,
+    }
+
+    impl HashPartitionManager {
+        fn new(partition_num: u32) -> Self {
+            HashPartitionManager {
+                partition_num,
+                global_lookups: Mutex::new(0),
+                local_probes: Mutex::new(vec![]),
+            }
+        }
+
+        fn lookup(&self, pks: &[Property]) -> Option<VertexId> {
+            match pks {
+                [Property::Long(pk)] => Some(*pk as VertexId),
+                _ => None,
+            }
+        }
+    }
+
+    impl GraphPartitionManager for HashPartitionManager {
+        fn get_partition_id(&self, vid: VertexId) -> i32 {
+            (vid % self.partition_num as VertexId) as i32
+        }
+
+        fn get_server_id(&self, _pid: PartitionId) -> Option<u32> {
+            Some(0)
+        }
+
+        fn get_process_partition_list(&self) -> Vec<PartitionId> {
+            (0..self.partition_num).collect()
+        }
+
+        fn get_vertex_id_by_primary_key(
+            &self, _label_id: StoreLabelId, _key: &String,
+        ) -> Option<(PartitionId, VertexId)> {
+            None
+        }
+
+        fn get_vertex_id_by_primary_keys(
+            &self, _label_id: StoreLabelId, pks: &[Property],
+        ) -> Option<VertexId> {
+            *self.global_lookups.lock().unwrap() += 1;
+            self.lookup(pks)
+        }
+
+        fn get_vertex_id_by_primary_keys_in_partition(
+            &self, partition_id: PartitionId, _label_id: StoreLabelId, pks: &[Property],
+        ) -> Option<VertexId> {
+            self.local_probes
+                .lock()
+                .unwrap()
+                .push(partition_id);
+            self.lookup(pks)
+                .filter(|vid| self.get_partition_id(*vid) as PartitionId == partition_id)
+        }
+    }
+
+    struct TestClusterInfo;
+
+    impl ClusterInfo for TestClusterInfo {
+        fn get_server_num(&self) -> GraphProxyResult<u32> {
+            Ok(1)
+        }
+
+        fn get_server_index(&self) -> GraphProxyResult<u32> {
+            Ok(0)
+        }
+
+        fn get_local_worker_num(&self) -> GraphProxyResult<u32> {
+            Ok(1)
+        }
+
+        fn get_worker_index(&self) -> GraphProxyResult<u32> {
+            Ok(0)
+        }
+    }
+
+    fn create_test_store(
+        store: Arc<TestStore>,
+    ) -> Arc<GraphScopeStore<TestVertex, VertexIter, TestEdge, EdgeIter>> {
+        create_gs_store(
+            store,
+            Arc::new(TestPartitionManager),
+            vec![0],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scan_forwards_dedup_props() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+
+        let mut params = QueryParams::default();
+        params.dedup_props = Some(vec![1, 2]);
+        assert_eq!(graph.scan_vertex(&params).unwrap().count(), 1);
+        assert_eq!(graph.scan_edge(&params).unwrap().count(), 0);
+
+        // no dedup by default
+        let params = QueryParams::default();
+        graph.scan_vertex(&params).unwrap().count();
+
+        assert_eq!(*store.dedup_prop_ids.lock().unwrap(), vec![Some(vec![1, 2]), Some(vec![1, 2]), None]);
+    }
+
+    #[test]
+    fn test_scan_fetches_projected_props() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+
+        // the projection narrows the props fetched for the columns
+        let mut params = QueryParams::default();
+        params.columns = Some(vec![NameOrId::Id(1), NameOrId::Id(2)]);
+        params.projection = Some(vec![NameOrId::Id(2)]);
+        assert_eq!(graph.scan_vertex(&params).unwrap().count(), 1);
+
+        // no projection by default
+        params.projection = None;
+        graph.scan_vertex(&params).unwrap().count();
+
+        assert_eq!(*store.output_prop_ids.lock().unwrap(), vec![Some(vec![2]), Some(vec![1, 2])]);
+    }
+
+    #[test]
+    fn test_validate_store_caps() {
+        let create_store = |caps: StoreCaps, row_filter_push_down: bool, column_filter_push_down: bool| {
+            let store = Arc::new(TestStore { caps: Some(caps), ..Default::default() });
+            create_gs_store(
+                store,
+                Arc::new(TestPartitionManager),
+                vec![0],
+                Arc::new(TestClusterInfo),
+                row_filter_push_down,
+                column_filter_push_down,
+            )
+        };
+        let row_only =
+            StoreCaps { supports_row_filter: true, supports_column_filter: false, supports_regex: false };
+        assert!(create_store(row_only, true, false).is_ok());
+        assert!(create_store(row_only, false, false).is_ok());
+        match create_store(row_only, true, true) {
+            Err(GraphProxyError::UnsupportedOperation(op)) => assert_eq!(op, "column filter pushdown"),
+            _ => panic!("the unsupported column filter pushdown is not rejected"),
+        }
+        match create_store(StoreCaps::default(), true, false) {
+            Err(GraphProxyError::UnsupportedOperation(op)) => assert_eq!(op, "row filter pushdown"),
+            _ => panic!("the unsupported row filter pushdown is not rejected"),
+        }
+    }
+
+    #[test]
+    fn test_partition_statistics() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_gs_store(
+            store.clone(),
+            Arc::new(TestPartitionManager),
+            vec![0, 1, 2],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+
+        let statistics = graph.statistics().unwrap();
+        let expected_vertex_counts: HashMap<PartitionId, u64> = vec![(0, 10), (1, 20), (2, 30)]
+            .into_iter()
+            .collect();
+        let expected_edge_counts: HashMap<PartitionId, u64> = vec![(0, 0), (1, 1), (2, 2)]
+            .into_iter()
+            .collect();
+        assert_eq!(statistics.partition_vertex_counts, expected_vertex_counts);
+        assert_eq!(statistics.partition_edge_counts, expected_edge_counts);
+        assert_eq!(statistics.total_vertex_count, 60);
+        assert_eq!(statistics.total_edge_count, 3);
+        assert_eq!(*store.count_calls.lock().unwrap(), 6);
+
+        // cached within the ttl
+        assert_eq!(graph.statistics().unwrap(), statistics);
+        assert_eq!(*store.count_calls.lock().unwrap(), 6);
+
+        // recomputed once expired
+        graph
+            .set_statistics_ttl(Duration::from_millis(0))
+            .unwrap();
+        assert_eq!(graph.statistics().unwrap(), statistics);
+        assert_eq!(*store.count_calls.lock().unwrap(), 12);
+    }
+
+    #[test]
+    fn test_estimate_filter_selectivity() {
+        let graph = create_gs_store(
+            Arc::new(TestStore::default()),
+            Arc::new(TestPartitionManager),
+            vec![0, 1, 2],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+        let params_with_filter = |filter: &str| {
+            let mut params = QueryParams::default();
+            params.filter =
+                Some(Arc::new(PEvaluator::try_from(str_to_expr_pb(filter.to_string()).unwrap()).unwrap()));
+            params
+        };
+
+        // no filter
+        assert_eq!(graph.estimate_filter_selectivity(&QueryParams::default()), Some(1.0));
+        // not collected yet
+        assert_eq!(graph.estimate_filter_selectivity(&params_with_filter("@.~id >= 0")), None);
+        // always true
+        let selectivity = graph
+            .collect_filter_selectivity(&params_with_filter("@.~id >= 0"))
+            .unwrap()
+            .unwrap();
+        assert!((selectivity - 1.0).abs() < 1e-6);
+        assert_eq!(graph.estimate_filter_selectivity(&params_with_filter("@.~id >= 0")), Some(selectivity));
+        // equality on the unique id, which keeps 1 out of 60 vertices
+        let selectivity = graph
+            .collect_filter_selectivity(&params_with_filter("@.~id == 104"))
+            .unwrap()
+            .unwrap();
+        assert!(selectivity > 0.0 && selectivity < 0.05);
+        assert_eq!(
+            graph.estimate_filter_selectivity(&params_with_filter("@.~id == 104")),
+            Some(selectivity)
+        );
+        // the function calls cannot be pushed down
+        assert_eq!(
+            graph
+                .collect_filter_selectivity(&params_with_filter("year(@.creationDate) == 2012"))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            graph.estimate_filter_selectivity(&params_with_filter("year(@.creationDate) == 2012")),
+            None
+        );
+        // expired with the statistics
+        graph
+            .set_statistics_ttl(Duration::from_millis(0))
+            .unwrap();
+        assert_eq!(graph.estimate_filter_selectivity(&params_with_filter("@.~id == 104")), None);
+    }
+
+    #[test]
+    fn test_scan_store_setup_timeout() {
+        let create_store = |store: Arc<TestStore>| {
+            create_gs_store_with_setup_timeout(
+                store,
+                Arc::new(TestPartitionManager),
+                vec![0],
+                Arc::new(TestClusterInfo),
+                true,
+                true,
+                Some(Duration::from_millis(100)),
+            )
+            .unwrap()
+        };
+
+        let slow_store =
+            Arc::new(TestStore { setup_delay: Duration::from_millis(300), ..Default::default() });
+        match create_store(slow_store).scan_vertex(&QueryParams::default()) {
+            Err(e @ GraphProxyError::Timeout(_)) => assert!(e.is_retryable()),
+            Err(e) => panic!("unexpected error {:?}", e),
+            Ok(_) => panic!("the slow store setup should time out"),
+        }
+
+        let store = Arc::new(TestStore::default());
+        let graph = create_store(store.clone());
+        assert_eq!(
+            graph
+                .scan_vertex(&QueryParams::default())
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(*store.scan_partitions.lock().unwrap(), vec![vec![0]]);
+    }
+
+    #[test]
+    fn test_scan_skip_failed_partitions() {
+        // partition 3 times out in setup, while 1 and 5 do not
+        let store = Arc::new(TestStore {
+            setup_delay: Duration::from_millis(300),
+            slow_partitions: vec![3],
+            ..Default::default()
+        });
+        let cluster_info: Arc<dyn ClusterInfo> = Arc::new(MultiWorkerClusterInfo { workers: 2, index: 1 });
+        let graph = create_gs_store_with_setup_timeout(
+            store,
+            Arc::new(TestPartitionManager),
+            (0..6).collect(),
+            cluster_info.clone(),
+            true,
+            true,
+            Some(Duration::from_millis(50)),
+        )
+        .unwrap();
+
+        // the whole scan fails by default
+        assert!(graph
+            .scan_vertex(&QueryParams::default())
+            .is_err());
+
+        let params = params_skipping_failed_partitions();
+        assert_eq!(graph.scan_vertex(&params).unwrap().count(), 2);
+
+        let worker_partitions = assign_worker_partitions(&graph.server_partitions, &cluster_info).unwrap();
+        let mut vertices = graph
+            .scan_vertex_skipping_failed_partitions(&params, worker_partitions)
+            .unwrap();
+        assert_eq!(vertices.skipped_partitions(), &[3]);
+        assert_eq!(vertices.by_ref().count(), 2);
+        assert_eq!(vertices.skipped_partitions(), &[3]);
+
+        // fails if all the partitions fail
+        assert!(graph
+            .scan_vertex_skipping_failed_partitions(&params, vec![3])
+            .is_err());
+    }
+
+    fn params_skipping_failed_partitions() -> QueryParams {
+        let mut params = QueryParams::default();
+        params.extra_params = Some(
+            vec![(SKIP_FAILED_PARTITIONS_KEY.to_string(), "true".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        params
+    }
+
+    #[test]
+    fn test_scan_skip_panicked_partitions() {
+        // partition 1 panics in setup, and 3 in the iteration after its first vertex
+        let store = Arc::new(TestStore {
+            crashed_partitions: vec![1],
+            broken_partitions: vec![3],
+            ..Default::default()
+        });
+        let graph = create_gs_store(
+            store,
+            Arc::new(TestPartitionManager),
+            vec![0],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+
+        let params = params_skipping_failed_partitions();
+        let mut vertices = graph
+            .scan_vertex_skipping_failed_partitions(&params, vec![1, 3, 5])
+            .unwrap();
+        assert_eq!(vertices.skipped_partitions(), &[1]);
+        // the vertex of partition 3 before its panic is kept
+        assert_eq!(vertices.by_ref().count(), 2);
+        assert_eq!(vertices.skipped_partitions(), &[1, 3]);
+    }
+
+    #[test]
+    fn test_scan_skip_failed_partitions_with_sample_limit() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_gs_store(
+            store,
+            Arc::new(TestPartitionManager),
+            vec![0],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+
+        // the limit applies to the vertices of all the partitions
+        let mut params = params_skipping_failed_partitions();
+        params.limit = Some(3);
+        let count = graph
+            .scan_vertex_skipping_failed_partitions(&params, (0..8).collect())
+            .unwrap()
+            .count();
+        assert_eq!(count, 3);
+
+        // the sample is drawn by a single rng across all the partitions, reproducible with the seed
+        let mut params = params_skipping_failed_partitions();
+        params.sample_ratio = Some(0.5);
+        params
+            .extra_params
+            .as_mut()
+            .unwrap()
+            .insert(SAMPLE_SEED_KEY.to_string(), "7".to_string());
+        let scan_sampled = || {
+            graph
+                .scan_vertex_skipping_failed_partitions(&params, (0..64).collect())
+                .unwrap()
+                .count()
+        };
+        let count = scan_sampled();
+        // a rng of the same seed per partition would sample all or none of the single-vertex partitions
+        assert!(count > 0 && count < 64);
+        assert_eq!(count, scan_sampled());
+    }
+
+    fn params_with_pks(key: NameOrId, pks: Vec<Object>) -> QueryParams {
+        let mut params = QueryParams::default();
+        params.labels = vec![PERSON as LabelId];
+        params.primary_keys = Some(
+            pks.into_iter()
+                .map(|pk| PKV::from((key.clone(), pk)))
+                .collect(),
+        );
+        params
+    }
+
+    #[test]
+    fn test_scan_prune_partitions_by_pks() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_gs_store(
+            store.clone(),
+            Arc::new(HashPartitionManager::new(16)),
+            (0..16).collect(),
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+
+        let pk = NameOrId::Id(GS_STORE_PK);
+        // a single pk only needs to scan the one partition its vertex resides in
+        graph
+            .scan_vertex(&params_with_pks(pk.clone(), vec![object!(23_i64)]))
+            .unwrap()
+            .count();
+        // no pk given
+        graph
+            .scan_vertex(&QueryParams::default())
+            .unwrap()
+            .count();
+        // the pk-to-partition mapping is unavailable for a string pk
+        graph
+            .scan_vertex(&params_with_pks(pk, vec![object!(23_i64), object!("23")]))
+            .unwrap()
+            .count();
+        // the property given is not the primary key of the label
+        graph
+            .scan_vertex(&params_with_pks(NameOrId::Id(1), vec![object!(23_i64)]))
+            .unwrap()
+            .count();
+
+        let all_partitions: Vec<PartitionId> = (0..16).collect();
+        assert_eq!(
+            *store.scan_partitions.lock().unwrap(),
+            vec![vec![7], all_partitions.clone(), all_partitions.clone(), all_partitions]
+        );
+    }
+
+    #[test]
+    fn test_scan_push_down_pk_equality() {
+        let pk_params = |pks: Vec<Object>| {
+            let mut params = QueryParams::default();
+            params.labels = vec![PERSON as LabelId];
+            params.primary_keys = Some(
+                pks.into_iter()
+                    .map(|pk| PKV::from((NameOrId::Id(GS_STORE_PK), pk)))
+                    .collect(),
+            );
+            params
+        };
+
+        // no index of the pks in the store
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+        graph
+            .scan_vertex(&pk_params(vec![object!(23_i64)]))
+            .unwrap()
+            .count();
+        // more than one pk
+        graph
+            .scan_vertex(&pk_params(vec![object!(23_i64), object!(24_i64)]))
+            .unwrap()
+            .count();
+        let expected = Condition::from_pk_equality(GS_STORE_PK as PropId, &object!(23_i64)).unwrap();
+        assert_eq!(*store.conditions.lock().unwrap(), vec![Some(expected), None]);
+
+        // the pk can be looked up in the index
+        let store = Arc::new(TestStore::default());
+        let graph = create_gs_store(
+            store.clone(),
+            Arc::new(HashPartitionManager::new(1)),
+            vec![0],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+        graph
+            .scan_vertex(&pk_params(vec![object!(23_i64)]))
+            .unwrap()
+            .count();
+        assert_eq!(*store.conditions.lock().unwrap(), vec![None]);
+    }
+
+    struct MultiWorkerClusterInfo {
+        workers: u32,
+        index: u32,
+    }
+
+    impl ClusterInfo for MultiWorkerClusterInfo {
+        fn get_server_num(&self) -> GraphProxyResult<u32> {
+            Ok(1)
+        }
+
+        fn get_server_index(&self) -> GraphProxyResult<u32> {
+            Ok(0)
+        }
+
+        fn get_local_worker_num(&self) -> GraphProxyResult<u32> {
+            Ok(self.workers)
+        }
+
+        fn get_worker_index(&self) -> GraphProxyResult<u32> {
+            Ok(self.index)
+        }
+    }
+
+    #[test]
+    fn test_scan_vertex_by_partition() {
+        let store = Arc::new(TestStore::default());
+        let cluster_info: Arc<dyn ClusterInfo> = Arc::new(MultiWorkerClusterInfo { workers: 2, index: 1 });
+        let server_partitions: Vec<PartitionId> = (0..6).collect();
+        let graph = create_gs_store(
+            store.clone(),
+            Arc::new(TestPartitionManager),
+            server_partitions.clone(),
+            cluster_info.clone(),
+            true,
+            true,
+        )
+        .unwrap();
+
+        let mut params = QueryParams::default();
+        params.limit = Some(1);
+        let mut partitions = vec![];
+        for (partition_id, vertices) in graph.scan_vertex_by_partition(&params).unwrap() {
+            // the limit applies within each partition
+            assert_eq!(vertices.count(), 1);
+            partitions.push(partition_id);
+        }
+        let worker_partitions = assign_worker_partitions(&server_partitions, &cluster_info).unwrap();
+        assert_eq!(partitions, vec![1, 3, 5]);
+        assert_eq!(partitions, worker_partitions);
+        assert_eq!(*store.scan_partitions.lock().unwrap(), vec![vec![1], vec![3], vec![5]]);
+    }
+
+    #[test]
+    fn test_assign_worker_partitions_without_workers() {
+        let cluster_info: Arc<dyn ClusterInfo> = Arc::new(MultiWorkerClusterInfo { workers: 0, index: 0 });
+        match assign_worker_partitions(&(0..6).collect(), &cluster_info) {
+            Err(e @ GraphProxyError::StorageUnavailable(_)) => assert!(e.is_retryable()),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let graph = create_gs_store(
+            Arc::new(TestStore::default()),
+            Arc::new(TestPartitionManager),
+            (0..6).collect(),
+            cluster_info,
+            true,
+            true,
+        )
+        .unwrap();
+        assert!(graph
+            .scan_vertex(&QueryParams::default())
+            .is_err());
+        assert!(graph
+            .count_vertex(&QueryParams::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_scan_vertex_with_partition_counts() {
+        let store = Arc::new(TestStore { empty_partitions: vec![3], ..Default::default() });
+        let cluster_info: Arc<dyn ClusterInfo> = Arc::new(MultiWorkerClusterInfo { workers: 2, index: 1 });
+        let graph = create_gs_store(
+            store,
+            Arc::new(TestPartitionManager),
+            (0..6).collect(),
+            cluster_info,
+            true,
+            true,
+        )
+        .unwrap();
+
+        let partition_counts = Arc::new(Mutex::new(HashMap::new()));
+        let vertices = graph
+            .scan_vertex_with_partition_counts(&QueryParams::default(), partition_counts.clone())
+            .unwrap();
+        // all the assigned partitions are recorded before the scan
+        assert_eq!(
+            *partition_counts.lock().unwrap(),
+            vec![(1, 0), (3, 0), (5, 0)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(vertices.count(), 2);
+        assert_eq!(
+            *partition_counts.lock().unwrap(),
+            vec![(1, 1), (3, 0), (5, 1)]
+                .into_iter()
+                .collect()
+        );
+
+        // the count of a partition left behind by the limit is added when the scan is dropped
+        let partition_counts = Arc::new(Mutex::new(HashMap::new()));
+        let mut params = QueryParams::default();
+        params.limit = Some(1);
+        let mut vertices = graph
+            .scan_vertex_with_partition_counts(&params, partition_counts.clone())
+            .unwrap();
+        assert!(vertices.next().is_some());
+        drop(vertices);
+        assert_eq!(
+            *partition_counts.lock().unwrap(),
+            vec![(1, 1), (3, 0), (5, 0)]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_index_scan_vertex_local() {
+        let partition_manager = Arc::new(HashPartitionManager::new(4));
+        // partitions 2 and 3 reside in the remote server
+        let graph = create_gs_store(
+            Arc::new(TestStore::default()),
+            partition_manager.clone(),
+            vec![0, 1],
+            Arc::new(TestClusterInfo),
+            true,
+            true,
+        )
+        .unwrap();
+        let pk = |id: i64| PKV::from(("id".into(), object!(id)));
+
+        // person 4 resides in partition 0
+        let vertex = graph
+            .index_scan_vertex_local(0, PERSON as LabelId, &pk(4), &QueryParams::default())
+            .unwrap();
+        assert_eq!(vertex.map(|v| v.id()), Some(4));
+        // not found in the hinted partition
+        let vertex = graph
+            .index_scan_vertex_local(1, PERSON as LabelId, &pk(4), &QueryParams::default())
+            .unwrap();
+        assert!(vertex.is_none());
+        // the remote partition is not probed at all
+        let vertex = graph
+            .index_scan_vertex_local(2, COMMENT as LabelId, &pk(2), &QueryParams::default())
+            .unwrap();
+        assert!(vertex.is_none());
+
+        assert_eq!(*partition_manager.local_probes.lock().unwrap(), vec![0, 1]);
+        assert_eq!(*partition_manager.global_lookups.lock().unwrap(), 0);
+
+        // while index_scan_vertex() searches for the vertex globally
+        let vertex = graph
+            .index_scan_vertex(PERSON as LabelId, &pk(4), &QueryParams::default())
+            .unwrap();
+        assert_eq!(vertex.map(|v| v.id()), Some(4));
+        assert_eq!(*partition_manager.global_lookups.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_get_edge_unsupported() {
+        let graph = create_test_store(Arc::new(TestStore::default()));
+        assert!(matches!(
+            graph.get_edge(&[1], &QueryParams::default()),
+            Err(GraphProxyError::UnsupportedOperation(_))
+        ));
+    }
+
+    fn params_with_end_v_labels(labels: Vec<StoreLabelId>) -> QueryParams {
+        let mut params = QueryParams::default();
+        params.end_v_labels = Some(
+            labels
+                .into_iter()
+                .map(|label| label as LabelId)
+                .collect(),
+        );
+        params
+    }
+
+    #[test]
+    fn test_explore_edge_by_end_v_labels() {
+        let graph = create_test_store(Arc::new(TestStore::default()));
+        let explore_edge = |direction: Direction, params: &QueryParams, v: ID| -> Vec<(ID, LabelId)> {
+            graph
+                .prepare_explore_edge(direction, params)
+                .unwrap()
+                .exec(v)
+                .unwrap()
+                .map(|e| (e.get_other_id(), *e.get_other_label().unwrap()))
+                .collect()
+        };
+
+        // all destinations of mixed labels by default
+        assert_eq!(explore_edge(Direction::Out, &QueryParams::default(), 0).len(), 3);
+        let params = params_with_end_v_labels(vec![POST]);
+        assert_eq!(
+            explore_edge(Direction::Out, &params, 0),
+            vec![(1, POST as LabelId), (3, POST as LabelId)]
+        );
+        let params = params_with_end_v_labels(vec![COMMENT]);
+        assert_eq!(explore_edge(Direction::Out, &params, 0), vec![(2, COMMENT as LabelId)]);
+        let params = params_with_end_v_labels(vec![POST, COMMENT]);
+        assert_eq!(explore_edge(Direction::Out, &params, 0).len(), 3);
+        // the limit applies to the filtered edges
+        let mut params = params_with_end_v_labels(vec![POST]);
+        params.limit = Some(1);
+        assert_eq!(explore_edge(Direction::Out, &params, 0), vec![(1, POST as LabelId)]);
+
+        // the other end of an in-expansion is the source vertex
+        let params = params_with_end_v_labels(vec![PERSON]);
+        assert_eq!(explore_edge(Direction::In, &params, 2).len(), 2);
+        let params = params_with_end_v_labels(vec![POST]);
+        assert!(explore_edge(Direction::In, &params, 2).is_empty());
+        assert!(explore_edge(Direction::Both, &params, 2).is_empty());
+    }
+
+    #[test]
+    fn test_get_degree() {
+        let graph = create_test_store(Arc::new(TestStore::default()));
+        let explore_count = |direction: Direction, params: &QueryParams, v: ID| -> u64 {
+            graph
+                .prepare_explore_edge(direction, params)
+                .unwrap()
+                .exec(v)
+                .unwrap()
+                .count() as u64
+        };
+        let cases = vec![
+            // read from the count of the store
+            (0, Direction::Out, QueryParams::default(), 3),
+            (2, Direction::In, QueryParams::default(), 2),
+            (0, Direction::Both, QueryParams::default(), 3),
+            (0, Direction::Out, QueryParams { limit: Some(2), ..Default::default() }, 2),
+            // counted by exploring the edges to the vertices of the end labels
+            (0, Direction::Out, params_with_end_v_labels(vec![POST]), 2),
+        ];
+        for (v, direction, params, expected) in cases {
+            assert_eq!(graph.get_degree(v, direction, &params).unwrap(), expected);
+            assert_eq!(explore_count(direction, &params, v), expected);
+        }
+    }
+
+    #[test]
+    fn test_explore_vertex_by_end_v_labels() {
+        let graph = create_test_store(Arc::new(TestStore::default()));
+        let params = params_with_end_v_labels(vec![POST]);
+        let ids: Vec<ID> = graph
+            .prepare_explore_vertex(Direction::Out, &params)
+            .unwrap()
+            .exec(0)
+            .unwrap()
+            .map(|v| v.id())
+            .collect();
+        assert_eq!(ids, vec![1, 3]);
+        let count = graph
+            .prepare_explore_vertex(Direction::Out, &QueryParams::default())
+            .unwrap()
+            .exec(0)
+            .unwrap()
+            .count();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_get_vertex_in_batches() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+        graph.set_max_batch_size(2);
+
+        let mut ids: Vec<ID> = graph
+            .get_vertex(&[0, 1, 2, 3, 4], &QueryParams::default())
+            .unwrap()
+            .map(|v| v.id())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![2, 2, 1]);
+
+        // fetched in one call within the batch size
+        store.vertex_batches.lock().unwrap().clear();
+        assert_eq!(
+            graph
+                .get_vertex(&[0, 1], &QueryParams::default())
+                .unwrap()
+                .count(),
+            2
+        );
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn test_read_consistency_of_unavailable_snapshot() {
+        let store = Arc::new(TestStore { oldest_snapshot_id: Some(10), ..Default::default() });
+        let graph = create_test_store(store);
+        let params_with_snapshot = |si: &str, consistency: Option<&str>| {
+            let mut extra_params = std::collections::HashMap::new();
+            extra_params.insert(SNAPSHOT_ID.to_string(), si.to_string());
+            if let Some(consistency) = consistency {
+                extra_params.insert(READ_CONSISTENCY_KEY.to_string(), consistency.to_string());
+            }
+            let mut params = QueryParams::default();
+            params.extra_params = Some(extra_params);
+            params
+        };
+
+        // the compacted snapshot is read in best effort by default
+        let mut warned_at = None;
+        for consistency in vec![None, Some("best_effort")] {
+            let params = params_with_snapshot("5", consistency);
+            assert!(graph.scan_vertex(&params).is_ok());
+            assert!(graph.get_vertex(&[0], &params).is_ok());
+            // only warned once within the interval
+            let last_warned_at = *graph.stale_snapshot_warned_at.lock().unwrap();
+            assert!(last_warned_at.is_some());
+            assert!(warned_at.is_none() || warned_at == last_warned_at);
+            warned_at = last_warned_at;
+        }
+
+        let params = params_with_snapshot("5", Some("strict"));
+        match graph.scan_vertex(&params) {
+            Err(e @ GraphProxyError::SnapshotUnavailable { requested: 5, oldest: 10 }) => {
+                assert!(!e.is_retryable())
+            }
+            Err(e) => panic!("unexpected error {:?}", e),
+            Ok(_) => panic!("the unavailable snapshot should fail the query in strict consistency"),
+        }
+        assert!(graph.get_vertex(&[0], &params).is_err());
+
+        // the available snapshots are read as usual in strict consistency
+        assert!(graph
+            .scan_vertex(&params_with_snapshot("10", Some("strict")))
+            .is_ok());
+        let mut params = QueryParams::default();
+        params.extra_params = Some(
+            vec![(READ_CONSISTENCY_KEY.to_string(), "strict".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        assert!(graph.scan_vertex(&params).is_ok());
+    }
+
+    #[test]
+    fn test_get_vertex_with_misses() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+
+        // 3 resides in the remote partition, while 5 and 6 do not exist at all
+        let (vertices, mut misses) = graph
+            .get_vertex_with_misses(&[0, 3, 5, 6], &QueryParams::default())
+            .unwrap();
+        let mut ids: Vec<ID> = vertices.map(|v| v.id()).collect();
+        ids.sort();
+        misses.sort();
+        assert_eq!(ids, vec![0, 3]);
+        assert_eq!(misses, vec![5, 6]);
+        let mut partitions = store.vertex_partitions.lock().unwrap().clone();
+        partitions.sort();
+        assert_eq!(partitions, vec![0, 1]);
+
+        // all found
+        let (vertices, misses) = graph
+            .get_vertex_with_misses(&[1, 2], &QueryParams::default())
+            .unwrap();
+        assert_eq!(vertices.count(), 2);
+        assert!(misses.is_empty());
+
+        // vertices rejected by the filter are not misses
+        let mut params = QueryParams::default();
+        params.filter = Some(Arc::new(
+            PEvaluator::try_from(str_to_expr_pb("@.~id == 4".to_string()).unwrap()).unwrap(),
+        ));
+        let (vertices, misses) = graph
+            .get_vertex_with_misses(&[2, 4, 7], &params)
+            .unwrap();
+        assert_eq!(vertices.map(|v| v.id()).collect::<Vec<_>>(), vec![4]);
+        assert_eq!(misses, vec![7]);
+    }
+
+    #[test]
+    fn test_get_primary_key_by_schema() {
+        let store = Arc::new(TestStore { schema: Some(Arc::new(TestSchema)), ..Default::default() });
+        let graph = create_test_store(store.clone());
+        let params = QueryParams::default();
+        // the two-column primary key of the order, read in one lookup of the vertex
+        let pkv = graph
+            .get_primary_key_with_label(&8, &(ORDER as LabelId), &params)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(pkv, OneOrMany::Many(_)));
+        assert_eq!(
+            pkv.as_ref(),
+            &[(NameOrId::Id(1), Object::from(800_i64)), (NameOrId::Id(2), Object::from(8_i32))]
+        );
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![1]);
+        // the single-column primary key of the comment
+        let pkv = graph
+            .get_primary_key_with_label(&2, &(COMMENT as LabelId), &params)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(pkv, OneOrMany::One(_)));
+        assert_eq!(pkv.as_ref(), &[(NameOrId::Id(2), Object::from(2_i32))]);
+        // the label without the primary key in the schema falls back to the outer id
+        let pkv = graph
+            .get_primary_key_with_label(&4, &(PERSON as LabelId), &params)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(pkv, OneOrMany::One(_)));
+        assert_eq!(pkv.as_ref(), &[(NameOrId::Id(GS_STORE_PK), Object::from(4_i64))]);
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![1, 1]);
+        // as it does without the label
+        let pkv = graph.get_primary_key(&8).unwrap().unwrap();
+        assert!(matches!(pkv, OneOrMany::One(_)));
+        assert_eq!(pkv.as_ref(), &[(NameOrId::Id(GS_STORE_PK), Object::from(8_i64))]);
+
+        // the same without the schema
+        let graph = create_test_store(Arc::new(TestStore::default()));
+        let pkv = graph
+            .get_primary_key_with_label(&8, &(ORDER as LabelId), &params)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(pkv, OneOrMany::One(_)));
+        assert_eq!(pkv.as_ref(), &[(NameOrId::Id(GS_STORE_PK), Object::from(8_i64))]);
+    }
+
+    #[test]
+    fn test_fetch_vertex_property_on_demand() {
+        use ir_common::generated::common as common_pb;
+
+        use crate::apis::PropKey;
+        use crate::utils::expr::eval::Operand;
+        use crate::utils::expr::eval_pred::{Predicate, Predicates};
+
+        // @.1 > 0, with the columns pruned to the ones in the filter
+        let filter = Arc::new(PEvaluator::Predicates(Predicates::Binary(Predicate::new(
+            Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(1))) },
+            common_pb::Logical::Gt,
+            Operand::Const(object!(0)),
+        ))));
+        let mut params = QueryParams::default();
+        params.filter = Some(filter);
+        params.columns = Some(vec![NameOrId::Id(1)]);
+
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+        let vertex = graph
+            .get_vertex(&[8], &params)
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![1]);
+        assert_eq!(
+            vertex
+                .get_property(&NameOrId::Id(1))
+                .and_then(|prop| prop.try_to_owned()),
+            Some(Object::from(800_i64))
+        );
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![1]);
+        // a wider projection than the pruned columns, which is fetched from the storage on demand
+        assert_eq!(
+            vertex
+                .get_property(&NameOrId::Id(2))
+                .and_then(|prop| prop.try_to_owned()),
+            Some(Object::from(8_i32))
+        );
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![1, 1]);
+        // as well as the other properties, which are fetched along with it
+        assert_eq!(
+            vertex
+                .get_property(&NameOrId::Id(1))
+                .and_then(|prop| prop.try_to_owned()),
+            Some(Object::from(800_i64))
+        );
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![1, 1]);
+    }
+
+    #[test]
+    fn test_fetch_edge_property_on_demand() {
+        let mut params = QueryParams::default();
+        params.columns = Some(vec![NameOrId::Id(1)]);
+
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+        let edges: Vec<Edge> = graph
+            .prepare_explore_edge(Direction::Out, &params)
+            .unwrap()
+            .exec(0)
+            .unwrap()
+            .collect();
+        assert_eq!(edges.len(), 3);
+        assert_eq!(*store.out_edge_reads.lock().unwrap(), 1);
+        for _ in 0..2 {
+            for edge in edges.iter() {
+                // a wider projection than the pruned columns, which is fetched from the storage on demand
+                assert_eq!(
+                    edge.get_property(&NameOrId::Id(2))
+                        .and_then(|prop| prop.try_to_owned()),
+                    Some(Object::from(edge.get_other_id() as i32))
+                );
+            }
+        }
+        // the adjacency of the source vertex is read once for all of its edges, rather than per edge
+        assert_eq!(*store.out_edge_reads.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_count_vertex_by_ids() {
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+        graph.set_max_batch_size(2);
+
+        // 5 and 6 do not exist, and the existing ones are checked in batches
+        assert_eq!(
+            graph
+                .count_vertex_by_ids(&[0, 3, 5, 6], &QueryParams::default())
+                .unwrap(),
+            2
+        );
+        assert_eq!(*store.vertex_batches.lock().unwrap(), vec![2, 2]);
+
+        let mut params = QueryParams::default();
+        params.limit = Some(1);
+        assert_eq!(
+            graph
+                .count_vertex_by_ids(&[0, 1, 2], &params)
+                .unwrap(),
+            1
+        );
+
+        params.limit = None;
+        params.filter = Some(Arc::new(
+            PEvaluator::try_from(str_to_expr_pb("@.~id == 4".to_string()).unwrap()).unwrap(),
+        ));
+        assert_eq!(
+            graph
+                .count_vertex_by_ids(&[2, 4, 7], &params)
+                .unwrap(),
+            1
+        );
+    }
+    #[test]
+    fn test_function_call_filter_not_pushed_down() {
+        let filter = Arc::new(
+            PEvaluator::try_from(str_to_expr_pb("year(@.creationDate) == 2012".to_string()).unwrap())
+                .unwrap(),
+        );
+        let (condition, not_pushed_down) = encode_storage_row_filter_condition(Some(&filter), true, true);
+        assert!(condition.is_none());
+        assert!(not_pushed_down);
+    }
+
+    #[test]
+    fn test_regex_filter_push_down() {
+        use ir_common::generated::common as common_pb;
+
+        use crate::apis::PropKey;
+        use crate::utils::expr::eval::Operand;
+        use crate::utils::expr::eval_pred::{Predicate, Predicates};
+
+        // @.2 regex "^m.*", i.e., the name matches "^m.*"
+        let filter = Arc::new(PEvaluator::Predicates(Predicates::Binary(Predicate::new(
+            Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(2))) },
+            common_pb::Logical::Regex,
+            Operand::Const(object!("^m.*")),
+        ))));
+        let mut params = QueryParams::default();
+        params.filter = Some(filter.clone());
+
+        // pushed down to the storage supporting regex
+        let store = Arc::new(TestStore::default());
+        let graph = create_test_store(store.clone());
+        assert_eq!(graph.scan_vertex(&params).unwrap().count(), 1);
+        let expected: Option<Condition> = filter.as_ref().try_into().unwrap();
+        assert!(expected.is_some());
+        assert_eq!(*store.conditions.lock().unwrap(), vec![expected]);
+
+        // otherwise, evaluated in runtime, where the test vertex has no name at all
+        let caps =
+            StoreCaps { supports_row_filter: true, supports_column_filter: true, supports_regex: false };
+        let store = Arc::new(TestStore { caps: Some(caps), ..Default::default() });
+        let graph = create_test_store(store.clone());
+        assert_eq!(graph.scan_vertex(&params).unwrap().count(), 0);
+        assert_eq!(*store.conditions.lock().unwrap(), vec![None]);
+    }
+}
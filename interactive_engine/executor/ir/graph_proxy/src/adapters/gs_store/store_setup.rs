@@ -0,0 +1,168 @@
+//
+//! Copyright 2024 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{GraphProxyError, GraphProxyResult};
+
+type SetupJob = Box<dyn FnOnce() + Send>;
+
+/// A bounded pool of threads that set up the scans in the storage, with which a scan only waits
+/// for its setup within a timeout. As a setup in the storage cannot be interrupted, the one beyond
+/// its timeout is dropped if it has not started yet, or is left to finish on its thread otherwise.
+/// Once all the threads are busy and the queue of the pending setups is full, e.g., as the storage
+/// hangs, a setup fails right away, rather than spawning any more threads.
+pub(crate) struct StoreSetupPool {
+    timeout: Duration,
+    sender: Mutex<mpsc::SyncSender<SetupJob>>,
+}
+
+impl StoreSetupPool {
+    pub fn new(timeout: Duration, num_threads: usize, queue_size: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<SetupJob>(queue_size);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..num_threads.max(1) {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("store-setup-{}", i))
+                .spawn(move || loop {
+                    // the lock is released once a job is received, before running it
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    match job {
+                        // a panicking setup fails with its result dropped, without the thread lost
+                        Ok(job) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                        // the pool is dropped
+                        Err(_) => break,
+                    }
+                })
+                .expect("spawn store setup thread failed");
+        }
+        StoreSetupPool { timeout, sender: Mutex::new(sender) }
+    }
+
+    /// Run the setup on the pool, and wait for its result within the timeout.
+    pub fn setup<T, F>(&self, setup: F) -> GraphProxyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let job_cancelled = cancelled.clone();
+        let job: SetupJob = Box::new(move || {
+            if !job_cancelled.load(Ordering::Relaxed) {
+                // the receiver may have been dropped on timeout
+                let _ = tx.send(setup());
+            }
+        });
+        let sent = self
+            .sender
+            .lock()
+            .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?
+            .try_send(job);
+        match sent {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => Err(GraphProxyError::storage_unavailable(
+                "too many store setups in progress, the storage may hang",
+            ))?,
+            Err(TrySendError::Disconnected(_)) => {
+                Err(GraphProxyError::internal_error("store setup pool is shut down"))?
+            }
+        }
+        rx.recv_timeout(self.timeout)
+            .map_err(|e| match e {
+                RecvTimeoutError::Timeout => {
+                    cancelled.store(true, Ordering::Relaxed);
+                    GraphProxyError::timeout_error(&format!(
+                        "store setup timed out after {:?}",
+                        self.timeout
+                    ))
+                }
+                RecvTimeoutError::Disconnected => {
+                    GraphProxyError::storage_unavailable("store setup aborted unexpectedly")
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::mpsc::Receiver;
+    use std::time::Instant;
+
+    use super::*;
+
+    /// A setup that blocks until released, and counts the times that it runs
+    fn blocking_setup(
+        runs: &Arc<AtomicUsize>, release: &Arc<Mutex<Receiver<()>>>,
+    ) -> impl FnOnce() -> usize + Send + 'static {
+        let runs = runs.clone();
+        let release = release.clone();
+        move || {
+            let _ = release.lock().unwrap().recv();
+            runs.fetch_add(1, Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_store_setup_pool() {
+        let pool = StoreSetupPool::new(Duration::from_millis(100), 1, 1);
+        assert_eq!(pool.setup(|| 1).unwrap(), 1);
+
+        let (release_tx, release_rx) = mpsc::channel();
+        let release = Arc::new(Mutex::new(release_rx));
+        let runs = Arc::new(AtomicUsize::new(0));
+        // occupies the only thread beyond its timeout
+        assert!(matches!(pool.setup(blocking_setup(&runs, &release)), Err(GraphProxyError::Timeout(_))));
+        // queued beyond its timeout, and is never run
+        assert!(matches!(pool.setup(blocking_setup(&runs, &release)), Err(GraphProxyError::Timeout(_))));
+        // neither a thread nor the queue is available
+        match pool.setup(blocking_setup(&runs, &release)) {
+            Err(e @ GraphProxyError::StorageUnavailable(_)) => assert!(e.is_retryable()),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+
+        release_tx.send(()).unwrap();
+        // the pool is available again once the hanging setup finishes, and the queued one is dropped,
+        // which is retried for a while, as the thread may not have been through them yet
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let result = loop {
+            match pool.setup(|| 2) {
+                Ok(result) => break result,
+                Err(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+                Err(e) => panic!("the pool is not available again: {}", e),
+            }
+        };
+        assert_eq!(result, 2);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // a panicking setup fails, and leaves the thread to the following setups
+        assert!(matches!(
+            pool.setup(|| -> usize { panic!("store crashed") }),
+            Err(GraphProxyError::StorageUnavailable(_))
+        ));
+        assert_eq!(pool.setup(|| 3).unwrap(), 3);
+    }
+}
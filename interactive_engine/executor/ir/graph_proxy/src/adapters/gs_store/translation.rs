@@ -33,7 +33,7 @@ impl Operand {
         match self {
             Operand::Var { tag: None, prop_key: Some(prop_key) } => match prop_key {
                 PropKey::Key(NameOrId::Id(id)) => Ok(*id as PropId),
-                _ => Err(GraphProxyError::UnSupported(format!("var error {:?}", self))),
+                _ => Err(GraphProxyError::unsupported_error(&format!("var error {:?}", self))),
             },
             _ => Err(GraphProxyError::FilterPushDownError(format!("not a var {:?}", self))),
         }
@@ -105,11 +105,45 @@ impl Predicates {
     }
 }
 
+impl Predicates {
+    /// Whether any of the predicates compares by `Regex`
+    pub(crate) fn has_regex(&self) -> bool {
+        match self {
+            Predicates::Binary(pred) => pred.cmp == common_pb::Logical::Regex,
+            Predicates::Not(pred) => pred.has_regex(),
+            Predicates::And((left, right)) | Predicates::Or((left, right)) => {
+                left.has_regex() || right.has_regex()
+            }
+            _ => false,
+        }
+    }
+}
+
 impl PEvaluator {
+    /// Whether the predicates compare by `Regex`, while a general evaluator is never pushed down anyway
+    pub(crate) fn has_regex(&self) -> bool {
+        match self {
+            PEvaluator::Predicates(preds) => preds.has_regex(),
+            PEvaluator::General(_) => false,
+        }
+    }
+
     pub(crate) fn extract_prop_ids(&self) -> Option<Vec<PropId>> {
         match self {
             PEvaluator::Predicates(preds) => preds.extract_prop_ids(),
-            _ => None,
+            // e.g., a filter calling functions, of which the arguments refer to properties
+            PEvaluator::General(eval) => {
+                let prop_ids: Vec<PropId> = eval
+                    .operands()
+                    .into_iter()
+                    .filter_map(|operand| operand.get_var_prop_id().ok())
+                    .collect();
+                if prop_ids.is_empty() {
+                    None
+                } else {
+                    Some(prop_ids)
+                }
+            }
         }
     }
 }
@@ -148,6 +182,9 @@ impl TryFrom<&Predicate> for StorePredCondition {
             common_pb::Logical::Endswith => {
                 StorePredCondition::new_predicate(left, StoreOprator::EndWith, right)
             }
+            common_pb::Logical::Regex => {
+                StorePredCondition::new_predicate(left, StoreOprator::Regex, right)
+            }
             _ => {
                 return Err(GraphProxyError::FilterPushDownError(format!(
                     "op {:?} shouldn't appear",
@@ -181,16 +218,7 @@ impl TryFrom<&Predicates> for Option<Condition> {
                 builder.and(Condition::new(pred));
                 Ok(builder.build())
             }
-            Predicates::Not(pred) => {
-                let cond: Option<Condition> = pred.as_ref().try_into()?;
-                if let Some(cond) = cond {
-                    builder.and(cond);
-                    builder.not();
-                    Ok(builder.build())
-                } else {
-                    Ok(None)
-                }
-            }
+            Predicates::Not(pred) => negate_condition(pred),
             Predicates::And((left, right)) => {
                 let left_cond: Option<Condition> = left.as_ref().try_into()?;
                 let right_cond: Option<Condition> = right.as_ref().try_into()?;
@@ -219,6 +247,59 @@ impl TryFrom<&Predicates> for Option<Condition> {
     }
 }
 
+/// Translate the negation of the predicates. The storage evaluates the condition in the two-valued logic,
+/// where a comparison against a missing property is simply `false`, and it agrees with the three-valued
+/// logic of `Predicates` (with `Unknown` filtered out) as long as `Not` is absent. Hence, the negation is
+/// pushed down to the comparisons by De Morgan's laws, and the negated comparison further requires its
+/// properties to exist, e.g., `!(@.age > 20)` is translated into `HasProp(age) && !(age > 20)`.
+fn negate_condition(preds: &Predicates) -> GraphProxyResult<Option<Condition>> {
+    let mut builder = ConditionBuilder::new();
+    match preds {
+        Predicates::Init => Ok(None),
+        Predicates::SingleItem(_) => {
+            // the existence of a property is never unknown
+            let cond: Option<Condition> = preds.try_into()?;
+            if let Some(cond) = cond {
+                builder.and(cond);
+                builder.not();
+            }
+            Ok(builder.build())
+        }
+        Predicates::Unary(_) => preds.try_into(),
+        Predicates::Binary(pred) => {
+            for prop_id in pred.extract_prop_ids().unwrap_or_default() {
+                builder.and(Condition::new(StorePredCondition::new_has_prop(prop_id)));
+            }
+            let pred: StorePredCondition = pred.try_into()?;
+            let cond = ConditionBuilder::new()
+                .and(Condition::new(pred))
+                .not()
+                .build();
+            if let Some(cond) = cond {
+                builder.and(cond);
+            }
+            Ok(builder.build())
+        }
+        Predicates::Not(pred) => pred.as_ref().try_into(),
+        Predicates::And((left, right)) | Predicates::Or((left, right)) => {
+            let left_cond = negate_condition(left)?;
+            let right_cond = negate_condition(right)?;
+            match (left_cond, right_cond) {
+                (Some(left_cond), Some(right_cond)) => {
+                    builder.and(left_cond);
+                    if let Predicates::And(_) = preds {
+                        builder.or(right_cond);
+                    } else {
+                        builder.and(right_cond);
+                    }
+                    Ok(builder.build())
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+}
+
 impl TryFrom<&PEvaluator> for Option<Condition> {
     type Error = GraphProxyError;
 
@@ -236,13 +317,16 @@ impl TryFrom<&PEvaluator> for Option<Condition> {
 
 #[cfg(test)]
 mod test {
+    use ahash::HashMap;
     use dyn_type::{Object, Primitives};
     use global_query::store_api::prelude::{Operand as StoreOperand, Property as StoreProperty};
-    use ir_common::NameOrId;
+    use global_query::store_api::{ElemFilter, LabelId as StoreLabelId, Vertex as StoreVertex, VertexId};
+    use ir_common::{KeyId, NameOrId};
 
     use super::*;
-    use crate::apis::PropKey;
+    use crate::apis::{DynDetails, PropKey, Vertex};
     use crate::utils::expr::eval::Operand;
+    use crate::utils::expr::eval_pred::EvalPred;
 
     #[test]
     fn test_empty_predicates_to_condition() {
@@ -271,7 +355,7 @@ mod test {
         let right = Operand::Const(Object::Primitive(Primitives::Integer(10)));
         let cmp = common_pb::Logical::Eq;
 
-        let pred = &Predicates::Binary(Predicate { left, cmp, right });
+        let pred = &Predicates::Binary(Predicate::new(left, cmp, right));
 
         let target = ConditionBuilder::new()
             .and(Condition::Pred(StorePredCondition::new_predicate(
@@ -289,7 +373,7 @@ mod test {
         let right = Operand::Const(Object::String("hello world".to_owned()));
         let cmp = common_pb::Logical::Startswith;
 
-        let pred = &Predicates::Binary(Predicate { left, cmp, right });
+        let pred = &Predicates::Binary(Predicate::new(left, cmp, right));
 
         let target = ConditionBuilder::new()
             .and(Condition::Pred(StorePredCondition::new_predicate(
@@ -317,19 +401,138 @@ mod test {
         assert_eq!(cond, target);
     }
 
+    #[test]
+    fn test_negated_predicates_to_condition() {
+        let left = Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(1))) };
+        let right = Operand::Const(Object::Primitive(Primitives::Integer(10)));
+        let cmp = common_pb::Logical::Ge;
+        let pred = &Predicates::Binary(Predicate::new(left, cmp, right)).not();
+
+        let target = ConditionBuilder::new()
+            .and(Condition::Pred(StorePredCondition::new_has_prop(1)))
+            .and(
+                ConditionBuilder::new()
+                    .and(Condition::Pred(StorePredCondition::new_predicate(
+                        StoreOperand::PropId(1),
+                        StoreOprator::GreaterEqual,
+                        StoreOperand::Const(StoreProperty::Int(10)),
+                    )))
+                    .not()
+                    .build()
+                    .unwrap(),
+            )
+            .build();
+        let cond: Option<Condition> = pred.try_into().unwrap();
+        assert_eq!(cond, target);
+    }
+
+    struct PropVertex(Vec<(PropId, i32)>);
+
+    impl StoreVertex for PropVertex {
+        type PI = std::vec::IntoIter<(PropId, StoreProperty)>;
+
+        fn get_id(&self) -> VertexId {
+            0
+        }
+
+        fn get_label_id(&self) -> StoreLabelId {
+            0
+        }
+
+        fn get_property(&self, prop_id: PropId) -> Option<StoreProperty> {
+            self.0
+                .iter()
+                .find(|(id, _)| *id == prop_id)
+                .map(|(_, val)| StoreProperty::Int(*val))
+        }
+
+        fn get_properties(&self) -> Self::PI {
+            self.0
+                .iter()
+                .map(|(id, val)| (*id, StoreProperty::Int(*val)))
+                .collect::<Vec<_>>()
+                .into_iter()
+        }
+    }
+
+    impl PropVertex {
+        fn to_runtime_vertex(&self) -> Vertex {
+            let props: HashMap<NameOrId, Object> = self
+                .0
+                .iter()
+                .map(|(id, val)| (NameOrId::Id(*id as KeyId), (*val).into()))
+                .collect();
+            Vertex::new(0, Some(0), DynDetails::new(props))
+        }
+    }
+
+    #[test]
+    fn test_condition_consistent_with_predicates() {
+        let cmp = |prop_id: KeyId, cmp: common_pb::Logical, val: i32| {
+            Predicates::Binary(Predicate::new(
+                Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(prop_id))) },
+                cmp,
+                Operand::Const(Object::Primitive(Primitives::Integer(val))),
+            ))
+        };
+        let has = |prop_id: KeyId| {
+            Predicates::SingleItem(Operand::Var {
+                tag: None,
+                prop_key: Some(PropKey::Key(NameOrId::Id(prop_id))),
+            })
+        };
+        let a = || cmp(1, common_pb::Logical::Ge, 10);
+        let b = || cmp(2, common_pb::Logical::Lt, 20);
+        let cases = vec![
+            a(),
+            a().not(),
+            a().not().not(),
+            a().and(b()),
+            a().or(b()),
+            a().and(b()).not(),
+            a().or(b()).not(),
+            a().not().or(b()),
+            a().not().and(b().not()).not(),
+            has(2).not().or(a().not()),
+            has(1).and(b().not()),
+        ];
+        // a vertex with both properties, either one, or none of them
+        let vertices = vec![
+            PropVertex(vec![(1, 15), (2, 30)]),
+            PropVertex(vec![(1, 5), (2, 10)]),
+            PropVertex(vec![(1, 15)]),
+            PropVertex(vec![(2, 10)]),
+            PropVertex(vec![]),
+        ];
+        for preds in cases {
+            let cond: Option<Condition> = (&preds).try_into().unwrap();
+            let cond = cond.unwrap();
+            for v in &vertices {
+                let runtime_vertex = v.to_runtime_vertex();
+                assert_eq!(
+                    cond.filter_vertex(v).unwrap(),
+                    preds.eval_bool(Some(&runtime_vertex)).unwrap(),
+                    "{:?} on {:?}",
+                    preds,
+                    v.0
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_and_predicates_to_condition() {
         let left = Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(1))) };
         let right = Operand::Const(Object::Primitive(Primitives::Integer(10)));
         let cmp = common_pb::Logical::Ge;
 
-        let pred_left = Predicates::Binary(Predicate { left, cmp, right });
+        let pred_left = Predicates::Binary(Predicate::new(left, cmp, right));
 
         let left = Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(1))) };
         let right = Operand::Const(Object::Primitive(Primitives::Integer(20)));
         let cmp = common_pb::Logical::Le;
 
-        let pred_right = Predicates::Binary(Predicate { left, cmp, right });
+        let pred_right = Predicates::Binary(Predicate::new(left, cmp, right));
 
         let pred = &Predicates::And((Box::new(pred_left), Box::new(pred_right)));
 
@@ -357,13 +560,13 @@ mod test {
         let right = Operand::Const(Object::Primitive(Primitives::Integer(10)));
         let cmp = common_pb::Logical::Ge;
 
-        let pred_left = Predicates::Binary(Predicate { left, cmp, right });
+        let pred_left = Predicates::Binary(Predicate::new(left, cmp, right));
 
         let left = Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Id(1))) };
         let right = Operand::Const(Object::Primitive(Primitives::Integer(20)));
         let cmp = common_pb::Logical::Le;
 
-        let pred_right = Predicates::Binary(Predicate { left, cmp, right });
+        let pred_right = Predicates::Binary(Predicate::new(left, cmp, right));
 
         let pred = &Predicates::Or((Box::new(pred_left), Box::new(pred_right)));
 
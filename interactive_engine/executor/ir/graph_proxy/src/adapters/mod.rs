@@ -21,8 +21,11 @@ mod gs_store;
 mod vineyard_store;
 
 pub use csr_store::create_csr_store;
-pub use exp_store::{create_exp_store, SimplePartition};
+pub use exp_store::{create_exp_store, create_exp_store_with_graph, SimplePartition};
 #[cfg(feature = "with_global_query")]
-pub use gs_store::{create_gs_store, GraphScopeStore, GrootMultiPartition, VineyardMultiPartition};
+pub use gs_store::{
+    create_gs_store, create_gs_store_with_setup_timeout, GraphScopeStore, GrootMultiPartition,
+    PartitionStatistics, VineyardMultiPartition,
+};
 #[cfg(feature = "with_global_query")]
 pub use vineyard_store::VineyardGraphWriter;
@@ -43,10 +43,10 @@ impl VineyardGraphWriter {
             OneOrMany::One(pkv) => {
                 let pk_value = &pkv[0].1;
                 pk_value.as_u64().map_err(|e| {
-                    GraphProxyError::query_store_error(&format!(
-                        "cast outer_id as u64 failed {:?}",
-                        e.to_string()
-                    ))
+                    GraphProxyError::store_permanent_error(
+                        "vineyard",
+                        &format!("cast outer_id as u64 failed {:?}", e.to_string()),
+                    )
                 })
             }
             OneOrMany::Many(_) => Err(GraphProxyError::write_graph_error(
@@ -0,0 +1,295 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use ir_common::generated::algebra as algebra_pb;
+use ir_common::generated::physical as physical_pb;
+
+use crate::apis::{QueryParams, ReadGraph};
+use crate::{GraphProxyError, GraphProxyResult};
+
+/// The selectivity of a predicate, for which there is no statistics to estimate it.
+const DEFAULT_SELECTIVITY: f64 = 0.5;
+
+/// The time that the degree collected by `collect_expand_degree()` is used for.
+const EXPAND_DEGREE_TTL: Duration = Duration::from_secs(60);
+
+/// Build the physical plan of a logical plan, which is done by the planner (i.e., `ir_core`) that the
+/// storage does not depend on, and is thus registered via `register_physical_planner()`.
+pub type PhysicalPlanner = fn(&algebra_pb::LogicalPlan) -> Result<physical_pb::PhysicalPlan, String>;
+
+lazy_static! {
+    static ref PHYSICAL_PLANNER: RwLock<Option<PhysicalPlanner>> = RwLock::new(None);
+    /// The average degrees collected by `collect_expand_degree()`, keyed by the labels and the direction
+    static ref EXPAND_DEGREES: RwLock<HashMap<String, (Instant, f64)>> = RwLock::new(HashMap::new());
+}
+
+/// Register the planner to build the physical plan of `QueryPlan`,
+/// which is expected to be done once when the server starts.
+pub fn register_physical_planner(planner: PhysicalPlanner) {
+    *PHYSICAL_PLANNER
+        .write()
+        .expect("physical planner poisoned") = Some(planner);
+}
+
+fn build_physical_plan(
+    logical_plan: &algebra_pb::LogicalPlan,
+) -> GraphProxyResult<Option<physical_pb::PhysicalPlan>> {
+    let planner = *PHYSICAL_PLANNER
+        .read()
+        .map_err(|e| GraphProxyError::internal_error(&format!("physical planner poisoned {}", e)))?;
+    planner
+        .map(|planner| planner(logical_plan).map_err(to_graph_proxy_error))
+        .transpose()
+}
+
+/// The estimated cost of an operator in a `QueryPlan`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OperatorCost {
+    /// The id of the operator, i.e., the index of its node in the logical plan
+    pub node_id: usize,
+    /// The estimated number of rows that the operator outputs
+    pub estimated_rows: f64,
+    /// The estimated cpu cost, counted as the number of rows the operator consumes and produces
+    pub estimated_cpu_cost: f64,
+    /// The estimated io cost, counted as the number of rows the operator reads from the storage
+    pub estimated_io_cost: f64,
+}
+
+/// A query plan that is annotated with the estimated cost of each operator, without being executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    pub logical_plan: algebra_pb::LogicalPlan,
+    /// The physical plan is built by the planner registered via `register_physical_planner()`,
+    /// and is `None` if no planner is registered.
+    pub physical_plan: Option<physical_pb::PhysicalPlan>,
+    /// The cost of each operator, in the same order as the nodes of the logical plan
+    pub costs: Vec<OperatorCost>,
+}
+
+impl QueryPlan {
+    pub fn with_physical_plan(mut self, physical_plan: physical_pb::PhysicalPlan) -> Self {
+        self.physical_plan = Some(physical_plan);
+        self
+    }
+
+    pub fn get_cost(&self, node_id: usize) -> Option<&OperatorCost> {
+        self.costs.get(node_id)
+    }
+
+    /// The total cost of the plan, summing up the cpu and io costs of all operators.
+    pub fn total_cost(&self) -> f64 {
+        self.costs
+            .iter()
+            .map(|cost| cost.estimated_cpu_cost + cost.estimated_io_cost)
+            .sum()
+    }
+}
+
+fn to_graph_proxy_error<E: std::fmt::Debug>(e: E) -> GraphProxyError {
+    GraphProxyError::unsupported_error(&format!("invalid query plan {:?}", e))
+}
+
+/// Only the labels of the query parameters are counted with the statistics of the storage,
+/// which avoids scanning the graph to evaluate the predicates.
+fn labels_only(params: &Option<algebra_pb::QueryParams>) -> GraphProxyResult<QueryParams> {
+    let tables = params
+        .as_ref()
+        .map(|params| params.tables.clone())
+        .unwrap_or_default();
+    let labels = tables
+        .into_iter()
+        .map(|table| table.try_into())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(to_graph_proxy_error)?;
+    Ok(QueryParams { labels, ..Default::default() })
+}
+
+fn selectivity(params: &Option<algebra_pb::QueryParams>) -> f64 {
+    if params
+        .as_ref()
+        .map(|params| params.predicate.is_some())
+        .unwrap_or(false)
+    {
+        DEFAULT_SELECTIVITY
+    } else {
+        1.0
+    }
+}
+
+fn apply_limit(rows: f64, params: &Option<algebra_pb::QueryParams>) -> f64 {
+    match params
+        .as_ref()
+        .and_then(|params| params.limit.as_ref())
+    {
+        Some(range) => rows.min((range.upper - range.lower).max(0) as f64),
+        None => rows,
+    }
+}
+
+/// The average number of edges that a vertex expands to, via the edges of the given labels.
+fn avg_degree<G: ReadGraph + ?Sized>(graph: &G, expand: &algebra_pb::EdgeExpand) -> GraphProxyResult<f64> {
+    avg_degree_of(graph, &expand.params, expand.direction)
+}
+
+fn avg_degree_of<G: ReadGraph + ?Sized>(
+    graph: &G, params: &Option<algebra_pb::QueryParams>, direction: i32,
+) -> GraphProxyResult<f64> {
+    let edges = graph.count_edge(&labels_only(params)?)? as f64;
+    let vertices = graph
+        .count_vertex(&QueryParams::default())?
+        .max(1) as f64;
+    let degree = edges / vertices;
+    // the directions of the algebra and the physical expansions are of the same values
+    if direction == algebra_pb::edge_expand::Direction::Both as i32 {
+        Ok(degree * 2.0)
+    } else {
+        Ok(degree)
+    }
+}
+
+/// The key of the degree of `expand` in `EXPAND_DEGREES`, of which only the labels and the direction matter.
+fn expand_degree_key(expand: &physical_pb::EdgeExpand) -> GraphProxyResult<String> {
+    let params = labels_only(&expand.params)?;
+    Ok(format!("{:?}/{}", params.labels, expand.direction))
+}
+
+/// Estimate the number of neighbors that a vertex expands to via the physical `EdgeExpand`, after
+/// its predicates are applied, e.g., to decide which branch of an intersection to expand first.
+/// As it is meant to be called when building the jobs, it never counts the graph, but takes the
+/// degree cached by `collect_expand_degree()` within the ttl, or returns `None` if there is none.
+pub fn estimate_expand_degree(expand: &physical_pb::EdgeExpand) -> Option<f64> {
+    let key = expand_degree_key(expand).ok()?;
+    let degrees = EXPAND_DEGREES.read().ok()?;
+    degrees
+        .get(&key)
+        .filter(|(collected_at, _)| collected_at.elapsed() < EXPAND_DEGREE_TTL)
+        .map(|(_, degree)| degree * selectivity(&expand.params))
+}
+
+/// Compute the average degree of the labels and the direction of `expand`, to be estimated by
+/// `estimate_expand_degree()`. It counts the edges and the vertices of the graph, which may scan
+/// the graph in some storage, e.g., groot, thus it is meant to be called off the job building,
+/// e.g., in the background.
+pub fn collect_expand_degree<G: ReadGraph + ?Sized>(
+    graph: &G, expand: &physical_pb::EdgeExpand,
+) -> GraphProxyResult<f64> {
+    let key = expand_degree_key(expand)?;
+    let degree = avg_degree_of(graph, &expand.params, expand.direction)?;
+    EXPAND_DEGREES
+        .write()
+        .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?
+        .insert(key, (Instant::now(), degree));
+    Ok(degree)
+}
+
+/// Estimate the cost of each operator of the logical plan, of which the nodes must be in a
+/// topological order, i.e., a node is always placed after its parents.
+pub(crate) fn explain_plan<G: ReadGraph + ?Sized>(
+    graph: &G, logical_plan: algebra_pb::LogicalPlan,
+) -> GraphProxyResult<QueryPlan> {
+    use algebra_pb::logical_plan::operator::Opr;
+
+    let num_nodes = logical_plan.nodes.len();
+    let mut parents: Vec<Vec<usize>> = vec![vec![]; num_nodes];
+    for (idx, node) in logical_plan.nodes.iter().enumerate() {
+        for child in &node.children {
+            let child = *child as usize;
+            if child <= idx || child >= num_nodes {
+                return Err(to_graph_proxy_error(format!("child {} of node {}", child, idx)));
+            }
+            parents[child].push(idx);
+        }
+    }
+
+    let mut costs: Vec<OperatorCost> = Vec::with_capacity(num_nodes);
+    for (idx, node) in logical_plan.nodes.iter().enumerate() {
+        let input_rows: f64 = parents[idx]
+            .iter()
+            .map(|parent| costs[*parent].estimated_rows)
+            .sum();
+        let opr = node
+            .opr
+            .as_ref()
+            .and_then(|opr| opr.opr.as_ref());
+        // (the estimated output rows, the estimated rows read from the storage)
+        let (rows, io_rows) = match opr {
+            Some(Opr::Scan(scan)) => {
+                let rows = if let Some(index) = scan.idx_predicate.as_ref() {
+                    // each branch of the index predicate locates at most one row
+                    index.or_predicates.len() as f64
+                } else {
+                    let params = labels_only(&scan.params)?;
+                    let count = if scan.scan_opt == algebra_pb::scan::ScanOpt::Edge as i32 {
+                        graph.count_edge(&params)?
+                    } else {
+                        graph.count_vertex(&params)?
+                    };
+                    count as f64 * selectivity(&scan.params)
+                };
+                let rows = apply_limit(rows, &scan.params);
+                (rows, rows)
+            }
+            Some(Opr::Edge(expand)) => {
+                let rows = input_rows * avg_degree(graph, expand)?;
+                (apply_limit(rows * selectivity(&expand.params), &expand.params), rows)
+            }
+            Some(Opr::Vertex(getv)) => (input_rows * selectivity(&getv.params), input_rows),
+            Some(Opr::Path(path)) => {
+                let degree = match path
+                    .base
+                    .as_ref()
+                    .and_then(|base| base.edge_expand.as_ref())
+                {
+                    Some(expand) => avg_degree(graph, expand)?,
+                    None => 1.0,
+                };
+                let (lower, upper) = path
+                    .hop_range
+                    .as_ref()
+                    .map(|range| (range.lower.max(0), range.upper.max(0)))
+                    .unwrap_or((1, 2));
+                let paths: f64 = (lower..upper)
+                    .map(|hops| degree.powi(hops))
+                    .sum();
+                let rows = input_rows * paths;
+                (rows, rows)
+            }
+            Some(Opr::Select(_)) => (input_rows * DEFAULT_SELECTIVITY, 0.0),
+            Some(Opr::Limit(limit)) => {
+                let rows = match limit.range.as_ref() {
+                    Some(range) => input_rows.min((range.upper - range.lower).max(0) as f64),
+                    None => input_rows,
+                };
+                (rows, 0.0)
+            }
+            Some(Opr::GroupBy(group)) if group.mappings.is_empty() => (input_rows.min(1.0), 0.0),
+            _ => (input_rows, 0.0),
+        };
+        costs.push(OperatorCost {
+            node_id: idx,
+            estimated_rows: rows,
+            estimated_cpu_cost: input_rows + rows,
+            estimated_io_cost: io_rows,
+        });
+    }
+
+    let physical_plan = build_physical_plan(&logical_plan)?;
+    Ok(QueryPlan { logical_plan, physical_plan, costs })
+}
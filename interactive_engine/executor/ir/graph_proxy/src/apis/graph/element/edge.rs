@@ -30,6 +30,7 @@ use pegasus_common::impl_as_any;
 
 use crate::apis::{read_id, write_id, Details, DynDetails, Element, GraphElement, PropertyValue, ID};
 use crate::utils::expr::eval::Context;
+use crate::GraphProxyResult;
 
 #[derive(Clone, Debug, Default)]
 pub struct Edge {
@@ -73,6 +74,9 @@ impl GraphElement for Edge {
     fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
         self.details.get_all_properties()
     }
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        self.details.try_get_property(key)
+    }
 }
 
 impl Edge {
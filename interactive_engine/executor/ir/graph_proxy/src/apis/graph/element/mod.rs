@@ -18,10 +18,11 @@ use dyn_type::{BorrowObject, Object};
 pub use edge::Edge;
 use ir_common::{LabelId, NameOrId};
 pub use path::{GraphPath, VertexOrEdge};
-pub use property::{Details, DynDetails, PropKey, PropertyValue};
+pub use property::{Details, DynDetails, EagerDetails, MergeStrategy, PropKey, PropertyValue};
 pub use vertex::Vertex;
 
 use crate::apis::ID;
+use crate::GraphProxyResult;
 
 mod edge;
 mod path;
@@ -48,6 +49,10 @@ pub trait GraphElement: Element {
     fn label(&self) -> Option<LabelId>;
     fn get_property(&self, key: &NameOrId) -> Option<PropertyValue>;
     fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>>;
+    /// Get a property with given key as `Details::try_get_property()`
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        Ok(self.get_property(key))
+    }
 }
 
 impl Element for () {
@@ -30,6 +30,7 @@ use pegasus_common::impl_as_any;
 
 use crate::apis::{Edge, Element, GraphElement, PropertyValue, Vertex, ID};
 use crate::utils::expr::eval::Context;
+use crate::GraphProxyResult;
 
 #[derive(Clone, Debug, Hash, PartialEq, PartialOrd)]
 pub enum VertexOrEdge {
@@ -98,7 +99,9 @@ impl GraphPath {
         entry: E, path_opt: pb::path_expand::PathOpt, result_opt: pb::path_expand::ResultOpt,
     ) -> Result<Self, ParsePbError> {
         if let pb::path_expand::PathOpt::AnyShortest | pb::path_expand::PathOpt::AllShortest = path_opt {
-            return Err(ParsePbError::Unsupported("unsupported path type of shortest path".to_string()));
+            // the start of a shortest path is always preserved, as the visited vertices are tracked
+            // per start vertex, while the path is reduced to its end vertex after the expansion if `EndV`
+            return Ok(GraphPath::SimpleAllPath(vec![entry.into()]));
         }
         let path = match result_opt {
             pb::path_expand::ResultOpt::EndV => match path_opt {
@@ -202,6 +205,30 @@ impl GraphPath {
         }
     }
 
+    /// Get the vertex at the given position of the path, where a negative position counts from the end,
+    /// e.g., 0 for the head and -1 for the last vertex.
+    /// Notice that only the end vertex can be found if the path preserves only its end vertex.
+    pub fn get_vertex(&self, index: i32) -> Option<&Vertex> {
+        let num_vertices = self.len() as i32 + 1;
+        let index = if index < 0 { num_vertices + index } else { index };
+        if index < 0 || index >= num_vertices {
+            return None;
+        }
+        match self {
+            GraphPath::AllPath(p) | GraphPath::SimpleAllPath(p) | GraphPath::TrailAllPath(p) => p
+                .iter()
+                .filter_map(|v_or_e| v_or_e.as_vertex())
+                .nth(index as usize),
+            GraphPath::EndV((e, _)) | GraphPath::SimpleEndV((e, _, _)) => {
+                if index == num_vertices - 1 {
+                    e.as_vertex()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
     pub fn get_path(&self) -> Option<&Vec<VertexOrEdge>> {
         match self {
             GraphPath::AllPath(p) | GraphPath::SimpleAllPath(p) | GraphPath::TrailAllPath(p) => Some(p),
@@ -340,6 +367,13 @@ impl GraphElement for VertexOrEdge {
             VertexOrEdge::E(e) => e.get_all_properties(),
         }
     }
+
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        match self {
+            VertexOrEdge::V(v) => v.try_get_property(key),
+            VertexOrEdge::E(e) => e.try_get_property(key),
+        }
+    }
 }
 
 impl Context<VertexOrEdge> for VertexOrEdge {
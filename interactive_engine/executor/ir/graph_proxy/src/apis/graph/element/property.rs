@@ -28,6 +28,7 @@ use pegasus_common::impl_as_any;
 
 use super::Element;
 use crate::utils::expr::{ExprEvalError, ExprEvalResult};
+use crate::{GraphProxyError, GraphProxyResult};
 
 /// The three types of property to get
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -70,7 +71,10 @@ impl PropKey {
                     })
                     .unwrap_or(Object::None),
                 PropKey::Key(key) => {
-                    if let Some(prop_val) = graph_element.get_property(key) {
+                    let prop_val = graph_element
+                        .try_get_property(key)
+                        .map_err(|e| ExprEvalError::OtherErr(e.to_string()))?;
+                    if let Some(prop_val) = prop_val {
                         prop_val.try_to_owned().ok_or_else(|| {
                             ExprEvalError::OtherErr("cannot get `Object` from `BorrowObject`".to_string())
                         })?
@@ -99,6 +103,10 @@ impl TryFrom<pb::Property> for PropKey {
                 Item::Len(_) => Ok(PropKey::Len),
                 Item::All(_) => Ok(PropKey::All),
                 Item::Key(k) => Ok(PropKey::Key(NameOrId::try_from(k)?)),
+                Item::PathElem(path_elem) => Err(ParsePbError::Unsupported(format!(
+                    "path element key {:?} in a property",
+                    path_elem
+                ))),
             }
         } else {
             Err(ParsePbError::from("empty content provided"))
@@ -178,6 +186,13 @@ pub trait Details: std::fmt::Debug + Send + Sync + AsAny {
     /// Get a property with given key
     fn get_property(&self, key: &NameOrId) -> Option<PropertyValue>;
 
+    /// Get a property with given key, where the property that is absent from the element is told apart from
+    /// the one that is not fetched, i.e., `GraphProxyError::PropertyNotFetched`, e.g., as the columns
+    /// pushed down to the storage are trimmed. By default, a property not in the details is regarded as absent.
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        Ok(self.get_property(key))
+    }
+
     /// get_all_properties returns all properties. None means that we failed in getting the properties.
     fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>>;
 
@@ -200,6 +215,18 @@ pub enum DynDetails {
     Lazy(Arc<dyn Details>),
 }
 
+/// The strategy to resolve the conflicts, i.e., the same property key with different values,
+/// when merging the properties of a graph element fetched from different sources.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the value that is already present
+    TakeFirst,
+    /// Overwrite with the value being merged
+    TakeLast,
+    /// Fail the merge
+    Error,
+}
+
 impl Default for DynDetails {
     fn default() -> Self {
         DynDetails::Empty
@@ -214,6 +241,71 @@ impl DynDetails {
     pub fn lazy<P: Details + 'static>(p: P) -> Self {
         DynDetails::Lazy(Arc::new(p))
     }
+
+    /// Fetch all the properties of the details into a local `HashMap`,
+    /// so that the following property accesses no longer go to the storage.
+    pub fn into_eager(&self) -> GraphProxyResult<EagerDetails> {
+        let props = match self {
+            DynDetails::Empty => HashMap::new(),
+            DynDetails::Default(default) => default.clone(),
+            DynDetails::Lazy(lazy) => lazy.get_all_properties().ok_or_else(|| {
+                GraphProxyError::store_permanent_error(
+                    "lazy details",
+                    "failed to get all properties from the storage",
+                )
+            })?,
+        };
+        Ok(EagerDetails::new(props))
+    }
+
+    /// Fetch the properties of the given `keys` of the lazy details into a local `HashMap`, so that
+    /// accessing them no longer goes to the storage, while the other properties are still accessed lazily.
+    /// The details that are not lazy are already local, and thus are fully materialized as `into_eager()`.
+    pub fn into_eager_with(&self, keys: &[NameOrId]) -> GraphProxyResult<EagerDetails> {
+        match self {
+            DynDetails::Lazy(lazy) => {
+                let mut props = HashMap::with_capacity(keys.len());
+                for key in keys {
+                    // the property failed to get is left to the lazy details, to be reported when accessed
+                    if let Ok(prop) = lazy.try_get_property(key) {
+                        props.insert(key.clone(), prop.and_then(|prop| prop.try_to_owned()));
+                    }
+                }
+                Ok(EagerDetails { props, lazy: Some(lazy.clone()) })
+            }
+            _ => self.into_eager(),
+        }
+    }
+
+    /// Merge the properties of `other` into `self`, where the conflicts are resolved by `strategy`.
+    /// After merging, `self` turns into `DynDetails::Default` unless both are empty.
+    pub fn merge(&mut self, other: &DynDetails, strategy: MergeStrategy) -> GraphProxyResult<()> {
+        if let DynDetails::Empty = other {
+            return Ok(());
+        }
+        let other_props = other.into_eager()?.into_props();
+        let mut props = self.into_eager()?.into_props();
+        for (key, value) in other_props {
+            match props.get(&key) {
+                Some(existing) if *existing != value => match strategy {
+                    MergeStrategy::TakeFirst => {}
+                    MergeStrategy::TakeLast => {
+                        props.insert(key, value);
+                    }
+                    MergeStrategy::Error => Err(GraphProxyError::internal_error(&format!(
+                        "conflict values of property {:?} to merge: {:?} vs {:?}",
+                        key, existing, value
+                    )))?,
+                },
+                Some(_) => {}
+                None => {
+                    props.insert(key, value);
+                }
+            }
+        }
+        *self = DynDetails::Default(props);
+        Ok(())
+    }
 }
 
 impl_as_any!(DynDetails);
@@ -229,6 +321,13 @@ impl Details for DynDetails {
         }
     }
 
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        match self {
+            DynDetails::Lazy(lazy) => lazy.try_get_property(key),
+            _ => Ok(self.get_property(key)),
+        }
+    }
+
     fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
         match self {
             DynDetails::Empty => None,
@@ -246,6 +345,122 @@ impl Details for DynDetails {
     }
 }
 
+/// The properties of a graph element shuffled from another worker, where the properties other than the
+/// required ones are trimmed. Thus, accessing a trimmed property is `GraphProxyError::PropertyNotFetched`
+/// by `try_get_property()`, rather than regarded as absent.
+#[derive(Clone, Debug, Default)]
+pub struct TrimmedDetails {
+    props: HashMap<NameOrId, Object>,
+}
+
+impl TrimmedDetails {
+    pub fn new(props: HashMap<NameOrId, Object>) -> Self {
+        TrimmedDetails { props }
+    }
+}
+
+impl_as_any!(TrimmedDetails);
+
+impl Details for TrimmedDetails {
+    fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
+        self.props
+            .get(key)
+            .map(|o| PropertyValue::Borrowed(o.as_borrow()))
+    }
+
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        if self.props.contains_key(key) {
+            Ok(self.get_property(key))
+        } else {
+            Err(GraphProxyError::property_not_fetched(key, "which is trimmed before shuffled"))
+        }
+    }
+
+    fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
+        Some(self.props.clone())
+    }
+
+    fn get_property_keys(&self) -> Option<Vec<NameOrId>> {
+        // the required properties, to be shuffled again as trimmed
+        Some(self.props.keys().cloned().collect())
+    }
+}
+
+/// The properties of a graph element materialized in a local `HashMap`, where a property of `None`
+/// is absent from the element. The properties not materialized are accessed in the `lazy` details,
+/// if the element is partially materialized, or else they are absent too.
+#[derive(Clone, Debug, Default)]
+pub struct EagerDetails {
+    props: HashMap<NameOrId, Option<Object>>,
+    lazy: Option<Arc<dyn Details>>,
+}
+
+impl EagerDetails {
+    pub fn new(props: HashMap<NameOrId, Object>) -> Self {
+        let props = props
+            .into_iter()
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        EagerDetails { props, lazy: None }
+    }
+
+    /// The materialized properties, leaving out the absent ones
+    pub fn into_props(self) -> HashMap<NameOrId, Object> {
+        self.props
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .collect()
+    }
+}
+
+impl_as_any!(EagerDetails);
+
+impl Details for EagerDetails {
+    fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
+        match self.props.get(key) {
+            Some(value) => value
+                .as_ref()
+                .map(|o| PropertyValue::Borrowed(o.as_borrow())),
+            None => self
+                .lazy
+                .as_ref()
+                .and_then(|lazy| lazy.get_property(key)),
+        }
+    }
+
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        match (self.props.contains_key(key), self.lazy.as_ref()) {
+            (false, Some(lazy)) => lazy.try_get_property(key),
+            _ => Ok(self.get_property(key)),
+        }
+    }
+
+    fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
+        match self.lazy.as_ref() {
+            Some(lazy) => lazy.get_all_properties(),
+            None => Some(self.clone().into_props()),
+        }
+    }
+
+    fn get_property_keys(&self) -> Option<Vec<NameOrId>> {
+        match self.lazy.as_ref() {
+            Some(lazy) => lazy.get_property_keys(),
+            // all the properties are materialized
+            None => Some(vec![]),
+        }
+    }
+}
+
+impl From<EagerDetails> for DynDetails {
+    fn from(eager: EagerDetails) -> Self {
+        if eager.lazy.is_some() {
+            DynDetails::lazy(eager)
+        } else {
+            DynDetails::Default(eager.into_props())
+        }
+    }
+}
+
 impl Encode for DynDetails {
     fn write_to<W: WriteExt>(&self, writer: &mut W) -> io::Result<()> {
         match self {
@@ -279,15 +494,26 @@ impl Encode for DynDetails {
                             }
                         }
                     } else {
-                        // If some specific properties required, try to get these properties; and hint as Default DynDetails
-                        writer.write_u8(1)?;
-                        writer.write_u64(prop_keys.len() as u64)?;
-                        for k in prop_keys {
-                            let v = self
-                                .get_property(&k)
-                                .map(|prop| prop.try_to_owned())
-                                .unwrap_or(None)
-                                .unwrap_or(Object::None);
+                        // If some specific properties required, try to get these properties; and hint as
+                        // TrimmedDetails, where the ones failed to get are left out to be reported when accessed
+                        let props: Vec<(NameOrId, Object)> = prop_keys
+                            .into_iter()
+                            .filter_map(|k| match self.try_get_property(&k) {
+                                Ok(prop) => {
+                                    let v = prop
+                                        .and_then(|prop| prop.try_to_owned())
+                                        .unwrap_or(Object::None);
+                                    Some((k, v))
+                                }
+                                Err(e) => {
+                                    warn!("{}", e);
+                                    None
+                                }
+                            })
+                            .collect();
+                        writer.write_u8(2)?;
+                        writer.write_u64(props.len() as u64)?;
+                        for (k, v) in props {
                             k.write_to(writer)?;
                             v.write_to(writer)?;
                         }
@@ -308,8 +534,9 @@ impl Decode for DynDetails {
         if kind == 0 {
             // Empty DynDetails
             Ok(DynDetails::default())
-        } else if kind == 1 {
-            // For either DefaultDetails or LazyDetails(with required details), we decoded as DefaultDetails
+        } else if kind == 1 || kind == 2 {
+            // For either DefaultDetails or LazyDetails(with all details), we decoded as DefaultDetails,
+            // and for LazyDetails(with required details), we decoded as TrimmedDetails
             let len = reader.read_u64()?;
             let mut map = HashMap::with_capacity(len as usize);
             for _i in 0..len {
@@ -317,7 +544,11 @@ impl Decode for DynDetails {
                 let v = <Object>::read_from(reader)?;
                 map.insert(k, v);
             }
-            Ok(DynDetails::Default(map))
+            if kind == 1 {
+                Ok(DynDetails::Default(map))
+            } else {
+                Ok(DynDetails::lazy(TrimmedDetails::new(map)))
+            }
         } else {
             Err(io::Error::from(io::ErrorKind::Other))
         }
@@ -29,10 +29,11 @@ use pegasus_common::downcast::*;
 use pegasus_common::impl_as_any;
 
 use crate::apis::{
-    get_graph, read_id, write_id, Details, DynDetails, Element, GraphElement, PropertyValue, QueryParams,
-    ID,
+    get_graph, read_id, write_id, Details, DynDetails, Element, GraphElement, MergeStrategy, PropertyValue,
+    QueryParams, ID,
 };
 use crate::utils::expr::eval::Context;
+use crate::{GraphProxyError, GraphProxyResult};
 
 #[derive(Clone, Debug, Default)]
 pub struct Vertex {
@@ -53,6 +54,18 @@ impl Vertex {
     pub fn get_details(&self) -> &DynDetails {
         &self.details
     }
+
+    /// Merge the properties of `other`, e.g., the partial properties of the same vertex fetched
+    /// from another partition, into `self`, where the conflicts are resolved by `strategy`.
+    pub fn merge_properties(&mut self, other: &Vertex, strategy: MergeStrategy) -> GraphProxyResult<()> {
+        if self.id != other.id {
+            Err(GraphProxyError::internal_error(&format!(
+                "cannot merge properties of vertex {} into vertex {}",
+                other.id, self.id
+            )))?
+        }
+        self.details.merge(&other.details, strategy)
+    }
 }
 
 impl Element for Vertex {
@@ -128,6 +141,14 @@ impl GraphElement for Vertex {
             DynDetails::Default(_) | DynDetails::Lazy(_) => self.details.get_all_properties(),
         }
     }
+
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        match self.details {
+            // the lazy details tell the property not fetched apart from the absent one
+            DynDetails::Lazy(_) => self.details.try_get_property(key),
+            DynDetails::Empty | DynDetails::Default(_) => Ok(self.get_property(key)),
+        }
+    }
 }
 
 impl_as_any!(Vertex);
@@ -202,3 +223,80 @@ impl TryFrom<result_pb::Vertex> for Vertex {
         Ok(vertex)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertex_with_props(props: Vec<(&str, Object)>) -> Vertex {
+        let props = props
+            .into_iter()
+            .map(|(key, value)| (NameOrId::from(key.to_string()), value))
+            .collect();
+        Vertex::new(1, Some(0), DynDetails::new(props))
+    }
+
+    fn get_prop(vertex: &Vertex, key: &str) -> Option<Object> {
+        vertex
+            .get_details()
+            .get_property(&NameOrId::from(key.to_string()))
+            .and_then(|prop| prop.try_to_owned())
+    }
+
+    #[test]
+    fn merge_disjoint_properties() {
+        let mut v = vertex_with_props(vec![("name", "marko".into())]);
+        let other = vertex_with_props(vec![("age", 29.into())]);
+        v.merge_properties(&other, MergeStrategy::Error)
+            .unwrap();
+        assert_eq!(get_prop(&v, "name"), Some("marko".into()));
+        assert_eq!(get_prop(&v, "age"), Some(29.into()));
+
+        // merging into a vertex without details
+        let mut v = Vertex::new(1, Some(0), DynDetails::Empty);
+        v.merge_properties(&other, MergeStrategy::Error)
+            .unwrap();
+        assert_eq!(get_prop(&v, "age"), Some(29.into()));
+    }
+
+    #[test]
+    fn merge_properties_take_first() {
+        let mut v = vertex_with_props(vec![("name", "marko".into())]);
+        let other = vertex_with_props(vec![("name", "vadas".into()), ("age", 29.into())]);
+        v.merge_properties(&other, MergeStrategy::TakeFirst)
+            .unwrap();
+        assert_eq!(get_prop(&v, "name"), Some("marko".into()));
+        assert_eq!(get_prop(&v, "age"), Some(29.into()));
+    }
+
+    #[test]
+    fn merge_properties_take_last() {
+        let mut v = vertex_with_props(vec![("name", "marko".into())]);
+        let other = vertex_with_props(vec![("name", "vadas".into()), ("age", 29.into())]);
+        v.merge_properties(&other, MergeStrategy::TakeLast)
+            .unwrap();
+        assert_eq!(get_prop(&v, "name"), Some("vadas".into()));
+        assert_eq!(get_prop(&v, "age"), Some(29.into()));
+    }
+
+    #[test]
+    fn merge_properties_error() {
+        // the same value is not regarded as a conflict
+        let mut v = vertex_with_props(vec![("name", "marko".into())]);
+        let other = vertex_with_props(vec![("name", "marko".into()), ("age", 29.into())]);
+        v.merge_properties(&other, MergeStrategy::Error)
+            .unwrap();
+        assert_eq!(get_prop(&v, "age"), Some(29.into()));
+
+        let conflict = vertex_with_props(vec![("name", "vadas".into())]);
+        assert!(v
+            .merge_properties(&conflict, MergeStrategy::Error)
+            .is_err());
+        assert_eq!(get_prop(&v, "name"), Some("marko".into()));
+
+        let another = Vertex::new(2, Some(0), DynDetails::Empty);
+        assert!(v
+            .merge_properties(&another, MergeStrategy::TakeLast)
+            .is_err());
+    }
+}
@@ -16,6 +16,8 @@
 use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::io;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use dyn_type::Object;
@@ -27,6 +29,7 @@ use ir_common::{LabelId, NameOrId, OneOrMany};
 use pegasus::codec::{ReadExt, WriteExt};
 
 use crate::utils::expr::eval_pred::PEvaluator;
+use crate::{GraphProxyError, GraphProxyResult};
 
 pub mod element;
 pub type ID = i64;
@@ -41,6 +44,9 @@ pub fn write_id<W: WriteExt>(writer: &mut W, id: ID) -> io::Result<()> {
     writer.write_i64(id)
 }
 
+/// The id of a property in the storage
+pub type PropId = u32;
+
 /// The number of bits in an `ID`
 pub const ID_BITS: usize = std::mem::size_of::<ID>() * 8;
 
@@ -67,6 +73,77 @@ impl From<pb::edge_expand::Direction> for Direction {
     }
 }
 
+/// The extra params interpreted by graph_proxy itself are namespaced by this prefix,
+/// so as not to collide with the storage-specific ones, e.g., "SID" given by the compiler.
+pub const EXTRA_PARAM_PREFIX: &str = "gs.";
+/// The seed of the random number generator for sampling in scan, as an `i64`
+pub const SAMPLE_SEED_KEY: &str = "gs.sample_seed";
+/// The expected labels of the end vertices of an expansion, as comma-separated label ids
+pub const END_V_LABELS_KEY: &str = "gs.end_v_labels";
+/// Whether the queried vertices are optional, as a `bool`, i.e., a vertex absent from the graph
+/// is regarded as a null instead of being filtered out, as the outer semantics of optional expand.
+pub const OPTIONAL_KEY: &str = "gs.optional";
+/// The read consistency of the query, as "strict" or "best_effort", see `ReadConsistency`
+pub const READ_CONSISTENCY_KEY: &str = "gs.read_consistency";
+/// The number of the adjacent edges to sample per source vertex in an expansion, as a positive `usize`
+pub const PER_VERTEX_SAMPLE_KEY: &str = "gs.per_vertex_sample";
+/// The number of the source ids whose expanded results are memoized in an expansion, as a `usize`,
+/// e.g., for the same vertices expanded repeatedly across the iterations of a BFS. See `memoize_fn()`.
+pub const MEMOIZE_SIZE_KEY: &str = "gs.memoize_size";
+/// Whether to skip the partitions whose store calls fail in a scan, as a `bool`, i.e., to accept
+/// the partial results of the other partitions, e.g., for the best-effort analytics.
+pub const SKIP_FAILED_PARTITIONS_KEY: &str = "gs.skip_failed_partitions";
+/// The name of the worker's counter of the failed partitions that are skipped by the scans,
+/// with `SKIP_FAILED_PARTITIONS_KEY` on
+pub const SKIPPED_PARTITIONS_METRIC: &str = "gs.skipped_partitions";
+/// Whether the unknown extra params with the prefix of `EXTRA_PARAM_PREFIX` are rejected
+/// when the query params are parsed from pb, see `QueryParams::validate_extra_params()`.
+static STRICT_EXTRA_PARAMS: AtomicBool = AtomicBool::new(false);
+
+/// Reject the unknown extra params with the prefix of `EXTRA_PARAM_PREFIX` if `strict`,
+/// which is expected to be set once when the server starts.
+pub fn set_strict_extra_params(strict: bool) {
+    STRICT_EXTRA_PARAMS.store(strict, Ordering::Relaxed);
+}
+
+/// All the known extra params with the prefix of `EXTRA_PARAM_PREFIX`
+const KNOWN_EXTRA_PARAMS: &[&str] = &[
+    SAMPLE_SEED_KEY,
+    END_V_LABELS_KEY,
+    OPTIONAL_KEY,
+    READ_CONSISTENCY_KEY,
+    PER_VERTEX_SAMPLE_KEY,
+    MEMOIZE_SIZE_KEY,
+    SKIP_FAILED_PARTITIONS_KEY,
+];
+
+/// What to do if the snapshot to query is no longer available in the storage, e.g., compacted away.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ReadConsistency {
+    /// Fail the query, e.g., for the read-your-writes clients
+    Strict,
+    /// Query whatever snapshot the storage serves instead, e.g., the oldest available one
+    BestEffort,
+}
+
+impl Default for ReadConsistency {
+    fn default() -> Self {
+        ReadConsistency::BestEffort
+    }
+}
+
+impl FromStr for ReadConsistency {
+    type Err = ParsePbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(ReadConsistency::Strict),
+            "best_effort" => Ok(ReadConsistency::BestEffort),
+            _ => Err(ParsePbError::ParseError(format!("unknown read consistency {:?}", s))),
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct QueryParams {
     pub labels: Vec<LabelId>,
@@ -75,6 +152,24 @@ pub struct QueryParams {
     pub filter: Option<Arc<PEvaluator>>,
     pub sample_ratio: Option<f64>,
     pub extra_params: Option<HashMap<String, String>>,
+    // the ids of the props to dedup the scanned results by in storage, which matters when
+    // the scanned partitions may overlap. None indicates no dedup.
+    pub dedup_props: Option<Vec<PropId>>,
+    // the expected labels of the other end vertices in an expansion, e.g., the `dst_label`
+    // of the (src_label, edge_label, dst_label) triplet in an out-expansion. None indicates any label.
+    pub end_v_labels: Option<Vec<LabelId>>,
+    // the property values that the scanned vertices are restricted to by the equalities of `filter`,
+    // with which the storage may prune the partitions to scan, if they cover the primary key of a label.
+    // It is only a hint, and the restriction itself is still enforced by `filter`. None indicates no such hint.
+    pub primary_keys: Option<Vec<PKV>>,
+    // the properties that a scan is immediately projected to, with which the storage may drop
+    // the other properties right after fetching the scanned vertices. The projection applies after
+    // `filter`, as the filter may refer to the other properties. None indicates no projection.
+    pub projection: Option<Vec<NameOrId>>,
+    // the number of the adjacent edges to sample per source vertex in an expansion, e.g., for sampling
+    // the neighbors in a GNN, which differs from `sample_ratio` over all the expanded edges.
+    // None indicates no such sampling.
+    pub per_vertex_sample: Option<usize>,
 }
 
 impl TryFrom<Option<algebra_pb::QueryParams>> for QueryParams {
@@ -88,6 +183,9 @@ impl TryFrom<Option<algebra_pb::QueryParams>> for QueryParams {
                 .with_limit(query_params_pb.limit)?
                 .with_sample_ratio(query_params_pb.sample_ratio)?
                 .with_extra_params(query_params_pb.extra)?;
+            query_param
+                .validate_extra_params(STRICT_EXTRA_PARAMS.load(Ordering::Relaxed))
+                .map_err(|err| ParsePbError::ParseError(err.to_string()))?;
             if query_params_pb.is_all_columns {
                 query_param.with_all_columns()
             } else {
@@ -163,6 +261,30 @@ impl QueryParams {
 
     // Extra query params for different storages
     fn with_extra_params(mut self, extra_params_pb: HashMap<String, String>) -> Result<Self, ParsePbError> {
+        if let Some(labels) = extra_params_pb.get(END_V_LABELS_KEY) {
+            self.end_v_labels = Some(
+                labels
+                    .split(',')
+                    .map(|label| {
+                        label.trim().parse::<LabelId>().map_err(|_| {
+                            ParsePbError::ParseError(format!(
+                                "invalid label {:?} in extra param {}",
+                                label, END_V_LABELS_KEY
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+        if let Some(size) = extra_params_pb.get(PER_VERTEX_SAMPLE_KEY) {
+            match size.trim().parse::<usize>() {
+                Ok(size) if size > 0 => self.per_vertex_sample = Some(size),
+                _ => Err(ParsePbError::ParseError(format!(
+                    "invalid sample size {:?} in extra param {}",
+                    size, PER_VERTEX_SAMPLE_KEY
+                )))?,
+            }
+        }
         if !extra_params_pb.is_empty() {
             self.extra_params = Some(extra_params_pb);
         }
@@ -177,6 +299,76 @@ impl QueryParams {
         }
     }
 
+    pub fn get_extra_i64(&self, key: &str) -> GraphProxyResult<Option<i64>> {
+        self.parse_extra_param(key)
+    }
+
+    pub fn get_extra_bool(&self, key: &str) -> GraphProxyResult<Option<bool>> {
+        self.parse_extra_param(key)
+    }
+
+    pub fn get_extra_f64(&self, key: &str) -> GraphProxyResult<Option<f64>> {
+        self.parse_extra_param(key)
+    }
+
+    // A present but malformed value is an error, instead of being regarded as absent.
+    fn parse_extra_param<T: FromStr>(&self, key: &str) -> GraphProxyResult<Option<T>> {
+        self.get_extra_param(key)
+            .map(|value| {
+                value.parse::<T>().map_err(|_| {
+                    GraphProxyError::invalid_extra_param(
+                        key,
+                        &format!("cannot parse {:?} as {}", value, std::any::type_name::<T>()),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    pub fn get_sample_seed(&self) -> GraphProxyResult<Option<u64>> {
+        Ok(self
+            .get_extra_i64(SAMPLE_SEED_KEY)?
+            .map(|seed| seed as u64))
+    }
+
+    pub fn is_optional(&self) -> GraphProxyResult<bool> {
+        Ok(self
+            .get_extra_bool(OPTIONAL_KEY)?
+            .unwrap_or(false))
+    }
+
+    /// The number of the source ids to memoize the expanded results of, if the memoization is opted in.
+    pub fn get_memoize_size(&self) -> GraphProxyResult<Option<usize>> {
+        self.parse_extra_param(MEMOIZE_SIZE_KEY)
+    }
+
+    pub fn skip_failed_partitions(&self) -> GraphProxyResult<bool> {
+        Ok(self
+            .get_extra_bool(SKIP_FAILED_PARTITIONS_KEY)?
+            .unwrap_or(false))
+    }
+
+    pub fn get_read_consistency(&self) -> GraphProxyResult<ReadConsistency> {
+        Ok(self
+            .parse_extra_param(READ_CONSISTENCY_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// Check that all the extra params with the prefix of `EXTRA_PARAM_PREFIX` are known if `strict`.
+    /// The other extra params are left to the storages.
+    pub fn validate_extra_params(&self, strict: bool) -> GraphProxyResult<()> {
+        if strict {
+            if let Some(ref extra_params) = self.extra_params {
+                for key in extra_params.keys() {
+                    if key.starts_with(EXTRA_PARAM_PREFIX) && !KNOWN_EXTRA_PARAMS.contains(&key.as_str()) {
+                        Err(GraphProxyError::invalid_extra_param(key, "unknown key"))?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn has_labels(&self) -> bool {
         !self.labels.is_empty()
     }
@@ -188,4 +380,169 @@ impl QueryParams {
     pub fn has_columns(&self) -> bool {
         self.columns.is_some()
     }
+
+    /// Cap the count of the queried elements at `limit`, if any.
+    pub fn limit_count(&self, count: u64) -> u64 {
+        self.limit
+            .map_or(count, |limit| count.min(limit as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params_with_extra(extra: Vec<(&str, &str)>) -> QueryParams {
+        let mut params = QueryParams::default();
+        params.extra_params = Some(
+            extra
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        );
+        params
+    }
+
+    #[test]
+    fn typed_extra_params() {
+        let params = params_with_extra(vec![("SID", "10"), ("gs.flag", "true"), ("gs.ratio", "0.5")]);
+        assert_eq!(params.get_extra_i64("SID").unwrap(), Some(10));
+        assert_eq!(params.get_extra_bool("gs.flag").unwrap(), Some(true));
+        assert_eq!(params.get_extra_f64("gs.ratio").unwrap(), Some(0.5));
+        assert_eq!(params.get_extra_i64("absent").unwrap(), None);
+        assert_eq!(
+            QueryParams::default()
+                .get_extra_bool("gs.flag")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn malformed_extra_params() {
+        let params = params_with_extra(vec![("SID", "latest"), ("gs.flag", "1"), ("gs.ratio", "half")]);
+        assert!(matches!(params.get_extra_i64("SID"), Err(GraphProxyError::InvalidExtraParam { .. })));
+        assert!(params.get_extra_bool("gs.flag").is_err());
+        assert!(params.get_extra_f64("gs.ratio").is_err());
+
+        let params = params_with_extra(vec![(SAMPLE_SEED_KEY, "")]);
+        assert!(params.get_sample_seed().is_err());
+        let params = params_with_extra(vec![(SAMPLE_SEED_KEY, "-1")]);
+        assert_eq!(params.get_sample_seed().unwrap(), Some(u64::MAX));
+    }
+
+    #[test]
+    fn read_consistency_from_extra_params() {
+        assert_eq!(
+            QueryParams::default()
+                .get_read_consistency()
+                .unwrap(),
+            ReadConsistency::BestEffort
+        );
+        let params = params_with_extra(vec![(READ_CONSISTENCY_KEY, "strict")]);
+        assert_eq!(params.get_read_consistency().unwrap(), ReadConsistency::Strict);
+        assert!(params.validate_extra_params(true).is_ok());
+        let params = params_with_extra(vec![(READ_CONSISTENCY_KEY, "best_effort")]);
+        assert_eq!(params.get_read_consistency().unwrap(), ReadConsistency::BestEffort);
+        let params = params_with_extra(vec![(READ_CONSISTENCY_KEY, "eventual")]);
+        assert!(params.get_read_consistency().is_err());
+    }
+
+    #[test]
+    fn memoize_size_from_extra_params() {
+        assert_eq!(
+            QueryParams::default()
+                .get_memoize_size()
+                .unwrap(),
+            None
+        );
+        let params = params_with_extra(vec![(MEMOIZE_SIZE_KEY, "128")]);
+        assert_eq!(params.get_memoize_size().unwrap(), Some(128));
+        assert!(params.validate_extra_params(true).is_ok());
+        let params = params_with_extra(vec![(MEMOIZE_SIZE_KEY, "-1")]);
+        assert!(params.get_memoize_size().is_err());
+    }
+
+    #[test]
+    fn skip_failed_partitions_from_extra_params() {
+        assert!(!QueryParams::default()
+            .skip_failed_partitions()
+            .unwrap());
+        let params = params_with_extra(vec![(SKIP_FAILED_PARTITIONS_KEY, "true")]);
+        assert!(params.skip_failed_partitions().unwrap());
+        assert!(params.validate_extra_params(true).is_ok());
+        let params = params_with_extra(vec![(SKIP_FAILED_PARTITIONS_KEY, "yes")]);
+        assert!(params.skip_failed_partitions().is_err());
+    }
+
+    #[test]
+    fn validate_extra_params() {
+        let params = params_with_extra(vec![("SID", "10"), (SAMPLE_SEED_KEY, "1")]);
+        assert!(params.validate_extra_params(true).is_ok());
+
+        let params = params_with_extra(vec![("SID", "10"), ("gs.sample_sead", "1")]);
+        assert!(params.validate_extra_params(false).is_ok());
+        assert!(params.validate_extra_params(true).is_err());
+    }
+
+    #[test]
+    fn end_v_labels_from_pb() {
+        let query_params_pb = |extra: Vec<(&str, &str)>| algebra_pb::QueryParams {
+            tables: vec![],
+            columns: vec![],
+            is_all_columns: false,
+            limit: None,
+            predicate: None,
+            sample_ratio: 1.0,
+            extra: extra
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+        let params =
+            QueryParams::try_from(Some(query_params_pb(vec![(END_V_LABELS_KEY, "1, 2")]))).unwrap();
+        assert_eq!(params.end_v_labels, Some(vec![1, 2]));
+        assert!(params.validate_extra_params(true).is_ok());
+
+        let params = QueryParams::try_from(Some(query_params_pb(vec![]))).unwrap();
+        assert_eq!(params.end_v_labels, None);
+
+        assert!(QueryParams::try_from(Some(query_params_pb(vec![(END_V_LABELS_KEY, "1,post")]))).is_err());
+
+        let params =
+            QueryParams::try_from(Some(query_params_pb(vec![(PER_VERTEX_SAMPLE_KEY, "10")]))).unwrap();
+        assert_eq!(params.per_vertex_sample, Some(10));
+        assert!(params.validate_extra_params(true).is_ok());
+        assert!(QueryParams::try_from(Some(query_params_pb(vec![(PER_VERTEX_SAMPLE_KEY, "0")]))).is_err());
+    }
+
+    #[test]
+    fn strict_extra_params_from_pb() {
+        let query_params_pb = |key: &str| algebra_pb::QueryParams {
+            sample_ratio: 1.0,
+            extra: vec![(key.to_string(), "1".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        // an unknown key is left to the storages unless strict
+        assert!(QueryParams::try_from(Some(query_params_pb("gs.sample_sead"))).is_ok());
+        set_strict_extra_params(true);
+        let unknown = QueryParams::try_from(Some(query_params_pb("gs.sample_sead")));
+        let known = QueryParams::try_from(Some(query_params_pb(SAMPLE_SEED_KEY)));
+        set_strict_extra_params(false);
+        assert!(unknown.is_err());
+        assert!(known.is_ok());
+    }
+
+    #[test]
+    fn optional_extra_param() {
+        assert!(!QueryParams::default().is_optional().unwrap());
+        let params = params_with_extra(vec![(OPTIONAL_KEY, "true")]);
+        assert!(params.is_optional().unwrap());
+        assert!(params.validate_extra_params(true).is_ok());
+        assert!(params_with_extra(vec![(OPTIONAL_KEY, "yes")])
+            .is_optional()
+            .is_err());
+    }
 }
@@ -14,16 +14,20 @@
 //! limitations under the License.
 
 pub mod cluster_info;
+pub mod explain;
 pub mod graph;
 pub mod partitioner;
 pub mod read_graph;
+pub mod timeout_graph;
 pub mod write_graph;
 
 pub use cluster_info::*;
+pub use explain::{register_physical_planner, OperatorCost, PhysicalPlanner, QueryPlan};
 pub use graph::element::{
-    Details, DynDetails, Edge, Element, GraphElement, GraphPath, PropKey, PropertyValue, Vertex,
-    VertexOrEdge,
+    Details, DynDetails, EagerDetails, Edge, Element, GraphElement, GraphPath, MergeStrategy, PropKey,
+    PropertyValue, Vertex, VertexOrEdge,
 };
-pub use graph::{read_id, write_id, Direction, QueryParams, ID};
-pub use read_graph::{from_fn, get_graph, register_graph, ReadGraph, Statement};
+pub use graph::{read_id, set_strict_extra_params, write_id, Direction, QueryParams, ReadConsistency, ID};
+pub use read_graph::{from_fn, get_graph, memoize_fn, register_graph, ReadGraph, Statement};
+pub use timeout_graph::TimeoutReadGraph;
 pub use write_graph::WriteGraphProxy;
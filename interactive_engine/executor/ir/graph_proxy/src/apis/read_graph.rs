@@ -13,14 +13,18 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+use ahash::{HashMap, HashSet};
 use ir_common::LabelId;
 
+use crate::apis::explain::{explain_plan, QueryPlan};
 use crate::apis::graph::PKV;
-use crate::apis::{Direction, Edge, QueryParams, Vertex, ID};
-use crate::GraphProxyResult;
+use crate::apis::partitioner::PartitionId;
+use crate::apis::{Direction, Edge, GraphElement, QueryParams, Vertex, ID};
+use crate::{filter_limit, limit_n, GraphProxyError, GraphProxyResult};
 
 /// The function for graph query
 pub trait Statement<I, O>: Send + 'static {
@@ -43,6 +47,162 @@ where
     Box::new(func) as Box<dyn Statement<I, O>>
 }
 
+/// Memoize the results of `func` for the last `n` distinct ids, with the least recently used one evicted,
+/// e.g., as the same vertex may be expanded repeatedly across the iterations of a BFS.
+/// The results of an id are cached only once they are all consumed, so that a consumer stopping early,
+/// e.g., under a limit, does not materialize the rest of them. The errors are never cached.
+pub fn memoize_fn<T, F>(n: usize, func: F) -> impl Statement<ID, T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(ID) -> GraphProxyResult<Box<dyn Iterator<Item = T> + Send>> + Send + 'static,
+{
+    MemoizedStatement { func, cache: Arc::new(Mutex::new(LruCache::new(n))) }
+}
+
+struct MemoizedStatement<T, F> {
+    func: F,
+    cache: Arc<Mutex<LruCache<T>>>,
+}
+
+impl<T, F> Statement<ID, T> for MemoizedStatement<T, F>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(ID) -> GraphProxyResult<Box<dyn Iterator<Item = T> + Send>> + Send + 'static,
+{
+    fn exec(&self, id: ID) -> GraphProxyResult<Box<dyn Iterator<Item = T> + Send>> {
+        let cached = self
+            .cache
+            .lock()
+            .map_err(|e| GraphProxyError::internal_error(&format!("memoize cache poisoned {}", e)))?
+            .get(id);
+        if let Some(results) = cached {
+            Ok(Box::new((0..results.len()).map(move |i| results[i].clone())))
+        } else {
+            let inner = (self.func)(id)?;
+            Ok(Box::new(CachingIter { id, inner, results: Some(vec![]), cache: self.cache.clone() }))
+        }
+    }
+}
+
+/// The iterator over the results of an id, which puts the results into the cache once they are exhausted
+struct CachingIter<T> {
+    id: ID,
+    inner: Box<dyn Iterator<Item = T> + Send>,
+    // None indicates that the results have been cached
+    results: Option<Vec<T>>,
+    cache: Arc<Mutex<LruCache<T>>>,
+}
+
+impl<T: Clone> Iterator for CachingIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self.inner.next() {
+            Some(item) => {
+                if let Some(results) = self.results.as_mut() {
+                    results.push(item.clone());
+                }
+                Some(item)
+            }
+            None => {
+                if let Some(results) = self.results.take() {
+                    // a poisoned cache is left as is, as caching is only an optimization
+                    if let Ok(mut cache) = self.cache.lock() {
+                        cache.put(self.id, Arc::new(results));
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+/// The cache of the results of the last `capacity` distinct ids, where the ids are ordered by
+/// the ticks when they are last accessed, and the one of the least tick is evicted first.
+struct LruCache<T> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<ID, (u64, Arc<Vec<T>>)>,
+    ticks: BTreeMap<u64, ID>,
+}
+
+impl<T> LruCache<T> {
+    fn new(capacity: usize) -> Self {
+        LruCache { capacity, tick: 0, entries: HashMap::default(), ticks: BTreeMap::new() }
+    }
+
+    fn get(&mut self, id: ID) -> Option<Arc<Vec<T>>> {
+        let tick = self.next_tick();
+        let (last_tick, results) = self.entries.get_mut(&id)?;
+        self.ticks.remove(last_tick);
+        self.ticks.insert(tick, id);
+        *last_tick = tick;
+        Some(results.clone())
+    }
+
+    fn put(&mut self, id: ID, results: Arc<Vec<T>>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.next_tick();
+        if let Some((last_tick, _)) = self.entries.insert(id, (tick, results)) {
+            self.ticks.remove(&last_tick);
+        }
+        self.ticks.insert(tick, id);
+        if self.entries.len() > self.capacity {
+            if let Some((&least_tick, &evicted)) = self.ticks.iter().next() {
+                self.ticks.remove(&least_tick);
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// Count the vertices of a partition as they are yielded, and add the count into the shared counts
+/// at once when the vertices are exhausted (or dropped), instead of locking the counts per vertex.
+struct PartitionCountIter<I> {
+    partition_id: PartitionId,
+    vertices: I,
+    count: u64,
+    partition_counts: Arc<Mutex<HashMap<PartitionId, u64>>>,
+}
+
+impl<I> PartitionCountIter<I> {
+    fn flush(&mut self) {
+        if self.count > 0 {
+            if let Ok(mut counts) = self.partition_counts.lock() {
+                *counts.entry(self.partition_id).or_insert(0) += self.count;
+            }
+            self.count = 0;
+        }
+    }
+}
+
+impl<I: Iterator<Item = Vertex>> Iterator for PartitionCountIter<I> {
+    type Item = Vertex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.vertices.next();
+        if next.is_some() {
+            self.count += 1;
+        } else {
+            self.flush();
+        }
+        next
+    }
+}
+
+impl<I> Drop for PartitionCountIter<I> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
 /// The interfaces of reading data (vertices, edges and their properties) from a graph.
 pub trait ReadGraph: Send + Sync {
     /// Scan all vertices with query parameters, and return an iterator over them.
@@ -50,12 +210,73 @@ pub trait ReadGraph: Send + Sync {
         &self, params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>>;
 
+    /// Scan all vertices with query parameters as `scan_vertex()`, while the vertices are grouped by
+    /// the partitions assigned to the current worker, one sub-iterator per partition, for processing
+    /// partition-by-partition. The sample and limit of `params` apply within each partition.
+    /// By default, the graph is regarded as a single partition `0`.
+    fn scan_vertex_by_partition(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<
+        Box<dyn Iterator<Item = (PartitionId, Box<dyn Iterator<Item = Vertex> + Send>)> + Send>,
+    > {
+        Ok(Box::new(std::iter::once((0, self.scan_vertex(params)?))))
+    }
+
+    /// Scan all vertices with query parameters as `scan_vertex()`, while counting the vertices yielded by
+    /// each partition assigned to the current worker into `partition_counts`, e.g., to find the partitions
+    /// that yield no vertices when debugging a skew. Every assigned partition is recorded, with 0 at first,
+    /// and the count of a partition is added once its vertices are exhausted, or the returned iterator is dropped.
+    /// By default, the partitions are given by `scan_vertex_by_partition()`.
+    fn scan_vertex_with_partition_counts(
+        &self, params: &QueryParams, partition_counts: Arc<Mutex<HashMap<PartitionId, u64>>>,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        let groups: Vec<_> = self.scan_vertex_by_partition(params)?.collect();
+        {
+            let mut counts = partition_counts
+                .lock()
+                .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?;
+            for (partition_id, _) in &groups {
+                counts.entry(*partition_id).or_insert(0);
+            }
+        }
+        let result = groups
+            .into_iter()
+            .flat_map(move |(partition_id, vertices)| PartitionCountIter {
+                partition_id,
+                vertices,
+                count: 0,
+                partition_counts: partition_counts.clone(),
+            });
+        // the limit of `params` applies within each partition, and further to all the partitions
+        Ok(limit_n!(result, params.limit))
+    }
+
+    /// Scan the ids of all vertices with query parameters as `scan_vertex()`, for the cases where neither
+    /// the labels nor the properties of the vertices are needed, e.g., to initialize the vertices in
+    /// an iterative algorithm. The stores may override it to skip building the vertices.
+    /// By default, it takes the ids of the vertices from `scan_vertex()`.
+    fn scan_vertex_ids(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = ID> + Send>> {
+        Ok(Box::new(self.scan_vertex(params)?.map(|v| v.id())))
+    }
+
     /// Scan a vertex with a specified label and its primary key value(s), and additional query parameters,
     /// and return the vertex if exists.
     fn index_scan_vertex(
         &self, label: LabelId, primary_key: &PKV, params: &QueryParams,
     ) -> GraphProxyResult<Option<Vertex>>;
 
+    /// Scan a vertex as `index_scan_vertex()`, given the hint of the partition it resides in.
+    /// Only the index of this partition is probed, and `None` is returned if the vertex is not found there,
+    /// which saves the lookup across partitions for the jobs pinned to partitions.
+    /// By default, it falls back to `index_scan_vertex()` for the stores without partition-local indices.
+    fn index_scan_vertex_local(
+        &self, _partition_id: PartitionId, label: LabelId, primary_key: &PKV, params: &QueryParams,
+    ) -> GraphProxyResult<Option<Vertex>> {
+        self.index_scan_vertex(label, primary_key, params)
+    }
+
     /// Scan all edges with query parameters, and return an iterator over them.
     fn scan_edge(&self, params: &QueryParams) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>>;
 
@@ -64,6 +285,26 @@ pub trait ReadGraph: Send + Sync {
         &self, ids: &[ID], params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>>;
 
+    /// Get vertices with the given global_ids (defined in runtime) and parameters, as `get_vertex()`,
+    /// and additionally report the ids that do not exist in the graph at all.
+    /// A vertex that exists but is rejected by the filter in `params` is not regarded as a miss,
+    /// and the order of the missing ids is not specified.
+    fn get_vertex_with_misses(
+        &self, ids: &[ID], params: &QueryParams,
+    ) -> GraphProxyResult<(Box<dyn Iterator<Item = Vertex> + Send>, Vec<ID>)> {
+        let mut lookup_params = params.clone();
+        lookup_params.filter = None;
+        lookup_params.limit = None;
+        let found: Vec<Vertex> = self.get_vertex(ids, &lookup_params)?.collect();
+        let found_ids: HashSet<ID> = found.iter().map(|v| v.id()).collect();
+        let misses = ids
+            .iter()
+            .filter(|id| !found_ids.contains(*id))
+            .cloned()
+            .collect();
+        Ok((filter_limit!(found.into_iter(), params.filter, params.limit), misses))
+    }
+
     /// Get edges with the given global_ids (defined in runtime) and parameters, and return an iterator over them.
     fn get_edge(
         &self, ids: &[ID], params: &QueryParams,
@@ -81,15 +322,84 @@ pub trait ReadGraph: Send + Sync {
         &self, direction: Direction, params: &QueryParams,
     ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>>;
 
+    /// Count the adjacent vertices of the given vertex in the given direction, via the edges of
+    /// `params.labels` (of any label if empty), i.e., the degree of the vertex, which is read at the
+    /// snapshot given in `params.extra_params`, if any. The other parameters, e.g., the filter, are ignored.
+    /// The stores may override it to read the degree directly, instead of iterating the neighbors,
+    /// which matters for the hub vertices. By default, it counts the neighbors from `prepare_explore_vertex()`.
+    fn count_neighbors(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        let params = QueryParams {
+            labels: params.labels.clone(),
+            extra_params: params.extra_params.clone(),
+            ..Default::default()
+        };
+        Ok(self
+            .prepare_explore_vertex(direction, &params)?
+            .exec(id)?
+            .count() as u64)
+    }
+
+    /// Get the degree of the given vertex in the given direction, i.e., the number of its adjacent edges
+    /// with query parameters, e.g., of the labels in `params.labels` and passing `params.filter`,
+    /// which is capped at `params.limit`, if any.
+    /// Without the filter or the sampling, it reads the degree from `count_neighbors()`, which the stores
+    /// may serve without building the edges. Otherwise, it counts the edges from `prepare_explore_edge()`.
+    fn get_degree(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        if params.filter.is_none()
+            && params.sample_ratio.is_none()
+            && params.per_vertex_sample.is_none()
+            && params.end_v_labels.is_none()
+        {
+            let degree = self.count_neighbors(id, direction, params)?;
+            Ok(params.limit_count(degree))
+        } else {
+            Ok(self
+                .prepare_explore_edge(direction, params)?
+                .exec(id)?
+                .count() as u64)
+        }
+    }
+
     /// Count vertices with query parameters, and return the number of vertices.
     fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64>;
 
     /// Count edges with query parameters, and return the number of edges.
     fn count_edge(&self, params: &QueryParams) -> GraphProxyResult<u64>;
 
+    /// Count the vertices of the given global_ids with query parameters, i.e., the number of ids that
+    /// exist in the graph (and satisfy the filter in `params`, if any), capped at the limit in `params`.
+    /// The stores may override it to check the existence of the ids without building the vertices.
+    /// By default, it counts the vertices from `get_vertex()`.
+    fn count_vertex_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        Ok(params.limit_count(self.get_vertex(ids, params)?.count() as u64))
+    }
+
+    /// Count the edges of the given global_ids with query parameters, as `count_vertex_by_ids()`.
+    /// By default, it counts the edges from `get_edge()`.
+    fn count_edge_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        Ok(params.limit_count(self.get_edge(ids, params)?.count() as u64))
+    }
+
     /// Get primary key value(s) with the given global_id,
     /// and return the primary key value(s) if exists
     fn get_primary_key(&self, id: &ID) -> GraphProxyResult<Option<PKV>>;
+
+    /// Get primary key value(s) of the vertex with the given global_id and label, where the label is
+    /// known from the element at hand, e.g., a vertex or the ends of an edge, and the primary key is
+    /// read on the snapshot of the query parameters. By default, it is `get_primary_key()`.
+    fn get_primary_key_with_label(
+        &self, id: &ID, _label: &LabelId, _params: &QueryParams,
+    ) -> GraphProxyResult<Option<PKV>> {
+        self.get_primary_key(id)
+    }
+
+    /// Explain a query, given as a json-serialized logical plan, without executing it. Each operator
+    /// of the plan is annotated with its cost, as estimated by `count_vertex()` and `count_edge()`.
+    fn explain(&self, query: &str) -> GraphProxyResult<QueryPlan> {
+        let logical_plan = serde_json::from_str(query)
+            .map_err(|e| GraphProxyError::unsupported_error(&format!("invalid query plan {}", e)))?;
+        explain_plan(self, logical_plan)
+    }
 }
 
 lazy_static! {
@@ -110,3 +420,73 @@ pub fn get_graph() -> Option<Arc<dyn ReadGraph>> {
         Some(unsafe { (*ptr).clone() })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// A statement expanding each id to its next two ids, as a mock store counting the calls per id
+    fn counting_stmt(
+        calls: Arc<Mutex<Vec<ID>>>,
+    ) -> impl Fn(ID) -> GraphProxyResult<Box<dyn Iterator<Item = ID> + Send>> + Send + 'static {
+        move |id| {
+            calls.lock().unwrap().push(id);
+            Ok(Box::new(vec![id + 1, id + 2].into_iter()))
+        }
+    }
+
+    #[test]
+    fn memoize_repeated_ids() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let stmt = memoize_fn(2, counting_stmt(calls.clone()));
+        for _ in 0..3 {
+            assert_eq!(stmt.exec(1).unwrap().collect::<Vec<_>>(), vec![2, 3]);
+        }
+        assert_eq!(*calls.lock().unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn memoize_evict_least_recently_used() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let stmt = memoize_fn(2, counting_stmt(calls.clone()));
+        for id in [1, 2, 1, 3, 1, 2] {
+            stmt.exec(id).unwrap().count();
+        }
+        // 2 is evicted by 3, as 1 is used more recently
+        assert_eq!(*calls.lock().unwrap(), vec![1, 2, 3, 2]);
+
+        // nothing is cached with the zero capacity
+        let calls = Arc::new(Mutex::new(vec![]));
+        let stmt = memoize_fn(0, counting_stmt(calls.clone()));
+        stmt.exec(1).unwrap().count();
+        stmt.exec(1).unwrap().count();
+        assert_eq!(*calls.lock().unwrap(), vec![1, 1]);
+    }
+
+    #[test]
+    fn memoize_consumed_results_only() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let stmt = memoize_fn(2, counting_stmt(calls.clone()));
+        // the results partially consumed, e.g., under a limit, are not cached
+        assert_eq!(stmt.exec(1).unwrap().next(), Some(2));
+        assert_eq!(stmt.exec(1).unwrap().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(stmt.exec(1).unwrap().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(*calls.lock().unwrap(), vec![1, 1]);
+    }
+
+    #[test]
+    fn memoize_no_errors() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = count.clone();
+        let stmt = memoize_fn(2, move |_id| -> GraphProxyResult<Box<dyn Iterator<Item = ID> + Send>> {
+            count_clone.fetch_add(1, Ordering::SeqCst);
+            Err(GraphProxyError::storage_unavailable("unavailable"))
+        });
+        assert!(stmt.exec(1).is_err());
+        assert!(stmt.exec(1).is_err());
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,658 @@
+//
+//! Copyright 2024 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ahash::HashMap;
+use ir_common::LabelId;
+
+use crate::apis::explain::QueryPlan;
+use crate::apis::graph::PKV;
+use crate::apis::partitioner::PartitionId;
+use crate::apis::{Direction, Edge, QueryParams, ReadGraph, Statement, Vertex, ID};
+use crate::{GraphProxyError, GraphProxyResult};
+
+/// The number of threads that run the operations of a `TimeoutReadGraph`.
+const NUM_CALL_THREADS: usize = 16;
+/// The number of elements that an iterator bounded by a deadline fetches from the graph at a time.
+const ITER_BATCH_SIZE: usize = 1024;
+
+/// A `ReadGraph` that enforces a wall-clock deadline on each operation of the wrapped graph,
+/// which fails with `GraphProxyError::OperationTimeout` once the deadline is exceeded, rather than
+/// occupying the worker indefinitely, e.g., on a full scan over a slow partition.
+///
+/// The scans (including the counts over all vertices or edges) are bounded by `scan_timeout`, and
+/// the lookups by ids or primary keys, including the explorations of `prepare_explore_vertex()`
+/// and `prepare_explore_edge()`, by `lookup_timeout`.
+///
+/// The operations run on a pool of threads, with the worker of the caller, if any, set as the current
+/// worker of the thread, as the wrapped graph may rely on it, e.g., to locate the partitions of the
+/// worker. The caller waits for an operation until its deadline, and then leaves it to finish on its
+/// thread, as it cannot be interrupted. The iterators returned are bounded by the same deadline,
+/// and fetch the elements in batches on the pool. As an iterator cannot fail, the one beyond its
+/// deadline stops yielding, and fails the job of the current worker with the timeout instead.
+pub struct TimeoutReadGraph<G: ReadGraph> {
+    graph: Arc<G>,
+    scan_timeout: Duration,
+    lookup_timeout: Duration,
+    pool: Arc<CallPool>,
+}
+
+impl<G: ReadGraph + 'static> TimeoutReadGraph<G> {
+    pub fn new(graph: Arc<G>, scan_timeout: Duration, lookup_timeout: Duration) -> Self {
+        TimeoutReadGraph {
+            graph,
+            scan_timeout,
+            lookup_timeout,
+            pool: Arc::new(CallPool::new(NUM_CALL_THREADS)),
+        }
+    }
+
+    fn scan<T, F>(&self, operation: &'static str, f: F) -> GraphProxyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&G, Deadline) -> GraphProxyResult<T> + Send + 'static,
+    {
+        let graph = self.graph.clone();
+        let deadline = Deadline::new(operation, self.scan_timeout, self.pool.clone());
+        deadline.call(move |deadline| f(graph.as_ref(), deadline))
+    }
+
+    fn lookup<T, F>(&self, operation: &'static str, f: F) -> GraphProxyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&G, Deadline) -> GraphProxyResult<T> + Send + 'static,
+    {
+        let graph = self.graph.clone();
+        let deadline = Deadline::new(operation, self.lookup_timeout, self.pool.clone());
+        deadline.call(move |deadline| f(graph.as_ref(), deadline))
+    }
+}
+
+type CallJob = Box<dyn FnOnce() + Send>;
+
+/// The threads that run the operations of the graph. An operation that is beyond its deadline before
+/// it starts is dropped, and the one that hangs occupies its thread until it returns.
+struct CallPool {
+    sender: Mutex<mpsc::Sender<CallJob>>,
+}
+
+impl CallPool {
+    fn new(num_threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<CallJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..num_threads.max(1) {
+            let receiver = receiver.clone();
+            std::thread::Builder::new()
+                .name(format!("graph-call-{}", i))
+                .spawn(move || loop {
+                    // the lock is released once a job is received, before running it
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => break,
+                    };
+                    match job {
+                        // a panicking operation fails with its result dropped, without the thread lost
+                        Ok(job) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                        // the pool is dropped
+                        Err(_) => break,
+                    }
+                })
+                .expect("spawn graph call thread failed");
+        }
+        CallPool { sender: Mutex::new(sender) }
+    }
+
+    fn execute(&self, job: CallJob) -> GraphProxyResult<()> {
+        self.sender
+            .lock()
+            .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?
+            .send(job)
+            .map_err(|_| GraphProxyError::internal_error("graph call pool is shut down"))
+    }
+}
+
+/// The deadline of an operation, which bounds the operation as well as the iterators it returns.
+#[derive(Clone)]
+struct Deadline {
+    operation: &'static str,
+    start: Instant,
+    timeout: Duration,
+    pool: Arc<CallPool>,
+}
+
+impl Deadline {
+    fn new(operation: &'static str, timeout: Duration, pool: Arc<CallPool>) -> Self {
+        Deadline { operation, start: Instant::now(), timeout, pool }
+    }
+
+    fn timeout_error(&self) -> GraphProxyError {
+        GraphProxyError::operation_timeout(self.operation, self.start.elapsed().as_millis() as u64)
+    }
+
+    /// Run `f` on the pool, and wait for its result until the deadline.
+    fn call<T, F>(&self, f: F) -> GraphProxyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(Deadline) -> GraphProxyResult<T> + Send + 'static,
+    {
+        let remaining = match self.timeout.checked_sub(self.start.elapsed()) {
+            Some(remaining) if !remaining.is_zero() => remaining,
+            _ => return Err(self.timeout_error()),
+        };
+        let (tx, rx) = mpsc::sync_channel(1);
+        let deadline = self.clone();
+        let worker = pegasus::get_current_worker_checked();
+        self.pool.execute(Box::new(move || {
+            // dropped if it has not started before the deadline
+            if deadline.start.elapsed() >= deadline.timeout {
+                return;
+            }
+            pegasus::set_current_worker(worker);
+            // the receiver may have been dropped on timeout
+            let _ = tx.send(f(deadline));
+            pegasus::set_current_worker(None);
+        }))?;
+        rx.recv_timeout(remaining)
+            .map_err(|e| match e {
+                RecvTimeoutError::Timeout => self.timeout_error(),
+                // also dropped right before the deadline
+                RecvTimeoutError::Disconnected if self.start.elapsed() >= self.timeout => {
+                    self.timeout_error()
+                }
+                RecvTimeoutError::Disconnected => GraphProxyError::storage_unavailable(&format!(
+                    "{} aborted unexpectedly",
+                    self.operation
+                )),
+            })?
+    }
+
+    fn bound<T: Send + 'static>(
+        self, iter: Box<dyn Iterator<Item = T> + Send>,
+    ) -> Box<dyn Iterator<Item = T> + Send> {
+        Box::new(DeadlineIter { iter: Some(iter), batch: vec![].into_iter(), deadline: self })
+    }
+}
+
+/// An iterator that fetches the elements in batches on the pool within its deadline. Once beyond the
+/// deadline, it stops yielding, and fails the job of the current worker, if any, with the timeout,
+/// as the timeout cannot be returned by the iterator.
+struct DeadlineIter<T> {
+    /// The iterator to fetch the elements from, which is `None` once exhausted or expired
+    iter: Option<Box<dyn Iterator<Item = T> + Send>>,
+    batch: std::vec::IntoIter<T>,
+    deadline: Deadline,
+}
+
+impl<T: Send + 'static> Iterator for DeadlineIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(next) = self.batch.next() {
+            return Some(next);
+        }
+        let mut iter = self.iter.take()?;
+        let fetched = self.deadline.call(move |_| {
+            let batch: Vec<T> = iter.by_ref().take(ITER_BATCH_SIZE).collect();
+            Ok((iter, batch))
+        });
+        match fetched {
+            Ok((iter, batch)) => {
+                if batch.len() == ITER_BATCH_SIZE {
+                    self.iter = Some(iter);
+                }
+                self.batch = batch.into_iter();
+                self.batch.next()
+            }
+            Err(e) => {
+                match pegasus::get_current_worker_checked() {
+                    Some(worker) => {
+                        error!("{}, fail the job {}", e, worker.job_id);
+                        let (kind, is_system) = (e.job_exec_error_kind(), e.is_system());
+                        if let Err(e) = pegasus::fail_job(worker.job_id, kind, is_system, e) {
+                            warn!("fail to fail the job {}: {:?}", worker.job_id, e);
+                        }
+                    }
+                    None => error!("{}", e),
+                }
+                None
+            }
+        }
+    }
+}
+
+/// A statement of which each execution is bounded by its own deadline.
+struct DeadlineStatement<O> {
+    /// The statement is shared with the pool, where it is executed one at a time
+    stmt: Arc<Mutex<Box<dyn Statement<ID, O>>>>,
+    operation: &'static str,
+    timeout: Duration,
+    pool: Arc<CallPool>,
+}
+
+impl<O: Send + 'static> Statement<ID, O> for DeadlineStatement<O> {
+    fn exec(&self, next: ID) -> GraphProxyResult<Box<dyn Iterator<Item = O> + Send>> {
+        let stmt = self.stmt.clone();
+        Deadline::new(self.operation, self.timeout, self.pool.clone()).call(move |deadline| {
+            let iter = stmt
+                .lock()
+                .map_err(|e| GraphProxyError::internal_error(&format!("{:?}", e)))?
+                .exec(next)?;
+            Ok(deadline.bound(iter))
+        })
+    }
+}
+
+impl<G: ReadGraph + 'static> ReadGraph for TimeoutReadGraph<G> {
+    fn scan_vertex(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        let params = params.clone();
+        self.scan("scan_vertex", move |graph, deadline| Ok(deadline.bound(graph.scan_vertex(&params)?)))
+    }
+
+    fn scan_vertex_by_partition(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<
+        Box<dyn Iterator<Item = (PartitionId, Box<dyn Iterator<Item = Vertex> + Send>)> + Send>,
+    > {
+        let params = params.clone();
+        self.scan("scan_vertex_by_partition", move |graph, deadline| {
+            let bound = deadline.clone();
+            let partitions = graph
+                .scan_vertex_by_partition(&params)?
+                .map(move |(partition_id, iter)| (partition_id, bound.clone().bound(iter)));
+            Ok(deadline.bound(Box::new(partitions)))
+        })
+    }
+
+    fn scan_vertex_with_partition_counts(
+        &self, params: &QueryParams, partition_counts: Arc<Mutex<HashMap<PartitionId, u64>>>,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        let params = params.clone();
+        self.scan("scan_vertex_with_partition_counts", move |graph, deadline| {
+            Ok(deadline.bound(graph.scan_vertex_with_partition_counts(&params, partition_counts)?))
+        })
+    }
+
+    fn scan_vertex_ids(
+        &self, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = ID> + Send>> {
+        let params = params.clone();
+        self.scan("scan_vertex_ids", move |graph, deadline| {
+            Ok(deadline.bound(graph.scan_vertex_ids(&params)?))
+        })
+    }
+
+    fn index_scan_vertex(
+        &self, label: LabelId, primary_key: &PKV, params: &QueryParams,
+    ) -> GraphProxyResult<Option<Vertex>> {
+        let primary_key = primary_key.clone();
+        let params = params.clone();
+        self.lookup("index_scan_vertex", move |graph, _| {
+            graph.index_scan_vertex(label, &primary_key, &params)
+        })
+    }
+
+    fn index_scan_vertex_local(
+        &self, partition_id: PartitionId, label: LabelId, primary_key: &PKV, params: &QueryParams,
+    ) -> GraphProxyResult<Option<Vertex>> {
+        let primary_key = primary_key.clone();
+        let params = params.clone();
+        self.lookup("index_scan_vertex_local", move |graph, _| {
+            graph.index_scan_vertex_local(partition_id, label, &primary_key, &params)
+        })
+    }
+
+    fn scan_edge(&self, params: &QueryParams) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+        let params = params.clone();
+        self.scan("scan_edge", move |graph, deadline| Ok(deadline.bound(graph.scan_edge(&params)?)))
+    }
+
+    fn get_vertex(
+        &self, ids: &[ID], params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+        let ids = ids.to_vec();
+        let params = params.clone();
+        self.lookup("get_vertex", move |graph, deadline| {
+            Ok(deadline.bound(graph.get_vertex(&ids, &params)?))
+        })
+    }
+
+    fn get_vertex_with_misses(
+        &self, ids: &[ID], params: &QueryParams,
+    ) -> GraphProxyResult<(Box<dyn Iterator<Item = Vertex> + Send>, Vec<ID>)> {
+        let ids = ids.to_vec();
+        let params = params.clone();
+        self.lookup("get_vertex_with_misses", move |graph, deadline| {
+            let (iter, misses) = graph.get_vertex_with_misses(&ids, &params)?;
+            Ok((deadline.bound(iter), misses))
+        })
+    }
+
+    fn get_edge(
+        &self, ids: &[ID], params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+        let ids = ids.to_vec();
+        let params = params.clone();
+        self.lookup("get_edge", move |graph, deadline| Ok(deadline.bound(graph.get_edge(&ids, &params)?)))
+    }
+
+    fn prepare_explore_vertex(
+        &self, direction: Direction, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
+        let stmt = self
+            .graph
+            .prepare_explore_vertex(direction, params)?;
+        Ok(Box::new(DeadlineStatement {
+            stmt: Arc::new(Mutex::new(stmt)),
+            operation: "explore_vertex",
+            timeout: self.lookup_timeout,
+            pool: self.pool.clone(),
+        }))
+    }
+
+    fn prepare_explore_edge(
+        &self, direction: Direction, params: &QueryParams,
+    ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
+        let stmt = self
+            .graph
+            .prepare_explore_edge(direction, params)?;
+        Ok(Box::new(DeadlineStatement {
+            stmt: Arc::new(Mutex::new(stmt)),
+            operation: "explore_edge",
+            timeout: self.lookup_timeout,
+            pool: self.pool.clone(),
+        }))
+    }
+
+    fn count_neighbors(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        let params = params.clone();
+        self.lookup("count_neighbors", move |graph, _| graph.count_neighbors(id, direction, &params))
+    }
+
+    fn get_degree(&self, id: ID, direction: Direction, params: &QueryParams) -> GraphProxyResult<u64> {
+        let params = params.clone();
+        self.lookup("get_degree", move |graph, _| graph.get_degree(id, direction, &params))
+    }
+
+    fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64> {
+        let params = params.clone();
+        self.scan("count_vertex", move |graph, _| graph.count_vertex(&params))
+    }
+
+    fn count_edge(&self, params: &QueryParams) -> GraphProxyResult<u64> {
+        let params = params.clone();
+        self.scan("count_edge", move |graph, _| graph.count_edge(&params))
+    }
+
+    fn count_vertex_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        let ids = ids.to_vec();
+        let params = params.clone();
+        self.lookup("count_vertex_by_ids", move |graph, _| graph.count_vertex_by_ids(&ids, &params))
+    }
+
+    fn count_edge_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+        let ids = ids.to_vec();
+        let params = params.clone();
+        self.lookup("count_edge_by_ids", move |graph, _| graph.count_edge_by_ids(&ids, &params))
+    }
+
+    fn get_primary_key(&self, id: &ID) -> GraphProxyResult<Option<PKV>> {
+        let id = *id;
+        self.lookup("get_primary_key", move |graph, _| graph.get_primary_key(&id))
+    }
+
+    fn get_primary_key_with_label(
+        &self, id: &ID, label: &LabelId, params: &QueryParams,
+    ) -> GraphProxyResult<Option<PKV>> {
+        let (id, label) = (*id, *label);
+        let params = params.clone();
+        self.lookup("get_primary_key_with_label", move |graph, _| {
+            graph.get_primary_key_with_label(&id, &label, &params)
+        })
+    }
+
+    fn explain(&self, query: &str) -> GraphProxyResult<QueryPlan> {
+        self.graph.explain(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use ir_common::LabelId;
+
+    use super::{CallPool, Deadline, TimeoutReadGraph};
+    use crate::apis::graph::PKV;
+    use crate::apis::{
+        from_fn, Direction, DynDetails, Edge, QueryParams, ReadGraph, Statement, Vertex, ID,
+    };
+    use crate::{GraphProxyError, GraphProxyResult};
+
+    /// A graph of the vertices 1 to 3, which counts the vertices yielded by its scans, and of which
+    /// the scans and the vertex lookups block for `delay` before returning, and then between the vertices.
+    #[derive(Default)]
+    struct CountingGraph {
+        yielded: Arc<AtomicUsize>,
+        delay: Duration,
+    }
+
+    impl CountingGraph {
+        fn blocking(delay: Duration) -> Self {
+            CountingGraph { yielded: Arc::default(), delay }
+        }
+
+        fn vertices(&self) -> Box<dyn Iterator<Item = Vertex> + Send> {
+            let yielded = self.yielded.clone();
+            let delay = self.delay;
+            Box::new((1..=3).map(move |id| {
+                if id > 1 {
+                    std::thread::sleep(delay);
+                }
+                yielded.fetch_add(1, Ordering::SeqCst);
+                Vertex::new(id, None, DynDetails::default())
+            }))
+        }
+    }
+
+    impl ReadGraph for CountingGraph {
+        fn scan_vertex(
+            &self, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            std::thread::sleep(self.delay);
+            Ok(self.vertices())
+        }
+
+        fn index_scan_vertex(
+            &self, _label: LabelId, _primary_key: &PKV, _params: &QueryParams,
+        ) -> GraphProxyResult<Option<Vertex>> {
+            Ok(None)
+        }
+
+        fn scan_edge(
+            &self, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            Ok(Box::new(std::iter::empty()))
+        }
+
+        fn get_vertex(
+            &self, ids: &[ID], _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            std::thread::sleep(self.delay);
+            let vertices: Vec<Vertex> = ids
+                .iter()
+                .map(|id| Vertex::new(*id, None, DynDetails::default()))
+                .collect();
+            Ok(Box::new(vertices.into_iter()))
+        }
+
+        fn get_edge(
+            &self, _ids: &[ID], _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            Ok(Box::new(std::iter::empty()))
+        }
+
+        fn prepare_explore_vertex(
+            &self, _direction: Direction, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
+            Ok(from_fn(|v: ID| {
+                Ok(Box::new(std::iter::once(Vertex::new(v + 1, None, DynDetails::default())))
+                    as Box<dyn Iterator<Item = Vertex> + Send>)
+            }))
+        }
+
+        fn prepare_explore_edge(
+            &self, _direction: Direction, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
+            Ok(from_fn(|_v: ID| Ok(Box::new(std::iter::empty()) as Box<dyn Iterator<Item = Edge> + Send>)))
+        }
+
+        fn count_vertex(&self, _params: &QueryParams) -> GraphProxyResult<u64> {
+            Ok(3)
+        }
+
+        fn count_edge(&self, _params: &QueryParams) -> GraphProxyResult<u64> {
+            Ok(0)
+        }
+
+        fn get_primary_key(&self, _id: &ID) -> GraphProxyResult<Option<PKV>> {
+            Ok(None)
+        }
+    }
+
+    // the deadlines are either far beyond, or already exceeded as soon as the operations are called,
+    // so that the tests do not depend on the elapsed time
+    const NEVER: Duration = Duration::from_secs(3600);
+    const EXPIRED: Duration = Duration::from_secs(0);
+
+    fn is_timeout<T>(result: GraphProxyResult<T>) -> bool {
+        match result {
+            Err(e @ GraphProxyError::OperationTimeout { .. }) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_operations_within_deadline() {
+        let graph = TimeoutReadGraph::new(Arc::new(CountingGraph::default()), NEVER, NEVER);
+        let params = QueryParams::default();
+        assert_eq!(graph.scan_vertex(&params).unwrap().count(), 3);
+        assert_eq!(graph.count_vertex(&params).unwrap(), 3);
+        assert_eq!(
+            graph
+                .get_vertex(&[1, 2], &params)
+                .unwrap()
+                .count(),
+            2
+        );
+        assert!(graph.get_primary_key(&1).unwrap().is_none());
+        let stmt = graph
+            .prepare_explore_vertex(Direction::Out, &params)
+            .unwrap();
+        assert_eq!(stmt.exec(1).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_scan_timeout() {
+        let inner = Arc::new(CountingGraph::default());
+        let yielded = inner.yielded.clone();
+        let graph = TimeoutReadGraph::new(inner, EXPIRED, NEVER);
+        let params = QueryParams::default();
+        assert!(is_timeout(graph.scan_vertex(&params)));
+        assert!(is_timeout(graph.count_vertex(&params)));
+        // the scans are never consumed beyond the deadline
+        assert_eq!(yielded.load(Ordering::SeqCst), 0);
+        // the lookups are bounded by their own timeout
+        assert_eq!(graph.get_vertex(&[1], &params).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_lookup_timeout() {
+        let graph = TimeoutReadGraph::new(Arc::new(CountingGraph::default()), NEVER, EXPIRED);
+        let params = QueryParams::default();
+        assert!(is_timeout(graph.get_vertex(&[1], &params)));
+        assert!(is_timeout(graph.get_primary_key(&1)));
+        let stmt = graph
+            .prepare_explore_vertex(Direction::Out, &params)
+            .unwrap();
+        assert!(is_timeout(stmt.exec(1)));
+        assert_eq!(graph.count_vertex(&params).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_iterator_beyond_deadline() {
+        let inner = Arc::new(CountingGraph::default());
+        let yielded = inner.yielded.clone();
+        let deadline = Deadline::new("scan_vertex", EXPIRED, Arc::new(CallPool::new(1)));
+        // the iterator stops yielding once beyond the deadline, without consuming the store
+        let iter = deadline.bound(
+            inner
+                .scan_vertex(&QueryParams::default())
+                .unwrap(),
+        );
+        assert_eq!(iter.count(), 0);
+        assert_eq!(yielded.load(Ordering::SeqCst), 0);
+    }
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+    const BLOCKING: Duration = Duration::from_secs(2);
+
+    #[test]
+    fn test_blocking_operation_timeout() {
+        let graph = TimeoutReadGraph::new(Arc::new(CountingGraph::blocking(BLOCKING)), TIMEOUT, TIMEOUT);
+        let params = QueryParams::default();
+        for operation in ["scan_vertex", "get_vertex"] {
+            let start = Instant::now();
+            let result = if operation == "scan_vertex" {
+                graph.scan_vertex(&params)
+            } else {
+                graph.get_vertex(&[1], &params)
+            };
+            // the caller gives up on the blocking operation at its deadline, rather than waiting for it
+            assert!(start.elapsed() < BLOCKING);
+            match result {
+                Err(GraphProxyError::OperationTimeout { operation: op, elapsed_ms }) => {
+                    assert_eq!(op, operation);
+                    assert!(elapsed_ms >= TIMEOUT.as_millis() as u64);
+                }
+                _ => panic!("{} should be beyond its deadline", operation),
+            }
+        }
+        // the operations not blocking are not affected by the ones hanging on the pool
+        assert_eq!(graph.count_vertex(&params).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_blocking_iterator_timeout() {
+        let inner = Arc::new(CountingGraph::blocking(BLOCKING));
+        let yielded = inner.yielded.clone();
+        let iter = inner.vertices();
+        let deadline = Deadline::new("scan_vertex", TIMEOUT, Arc::new(CallPool::new(1)));
+        let start = Instant::now();
+        // the first vertex is yielded at once, while the second blocks beyond the deadline
+        assert_eq!(deadline.bound(iter).count(), 0);
+        assert!(start.elapsed() < BLOCKING);
+        assert!(yielded.load(Ordering::SeqCst) <= 1);
+    }
+}
@@ -13,32 +13,73 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use ir_common::NameOrId;
 use pegasus::api::function::DynError;
+use pegasus::errors::{ErrorKind, JobExecError};
+
+use crate::apis::partitioner::PartitionId;
 
 pub type GraphProxyResult<T> = Result<T, GraphProxyError>;
 
-/// Errors that occur when querying or writing in graph proxy
+/// Errors that occur when querying or writing in graph proxy, categorized by their causes,
+/// each of which carries a message for the client.
 #[derive(Debug)]
 pub enum GraphProxyError {
-    /// Query storage error
-    QueryStoreError(String),
-    /// Write storage error
-    WriteGraphError(String),
+    /// The storage is temporarily unable to serve the request, e.g., it is recovering
+    StorageUnavailable(String),
+    /// The partition cannot be located among the partitions known by the current server
+    PartitionNotLocal { partition_id: PartitionId },
+    /// The schema is unavailable or inconsistent with the query
+    SchemaError(String),
+    /// A name, e.g., of a label or a property, that cannot be resolved into its id by the schema
+    SchemaResolution { kind: String, name: String },
+    /// An extra param in `QueryParams` that is malformed or unknown
+    InvalidExtraParam { key: String, msg: String },
     /// filter push down error
     FilterPushDownError(String),
-    /// Cluster info missing error
-    ClusterInfoMissing(String),
-    /// Not supported error
-    UnSupported(String),
+    /// The operation, or a parameter of it, is not supported by the storage or by graph_proxy itself
+    UnsupportedOperation(String),
+    /// The snapshot to read is older than the oldest snapshot that the storage serves
+    SnapshotUnavailable { requested: i64, oldest: i64 },
+    /// The storage does not respond in time, e.g., to set up a scan
+    Timeout(String),
+    /// An operation of the graph beyond the deadline enforced by `TimeoutReadGraph`
+    OperationTimeout { operation: &'static str, elapsed_ms: u64 },
+    /// A property of a graph element that is neither fetched along with the element, e.g., as the columns
+    /// pushed down to the storage are trimmed, nor able to be fetched on demand
+    PropertyNotFetched(String),
+    /// Other failures, e.g., a write failure or missing cluster info, that are not going to succeed on retry
+    Internal(String),
 }
 
 impl GraphProxyError {
+    pub fn storage_unavailable(e: &str) -> Self {
+        GraphProxyError::StorageUnavailable(e.to_string())
+    }
+
+    pub fn timeout_error(e: &str) -> Self {
+        GraphProxyError::Timeout(e.to_string())
+    }
+
+    pub fn operation_timeout(operation: &'static str, elapsed_ms: u64) -> Self {
+        GraphProxyError::OperationTimeout { operation, elapsed_ms }
+    }
+
+    pub fn internal_error(e: &str) -> Self {
+        GraphProxyError::Internal(e.to_string())
+    }
+
+    pub fn schema_error(e: &str) -> Self {
+        GraphProxyError::SchemaError(e.to_string())
+    }
+
+    /// A failure of the storage that cannot be categorized further
     pub fn query_store_error(e: &str) -> Self {
-        GraphProxyError::QueryStoreError(e.to_string())
+        GraphProxyError::Internal(format!("query store error, {}", e))
     }
 
     pub fn write_graph_error(e: &str) -> Self {
-        GraphProxyError::WriteGraphError(e.to_string())
+        GraphProxyError::Internal(format!("write graph error, {}", e))
     }
 
     pub fn filter_push_down_error(e: &str) -> Self {
@@ -46,25 +87,111 @@ impl GraphProxyError {
     }
 
     pub fn cluster_info_missing(e: &str) -> Self {
-        GraphProxyError::ClusterInfoMissing(e.to_string())
+        GraphProxyError::Internal(format!("cluster info missing, {}", e))
+    }
+
+    pub fn unsupported_error(op: &str) -> Self {
+        GraphProxyError::UnsupportedOperation(op.to_string())
+    }
+
+    pub fn schema_resolution_error(kind: &str, name: &str) -> Self {
+        GraphProxyError::SchemaResolution { kind: kind.to_string(), name: name.to_string() }
+    }
+
+    pub fn partition_not_local(partition_id: PartitionId) -> Self {
+        GraphProxyError::PartitionNotLocal { partition_id }
+    }
+
+    pub fn invalid_extra_param(key: &str, msg: &str) -> Self {
+        GraphProxyError::InvalidExtraParam { key: key.to_string(), msg: msg.to_string() }
+    }
+
+    pub fn store_transient_error(store: &str, msg: &str) -> Self {
+        GraphProxyError::StorageUnavailable(format!("{}, {}", store, msg))
     }
-    pub fn unsupported_error(e: &str) -> Self {
-        GraphProxyError::UnSupported(e.to_string())
+
+    pub fn store_permanent_error(store: &str, msg: &str) -> Self {
+        GraphProxyError::Internal(format!("{}, {}", store, msg))
+    }
+
+    pub fn property_not_fetched(key: &NameOrId, e: &str) -> Self {
+        GraphProxyError::PropertyNotFetched(format!("property {:?}, {}", key, e))
+    }
+
+    /// The snapshot to query has been compacted away, which fails the query of strict read consistency
+    pub fn snapshot_unavailable(requested: i64, oldest: i64) -> Self {
+        GraphProxyError::SnapshotUnavailable { requested, oldest }
+    }
+
+    /// Whether the failed request may succeed if retried later as is.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            GraphProxyError::StorageUnavailable(_)
+                | GraphProxyError::Timeout(_)
+                | GraphProxyError::OperationTimeout { .. }
+        )
+    }
+
+    /// Whether the error is caused by the system, rather than by the query of the user.
+    pub fn is_system(&self) -> bool {
+        !matches!(
+            self,
+            GraphProxyError::SchemaError(_)
+                | GraphProxyError::SchemaResolution { .. }
+                | GraphProxyError::InvalidExtraParam { .. }
+                | GraphProxyError::FilterPushDownError(_)
+                | GraphProxyError::UnsupportedOperation(_)
+        )
+    }
+
+    /// The kind of `JobExecError` that the error is classified as. The retryable errors are classified
+    /// as `ErrorKind::IOError` rather than `WouldBlock` or `Interrupted`, which are reserved for the
+    /// flow control of pegasus.
+    pub fn job_exec_error_kind(&self) -> ErrorKind {
+        if self.is_retryable() {
+            ErrorKind::IOError
+        } else {
+            ErrorKind::Others
+        }
     }
 }
 
 impl std::fmt::Display for GraphProxyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            GraphProxyError::QueryStoreError(e) => write!(f, "Query store error in graph_proxy {}", e),
-            GraphProxyError::WriteGraphError(e) => write!(f, "Write graph error in graph_proxy {}", e),
+            GraphProxyError::StorageUnavailable(e) => {
+                write!(f, "Storage unavailable error in graph_proxy {}", e)
+            }
+            GraphProxyError::PartitionNotLocal { partition_id } => {
+                write!(f, "Partition {} is not located in graph_proxy", partition_id)
+            }
+            GraphProxyError::SchemaError(e) => write!(f, "Schema error in graph_proxy {}", e),
+            GraphProxyError::SchemaResolution { kind, name } => {
+                write!(f, "Schema resolution error in graph_proxy, unknown {} {:?}", kind, name)
+            }
+            GraphProxyError::InvalidExtraParam { key, msg } => {
+                write!(f, "Invalid extra param {:?} in graph_proxy, {}", key, msg)
+            }
             GraphProxyError::FilterPushDownError(e) => {
                 write!(f, "Filter push down error in graph_proxy {}", e)
             }
-            GraphProxyError::UnSupported(e) => write!(f, "Op not supported error in graph_proxy {}", e),
-            GraphProxyError::ClusterInfoMissing(e) => {
-                write!(f, "Cluster info missing error in graph_proxy {}", e)
+            GraphProxyError::UnsupportedOperation(e) => {
+                write!(f, "Op not supported error in graph_proxy {}", e)
+            }
+            GraphProxyError::SnapshotUnavailable { requested, oldest } => write!(
+                f,
+                "Snapshot unavailable error in graph_proxy, read snapshot {} in strict consistency, while the oldest available snapshot is {}",
+                requested, oldest
+            ),
+            GraphProxyError::Timeout(e) => write!(f, "Timeout error in graph_proxy {}", e),
+            GraphProxyError::OperationTimeout { operation, elapsed_ms } => {
+                write!(f, "Timeout error in graph_proxy, {} is beyond its deadline after {} ms", operation, elapsed_ms)
+            }
+            GraphProxyError::PropertyNotFetched(e) => {
+                write!(f, "Property not fetched error in graph_proxy {}", e)
             }
+            GraphProxyError::Internal(e) => write!(f, "Internal error in graph_proxy {}", e),
         }
     }
 }
@@ -77,3 +204,75 @@ impl From<GraphProxyError> for DynError {
         err
     }
 }
+
+/// Classified by `job_exec_error_kind()`; use `JobExecError::as_ref()` to tell whether the cause
+/// `is_retryable()`.
+impl From<GraphProxyError> for JobExecError {
+    fn from(e: GraphProxyError) -> Self {
+        let kind = e.job_exec_error_kind();
+        let is_system = e.is_system();
+        let mut err = JobExecError::new(kind, e);
+        if is_system {
+            err.set_system();
+        }
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_unavailable_to_job_exec_error() {
+        let e = GraphProxyError::store_transient_error("GraphScopeStore", "recovering");
+        assert!(matches!(e, GraphProxyError::StorageUnavailable(_)));
+        assert!(e.is_retryable());
+        let err = JobExecError::from(e);
+        assert!(matches!(err.kind, ErrorKind::IOError));
+        assert!(err.is_system);
+        // not to be confused with the flow control of pegasus
+        assert!(!err.can_be_retried());
+        assert!(err
+            .as_ref::<GraphProxyError>()
+            .map(|e| e.is_retryable())
+            .unwrap_or(false));
+    }
+
+    #[test]
+    fn test_unsupported_operation_to_job_exec_error() {
+        let e = GraphProxyError::unsupported_error("get_edge");
+        assert!(matches!(e, GraphProxyError::UnsupportedOperation(_)));
+        assert!(!e.is_retryable());
+        let err = JobExecError::from(e);
+        assert!(matches!(err.kind, ErrorKind::Others));
+        assert!(!err.is_system);
+        assert!(!err.can_be_retried());
+    }
+
+    #[test]
+    fn test_error_retryable() {
+        assert!(GraphProxyError::timeout_error("scan").is_retryable());
+        assert!(GraphProxyError::operation_timeout("scan_vertex", 100).is_retryable());
+        assert!(!GraphProxyError::query_store_error("scan").is_retryable());
+        assert!(!GraphProxyError::partition_not_local(1).is_retryable());
+        assert!(!GraphProxyError::schema_resolution_error("label", "person").is_retryable());
+        assert!(!GraphProxyError::filter_push_down_error("var").is_retryable());
+    }
+
+    #[test]
+    fn test_structured_errors_to_job_exec_error() {
+        let err = JobExecError::from(GraphProxyError::partition_not_local(1));
+        assert!(matches!(err.kind, ErrorKind::Others));
+        assert!(err.is_system);
+        assert!(matches!(
+            err.as_ref::<GraphProxyError>(),
+            Some(GraphProxyError::PartitionNotLocal { partition_id: 1 })
+        ));
+        // caused by the query of the user
+        let err = JobExecError::from(GraphProxyError::invalid_extra_param("SID", "not an integer"));
+        assert!(!err.is_system);
+        let err = JobExecError::from(GraphProxyError::schema_resolution_error("label", "person"));
+        assert!(!err.is_system);
+    }
+}
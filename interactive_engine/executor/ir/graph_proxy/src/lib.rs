@@ -17,10 +17,11 @@
 extern crate log;
 #[macro_use]
 extern crate lazy_static;
-pub use adapters::{create_csr_store, create_exp_store, SimplePartition};
+pub use adapters::{create_csr_store, create_exp_store, create_exp_store_with_graph, SimplePartition};
 #[cfg(feature = "with_global_query")]
 pub use adapters::{
-    create_gs_store, GraphScopeStore, GrootMultiPartition, VineyardGraphWriter, VineyardMultiPartition,
+    create_gs_store, create_gs_store_with_setup_timeout, GraphScopeStore, GrootMultiPartition,
+    PartitionStatistics, VineyardGraphWriter, VineyardMultiPartition,
 };
 pub use errors::{GraphProxyError, GraphProxyResult};
 
@@ -56,13 +57,18 @@ macro_rules! filter_limit {
     };
 }
 
+/// Sample each element of the iterator with the probability of the given ratio,
+/// and then take at most `limit` of the sampled elements.
 #[macro_export]
 macro_rules! sample_limit {
-    ($iter: expr, $s: expr, $n: expr) => {
+    ($iter: expr, $s: expr, $seed: expr, $n: expr) => {
         if let Some(ratio) = $s {
             use rand::prelude::StdRng;
             use rand::{Rng, SeedableRng};
-            let mut rng: StdRng = SeedableRng::from_entropy();
+            let mut rng: StdRng = match $seed {
+                Some(seed) => SeedableRng::seed_from_u64(seed),
+                None => SeedableRng::from_entropy(),
+            };
             let r = $iter.filter(move |_| rng.gen_bool(ratio));
             limit_n!(r, $n)
         } else {
@@ -72,17 +78,152 @@ macro_rules! sample_limit {
     };
 }
 
+/// Filter, sample and limit the iterator in order, i.e., only the elements passing the filter
+/// are sampled, and the limit counts the elements that pass both the filter and the sampling.
 #[macro_export]
 macro_rules! filter_sample_limit {
-    ($iter: expr, $f: expr, $s: expr, $n: expr) => {
+    ($iter: expr, $f: expr, $s: expr, $seed: expr, $n: expr) => {
         if let Some(ref f) = $f {
             use crate::utils::expr::eval_pred::EvalPred;
             let f = f.clone();
             let r = $iter.filter(move |v| f.eval_bool(Some(v)).unwrap_or(false));
-            sample_limit!(r, $s, $n)
+            sample_limit!(r, $s, $seed, $n)
         } else {
             let r = $iter;
-            sample_limit!(r, $s, $n)
+            sample_limit!(r, $s, $seed, $n)
         }
     };
 }
+
+/// Sample at most `size` elements of the iterator by reservoir sampling, where each element is sampled
+/// with the same probability, and then take at most `limit` of the sampled elements. It differs from
+/// `sample_limit!` in that the number rather than the ratio of the sampled elements is fixed, e.g., to sample
+/// a fixed number of neighbors per vertex. The sampled elements keep their order in the iterator.
+#[macro_export]
+macro_rules! reservoir_sample_limit {
+    ($iter: expr, $s: expr, $seed: expr, $n: expr) => {
+        if let Some(size) = $s {
+            use rand::prelude::StdRng;
+            use rand::{Rng, SeedableRng};
+            let mut rng: StdRng = match $seed {
+                Some(seed) => SeedableRng::seed_from_u64(seed),
+                None => SeedableRng::from_entropy(),
+            };
+            let mut reservoir = Vec::with_capacity(size);
+            for (i, item) in $iter.enumerate() {
+                if i < size {
+                    reservoir.push((i, item));
+                } else {
+                    let j = rng.gen_range(0..=i);
+                    if j < size {
+                        reservoir[j] = (i, item);
+                    }
+                }
+            }
+            reservoir.sort_by_key(|(i, _)| *i);
+            let r = reservoir.into_iter().map(|(_, item)| item);
+            limit_n!(r, $n)
+        } else {
+            let r = $iter;
+            limit_n!(r, $n)
+        }
+    };
+}
+
+/// Filter, reservoir-sample and limit the iterator in order, as `filter_sample_limit!`.
+#[macro_export]
+macro_rules! filter_reservoir_sample_limit {
+    ($iter: expr, $f: expr, $s: expr, $seed: expr, $n: expr) => {
+        if let Some(ref f) = $f {
+            use crate::utils::expr::eval_pred::EvalPred;
+            let f = f.clone();
+            let r = $iter.filter(move |v| f.eval_bool(Some(v)).unwrap_or(false));
+            reservoir_sample_limit!(r, $s, $seed, $n)
+        } else {
+            let r = $iter;
+            reservoir_sample_limit!(r, $s, $seed, $n)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+
+    use ir_common::expr_parse::str_to_expr_pb;
+
+    use crate::apis::{DynDetails, GraphElement, Vertex, ID};
+    use crate::utils::expr::eval_pred::PEvaluator;
+
+    fn vertices(n: ID) -> impl Iterator<Item = Vertex> {
+        (0..n).map(|id| Vertex::new(id, None, DynDetails::default()))
+    }
+
+    fn ids(iter: Box<dyn Iterator<Item = Vertex>>) -> Vec<ID> {
+        iter.map(|v| v.id()).collect()
+    }
+
+    fn filter(expr: &str) -> Option<Arc<PEvaluator>> {
+        Some(Arc::new(PEvaluator::try_from(str_to_expr_pb(expr.to_string()).unwrap()).unwrap()))
+    }
+
+    #[test]
+    fn limit_after_filter() {
+        let f = filter("@.~id >= 900");
+        let result = ids(filter_sample_limit!(vertices(1000), f, None, None::<u64>, Some(10)));
+        assert_eq!(result, (900..910).collect::<Vec<ID>>());
+
+        let f = filter("@.~id >= 995");
+        let result = ids(filter_sample_limit!(vertices(1000), f, None, None::<u64>, Some(10)));
+        assert_eq!(result, (995..1000).collect::<Vec<ID>>());
+    }
+
+    #[test]
+    fn limit_after_sample() {
+        let sampled = ids(sample_limit!(vertices(1000), Some(0.5), Some(7_u64), None));
+        assert!(sampled.len() > 10);
+        let result = ids(sample_limit!(vertices(1000), Some(0.5), Some(7_u64), Some(10)));
+        assert_eq!(result, sampled[..10].to_vec());
+
+        // fewer sampled elements than the limit
+        let sampled = ids(sample_limit!(vertices(12), Some(0.5), Some(7_u64), None));
+        let result = ids(sample_limit!(vertices(12), Some(0.5), Some(7_u64), Some(10)));
+        assert_eq!(result, sampled[..sampled.len().min(10)].to_vec());
+    }
+
+    #[test]
+    fn limit_after_filter_and_sample() {
+        let f = filter("@.~id >= 500");
+        let filtered_sampled = ids(filter_sample_limit!(vertices(1000), f, Some(0.5), Some(7_u64), None));
+        assert!(filtered_sampled.iter().all(|id| *id >= 500));
+        // the sampling applies to the filtered elements only
+        let sampled =
+            ids(sample_limit!(vertices(1000).filter(|v| v.id() >= 500), Some(0.5), Some(7_u64), None));
+        assert_eq!(filtered_sampled, sampled);
+
+        let f = filter("@.~id >= 500");
+        let result = ids(filter_sample_limit!(vertices(1000), f, Some(0.5), Some(7_u64), Some(10)));
+        assert_eq!(result.len(), 10);
+        assert_eq!(result, filtered_sampled[..10].to_vec());
+    }
+
+    #[test]
+    fn limit_after_reservoir_sample() {
+        let sampled = ids(reservoir_sample_limit!(vertices(1000), Some(20), Some(7_u64), None));
+        assert_eq!(sampled.len(), 20);
+        // in the order of the iterator
+        assert!(sampled.windows(2).all(|w| w[0] < w[1]));
+        let result = ids(reservoir_sample_limit!(vertices(1000), Some(20), Some(7_u64), Some(10)));
+        assert_eq!(result, sampled[..10].to_vec());
+
+        // fewer elements than the sample size
+        let result = ids(reservoir_sample_limit!(vertices(12), Some(20), Some(7_u64), None));
+        assert_eq!(result, (0..12).collect::<Vec<ID>>());
+
+        let f = filter("@.~id >= 500");
+        let result = ids(filter_reservoir_sample_limit!(vertices(1000), f, Some(20), Some(7_u64), None));
+        assert_eq!(result.len(), 20);
+        assert!(result.iter().all(|id| *id >= 500));
+    }
+}
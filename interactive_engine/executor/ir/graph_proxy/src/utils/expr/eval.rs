@@ -15,14 +15,15 @@
 //!
 
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
+use std::ops::RangeInclusive;
 use std::panic;
 
 use dyn_type::arith::{BitOperand, Exp};
 use dyn_type::object;
 use dyn_type::object::RawType;
-use dyn_type::{BorrowObject, Object};
+use dyn_type::{BorrowObject, DateTimeFormats, Object};
 use ir_common::error::{ParsePbError, ParsePbResult};
 use ir_common::expr_parse::to_suffix_expr;
 use ir_common::generated::common as common_pb;
@@ -45,6 +46,9 @@ pub struct Evaluator {
     /// A stack for evaluating the suffix-tree-based expression
     /// Wrap it in a `RefCell` to avoid conflict mutable reference
     stack: RefCell<Vec<ExprEvalResult<Object>>>,
+    /// For the first operator of the right operand of an `And` or `Or`, it maintains the position of
+    /// the `And` or `Or`, so as to skip the right operand if the left one is conclusive
+    short_circuits: Vec<Option<usize>>,
 }
 
 unsafe impl Sync for Evaluator {}
@@ -111,6 +115,7 @@ pub(crate) enum InnerOpr {
     Function(Function),
     Operand(Operand),
     Conditional(Conditional),
+    Call(FunctionCall),
 }
 
 impl ToString for InnerOpr {
@@ -121,6 +126,7 @@ impl ToString for InnerOpr {
             InnerOpr::Operand(item) => format!("{:?}", item),
             InnerOpr::Function(func) => format!("{:?}", func),
             InnerOpr::Conditional(conditional) => format!("{:?}", conditional),
+            InnerOpr::Call(call) => format!("{:?}", call),
         }
     }
 }
@@ -198,7 +204,53 @@ impl TryFrom<Vec<common_pb::ExprOpr>> for Evaluator {
         for unit in suffix_oprs {
             inner_tree.push(InnerOpr::try_from(unit)?);
         }
-        Ok(Self { suffix_tree: inner_tree, stack: RefCell::new(vec![]) })
+        let short_circuits = locate_short_circuits(&inner_tree);
+        Ok(Self { suffix_tree: inner_tree, stack: RefCell::new(vec![]), short_circuits })
+    }
+}
+
+/// Locate the right operands of all `And` and `Or` operators in the suffix tree, by maintaining
+/// the start position of each sub-expression. If the suffix tree is malformed, no short circuit
+/// is located, and the error is left to be reported while evaluating.
+fn locate_short_circuits(suffix_tree: &[InnerOpr]) -> Vec<Option<usize>> {
+    let mut short_circuits = vec![None; suffix_tree.len()];
+    let mut starts: Vec<usize> = vec![];
+    for (idx, opr) in suffix_tree.iter().enumerate() {
+        match opr {
+            InnerOpr::Operand(_) | InnerOpr::Conditional(_) | InnerOpr::Call(_) => starts.push(idx),
+            _ if opr.is_unary() => {
+                if starts.is_empty() {
+                    return vec![None; suffix_tree.len()];
+                }
+            }
+            _ => {
+                let right_start = starts.pop();
+                let left_start = starts.pop();
+                if let (Some(left_start), Some(right_start)) = (left_start, right_start) {
+                    if let InnerOpr::Logical(common_pb::Logical::And | common_pb::Logical::Or) = opr {
+                        short_circuits[right_start] = Some(idx);
+                    }
+                    starts.push(left_start);
+                } else {
+                    return vec![None; suffix_tree.len()];
+                }
+            }
+        }
+    }
+    short_circuits
+}
+
+/// Given the evaluated left operand of an `And` or `Or`, return the result if it is conclusive
+/// regardless of the right operand, i.e., `false` for `And` and `true` for `Or`.
+fn short_circuit(logical: &common_pb::Logical, left: &Object) -> Option<Object> {
+    if left.raw_type() == RawType::None {
+        return None;
+    }
+    let left = left.eval_bool::<(), NoneContext>(None).ok()?;
+    match logical {
+        common_pb::Logical::And if !left => Some(false.into()),
+        common_pb::Logical::Or if left => Some(true.into()),
+        _ => None,
     }
 }
 
@@ -371,6 +423,7 @@ pub(crate) fn apply_logical<'a>(
                     .into()),
                     Within => Ok(b.contains(&a).into()),
                     Without => Ok((!b.contains(&a)).into()),
+                    // matched by strings rather than bytes, which is correct for multi-byte characters
                     Startswith => Ok(a
                         .as_str()?
                         .starts_with(b.as_str()?.as_ref())
@@ -409,6 +462,159 @@ pub(crate) fn apply_condition_expr<'a, E: Element, C: Context<E>>(
     }
 }
 
+/// The signature of a built-in function, which is applied to the evaluated values of its arguments.
+type BuiltinFn = fn(&[Object]) -> ExprEvalResult<Object>;
+
+lazy_static! {
+    /// The registry of the built-in functions that can be called in an expression as `name(args)`.
+    /// Each function is keyed by its lowercase name, along with the numbers of arguments it accepts.
+    static ref BUILTIN_FUNCTIONS: HashMap<&'static str, (RangeInclusive<usize>, BuiltinFn)> = {
+        let mut functions: HashMap<&'static str, (RangeInclusive<usize>, BuiltinFn)> = HashMap::new();
+        functions.insert("length", (1..=1, str_length));
+        functions.insert("substring", (2..=3, str_substring));
+        functions.insert("tolower", (1..=1, str_to_lower));
+        functions.insert("toupper", (1..=1, str_to_upper));
+        functions.insert("concat", (1..=usize::MAX, str_concat));
+        functions.insert("trim", (1..=1, str_trim));
+        functions.insert("year", (1..=1, temporal_year));
+        functions.insert("month", (1..=1, temporal_month));
+        functions.insert("day", (1..=1, temporal_day));
+        functions.insert("hour", (1..=1, temporal_hour));
+        functions.insert("duration_between", (2..=2, duration_between));
+        functions
+    };
+}
+
+/// The number of characters (rather than bytes) of a string
+fn str_length(args: &[Object]) -> ExprEvalResult<Object> {
+    Ok((args[0].as_str()?.chars().count() as i64).into())
+}
+
+/// `substring(str, start[, len])` takes the characters of `str` from the 0-based position `start`,
+/// up to `len` characters if given, or to the end of `str` otherwise.
+fn str_substring(args: &[Object]) -> ExprEvalResult<Object> {
+    let string = args[0].as_str()?;
+    let start = args[1].as_i64()?;
+    let len = if let Some(len) = args.get(2) { Some(len.as_i64()?) } else { None };
+    if start < 0 || len.unwrap_or(0) < 0 {
+        return Err(ExprEvalError::OtherErr(format!(
+            "invalid arguments of `substring`: start {}, length {:?}",
+            start, len
+        )));
+    }
+    let chars = string.chars().skip(start as usize);
+    let substring: String =
+        if let Some(len) = len { chars.take(len as usize).collect() } else { chars.collect() };
+    Ok(substring.into())
+}
+
+fn str_to_lower(args: &[Object]) -> ExprEvalResult<Object> {
+    Ok(args[0].as_str()?.to_lowercase().into())
+}
+
+fn str_to_upper(args: &[Object]) -> ExprEvalResult<Object> {
+    Ok(args[0].as_str()?.to_uppercase().into())
+}
+
+fn str_concat(args: &[Object]) -> ExprEvalResult<Object> {
+    let mut result = String::new();
+    for arg in args {
+        result.push_str(arg.as_str()?.as_ref());
+    }
+    Ok(result.into())
+}
+
+fn str_trim(args: &[Object]) -> ExprEvalResult<Object> {
+    Ok(args[0].as_str()?.trim().to_string().into())
+}
+
+/// Extract a component of a date/time value, where a value with a timezone gives the component in
+/// its own timezone. Extracting a component that the value does not have, e.g., the hour of a `Date`
+/// or the year of a `Time`, is an error, rather than a default like 0 that passes for a valid one.
+fn extract_temporal<F: Fn(&DateTimeFormats) -> Option<u32>>(
+    arg: &Object, component: &str, extract: F,
+) -> ExprEvalResult<Object> {
+    let date_time = arg.as_date_format()?;
+    extract(date_time)
+        .map(|value| Object::from(value as i32))
+        .ok_or_else(|| ExprEvalError::Unsupported(format!("no {} in {:?}", component, date_time)))
+}
+
+fn temporal_year(args: &[Object]) -> ExprEvalResult<Object> {
+    extract_temporal(&args[0], "year", |date_time| date_time.year().map(|year| year as u32))
+}
+
+fn temporal_month(args: &[Object]) -> ExprEvalResult<Object> {
+    extract_temporal(&args[0], "month", DateTimeFormats::month)
+}
+
+fn temporal_day(args: &[Object]) -> ExprEvalResult<Object> {
+    extract_temporal(&args[0], "day", DateTimeFormats::day)
+}
+
+fn temporal_hour(args: &[Object]) -> ExprEvalResult<Object> {
+    extract_temporal(&args[0], "hour", DateTimeFormats::hour)
+}
+
+/// `duration_between(from, to)` gives the milliseconds elapsed from `from` to `to`, which is negative
+/// if `to` is earlier. A `Date` counts from its midnight in UTC, while a `Time` has no timestamp.
+fn duration_between(args: &[Object]) -> ExprEvalResult<Object> {
+    let millis = |arg: &Object| -> ExprEvalResult<i64> {
+        let date_time = arg.as_date_format()?;
+        date_time
+            .timestamp_millis()
+            .ok_or_else(|| ExprEvalError::Unsupported(format!("no timestamp of {:?}", date_time)))
+    };
+    Ok((millis(&args[1])? - millis(&args[0])?).into())
+}
+
+/// A call to a built-in function, e.g., `substring(@.name, 0, 3)`, where each argument
+/// is an expression by itself.
+#[derive(Debug)]
+pub struct FunctionCall {
+    name: String,
+    func: BuiltinFn,
+    args: Vec<Evaluator>,
+}
+
+impl TryFrom<common_pb::UserDefinedFunction> for FunctionCall {
+    type Error = ParsePbError;
+
+    fn try_from(udf: common_pb::UserDefinedFunction) -> ParsePbResult<Self> {
+        let (arity, func) = BUILTIN_FUNCTIONS
+            .get(udf.name.to_lowercase().as_str())
+            .ok_or_else(|| ParsePbError::Unsupported(format!("unknown function `{}`", udf.name)))?;
+        if !arity.contains(&udf.parameters.len()) {
+            return Err(ParsePbError::ParseError(format!(
+                "function `{}` does not accept {} arguments",
+                udf.name,
+                udf.parameters.len()
+            )));
+        }
+        let mut args = Vec::with_capacity(udf.parameters.len());
+        for param in udf.parameters {
+            args.push(Evaluator::try_from(param)?);
+        }
+        Ok(Self { name: udf.name, func: *func, args })
+    }
+}
+
+impl Evaluate for FunctionCall {
+    /// Evaluate the arguments, and apply the function to them. The result is `Object::None`
+    /// if any of the arguments is `None`, in accordance with the arithmetic operators.
+    fn eval<E: Element, C: Context<E>>(&self, context: Option<&C>) -> ExprEvalResult<Object> {
+        let mut values = Vec::with_capacity(self.args.len());
+        for arg in &self.args {
+            let value = get_object(arg.eval(context))?;
+            if let Object::None = value {
+                return Ok(Object::None);
+            }
+            values.push(value);
+        }
+        (self.func)(&values)
+    }
+}
+
 // Private api
 impl Evaluator {
     /// Evaluate simple expression that contains less than three operators
@@ -479,6 +685,9 @@ impl Evaluator {
                     }
                 } else {
                     let a = first.eval(context)?;
+                    if let Some(rst) = short_circuit(logical, &a) {
+                        return Ok(rst);
+                    }
                     let b = second.eval(context)?;
                     Ok(apply_logical(logical, a.as_borrow(), Some(b.as_borrow()))?)
                 }
@@ -552,7 +761,22 @@ impl Evaluate for Evaluator {
             return self.eval_without_stack(context);
         }
         stack.clear();
-        for opr in &self.suffix_tree {
+        let mut idx = 0;
+        while idx < self.suffix_tree.len() {
+            if let Some(end) = self.short_circuits[idx] {
+                // the left operand of an `And` or `Or` has just been evaluated
+                if let (InnerOpr::Logical(logical), Some(Ok(left))) = (&self.suffix_tree[end], stack.last())
+                {
+                    if let Some(rst) = short_circuit(logical, left) {
+                        stack.pop();
+                        stack.push(Ok(rst));
+                        idx = end + 1;
+                        continue;
+                    }
+                }
+            }
+            let opr = &self.suffix_tree[idx];
+            idx += 1;
             if opr.is_operand() {
                 stack.push(opr.eval(context));
             } else {
@@ -633,6 +857,30 @@ impl Evaluator {
     pub fn reset(&self) {
         self.stack.borrow_mut().clear();
     }
+
+    /// Whether the expression calls any function, e.g., `year(@.creationDate) == 2012`
+    pub fn has_function_call(&self) -> bool {
+        self.suffix_tree
+            .iter()
+            .any(|opr| matches!(opr, InnerOpr::Call(_)))
+    }
+
+    /// Collect the operands of the expression, including those in the arguments of function calls
+    pub fn operands(&self) -> Vec<&Operand> {
+        let mut operands = vec![];
+        for opr in &self.suffix_tree {
+            match opr {
+                InnerOpr::Operand(operand) => operands.push(operand),
+                InnerOpr::Call(call) => {
+                    for arg in &call.args {
+                        operands.extend(arg.operands());
+                    }
+                }
+                _ => {}
+            }
+        }
+        operands
+    }
 }
 
 impl TryFrom<common_pb::Value> for Operand {
@@ -702,14 +950,14 @@ impl TryFrom<common_pb::ExprOpr> for Operand {
                 Const(c) => c.try_into(),
                 Var(var) => var.try_into(),
                 Vars(vars) => {
-                    let mut vec = Vec::with_capacity(vars.keys.len());
+                    let mut vec = Vec::with_capacity(vars.len());
                     for var in vars.keys {
                         vec.push(var.try_into()?);
                     }
                     Ok(Self::Vars(vec))
                 }
                 VarMap(vars) => {
-                    let mut vec = Vec::with_capacity(vars.keys.len());
+                    let mut vec = Vec::with_capacity(vars.len());
                     for var in vars.keys {
                         vec.push(var.try_into()?);
                     }
@@ -744,6 +992,7 @@ impl TryFrom<common_pb::ExprOpr> for InnerOpr {
                     std::mem::transmute::<_, common_pb::extract::Interval>(extract.interval)
                 }))),
                 Case(case) => Ok(Self::Conditional(Conditional::Case(case.clone().try_into()?))),
+                UdfFunc(udf) => Ok(Self::Call(udf.clone().try_into()?)),
                 _ => Ok(Self::Operand(unit.clone().try_into()?)),
             }
         } else {
@@ -840,6 +1089,7 @@ impl Evaluate for InnerOpr {
     fn eval<E: Element, C: Context<E>>(&self, context: Option<&C>) -> ExprEvalResult<Object> {
         match self {
             Self::Operand(item) => item.eval(context),
+            Self::Call(call) => call.eval(context),
             _ => Err(ExprEvalError::UnmatchedOperator(self.into())),
         }
     }
@@ -848,7 +1098,7 @@ impl Evaluate for InnerOpr {
 impl InnerOpr {
     pub fn is_operand(&self) -> bool {
         match self {
-            InnerOpr::Operand(_) => true,
+            InnerOpr::Operand(_) | InnerOpr::Call(_) => true,
             _ => false,
         }
     }
@@ -1620,6 +1870,65 @@ mod tests {
         }
     }
 
+    /// A context that counts the accesses to each of its vertices
+    struct SpyVertices {
+        vertices: Vertices,
+        accesses: RefCell<Vec<usize>>,
+    }
+
+    impl Context<Vertex> for SpyVertices {
+        fn get(&self, key: Option<&NameOrId>) -> Option<&Vertex> {
+            if let Some(NameOrId::Id(i)) = key {
+                if let Some(count) = self.accesses.borrow_mut().get_mut(*i as usize) {
+                    *count += 1;
+                }
+            }
+            self.vertices.get(key)
+        }
+    }
+
+    #[test]
+    fn test_eval_short_circuit() {
+        // [v0: id = 1, label = 9, age = 31, name = John, birthday = 19900416, hobbies = [football, guitar]]
+        // [v1: id = 2, label = 11, age = 26, name = Nancy, birthday = 19950816]
+        let ctxt = SpyVertices { vertices: prepare_context(), accesses: RefCell::new(vec![0, 0]) };
+        // (expression, expected result, expected accesses of v1)
+        let cases = vec![
+            ("@0.age > 40 && @1.age > 20", false, 0),
+            ("@0.age > 20 || @1.age > 20", true, 0),
+            ("@0.age > 20 && @1.age > 20", true, 1),
+            ("@0.age > 40 || @1.age > 20", true, 1),
+            ("false && @1.age", false, 0),
+            ("true || @1.age", true, 0),
+            ("(@0.age > 40 && @1.age > 20) || @1.name == \"Nancy\"", true, 1),
+            ("@0.age > 20 || (@1.age > 20 && @1.name == \"Nancy\")", true, 0),
+            ("(@0.age > 20 || @1.age > 20) && @0.name == \"John\"", true, 0),
+        ];
+        for (case, expected, expected_accesses) in cases {
+            let expr = str_to_expr_pb(case.to_string()).unwrap();
+            let eval = Evaluator::try_from(expr.clone()).unwrap();
+            assert_eq!(
+                eval.eval_bool::<_, SpyVertices>(Some(&ctxt))
+                    .unwrap(),
+                expected,
+                "{}",
+                case
+            );
+            assert_eq!(ctxt.accesses.replace(vec![0, 0])[1], expected_accesses, "{}", case);
+
+            let p_eval = PEvaluator::try_from(expr).unwrap();
+            assert_eq!(
+                p_eval
+                    .eval_bool::<_, SpyVertices>(Some(&ctxt))
+                    .unwrap(),
+                expected,
+                "{}",
+                case
+            );
+            assert_eq!(ctxt.accesses.replace(vec![0, 0])[1], expected_accesses, "{}", case);
+        }
+    }
+
     #[test]
     fn test_eval_empty_string() {
         let map1: HashMap<NameOrId, Object> =
@@ -1632,4 +1941,142 @@ mod tests {
         let eval = Evaluator::try_from(str_to_expr_pb(case.to_string()).unwrap()).unwrap();
         assert_eq!(eval.eval::<_, Vertices>(Some(&ctxt)).unwrap(), object!(true));
     }
+    #[test]
+    fn test_eval_string_functions() {
+        // [v0: id = 1, label = 9, age = 31, name = John, birthday = 19900416, hobbies = [football, guitar]]
+        // [v1: id = 2, label = 11, age = 26, name = Nancy, birthday = 19950816]
+        let ctxt = prepare_context();
+        let cases: Vec<&str> = vec![
+            "length(@0.name)",                            // 4
+            "length(\"\")",                               // 0
+            "substring(@1.name, 1, 3)",                   // "anc"
+            "substring(@1.name, 2)",                      // "ncy"
+            "substring(@1.name, 3, 10)",                  // "cy"
+            "toLower(@0.name)",                           // "john"
+            "TOUPPER(@0.name)",                           // "JOHN"
+            "concat(@0.name, \" & \", @1.name)",          // "John & Nancy"
+            "trim(\"  John \")",                          // "John"
+            "length(concat(@0.name, @1.name)) == 9",      // true
+            "toUpper(substring(@1.name, 0, 1)) == \"N\"", // true
+            "length(@0.name) + length(@1.name) * 2 > 10", // true
+            "length(@0.not_exist)",                       // Object::None
+            "concat(@0.name, @0.not_exist)",              // Object::None
+        ];
+        let expected: Vec<Object> = vec![
+            object!(4i64),
+            object!(0i64),
+            object!("anc"),
+            object!("ncy"),
+            object!("cy"),
+            object!("john"),
+            object!("JOHN"),
+            object!("John & Nancy"),
+            object!("John"),
+            object!(true),
+            object!(true),
+            object!(true),
+            Object::None,
+            Object::None,
+        ];
+
+        for (case, expected) in cases.into_iter().zip(expected.into_iter()) {
+            let eval = Evaluator::try_from(str_to_expr_pb(case.to_string()).unwrap()).unwrap();
+            assert_eq!(eval.eval::<_, Vertices>(Some(&ctxt)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_eval_string_functions_errors() {
+        let ctxt = prepare_context();
+        // non-string arguments
+        let cases: Vec<&str> = vec!["length(@0.age)", "toUpper(12)", "concat(@0.name, @0.age)"];
+        for case in cases {
+            let eval = Evaluator::try_from(str_to_expr_pb(case.to_string()).unwrap()).unwrap();
+            match eval.eval::<_, Vertices>(Some(&ctxt)) {
+                Err(ExprEvalError::CastError(_)) => {}
+                rst => panic!("unexpected result {:?} of {}", rst, case),
+            }
+        }
+        let eval =
+            Evaluator::try_from(str_to_expr_pb("substring(@0.name, -1)".to_string()).unwrap()).unwrap();
+        assert!(eval.eval::<_, Vertices>(Some(&ctxt)).is_err());
+
+        // unknown functions, or a wrong number of arguments
+        let cases: Vec<&str> = vec!["reverse(@0.name)", "length(@0.name, @1.name)", "substring(@0.name)"];
+        for case in cases {
+            assert!(Evaluator::try_from(str_to_expr_pb(case.to_string()).unwrap()).is_err());
+        }
+    }
+    #[test]
+    fn test_eval_temporal_functions() {
+        let ctxt = prepare_context_with_date();
+        let cases: Vec<&str> = vec![
+            // date1: "2020-08-08"
+            "year(@0.date1)",
+            "month(@0.date1)",
+            "day(@0.date1)",
+            // time1: "10:11:12.100"
+            "hour(@0.time1)",
+            // datetime1: "2020-08-08T23:11:12.100-11:00", in its own timezone
+            "year(@0.datetime1)",
+            "day(@0.datetime1)",
+            "hour(@0.datetime1)",
+            // datetime2: "2020-08-09 10:11:12.100"
+            "hour(@0.datetime2)",
+            // compare the extracted year against an i64 constant
+            "year(@0.datetime2) == 2020",
+            "year(@0.date1) > 2019 && month(@0.date1) == 8",
+            // "2020-08-08T23:11:12.100-11:00" is "2020-08-09 10:11:12.100" in UTC
+            "duration_between(@0.datetime1, @0.datetime2)",
+            "duration_between(@0.date1, @0.datetime2)",
+            "duration_between(@0.datetime2, @0.date1)",
+            "year(@0.not_exist)",
+        ];
+        let expected: Vec<Object> = vec![
+            object!(2020),
+            object!(8),
+            object!(8),
+            object!(10),
+            object!(2020),
+            object!(8),
+            object!(23),
+            object!(10),
+            object!(true),
+            object!(true),
+            object!(0i64),
+            object!(123072100i64),
+            object!(-123072100i64),
+            Object::None,
+        ];
+
+        for (case, expected) in cases.into_iter().zip(expected.into_iter()) {
+            let eval = Evaluator::try_from(str_to_expr_pb(case.to_string()).unwrap()).unwrap();
+            assert_eq!(eval.eval::<_, Vertices>(Some(&ctxt)).unwrap(), expected, "{}", case);
+        }
+        // the constants parsed from a string are i64, so an i32 constant is given directly
+        let mut operators = str_to_expr_pb("year(@0.datetime2)".to_string())
+            .unwrap()
+            .operators;
+        operators.push(common_pb::Logical::Eq.into());
+        operators.push(common_pb::Value::from(2020i32).into());
+        let eval = Evaluator::try_from(common_pb::Expression { operators }).unwrap();
+        assert_eq!(eval.eval::<_, Vertices>(Some(&ctxt)).unwrap(), object!(true));
+
+        // a date has no hour, and a time has no year or timestamp
+        let cases: Vec<&str> =
+            vec!["hour(@0.date1)", "year(@0.time1)", "duration_between(@0.time1, @0.datetime2)"];
+        for case in cases {
+            let eval = Evaluator::try_from(str_to_expr_pb(case.to_string()).unwrap()).unwrap();
+            match eval.eval::<_, Vertices>(Some(&ctxt)) {
+                Err(ExprEvalError::Unsupported(_)) => {}
+                rst => panic!("unexpected result {:?} of {}", rst, case),
+            }
+        }
+        // not a date/time value
+        let eval = Evaluator::try_from(str_to_expr_pb("year(12)".to_string()).unwrap()).unwrap();
+        match eval.eval::<_, Vertices>(Some(&ctxt)) {
+            Err(ExprEvalError::CastError(_)) => {}
+            rst => panic!("unexpected result {:?}", rst),
+        }
+    }
 }
@@ -0,0 +1,581 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+
+//! Column-wise evaluation of simple predicates over a batch of rows.
+//!
+//! A [`ColumnPredicate`] is compiled from a [`PEvaluator`] whose predicate only consists of
+//! comparisons (`==`, `!=`, `<`, `<=`, `>`, `>=`) between a property of the current element and
+//! a constant, combined by `&&`, `||` and `!`. Each referenced property is extracted once per row
+//! into a typed column buffer, and the comparisons are then evaluated over the whole column.
+//! The result is guaranteed to be identical to evaluating the predicate row by row via
+//! [`EvalPred::eval_bool`](crate::utils::expr::eval_pred::EvalPred) (treating errors as `false`).
+
+use dyn_type::{BorrowObject, Object, Primitives};
+use ir_common::generated::common as common_pb;
+use ir_common::NameOrId;
+
+use crate::apis::PropKey;
+use crate::utils::expr::eval::{apply_logical, Operand};
+use crate::utils::expr::eval_pred::{PEvaluator, Predicate, Predicates};
+
+/// The default number of rows that are evaluated together.
+pub const DEFAULT_FILTER_BATCH_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnExpr {
+    /// Compare the column of index `col` with a constant, where `const_left` indicates
+    /// the constant is the left operand of the comparison.
+    Cmp {
+        col: usize,
+        cmp: common_pb::Logical,
+        value: Object,
+        const_left: bool,
+    },
+    Not(Box<ColumnExpr>),
+    And(Box<ColumnExpr>, Box<ColumnExpr>),
+    Or(Box<ColumnExpr>, Box<ColumnExpr>),
+}
+
+/// A predicate that can be evaluated column-wise over a batch of rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnPredicate {
+    expr: ColumnExpr,
+    columns: Vec<NameOrId>,
+}
+
+impl ColumnPredicate {
+    /// Compile the predicate into a column predicate, or `None` if it contains
+    /// any operand or operator that is not supported column-wise.
+    pub fn compile(evaluator: &PEvaluator) -> Option<Self> {
+        if let PEvaluator::Predicates(pred) = evaluator {
+            let mut columns = vec![];
+            let expr = compile_predicates(pred, &mut columns)?;
+            Some(ColumnPredicate { expr, columns })
+        } else {
+            None
+        }
+    }
+
+    /// The property keys referenced by the predicate. A column index refers to this list.
+    pub fn columns(&self) -> &[NameOrId] {
+        &self.columns
+    }
+
+    /// Evaluate the predicate over `rows`, where `get_prop` extracts a property of a row.
+    /// It is invoked exactly once for each pair of row and referenced property.
+    pub fn eval_batch<T, F>(&self, rows: &[T], mut get_prop: F) -> Vec<bool>
+    where
+        F: FnMut(&T, &NameOrId) -> Option<Object>,
+    {
+        let columns: Vec<Column> = self
+            .columns
+            .iter()
+            .map(|key| {
+                let mut builder = ColumnBuilder::with_capacity(rows.len());
+                for row in rows {
+                    builder.push(get_prop(row, key));
+                }
+                builder.finish()
+            })
+            .collect();
+        eval_expr(&self.expr, &columns, rows.len())
+            .into_iter()
+            // an evaluation error of the row-wise path filters out the row
+            .map(|r| r.unwrap_or(false))
+            .collect()
+    }
+}
+
+fn compile_predicates(pred: &Predicates, columns: &mut Vec<NameOrId>) -> Option<ColumnExpr> {
+    match pred {
+        Predicates::Binary(pred) => compile_predicate(pred, columns),
+        Predicates::Not(pred) => Some(ColumnExpr::Not(Box::new(compile_predicates(pred, columns)?))),
+        Predicates::And((left, right)) => Some(ColumnExpr::And(
+            Box::new(compile_predicates(left, columns)?),
+            Box::new(compile_predicates(right, columns)?),
+        )),
+        Predicates::Or((left, right)) => Some(ColumnExpr::Or(
+            Box::new(compile_predicates(left, columns)?),
+            Box::new(compile_predicates(right, columns)?),
+        )),
+        Predicates::Init | Predicates::SingleItem(_) | Predicates::Unary(_) => None,
+    }
+}
+
+fn compile_predicate(pred: &Predicate, columns: &mut Vec<NameOrId>) -> Option<ColumnExpr> {
+    use common_pb::Logical::*;
+    if !matches!(pred.cmp, Eq | Ne | Lt | Le | Gt | Ge) {
+        return None;
+    }
+    let (key, value, const_left) = match (&pred.left, &pred.right) {
+        (Operand::Var { tag: None, prop_key: Some(PropKey::Key(key)) }, Operand::Const(value)) => {
+            (key, value, false)
+        }
+        (Operand::Const(value), Operand::Var { tag: None, prop_key: Some(PropKey::Key(key)) }) => {
+            (key, value, true)
+        }
+        _ => return None,
+    };
+    if let Object::None = value {
+        return None;
+    }
+    let col = if let Some(idx) = columns.iter().position(|k| k == key) {
+        idx
+    } else {
+        columns.push(key.clone());
+        columns.len() - 1
+    };
+    Some(ColumnExpr::Cmp { col, cmp: pred.cmp, value: value.clone(), const_left })
+}
+
+/// A column buffer of a property, where `present[i]` indicates whether the i-th row has the property.
+/// A column is typed if all present values are of the same primitive (or string) type,
+/// and otherwise falls back to keep the values as `Object`s.
+#[derive(Debug)]
+enum Column {
+    Int(Vec<i32>, Vec<bool>),
+    Long(Vec<i64>, Vec<bool>),
+    Double(Vec<f64>, Vec<bool>),
+    Str(Vec<String>, Vec<bool>),
+    Mixed(Vec<Object>),
+}
+
+struct ColumnBuilder {
+    capacity: usize,
+    column: Option<Column>,
+    // the number of leading absent rows before the type of the column is determined
+    absent: usize,
+}
+
+impl ColumnBuilder {
+    fn with_capacity(capacity: usize) -> Self {
+        ColumnBuilder { capacity, column: None, absent: 0 }
+    }
+
+    fn push(&mut self, value: Option<Object>) {
+        let value = match value {
+            Some(Object::None) | None => None,
+            v => v,
+        };
+        match (&mut self.column, value) {
+            (None, None) => self.absent += 1,
+            (None, Some(value)) => {
+                let column = self.new_column(&value);
+                self.column = Some(column);
+                self.push_to_column(Some(value));
+            }
+            (Some(_), value) => self.push_to_column(value),
+        }
+    }
+
+    fn new_column(&self, value: &Object) -> Column {
+        let (cap, absent) = (self.capacity, self.absent);
+        match value {
+            Object::Primitive(Primitives::Integer(_)) => {
+                Column::Int(Self::defaults(cap, absent), Self::defaults(cap, absent))
+            }
+            Object::Primitive(Primitives::Long(_)) => {
+                Column::Long(Self::defaults(cap, absent), Self::defaults(cap, absent))
+            }
+            Object::Primitive(Primitives::Double(_)) => {
+                Column::Double(Self::defaults(cap, absent), Self::defaults(cap, absent))
+            }
+            Object::String(_) => Column::Str(Self::defaults(cap, absent), Self::defaults(cap, absent)),
+            _ => Column::Mixed(Self::defaults_by(cap, absent, || Object::None)),
+        }
+    }
+
+    fn defaults<V: Default + Clone>(cap: usize, len: usize) -> Vec<V> {
+        Self::defaults_by(cap, len, V::default)
+    }
+
+    fn defaults_by<V, F: FnMut() -> V>(cap: usize, len: usize, f: F) -> Vec<V> {
+        let mut vec = Vec::with_capacity(cap);
+        vec.resize_with(len, f);
+        vec
+    }
+
+    fn push_to_column(&mut self, value: Option<Object>) {
+        let column = self.column.as_mut().unwrap();
+        match (column, value) {
+            (Column::Int(vals, present), None) => {
+                vals.push(0);
+                present.push(false);
+            }
+            (Column::Int(vals, present), Some(Object::Primitive(Primitives::Integer(v)))) => {
+                vals.push(v);
+                present.push(true);
+            }
+            (Column::Long(vals, present), None) => {
+                vals.push(0);
+                present.push(false);
+            }
+            (Column::Long(vals, present), Some(Object::Primitive(Primitives::Long(v)))) => {
+                vals.push(v);
+                present.push(true);
+            }
+            (Column::Double(vals, present), None) => {
+                vals.push(0.0);
+                present.push(false);
+            }
+            (Column::Double(vals, present), Some(Object::Primitive(Primitives::Double(v)))) => {
+                vals.push(v);
+                present.push(true);
+            }
+            (Column::Str(vals, present), None) => {
+                vals.push(String::new());
+                present.push(false);
+            }
+            (Column::Str(vals, present), Some(Object::String(v))) => {
+                vals.push(v);
+                present.push(true);
+            }
+            (Column::Mixed(vals), value) => vals.push(value.unwrap_or(Object::None)),
+            (_, Some(value)) => {
+                // the column turns out to be of mixed types
+                let mut mixed = self.column.take().unwrap().into_objects();
+                mixed.push(value);
+                self.column = Some(Column::Mixed(mixed));
+            }
+        }
+    }
+
+    fn finish(self) -> Column {
+        let absent = self.absent;
+        self.column
+            .unwrap_or_else(|| Column::Mixed(Self::defaults_by(absent, absent, || Object::None)))
+    }
+}
+
+impl Column {
+    fn into_objects(self) -> Vec<Object> {
+        fn to_objects<V, F: Fn(V) -> Object>(vals: Vec<V>, present: Vec<bool>, f: F) -> Vec<Object> {
+            vals.into_iter()
+                .zip(present.into_iter())
+                .map(|(v, p)| if p { f(v) } else { Object::None })
+                .collect()
+        }
+        match self {
+            Column::Int(vals, present) => to_objects(vals, present, |v| v.into()),
+            Column::Long(vals, present) => to_objects(vals, present, |v| v.into()),
+            Column::Double(vals, present) => to_objects(vals, present, |v| v.into()),
+            Column::Str(vals, present) => to_objects(vals, present, |v| v.into()),
+            Column::Mixed(vals) => vals,
+        }
+    }
+
+    fn get(&self, idx: usize) -> Option<BorrowObject<'_>> {
+        let present = match self {
+            Column::Int(_, present)
+            | Column::Long(_, present)
+            | Column::Double(_, present)
+            | Column::Str(_, present) => present[idx],
+            Column::Mixed(vals) => !matches!(vals[idx], Object::None),
+        };
+        if !present {
+            return None;
+        }
+        Some(match self {
+            Column::Int(vals, _) => vals[idx].into(),
+            Column::Long(vals, _) => vals[idx].into(),
+            Column::Double(vals, _) => vals[idx].into(),
+            Column::Str(vals, _) => BorrowObject::String(vals[idx].as_str()),
+            Column::Mixed(vals) => vals[idx].as_borrow(),
+        })
+    }
+}
+
+#[inline]
+fn compare<V: PartialOrd + ?Sized>(cmp: common_pb::Logical, a: &V, b: &V) -> bool {
+    use common_pb::Logical::*;
+    match cmp {
+        Eq => a == b,
+        Ne => a != b,
+        Lt => a < b,
+        Le => a <= b,
+        Gt => a > b,
+        Ge => a >= b,
+        _ => unreachable!(),
+    }
+}
+
+#[inline]
+fn compare_typed<'a, V: PartialOrd + ?Sized + 'a>(
+    cmp: common_pb::Logical, vals: impl Iterator<Item = &'a V>, present: &[bool], value: &V,
+    const_left: bool,
+) -> Vec<Option<bool>> {
+    vals.zip(present.iter())
+        .map(|(v, p)| Some(*p && if const_left { compare(cmp, value, v) } else { compare(cmp, v, value) }))
+        .collect()
+}
+
+fn eval_cmp(
+    column: &Column, cmp: common_pb::Logical, value: &Object, const_left: bool, len: usize,
+) -> Vec<Option<bool>> {
+    // Only the comparisons between the values of the same type are evaluated natively,
+    // as mixed-typed comparisons in `dyn_type` are not necessarily symmetric.
+    match (column, value) {
+        (Column::Int(vals, present), Object::Primitive(Primitives::Integer(c))) => {
+            compare_typed(cmp, vals.iter(), present, c, const_left)
+        }
+        (Column::Long(vals, present), Object::Primitive(Primitives::Long(c))) => {
+            compare_typed(cmp, vals.iter(), present, c, const_left)
+        }
+        (Column::Double(vals, present), Object::Primitive(Primitives::Double(c))) => {
+            compare_typed(cmp, vals.iter(), present, c, const_left)
+        }
+        (Column::Str(vals, present), Object::String(c)) => {
+            compare_typed(cmp, vals.iter().map(|v| v.as_str()), present, c.as_str(), const_left)
+        }
+        _ => (0..len)
+            .map(|idx| match column.get(idx) {
+                None => Some(false),
+                Some(v) => {
+                    let (a, b) = if const_left { (value.as_borrow(), v) } else { (v, value.as_borrow()) };
+                    apply_logical(&cmp, a, Some(b))
+                        .ok()
+                        .map(|r| r.as_bool().unwrap_or(false))
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Evaluate the expression for each row, where `None` stands for an evaluation error
+/// that is propagated the same way as the row-wise path does.
+fn eval_expr(expr: &ColumnExpr, columns: &[Column], len: usize) -> Vec<Option<bool>> {
+    match expr {
+        ColumnExpr::Cmp { col, cmp, value, const_left } => {
+            eval_cmp(&columns[*col], *cmp, value, *const_left, len)
+        }
+        ColumnExpr::Not(expr) => eval_expr(expr, columns, len)
+            .into_iter()
+            .map(|r| r.map(|b| !b))
+            .collect(),
+        ColumnExpr::And(left, right) => {
+            let left = eval_expr(left, columns, len);
+            let right = eval_expr(right, columns, len);
+            left.into_iter()
+                .zip(right.into_iter())
+                .map(|(l, r)| match l {
+                    Some(true) => r,
+                    other => other,
+                })
+                .collect()
+        }
+        ColumnExpr::Or(left, right) => {
+            let left = eval_expr(left, columns, len);
+            let right = eval_expr(right, columns, len);
+            left.into_iter()
+                .zip(right.into_iter())
+                .map(|(l, r)| match l {
+                    Some(false) => r,
+                    other => other,
+                })
+                .collect()
+        }
+    }
+}
+
+/// An iterator that pulls the items of `iter` in batches, filters them by a [`ColumnPredicate`],
+/// and only maps the passing items into the output.
+pub struct BatchFilterIter<I: Iterator, G, M, O> {
+    iter: I,
+    pred: ColumnPredicate,
+    get_prop: G,
+    map: M,
+    batch_size: usize,
+    buffer: std::vec::IntoIter<O>,
+}
+
+impl<I, G, M, O> BatchFilterIter<I, G, M, O>
+where
+    I: Iterator,
+    G: FnMut(&I::Item, &NameOrId) -> Option<Object>,
+    M: FnMut(I::Item) -> O,
+{
+    pub fn new(iter: I, pred: ColumnPredicate, get_prop: G, map: M, batch_size: usize) -> Self {
+        BatchFilterIter {
+            iter,
+            pred,
+            get_prop,
+            map,
+            batch_size: batch_size.max(1),
+            buffer: vec![].into_iter(),
+        }
+    }
+
+    fn next_batch(&mut self) -> bool {
+        let rows: Vec<I::Item> = self
+            .iter
+            .by_ref()
+            .take(self.batch_size)
+            .collect();
+        if rows.is_empty() {
+            return false;
+        }
+        let passed = self.pred.eval_batch(&rows, &mut self.get_prop);
+        let map = &mut self.map;
+        self.buffer = rows
+            .into_iter()
+            .zip(passed.into_iter())
+            .filter_map(|(row, p)| if p { Some(map(row)) } else { None })
+            .collect::<Vec<O>>()
+            .into_iter();
+        true
+    }
+}
+
+impl<I, G, M, O> Iterator for BatchFilterIter<I, G, M, O>
+where
+    I: Iterator,
+    G: FnMut(&I::Item, &NameOrId) -> Option<Object>,
+    M: FnMut(I::Item) -> O,
+{
+    type Item = O;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.buffer.next() {
+                return Some(item);
+            }
+            if !self.next_batch() {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use ahash::HashMap;
+    use ir_common::expr_parse::str_to_expr_pb;
+
+    use super::*;
+    use crate::apis::{Details, DynDetails, GraphElement, Vertex, ID};
+    use crate::utils::expr::eval_pred::EvalPred;
+
+    fn prepare_vertices() -> Vec<Vertex> {
+        (0..200)
+            .map(|i: i64| {
+                let mut props: HashMap<NameOrId, Object> = HashMap::default();
+                if i % 7 != 0 {
+                    props.insert("age".into(), ((i % 50) as i32).into());
+                }
+                if i % 5 != 0 {
+                    props.insert("weight".into(), (i as f64 / 3.0).into());
+                }
+                props.insert("id".into(), (i * 1000).into());
+                props.insert("name".into(), format!("name_{}", i % 13).into());
+                // a column of mixed types
+                if i % 2 == 0 {
+                    props.insert("mixed".into(), ((i % 30) as i32).into());
+                } else {
+                    props.insert("mixed".into(), (i % 30).into());
+                }
+                Vertex::new(i, Some(1.into()), DynDetails::new(props))
+            })
+            .collect()
+    }
+
+    fn get_prop(v: &Vertex, key: &NameOrId) -> Option<Object> {
+        v.get_details()
+            .get_property(key)
+            .and_then(|p| p.try_to_owned())
+    }
+
+    fn check(expr: &str, expect_supported: bool) {
+        let evaluator = PEvaluator::try_from(str_to_expr_pb(expr.to_string()).unwrap()).unwrap();
+        let pred = ColumnPredicate::compile(&evaluator);
+        assert_eq!(pred.is_some(), expect_supported, "{}", expr);
+        if let Some(pred) = pred {
+            let vertices = prepare_vertices();
+            let expected: Vec<ID> = vertices
+                .iter()
+                .filter(|v| evaluator.eval_bool(Some(*v)).unwrap_or(false))
+                .map(|v| v.id())
+                .collect();
+            for batch_size in vec![1, 7, 64, 1000] {
+                let results: Vec<ID> = BatchFilterIter::new(
+                    prepare_vertices().into_iter(),
+                    pred.clone(),
+                    get_prop,
+                    |v| v.id(),
+                    batch_size,
+                )
+                .collect();
+                assert_eq!(results, expected, "{} with batch size {}", expr, batch_size);
+            }
+        }
+    }
+
+    #[test]
+    fn column_predicate_compile() {
+        let evaluator = PEvaluator::try_from(
+            str_to_expr_pb("@.age > 10 && (@.name == \"name_1\" || 20 >= @.age)".to_string()).unwrap(),
+        )
+        .unwrap();
+        let pred = ColumnPredicate::compile(&evaluator).unwrap();
+        assert_eq!(pred.columns(), &["age".into(), "name".into()]);
+
+        // unsupported operators or operands
+        check("@.name within [\"name_1\", \"name_2\"]", false);
+        check("@.name StartsWith \"name\"", false);
+        check("isNull @.age", false);
+        check("@a.age > 10", false);
+        check("@.age > @.id", false);
+        check("@.age + 1 > 10", false);
+    }
+
+    #[test]
+    fn column_predicate_eval_typed() {
+        for op in vec!["==", "!=", "<", "<=", ">", ">="] {
+            check(&format!("@.age {} 25", op), true);
+            check(&format!("25 {} @.age", op), true);
+            check(&format!("@.id {} 50000", op), true);
+            check(&format!("@.weight {} 20.5", op), true);
+            check(&format!("@.name {} \"name_5\"", op), true);
+            check(&format!("\"name_5\" {} @.name", op), true);
+        }
+    }
+
+    #[test]
+    fn column_predicate_eval_mixed() {
+        for op in vec!["==", "!=", "<", "<=", ">", ">="] {
+            // comparisons between different types
+            check(&format!("@.age {} 25.5", op), true);
+            check(&format!("@.id {} 7", op), true);
+            check(&format!("@.weight {} 20", op), true);
+            check(&format!("@.name {} 5", op), true);
+            check(&format!("@.mixed {} 15", op), true);
+            check(&format!("15 {} @.mixed", op), true);
+            // a property that does not exist
+            check(&format!("@.unknown {} 15", op), true);
+        }
+    }
+
+    #[test]
+    fn column_predicate_eval_logical() {
+        check("@.age > 10 && @.weight < 30.0", true);
+        check("@.age > 10 || @.weight < 30.0", true);
+        check("!(@.age > 10)", true);
+        check("!(@.age > 10 && @.weight < 30.0)", true);
+        check("!(@.age > 10) || !(@.unknown == 1)", true);
+        check("(@.age == 1 || @.name == \"name_3\") && !(@.id >= 100000)", true);
+    }
+}
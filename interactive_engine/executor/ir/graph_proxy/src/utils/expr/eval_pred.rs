@@ -15,12 +15,16 @@
 //!
 
 use std::convert::{TryFrom, TryInto};
+use std::sync::Arc;
 
-use dyn_type::{BorrowObject, Object};
+use ahash::HashSet;
+use dyn_type::object::RawType;
+use dyn_type::{BorrowObject, Object, Primitives};
 use ir_common::error::{ParsePbError, ParsePbResult};
 use ir_common::expr_parse::to_suffix_expr;
 use ir_common::generated::algebra as pb;
 use ir_common::generated::common as common_pb;
+use ir_common::NameOrId;
 
 use crate::apis::{Element, PropKey};
 use crate::utils::expr::eval::{apply_logical, Context, Evaluate, Evaluator, Operand};
@@ -37,11 +41,99 @@ pub struct UnaryPredicate {
     pub(crate) cmp: common_pb::Logical,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Predicate {
     pub(crate) left: Operand,
     pub(crate) cmp: common_pb::Logical,
     pub(crate) right: Operand,
+    /// The elements of the right operand of `within` or `without` hashed in advance, if it is a
+    /// large enough constant collection, e.g., the ids given by a subquery.
+    hashed_right: Option<Arc<HashedCollection>>,
+}
+
+impl PartialEq for Predicate {
+    fn eq(&self, other: &Self) -> bool {
+        // `hashed_right` is derived from `cmp` and `right`
+        self.left == other.left && self.cmp == other.cmp && self.right == other.right
+    }
+}
+
+/// The constant collections of at least this many elements are hashed for `within` and `without`,
+/// rather than searched linearly for each record.
+pub const HASHED_COLLECTION_MIN_SIZE: usize = 32;
+
+/// A constant collection hashed for the membership test. The integers are widened into `i64` (or `u128`
+/// beyond `i64`) as the keys, so that, e.g., an `I32` id hits the `I64` ids in the collection, as they
+/// are equal when compared by `==`.
+pub(crate) struct HashedCollection(HashSet<Object>);
+
+impl std::fmt::Debug for HashedCollection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HashedCollection(len: {})", self.0.len())
+    }
+}
+
+impl HashedCollection {
+    /// Hash the elements of the collection, or `None` if some element cannot be hashed consistently
+    /// with `==`, e.g., a float, which is then searched linearly as before.
+    fn new(collection: &[Object]) -> Option<Self> {
+        let mut set = HashSet::with_capacity_and_hasher(collection.len(), Default::default());
+        for obj in collection {
+            set.insert(Self::key_of(obj)?);
+        }
+        Some(HashedCollection(set))
+    }
+
+    fn key_of(obj: &Object) -> Option<Object> {
+        match obj {
+            Object::Primitive(Primitives::Float(_)) | Object::Primitive(Primitives::Double(_)) => None,
+            Object::Primitive(p) => p
+                .as_i64()
+                .map(Object::from)
+                .or_else(|_| p.as_u128().map(Object::from))
+                .ok(),
+            Object::String(_) => Some(obj.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether the object is in the collection, or `None` if it cannot be looked up by hashing.
+    /// As `Object::contains()`, a vector is contained if all of its elements are contained.
+    fn contains(&self, obj: &Object) -> Option<bool> {
+        match obj {
+            Object::Vector(vec) => {
+                for val in vec {
+                    if !self.0.contains(&Self::key_of(val)?) {
+                        return Some(false);
+                    }
+                }
+                Some(true)
+            }
+            _ => Some(self.0.contains(&Self::key_of(obj)?)),
+        }
+    }
+}
+
+impl Predicate {
+    /// Build the predicate of `left cmp right`, where a large constant collection on the right of
+    /// `within` or `without` is hashed once here, rather than for each record to filter.
+    pub fn new(left: Operand, cmp: common_pb::Logical, right: Operand) -> Self {
+        let hashed_right = match (cmp, &right) {
+            (common_pb::Logical::Within, Operand::Const(Object::Vector(vec)))
+            | (common_pb::Logical::Without, Operand::Const(Object::Vector(vec)))
+                if vec.len() >= HASHED_COLLECTION_MIN_SIZE =>
+            {
+                HashedCollection::new(vec).map(Arc::new)
+            }
+            _ => None,
+        };
+        Predicate { left, cmp, right, hashed_right }
+    }
+
+    /// Whether the right operand is hashed for `within` or `without`.
+    pub fn is_hashed(&self) -> bool {
+        self.hashed_right.is_some()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -76,18 +168,16 @@ impl TryFrom<pb::index_predicate::Triplet> for Predicates {
         } else {
             None
         };
-        let predicate = Predicate {
-            left: triplet
-                .key
-                .map(|var| var.try_into())
-                .transpose()?
-                .ok_or_else(|| ParsePbError::ParseError("missing key in `Triplet`".to_string()))?,
-            cmp: unsafe { std::mem::transmute(triplet.cmp) },
-            right: value
-                .map(|val| val.try_into())
-                .transpose()?
-                .ok_or_else(|| ParsePbError::ParseError("missing value in `Triplet`".to_string()))?,
-        };
+        let left = triplet
+            .key
+            .map(|var| var.try_into())
+            .transpose()?
+            .ok_or_else(|| ParsePbError::ParseError("missing key in `Triplet`".to_string()))?;
+        let right = value
+            .map(|val| val.try_into())
+            .transpose()?
+            .ok_or_else(|| ParsePbError::ParseError("missing value in `Triplet`".to_string()))?;
+        let predicate = Predicate::new(left, unsafe { std::mem::transmute(triplet.cmp) }, right);
         Ok(Predicates::Binary(predicate))
     }
 }
@@ -258,6 +348,14 @@ impl EvalPred for UnaryPredicate {
 
 impl EvalPred for Predicate {
     fn eval_bool<E: Element, C: Context<E>>(&self, context: Option<&C>) -> ExprEvalResult<bool> {
+        Ok(self.eval_tri(context)?.unwrap_or(false))
+    }
+}
+
+impl Predicate {
+    /// Evaluate the comparison in the three-valued logic, which is `Unknown` (i.e., `None`) if either
+    /// operand is missing, e.g., a property that the vertex does not have.
+    fn eval_tri<E: Element, C: Context<E>>(&self, context: Option<&C>) -> ExprEvalResult<Option<bool>> {
         use common_pb::Logical;
         match self.cmp {
             Logical::Eq
@@ -270,13 +368,33 @@ impl EvalPred for Predicate {
             | Logical::Without
             | Logical::Startswith
             | Logical::Endswith
-            | Logical::Regex => Ok(apply_logical(
-                &self.cmp,
-                self.left.eval(context)?.as_borrow_object(),
-                Some(self.right.eval(context)?.as_borrow_object()),
-            )?
-            .as_bool()
-            .unwrap_or(false)),
+            | Logical::Regex => {
+                if let Some(hashed_right) = &self.hashed_right {
+                    let left = eval_or_unknown(&self.left, context)?;
+                    if left.raw_type() == RawType::None {
+                        return Ok(None);
+                    }
+                    if let Some(contained) = hashed_right.contains(&left) {
+                        return Ok(Some(if self.cmp == Logical::Within { contained } else { !contained }));
+                    }
+                }
+                let (left, right) =
+                    (eval_or_unknown(&self.left, context)?, eval_or_unknown(&self.right, context)?);
+                if left.raw_type() == RawType::None || right.raw_type() == RawType::None {
+                    return Ok(None);
+                }
+                let (left, right) = match self.cmp {
+                    Logical::Eq | Logical::Ne | Logical::Lt | Logical::Le | Logical::Gt | Logical::Ge => {
+                        coerce_numeric_operands(left, right)
+                    }
+                    _ => (left, right),
+                };
+                Ok(Some(
+                    apply_logical(&self.cmp, left.as_borrow_object(), Some(right.as_borrow_object()))?
+                        .as_bool()
+                        .unwrap_or(false),
+                ))
+            }
             _ => Err(ExprEvalError::OtherErr(format!(
                 "invalid logical operator: {:?} in a binary predicate",
                 self.cmp
@@ -285,21 +403,109 @@ impl EvalPred for Predicate {
     }
 }
 
+/// Evaluate the operand, where the absence of the tag in the context gives `Object::None`,
+/// the same as the absence of the property.
+fn eval_or_unknown<E: Element, C: Context<E>>(
+    operand: &Operand, context: Option<&C>,
+) -> ExprEvalResult<Object> {
+    match operand.eval(context) {
+        Err(ExprEvalError::GetNoneFromContext) => Ok(Object::None),
+        result => result,
+    }
+}
+
+fn numeric_type(obj: &Object) -> Option<common_pb::PrimitiveType> {
+    match obj.raw_type() {
+        RawType::Integer => Some(common_pb::PrimitiveType::DtSignedInt32),
+        RawType::UInt => Some(common_pb::PrimitiveType::DtUnsignedInt32),
+        RawType::Long => Some(common_pb::PrimitiveType::DtSignedInt64),
+        RawType::ULong => Some(common_pb::PrimitiveType::DtUnsignedInt64),
+        RawType::Float => Some(common_pb::PrimitiveType::DtFloat),
+        RawType::Double => Some(common_pb::PrimitiveType::DtDouble),
+        _ => None,
+    }
+}
+
+fn coerce_object(obj: &Object, target: common_pb::PrimitiveType) -> Option<Object> {
+    let target =
+        common_pb::DataType { item: Some(common_pb::data_type::Item::PrimitiveType(target as i32)) };
+    common_pb::Value::try_from(obj.clone())
+        .ok()?
+        .coerce_to(target)
+        .ok()
+        .and_then(|value| Object::try_from(value).ok())
+}
+
+/// When comparing numbers of different types, e.g., an `I32` property against an `I64` literal,
+/// widen the narrower operand into the type of the other one, or both into a common wider type,
+/// so that they are compared by value rather than by a (possibly overflowing) cast. The operands
+/// are left as they are if they cannot be widened.
+fn coerce_numeric_operands(left: Object, right: Object) -> (Object, Object) {
+    if let (Some(left_type), Some(right_type)) = (numeric_type(&left), numeric_type(&right)) {
+        if left_type != right_type {
+            if let Some(left) = coerce_object(&left, right_type) {
+                return (left, right);
+            }
+            if let Some(right) = coerce_object(&right, left_type) {
+                return (left, right);
+            }
+            // e.g., `I32` against `U32`, where both must be widened
+            for common_type in [common_pb::PrimitiveType::DtSignedInt64, common_pb::PrimitiveType::DtDouble]
+            {
+                if let (Some(left), Some(right)) =
+                    (coerce_object(&left, common_type), coerce_object(&right, common_type))
+                {
+                    return (left, right);
+                }
+            }
+        }
+    }
+    (left, right)
+}
+
+/// The predicates are evaluated in the three-valued logic as of SQL, where a comparison against a
+/// missing property (or a missing tag) is `Unknown`, and `Not`, `And` and `Or` follow the truth tables
+/// of `Unknown`, e.g., `Unknown || true` is `true` while `!Unknown` is still `Unknown`. An `Unknown` of
+/// the whole predicates filters the record out, as `false` does.
+/// Note that a single item, e.g., `@.name`, checks the existence of the property, which is never `Unknown`,
+/// and so is `isNull`.
 impl EvalPred for Predicates {
     fn eval_bool<E: Element, C: Context<E>>(&self, context: Option<&C>) -> ExprEvalResult<bool> {
-        match self {
-            Predicates::Init => Ok(false),
-            Predicates::SingleItem(item) => item.eval_bool(context),
-            Predicates::Unary(upred) => upred.eval_bool(context),
-            Predicates::Binary(pred) => pred.eval_bool(context),
-            Predicates::Not(pred) => Ok(!pred.eval_bool(context)?),
-            Predicates::And((pred1, pred2)) => Ok(pred1.eval_bool(context)? && pred2.eval_bool(context)?),
-            Predicates::Or((pred1, pred2)) => Ok(pred1.eval_bool(context)? || pred2.eval_bool(context)?),
-        }
+        Ok(self.eval_tri(context)?.unwrap_or(false))
     }
 }
 
 impl Predicates {
+    /// Evaluate the predicates in the three-valued logic, where `None` stands for `Unknown`.
+    pub(crate) fn eval_tri<E: Element, C: Context<E>>(
+        &self, context: Option<&C>,
+    ) -> ExprEvalResult<Option<bool>> {
+        match self {
+            Predicates::Init => Ok(Some(false)),
+            Predicates::SingleItem(Operand::Const(Object::None)) => Ok(None),
+            Predicates::SingleItem(item) => Ok(Some(item.eval_bool(context)?)),
+            Predicates::Unary(upred) => Ok(Some(upred.eval_bool(context)?)),
+            Predicates::Binary(pred) => pred.eval_tri(context),
+            Predicates::Not(pred) => Ok(pred.eval_tri(context)?.map(|b| !b)),
+            Predicates::And((pred1, pred2)) => match pred1.eval_tri(context)? {
+                Some(false) => Ok(Some(false)),
+                left => match pred2.eval_tri(context)? {
+                    Some(false) => Ok(Some(false)),
+                    Some(true) => Ok(left),
+                    None => Ok(None),
+                },
+            },
+            Predicates::Or((pred1, pred2)) => match pred1.eval_tri(context)? {
+                Some(true) => Ok(Some(true)),
+                left => match pred2.eval_tri(context)? {
+                    Some(true) => Ok(Some(true)),
+                    Some(false) => Ok(left),
+                    None => Ok(None),
+                },
+            },
+        }
+    }
+
     pub fn not(self) -> Self {
         Predicates::Not(Box::new(self))
     }
@@ -326,6 +532,21 @@ impl Predicates {
             _ => None,
         }
     }
+
+    fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Predicates::Init => vec![],
+            Predicates::SingleItem(operand) => vec![operand],
+            Predicates::Unary(upred) => vec![&upred.operand],
+            Predicates::Binary(pred) => vec![&pred.left, &pred.right],
+            Predicates::Not(pred) => pred.operands(),
+            Predicates::And((left, right)) | Predicates::Or((left, right)) => {
+                let mut operands = left.operands();
+                operands.extend(right.operands());
+                operands
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -376,11 +597,7 @@ fn process_predicates(rpn_tokens: Vec<common_pb::ExprOpr>) -> ParsePbResult<Pred
                                         "missing left operand for binary operator".to_string(),
                                     )
                                 })?;
-                            stack.push(Predicates::Binary(Predicate {
-                                left: lhs,
-                                cmp: logical,
-                                right: rhs,
-                            }));
+                            stack.push(Predicates::Binary(Predicate::new(lhs, logical, rhs)));
                         }
                         Logical::Isnull => {
                             // unary operator
@@ -494,12 +711,73 @@ impl TryFrom<pb::IndexPredicate> for PEvaluator {
     }
 }
 
+/// Get the property names of the current entity that are used in the filter, for the storage that
+/// accesses properties by names rather than by ids. `None` means that no property is referred by name.
+pub fn extract_prop_names(filter: &Arc<PEvaluator>) -> Option<Vec<NameOrId>> {
+    let operands = match filter.as_ref() {
+        PEvaluator::Predicates(preds) => preds.operands(),
+        // e.g., a filter calling functions, of which the arguments refer to properties
+        PEvaluator::General(eval) => eval.operands(),
+    };
+    let mut prop_names = vec![];
+    for operand in operands {
+        if let Operand::Var { tag: None, prop_key: Some(PropKey::Key(NameOrId::Str(name))) } = operand {
+            let prop_name = NameOrId::Str(name.clone());
+            if !prop_names.contains(&prop_name) {
+                prop_names.push(prop_name);
+            }
+        }
+    }
+    if prop_names.is_empty() {
+        None
+    } else {
+        Some(prop_names)
+    }
+}
+
+/// Get the constant values that the properties of the current entity are restricted to by the filter,
+/// i.e., the conjuncts of the filter of `@.key == value`, or `@.key within [values]`.
+pub fn extract_prop_equalities(filter: &PEvaluator) -> Vec<(NameOrId, Vec<Object>)> {
+    let mut equalities = vec![];
+    if let PEvaluator::Predicates(preds) = filter {
+        collect_prop_equalities(preds, &mut equalities);
+    }
+    equalities
+}
+
+fn collect_prop_equalities(preds: &Predicates, equalities: &mut Vec<(NameOrId, Vec<Object>)>) {
+    match preds {
+        Predicates::And((left, right)) => {
+            collect_prop_equalities(left, equalities);
+            collect_prop_equalities(right, equalities);
+        }
+        Predicates::Binary(pred) => match (&pred.left, pred.cmp, &pred.right) {
+            (
+                Operand::Var { tag: None, prop_key: Some(PropKey::Key(key)) },
+                common_pb::Logical::Eq,
+                Operand::Const(value),
+            )
+            | (
+                Operand::Const(value),
+                common_pb::Logical::Eq,
+                Operand::Var { tag: None, prop_key: Some(PropKey::Key(key)) },
+            ) => equalities.push((key.clone(), vec![value.clone()])),
+            (
+                Operand::Var { tag: None, prop_key: Some(PropKey::Key(key)) },
+                common_pb::Logical::Within,
+                Operand::Const(Object::Vector(values)),
+            ) => equalities.push((key.clone(), values.clone())),
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ahash::HashMap;
     use dyn_type::object;
     use ir_common::expr_parse::str_to_expr_pb;
-    use ir_common::NameOrId;
 
     use super::*;
     use crate::apis::{DynDetails, Vertex};
@@ -602,11 +880,11 @@ mod tests {
             PEvaluator::Predicates(pred) => {
                 assert_eq!(
                     pred.clone(),
-                    Predicates::Binary(Predicate {
-                        left: Operand::Const(object!(1)),
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(object!(2)),
-                    })
+                    Predicates::Binary(Predicate::new(
+                        Operand::Const(object!(1)),
+                        common_pb::Logical::Gt,
+                        Operand::Const(object!(2))
+                    ))
                 );
             }
             PEvaluator::General(_) => panic!("should be predicate"),
@@ -621,11 +899,11 @@ mod tests {
             PEvaluator::Predicates(pred) => {
                 assert_eq!(
                     pred.clone(),
-                    Predicates::Binary(Predicate {
-                        left: Operand::Const(object!(1)),
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(object!(2)),
-                    })
+                    Predicates::Binary(Predicate::new(
+                        Operand::Const(object!(1)),
+                        common_pb::Logical::Gt,
+                        Operand::Const(object!(2))
+                    ))
                     .not()
                 );
             }
@@ -641,11 +919,11 @@ mod tests {
             PEvaluator::Predicates(pred) => {
                 assert_eq!(
                     pred.clone(),
-                    Predicates::Binary(Predicate {
-                        left: Operand::Const(object!(1)),
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(object!(2)),
-                    })
+                    Predicates::Binary(Predicate::new(
+                        Operand::Const(object!(1)),
+                        common_pb::Logical::Gt,
+                        Operand::Const(object!(2))
+                    ))
                     .not()
                     .not()
                     .not()
@@ -664,11 +942,13 @@ mod tests {
             PEvaluator::Predicates(pred) => {
                 assert_eq!(
                     pred.clone(),
-                    Predicates::SingleItem(Operand::Const(object!(1))).and(Predicates::Binary(Predicate {
-                        left: Operand::Const(object!(1)),
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(object!(2)),
-                    }))
+                    Predicates::SingleItem(Operand::Const(object!(1))).and(Predicates::Binary(
+                        Predicate::new(
+                            Operand::Const(object!(1)),
+                            common_pb::Logical::Gt,
+                            Operand::Const(object!(2))
+                        )
+                    ))
                 );
             }
             PEvaluator::General(_) => panic!("should be predicate"),
@@ -687,19 +967,16 @@ mod tests {
                         tag: Some("a".into()),
                         prop_key: Some(PropKey::Key("name".into()))
                     }))
-                    .and(Predicates::Binary(Predicate {
-                        left: Operand::Var {
-                            tag: Some("a".into()),
-                            prop_key: Some(PropKey::Key("age".into()))
-                        },
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(object!(2)),
-                    }))
-                    .or(Predicates::Binary(Predicate {
-                        left: Operand::Var { tag: Some("b".into()), prop_key: Some(PropKey::Id) },
-                        cmp: common_pb::Logical::Eq,
-                        right: Operand::Const(object!(10)),
-                    }))
+                    .and(Predicates::Binary(Predicate::new(
+                        Operand::Var { tag: Some("a".into()), prop_key: Some(PropKey::Key("age".into())) },
+                        common_pb::Logical::Gt,
+                        Operand::Const(object!(2))
+                    )))
+                    .or(Predicates::Binary(Predicate::new(
+                        Operand::Var { tag: Some("b".into()), prop_key: Some(PropKey::Id) },
+                        common_pb::Logical::Eq,
+                        Operand::Const(object!(10))
+                    )))
                 );
             }
             PEvaluator::General(_) => panic!("should be predicate"),
@@ -713,35 +990,26 @@ mod tests {
         match &p_eval {
             PEvaluator::Predicates(pred) => assert_eq!(
                 pred.clone(),
-                Predicates::Binary(Predicate {
-                    left: Operand::Var {
-                        tag: Some("a".into()),
-                        prop_key: Some(PropKey::Key("name".into()))
-                    },
-                    cmp: common_pb::Logical::Eq,
-                    right: Operand::Const(object!("John")),
-                })
-                .and(Predicates::Binary(Predicate {
-                    left: Operand::Var {
-                        tag: Some("a".into()),
-                        prop_key: Some(PropKey::Key("age".into()))
-                    },
-                    cmp: common_pb::Logical::Gt,
-                    right: Operand::Const(27_i64.into()),
-                }))
-                .or(Predicates::Binary(Predicate {
-                    left: Operand::Var { tag: Some("b".into()), prop_key: Some(PropKey::Label) },
-                    cmp: common_pb::Logical::Eq,
-                    right: Operand::Const(11_i64.into()),
-                })
-                .and(Predicates::Binary(Predicate {
-                    left: Operand::Var {
-                        tag: Some("b".into()),
-                        prop_key: Some(PropKey::Key("name".into()))
-                    },
-                    cmp: common_pb::Logical::Eq,
-                    right: Operand::Const(object!("Alien")),
-                })))
+                Predicates::Binary(Predicate::new(
+                    Operand::Var { tag: Some("a".into()), prop_key: Some(PropKey::Key("name".into())) },
+                    common_pb::Logical::Eq,
+                    Operand::Const(object!("John"))
+                ))
+                .and(Predicates::Binary(Predicate::new(
+                    Operand::Var { tag: Some("a".into()), prop_key: Some(PropKey::Key("age".into())) },
+                    common_pb::Logical::Gt,
+                    Operand::Const(27_i64.into())
+                )))
+                .or(Predicates::Binary(Predicate::new(
+                    Operand::Var { tag: Some("b".into()), prop_key: Some(PropKey::Label) },
+                    common_pb::Logical::Eq,
+                    Operand::Const(11_i64.into())
+                ))
+                .and(Predicates::Binary(Predicate::new(
+                    Operand::Var { tag: Some("b".into()), prop_key: Some(PropKey::Key("name".into())) },
+                    common_pb::Logical::Eq,
+                    Operand::Const(object!("Alien"))
+                ))))
             ),
             PEvaluator::General(_) => panic!("should be predicate"),
         }
@@ -754,35 +1022,26 @@ mod tests {
         match &p_eval {
             PEvaluator::Predicates(pred) => assert_eq!(
                 pred.clone(),
-                Predicates::Binary(Predicate {
-                    left: Operand::Var {
-                        tag: Some("a".into()),
-                        prop_key: Some(PropKey::Key("name".into()))
-                    },
-                    cmp: common_pb::Logical::Eq,
-                    right: Operand::Const(object!("John")),
-                })
+                Predicates::Binary(Predicate::new(
+                    Operand::Var { tag: Some("a".into()), prop_key: Some(PropKey::Key("name".into())) },
+                    common_pb::Logical::Eq,
+                    Operand::Const(object!("John"))
+                ))
                 .and(
-                    Predicates::Binary(Predicate {
-                        left: Operand::Var {
-                            tag: Some("a".into()),
-                            prop_key: Some(PropKey::Key("age".into()))
-                        },
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(27_i64.into()),
-                    })
+                    Predicates::Binary(Predicate::new(
+                        Operand::Var { tag: Some("a".into()), prop_key: Some(PropKey::Key("age".into())) },
+                        common_pb::Logical::Gt,
+                        Operand::Const(27_i64.into())
+                    ))
                     .or(Predicates::not(Predicates::SingleItem(Operand::Var {
                         tag: Some("b".into()),
                         prop_key: Some(PropKey::Label)
                     })))
-                    .and(Predicates::Binary(Predicate {
-                        left: Operand::Var {
-                            tag: Some("b".into()),
-                            prop_key: Some(PropKey::Key("name".into()))
-                        },
-                        cmp: common_pb::Logical::Eq,
-                        right: Operand::Const(object!("Alien")),
-                    }))
+                    .and(Predicates::Binary(Predicate::new(
+                        Operand::Var { tag: Some("b".into()), prop_key: Some(PropKey::Key("name".into())) },
+                        common_pb::Logical::Eq,
+                        Operand::Const(object!("Alien"))
+                    )))
                 )
             ),
             PEvaluator::General(_) => panic!("should be predicate"),
@@ -819,14 +1078,11 @@ mod tests {
                         },
                         cmp: common_pb::Logical::Isnull,
                     })
-                    .and(Predicates::Binary(Predicate {
-                        left: Operand::Var {
-                            tag: Some("a".into()),
-                            prop_key: Some(PropKey::Key("age".into()))
-                        },
-                        cmp: common_pb::Logical::Gt,
-                        right: Operand::Const(object!(2)),
-                    }))
+                    .and(Predicates::Binary(Predicate::new(
+                        Operand::Var { tag: Some("a".into()), prop_key: Some(PropKey::Key("age".into())) },
+                        common_pb::Logical::Gt,
+                        Operand::Const(object!(2))
+                    )))
                     .or(Predicates::Unary(UnaryPredicate {
                         operand: Operand::Var {
                             tag: Some("b".into()),
@@ -920,6 +1176,54 @@ mod tests {
             .unwrap());
     }
 
+    #[test]
+    fn test_eval_predicate_mixed_numeric_types() {
+        // [v0: age = 31 (i32)], [v1: age = 26 (i32)]
+        let context = prepare_context();
+        let cases: Vec<(&str, bool)> = vec![
+            // i32 property against an i64 literal beyond the range of i32
+            ("@0.age < 10000000000", true),
+            ("@0.age > -10000000000", true),
+            ("@0.age == 10000000000", false),
+            ("@0.age != 10000000000", true),
+            // i32 property against a double literal
+            ("@0.age == 31.0", true),
+            ("@1.age < 26.5", true),
+            ("@1.age >= 26.5", false),
+        ];
+        for (expr_str, expected) in cases {
+            let expr = str_to_expr_pb(expr_str.to_string()).unwrap();
+            let p_eval = PEvaluator::try_from(expr).unwrap();
+            assert_eq!(
+                p_eval
+                    .eval_bool::<_, Vertices>(Some(&context))
+                    .unwrap(),
+                expected,
+                "{}",
+                expr_str
+            );
+        }
+
+        // f32/u32/u64 properties against i64/f64 constants
+        let predicate = |left: Object, cmp: common_pb::Logical, right: Object| {
+            Predicate::new(Operand::Const(left), cmp, Operand::Const(right))
+        };
+        let cases = vec![
+            (predicate(Object::from(0.5_f32), common_pb::Logical::Eq, 0.5_f64.into()), true),
+            (predicate(Object::from(u32::MAX), common_pb::Logical::Lt, i64::MAX.into()), true),
+            (predicate(Object::from(u32::MAX), common_pb::Logical::Gt, (-1_i64).into()), true),
+            (
+                predicate(Object::from(1_u64 << 40), common_pb::Logical::Eq, ((1_u64 << 40) as f64).into()),
+                true,
+            ),
+            (predicate(Object::from(-1_i32), common_pb::Logical::Lt, u32::MAX.into()), true),
+            (predicate(Object::from(-1_i32), common_pb::Logical::Lt, u64::MAX.into()), true),
+        ];
+        for (pred, expected) in cases {
+            assert_eq!(pred.eval_bool::<_, Vertices>(None).unwrap(), expected, "{:?}", pred);
+        }
+    }
+
     #[test]
     fn test_eval_predicates_is_null() {
         // [v0: id = 1, label = 9, age = 31, name = John, birthday = 19900416, hobbies = [football, guitar]]
@@ -946,6 +1250,227 @@ mod tests {
         }
     }
 
+    // the truth values of `@1.age == 26`, `@1.age > 30` and a comparison against a missing property
+    const TRI_CASES: [(&str, Option<bool>); 3] =
+        [("@1.age == 26", Some(true)), ("@1.age > 30", Some(false)), ("@1.none_exist > 20", None)];
+
+    fn predicates_of(expr: &str) -> Predicates {
+        match PEvaluator::try_from(str_to_expr_pb(expr.to_string()).unwrap()).unwrap() {
+            PEvaluator::Predicates(preds) => preds,
+            PEvaluator::General(_) => panic!("expect predicates for {:?}", expr),
+        }
+    }
+
+    fn eval_filter(expr: &str, context: &Vertices) -> bool {
+        let eval = PEvaluator::try_from(str_to_expr_pb(expr.to_string()).unwrap()).unwrap();
+        eval.eval_bool::<_, Vertices>(Some(context))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_eval_predicates_three_valued_not() {
+        let ctxt = prepare_context();
+        for (expr, value) in TRI_CASES {
+            let not = predicates_of(expr).not();
+            let expected = value.map(|b| !b);
+            assert_eq!(
+                not.eval_tri::<_, Vertices>(Some(&ctxt))
+                    .unwrap(),
+                expected,
+                "!({})",
+                expr
+            );
+            // an unknown filters the record out
+            assert_eq!(
+                not.eval_bool::<_, Vertices>(Some(&ctxt))
+                    .unwrap(),
+                expected.unwrap_or(false)
+            );
+            // the same as the evaluator of the expression
+            assert_eq!(
+                eval_filter(&format!("!({})", expr), &ctxt),
+                expected.unwrap_or(false),
+                "!({})",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_predicates_three_valued_and_or() {
+        let ctxt = prepare_context();
+        for (left, left_value) in TRI_CASES {
+            for (right, right_value) in TRI_CASES {
+                let expected_and = match (left_value, right_value) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                };
+                let expected_or = match (left_value, right_value) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                };
+                let and = predicates_of(left).and(predicates_of(right));
+                let or = predicates_of(left).or(predicates_of(right));
+                assert_eq!(
+                    and.eval_tri::<_, Vertices>(Some(&ctxt))
+                        .unwrap(),
+                    expected_and
+                );
+                assert_eq!(or.eval_tri::<_, Vertices>(Some(&ctxt)).unwrap(), expected_or);
+                // the negations
+                assert_eq!(
+                    and.not()
+                        .eval_tri::<_, Vertices>(Some(&ctxt))
+                        .unwrap(),
+                    expected_and.map(|b| !b)
+                );
+                assert_eq!(
+                    or.not()
+                        .eval_tri::<_, Vertices>(Some(&ctxt))
+                        .unwrap(),
+                    expected_or.map(|b| !b)
+                );
+
+                // the same as evaluating the expressions, where the bracketed ones fall back to the general evaluator
+                let and_expr = format!("{} && {}", left, right);
+                let or_expr = format!("{} || {}", left, right);
+                assert_eq!(eval_filter(&and_expr, &ctxt), expected_and.unwrap_or(false), "{}", and_expr);
+                assert_eq!(eval_filter(&or_expr, &ctxt), expected_or.unwrap_or(false), "{}", or_expr);
+                for (expr, expected) in [
+                    (format!("({}) && ({})", left, right), expected_and),
+                    (format!("({}) || ({})", left, right), expected_or),
+                    (format!("!(({}) && ({}))", left, right), expected_and.map(|b| !b)),
+                    (format!("!(({}) || ({}))", left, right), expected_or.map(|b| !b)),
+                ] {
+                    assert_eq!(eval_filter(&expr, &ctxt), expected.unwrap_or(false), "{}", expr);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_predicates_missing_tag_as_unknown() {
+        let ctxt = prepare_context();
+        // tag 2 does not present, which is unknown rather than an error
+        let unknown = predicates_of("@2.age > 20");
+        assert_eq!(
+            unknown
+                .eval_tri::<_, Vertices>(Some(&ctxt))
+                .unwrap(),
+            None
+        );
+        let or = unknown
+            .clone()
+            .or(predicates_of("@1.age == 26"));
+        assert!(or
+            .eval_bool::<_, Vertices>(Some(&ctxt))
+            .unwrap());
+        assert!(!unknown
+            .not()
+            .eval_bool::<_, Vertices>(Some(&ctxt))
+            .unwrap());
+        // the existence of a property is never unknown
+        let exists = predicates_of("@1.none_exist");
+        assert_eq!(
+            exists
+                .clone()
+                .not()
+                .eval_tri::<_, Vertices>(Some(&ctxt))
+                .unwrap(),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_eval_within_hashed_collection() {
+        let ctxt = prepare_context();
+        let var =
+            |tag: i32, prop_key: PropKey| Operand::Var { tag: Some(tag.into()), prop_key: Some(prop_key) };
+        let age = || var(0, PropKey::Key("age".into()));
+        let large_ids: Vec<i64> = (30..30 + HASHED_COLLECTION_MIN_SIZE as i64).collect();
+        let cases = vec![
+            // an `I32` property within `I64`s
+            (age(), Object::from(large_ids.clone()), Some(true)),
+            // an `I64` id within `I32`s
+            (
+                var(1, PropKey::Id),
+                Object::from((0..HASHED_COLLECTION_MIN_SIZE as i32).collect::<Vec<_>>()),
+                Some(true),
+            ),
+            (var(0, PropKey::Id), Object::from(large_ids.clone()), Some(false)),
+            (
+                var(0, PropKey::Key("name".into())),
+                Object::from(
+                    (0..HASHED_COLLECTION_MIN_SIZE)
+                        .map(|i| format!("John{}", i))
+                        .collect::<Vec<_>>(),
+                ),
+                Some(false),
+            ),
+            // a missing property is unknown
+            (var(0, PropKey::Key("none_exist".into())), Object::from(large_ids.clone()), None),
+        ];
+        for (left, right, expected) in cases {
+            let within =
+                Predicate::new(left.clone(), common_pb::Logical::Within, Operand::Const(right.clone()));
+            let without = Predicate::new(left, common_pb::Logical::Without, Operand::Const(right));
+            assert!(within.is_hashed() && without.is_hashed());
+            assert_eq!(
+                within
+                    .eval_tri::<_, Vertices>(Some(&ctxt))
+                    .unwrap(),
+                expected
+            );
+            assert_eq!(
+                without
+                    .eval_tri::<_, Vertices>(Some(&ctxt))
+                    .unwrap(),
+                expected.map(|b| !b)
+            );
+        }
+
+        // small or unhashable collections are searched linearly
+        let small = Predicate::new(age(), common_pb::Logical::Within, Operand::Const(vec![31_i64].into()));
+        assert!(!small.is_hashed());
+        let mut doubles: Vec<f64> = large_ids.iter().map(|i| *i as f64).collect();
+        doubles.push(31.0);
+        let floats = Predicate::new(age(), common_pb::Logical::Within, Operand::Const(doubles.into()));
+        assert!(!floats.is_hashed());
+        assert!(floats
+            .eval_bool::<_, Vertices>(Some(&ctxt))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_eval_without_dynamic_param() {
+        let ctxt = prepare_context();
+        // @1.~id without $ids
+        let mut expr = str_to_expr_pb("@1.~id without 0".to_string()).unwrap();
+        expr.operators.last_mut().unwrap().item =
+            Some(common_pb::expr_opr::Item::Param(common_pb::DynamicParam {
+                name: "ids".to_string(),
+                index: 0,
+                data_type: None,
+            }));
+        let mut params = std::collections::HashMap::new();
+        assert!(expr.clone().resolve_params(&params).is_err());
+
+        let ids: Vec<i64> = (2..100_000).collect();
+        params.insert("ids".to_string(), common_pb::Value::from(ids));
+        expr.resolve_params(&params).unwrap();
+        let eval = PEvaluator::try_from(expr).unwrap();
+        match &eval {
+            PEvaluator::Predicates(Predicates::Binary(pred)) => assert!(pred.is_hashed()),
+            _ => panic!("should be a binary predicate"),
+        }
+        // the vertex of id 2
+        assert!(!eval
+            .eval_bool::<_, Vertices>(Some(&ctxt))
+            .unwrap());
+    }
+
     fn gen_regex_expression(to_match: &str, pattern: &str) -> common_pb::Expression {
         let mut regex_expr = str_to_expr_pb(to_match.to_string()).unwrap();
         let regex_opr = common_pb::ExprOpr {
@@ -1003,4 +1528,94 @@ mod tests {
             );
         }
     }
+
+    fn eval_str_predicate(left: &str, cmp: common_pb::Logical, right: &str) -> bool {
+        let expr = common_pb::Expression {
+            operators: vec![
+                common_pb::Value::from(left.to_string()).into(),
+                cmp.into(),
+                common_pb::Value::from(right.to_string()).into(),
+            ],
+        };
+        PEvaluator::try_from(expr)
+            .unwrap()
+            .eval_bool::<(), NoneContext>(None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_eval_startswith_endswith_unicode() {
+        use common_pb::Logical::{Endswith, Startswith};
+
+        assert!(eval_str_predicate("résumé", Startswith, "r"));
+        assert!(eval_str_predicate("résumé", Startswith, "ré"));
+        assert!(!eval_str_predicate("résumé", Startswith, "é"));
+        assert!(eval_str_predicate("résumé", Endswith, "é"));
+        assert!(!eval_str_predicate("résumé", Endswith, "e"));
+        assert!(eval_str_predicate("日本語", Startswith, "日"));
+        assert!(eval_str_predicate("日本語", Endswith, "本語"));
+        assert!(eval_str_predicate("👨‍👩‍👧", Startswith, "👨"));
+        assert!(eval_str_predicate("abc", Startswith, ""));
+        assert!(eval_str_predicate("", Endswith, ""));
+        assert!(!eval_str_predicate("", Startswith, "é"));
+    }
+
+    fn prop_names_of(expr: &str) -> Option<Vec<NameOrId>> {
+        let expr = str_to_expr_pb(expr.to_string()).unwrap();
+        extract_prop_names(&Arc::new(PEvaluator::try_from(expr).unwrap()))
+    }
+
+    #[test]
+    fn test_extract_prop_names() {
+        assert_eq!(prop_names_of("@.name == \"marko\""), Some(vec![NameOrId::Str("name".to_string())]));
+        assert_eq!(
+            prop_names_of("@.name == \"marko\" && (@.age > 27 || @.name == \"josh\")"),
+            Some(vec![NameOrId::Str("name".to_string()), NameOrId::Str("age".to_string())])
+        );
+        assert_eq!(prop_names_of("isNull @.name"), Some(vec![NameOrId::Str("name".to_string())]));
+        // the properties of the tagged entities are not included
+        assert_eq!(prop_names_of("@a.name == \"marko\""), None);
+        assert_eq!(prop_names_of("@.~id == 1"), None);
+    }
+
+    #[test]
+    fn test_extract_prop_equalities() {
+        let equalities_of = |expr: &str| {
+            let filter = PEvaluator::try_from(str_to_expr_pb(expr.to_string()).unwrap()).unwrap();
+            extract_prop_equalities(&filter)
+        };
+        assert_eq!(
+            equalities_of("@.id == 1 && @.age > 27 && \"marko\" == @.name"),
+            vec![
+                (NameOrId::Str("id".to_string()), vec![object!(1)]),
+                (NameOrId::Str("name".to_string()), vec![object!("marko")])
+            ]
+        );
+        assert_eq!(
+            equalities_of("@.id within [1, 2]"),
+            vec![(NameOrId::Str("id".to_string()), vec![object!(1), object!(2)])]
+        );
+        // not implied by the filter
+        assert!(equalities_of("@.id == 1 || @.id == 2").is_empty());
+        assert!(equalities_of("@.id != 1").is_empty());
+        // of the tagged entities
+        assert!(equalities_of("@a.id == 1").is_empty());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_eval_startswith_endswith(
+            left in proptest::string::string_regex("\\PC{0,8}").unwrap(),
+            right in proptest::string::string_regex("\\PC{0,3}").unwrap(),
+        ) {
+            use common_pb::Logical::{Endswith, Startswith};
+
+            proptest::prop_assert_eq!(eval_str_predicate(&left, Startswith, &right), left.starts_with(&right));
+            proptest::prop_assert_eq!(eval_str_predicate(&left, Endswith, &right), left.ends_with(&right));
+            let joined = format!("{}{}", right, left);
+            proptest::prop_assert!(eval_str_predicate(&joined, Startswith, &right));
+            let joined = format!("{}{}", left, right);
+            proptest::prop_assert!(eval_str_predicate(&joined, Endswith, &right));
+        }
+    }
 }
@@ -20,6 +20,7 @@ use dyn_type::CastError;
 use crate::utils::expr::eval::OperatorDesc;
 
 pub mod eval;
+pub mod eval_batch;
 pub mod eval_pred;
 
 pub type ExprEvalResult<T> = Result<T, ExprEvalError>;
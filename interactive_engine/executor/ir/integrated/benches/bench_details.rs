@@ -0,0 +1,169 @@
+//
+//! Copyright 2023 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+
+#![feature(test)]
+
+extern crate test;
+
+use ahash::HashMap;
+use dyn_type::Object;
+use graph_proxy::apis::{Details, DynDetails, PropertyValue, Vertex};
+use ir_common::generated::common as common_pb;
+use ir_common::generated::physical as pb;
+use ir_common::NameOrId;
+use pegasus_common::downcast::*;
+use pegasus_common::impl_as_any;
+use runtime::process::entry::Entry;
+use runtime::process::functions::{GroupGen, KeyFunction};
+use runtime::process::record::Record;
+use test::Bencher;
+
+const PROP_NUM: usize = 10;
+const VERTEX_NUM: usize = 1000;
+
+/// Mimic the lazy details of a storage, which decodes the property each time it is accessed.
+#[derive(Debug)]
+struct EncodedDetails {
+    encoded: Vec<(String, String)>,
+}
+
+impl_as_any!(EncodedDetails);
+
+impl Details for EncodedDetails {
+    fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
+        let key = match key {
+            NameOrId::Str(key) => key.as_str(),
+            NameOrId::Id(_) => return None,
+        };
+        self.encoded
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| PropertyValue::Owned(Object::from(v.parse::<i64>().unwrap())))
+    }
+
+    fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
+        Some(
+            self.encoded
+                .iter()
+                .map(|(k, v)| (k.as_str().into(), Object::from(v.parse::<i64>().unwrap())))
+                .collect(),
+        )
+    }
+
+    fn get_property_keys(&self) -> Option<Vec<NameOrId>> {
+        Some(vec![])
+    }
+}
+
+fn prop_names() -> Vec<String> {
+    (0..PROP_NUM)
+        .map(|i| format!("prop{}", i))
+        .collect()
+}
+
+fn lazy_source() -> Vec<Record> {
+    (0..VERTEX_NUM)
+        .map(|id| {
+            let encoded = prop_names()
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| (name, ((id + i) % 7).to_string()))
+                .collect();
+            let details = DynDetails::lazy(EncodedDetails { encoded });
+            Record::new(Vertex::new(id as i64, None, details), None)
+        })
+        .collect()
+}
+
+fn group_by_props() -> pb::GroupBy {
+    let mappings = prop_names()
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| pb::group_by::KeyAlias {
+            key: Some(common_pb::Variable::from(format!("@.{}", name))),
+            alias: Some((i as i32).into()),
+        })
+        .collect();
+    pb::GroupBy { mappings, functions: vec![] }
+}
+
+// g.V().group().by(values("prop0", ..., "prop9")), accessing the lazy properties one by one
+#[bench]
+fn bench_group_key_lazy_props(b: &mut Bencher) {
+    let source = lazy_source();
+    let keys: Vec<NameOrId> = prop_names()
+        .into_iter()
+        .map(|name| name.into())
+        .collect();
+    b.iter(|| {
+        for record in source.iter() {
+            let details = record
+                .get(None)
+                .unwrap()
+                .as_vertex()
+                .unwrap()
+                .get_details();
+            for key in keys.iter() {
+                test::black_box(
+                    details
+                        .get_property(key)
+                        .and_then(|p| p.try_to_owned()),
+                );
+            }
+        }
+    })
+}
+
+// g.V().group().by(values("prop0", ..., "prop9")), materializing the keyed properties before the access
+#[bench]
+fn bench_group_key_eager_props(b: &mut Bencher) {
+    let source = lazy_source();
+    let keys: Vec<NameOrId> = prop_names()
+        .into_iter()
+        .map(|name| name.into())
+        .collect();
+    b.iter(|| {
+        for record in source.iter() {
+            let details = record
+                .get(None)
+                .unwrap()
+                .as_vertex()
+                .unwrap()
+                .get_details()
+                .into_eager_with(&keys)
+                .unwrap();
+            for key in keys.iter() {
+                test::black_box(
+                    details
+                        .get_property(key)
+                        .and_then(|p| p.try_to_owned()),
+                );
+            }
+        }
+    })
+}
+
+// g.V().group().by(values("prop0", ..., "prop9")) via the key selector of GroupBy
+#[bench]
+fn bench_group_key_selector(b: &mut Bencher) {
+    let source = lazy_source();
+    let group_key = group_by_props().gen_group_key().unwrap();
+    b.iter(|| {
+        for record in source.iter() {
+            test::black_box(group_key.get_kv(record.clone()).unwrap());
+        }
+    })
+}
@@ -137,7 +137,8 @@ fn init_intersect_job_request(edge_tag_1: Option<KeyId>, edge_tag_2: Option<KeyI
     if let Some(edge_tag_2) = edge_tag_2 {
         sink_tags.push(common_pb::NameOrIdKey { key: Some(edge_tag_2.into()) });
     }
-    let sink_pb = algebra_pb::Sink { tags: sink_tags, sink_target: default_sink_target() };
+    let sink_pb =
+        algebra_pb::Sink { tags: sink_tags, sink_target: default_sink_target(), tagged_targets: vec![] };
 
     let mut job_builder = JobBuilder::default();
     job_builder.add_scan_source(source_opr.clone());
@@ -293,11 +293,11 @@ pub mod benchmark {
             .into_iter()
             .map(|(tag, var, order)| {
                 let key = to_var_pb(tag, var);
-                pb::order_by::OrderingPair { key: Some(key), order: order as i32 }
+                pb::order_by::OrderingPair { key: Some(key), order: order as i32, null_order: 0 }
             })
             .collect();
         let limit = limit.map(|upper| pb::Range { lower: 0, upper });
-        pb::OrderBy { pairs, limit }
+        pb::OrderBy { pairs, limit, unstable: false }
     }
 
     pub fn default_count_pb() -> pb::GroupBy {
@@ -313,7 +313,11 @@ pub mod benchmark {
     }
 
     pub fn default_sink_pb() -> pb::Sink {
-        pb::Sink { tags: vec![common_pb::NameOrIdKey { key: None }], sink_target: default_sink_target() }
+        pb::Sink {
+            tags: vec![common_pb::NameOrIdKey { key: None }],
+            sink_target: default_sink_target(),
+            tagged_targets: vec![],
+        }
     }
 
     pub fn default_sink_target() -> Option<pb::sink::SinkTarget> {
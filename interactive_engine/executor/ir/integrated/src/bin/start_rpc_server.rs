@@ -16,7 +16,7 @@
 use std::{path::PathBuf, sync::Arc};
 
 use graph_proxy::{apis::PegasusClusterInfo, create_exp_store, SimplePartition};
-use log::info;
+use log::{error, info};
 #[cfg(feature = "mimalloc")]
 use mimalloc_rust::*;
 use runtime::initialize_job_assembly;
@@ -30,23 +30,51 @@ static GLOBAL_MIMALLOC: GlobalMiMalloc = GlobalMiMalloc;
 struct Config {
     #[structopt(long = "config", parse(from_os_str))]
     config_dir: PathBuf,
+    /// The directory in which the csv files are sinked into, if allowed
+    #[structopt(long = "csv_sink_dir", parse(from_os_str))]
+    csv_sink_dir: Option<PathBuf>,
+    /// Reject the unknown extra params of the queries with the prefix of `gs.`
+    #[structopt(long = "strict_extra_params")]
+    strict_extra_params: bool,
+    /// The port to transcode the physical plans in json into protobuf over http, which is not served if not given
+    #[structopt(long = "http_plan_port")]
+    http_plan_port: Option<u16>,
+    /// The address that the transcoder of the plans binds to, which is only reachable locally by default
+    #[structopt(long = "http_plan_host", default_value = "127.0.0.1")]
+    http_plan_host: std::net::IpAddr,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     pegasus_common::logs::init_log();
     let config: Config = Config::from_args();
+    if let Some(csv_sink_dir) = config.csv_sink_dir.as_ref() {
+        runtime::process::operator::sink::set_csv_sink_dir(csv_sink_dir);
+    }
+    graph_proxy::apis::set_strict_extra_params(config.strict_extra_params);
     let (server_config, rpc_config) = pegasus_server::config::load_configs(config.config_dir).unwrap();
 
     let num_servers = server_config.servers_size();
     let cluster_info = Arc::new(PegasusClusterInfo::default());
     let exp_store = create_exp_store(cluster_info.clone());
     let partition_info = Arc::new(SimplePartition { num_servers });
+    // to explain the queries with their physical plans
+    graph_proxy::apis::register_physical_planner(|plan| {
+        ir_core::plan::physical::build_physical_plan(plan, 0).map_err(|e| e.to_string())
+    });
     let job_assembly = initialize_job_assembly::<_, SimplePartition, PegasusClusterInfo>(
         exp_store,
         partition_info,
         cluster_info,
     );
+    if let Some(port) = config.http_plan_port {
+        let addr = std::net::SocketAddr::new(config.http_plan_host, port);
+        tokio::spawn(async move {
+            if let Err(e) = runtime::http::serve_json_plan_transcoder(addr).await {
+                error!("http plan server error: {}", e);
+            }
+        });
+    }
     info!("try to start rpc server;");
 
     pegasus_server::cluster::standalone::start(rpc_config, server_config, job_assembly).await?;
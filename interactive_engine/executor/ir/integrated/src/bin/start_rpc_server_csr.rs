@@ -30,18 +30,32 @@ static GLOBAL_MIMALLOC: GlobalMiMalloc = GlobalMiMalloc;
 struct Config {
     #[structopt(long = "config", parse(from_os_str))]
     config_dir: PathBuf,
+    /// The directory in which the csv files are sinked into, if allowed
+    #[structopt(long = "csv_sink_dir", parse(from_os_str))]
+    csv_sink_dir: Option<PathBuf>,
+    /// Reject the unknown extra params of the queries with the prefix of `gs.`
+    #[structopt(long = "strict_extra_params")]
+    strict_extra_params: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     pegasus_common::logs::init_log();
     let config: Config = Config::from_args();
+    if let Some(csv_sink_dir) = config.csv_sink_dir.as_ref() {
+        runtime::process::operator::sink::set_csv_sink_dir(csv_sink_dir);
+    }
+    graph_proxy::apis::set_strict_extra_params(config.strict_extra_params);
     let (server_config, rpc_config) = pegasus_server::config::load_configs(config.config_dir).unwrap();
 
     let num_servers = server_config.servers_size();
     let cluster_info = Arc::new(PegasusClusterInfo::default());
     let csr_store = create_csr_store(cluster_info.clone());
     let partition_info = Arc::new(SimplePartition { num_servers });
+    // to explain the queries with their physical plans
+    graph_proxy::apis::register_physical_planner(|plan| {
+        ir_core::plan::physical::build_physical_plan(plan, 0).map_err(|e| e.to_string())
+    });
     let job_assembly = initialize_job_assembly::<_, SimplePartition, PegasusClusterInfo>(
         csr_store,
         partition_info,
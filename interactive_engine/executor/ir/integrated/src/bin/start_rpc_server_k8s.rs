@@ -40,6 +40,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cluster_info = Arc::new(PegasusClusterInfo::default());
     let exp_store = create_exp_store(cluster_info.clone());
     let partition_info = Arc::new(SimplePartition { num_servers: server_config.servers_size() });
+    // to explain the queries with their physical plans
+    graph_proxy::apis::register_physical_planner(|plan| {
+        ir_core::plan::physical::build_physical_plan(plan, 0).map_err(|e| e.to_string())
+    });
     let job_assembly = initialize_job_assembly::<_, SimplePartition, PegasusClusterInfo>(
         exp_store,
         partition_info,
@@ -19,16 +19,24 @@ mod common;
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use dyn_type::Object;
     use graph_proxy::apis::GraphElement;
     use graph_store::common::DefaultId;
     use graph_store::ldbc::LDBCVertexParser;
+    use ir_common::expr_parse::str_to_expr_pb;
     use ir_common::generated::algebra as pb;
     use ir_common::generated::common as common_pb;
     use ir_common::KeyId;
+    use ir_core::plan::logical::LogicalPlan;
+    use ir_core::plan::physical::AsPhysical;
     use ir_physical_client::physical_builder::*;
+    use pegasus::JobConf;
     use pegasus_server::JobRequest;
     use runtime::process::entry::Entry;
+    use runtime::process::operator::subtask::APPLY_SCOPES_METRIC;
+    use runtime::process::record::Record;
 
     use crate::common::test::*;
 
@@ -180,6 +188,7 @@ mod test {
                 common_pb::NameOrIdKey { key: Some(alias.into()) },
             ],
             sink_target: default_sink_target(),
+            tagged_targets: vec![],
         };
 
         let mut job_builder = JobBuilder::default();
@@ -298,4 +307,288 @@ mod test {
     fn apply_left_out_join_w2_test() {
         apply_left_out_join(2)
     }
+
+    // g.V().hasLabel("person").as("a").where(out("knows").where(predicate)), where the subtask of the
+    // lateral apply refers to the tag "a" (TAG_A) of the outer scope
+    fn init_lateral_apply_request(predicate: &str) -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: Some(TAG_A.into()),
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let expand_opr = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec![KNOWS_LABEL.into()], vec![], None)),
+            expand_opt: 0,
+            alias: None,
+            meta_data: None,
+            is_optional: false,
+        };
+        let select_opr = pb::Select { predicate: str_to_expr_pb(predicate.to_string()).ok() };
+        let sink_opr =
+            pb::Sink { tags: vec![], sink_target: default_sink_target(), tagged_targets: vec![] };
+
+        let mut plan = LogicalPlan::with_root();
+        let source_id = plan
+            .append_operator_as_node(source_opr.into(), vec![0])
+            .unwrap();
+        let subtask_id = plan
+            .append_operator_as_node(expand_opr.into(), vec![])
+            .unwrap();
+        plan.append_operator_as_node(select_opr.into(), vec![subtask_id])
+            .unwrap();
+        let apply_opr = pb::Apply {
+            join_kind: 4, // semi join
+            tags: vec![],
+            subtask: subtask_id as i32,
+            alias: None,
+            lateral: true,
+        };
+        let apply_id = plan
+            .append_operator_as_node(apply_opr.into(), vec![source_id])
+            .unwrap();
+        plan.append_operator_as_node(sink_opr.into(), vec![apply_id])
+            .unwrap();
+
+        let mut plan_builder = PlanBuilder::default();
+        let mut plan_meta = plan.get_meta().clone();
+        plan.add_job_builder(&mut plan_builder, &mut plan_meta)
+            .unwrap();
+        JobBuilder::with_plan(plan_builder)
+            .build()
+            .unwrap()
+    }
+
+    fn lateral_apply(predicate: &str, worker_num: u32) -> Vec<DefaultId> {
+        initialize();
+        let request = init_lateral_apply_request(predicate);
+        let mut results = submit_query(request, worker_num);
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    let record = parse_result(res).unwrap();
+                    if let Some(vertex) = record.get(Some(TAG_A)).unwrap().as_vertex() {
+                        result_collection.push(vertex.id() as DefaultId);
+                    }
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        result_collection.sort();
+        result_collection
+    }
+
+    // find the persons who know someone of the same age, where marko (29) knows vadas (27) and josh (32)
+    #[test]
+    fn lateral_apply_same_age_test() {
+        assert!(lateral_apply("@.age == @0.age", 1).is_empty());
+        assert!(lateral_apply("@.age == @0.age", 2).is_empty());
+    }
+
+    // find the persons who know someone exactly 3 years older
+    #[test]
+    fn lateral_apply_older_friend_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        assert_eq!(lateral_apply("@.age == @0.age + 3", 1), vec![v1]);
+        assert_eq!(lateral_apply("@.age == @0.age + 3", 2), vec![v1]);
+    }
+
+    // find the persons who know someone younger
+    #[test]
+    fn lateral_apply_younger_friend_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        assert_eq!(lateral_apply("@.age < @0.age", 2), vec![v1]);
+        assert!(lateral_apply("@.age > @0.age + 3", 2).is_empty());
+    }
+
+    // g.V().where(out().count().is(gt(1)))
+    fn init_apply_count_filter_request() -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+
+        let expand_opr = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec![], vec![], None)),
+            expand_opt: 0,
+            alias: None,
+            meta_data: None,
+            is_optional: false,
+        };
+
+        let fold_opr = pb::GroupBy {
+            mappings: vec![],
+            functions: vec![pb::group_by::AggFunc {
+                vars: vec![common_pb::Variable::from("@".to_string())],
+                aggregate: 3, // count
+                alias: None,
+            }],
+            meta_data: vec![],
+        };
+
+        let select_opr = pb::Select { predicate: str_to_expr_pb("@ > 1".to_string()).ok() };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.apply_func(
+            // join_kind: SemiJoin
+            unsafe { ::std::mem::transmute(4) },
+            move |plan| {
+                plan.shuffle(None)
+                    .edge_expand(expand_opr.clone().into())
+                    .group(fold_opr.clone().into())
+                    .select(select_opr.clone());
+            },
+            None,
+        );
+        job_builder.sink(default_sink_pb());
+
+        job_builder.build().unwrap()
+    }
+
+    // returns the results, and the number of the scopes that the subtask of `Apply` is run in
+    fn submit_apply(request: JobRequest, worker_num: u32, apply_batch_size: u32) -> (Vec<Record>, u64) {
+        initialize();
+        let mut conf = JobConf::default();
+        conf.workers = worker_num;
+        conf.apply_batch_size = apply_batch_size;
+        let (tx, rx) = crossbeam_channel::unbounded();
+        conf.set_metrics_callback(move |metrics| {
+            let scopes = metrics
+                .counters
+                .get(APPLY_SCOPES_METRIC)
+                .cloned()
+                .unwrap_or(0);
+            tx.send(scopes).unwrap();
+        });
+        let mut results = submit_query_with_conf(request, conf);
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => result_collection.push(parse_result(res).unwrap()),
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        let scopes = rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("metrics not delivered");
+        (result_collection, scopes)
+    }
+
+    fn get_vertex_ids(records: &[Record]) -> Vec<DefaultId> {
+        let mut ids: Vec<DefaultId> = records
+            .iter()
+            .filter_map(|record| record.get(None).unwrap().as_vertex())
+            .map(|vertex| vertex.id() as DefaultId)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    fn get_vertex_counts(records: &[Record]) -> Vec<(DefaultId, Object)> {
+        let mut counts: Vec<(DefaultId, Object)> = records
+            .iter()
+            .map(|record| {
+                let vertex = record.get(None).unwrap().as_vertex().unwrap();
+                let cnt = record
+                    .get(Some(TAG_A))
+                    .unwrap()
+                    .as_object()
+                    .unwrap();
+                (vertex.id() as DefaultId, cnt.clone())
+            })
+            .collect();
+        counts.sort_by(|c1, c2| c1.0.cmp(&c2.0));
+        counts
+    }
+
+    // the subtask of g.V().where(out()) is applied to 4 parents at a time
+    #[test]
+    fn batched_apply_test() {
+        for worker_num in [1, 2] {
+            // join_kind: SemiJoin, AntiJoin, InnerJoin, LeftOuterJoin
+            for join_kind in [4, 5, 0, 1] {
+                let (expected, expected_scopes) =
+                    submit_apply(init_apply_request(join_kind), worker_num, 1);
+                let (results, scopes) = submit_apply(init_apply_request(join_kind), worker_num, 4);
+                if join_kind == 4 || join_kind == 5 {
+                    assert_eq!(get_vertex_ids(&results), get_vertex_ids(&expected));
+                } else {
+                    // the inner/left-outer join keeps any one of the results of the subtask
+                    assert_eq!(results.len(), expected.len());
+                }
+                // one scope per person without batching, and one per batch of persons otherwise
+                assert_eq!(expected_scopes, 4);
+                assert!(scopes < expected_scopes);
+            }
+        }
+    }
+
+    // the count of g.V().apply(out().count()) is evaluated per parent over the batched subtask
+    #[test]
+    fn batched_apply_count_test() {
+        for worker_num in [1, 2] {
+            // join_kind: InnerJoin, LeftOuterJoin
+            for join_kind in [0, 1] {
+                let (expected, expected_scopes) =
+                    submit_apply(init_apply_count_request(join_kind, TAG_A), worker_num, 1);
+                let (results, scopes) =
+                    submit_apply(init_apply_count_request(join_kind, TAG_A), worker_num, 4);
+                assert_eq!(get_vertex_counts(&results), get_vertex_counts(&expected));
+                assert!(scopes < expected_scopes);
+            }
+        }
+    }
+
+    // g.V().where(out().count().is(gt(1))) is batched with the aggregation of the subtask
+    #[test]
+    fn batched_apply_count_filter_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        for worker_num in [1, 2] {
+            let (expected, expected_scopes) =
+                submit_apply(init_apply_count_filter_request(), worker_num, 1);
+            let (results, scopes) = submit_apply(init_apply_count_filter_request(), worker_num, 4);
+            assert_eq!(get_vertex_ids(&expected), vec![v1, v4]);
+            assert_eq!(get_vertex_ids(&results), vec![v1, v4]);
+            assert!(scopes < expected_scopes);
+        }
+    }
+
+    // the lateral subtask refers to the tag of its parent when applied to a batch of parents
+    #[test]
+    fn batched_lateral_apply_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        for worker_num in [1, 2] {
+            initialize();
+            let mut conf = JobConf::default();
+            conf.workers = worker_num;
+            conf.apply_batch_size = 4;
+            let mut results =
+                submit_query_with_conf(init_lateral_apply_request("@.age == @0.age + 3"), conf);
+            let mut result_collection = vec![];
+            while let Some(result) = results.next() {
+                let record = parse_result(result.unwrap()).unwrap();
+                if let Some(vertex) = record.get(Some(TAG_A)).unwrap().as_vertex() {
+                    result_collection.push(vertex.id() as DefaultId);
+                }
+            }
+            assert_eq!(result_collection, vec![v1]);
+        }
+    }
 }
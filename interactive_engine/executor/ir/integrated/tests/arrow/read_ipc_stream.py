@@ -0,0 +1,29 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+#
+# Copyright 2024 Alibaba Group Holding Limited. All Rights Reserved.
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+#
+
+# Read a record batch of the Arrow sink from stdin, and print its rows as comma-separated values.
+
+import sys
+
+import pyarrow as pa
+
+if __name__ == "__main__":
+    reader = pa.ipc.open_stream(sys.stdin.buffer)
+    table = reader.read_all()
+    for row in table.to_pylist():
+        print(",".join(str(value) for value in row.values()))
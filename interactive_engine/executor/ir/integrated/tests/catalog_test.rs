@@ -729,6 +729,7 @@ mod test {
             result_opt: pb::path_expand::ResultOpt::EndV as i32,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
         let pattern = pb::Pattern {
             sentences: vec![pb::pattern::Sentence {
@@ -779,6 +780,7 @@ mod test {
             result_opt: pb::path_expand::ResultOpt::EndV as i32,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
         let pattern = pb::Pattern {
             sentences: vec![
@@ -848,6 +850,7 @@ mod test {
             result_opt: pb::path_expand::ResultOpt::EndV as i32,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
         let pattern = pb::Pattern {
             sentences: vec![
@@ -1675,6 +1678,7 @@ mod test {
                     id_name_mappings: vec![],
                 })),
             }),
+            tagged_targets: vec![],
         }
     }
 
@@ -94,6 +94,10 @@ pub mod test {
     pub fn submit_query(job_req: JobRequest, num_workers: u32) -> ResultStream<Vec<u8>> {
         let mut conf = JobConf::default();
         conf.workers = num_workers;
+        submit_query_with_conf(job_req, conf)
+    }
+
+    pub fn submit_query_with_conf(job_req: JobRequest, conf: JobConf) -> ResultStream<Vec<u8>> {
         let (tx, rx) = crossbeam_channel::unbounded();
         let sink = ResultSink::new(tx);
         let cancel_hook = sink.get_cancel_hook().clone();
@@ -225,6 +229,7 @@ pub mod test {
                     id_name_mappings: vec![],
                 })),
             }),
+            tagged_targets: vec![],
         }
     }
 
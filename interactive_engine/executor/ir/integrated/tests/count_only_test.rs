@@ -0,0 +1,201 @@
+//
+//! Copyright 2022 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+//!
+
+mod common;
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use graph_proxy::apis::graph::PKV;
+    use graph_proxy::apis::{
+        register_graph, Direction, Edge, PegasusClusterInfo, QueryParams, ReadGraph, Statement, Vertex, ID,
+    };
+    use graph_proxy::{create_exp_store, GraphProxyResult, SimplePartition};
+    use ir_common::generated::algebra as pb;
+    use ir_common::LabelId;
+    use ir_physical_client::physical_builder::*;
+    use pegasus::result::{ResultSink, ResultStream};
+    use pegasus::{run_opt, Configuration, JobConf};
+    use pegasus_server::job::{JobAssembly, JobDesc};
+    use pegasus_server::JobRequest;
+    use runtime::process::entry::Entry;
+    use runtime::IRJobAssembly;
+
+    use crate::common::test::*;
+
+    /// The modern graph in exp_store, that counts how many runtime vertices are built out of the store.
+    struct CountingGraph {
+        inner: Arc<dyn ReadGraph>,
+        built: Arc<AtomicUsize>,
+    }
+
+    impl CountingGraph {
+        fn counted(
+            &self, vertices: Box<dyn Iterator<Item = Vertex> + Send>,
+        ) -> Box<dyn Iterator<Item = Vertex> + Send> {
+            let built = self.built.clone();
+            Box::new(vertices.inspect(move |_| {
+                built.fetch_add(1, Ordering::SeqCst);
+            }))
+        }
+    }
+
+    impl ReadGraph for CountingGraph {
+        fn scan_vertex(
+            &self, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            Ok(self.counted(self.inner.scan_vertex(params)?))
+        }
+
+        fn index_scan_vertex(
+            &self, label: LabelId, primary_key: &PKV, params: &QueryParams,
+        ) -> GraphProxyResult<Option<Vertex>> {
+            let vertex = self
+                .inner
+                .index_scan_vertex(label, primary_key, params)?;
+            if vertex.is_some() {
+                self.built.fetch_add(1, Ordering::SeqCst);
+            }
+            Ok(vertex)
+        }
+
+        fn scan_edge(
+            &self, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            self.inner.scan_edge(params)
+        }
+
+        fn get_vertex(
+            &self, ids: &[ID], params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            Ok(self.counted(self.inner.get_vertex(ids, params)?))
+        }
+
+        fn get_edge(
+            &self, ids: &[ID], params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            self.inner.get_edge(ids, params)
+        }
+
+        fn prepare_explore_vertex(
+            &self, direction: Direction, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
+            self.inner
+                .prepare_explore_vertex(direction, params)
+        }
+
+        fn prepare_explore_edge(
+            &self, direction: Direction, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
+            self.inner
+                .prepare_explore_edge(direction, params)
+        }
+
+        fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64> {
+            self.inner.count_vertex(params)
+        }
+
+        fn count_edge(&self, params: &QueryParams) -> GraphProxyResult<u64> {
+            self.inner.count_edge(params)
+        }
+
+        fn count_vertex_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+            self.inner.count_vertex_by_ids(ids, params)
+        }
+
+        fn count_edge_by_ids(&self, ids: &[ID], params: &QueryParams) -> GraphProxyResult<u64> {
+            self.inner.count_edge_by_ids(ids, params)
+        }
+
+        fn get_primary_key(&self, id: &ID) -> GraphProxyResult<Option<PKV>> {
+            self.inner.get_primary_key(id)
+        }
+    }
+
+    fn initialize_counting_graph() -> (IRJobAssembly<SimplePartition, PegasusClusterInfo>, Arc<AtomicUsize>)
+    {
+        pegasus::startup(Configuration::singleton()).ok();
+        let cluster_info = Arc::new(PegasusClusterInfo::default());
+        let built = Arc::new(AtomicUsize::new(0));
+        let inner: Arc<dyn ReadGraph> = create_exp_store(cluster_info.clone());
+        register_graph(Arc::new(CountingGraph { inner, built: built.clone() }));
+        let partition_info = Arc::new(SimplePartition { num_servers: 1 });
+        (IRJobAssembly::with(partition_info, cluster_info), built)
+    }
+
+    // g.V().hasLabel('person').count(), where the count is fused into the scan
+    fn init_count_person_request(is_count_only: bool) -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec![], None)),
+            idx_predicate: None,
+            is_count_only,
+            meta_data: None,
+        };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.sink(default_sink_pb());
+        job_builder.build().unwrap()
+    }
+
+    fn submit(
+        service: &'static IRJobAssembly<SimplePartition, PegasusClusterInfo>, job_req: JobRequest,
+        num_workers: u32,
+    ) -> Vec<Vec<u8>> {
+        let mut conf = JobConf::new("count_only_test");
+        conf.workers = num_workers;
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sink = ResultSink::new(tx);
+        let cancel_hook = sink.get_cancel_hook().clone();
+        let results = ResultStream::new(conf.job_id, cancel_hook, rx);
+        let job = JobDesc { input: job_req.source, plan: job_req.plan, resource: job_req.resource };
+        run_opt(conf, sink, move |worker| service.assemble(&job, worker)).expect("submit job failure;");
+        results
+            .map(|result| result.expect("err result"))
+            .collect()
+    }
+
+    #[test]
+    fn count_person_without_runtime_vertices_test() {
+        let (service, built) = initialize_counting_graph();
+        let service: &'static _ = Box::leak(Box::new(service));
+
+        // each worker counts its own part, without building any vertex
+        let mut count = 0;
+        for result in submit(service, init_count_person_request(true), 2) {
+            let record = parse_result(result).unwrap();
+            count += record
+                .get(None)
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .as_u64()
+                .unwrap();
+        }
+        assert_eq!(count, 4);
+        assert_eq!(built.swap(0, Ordering::SeqCst), 0);
+
+        // otherwise, the vertices are scanned one by one
+        let results = submit(service, init_count_person_request(false), 2);
+        assert_eq!(results.len(), 4);
+        assert_eq!(built.swap(0, Ordering::SeqCst), 4);
+    }
+}
@@ -872,7 +872,8 @@ mod test {
         };
 
         // unfold tag C
-        let unfold_opr = pb::Unfold { tag: Some(TAG_C.into()), alias: Some(TAG_C.into()) };
+        let unfold_opr =
+            pb::Unfold { tag: Some(TAG_C.into()), alias: Some(TAG_C.into()), keep_empty: false };
 
         let conf = JobConf::new("expand_and_intersection_unfold_test");
         let mut result = pegasus::run(conf, || {
@@ -948,7 +949,8 @@ mod test {
         };
 
         // unfold tag C
-        let unfold_opr = pb::Unfold { tag: Some(TAG_C.into()), alias: Some(TAG_C.into()) };
+        let unfold_opr =
+            pb::Unfold { tag: Some(TAG_C.into()), alias: Some(TAG_C.into()), keep_empty: false };
 
         let conf = JobConf::new("expand_and_intersection_unfold_multiv_test");
         let mut result = pegasus::run(conf, || {
@@ -1025,7 +1027,8 @@ mod test {
         };
 
         // unfold tag C
-        let unfold_opr = pb::Unfold { tag: Some(TAG_C.into()), alias: Some(TAG_C.into()) };
+        let unfold_opr =
+            pb::Unfold { tag: Some(TAG_C.into()), alias: Some(TAG_C.into()), keep_empty: false };
 
         let conf = JobConf::new("expand_filter_and_intersection_unfold_test");
         let mut result = pegasus::run(conf, || {
@@ -1159,6 +1162,7 @@ mod test {
             result_opt: 0, // endv
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let end_v = algebra_pb::GetV {
@@ -1391,7 +1395,11 @@ mod test {
         if let Some(edge_tag_2) = edge_tag_2 {
             sink_tags.push(common_pb::NameOrIdKey { key: Some(edge_tag_2.into()) });
         }
-        let sink_pb = algebra_pb::Sink { tags: sink_tags, sink_target: default_sink_target() };
+        let sink_pb = algebra_pb::Sink {
+            tags: sink_tags,
+            sink_target: default_sink_target(),
+            tagged_targets: vec![],
+        };
 
         let mut job_builder = JobBuilder::default();
         job_builder.add_scan_source(source_opr.clone());
@@ -1630,6 +1638,7 @@ mod test {
             result_opt: 0, // endv
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let endv = algebra_pb::GetV {
@@ -1647,7 +1656,11 @@ mod test {
         if let Some(edge_tag) = edge_tag {
             sink_tags.push(common_pb::NameOrIdKey { key: Some(edge_tag.into()) });
         }
-        let sink_pb = algebra_pb::Sink { tags: sink_tags, sink_target: default_sink_target() };
+        let sink_pb = algebra_pb::Sink {
+            tags: sink_tags,
+            sink_target: default_sink_target(),
+            tagged_targets: vec![],
+        };
 
         let mut job_builder = JobBuilder::default();
         job_builder.add_scan_source(source_opr.clone());
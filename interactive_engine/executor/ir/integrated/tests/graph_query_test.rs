@@ -24,6 +24,7 @@ mod test {
     use graph_proxy::apis::GraphElement;
     use ir_common::expr_parse::str_to_expr_pb;
     use ir_common::generated::algebra as pb;
+    use ir_common::generated::common as common_pb;
     use ir_physical_client::physical_builder::*;
     use pegasus_server::JobRequest;
     use runtime::process::entry::Entry;
@@ -56,7 +57,9 @@ mod test {
         job_builder.select(select_opr);
         job_builder.shuffle(None);
         job_builder.edge_expand(expand_opr);
-        job_builder.limit(pb::Limit { range: Some(pb::Range { lower: 0, upper: 10 }) });
+        job_builder
+            .limit(pb::Limit { range: Some(pb::Range { lower: 0, upper: 10 }) })
+            .unwrap();
         job_builder.sink(default_sink_pb());
 
         job_builder.build().unwrap()
@@ -268,4 +271,65 @@ mod test {
     fn get_all_property_after_shuffle_w2_test() {
         get_all_properties_after_shuffle(2)
     }
+
+    // g.V().hasLabel("person").order().by('age', desc).limit(3)
+    fn init_order_limit_request() -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec!["age".into()], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let order_opr = pb::OrderBy {
+            pairs: vec![pb::order_by::OrderingPair {
+                key: Some(common_pb::Variable::property("age")),
+                order: 2, // descending
+                null_order: 0,
+            }],
+            limit: None,
+            unstable: false,
+        };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.order(order_opr).unwrap();
+        job_builder
+            .limit(pb::Limit { range: Some(pb::Range { lower: 0, upper: 3 }) })
+            .unwrap();
+        job_builder.sink(default_sink_pb());
+        job_builder.build().unwrap()
+    }
+
+    fn order_limit_query(worker_num: u32) {
+        initialize();
+        let request = init_order_limit_request();
+        let mut results = submit_query(request, worker_num);
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    let entry = parse_result(res).unwrap();
+                    if let Some(vertex) = entry.get(None).unwrap().as_vertex() {
+                        result_collection.push(vertex.id());
+                    }
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        assert_eq!(result_collection, vec![6, 4, 1])
+    }
+
+    #[test]
+    fn order_limit_test() {
+        order_limit_query(1)
+    }
+
+    #[test]
+    fn order_limit_w2_test() {
+        order_limit_query(2)
+    }
 }
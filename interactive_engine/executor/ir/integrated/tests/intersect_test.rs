@@ -0,0 +1,266 @@
+//
+//! Copyright 2022 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+//!
+
+mod common;
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use graph_proxy::apis::explain::collect_expand_degree;
+    use graph_proxy::apis::graph::PKV;
+    use graph_proxy::apis::{
+        get_graph, register_graph, Direction, Edge, GraphElement, PegasusClusterInfo, QueryParams,
+        ReadGraph, Statement, Vertex, ID,
+    };
+    use graph_proxy::{create_exp_store, GraphProxyResult, SimplePartition};
+    use ir_common::generated::algebra as pb;
+    use ir_common::generated::common as common_pb;
+    use ir_common::generated::physical as physical_pb;
+    use ir_common::{KeyId, LabelId};
+    use ir_physical_client::physical_builder::*;
+    use pegasus::result::{ResultSink, ResultStream};
+    use pegasus::{run_opt, Configuration, JobConf};
+    use pegasus_server::job::{JobAssembly, JobDesc};
+    use pegasus_server::JobRequest;
+    use runtime::process::entry::Entry;
+    use runtime::IRJobAssembly;
+
+    use crate::common::test::*;
+
+    /// The graph that counts how many times the vertices of each edge labels are explored.
+    struct ExploreCountGraph {
+        inner: Arc<dyn ReadGraph>,
+        explored: Arc<Mutex<HashMap<Vec<LabelId>, usize>>>,
+    }
+
+    struct ExploreCountStatement {
+        stmt: Box<dyn Statement<ID, Vertex>>,
+        labels: Vec<LabelId>,
+        explored: Arc<Mutex<HashMap<Vec<LabelId>, usize>>>,
+    }
+
+    impl Statement<ID, Vertex> for ExploreCountStatement {
+        fn exec(&self, id: ID) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            *self
+                .explored
+                .lock()
+                .unwrap()
+                .entry(self.labels.clone())
+                .or_default() += 1;
+            self.stmt.exec(id)
+        }
+    }
+
+    impl ReadGraph for ExploreCountGraph {
+        fn scan_vertex(
+            &self, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            self.inner.scan_vertex(params)
+        }
+
+        fn index_scan_vertex(
+            &self, label: LabelId, primary_key: &PKV, params: &QueryParams,
+        ) -> GraphProxyResult<Option<Vertex>> {
+            self.inner
+                .index_scan_vertex(label, primary_key, params)
+        }
+
+        fn scan_edge(
+            &self, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            self.inner.scan_edge(params)
+        }
+
+        fn get_vertex(
+            &self, ids: &[ID], params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            self.inner.get_vertex(ids, params)
+        }
+
+        fn get_edge(
+            &self, ids: &[ID], params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            self.inner.get_edge(ids, params)
+        }
+
+        fn prepare_explore_vertex(
+            &self, direction: Direction, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
+            let stmt = self
+                .inner
+                .prepare_explore_vertex(direction, params)?;
+            Ok(Box::new(ExploreCountStatement {
+                stmt,
+                labels: params.labels.clone(),
+                explored: self.explored.clone(),
+            }))
+        }
+
+        fn prepare_explore_edge(
+            &self, direction: Direction, params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
+            self.inner
+                .prepare_explore_edge(direction, params)
+        }
+
+        fn count_vertex(&self, params: &QueryParams) -> GraphProxyResult<u64> {
+            self.inner.count_vertex(params)
+        }
+
+        fn count_edge(&self, params: &QueryParams) -> GraphProxyResult<u64> {
+            self.inner.count_edge(params)
+        }
+
+        fn get_primary_key(&self, id: &ID) -> GraphProxyResult<Option<PKV>> {
+            self.inner.get_primary_key(id)
+        }
+    }
+
+    fn initialize_explore_count_graph(
+    ) -> (IRJobAssembly<SimplePartition, PegasusClusterInfo>, Arc<Mutex<HashMap<Vec<LabelId>, usize>>>)
+    {
+        pegasus::startup(Configuration::singleton()).ok();
+        let cluster_info = Arc::new(PegasusClusterInfo::default());
+        let explored = Arc::new(Mutex::new(HashMap::new()));
+        let graph =
+            ExploreCountGraph { inner: create_exp_store(cluster_info.clone()), explored: explored.clone() };
+        register_graph(Arc::new(graph));
+        let partition_info = Arc::new(SimplePartition { num_servers: 1 });
+        (IRJobAssembly::with(partition_info, cluster_info), explored)
+    }
+
+    fn expand_pb(v_tag: KeyId, direction: i32, label: LabelId, alias: KeyId) -> pb::EdgeExpand {
+        pb::EdgeExpand {
+            v_tag: Some(v_tag.into()),
+            direction,
+            params: Some(query_params(vec![label.into()], vec![], None)),
+            expand_opt: 0,
+            alias: Some(alias.into()),
+            meta_data: None,
+            is_optional: false,
+        }
+    }
+
+    // g.V().hasLabel("person").as("a").out("created").as("b")
+    //  .match(__.as("a").out("knows").as("c"), __.as("b").in("created").as("c"))
+    // where the branches to intersect on "c" are given in the order of `knows_first`
+    fn init_intersect_request(knows_first: bool) -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: Some(TAG_A.into()),
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let mut knows_plan = PlanBuilder::default();
+        knows_plan.edge_expand(expand_pb(TAG_A, 0, KNOWS_LABEL, TAG_C));
+        let mut created_plan = PlanBuilder::default();
+        created_plan.edge_expand(expand_pb(TAG_B, 1, CREATED_LABEL, TAG_C));
+        let sub_plans =
+            if knows_first { vec![knows_plan, created_plan] } else { vec![created_plan, knows_plan] };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.edge_expand(expand_pb(TAG_A, 0, CREATED_LABEL, TAG_B));
+        job_builder.intersect(sub_plans, TAG_C.into());
+        job_builder.sink(pb::Sink {
+            tags: vec![TAG_A, TAG_B, TAG_C]
+                .into_iter()
+                .map(|tag| common_pb::NameOrIdKey { key: Some(tag.into()) })
+                .collect(),
+            sink_target: default_sink_target(),
+            tagged_targets: vec![],
+        });
+        job_builder.build().unwrap()
+    }
+
+    fn submit(
+        service: &'static IRJobAssembly<SimplePartition, PegasusClusterInfo>, job_req: JobRequest,
+    ) -> Vec<(ID, ID, ID)> {
+        let conf = JobConf::new("intersect_order_test");
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sink = ResultSink::new(tx);
+        let cancel_hook = sink.get_cancel_hook().clone();
+        let mut results = ResultStream::new(conf.job_id, cancel_hook, rx);
+        let job = JobDesc { input: job_req.source, plan: job_req.plan, resource: job_req.resource };
+        run_opt(conf, sink, move |worker| service.assemble(&job, worker)).expect("submit job failure;");
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            let record = parse_result(result.expect("err result")).unwrap();
+            let id_of = |tag: KeyId| {
+                record
+                    .get(Some(tag))
+                    .unwrap()
+                    .as_vertex()
+                    .unwrap()
+                    .id()
+            };
+            result_collection.push((id_of(TAG_A), id_of(TAG_B), id_of(TAG_C)));
+        }
+        result_collection.sort();
+        result_collection
+    }
+
+    fn physical_expand_pb(direction: i32, label: LabelId) -> physical_pb::EdgeExpand {
+        physical_pb::EdgeExpand {
+            v_tag: None,
+            direction,
+            params: Some(query_params(vec![label.into()], vec![], None)),
+            alias: None,
+            expand_opt: 0,
+            is_optional: false,
+        }
+    }
+
+    #[test]
+    fn intersect_expand_fewest_neighbors_first_test() {
+        let (service, explored) = initialize_explore_count_graph();
+        let service: &'static _ = Box::leak(Box::new(service));
+
+        // without the degrees collected, the branches are expanded in the order of the plan,
+        // i.e., "created" from each of (marko, lop), (josh, lop), (josh, ripple), (peter, lop), then
+        // "knows" from them all, as each of them is left with some one who created lop or ripple
+        let results = submit(service, init_intersect_request(false));
+        assert_eq!(results, vec![(1, 1 << 56 | 3, 4)]);
+        {
+            let explored = explored.lock().unwrap();
+            assert_eq!(explored.get(&vec![KNOWS_LABEL]), Some(&4));
+            assert_eq!(explored.get(&vec![CREATED_LABEL]), Some(&(4 + 4)));
+        }
+
+        // the degrees are collected off the job building, e.g., in the background
+        let graph = get_graph().unwrap();
+        collect_expand_degree(graph.as_ref(), &physical_expand_pb(0, KNOWS_LABEL)).unwrap();
+        collect_expand_degree(graph.as_ref(), &physical_expand_pb(1, CREATED_LABEL)).unwrap();
+        for knows_first in vec![true, false] {
+            explored.lock().unwrap().clear();
+            let results = submit(service, init_intersect_request(knows_first));
+            // marko created lop, which is also created by josh, whom marko knows
+            assert_eq!(results, vec![(1, 1 << 56 | 3, 4)]);
+            // "knows" is of fewer edges than "created", and thus always expanded first, from each of
+            // (marko, lop), (josh, lop), (josh, ripple), (peter, lop); then the "created" branch is
+            // expanded from (marko, lop) only, as marko is the only one who knows someone, in addition
+            // to the expansion of "created" from the 4 persons before the intersection
+            let explored = explored.lock().unwrap();
+            assert_eq!(explored.get(&vec![KNOWS_LABEL]), Some(&4), "knows_first: {}", knows_first);
+            assert_eq!(explored.get(&vec![CREATED_LABEL]), Some(&(4 + 1)), "knows_first: {}", knows_first);
+        }
+    }
+}
@@ -25,6 +25,7 @@ mod test {
     use ir_common::expr_parse::str_to_expr_pb;
     use ir_common::generated::algebra as algebra_pb;
     use ir_common::generated::common as common_pb;
+    use ir_common::generated::physical as pb;
     use ir_physical_client::physical_builder::*;
     use pegasus_server::JobRequest;
     use runtime::process::entry::Entry;
@@ -73,6 +74,7 @@ mod test {
                 common_pb::NameOrIdKey { key: None },
             ],
             sink_target: default_sink_target(),
+            tagged_targets: vec![],
         });
 
         job_builder.build().unwrap()
@@ -314,6 +316,110 @@ mod test {
         anti_join(2)
     }
 
+    // the same as `init_join_request()`, except that the right relation is tagged with `TAG_B`,
+    // and all tagged columns are sunk
+    fn init_join_with_right_alias_request(join_kind: i32) -> JobRequest {
+        let source_opr_1 = algebra_pb::Scan {
+            scan_opt: 0,
+            alias: Some(TAG_A.into()),
+            params: Some(query_params(vec![], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+
+        let source_opr_2 = algebra_pb::Scan {
+            scan_opt: 0,
+            alias: Some(TAG_B.into()),
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_dummy_source();
+        job_builder.join_func(
+            unsafe { std::mem::transmute(join_kind) },
+            move |left| {
+                left.add_scan_source(source_opr_1.clone());
+            },
+            move |right| {
+                right.add_scan_source(source_opr_2.clone());
+            },
+            vec![common_pb::Variable::from("@0.~id".to_string())],
+            vec![common_pb::Variable::from("@1.~id".to_string())],
+        );
+        job_builder.sink(algebra_pb::Sink {
+            tags: vec![],
+            sink_target: default_sink_target(),
+            tagged_targets: vec![],
+        });
+
+        job_builder.build().unwrap()
+    }
+
+    fn join_without_right_columns(
+        join_kind: i32, mut expected_result_ids: Vec<DefaultId>, worker_num: u32,
+    ) {
+        initialize();
+        let request = init_join_with_right_alias_request(join_kind);
+        let mut results = submit_query(request, worker_num);
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    let record = parse_result(res).unwrap();
+                    // the columns of the right relation must not appear in the output
+                    assert!(record.get(Some(TAG_B)).is_none());
+                    if let Some(vertex) = record.get(Some(TAG_A)).unwrap().as_vertex() {
+                        result_collection.push(vertex.id() as DefaultId);
+                    }
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        expected_result_ids.sort();
+        result_collection.sort();
+        assert_eq!(result_collection, expected_result_ids)
+    }
+
+    fn semi_join_without_right_columns(worker_num: u32) {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        let v6: DefaultId = LDBCVertexParser::to_global_id(6, 0);
+        join_without_right_columns(4, vec![v1, v2, v4, v6], worker_num) // SEMI
+    }
+
+    #[test]
+    fn semi_join_without_right_columns_test() {
+        semi_join_without_right_columns(1)
+    }
+
+    #[test]
+    fn semi_join_without_right_columns_w2_test() {
+        semi_join_without_right_columns(2)
+    }
+
+    fn anti_join_without_right_columns(worker_num: u32) {
+        let v3: DefaultId = LDBCVertexParser::to_global_id(3, 1);
+        let v5: DefaultId = LDBCVertexParser::to_global_id(5, 1);
+        join_without_right_columns(5, vec![v3, v5], worker_num) // ANTI
+    }
+
+    #[test]
+    fn anti_join_without_right_columns_test() {
+        anti_join_without_right_columns(1)
+    }
+
+    #[test]
+    fn anti_join_without_right_columns_w2_test() {
+        anti_join_without_right_columns(2)
+    }
+
     #[test]
     fn left_join_is_none_test() {
         initialize();
@@ -362,6 +468,7 @@ mod test {
                 common_pb::NameOrIdKey { key: Some(TAG_B.into()) },
             ],
             sink_target: default_sink_target(),
+            tagged_targets: vec![],
         });
 
         let request = job_builder.build().unwrap();
@@ -436,6 +543,7 @@ mod test {
                 common_pb::NameOrIdKey { key: Some(TAG_B.into()) },
             ],
             sink_target: default_sink_target(),
+            tagged_targets: vec![],
         });
 
         let request = job_builder.build().unwrap();
@@ -464,4 +572,126 @@ mod test {
         result_collection.sort();
         assert_eq!(result_collection, expected_result_ids)
     }
+
+    // g.V().as("a") join g.V().as("b") on (a.name, a.age) == (b.name, b.age), where the age of
+    // the software vertices is null
+    fn init_join_with_null_key_request(join_kind: i32, null_policy: pb::join::NullPolicy) -> JobRequest {
+        let source_opr_1 = algebra_pb::Scan {
+            scan_opt: 0,
+            alias: Some(TAG_A.into()),
+            params: Some(query_params(vec![], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+
+        let source_opr_2 = algebra_pb::Scan {
+            scan_opt: 0,
+            alias: Some(TAG_B.into()),
+            params: Some(query_params(vec![], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+
+        let mut left_plan = PlanBuilder::default();
+        left_plan.add_scan_source(source_opr_1);
+        let mut right_plan = PlanBuilder::default();
+        right_plan.add_scan_source(source_opr_2);
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_dummy_source();
+        job_builder.join_with_null_policy(
+            unsafe { std::mem::transmute(join_kind) },
+            left_plan,
+            right_plan,
+            vec![
+                common_pb::Variable::from("@0.name".to_string()),
+                common_pb::Variable::from("@0.age".to_string()),
+            ],
+            vec![
+                common_pb::Variable::from("@1.name".to_string()),
+                common_pb::Variable::from("@1.age".to_string()),
+            ],
+            null_policy,
+        );
+        job_builder.sink(algebra_pb::Sink {
+            tags: vec![],
+            sink_target: default_sink_target(),
+            tagged_targets: vec![],
+        });
+
+        job_builder.build().unwrap()
+    }
+
+    // Returns the ids of the vertices tagged `a`, with whether the vertex tagged `b` is null
+    fn join_with_null_key(join_kind: i32, null_policy: pb::join::NullPolicy) -> Vec<(DefaultId, bool)> {
+        initialize();
+        let request = init_join_with_null_key_request(join_kind, null_policy);
+        let mut results = submit_query(request, 2);
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    let record = parse_result(res).unwrap();
+                    let a = record
+                        .get(Some(TAG_A))
+                        .unwrap()
+                        .as_vertex()
+                        .unwrap();
+                    let b = record.get(Some(TAG_B)).unwrap();
+                    result_collection.push((a.id() as DefaultId, b.is_none()));
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        result_collection.sort();
+        result_collection
+    }
+
+    #[test]
+    fn inner_join_with_null_key_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        let v6: DefaultId = LDBCVertexParser::to_global_id(6, 0);
+        // the software vertices never match as their keys contain null
+        let results = join_with_null_key(0, pb::join::NullPolicy::NullNotMatch); // INNER
+        assert_eq!(results, vec![(v1, false), (v2, false), (v4, false), (v6, false)]);
+    }
+
+    #[test]
+    fn inner_join_with_null_safe_key_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let v3: DefaultId = LDBCVertexParser::to_global_id(3, 1);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        let v5: DefaultId = LDBCVertexParser::to_global_id(5, 1);
+        let v6: DefaultId = LDBCVertexParser::to_global_id(6, 0);
+        let mut expected_results = vec![v1, v2, v3, v4, v5, v6]
+            .into_iter()
+            .map(|id| (id, false))
+            .collect::<Vec<_>>();
+        expected_results.sort();
+        let results = join_with_null_key(0, pb::join::NullPolicy::NullSafe); // INNER
+        assert_eq!(results, expected_results);
+    }
+
+    #[test]
+    fn left_join_with_null_key_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let v3: DefaultId = LDBCVertexParser::to_global_id(3, 1);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        let v5: DefaultId = LDBCVertexParser::to_global_id(5, 1);
+        let v6: DefaultId = LDBCVertexParser::to_global_id(6, 0);
+        // the software vertices are unmatched, and padded with null for `b`
+        let mut expected_results =
+            vec![(v1, false), (v2, false), (v3, true), (v4, false), (v5, true), (v6, false)];
+        expected_results.sort();
+        let results = join_with_null_key(1, pb::join::NullPolicy::NullNotMatch); // LEFT_OUTER
+        assert_eq!(results, expected_results);
+    }
 }
@@ -120,7 +120,7 @@ mod test {
             .into_iter()
             .map(|tag| common_pb::Variable { tag: Some(tag.into()), property: None, node_type: None })
             .collect();
-        pb::Join { left_keys: keys.clone(), right_keys: keys.clone(), kind: 0 }
+        pb::Join { left_keys: keys.clone(), right_keys: keys.clone(), kind: 0, null_policy: 0 }
     }
 
     fn get_sink(tags: Vec<KeyId>) -> pb::Sink {
@@ -130,6 +130,7 @@ mod test {
                 .map(|tag| common_pb::NameOrIdKey { key: Some(tag.into()) })
                 .collect(),
             sink_target: default_sink_target(),
+            tagged_targets: vec![],
         }
     }
 
@@ -21,7 +21,8 @@ mod common;
 mod test {
     use std::sync::Arc;
 
-    use graph_proxy::apis::{register_graph, GraphElement};
+    use graph_proxy::apis::graph::OPTIONAL_KEY;
+    use graph_proxy::apis::{register_graph, DynDetails, GraphElement, Vertex, ID};
     use graph_proxy::create_exp_store;
     use graph_store::ldbc::LDBCVertexParser;
     use graph_store::prelude::DefaultId;
@@ -332,4 +333,63 @@ mod test {
 
         assert_eq!(results, expected_results)
     }
+
+    fn auxilia_on_vertices_test(ids: Vec<DefaultId>, is_optional: bool) -> Vec<Option<String>> {
+        let mut vertex_query_param = query_params(vec![], vec!["name".into()], None);
+        if is_optional {
+            vertex_query_param
+                .extra
+                .insert(OPTIONAL_KEY.to_string(), "true".to_string());
+        }
+        let auxilia_opr_pb = pb::GetV { tag: None, opt: 4, params: Some(vertex_query_param), alias: None };
+
+        let graph = create_exp_store(Arc::new(TestCluster {}));
+        register_graph(graph);
+        let conf = JobConf::new("auxilia_on_vertices_test");
+        let mut result = pegasus::run(conf, || {
+            let auxilia = auxilia_opr_pb.clone();
+            let ids = ids.clone();
+            |input, output| {
+                let source = ids
+                    .into_iter()
+                    .map(|id| Record::new(Vertex::new(id as ID, None, DynDetails::default()), None));
+                let mut stream = input.input_from(source)?;
+                let filter_map_func = auxilia.gen_filter_map().unwrap();
+                stream = stream.filter_map(move |input| filter_map_func.exec(input))?;
+                stream.sink_into(output)
+            }
+        })
+        .expect("build job failure");
+
+        let mut results = vec![];
+        while let Some(Ok(record)) = result.next() {
+            let entry = record.get(None).unwrap();
+            if let Some(vertex) = entry.as_vertex() {
+                let name = vertex
+                    .get_property(&"name".into())
+                    .unwrap()
+                    .try_to_owned()
+                    .unwrap();
+                results.push(Some(name.as_str().unwrap().into_owned()));
+            } else if entry.is_none() {
+                results.push(None);
+            }
+        }
+        results.sort();
+        results
+    }
+
+    // vertices that are absent from the graph are regarded as null if optional, and are filtered out otherwise
+    #[test]
+    fn optional_auxilia_absent_vertex_test() {
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let absent: DefaultId = LDBCVertexParser::to_global_id(10, 0);
+
+        let results = auxilia_on_vertices_test(vec![v1, absent, v2], true);
+        assert_eq!(results, vec![None, Some("marko".to_string()), Some("vadas".to_string())]);
+
+        let results = auxilia_on_vertices_test(vec![v1, absent, v2], false);
+        assert_eq!(results, vec![Some("marko".to_string()), Some("vadas".to_string())]);
+    }
 }
@@ -0,0 +1,242 @@
+//
+//! Copyright 2022 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+//!
+
+mod common;
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use ahash::{HashMap, HashMapExt};
+    use dyn_type::Object;
+    use graph_proxy::apis::graph::PKV;
+    use graph_proxy::apis::{
+        register_graph, Direction, DynDetails, Edge, GraphElement, PegasusClusterInfo, QueryParams,
+        ReadGraph, Statement, Vertex, ID,
+    };
+    use graph_proxy::{GraphProxyResult, SimplePartition};
+    use ir_common::expr_parse::str_to_expr_pb;
+    use ir_common::generated::algebra as pb;
+    use ir_common::{LabelId, NameOrId};
+    use ir_physical_client::physical_builder::*;
+    use pegasus::result::{ResultSink, ResultStream};
+    use pegasus::{run_opt, Configuration, JobConf};
+    use pegasus_server::job::{JobAssembly, JobDesc};
+    use pegasus_server::JobRequest;
+    use runtime::process::entry::Entry;
+    use runtime::IRJobAssembly;
+
+    use crate::common::test::*;
+
+    const CHAIN_LEN: ID = 10;
+
+    /// The chain graph of `v0 -> v1 -> ... -> v9`, where the vertex `vi` is named "vi", and that
+    /// counts how many times the out-going edges of a vertex are expanded.
+    struct ChainGraph {
+        expanded: Arc<AtomicUsize>,
+    }
+
+    fn chain_vertex(id: ID) -> Vertex {
+        let mut properties = HashMap::new();
+        properties.insert(NameOrId::from("name".to_string()), Object::from(format!("v{}", id)));
+        Vertex::new(id, Some(0), DynDetails::new(properties))
+    }
+
+    struct ChainStatement {
+        expanded: Arc<AtomicUsize>,
+    }
+
+    impl Statement<ID, Vertex> for ChainStatement {
+        fn exec(&self, id: ID) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            self.expanded.fetch_add(1, Ordering::SeqCst);
+            let next = Some(id + 1).filter(|next| *next < CHAIN_LEN);
+            Ok(Box::new(next.into_iter().map(chain_vertex)))
+        }
+    }
+
+    impl ReadGraph for ChainGraph {
+        fn scan_vertex(
+            &self, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            Ok(Box::new((0..CHAIN_LEN).map(chain_vertex)))
+        }
+
+        fn index_scan_vertex(
+            &self, _label: LabelId, _primary_key: &PKV, _params: &QueryParams,
+        ) -> GraphProxyResult<Option<Vertex>> {
+            Ok(None)
+        }
+
+        fn scan_edge(
+            &self, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            Ok(Box::new(std::iter::empty()))
+        }
+
+        fn get_vertex(
+            &self, ids: &[ID], _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            let vertices: Vec<Vertex> = ids
+                .iter()
+                .filter(|id| **id < CHAIN_LEN)
+                .map(|id| chain_vertex(*id))
+                .collect();
+            Ok(Box::new(vertices.into_iter()))
+        }
+
+        fn get_edge(
+            &self, _ids: &[ID], _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            Ok(Box::new(std::iter::empty()))
+        }
+
+        fn prepare_explore_vertex(
+            &self, direction: Direction, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
+            assert_eq!(direction, Direction::Out);
+            Ok(Box::new(ChainStatement { expanded: self.expanded.clone() }))
+        }
+
+        fn prepare_explore_edge(
+            &self, _direction: Direction, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
+            unimplemented!()
+        }
+
+        fn count_vertex(&self, _params: &QueryParams) -> GraphProxyResult<u64> {
+            Ok(CHAIN_LEN as u64)
+        }
+
+        fn count_edge(&self, _params: &QueryParams) -> GraphProxyResult<u64> {
+            Ok(CHAIN_LEN as u64 - 1)
+        }
+
+        fn get_primary_key(&self, _id: &ID) -> GraphProxyResult<Option<PKV>> {
+            Ok(None)
+        }
+    }
+
+    fn initialize_chain_graph() -> (IRJobAssembly<SimplePartition, PegasusClusterInfo>, Arc<AtomicUsize>) {
+        pegasus::startup(Configuration::singleton()).ok();
+        let cluster_info = Arc::new(PegasusClusterInfo::default());
+        let expanded = Arc::new(AtomicUsize::new(0));
+        register_graph(Arc::new(ChainGraph { expanded: expanded.clone() }));
+        let partition_info = Arc::new(SimplePartition { num_servers: 1 });
+        (IRJobAssembly::with(partition_info, cluster_info), expanded)
+    }
+
+    // g.V().out("1..10").until(has("name", "v3")).with("RESULT_OPT", "ALL_V")
+    fn init_path_until_request(is_condition_emit_only: bool, is_optional: bool) -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let edge_expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec![], vec![], None)),
+            expand_opt: 0,
+            alias: None,
+            meta_data: None,
+            is_optional: false,
+        };
+        let path_expand_opr = pb::PathExpand {
+            base: Some(edge_expand.into()),
+            start_tag: None,
+            alias: None,
+            hop_range: Some(pb::Range { lower: 1, upper: 10 }),
+            path_opt: 0,
+            result_opt: 1,
+            condition: str_to_expr_pb("@.name == \"v3\"".to_string()).ok(),
+            is_optional,
+            is_condition_emit_only,
+        };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.path_expand(path_expand_opr);
+        job_builder.sink(default_sink_pb());
+        job_builder.build().unwrap()
+    }
+
+    fn submit(
+        service: &'static IRJobAssembly<SimplePartition, PegasusClusterInfo>, job_req: JobRequest,
+    ) -> Vec<Vec<ID>> {
+        let conf = JobConf::new("path_until_test");
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sink = ResultSink::new(tx);
+        let cancel_hook = sink.get_cancel_hook().clone();
+        let mut results = ResultStream::new(conf.job_id, cancel_hook, rx);
+        let job = JobDesc { input: job_req.source, plan: job_req.plan, resource: job_req.resource };
+        run_opt(conf, sink, move |worker| service.assemble(&job, worker)).expect("submit job failure;");
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            let record = parse_result(result.expect("err result")).unwrap();
+            // an optional path expansion emits a null entry for the start without any path
+            let path = record
+                .get(None)
+                .unwrap()
+                .as_graph_path()
+                .cloned();
+            result_collection.push(
+                path.map(|path| {
+                    path.take_path()
+                        .unwrap()
+                        .into_iter()
+                        .map(|v| v.id())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            );
+        }
+        result_collection.sort();
+        result_collection
+    }
+
+    #[test]
+    fn path_until_terminate_expansion_test() {
+        let (service, expanded) = initialize_chain_graph();
+        let service: &'static _ = Box::leak(Box::new(service));
+        let expected_paths = vec![vec![0, 1, 2, 3], vec![1, 2, 3], vec![2, 3]];
+
+        let results = submit(service, init_path_until_request(false, false));
+        assert_eq!(results, expected_paths);
+        // the first hop is expanded from the 10 vertices; then a path terminates once reaching v3,
+        // e.g., the path from v0 is expanded from v1 and v2 only, while the paths starting from v3
+        // or after are expanded till v9, where there is no further vertex
+        let terminated_expanded = expanded.swap(0, Ordering::SeqCst);
+        assert_eq!(terminated_expanded, 10 + (2 + 1 + 0) + (6 + 5 + 4 + 3 + 2 + 1));
+
+        let results = submit(service, init_path_until_request(true, false));
+        assert_eq!(results, expected_paths);
+        // the path from each vertex is expanded till the upper bound of hops, i.e., 9 hops
+        let emit_only_expanded = expanded.swap(0, Ordering::SeqCst);
+        assert_eq!(emit_only_expanded, 10 + (8 + 8 + 7 + 6 + 5 + 4 + 3 + 2 + 1));
+        assert!(terminated_expanded < emit_only_expanded);
+
+        // the starts from v3 or after, without any path reaching v3, are kept as null entries
+        let results = submit(service, init_path_until_request(true, true));
+        let mut expected_optional_paths = vec![vec![]; 7];
+        expected_optional_paths.extend(expected_paths);
+        assert_eq!(results, expected_optional_paths);
+    }
+}
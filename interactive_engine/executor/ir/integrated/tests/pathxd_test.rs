@@ -62,6 +62,7 @@ mod test {
             result_opt,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let mut job_builder = JobBuilder::default();
@@ -114,6 +115,7 @@ mod test {
             result_opt: 1,
             condition: str_to_expr_pb("@.name == \"marko\"".to_string()).ok(),
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let mut job_builder = JobBuilder::default();
@@ -164,6 +166,7 @@ mod test {
             result_opt: if is_whole_path { 1 } else { 0 },
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let mut job_builder = JobBuilder::default();
@@ -916,6 +919,7 @@ mod test {
             result_opt,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let project_opr = pb::Project {
@@ -1008,9 +1012,10 @@ mod test {
             result_opt,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
-        let unfold_opr = pb::Unfold { tag: None, alias: None, meta_data: None };
+        let unfold_opr = pb::Unfold { tag: None, alias: None, meta_data: None, keep_empty: false };
 
         let mut job_builder = JobBuilder::default();
         job_builder.add_scan_source(source_opr);
@@ -1130,6 +1135,7 @@ mod test {
             result_opt: 2, // AllVE
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         let path_end = pb::GetV {
@@ -1222,6 +1228,7 @@ mod test {
             result_opt,
             condition: None,
             is_optional: false,
+            is_condition_emit_only: false,
         };
 
         // to project path.name
@@ -62,7 +62,7 @@ mod test {
 
         let mut job_builder = JobBuilder::default();
         job_builder.add_scan_source(source_opr);
-        job_builder.sample(sample);
+        job_builder.sample(sample).unwrap();
         job_builder.sink(default_sink_pb());
 
         job_builder.build().unwrap()
@@ -92,7 +92,7 @@ mod test {
         let mut job_builder = JobBuilder::default();
         job_builder.add_scan_source(source_opr);
         job_builder.edge_expand(expand_opr);
-        job_builder.sample(sample);
+        job_builder.sample(sample).unwrap();
         job_builder.sink(default_sink_pb());
 
         job_builder.build().unwrap()
@@ -396,7 +396,7 @@ mod test {
                     predicates: vec![ir_common::generated::algebra::index_predicate::Triplet {
                         key: Some("id".to_string().into()),
                         value: Some(ir_common::generated::algebra::index_predicate::triplet::Value::Const(
-                            vec![1, 2, 4, 6].into(),
+                            vec![1i64, 2, 4, 6].into(),
                         )),
                         cmp: 6, // within
                     }],
@@ -420,6 +420,63 @@ mod test {
         assert_eq!(result_ids, expected_ids)
     }
 
+    // g.V().hasLabel('person').has("age", P.between(27, 32))
+    #[test]
+    fn scan_between_test() {
+        let source_iter = scan_gen(pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec![], None)),
+            idx_predicate: Some(ir_common::generated::algebra::IndexPredicate::between(
+                "age".to_string().into(),
+                27.into(),
+                32.into(),
+            )),
+            is_count_only: false,
+        });
+        let mut result_ids = vec![];
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let mut expected_ids = vec![v1, v2];
+        for record in source_iter {
+            if let Some(element) = record.get(None).unwrap().as_vertex() {
+                result_ids.push(element.id() as usize)
+            }
+        }
+        result_ids.sort();
+        expected_ids.sort();
+        assert_eq!(result_ids, expected_ids)
+    }
+
+    // g.V().has("age", P.between(27, 32)).has("name", "marko")
+    #[test]
+    fn scan_between_and_pred_test() {
+        let source_iter = scan_gen(pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(
+                vec![],
+                vec![],
+                str_to_expr_pb("@.name == \"marko\"".to_string()).ok(),
+            )),
+            idx_predicate: Some(ir_common::generated::algebra::IndexPredicate::between(
+                "age".to_string().into(),
+                27.into(),
+                32.into(),
+            )),
+            is_count_only: false,
+        });
+        let mut result_ids = vec![];
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let expected_ids = vec![v1];
+        for record in source_iter {
+            if let Some(element) = record.get(None).unwrap().as_vertex() {
+                result_ids.push(element.id() as usize)
+            }
+        }
+        assert_eq!(result_ids, expected_ids)
+    }
+
     // g.V().hasLabel('person').has("id", within[1,2,4,6]).has("name", "marko")
     #[test]
     fn scan_pk_and_pred_test() {
@@ -436,7 +493,7 @@ mod test {
                     predicates: vec![ir_common::generated::algebra::index_predicate::Triplet {
                         key: Some("id".to_string().into()),
                         value: Some(ir_common::generated::algebra::index_predicate::triplet::Value::Const(
-                            vec![1, 2, 4, 6].into(),
+                            vec![1i64, 2, 4, 6].into(),
                         )),
                         cmp: 6, // within
                     }],
@@ -0,0 +1,313 @@
+//
+//! Copyright 2022 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+//!
+//!
+
+mod common;
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    use ahash::{HashMap, HashMapExt};
+    use dyn_type::Object;
+    use graph_proxy::apis::graph::PKV;
+    use graph_proxy::apis::{
+        register_graph, Direction, DynDetails, Edge, GraphElement, PegasusClusterInfo, QueryParams,
+        ReadGraph, Statement, Vertex, ID,
+    };
+    use graph_proxy::{GraphProxyResult, SimplePartition};
+    use ir_common::expr_parse::str_to_expr_pb;
+    use ir_common::generated::algebra as pb;
+    use ir_common::{LabelId, NameOrId};
+    use ir_physical_client::physical_builder::*;
+    use pegasus::result::{ResultSink, ResultStream};
+    use pegasus::{run_opt, Configuration, JobConf};
+    use pegasus_server::job::{JobAssembly, JobDesc};
+    use pegasus_server::JobRequest;
+    use runtime::process::entry::Entry;
+    use runtime::IRJobAssembly;
+
+    use crate::common::test::*;
+
+    const GRID_WIDTH: ID = 4;
+    const GRID_SIZE: ID = GRID_WIDTH * GRID_WIDTH;
+
+    /// The 4x4 grid graph, where the vertex `vi` is named "vi" and located at the row `i / 4` and the
+    /// column `i % 4`, and has out-going edges to its neighbors in the four directions.
+    struct GridGraph;
+
+    fn grid_vertex(id: ID) -> Vertex {
+        let mut properties = HashMap::new();
+        properties.insert(NameOrId::from("name".to_string()), Object::from(format!("v{}", id)));
+        Vertex::new(id, Some(0), DynDetails::new(properties))
+    }
+
+    fn grid_neighbors(id: ID) -> Vec<ID> {
+        let (row, col) = (id / GRID_WIDTH, id % GRID_WIDTH);
+        let mut neighbors = vec![];
+        if row > 0 {
+            neighbors.push(id - GRID_WIDTH);
+        }
+        if row < GRID_WIDTH - 1 {
+            neighbors.push(id + GRID_WIDTH);
+        }
+        if col > 0 {
+            neighbors.push(id - 1);
+        }
+        if col < GRID_WIDTH - 1 {
+            neighbors.push(id + 1);
+        }
+        neighbors
+    }
+
+    struct GridStatement;
+
+    impl Statement<ID, Vertex> for GridStatement {
+        fn exec(&self, id: ID) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            Ok(Box::new(grid_neighbors(id).into_iter().map(grid_vertex)))
+        }
+    }
+
+    impl ReadGraph for GridGraph {
+        fn scan_vertex(
+            &self, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            Ok(Box::new((0..GRID_SIZE).map(grid_vertex)))
+        }
+
+        fn index_scan_vertex(
+            &self, _label: LabelId, _primary_key: &PKV, _params: &QueryParams,
+        ) -> GraphProxyResult<Option<Vertex>> {
+            Ok(None)
+        }
+
+        fn scan_edge(
+            &self, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            Ok(Box::new(std::iter::empty()))
+        }
+
+        fn get_vertex(
+            &self, ids: &[ID], _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Vertex> + Send>> {
+            let vertices: Vec<Vertex> = ids
+                .iter()
+                .filter(|id| **id < GRID_SIZE)
+                .map(|id| grid_vertex(*id))
+                .collect();
+            Ok(Box::new(vertices.into_iter()))
+        }
+
+        fn get_edge(
+            &self, _ids: &[ID], _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Iterator<Item = Edge> + Send>> {
+            Ok(Box::new(std::iter::empty()))
+        }
+
+        fn prepare_explore_vertex(
+            &self, direction: Direction, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Vertex>>> {
+            assert_eq!(direction, Direction::Out);
+            Ok(Box::new(GridStatement))
+        }
+
+        fn prepare_explore_edge(
+            &self, _direction: Direction, _params: &QueryParams,
+        ) -> GraphProxyResult<Box<dyn Statement<ID, Edge>>> {
+            unimplemented!()
+        }
+
+        fn count_vertex(&self, _params: &QueryParams) -> GraphProxyResult<u64> {
+            Ok(GRID_SIZE as u64)
+        }
+
+        fn count_edge(&self, _params: &QueryParams) -> GraphProxyResult<u64> {
+            Ok((0..GRID_SIZE)
+                .map(|id| grid_neighbors(id).len() as u64)
+                .sum())
+        }
+
+        fn get_primary_key(&self, _id: &ID) -> GraphProxyResult<Option<PKV>> {
+            Ok(None)
+        }
+    }
+
+    /// The reference BFS from `source`, which returns the hops and the number of the shortest paths
+    /// from `source` to each of the vertices in the grid.
+    fn reference_bfs(source: ID) -> HashMap<ID, (usize, usize)> {
+        let mut visited = HashMap::new();
+        visited.insert(source, (0, 1));
+        let mut queue = VecDeque::new();
+        queue.push_back(source);
+        while let Some(curr) = queue.pop_front() {
+            let (hops, count) = visited[&curr];
+            for next in grid_neighbors(curr) {
+                match visited.get_mut(&next) {
+                    None => {
+                        visited.insert(next, (hops + 1, count));
+                        queue.push_back(next);
+                    }
+                    Some((next_hops, next_count)) if *next_hops == hops + 1 => *next_count += count,
+                    Some(_) => {}
+                }
+            }
+        }
+        visited
+    }
+
+    fn initialize_grid_graph() -> IRJobAssembly<SimplePartition, PegasusClusterInfo> {
+        pegasus::startup(Configuration::singleton()).ok();
+        let cluster_info = Arc::new(PegasusClusterInfo::default());
+        register_graph(Arc::new(GridGraph));
+        let partition_info = Arc::new(SimplePartition { num_servers: 1 });
+        IRJobAssembly::with(partition_info, cluster_info)
+    }
+
+    // g.V().out("1..7").with("PATH_OPT", path_opt).with("RESULT_OPT", "ALL_V"), with an optional
+    // condition on the target vertices
+    fn init_shortest_path_request(path_opt: i32, condition: Option<&str>) -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let edge_expand = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0,
+            params: Some(query_params(vec![], vec![], None)),
+            expand_opt: 0,
+            alias: None,
+            meta_data: None,
+            is_optional: false,
+        };
+        let path_expand_opr = pb::PathExpand {
+            base: Some(edge_expand.into()),
+            start_tag: None,
+            alias: None,
+            hop_range: Some(pb::Range { lower: 1, upper: 7 }),
+            path_opt,
+            result_opt: 1,
+            condition: condition.and_then(|condition| str_to_expr_pb(condition.to_string()).ok()),
+            is_optional: false,
+            is_condition_emit_only: false,
+        };
+
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.path_expand(path_expand_opr);
+        job_builder.sink(default_sink_pb());
+        job_builder.build().unwrap()
+    }
+
+    fn submit(
+        service: &'static IRJobAssembly<SimplePartition, PegasusClusterInfo>, job_req: JobRequest,
+    ) -> Vec<Vec<ID>> {
+        let conf = JobConf::new("shortest_path_test");
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let sink = ResultSink::new(tx);
+        let cancel_hook = sink.get_cancel_hook().clone();
+        let mut results = ResultStream::new(conf.job_id, cancel_hook, rx);
+        let job = JobDesc { input: job_req.source, plan: job_req.plan, resource: job_req.resource };
+        run_opt(conf, sink, move |worker| service.assemble(&job, worker)).expect("submit job failure;");
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            let record = parse_result(result.expect("err result")).unwrap();
+            let path = record
+                .get(None)
+                .unwrap()
+                .as_graph_path()
+                .unwrap()
+                .clone();
+            result_collection.push(
+                path.take_path()
+                    .unwrap()
+                    .into_iter()
+                    .map(|v| v.id())
+                    .collect(),
+            );
+        }
+        result_collection.sort();
+        result_collection
+    }
+
+    /// Group the paths by their (start, end) vertices, and check that each path is a valid path in the grid.
+    fn group_paths(paths: Vec<Vec<ID>>) -> HashMap<(ID, ID), Vec<Vec<ID>>> {
+        let mut grouped: HashMap<(ID, ID), Vec<Vec<ID>>> = HashMap::new();
+        for path in paths {
+            for hop in path.windows(2) {
+                assert!(grid_neighbors(hop[0]).contains(&hop[1]));
+            }
+            grouped
+                .entry((path[0], *path.last().unwrap()))
+                .or_default()
+                .push(path);
+        }
+        grouped
+    }
+
+    #[test]
+    fn any_shortest_path_test() {
+        let service: &'static _ = Box::leak(Box::new(initialize_grid_graph()));
+        let grouped = group_paths(submit(service, init_shortest_path_request(3, None)));
+        // every pair of distinct vertices is connected within 6 hops in the 4x4 grid
+        assert_eq!(grouped.len(), (GRID_SIZE * (GRID_SIZE - 1)) as usize);
+        for source in 0..GRID_SIZE {
+            for (target, (hops, _)) in reference_bfs(source) {
+                if target == source {
+                    continue;
+                }
+                let paths = &grouped[&(source, target)];
+                assert_eq!(paths.len(), 1);
+                assert_eq!(paths[0].len() - 1, hops);
+            }
+        }
+    }
+
+    #[test]
+    fn all_shortest_path_test() {
+        let service: &'static _ = Box::leak(Box::new(initialize_grid_graph()));
+        let grouped = group_paths(submit(service, init_shortest_path_request(4, None)));
+        assert_eq!(grouped.len(), (GRID_SIZE * (GRID_SIZE - 1)) as usize);
+        for source in 0..GRID_SIZE {
+            for (target, (hops, count)) in reference_bfs(source) {
+                if target == source {
+                    continue;
+                }
+                let paths = &grouped[&(source, target)];
+                assert_eq!(paths.len(), count);
+                assert!(paths.iter().all(|path| path.len() - 1 == hops));
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_path_to_target_test() {
+        let service: &'static _ = Box::leak(Box::new(initialize_grid_graph()));
+        let grouped =
+            group_paths(submit(service, init_shortest_path_request(4, Some("@.name == \"v15\""))));
+        assert_eq!(grouped.len(), (GRID_SIZE - 1) as usize);
+        for source in 0..GRID_SIZE - 1 {
+            let (hops, count) = reference_bfs(source)[&(GRID_SIZE - 1)];
+            let paths = &grouped[&(source, GRID_SIZE - 1)];
+            assert_eq!(paths.len(), count);
+            assert!(paths.iter().all(|path| path.len() - 1 == hops));
+        }
+    }
+}
@@ -20,6 +20,10 @@ mod common;
 #[cfg(test)]
 mod test {
 
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    use dyn_type::Object;
     use graph_proxy::apis::GraphElement;
     use graph_store::common::DefaultId;
     use graph_store::ldbc::LDBCVertexParser;
@@ -29,6 +33,7 @@ mod test {
     use ir_physical_client::physical_builder::JobBuilder;
     use pegasus_server::JobRequest;
     use runtime::process::entry::Entry;
+    use runtime::process::operator::sink::{set_csv_sink_dir, CSV_PATH_KEY};
 
     use crate::common::test::*;
 
@@ -45,7 +50,8 @@ mod test {
             meta_data: None,
         };
 
-        let sink_opr = pb::Sink { tags: sink_keys, sink_target: default_sink_target() };
+        let sink_opr =
+            pb::Sink { tags: sink_keys, sink_target: default_sink_target(), tagged_targets: vec![] };
 
         let mut job_builder = JobBuilder::default();
         job_builder.add_scan_source(source_opr);
@@ -214,4 +220,445 @@ mod test {
             }
         }
     }
+
+    // g.V().as(0).out().as(1) + sink_opr
+    fn init_expand_sink_request(sink_opr: pb::Sink) -> JobRequest {
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: Some(0.into()),
+            params: Some(query_params(vec![], vec![], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let expand_opr = pb::EdgeExpand {
+            v_tag: None,
+            direction: 0, // out
+            params: Some(query_params(vec![], vec![], None)),
+            expand_opt: 0, // expand vertex
+            alias: Some(1.into()),
+            meta_data: None,
+            is_optional: false,
+        };
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.shuffle(None);
+        job_builder.edge_expand(expand_opr);
+        job_builder.sink(sink_opr);
+        job_builder.build().unwrap()
+    }
+
+    fn csv_sink_target(csv_file: String) -> pb::sink::SinkTarget {
+        pb::sink::SinkTarget {
+            inner: Some(pb::sink::sink_target::Inner::SinkCsv(pb::SinkCsv {
+                extra: vec![(CSV_PATH_KEY.to_string(), csv_file)]
+                    .into_iter()
+                    .collect::<HashMap<_, _>>(),
+            })),
+        }
+    }
+
+    // g.V().as(0).out().as(1) + Sink(0) to client, and Sink(csv_tags) to csv
+    fn init_multi_target_sink_request(
+        csv_file: String, csv_tags: Vec<common_pb::NameOrIdKey>,
+    ) -> JobRequest {
+        let sink_opr = pb::Sink {
+            tags: vec![common_pb::NameOrIdKey { key: Some(0.into()) }],
+            sink_target: default_sink_target(),
+            tagged_targets: vec![pb::sink::TaggedSinkTarget {
+                tags: csv_tags,
+                sink_target: Some(csv_sink_target(csv_file)),
+            }],
+        };
+        init_expand_sink_request(sink_opr)
+    }
+
+    fn read_csv_ids(csv_path: &Path) -> Vec<DefaultId> {
+        let csv = std::fs::read_to_string(csv_path).unwrap();
+        std::fs::remove_file(csv_path).unwrap();
+        let mut csv_collection: Vec<DefaultId> = csv
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        csv_collection.sort();
+        csv_collection
+    }
+
+    fn expected_csv_ids() -> Vec<DefaultId> {
+        let v2: DefaultId = LDBCVertexParser::to_global_id(2, 0);
+        let v3: DefaultId = LDBCVertexParser::to_global_id(3, 1);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        let v5: DefaultId = LDBCVertexParser::to_global_id(5, 1);
+        let mut expected_csv_ids = vec![v2, v3, v4, v3, v5, v3];
+        expected_csv_ids.sort();
+        expected_csv_ids
+    }
+
+    // g.V().as(0).out().as(1) + Sink(0) to client, and Sink(1) to csv
+    #[test]
+    fn sink_tags_into_multiple_targets() {
+        initialize();
+        let csv_file = format!("sink_test_{}.csv", std::process::id());
+        let csv_path = std::env::temp_dir().join(&csv_file);
+        // the lines written by a previous run are truncated
+        std::fs::write(&csv_path, "stale line\n").unwrap();
+        set_csv_sink_dir(std::env::temp_dir());
+        let request =
+            init_multi_target_sink_request(csv_file, vec![common_pb::NameOrIdKey { key: Some(1.into()) }]);
+
+        let mut results = submit_query(request, 2);
+        let mut client_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    let entry = parse_result(res).unwrap();
+                    // only the tag of the client target is sinked into the client
+                    assert!(entry.get(Some(1)).is_none());
+                    if let Some(vertex) = entry.get(Some(0)).unwrap().as_vertex() {
+                        client_collection.push(vertex.id() as DefaultId);
+                    }
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        let csv_collection = read_csv_ids(&csv_path);
+
+        let v1: DefaultId = LDBCVertexParser::to_global_id(1, 0);
+        let v4: DefaultId = LDBCVertexParser::to_global_id(4, 0);
+        let v6: DefaultId = LDBCVertexParser::to_global_id(6, 0);
+        let mut expected_client_ids = vec![v1, v1, v1, v4, v4, v6];
+        client_collection.sort();
+        expected_client_ids.sort();
+        assert_eq!(client_collection, expected_client_ids);
+        assert_eq!(csv_collection, expected_csv_ids());
+    }
+
+    // g.V().as(0).out().as(1) + Sink(1) to csv only, responding with the number of the sinked records
+    #[test]
+    fn sink_tags_into_csv_target_only() {
+        initialize();
+        let csv_file = format!("sink_test_only_{}.csv", std::process::id());
+        let csv_path = std::env::temp_dir().join(&csv_file);
+        set_csv_sink_dir(std::env::temp_dir());
+        let sink_opr = pb::Sink {
+            tags: vec![common_pb::NameOrIdKey { key: Some(1.into()) }],
+            sink_target: Some(csv_sink_target(csv_file)),
+            tagged_targets: vec![],
+        };
+        let request = init_expand_sink_request(sink_opr);
+
+        let mut results = submit_query(request, 2);
+        let mut counts = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    let entry = parse_result(res).unwrap();
+                    if let Some(Object::KV(kv)) = entry.get(None).unwrap().as_object() {
+                        for count in kv.values() {
+                            counts.push(count.as_u64().unwrap());
+                        }
+                    }
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        assert_eq!(counts, vec![6]);
+        assert_eq!(read_csv_ids(&csv_path), expected_csv_ids());
+    }
+
+    #[test]
+    fn sink_into_invalid_csv_targets() {
+        initialize();
+        set_csv_sink_dir(std::env::temp_dir());
+        // no tags to sink into the csv file
+        let request = init_multi_target_sink_request("sink_test_no_tags.csv".to_string(), vec![]);
+        let mut results = submit_query(request, 2);
+        assert!(results.next().unwrap().is_err());
+        // the csv file is out of the sink directory
+        let request = init_multi_target_sink_request(
+            "../sink_test_out_of_dir.csv".to_string(),
+            vec![common_pb::NameOrIdKey { key: Some(1.into()) }],
+        );
+        let mut results = submit_query(request, 2);
+        assert!(results.next().unwrap().is_err());
+        // the csv file is out of the sink directory via a symbolic link within it
+        let link = format!("sink_test_link_{}", std::process::id());
+        let link_path = std::env::temp_dir().join(&link);
+        let _ = std::fs::remove_file(&link_path);
+        std::os::unix::fs::symlink("/", &link_path).unwrap();
+        let request = init_multi_target_sink_request(
+            format!("{}/sink_test_out_of_dir.csv", link),
+            vec![common_pb::NameOrIdKey { key: Some(1.into()) }],
+        );
+        let mut results = submit_query(request, 2);
+        let result = results.next().unwrap();
+        std::fs::remove_file(&link_path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "arrow_sink")]
+    fn primitive_type(t: common_pb::PrimitiveType) -> common_pb::IrDataType {
+        common_pb::IrDataType {
+            r#type: Some(common_pb::ir_data_type::Type::DataType(common_pb::DataType {
+                item: Some(common_pb::data_type::Item::PrimitiveType(t as i32)),
+            })),
+        }
+    }
+
+    #[cfg(feature = "arrow_sink")]
+    fn string_type() -> common_pb::IrDataType {
+        common_pb::IrDataType {
+            r#type: Some(common_pb::ir_data_type::Type::DataType(common_pb::DataType {
+                item: Some(common_pb::data_type::Item::String(common_pb::String {
+                    item: Some(common_pb::string::Item::LongText(common_pb::string::LongText {})),
+                })),
+            })),
+        }
+    }
+
+    // g.V().hasLabel("person").project(@.name, @.age, @.age > 30) + Sink(0, 1, 2) as arrow
+    #[cfg(feature = "arrow_sink")]
+    fn init_sink_arrow_request(batch_size: i32, column_types: Vec<common_pb::IrDataType>) -> JobRequest {
+        use ir_common::expr_parse::str_to_expr_pb;
+
+        let source_opr = pb::Scan {
+            scan_opt: 0,
+            alias: None,
+            params: Some(query_params(vec![PERSON_LABEL.into()], vec!["name".into(), "age".into()], None)),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let project_opr = pb::Project {
+            mappings: vec![
+                pb::project::ExprAlias {
+                    expr: str_to_expr_pb("@.name".to_string()).ok(),
+                    alias: Some(TAG_A.into()),
+                },
+                pb::project::ExprAlias {
+                    expr: str_to_expr_pb("@.age".to_string()).ok(),
+                    alias: Some(TAG_B.into()),
+                },
+                pb::project::ExprAlias {
+                    expr: str_to_expr_pb("@.age > 30".to_string()).ok(),
+                    alias: Some(TAG_C.into()),
+                },
+            ],
+            is_append: false,
+            meta_data: vec![],
+        };
+        let sink_opr = pb::Sink {
+            tags: vec![
+                common_pb::NameOrIdKey { key: Some(TAG_A.into()) },
+                common_pb::NameOrIdKey { key: Some(TAG_B.into()) },
+                common_pb::NameOrIdKey { key: Some(TAG_C.into()) },
+            ],
+            sink_target: Some(pb::sink::SinkTarget {
+                inner: Some(pb::sink::sink_target::Inner::SinkArrow(pb::SinkArrow {
+                    batch_size,
+                    id_name_mappings: vec![],
+                    column_types,
+                })),
+            }),
+            tagged_targets: vec![],
+        };
+        let mut job_builder = JobBuilder::default();
+        job_builder.add_scan_source(source_opr);
+        job_builder.project(project_opr);
+        job_builder.sink(sink_opr);
+        job_builder.build().unwrap()
+    }
+
+    #[cfg(feature = "arrow_sink")]
+    fn expected_person_rows() -> Vec<(String, i64, bool)> {
+        let mut expected_results = vec![
+            ("marko".to_string(), 29, false),
+            ("vadas".to_string(), 27, false),
+            ("josh".to_string(), 32, true),
+            ("peter".to_string(), 35, true),
+        ];
+        expected_results.sort();
+        expected_results
+    }
+
+    #[cfg(feature = "arrow_sink")]
+    #[test]
+    fn sink_projection_as_arrow() {
+        use arrow::array::{Array, BooleanArray, Int64Array, StringArray};
+        use arrow::datatypes::DataType;
+        use arrow::ipc::reader::StreamReader;
+
+        initialize();
+        let request = init_sink_arrow_request(
+            1,
+            vec![
+                string_type(),
+                primitive_type(common_pb::PrimitiveType::DtSignedInt64),
+                primitive_type(common_pb::PrimitiveType::DtBool),
+            ],
+        );
+        let mut results = submit_query(request, 2);
+        let mut batch_count = 0;
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            match result {
+                Ok(res) => {
+                    // each result is a standalone arrow ipc stream of a batch
+                    batch_count += 1;
+                    let reader = StreamReader::try_new(std::io::Cursor::new(res), None).unwrap();
+                    let schema = reader.schema();
+                    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+                    assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+                    assert_eq!(schema.field(2).data_type(), &DataType::Boolean);
+                    for batch in reader {
+                        let batch = batch.unwrap();
+                        let names = batch
+                            .column(0)
+                            .as_any()
+                            .downcast_ref::<StringArray>()
+                            .unwrap();
+                        let ages = batch
+                            .column(1)
+                            .as_any()
+                            .downcast_ref::<Int64Array>()
+                            .unwrap();
+                        let is_olds = batch
+                            .column(2)
+                            .as_any()
+                            .downcast_ref::<BooleanArray>()
+                            .unwrap();
+                        for i in 0..batch.num_rows() {
+                            assert!(!names.is_null(i));
+                            result_collection.push((
+                                names.value(i).to_string(),
+                                ages.value(i),
+                                is_olds.value(i),
+                            ));
+                        }
+                    }
+                }
+                Err(e) => {
+                    panic!("err result {:?}", e);
+                }
+            }
+        }
+        // a batch of a single record is sent out once it is full
+        assert_eq!(batch_count, 4);
+        result_collection.sort();
+        assert_eq!(result_collection, expected_person_rows());
+    }
+
+    // the columns of no given types, or of the values mismatching the given types, are encoded
+    #[cfg(feature = "arrow_sink")]
+    #[test]
+    fn sink_projection_as_arrow_of_mismatched_types() {
+        use arrow::array::{Array, BinaryArray};
+        use arrow::datatypes::DataType;
+        use arrow::ipc::reader::StreamReader;
+        use ir_common::generated::results as result_pb;
+        use prost::Message;
+
+        initialize();
+        let requests = vec![
+            init_sink_arrow_request(
+                8,
+                vec![
+                    primitive_type(common_pb::PrimitiveType::DtSignedInt64),
+                    primitive_type(common_pb::PrimitiveType::DtSignedInt64),
+                    primitive_type(common_pb::PrimitiveType::DtSignedInt64),
+                ],
+            ),
+            init_sink_arrow_request(8, vec![]),
+        ];
+        let expected_types = vec![
+            vec![DataType::Binary, DataType::Int64, DataType::Int64],
+            vec![DataType::Binary, DataType::Binary, DataType::Binary],
+        ];
+        for (request, expected_types) in requests
+            .into_iter()
+            .zip(expected_types.into_iter())
+        {
+            let mut results = submit_query(request, 2);
+            let mut row_count = 0;
+            while let Some(result) = results.next() {
+                match result {
+                    Ok(res) => {
+                        let reader = StreamReader::try_new(std::io::Cursor::new(res), None).unwrap();
+                        let schema = reader.schema();
+                        for (i, expected_type) in expected_types.iter().enumerate() {
+                            assert_eq!(schema.field(i).data_type(), expected_type);
+                        }
+                        for batch in reader {
+                            let batch = batch.unwrap();
+                            let names = batch
+                                .column(0)
+                                .as_any()
+                                .downcast_ref::<BinaryArray>()
+                                .unwrap();
+                            for i in 0..batch.num_rows() {
+                                let name = result_pb::Entry::decode(names.value(i)).unwrap();
+                                assert!(name.inner.is_some());
+                            }
+                            row_count += batch.num_rows();
+                        }
+                    }
+                    Err(e) => {
+                        panic!("err result {:?}", e);
+                    }
+                }
+            }
+            assert_eq!(row_count, 4);
+        }
+    }
+
+    // read the batches by pyarrow, as the python client does
+    #[cfg(feature = "arrow_sink")]
+    #[test]
+    fn sink_projection_as_arrow_read_by_pyarrow() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        initialize();
+        let request = init_sink_arrow_request(
+            3,
+            vec![
+                string_type(),
+                primitive_type(common_pb::PrimitiveType::DtSignedInt64),
+                primitive_type(common_pb::PrimitiveType::DtBool),
+            ],
+        );
+        let script = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/arrow/read_ipc_stream.py");
+        let mut results = submit_query(request, 2);
+        let mut result_collection = vec![];
+        while let Some(result) = results.next() {
+            let res = result.expect("err result");
+            let mut reader = Command::new("python3")
+                .arg(script)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .expect("python3 with pyarrow is required");
+            reader
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(&res)
+                .unwrap();
+            let output = reader.wait_with_output().unwrap();
+            assert!(output.status.success(), "pyarrow fails to read the batch");
+            for line in String::from_utf8(output.stdout)
+                .unwrap()
+                .lines()
+            {
+                let row: Vec<&str> = line.split(',').collect();
+                result_collection.push((row[0].to_string(), row[1].parse().unwrap(), row[2] == "True"));
+            }
+        }
+        result_collection.sort();
+        assert_eq!(result_collection, expected_person_rows());
+    }
 }
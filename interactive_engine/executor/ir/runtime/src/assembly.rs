@@ -13,21 +13,27 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::collections::HashMap;
 use std::convert::TryInto;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::vec;
 
 use graph_proxy::apis::cluster_info::ClusterInfo;
+use graph_proxy::apis::explain::{collect_expand_degree, estimate_expand_degree};
 use graph_proxy::apis::partitioner::PartitionInfo;
 use ir_common::error::ParsePbError;
 use ir_common::generated::algebra as algebra_pb;
 use ir_common::generated::algebra::join::JoinKind;
 use ir_common::generated::physical as pb;
 use ir_common::generated::physical::physical_opr::operator::OpKind;
+use ir_common::NameOrId;
+use lazy_static::lazy_static;
 use pegasus::api::function::*;
 use pegasus::api::{
-    Collect, CorrelatedSubTask, Count, Dedup, Filter, Fold, FoldByKey, HasAny, IterCondition, Iteration,
-    Join, KeyBy, Limit, Map, Merge, Sink, SortBy, SortLimitBy,
+    Chunk, Collect, CorrelatedSubTask, Count, Dedup, Filter, Fold, FoldByKey, HasAny, IterCondition,
+    Iteration, Join, KeyBy, Limit, Map, Merge, Pair, Sink, SortBy, SortLimitBy,
 };
 use pegasus::stream::Stream;
 use pegasus::{BuildJobError, Worker};
@@ -36,20 +42,32 @@ use pegasus_server::job_pb as server_pb;
 use prost::Message;
 
 use crate::error::{FnExecError, FnGenError, FnGenResult};
+use crate::process::entry::NullEntry;
 use crate::process::functions::{ApplyGen, CompareFunction, FoldGen, GroupGen, JoinKeyGen, KeyFunction};
 use crate::process::operator::accum::accumulator::Accumulator;
 use crate::process::operator::accum::{SampleAccum, SampleAccumFactoryGen};
-use crate::process::operator::filter::FilterFuncGen;
+use crate::process::operator::filter::{FilterFuncGen, ShortestPathCollector, ShortestPathVisitor};
 use crate::process::operator::flatmap::FlatMapFuncGen;
 use crate::process::operator::keyed::KeyFunctionGen;
 use crate::process::operator::map::{FilterMapFuncGen, MapFuncGen};
 use crate::process::operator::shuffle::RecordRouter;
 use crate::process::operator::sink::{SinkGen, Sinker};
 use crate::process::operator::sort::CompareFunctionGen;
-use crate::process::operator::source::SourceOperator;
+use crate::process::operator::source::{scan_projection, SourceOperator};
+use crate::process::operator::subtask::{count_apply_scope, BatchedApply};
 use crate::process::record::{Record, RecordKey};
 use crate::router::{DefaultRouter, Router};
 
+/// The time that the order of an intersection is kept for the workers of a job building it.
+const INTERSECT_ORDER_TTL: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    /// The orders of the intersected expansions decided for the jobs, with the number of workers
+    /// yet to take them, see `shared_intersect_order()`
+    static ref INTERSECT_ORDERS: Mutex<HashMap<(u64, i32), (Instant, u32, Option<Vec<usize>>)>> =
+        Mutex::new(HashMap::new());
+}
+
 type RecordMap = Box<dyn MapFunction<Record, Record>>;
 type RecordFilterMap = Box<dyn FilterMapFunction<Record, Record>>;
 type RecordFlatMap = Box<dyn FlatMapFunction<Record, Record, Target = DynIter<Record>>>;
@@ -88,9 +106,11 @@ impl<P: PartitionInfo, C: ClusterInfo> FnGenerator<P, C> {
         FnGenerator { router }
     }
 
-    fn gen_source(&self, opr: pb::PhysicalOpr) -> FnGenResult<DynIter<Record>> {
+    fn gen_source(
+        &self, opr: pb::PhysicalOpr, projection: Option<Vec<NameOrId>>,
+    ) -> FnGenResult<DynIter<Record>> {
         let worker_id = pegasus::get_current_worker();
-        let source_opr = SourceOperator::new(opr, self.router.clone())?;
+        let source_opr = SourceOperator::new(opr, self.router.clone())?.with_projection(projection);
         Ok(source_opr.gen_source(worker_id.index as usize)?)
     }
 
@@ -194,11 +214,224 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
         IRJobAssembly { udf_gen }
     }
 
+    /// Install the join of the left and the right plans on their keys, which are compared pairwise.
+    /// Unless the join is null-safe, the records whose keys contain null never match, and they bypass
+    /// the join to be output as unmatched directly. In an outer join, an unmatched record is padded
+    /// with null for the tags output by the plan of the other side.
+    fn install_join(
+        &self, stream: Stream<Record>, join: &pb::Join,
+    ) -> Result<Stream<Record>, BuildJobError> {
+        let joiner = self.udf_gen.gen_join(join.clone())?;
+        let left_key_selector = joiner.gen_left_kv_fn()?;
+        let right_key_selector = joiner.gen_right_kv_fn()?;
+        let join_kind = joiner.get_join_kind();
+        let null_safe = joiner.is_null_safe();
+        let (left_tags, right_tags) = joiner.get_output_tags();
+        let left_task = join
+            .left_plan
+            .as_ref()
+            .ok_or_else(|| FnGenError::ParseError("left_task is missing in merge".into()))?;
+        let right_task = join
+            .right_plan
+            .as_ref()
+            .ok_or_else(|| FnGenError::ParseError("right_task is missing in merge".into()))?;
+        let (left_stream, right_stream) = stream.copied()?;
+        let left_stream = self
+            .install(left_stream, &left_task.plan[..])?
+            .key_by(move |record| left_key_selector.get_kv(record))?;
+        let right_stream = self
+            .install(right_stream, &right_task.plan[..])?
+            .key_by(move |record| right_key_selector.get_kv(record))?;
+        let (left_stream, left_unmatched) = if null_safe {
+            (left_stream, None)
+        } else {
+            let output_unmatched =
+                matches!(join_kind, JoinKind::LeftOuter | JoinKind::FullOuter | JoinKind::Anti);
+            split_null_keys(left_stream, output_unmatched)?
+        };
+        let (right_stream, right_unmatched) = if null_safe {
+            (right_stream, None)
+        } else {
+            let output_unmatched = matches!(join_kind, JoinKind::RightOuter | JoinKind::FullOuter);
+            split_null_keys(right_stream, output_unmatched)?
+        };
+        let (left_padding, right_padding) = (right_tags.clone(), left_tags.clone());
+        let mut stream = match join_kind {
+            JoinKind::Inner => left_stream
+                .inner_join(right_stream)?
+                .map(|(left, right)| Ok(left.value.join(right.value, None)))?,
+            JoinKind::LeftOuter => {
+                left_stream
+                    .left_outer_join(right_stream)?
+                    .map(move |(left, right)| {
+                        let left = left.ok_or_else(|| {
+                            FnExecError::unexpected_data_error("left is None in left outer join")
+                        })?;
+                        if let Some(right) = right {
+                            // TODO(bingqing): Specify HeadJoinOpt if necessary
+                            Ok(left.value.join(right.value, None))
+                        } else {
+                            let mut left = left.value;
+                            left.pad_none(&right_tags);
+                            Ok(left)
+                        }
+                    })?
+            }
+            JoinKind::RightOuter => left_stream
+                .right_outer_join(right_stream)?
+                .map(move |(left, right)| {
+                    let right = right.ok_or_else(|| {
+                        FnExecError::unexpected_data_error("right is None in right outer join")
+                    })?;
+                    if let Some(left) = left {
+                        Ok(left.value.join(right.value, None))
+                    } else {
+                        let mut right = right.value;
+                        right.pad_none(&left_tags);
+                        Ok(right)
+                    }
+                })?,
+            JoinKind::FullOuter => {
+                left_stream
+                    .full_outer_join(right_stream)?
+                    .map(move |(left, right)| match (left, right) {
+                        (Some(left), Some(right)) => Ok(left.value.join(right.value, None)),
+                        (Some(left), None) => {
+                            let mut left = left.value;
+                            left.pad_none(&right_tags);
+                            Ok(left)
+                        }
+                        (None, Some(right)) => {
+                            let mut right = right.value;
+                            right.pad_none(&left_tags);
+                            Ok(right)
+                        }
+                        (None, None) => {
+                            unreachable!()
+                        }
+                    })?
+            }
+            JoinKind::Semi => left_stream
+                .semi_join(right_stream)?
+                .map(|left| Ok(left.value))?,
+            JoinKind::Anti => left_stream
+                .anti_join(right_stream)?
+                .map(|left| Ok(left.value))?,
+            JoinKind::Times => {
+                Err(BuildJobError::Unsupported("JoinKind of Times is not supported yet".to_string()))?
+            }
+        };
+        if let Some(left_unmatched) = left_unmatched {
+            // the anti join outputs the left records as they are
+            let padding = if join_kind == JoinKind::Anti { vec![] } else { left_padding };
+            stream = stream.merge(left_unmatched.map(move |mut record| {
+                record.pad_none(&padding);
+                Ok(record)
+            })?)?;
+        }
+        if let Some(right_unmatched) = right_unmatched {
+            stream = stream.merge(right_unmatched.map(move |mut record| {
+                record.pad_none(&right_padding);
+                Ok(record)
+            })?)?;
+        }
+        Ok(stream)
+    }
+
+    /// Install the shortest-path expansion, which expands the paths hop by hop in the BFS manner, with
+    /// the vertices visited from each start vertex pruned in the later hops. If the `condition` is given,
+    /// it works as the filter of the target vertices, and a path stops expanding once it reaches a target.
+    fn install_shortest_path(
+        &self, stream: Stream<Record>, path: &pb::PathExpand, base_expand_plan: &[pb::PhysicalOpr],
+    ) -> Result<Stream<Record>, BuildJobError> {
+        let range = path.hop_range.as_ref().ok_or_else(|| {
+            FnGenError::from(ParsePbError::EmptyFieldError("pb::PathExpand::hop_range".to_string()))
+        })?;
+        let all_shortest =
+            pb::path_expand::PathOpt::AllShortest == unsafe { std::mem::transmute(path.path_opt) };
+        let end_v_only =
+            pb::path_expand::ResultOpt::EndV == unsafe { std::mem::transmute(path.result_opt) };
+        let visitor = ShortestPathVisitor::new(stream.get_scope_level(), all_shortest);
+        let target_filter = || -> FnGenResult<Option<RecordFilter>> {
+            if path.condition.is_some() {
+                Ok(Some(self.udf_gen.gen_path_condition(path.clone())?))
+            } else {
+                Ok(None)
+            }
+        };
+
+        let mut hop_stream = visitor.install(stream, range.upper <= 1)?;
+        let mut emitted: Option<Stream<Record>> = None;
+        for hop in 0..range.upper {
+            if hop > 0 {
+                hop_stream = self.install(hop_stream, base_expand_plan)?;
+                hop_stream = visitor.install(hop_stream, hop + 1 == range.upper)?;
+            }
+            if hop < range.lower {
+                continue;
+            }
+            let (next_stream, copied_stream) = hop_stream.copied()?;
+            let hop_emitted = if let Some(func) = target_filter()? {
+                copied_stream.filter(move |input| func.test(input))?
+            } else {
+                copied_stream
+            };
+            emitted = Some(match emitted {
+                Some(emitted) => emitted.merge(hop_emitted)?,
+                None => hop_emitted,
+            });
+            // the paths reaching the targets stop expanding
+            hop_stream = if let Some(func) = target_filter()? {
+                next_stream.filter(move |input| Ok(!func.test(input)?))?
+            } else {
+                next_stream
+            };
+        }
+        let emitted = emitted.ok_or_else(|| {
+            FnGenError::from(ParsePbError::ParseError(format!("range {:?} in PathExpand Operator", range)))
+        })?;
+        ShortestPathCollector::new(range.lower as usize, all_shortest, end_v_only).install(emitted)
+    }
+
+    /// Expand the paths by hops till the upper bound of the hop range, where only the paths satisfying the
+    /// condition are emitted at each hop within the range.
+    fn install_path_emit_only(
+        &self, mut stream: Stream<Record>, path: &pb::PathExpand, base_expand_plan: &[pb::PhysicalOpr],
+    ) -> Result<Stream<Record>, BuildJobError> {
+        let range = path.hop_range.as_ref().ok_or_else(|| {
+            FnGenError::from(ParsePbError::EmptyFieldError("pb::PathExpand::hop_range".to_string()))
+        })?;
+        let emit_filter = || -> FnGenResult<_> {
+            let func = self.udf_gen.gen_path_condition(path.clone())?;
+            Ok(move |input: &Record| func.test(input))
+        };
+        for _ in 0..range.lower {
+            stream = self.install(stream, base_expand_plan)?;
+        }
+        let mut emitted: Option<Stream<Record>> = None;
+        for _ in range.lower..range.upper - 1 {
+            let (next_stream, copied_stream) = stream.copied()?;
+            let hop_emitted = copied_stream.filter(emit_filter()?)?;
+            emitted = Some(match emitted {
+                Some(emitted) => emitted.merge(hop_emitted)?,
+                None => hop_emitted,
+            });
+            stream = self.install(next_stream, base_expand_plan)?;
+        }
+        // the paths of the last hop are not expanded further
+        let hop_emitted = stream.filter(emit_filter()?)?;
+        match emitted {
+            Some(emitted) => emitted.merge(hop_emitted),
+            None => Ok(hop_emitted),
+        }
+    }
+
     fn install(
         &self, mut stream: Stream<Record>, plan: &[pb::PhysicalOpr],
     ) -> Result<Stream<Record>, BuildJobError> {
         let mut prev_op_kind = pb::physical_opr::operator::OpKind::Root(pb::Root {});
-        for op in &plan[..] {
+        let mut ops = plan.iter().peekable();
+        while let Some(op) = ops.next() {
             let op_kind = to_op_kind(op)?;
             match op_kind {
                 OpKind::Repartition(repartition) => {
@@ -240,7 +473,15 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                     }
                     stream = stream.limit(range.upper as u32)?;
                 }
-                OpKind::OrderBy(order) => {
+                OpKind::OrderBy(mut order) => {
+                    // `order().limit(k)` is executed as a top-k, which keeps at most k data per worker,
+                    // and keeps the ties in their input order as a stable sort does.
+                    if order.limit.is_none() {
+                        if let Some(range) = ops.peek().and_then(|next| limit_range_of(next)) {
+                            order.limit = Some(range);
+                            ops.next();
+                        }
+                    }
                     let cmp = self.udf_gen.gen_cmp(order.clone())?;
                     if let Some(range) = order.limit {
                         if range.upper <= range.lower || range.lower != 0 {
@@ -250,8 +491,10 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                             ))))?;
                         }
                         stream = stream.sort_limit_by(range.upper as u32, move |a, b| cmp.compare(a, b))?;
-                    } else {
+                    } else if order.requires_stable_sort() {
                         stream = stream.sort_by(move |a, b| cmp.compare(a, b))?;
+                    } else {
+                        stream = stream.sort_unstable_by(move |a, b| cmp.compare(a, b))?;
                     }
                 }
                 OpKind::GroupBy(group) => {
@@ -322,7 +565,28 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                     }
                 }
                 OpKind::Apply(apply) => {
-                    if apply.keys.is_empty() {
+                    let batch_size = stream.get_job_conf().apply_batch_size as usize;
+                    let batched_apply = if batch_size > 1 { BatchedApply::new(&apply)? } else { None };
+                    if let Some(batched_apply) = batched_apply {
+                        // apply the subtask to a chunk of parent records at a time
+                        let sub_task = apply.sub_plan.as_ref().ok_or_else(|| {
+                            BuildJobError::Unsupported("Task is missing in Apply".to_string())
+                        })?;
+                        let tagger = batched_apply.get_tagger();
+                        let batched_plan = &sub_task.plan[..batched_apply.get_num_batched_oprs()];
+                        stream = stream
+                            .chunk(batch_size)?
+                            .apply(|sub_start| {
+                                let sub_start =
+                                    sub_start.flat_map(move |parents| Ok(tagger.tag_parents(parents)))?;
+                                self.install(sub_start, batched_plan)?
+                                    .collect::<Vec<Record>>()
+                            })?
+                            .flat_map(move |(parents, subs)| {
+                                count_apply_scope();
+                                Ok(batched_apply.join(parents, subs)?.into_iter())
+                            })?;
+                    } else if apply.keys.is_empty() {
                         // apply
                         let apply_gen = self.udf_gen.gen_apply(apply.clone())?;
                         let join_kind = apply_gen.get_join_kind();
@@ -338,15 +602,14 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                                         .any()?;
                                     Ok(has_sub)
                                 })?
-                                .filter_map(
-                                    move |(parent, has_sub)| {
-                                        if has_sub {
-                                            Ok(Some(parent))
-                                        } else {
-                                            Ok(None)
-                                        }
-                                    },
-                                )?,
+                                .filter_map(move |(parent, has_sub)| {
+                                    count_apply_scope();
+                                    if has_sub {
+                                        Ok(Some(parent))
+                                    } else {
+                                        Ok(None)
+                                    }
+                                })?,
                             JoinKind::Anti => stream
                                 .apply(|sub_start| {
                                     let has_sub = self
@@ -354,15 +617,14 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                                         .any()?;
                                     Ok(has_sub)
                                 })?
-                                .filter_map(
-                                    move |(parent, has_sub)| {
-                                        if has_sub {
-                                            Ok(None)
-                                        } else {
-                                            Ok(Some(parent))
-                                        }
-                                    },
-                                )?,
+                                .filter_map(move |(parent, has_sub)| {
+                                    count_apply_scope();
+                                    if has_sub {
+                                        Ok(None)
+                                    } else {
+                                        Ok(Some(parent))
+                                    }
+                                })?,
                             JoinKind::Inner | JoinKind::LeftOuter => stream
                                 .apply(|sub_start| {
                                     let sub_end = self
@@ -370,7 +632,10 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                                         .collect::<Vec<Record>>()?;
                                     Ok(sub_end)
                                 })?
-                                .filter_map(move |(parent, sub)| join_func.exec(parent, sub))?,
+                                .filter_map(move |(parent, sub)| {
+                                    count_apply_scope();
+                                    join_func.exec(parent, sub)
+                                })?,
                             _ => Err(BuildJobError::Unsupported(format!(
                                 "Do not support join_kind {:?} in Apply",
                                 join_kind
@@ -382,80 +647,7 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                     }
                 }
                 OpKind::Join(join) => {
-                    let joiner = self.udf_gen.gen_join(join.clone())?;
-                    let left_key_selector = joiner.gen_left_kv_fn()?;
-                    let right_key_selector = joiner.gen_right_kv_fn()?;
-                    let join_kind = joiner.get_join_kind();
-                    let left_task = join
-                        .left_plan
-                        .as_ref()
-                        .ok_or_else(|| FnGenError::ParseError("left_task is missing in merge".into()))?;
-                    let right_task = join
-                        .right_plan
-                        .as_ref()
-                        .ok_or_else(|| FnGenError::ParseError("right_task is missing in merge".into()))?;
-                    let (left_stream, right_stream) = stream.copied()?;
-                    let left_stream = self
-                        .install(left_stream, &left_task.plan[..])?
-                        .key_by(move |record| left_key_selector.get_kv(record))?;
-                    let right_stream = self
-                        .install(right_stream, &right_task.plan[..])?
-                        .key_by(move |record| right_key_selector.get_kv(record))?;
-                    stream = match join_kind {
-                        JoinKind::Inner => left_stream
-                            .inner_join(right_stream)?
-                            .map(|(left, right)| Ok(left.value.join(right.value, None)))?,
-                        JoinKind::LeftOuter => {
-                            left_stream
-                                .left_outer_join(right_stream)?
-                                .map(|(left, right)| {
-                                    let left = left.ok_or_else(|| {
-                                        FnExecError::unexpected_data_error(
-                                            "left is None in left outer join",
-                                        )
-                                    })?;
-                                    if let Some(right) = right {
-                                        // TODO(bingqing): Specify HeadJoinOpt if necessary
-                                        Ok(left.value.join(right.value, None))
-                                    } else {
-                                        Ok(left.value)
-                                    }
-                                })?
-                        }
-                        JoinKind::RightOuter => left_stream
-                            .right_outer_join(right_stream)?
-                            .map(|(left, right)| {
-                                let right = right.ok_or_else(|| {
-                                    FnExecError::unexpected_data_error("right is None in right outer join")
-                                })?;
-                                if let Some(left) = left {
-                                    Ok(left.value.join(right.value, None))
-                                } else {
-                                    Ok(right.value)
-                                }
-                            })?,
-                        JoinKind::FullOuter => {
-                            left_stream
-                                .full_outer_join(right_stream)?
-                                .map(|(left, right)| match (left, right) {
-                                    (Some(left), Some(right)) => Ok(left.value.join(right.value, None)),
-                                    (Some(left), None) => Ok(left.value),
-                                    (None, Some(right)) => Ok(right.value),
-                                    (None, None) => {
-                                        unreachable!()
-                                    }
-                                })?
-                        }
-                        JoinKind::Semi => left_stream
-                            .semi_join(right_stream)?
-                            .map(|left| Ok(left.value))?,
-                        JoinKind::Anti => left_stream
-                            .anti_join(right_stream)?
-                            .map(|left| Ok(left.value))?,
-                        JoinKind::Times => Err(BuildJobError::Unsupported(
-                            "JoinKind of Times is not supported yet".to_string(),
-                        ))?,
-                    }
+                    stream = self.install_join(stream, join)?;
                 }
                 OpKind::Intersect(intersect) => {
                     // The subplan in intersect can be:
@@ -706,6 +898,43 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                     let is_optimized = intersected_expands
                         .iter()
                         .all(|(_, _, get_v)| get_v.is_none());
+                    // The intersection is the same in any order of the expansions, but a record is dropped
+                    // once the intersection becomes empty, without expanding the rest. Thus, we expand the
+                    // one of the fewest neighbors first. The expansions that are repartitioned must be
+                    // installed in the same order by all the workers exchanging the records, thus they are
+                    // reordered only in a job on a single server, where the workers share the order.
+                    if is_optimized {
+                        let is_repartitioned = intersected_expands
+                            .iter()
+                            .any(|(repartition, _, _)| repartition.is_some());
+                        let expands: Vec<&pb::EdgeExpand> = intersected_expands
+                            .iter()
+                            .map(|(_, expand, _)| expand)
+                            .collect();
+                        let order = if !is_repartitioned {
+                            order_by_expand_degree(&expands)
+                        } else {
+                            match pegasus::get_current_worker_checked() {
+                                Some(worker) if worker.servers <= 1 => {
+                                    shared_intersect_order(&worker, intersect.key, || {
+                                        order_by_expand_degree(&expands)
+                                    })
+                                }
+                                _ => None,
+                            }
+                        };
+                        if let Some(order) = order {
+                            let mut expands: Vec<_> = intersected_expands
+                                .drain(..)
+                                .map(Some)
+                                .collect();
+                            intersected_expands.extend(
+                                order
+                                    .into_iter()
+                                    .filter_map(|idx| expands[idx].take()),
+                            );
+                        }
+                    }
                     let mut intersect_expand_funcs = Vec::with_capacity(intersected_expands.len());
                     for (repartition, expand, get_v) in intersected_expands {
                         let expand_func = if !is_optimized {
@@ -727,8 +956,11 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                         })?;
                     }
                     // unfold the intersection
-                    let unfold =
-                        pb::Unfold { tag: Some(intersect.key.into()), alias: Some(intersect.key.into()) };
+                    let unfold = pb::Unfold {
+                        tag: Some(intersect.key.into()),
+                        alias: Some(intersect.key.into()),
+                        keep_empty: false,
+                    };
                     stream = self.install(stream, &vec![unfold.into()])?;
 
                     // add vertex filters
@@ -861,26 +1093,54 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                         }
                     }
 
-                    for _ in 0..range.lower {
-                        stream = self.install(stream, &base_expand_plan)?;
-                    }
-                    let times = range.upper - range.lower - 1;
-                    if times > 0 {
-                        if path.condition.is_some() {
-                            let mut until = IterCondition::max_iters(times as u32);
-                            let func = self.udf_gen.gen_path_condition(path.clone())?;
-                            until.set_until(func);
-                            // Notice that if UNTIL condition set, we expand path without `Emit`
+                    let path_opt: pb::path_expand::PathOpt = unsafe { std::mem::transmute(path.path_opt) };
+                    if let pb::path_expand::PathOpt::AnyShortest | pb::path_expand::PathOpt::AllShortest =
+                        path_opt
+                    {
+                        stream = self.install_shortest_path(stream, &path, &base_expand_plan)?;
+                    } else if path.condition.is_some() && path.is_condition_emit_only {
+                        if path.is_optional {
+                            // a start without any path satisfying the condition is kept, with a null entry
+                            // in place of the path
                             stream = stream
-                                .iterate_until(until, |start| self.install(start, &base_expand_plan[..]))?;
+                                .apply(|sub_start| {
+                                    self.install_path_emit_only(sub_start, &path, &base_expand_plan)?
+                                        .collect::<Vec<Record>>()
+                                })?
+                                .flat_map(move |(mut parent, paths)| {
+                                    if paths.is_empty() {
+                                        parent.append(NullEntry, None);
+                                        Ok(vec![parent].into_iter())
+                                    } else {
+                                        Ok(paths.into_iter())
+                                    }
+                                })?;
                         } else {
-                            let (mut hop_stream, copied_stream) = stream.copied()?;
-                            stream = copied_stream;
-                            for _ in 0..times {
-                                hop_stream = self.install(hop_stream, &base_expand_plan[..])?;
-                                let copied = hop_stream.copied()?;
-                                hop_stream = copied.0;
-                                stream = stream.merge(copied.1)?;
+                            stream = self.install_path_emit_only(stream, &path, &base_expand_plan)?;
+                        }
+                    } else {
+                        for _ in 0..range.lower {
+                            stream = self.install(stream, &base_expand_plan)?;
+                        }
+                        let times = range.upper - range.lower - 1;
+                        if times > 0 {
+                            if path.condition.is_some() {
+                                let mut until = IterCondition::max_iters(times as u32);
+                                let func = self.udf_gen.gen_path_condition(path.clone())?;
+                                until.set_until(func);
+                                // Notice that if UNTIL condition set, we expand path without `Emit`
+                                stream = stream.iterate_until(until, |start| {
+                                    self.install(start, &base_expand_plan[..])
+                                })?;
+                            } else {
+                                let (mut hop_stream, copied_stream) = stream.copied()?;
+                                stream = copied_stream;
+                                for _ in 0..times {
+                                    hop_stream = self.install(hop_stream, &base_expand_plan[..])?;
+                                    let copied = hop_stream.copied()?;
+                                    hop_stream = copied.0;
+                                    stream = stream.merge(copied.1)?;
+                                }
                             }
                         }
                     }
@@ -892,8 +1152,12 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
                 }
                 OpKind::Scan(scan) => {
                     let udf_gen = self.udf_gen.clone();
+                    // the scanned vertices may be projected right away by the next operator
+                    let projection = ops
+                        .peek()
+                        .and_then(|next| scan_projection(&scan, next));
                     stream = stream.flat_map(move |_| {
-                        let scan_iter = udf_gen.gen_source(scan.clone().into());
+                        let scan_iter = udf_gen.gen_source(scan.clone().into(), projection.clone());
                         Ok(scan_iter?)
                     })?;
                 }
@@ -964,7 +1228,11 @@ impl<P: PartitionInfo, C: ClusterInfo> IRJobAssembly<P, C> {
 impl<P: PartitionInfo, C: ClusterInfo> JobAssembly<Record> for IRJobAssembly<P, C> {
     fn assemble(&self, plan: &JobDesc, worker: &mut Worker<Record, Vec<u8>>) -> Result<(), BuildJobError> {
         worker.dataflow(move |input, output| {
-            let physical_plan = decode::<pb::PhysicalPlan>(&plan.plan)?;
+            let mut physical_plan = decode::<pb::PhysicalPlan>(&plan.plan)?;
+            // bind the dynamic params, e.g., the ids of a hashed within/without predicate
+            physical_plan
+                .resolve_params()
+                .map_err(FnGenError::from)?;
             if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
                 debug!("{:#?}", PhysicalPlanPrinter(&physical_plan));
             }
@@ -980,6 +1248,22 @@ impl<P: PartitionInfo, C: ClusterInfo> JobAssembly<Record> for IRJobAssembly<P,
                 Sinker::DefaultSinker(default_sinker) => stream
                     .map(move |record| default_sinker.exec(record))?
                     .sink_into(output),
+                Sinker::MultiTargetSinker(multi_target_sinker) => {
+                    if multi_target_sinker.sinks_into_client() {
+                        stream
+                            .filter_map(move |record| multi_target_sinker.exec(record))?
+                            .sink_into(output)
+                    } else {
+                        // respond with the number of the records sinked into each target instead
+                        let counts_encoder = multi_target_sinker.counts_encoder();
+                        stream
+                            .map(move |record| multi_target_sinker.exec(record))?
+                            .count()?
+                            .map(move |count| counts_encoder.exec(count))?
+                            .into_stream()?
+                            .sink_into(output)
+                    }
+                }
                 #[cfg(feature = "with_v6d")]
                 Sinker::GraphSinker(graph_sinker) => {
                     return stream
@@ -994,11 +1278,28 @@ impl<P: PartitionInfo, C: ClusterInfo> JobAssembly<Record> for IRJobAssembly<P,
                         .map(|_r| Ok(vec![]))?
                         .sink_into(output)
                 }
+                #[cfg(feature = "arrow_sink")]
+                Sinker::ArrowSinker(arrow_sinker) => arrow_sinker.install(stream)?.sink_into(output),
             }
         })
     }
 }
 
+/// Filter out the keyed records whose keys contain null, which are returned as a separate stream
+/// if `output_unmatched`, or dropped otherwise.
+fn split_null_keys(
+    stream: Stream<Pair<RecordKey, Record>>, output_unmatched: bool,
+) -> Result<(Stream<Pair<RecordKey, Record>>, Option<Stream<Record>>), BuildJobError> {
+    if output_unmatched {
+        let (stream, unmatched) = stream.copied()?;
+        let unmatched =
+            unmatched.filter_map(|pair| Ok(if pair.key.has_null() { Some(pair.value) } else { None }))?;
+        Ok((stream.filter(|pair| Ok(!pair.key.has_null()))?, Some(unmatched)))
+    } else {
+        Ok((stream.filter(|pair| Ok(!pair.key.has_null()))?, None))
+    }
+}
+
 #[inline]
 fn decode<T: Message + Default>(binary: &[u8]) -> FnGenResult<T> {
     Ok(T::decode(binary)?)
@@ -1009,6 +1310,100 @@ fn to_op_kind(opr: &pb::PhysicalOpr) -> FnGenResult<OpKind> {
     Ok(opr.try_into()?)
 }
 
+/// The order to expand the intersected expansions by their estimated degrees, or `None` to keep
+/// the order of the plan if the degree of any expansion is not cached yet, which is then collected
+/// in the background for the later jobs, as the job building should not wait for counting the graph.
+fn order_by_expand_degree(expands: &[&pb::EdgeExpand]) -> Option<Vec<usize>> {
+    let degrees = expands
+        .iter()
+        .map(|expand| estimate_expand_degree(expand))
+        .collect::<Option<Vec<f64>>>();
+    match degrees {
+        Some(degrees) => {
+            let mut order: Vec<usize> = (0..degrees.len()).collect();
+            // a stable sort, which keeps the order of the expansions of the same degrees
+            order.sort_by(|left, right| degrees[*left].total_cmp(&degrees[*right]));
+            Some(order)
+        }
+        None => {
+            collect_expand_degrees_in_background(
+                expands
+                    .iter()
+                    .map(|expand| (*expand).clone())
+                    .collect(),
+            );
+            None
+        }
+    }
+}
+
+/// Collect the degrees of the expansions on a separate thread, if no collection is in progress.
+fn collect_expand_degrees_in_background(expands: Vec<pb::EdgeExpand>) {
+    static COLLECTING: AtomicBool = AtomicBool::new(false);
+    let graph = match graph_proxy::apis::get_graph() {
+        Some(graph) => graph,
+        None => return,
+    };
+    if COLLECTING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    let spawned = std::thread::Builder::new()
+        .name("collect-expand-degrees".to_string())
+        .spawn(move || {
+            for expand in &expands {
+                if let Err(e) = collect_expand_degree(graph.as_ref(), expand) {
+                    debug!("fail to collect the degree of {:?}, as {:?}", expand, e);
+                }
+            }
+            COLLECTING.store(false, Ordering::Release);
+        });
+    if let Err(e) = spawned {
+        COLLECTING.store(false, Ordering::Release);
+        warn!("fail to collect the degrees of the expansions, as {:?}", e);
+    }
+}
+
+/// The order of the expansions of the intersection `key`, which is decided by the first worker of
+/// the job building it, and taken by the other `local_peers - 1` workers of the job on this server.
+fn shared_intersect_order<F>(worker: &pegasus::WorkerId, key: i32, decide: F) -> Option<Vec<usize>>
+where
+    F: FnOnce() -> Option<Vec<usize>>,
+{
+    // a poisoned lock keeps the order of the plan for all the workers alike
+    let mut orders = INTERSECT_ORDERS.lock().ok()?;
+    // the ones left by the jobs failed to build
+    orders.retain(|_, (decided_at, _, _)| decided_at.elapsed() < INTERSECT_ORDER_TTL);
+    let job_key = (worker.job_id, key);
+    match orders.get_mut(&job_key) {
+        Some((_, remaining, order)) => {
+            let order = order.clone();
+            *remaining -= 1;
+            if *remaining == 0 {
+                orders.remove(&job_key);
+            }
+            order
+        }
+        None => {
+            let order = decide();
+            if worker.local_peers > 1 {
+                orders.insert(job_key, (Instant::now(), worker.local_peers - 1, order.clone()));
+            }
+            order
+        }
+    }
+}
+
+/// The range of a `Limit` that takes the first data, i.e., `limit(k)`, which can be fused into a
+/// preceding `OrderBy`; an invalid range is left to the `Limit` to report.
+fn limit_range_of(opr: &pb::PhysicalOpr) -> Option<algebra_pb::Range> {
+    match to_op_kind(opr) {
+        Ok(OpKind::Limit(limit)) => limit
+            .range
+            .filter(|range| range.lower == 0 && range.upper > range.lower),
+        _ => None,
+    }
+}
+
 struct PhysicalPlanPrinter<'a>(&'a pb::PhysicalPlan);
 struct PhysicalOprPrinter<'a>(&'a pb::PhysicalOpr);
 
@@ -17,6 +17,7 @@ use graph_proxy::utils::expr::ExprEvalError;
 use graph_proxy::GraphProxyError;
 use ir_common::error::ParsePbError;
 use pegasus::api::function::DynError;
+use pegasus::errors::{ErrorKind, JobExecError};
 use pegasus::BuildJobError;
 use prost::DecodeError;
 
@@ -35,6 +36,8 @@ pub enum FnGenError {
     StoreError(GraphProxyError),
     /// Not supported error
     UnSupported(String),
+    /// IO error, e.g., when opening a file to sink into
+    IOError(std::io::Error),
 }
 
 impl FnGenError {
@@ -51,6 +54,7 @@ impl std::fmt::Display for FnGenError {
             FnGenError::NullGraphError => write!(f, "Null graph store error in fn gen",),
             FnGenError::StoreError(e) => write!(f, "Query store error in fn gen {}", e),
             FnGenError::UnSupported(e) => write!(f, "Unsupported error in fn gen  {}", e),
+            FnGenError::IOError(e) => write!(f, "IO error in fn gen {}", e),
         }
     }
 }
@@ -75,6 +79,12 @@ impl From<GraphProxyError> for FnGenError {
     }
 }
 
+impl From<std::io::Error> for FnGenError {
+    fn from(e: std::io::Error) -> Self {
+        FnGenError::IOError(e)
+    }
+}
+
 impl From<FnGenError> for DynError {
     fn from(e: FnGenError) -> Self {
         let err: Box<dyn std::error::Error + Send + Sync> = e.into();
@@ -105,6 +115,10 @@ impl From<FnGenError> for BuildJobError {
                 let err: Box<dyn std::error::Error + Send + Sync> = e.into();
                 BuildJobError::UserError(err)
             }
+            FnGenError::IOError(e) => {
+                let err: Box<dyn std::error::Error + Send + Sync> = e.into();
+                BuildJobError::UserError(err)
+            }
         }
     }
 }
@@ -165,7 +179,15 @@ impl std::fmt::Display for FnExecError {
     }
 }
 
-impl std::error::Error for FnExecError {}
+impl std::error::Error for FnExecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FnExecError::StoreError(e) => Some(e),
+            FnExecError::ExprEvalError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 impl From<ExprEvalError> for FnExecError {
     fn from(e: ExprEvalError) -> Self {
@@ -179,9 +201,47 @@ impl From<GraphProxyError> for FnExecError {
     }
 }
 
+/// The storage errors keep their classification, e.g., whether they are retryable or caused by the system.
+impl From<FnExecError> for JobExecError {
+    fn from(e: FnExecError) -> Self {
+        match &e {
+            FnExecError::StoreError(store_error) => {
+                let is_system = store_error.is_system();
+                let mut err = JobExecError::new(store_error.job_exec_error_kind(), e);
+                if is_system {
+                    err.set_system();
+                }
+                err
+            }
+            _ => JobExecError::new(ErrorKind::Others, e),
+        }
+    }
+}
+
 impl From<FnExecError> for DynError {
     fn from(e: FnExecError) -> Self {
-        let err: Box<dyn std::error::Error + Send + Sync> = e.into();
-        err
+        // wrapped in `JobExecError`, whose kind is kept as the error is raised to pegasus
+        Box::new(JobExecError::from(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_error_kind_through_fn_exec_error() {
+        let err: DynError = FnExecError::from(GraphProxyError::timeout_error("scan")).into();
+        let err = JobExecError::from(err);
+        assert!(matches!(err.kind, ErrorKind::IOError));
+        assert!(err.is_system);
+
+        let err: DynError = FnExecError::from(GraphProxyError::invalid_extra_param("SID", "")).into();
+        let err = JobExecError::from(err);
+        assert!(matches!(err.kind, ErrorKind::Others));
+        assert!(!err.is_system);
+
+        let err: DynError = FnExecError::unexpected_data_error("not a vertex").into();
+        assert!(matches!(JobExecError::from(err).kind, ErrorKind::Others));
     }
 }
@@ -0,0 +1,178 @@
+//
+//! Copyright 2021 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use hyper::body::HttpBody;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use ir_common::error::ParsePbResult;
+use ir_common::json::json_to_physical_plan;
+use prost::Message;
+
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+/// The largest plan in json accepted, beyond which the request is responded with `413 Payload Too Large`.
+pub const MAX_JSON_PLAN_SIZE: usize = 4 * 1024 * 1024;
+
+/// A thin HTTP handler that transcodes a physical plan in JSON, as `POST`ed by the clients without
+/// protobuf bindings, into binary protobuf in the response. It does not submit the plan, which is
+/// submitted by the client as the `plan` of a job over the rpc service as usual. An invalid plan is
+/// responded with `400 Bad Request`, and the one beyond `MAX_JSON_PLAN_SIZE` is declined.
+pub async fn handle_transcode_json_plan(req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    transcode_json_plan_within(req, MAX_JSON_PLAN_SIZE).await
+}
+
+/// Serve `handle_transcode_json_plan` at `addr` until the server fails, which must be called within
+/// a tokio runtime.
+pub async fn serve_json_plan_transcoder(addr: SocketAddr) -> Result<(), hyper::Error> {
+    let make_svc =
+        make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(handle_transcode_json_plan)) });
+    let server = Server::try_bind(&addr)?.serve(make_svc);
+    info!("transcode json plans over http at {}", server.local_addr());
+    server.await
+}
+
+async fn transcode_json_plan_within(
+    req: Request<Body>, max_size: usize,
+) -> Result<Response<Body>, hyper::Error> {
+    if req.method() != Method::POST {
+        return Ok(response(StatusCode::METHOD_NOT_ALLOWED, "text/plain", "only POST is allowed"));
+    }
+    let too_large = || {
+        response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "text/plain",
+            format!("the plan is larger than {} bytes", max_size),
+        )
+    };
+    // declined before reading the body if the declared length is beyond the limit
+    let declared_size = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<usize>().ok());
+    if declared_size.map_or(false, |size| size > max_size) {
+        return Ok(too_large());
+    }
+    let body = match read_body_within(req.into_body(), max_size).await? {
+        Some(body) => body,
+        None => return Ok(too_large()),
+    };
+    match transcode_json_plan(&body) {
+        Ok(plan) => Ok(response(StatusCode::OK, PROTOBUF_CONTENT_TYPE, plan)),
+        Err(err) => {
+            debug!("invalid json plan: {}", err);
+            Ok(response(StatusCode::BAD_REQUEST, "text/plain", err.to_string()))
+        }
+    }
+}
+
+/// Read the body, or `None` once it is beyond `max_size`, without reading the rest of it.
+async fn read_body_within(mut body: Body, max_size: usize) -> Result<Option<Vec<u8>>, hyper::Error> {
+    let mut buf = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > max_size {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(Some(buf))
+}
+
+fn transcode_json_plan(body: &[u8]) -> ParsePbResult<Vec<u8>> {
+    let json = serde_json::from_slice(body)?;
+    let plan = json_to_physical_plan(json)?;
+    Ok(plan.encode_to_vec())
+}
+
+fn response<B: Into<Body>>(status: StatusCode, content_type: &str, body: B) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(body.into())
+        .expect("build http response")
+}
+
+#[cfg(test)]
+mod tests {
+    use ir_common::generated::physical as pb;
+    use ir_common::json::physical_plan_to_json;
+
+    use super::*;
+
+    fn request(method: Method, body: String) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri("/plan")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn call(req: Request<Body>) -> (StatusCode, Vec<u8>) {
+        futures::executor::block_on(async {
+            let resp = handle_transcode_json_plan(req).await.unwrap();
+            let status = resp.status();
+            let body = hyper::body::to_bytes(resp.into_body())
+                .await
+                .unwrap();
+            (status, body.to_vec())
+        })
+    }
+
+    #[test]
+    fn transcode_json_plan_test() {
+        let plan = pb::PhysicalPlan {
+            plan_id: 1,
+            plan: vec![pb::PhysicalOpr {
+                opr: Some(pb::physical_opr::Operator {
+                    op_kind: Some(pb::physical_opr::operator::OpKind::Root(pb::Root {})),
+                }),
+                meta_data: vec![],
+            }],
+            params: Default::default(),
+        };
+        let (status, body) = call(request(Method::POST, physical_plan_to_json(&plan).to_string()));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(pb::PhysicalPlan::decode(body.as_slice()).unwrap(), plan);
+
+        let (status, _) = call(request(Method::POST, "{\"plan_id\": 1".to_string()));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = call(request(Method::GET, String::new()));
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn transcode_json_plan_beyond_size_test() {
+        let body = format!("{{\"plan_id\": 1, \"padding\": \"{}\"}}", "x".repeat(64));
+        let resp = futures::executor::block_on(transcode_json_plan_within(
+            request(Method::POST, body.clone()),
+            32,
+        ))
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // as declared by the content length, before the body is read
+        let req = Request::builder()
+            .method(Method::POST)
+            .header(header::CONTENT_LENGTH, MAX_JSON_PLAN_SIZE + 1)
+            .body(Body::from(body))
+            .unwrap();
+        let (status, _) = call(req);
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -18,6 +18,7 @@ use router::Router;
 
 pub mod assembly;
 pub mod error;
+pub mod http;
 pub mod process;
 pub mod router;
 
@@ -27,6 +27,7 @@ use dyn_type::{BorrowObject, Object};
 use graph_proxy::apis::graph::NULL_ID;
 use graph_proxy::apis::VertexOrEdge;
 use graph_proxy::apis::{Edge, Element, GraphElement, GraphPath, PropertyValue, Vertex, ID};
+use graph_proxy::GraphProxyResult;
 use ir_common::error::ParsePbError;
 use ir_common::generated::results as result_pb;
 use ir_common::NameOrId;
@@ -233,7 +234,10 @@ impl Decode for DynEntry {
                 Ok(DynEntry::new(general_intersect))
             }
             9 => Ok(DynEntry::new(NullEntry)),
-            _ => unreachable!(),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown entry type {}", entry_type),
+            )),
         }
     }
 }
@@ -292,6 +296,16 @@ impl GraphElement for DynEntry {
             _ => unreachable!(),
         }
     }
+    fn try_get_property(&self, key: &NameOrId) -> GraphProxyResult<Option<PropertyValue>> {
+        match self.get_type() {
+            EntryType::Vertex | EntryType::Edge | EntryType::Path | EntryType::Null => self
+                .inner
+                .as_graph_element()
+                .unwrap()
+                .try_get_property(key),
+            _ => unreachable!(),
+        }
+    }
 }
 
 // demanded when need to key the entry
@@ -314,7 +328,10 @@ impl Hash for DynEntry {
                 .as_any_ref()
                 .downcast_ref::<PairEntry>()
                 .hash(state),
-            EntryType::Null => self.hash(state),
+            EntryType::Null => self
+                .as_any_ref()
+                .downcast_ref::<NullEntry>()
+                .hash(state),
         }
     }
 }
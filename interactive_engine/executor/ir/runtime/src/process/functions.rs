@@ -16,6 +16,7 @@
 use std::cmp::Ordering;
 
 use ir_common::generated::algebra::join::JoinKind;
+use ir_common::KeyId;
 use pegasus::api::function::{BinaryFunction, FnResult, MapFunction};
 use pegasus_server::job_pb::AccumKind;
 
@@ -36,6 +37,13 @@ pub trait JoinKeyGen<D, K, V>: Send + 'static {
     fn gen_right_kv_fn(&self) -> FnGenResult<Box<dyn KeyFunction<D, K, V>>>;
 
     fn get_join_kind(&self) -> JoinKind;
+
+    /// Whether null matches null in the keys; otherwise, a key containing null never matches.
+    fn is_null_safe(&self) -> bool;
+
+    /// The tags output by the left and the right plans respectively, with which the unmatched
+    /// records of the other side are padded in an outer join.
+    fn get_output_tags(&self) -> (Vec<KeyId>, Vec<KeyId>);
 }
 
 pub trait GroupGen<D, K, V>: Send + 'static {
@@ -15,7 +15,7 @@
 
 use std::collections::HashSet;
 use std::convert::TryFrom;
-use std::ops::Div;
+use std::ops::{Add, Div};
 
 use dyn_type::{Object, Primitives};
 use ir_common::error::ParsePbError;
@@ -31,7 +31,7 @@ use crate::process::operator::accum::accumulator::{
 };
 use crate::process::operator::accum::AccumFactoryGen;
 use crate::process::operator::TagKey;
-use crate::process::record::Record;
+use crate::process::record::{read_versioned_len, write_version_header, Record};
 
 #[derive(Debug, Clone)]
 pub enum EntryAccumulator {
@@ -106,34 +106,12 @@ impl Accumulator<DynEntry, DynEntry> for EntryAccumulator {
             EntryAccumulator::ToSet(set) => set.accum(next),
             EntryAccumulator::ToDistinctCount(distinct_count) => distinct_count.accum(next),
             EntryAccumulator::ToSum(sum) => {
-                let primitive = next
-                    .as_object()
-                    .ok_or_else(|| {
-                        FnExecError::unexpected_data_error("DynEntry is not a object type `Sum`")
-                    })?
-                    .as_primitive()
-                    .map_err(|e| {
-                        FnExecError::unexpected_data_error(&format!(
-                            "DynEntry is not a primitive type `Sum` {}",
-                            e
-                        ))
-                    })?;
-                sum.accum(primitive)
+                let primitive = as_summable(&next, "Sum")?;
+                sum_checked(sum, primitive)
             }
             EntryAccumulator::ToAvg(sum, count) => {
-                let primitive = next
-                    .as_object()
-                    .ok_or_else(|| {
-                        FnExecError::unexpected_data_error("DynEntry is not a object type `ToAvg`")
-                    })?
-                    .as_primitive()
-                    .map_err(|e| {
-                        FnExecError::unexpected_data_error(&format!(
-                            "DynEntry is not a primitive type `ToAvg` {}",
-                            e
-                        ))
-                    })?;
-                sum.accum(primitive)?;
+                let primitive = as_summable(&next, "ToAvg")?;
+                sum_checked(sum, primitive)?;
                 count.accum(())
             }
             EntryAccumulator::ToFirst(first) => first.accum(next),
@@ -195,6 +173,63 @@ impl Accumulator<DynEntry, DynEntry> for EntryAccumulator {
     }
 }
 
+/// Get the primitive of the entry to be summed up by the accumulator `accum`, i.e., `Sum` or `Avg`.
+fn as_summable(entry: &DynEntry, accum: &str) -> FnExecResult<Primitives> {
+    let obj = entry.as_object().ok_or_else(|| {
+        FnExecError::unexpected_data_error(&format!("DynEntry is not a object type `{}`", accum))
+    })?;
+    if let Object::DateFormat(_) = obj {
+        // the temporal values are totally ordered, but not summable, e.g., the average of dates
+        Err(FnExecError::unsupported_error(&format!(
+            "`{}` over temporal values, e.g., {:?}, only min/max are supported",
+            accum, obj
+        )))?
+    }
+    obj.as_primitive().map_err(|e| {
+        FnExecError::unexpected_data_error(&format!("DynEntry is not a primitive type `{}` {}", accum, e))
+    })
+}
+
+/// Accumulate the sum of primitives, where the sum of unsigned values is promoted to `u64`, or `u128`
+/// if any value is `u128`, and fails on overflow instead of wrapping around.
+fn sum_checked(sum: &mut Sum<Primitives>, next: Primitives) -> FnExecResult<()> {
+    let unsigned_of = |p: &Primitives| match p {
+        Primitives::UInteger(v) => Some((*v as u128, false)),
+        Primitives::ULong(v) => Some((*v as u128, false)),
+        Primitives::ULLong(v) => Some((*v, true)),
+        _ => None,
+    };
+    let next = match next {
+        Primitives::UInteger(v) => Primitives::ULong(v as u64),
+        _ => next,
+    };
+    let seed = match sum.seed.take() {
+        Some(seed) => seed,
+        None => {
+            sum.seed = Some(next);
+            return Ok(());
+        }
+    };
+    let added = match (unsigned_of(&seed), unsigned_of(&next)) {
+        (Some((a, is_ullong_a)), Some((b, is_ullong_b))) => {
+            let overflow =
+                || FnExecError::accum_error(&format!("overflow in sum of {:?} and {:?}", seed, next));
+            if is_ullong_a || is_ullong_b {
+                Primitives::ULLong(a.checked_add(b).ok_or_else(overflow)?)
+            } else {
+                Primitives::ULong(
+                    (a as u64)
+                        .checked_add(b as u64)
+                        .ok_or_else(overflow)?,
+                )
+            }
+        }
+        _ => seed.add(next),
+    };
+    sum.seed = Some(added);
+    Ok(())
+}
+
 impl AccumFactoryGen for pb::GroupBy {
     fn gen_accum(self) -> FnGenResult<RecordAccumulator> {
         let mut accum_ops = Vec::with_capacity(self.functions.len());
@@ -336,6 +371,7 @@ impl Decode for EntryAccumulator {
 
 impl Encode for RecordAccumulator {
     fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_version_header(writer)?;
         writer.write_u32(self.accum_ops.len() as u32)?;
         for (accumulator, tag_key, alias) in self.accum_ops.iter() {
             accumulator.write_to(writer)?;
@@ -348,7 +384,7 @@ impl Encode for RecordAccumulator {
 
 impl Decode for RecordAccumulator {
     fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
-        let len = reader.read_u32()?;
+        let len = read_versioned_len(reader)?;
         let mut accum_ops = Vec::with_capacity(len as usize);
         for _ in 0..len {
             let accumulator = <EntryAccumulator>::read_from(reader)?;
@@ -365,18 +401,22 @@ mod tests {
 
     use std::cmp::Ordering;
 
-    use dyn_type::Object;
+    use dyn_type::{DateTimeFormats, Object, Primitives};
     use ir_common::generated::common as common_pb;
     use ir_common::generated::physical as pb;
+    use pegasus::api::function::FnResult;
     use pegasus::api::{Fold, Sink};
+    use pegasus::codec::{Decode, Encode, WriteExt};
     use pegasus::result::ResultStream;
     use pegasus::JobConf;
     use pegasus_common::downcast::AsAny;
 
+    use super::{EntryAccumulator, RecordAccumulator};
     use crate::process::entry::{CollectionEntry, DynEntry, Entry};
-    use crate::process::operator::accum::accumulator::Accumulator;
+    use crate::process::operator::accum::accumulator::{Accumulator, Count};
     use crate::process::operator::accum::AccumFactoryGen;
     use crate::process::operator::tests::{init_source, init_vertex1, init_vertex2, TAG_A, TAG_B};
+    use crate::process::operator::TagKey;
     use crate::process::record::Record;
 
     fn fold_test(source: Vec<Record>, fold_opr_pb: pb::GroupBy) -> ResultStream<Record> {
@@ -807,6 +847,53 @@ mod tests {
         assert_eq!(res, object!(60));
     }
 
+    fn sum_of(source: Vec<Object>, aggregate: i32) -> Option<FnResult<Object>> {
+        let function = pb::group_by::AggFunc {
+            vars: vec![common_pb::Variable::from("@".to_string())],
+            aggregate,
+            alias: None,
+        };
+        let fold_opr_pb = pb::GroupBy { mappings: vec![], functions: vec![function] };
+        let source = source
+            .into_iter()
+            .map(|obj| Record::new(obj, None))
+            .collect();
+        let mut result = fold_test(source, fold_opr_pb);
+        result.next().map(|record| {
+            record.map(|record| {
+                record
+                    .get(None)
+                    .unwrap()
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+        })
+    }
+
+    #[test]
+    fn sum_unsigned_test() {
+        // the sum of u32 is promoted to u64
+        let sum = sum_of(vec![object!(u32::MAX), object!(u32::MAX)], 0).unwrap();
+        assert_eq!(sum.unwrap(), Object::Primitive(Primitives::ULong(u32::MAX as u64 * 2)));
+        let sum = sum_of(vec![object!(u64::MAX), object!(1u128)], 0).unwrap();
+        assert_eq!(sum.unwrap(), Object::Primitive(Primitives::ULLong(u64::MAX as u128 + 1)));
+        // overflow is an error rather than wrapping around
+        assert!(sum_of(vec![object!(u64::MAX), object!(1u64)], 0)
+            .unwrap()
+            .is_err());
+        assert!(sum_of(vec![object!(u64::MAX), object!(1u32)], 7)
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn avg_over_date_test() {
+        let date = Object::DateFormat(DateTimeFormats::from_timestamp_millis(1602288000000).unwrap());
+        let avg = sum_of(vec![date.clone(), date], 7).unwrap();
+        assert!(avg.is_err());
+    }
+
     // g.V().values('age').mean()
     #[test]
     fn avg_test() {
@@ -938,4 +1025,36 @@ mod tests {
         }
         assert_eq!(fold_result, expected_result);
     }
+
+    #[test]
+    fn record_accumulator_codec_test() {
+        let count = EntryAccumulator::ToCount(Count { value: 2, _ph: Default::default() });
+        let accum = RecordAccumulator { accum_ops: vec![(count, TagKey::default(), Some(TAG_A))] };
+        let count_of = |mut accum: RecordAccumulator| {
+            accum
+                .finalize()
+                .unwrap()
+                .get(Some(TAG_A))
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+
+        let mut bytes = vec![];
+        accum.write_to(&mut bytes).unwrap();
+        let decoded = RecordAccumulator::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(count_of(decoded), object!(2u64));
+
+        // the format of the last release comes without a version header
+        let mut bytes = vec![];
+        bytes.write_u32(1).unwrap();
+        for (accumulator, tag_key, alias) in accum.accum_ops.iter() {
+            accumulator.write_to(&mut bytes).unwrap();
+            tag_key.write_to(&mut bytes).unwrap();
+            alias.write_to(&mut bytes).unwrap();
+        }
+        let decoded = RecordAccumulator::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(count_of(decoded), object!(2u64));
+    }
 }
@@ -23,7 +23,7 @@ use rand::{Rng, SeedableRng};
 use crate::error::{FnExecResult, FnGenError, FnGenResult};
 use crate::process::operator::accum::accumulator::Accumulator;
 use crate::process::operator::accum::SampleAccumFactoryGen;
-use crate::process::record::Record;
+use crate::process::record::{read_versioned_len, write_version_header, Record};
 
 /// Sample accumulator, which will keep a sampled vector of records, with the specified sample number.
 /// Implemented via Reservoir Sampling.
@@ -93,6 +93,7 @@ impl SampleAccumFactoryGen for algebra_pb::Sample {
 
 impl Encode for SampleAccum {
     fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_version_header(writer)?;
         self.accumulator.write_to(writer)?;
         writer.write_u64(self.count as u64)?;
         writer.write_u64(self.sample_num as u64)?;
@@ -103,7 +104,11 @@ impl Encode for SampleAccum {
 
 impl Decode for SampleAccum {
     fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
-        let accumulator = <Vec<Record>>::read_from(reader)?;
+        let len = read_versioned_len(reader)?;
+        let mut accumulator = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            accumulator.push(<Record>::read_from(reader)?);
+        }
         let count = reader.read_u64()? as usize;
         let sample_num = reader.read_u64()? as usize;
         let seed = Option::<u64>::read_from(reader)?;
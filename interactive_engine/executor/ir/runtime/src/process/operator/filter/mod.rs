@@ -15,6 +15,8 @@
 mod coin;
 mod path_condition;
 mod select;
+mod shortest_path;
+pub use shortest_path::{ShortestPathCollector, ShortestPathVisitor};
 
 use pegasus::api::function::FilterFunction;
 
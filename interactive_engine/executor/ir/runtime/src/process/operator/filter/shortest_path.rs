@@ -0,0 +1,217 @@
+//
+//! Copyright 2022 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use ahash::{HashMap, HashMapExt};
+use graph_proxy::apis::{Element, GraphElement, GraphPath, ID};
+use pegasus::api::function::FnResult;
+use pegasus::api::Unary;
+use pegasus::stream::Stream;
+use pegasus::tag::tools::map::TidyTagMap;
+use pegasus::BuildJobError;
+use pegasus_common::rc::UnsafeRcPtr;
+
+use crate::error::FnExecError;
+use crate::process::entry::Entry;
+use crate::process::record::Record;
+
+/// The traverser that a path expands from, i.e., the start vertex of the path, along with the hash of
+/// the columns of the record, which tells apart the traversers starting from the same vertex.
+type Traverser = (u64, ID);
+
+/// Get the traverser, the end vertex and the number of hops of the path in the head of the record.
+fn get_path_ends(input: &Record) -> FnResult<(Traverser, ID, usize)> {
+    let path = input
+        .get(None)
+        .and_then(|entry| entry.as_graph_path())
+        .ok_or_else(|| {
+            FnExecError::unexpected_data_error(&format!(
+                "unexpected input for shortest path {:?}",
+                input.get(None)
+            ))
+        })?;
+    let start = path.get_path_start().ok_or_else(|| {
+        FnExecError::unexpected_data_error(&format!("the start of path {:?} is not preserved", path))
+    })?;
+    // the columns are kept as they are while expanding, and are hashed identically in all the workers
+    let mut hasher = DefaultHasher::new();
+    for (tag, entry) in input.get_columns().iter() {
+        tag.hash(&mut hasher);
+        entry.hash(&mut hasher);
+    }
+    Ok(((hasher.finish(), start.id()), path.get_path_end().id(), path.len()))
+}
+
+/// The visited set of the shortest-path expansion, which records the hops at which a vertex is first
+/// reached by a traverser, and filters out the paths that reach a vertex later than that, namely, a
+/// vertex is expanded at most once per traverser (or once per minimal-length path, if `all_shortest`).
+///
+/// It is shared by the operators of all the hops of the expansion within a worker, which run in the
+/// same thread, and is kept per scope. As the paths are shuffled by their end vertices between hops
+/// (or stay in the worker of their source if not shuffled), the paths from a traverser to a vertex
+/// are always checked against the same visited set.
+#[derive(Clone)]
+pub struct ShortestPathVisitor {
+    visited: UnsafeRcPtr<RefCell<TidyTagMap<HashMap<(Traverser, ID), usize>>>>,
+    all_shortest: bool,
+}
+
+impl ShortestPathVisitor {
+    pub fn new(scope_level: u32, all_shortest: bool) -> Self {
+        ShortestPathVisitor {
+            visited: UnsafeRcPtr::new(RefCell::new(TidyTagMap::new(scope_level))),
+            all_shortest,
+        }
+    }
+
+    fn test(&self, visited: &mut HashMap<(Traverser, ID), usize>, input: &Record) -> FnResult<bool> {
+        let (traverser, end, hops) = get_path_ends(input)?;
+        match visited.get_mut(&(traverser, end)) {
+            None => {
+                visited.insert((traverser, end), hops);
+                Ok(true)
+            }
+            // a shorter path may arrive later, if the paths of different hops are shuffled across workers
+            Some(first_hops) if hops < *first_hops => {
+                *first_hops = hops;
+                Ok(true)
+            }
+            Some(first_hops) => Ok(self.all_shortest && hops == *first_hops),
+        }
+    }
+
+    /// Install the visitor after a hop of the expansion. The visited set of a scope is released once
+    /// the scope ends in the last hop.
+    pub fn install(
+        &self, stream: Stream<Record>, is_last_hop: bool,
+    ) -> Result<Stream<Record>, BuildJobError> {
+        let visitor = self.clone();
+        stream.unary("shortest_path_visit", move |_info| {
+            move |input, output| {
+                input.for_each_batch(|batch| {
+                    if !batch.is_empty() {
+                        let mut session = output.new_session(&batch.tag)?;
+                        let mut table = visitor.visited.borrow_mut();
+                        let visited = table.get_mut_or_else(&batch.tag, HashMap::new);
+                        for record in batch.drain() {
+                            if visitor.test(visited, &record)? {
+                                session.give(record)?;
+                            }
+                        }
+                    }
+                    if is_last_hop && batch.is_last() {
+                        visitor.visited.borrow_mut().remove(&batch.tag);
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
+
+/// The collector of the shortest paths emitted by the expansion, which keeps, for each pair of
+/// traverser and end vertex, only the paths of the minimal hops: any one of them, or all of them if
+/// `all_shortest`. The paths of a pair are collected in the same worker as they are visited.
+///
+/// A path of the fewest hops that the expansion emits is output right away, as no shorter one can
+/// arrive later; the others are held until the scope ends, so that a longer path emitted before a
+/// shorter one arrived is never output.
+#[derive(Clone, Debug, Default)]
+pub struct ShortestPathCollector {
+    // the hops, the held paths, and whether a path is already output, of each pair
+    paths: HashMap<(Traverser, ID), (usize, Vec<Record>, bool)>,
+    min_hops: usize,
+    all_shortest: bool,
+    // whether to preserve only the end vertex of the path in the results
+    end_v_only: bool,
+}
+
+impl ShortestPathCollector {
+    pub fn new(min_hops: usize, all_shortest: bool, end_v_only: bool) -> Self {
+        ShortestPathCollector { paths: HashMap::new(), min_hops, all_shortest, end_v_only }
+    }
+
+    fn accum(&mut self, input: Record) -> FnResult<Option<Record>> {
+        let (traverser, end, hops) = get_path_ends(&input)?;
+        let (shortest_hops, paths, is_output) = self
+            .paths
+            .entry((traverser, end))
+            .or_insert_with(|| (hops, vec![], false));
+        if hops < *shortest_hops {
+            *shortest_hops = hops;
+            paths.clear();
+        }
+        if hops != *shortest_hops || (!self.all_shortest && (*is_output || !paths.is_empty())) {
+            return Ok(None);
+        }
+        if hops == self.min_hops {
+            *is_output = true;
+            Ok(Some(finalize_path(input, self.end_v_only)))
+        } else {
+            paths.push(input);
+            Ok(None)
+        }
+    }
+
+    fn finalize(self) -> impl Iterator<Item = Record> + Send + 'static {
+        let end_v_only = self.end_v_only;
+        self.paths
+            .into_iter()
+            .flat_map(|(_, (_, paths, _))| paths.into_iter())
+            .map(move |record| finalize_path(record, end_v_only))
+    }
+
+    /// Install the collector after the expansion, which keeps the held paths per scope.
+    pub fn install(self, stream: Stream<Record>) -> Result<Stream<Record>, BuildJobError> {
+        stream.unary("shortest_path_collect", move |info| {
+            let mut table = TidyTagMap::<ShortestPathCollector>::new(info.scope_level);
+            move |input, output| {
+                input.for_each_batch(|batch| {
+                    if !batch.is_empty() {
+                        let mut session = output.new_session(&batch.tag)?;
+                        let collector = table.get_mut_or_else(&batch.tag, || self.clone());
+                        for record in batch.drain() {
+                            if let Some(record) = collector.accum(record)? {
+                                session.give(record)?;
+                            }
+                        }
+                    }
+                    if batch.is_last() {
+                        if let Some(collector) = table.remove(&batch.tag) {
+                            let mut session = output.new_session(&batch.tag)?;
+                            session.give_iterator(collector.finalize())?;
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
+
+fn finalize_path(mut record: Record, end_v_only: bool) -> Record {
+    if end_v_only {
+        let path = record
+            .get(None)
+            .and_then(|entry| entry.as_graph_path())
+            .unwrap();
+        let end_v = GraphPath::EndV((path.get_path_end().clone(), path.len() + 1));
+        record.append(end_v, None);
+    }
+    record
+}
@@ -17,7 +17,7 @@ use std::convert::TryInto;
 
 use dyn_type::Object;
 use graph_proxy::apis::{
-    get_graph, Direction, DynDetails, GraphElement, QueryParams, Statement, Vertex, ID,
+    get_graph, memoize_fn, Direction, DynDetails, GraphElement, QueryParams, Statement, Vertex, ID,
 };
 use ir_common::generated::algebra::edge_expand::ExpandOpt;
 use ir_common::generated::physical as pb;
@@ -131,6 +131,20 @@ impl<E: Entry + 'static> FlatMapFunction<Record, Record> for EdgeExpandOperator<
     }
 }
 
+/// Memoize the expanded results of the repeated source ids, if opted in by the query params
+fn memoize_if_opted<E>(
+    stmt: Box<dyn Statement<ID, E>>, query_params: &QueryParams,
+) -> FnGenResult<Box<dyn Statement<ID, E>>>
+where
+    E: Clone + Send + Sync + 'static,
+{
+    if let Some(size) = query_params.get_memoize_size()? {
+        Ok(Box::new(memoize_fn(size, move |id| stmt.exec(id))))
+    } else {
+        Ok(stmt)
+    }
+}
+
 impl FlatMapFuncGen for pb::EdgeExpand {
     fn gen_flat_map(
         self,
@@ -154,7 +168,10 @@ impl FlatMapFuncGen for pb::EdgeExpand {
                 if query_params.filter.is_some() {
                     // Expand vertices with filters on edges.
                     // This can be regarded as a combination of EdgeExpand (with is_edge = true) + GetV
-                    let stmt = graph.prepare_explore_edge(direction, &query_params)?;
+                    let stmt = memoize_if_opted(
+                        graph.prepare_explore_edge(direction, &query_params)?,
+                        &query_params,
+                    )?;
                     let edge_expand_operator = EdgeExpandOperator {
                         start_v_tag,
                         alias: edge_or_end_v_tag,
@@ -165,7 +182,10 @@ impl FlatMapFuncGen for pb::EdgeExpand {
                     Ok(Box::new(edge_expand_operator))
                 } else {
                     // Expand vertices without any filters
-                    let stmt = graph.prepare_explore_vertex(direction, &query_params)?;
+                    let stmt = memoize_if_opted(
+                        graph.prepare_explore_vertex(direction, &query_params)?,
+                        &query_params,
+                    )?;
                     let edge_expand_operator = EdgeExpandOperator {
                         start_v_tag,
                         alias: edge_or_end_v_tag,
@@ -176,8 +196,8 @@ impl FlatMapFuncGen for pb::EdgeExpand {
                     Ok(Box::new(edge_expand_operator))
                 }
             }
-            _ => {
-                // Expand edges or degree
+            ExpandOpt::Degree => {
+                // Count the edges, which are not memoized, as they are only counted
                 let stmt = graph.prepare_explore_edge(direction, &query_params)?;
                 let edge_expand_operator = EdgeExpandOperator {
                     start_v_tag,
@@ -188,6 +208,19 @@ impl FlatMapFuncGen for pb::EdgeExpand {
                 };
                 Ok(Box::new(edge_expand_operator))
             }
+            ExpandOpt::Edge => {
+                // Expand edges
+                let stmt =
+                    memoize_if_opted(graph.prepare_explore_edge(direction, &query_params)?, &query_params)?;
+                let edge_expand_operator = EdgeExpandOperator {
+                    start_v_tag,
+                    alias: edge_or_end_v_tag,
+                    stmt,
+                    expand_opt,
+                    is_optional: self.is_optional,
+                };
+                Ok(Box::new(edge_expand_operator))
+            }
         }
     }
 }
@@ -21,7 +21,7 @@ use pegasus::api::function::{DynIter, FlatMapFunction, FnResult};
 use pegasus_common::downcast::AsAny;
 
 use crate::error::{FnExecError, FnGenResult};
-use crate::process::entry::{CollectionEntry, Entry, EntryType};
+use crate::process::entry::{CollectionEntry, DynEntry, Entry, EntryType, PairEntry};
 use crate::process::operator::flatmap::FlatMapFuncGen;
 use crate::process::operator::map::{GeneralIntersectionEntry, IntersectionEntry};
 use crate::process::record::Record;
@@ -30,9 +30,34 @@ use crate::process::record::Record;
 /// Unfold the Collection entry referred by a given `tag`.
 /// Notice that unfold will remove the Collection entry from the Record,
 /// and append items in collection as new entries.
+/// Besides collections, a vector or map object, and a path can also be unfolded, where a map is
+/// unfolded into its key-value pairs, and a path is unfolded into its vertices (and edges).
 pub struct UnfoldOperator {
     tag: Option<KeyId>,
     alias: Option<KeyId>,
+    keep_empty: bool,
+}
+
+impl UnfoldOperator {
+    /// Append each of the `items` to a copy of the `input` record. If `items` is empty,
+    /// emit nothing, or the `input` record appended with a null entry if `keep_empty` is set.
+    fn unfold_items(&self, mut input: Record, items: Vec<DynEntry>) -> DynIter<Record> {
+        if items.is_empty() {
+            if self.keep_empty {
+                input.append(Object::None, self.alias);
+                Box::new(vec![input].into_iter())
+            } else {
+                Box::new(std::iter::empty())
+            }
+        } else {
+            let alias = self.alias;
+            Box::new(items.into_iter().map(move |item| {
+                let mut new_entry = input.clone();
+                new_entry.append(item, alias);
+                new_entry
+            }))
+        }
+    }
 }
 
 impl FlatMapFunction<Record, Record> for UnfoldOperator {
@@ -107,31 +132,45 @@ impl FlatMapFunction<Record, Record> for UnfoldOperator {
                     .ok_or_else(|| {
                         FnExecError::unexpected_data_error("downcast collection entry in UnfoldOperator")
                     })?;
-                let mut res = Vec::with_capacity(collection.len());
-                for item in collection.inner.iter().cloned() {
-                    let mut new_entry = input.clone();
-                    new_entry.append(item, self.alias);
-                    res.push(new_entry);
-                }
-                Ok(Box::new(res.into_iter()))
+                let items = collection.inner.clone();
+                Ok(self.unfold_items(input, items))
+            }
+            EntryType::Object => {
+                let entry = input.get(self.tag).unwrap();
+                let items: Vec<DynEntry> = match entry.as_object() {
+                    Some(Object::Vector(vec)) => vec
+                        .iter()
+                        .cloned()
+                        .map(DynEntry::from)
+                        .collect(),
+                    // a map is unfolded into its key-value pairs, each as a `PairEntry` of (key, value)
+                    Some(Object::KV(kv)) => kv
+                        .iter()
+                        .map(|(key, value)| PairEntry::new(key.clone().into(), value.clone().into()).into())
+                        .collect(),
+                    _ => Err(FnExecError::unexpected_data_error(&format!(
+                        "unfold object entry {:?} in UnfoldOperator",
+                        entry
+                    )))?,
+                };
+                Ok(self.unfold_items(input, items))
             }
             EntryType::Path => {
                 let entry = input.get(self.tag).unwrap();
                 let path = entry.as_graph_path().ok_or_else(|| {
-                    FnExecError::unexpected_data_error("downcast path entry in UnfoldOperatro")
+                    FnExecError::unexpected_data_error("downcast path entry in UnfoldOperator")
                 })?;
-                let path_vec = if let Some(path) = path.get_path() {
-                    path.clone()
+                // The path preserves the vertices (AllV), or the vertices and edges (AllVE) in order,
+                // or only the end vertex (EndV).
+                let items: Vec<DynEntry> = if let Some(path) = path.get_path() {
+                    path.iter()
+                        .cloned()
+                        .map(DynEntry::from)
+                        .collect()
                 } else {
-                    vec![path.get_path_end().clone()]
+                    vec![path.get_path_end().clone().into()]
                 };
-                let mut res = Vec::with_capacity(path_vec.len());
-                for item in path_vec {
-                    let mut new_entry = input.clone();
-                    new_entry.append(item, self.alias);
-                    res.push(new_entry)
-                }
-                Ok(Box::new(res.into_iter()))
+                Ok(self.unfold_items(input, items))
             }
             _ => Err(FnExecError::unexpected_data_error(&format!(
                 "unfold entry {:?} in UnfoldOperator",
@@ -145,7 +184,8 @@ impl FlatMapFuncGen for pb::Unfold {
     fn gen_flat_map(
         self,
     ) -> FnGenResult<Box<dyn FlatMapFunction<Record, Record, Target = DynIter<Record>>>> {
-        let unfold_operator = UnfoldOperator { tag: self.tag, alias: self.alias };
+        let unfold_operator =
+            UnfoldOperator { tag: self.tag, alias: self.alias, keep_empty: self.keep_empty };
         if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
             debug!("Runtime unfold operator {:?}", unfold_operator);
         }
@@ -155,20 +195,26 @@ impl FlatMapFuncGen for pb::Unfold {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
+    use dyn_type::Object;
     use graph_proxy::apis::graph::element::GraphElement;
+    use graph_proxy::apis::{DynDetails, Edge, GraphPath};
+    use ir_common::generated::algebra as algebra_pb;
     use ir_common::generated::common as common_pb;
     use ir_common::generated::physical as pb;
+    use pegasus::api::function::FlatMapFunction;
     use pegasus::api::{Fold, Map, Sink};
     use pegasus::result::ResultStream;
     use pegasus::JobConf;
     use pegasus_common::downcast::AsAny;
 
     use crate::process::entry::CollectionEntry;
-    use crate::process::entry::Entry;
+    use crate::process::entry::{DynEntry, Entry, PairEntry};
     use crate::process::functions::FoldGen;
     use crate::process::operator::accum::accumulator::Accumulator;
     use crate::process::operator::flatmap::FlatMapFuncGen;
-    use crate::process::operator::tests::{init_source, TAG_A};
+    use crate::process::operator::tests::{init_source, init_vertex1, init_vertex2, TAG_A, TAG_B};
     use crate::process::record::Record;
 
     fn fold_unfold_test(fold_opr_pb: pb::GroupBy, unfold_opr_pb: pb::Unfold) -> ResultStream<Record> {
@@ -206,7 +252,7 @@ mod tests {
             alias: Some(TAG_A.into()),
         };
         let fold_opr_pb = pb::GroupBy { mappings: vec![], functions: vec![function] };
-        let unfold_opr_pb = pb::Unfold { tag: Some(TAG_A.into()), alias: None };
+        let unfold_opr_pb = pb::Unfold { tag: Some(TAG_A.into()), alias: None, keep_empty: false };
         let mut result = fold_unfold_test(fold_opr_pb, unfold_opr_pb);
 
         let expected_result = vec![1, 2];
@@ -236,7 +282,7 @@ mod tests {
             alias: None,
         };
         let fold_opr_pb = pb::GroupBy { mappings: vec![], functions: vec![function] };
-        let unfold_opr_pb = pb::Unfold { tag: None, alias: None };
+        let unfold_opr_pb = pb::Unfold { tag: None, alias: None, keep_empty: false };
         let mut result = fold_unfold_test(fold_opr_pb, unfold_opr_pb);
 
         let expected_result = vec![1, 2];
@@ -260,7 +306,7 @@ mod tests {
             alias: Some(TAG_A.into()),
         };
         let fold_opr_pb = pb::GroupBy { mappings: vec![], functions: vec![function] };
-        let unfold_opr_pb = pb::Unfold { tag: None, alias: None };
+        let unfold_opr_pb = pb::Unfold { tag: None, alias: None, keep_empty: false };
 
         let mut result = fold_unfold_test(fold_opr_pb, unfold_opr_pb);
         let expected_result = vec![1, 2];
@@ -279,4 +325,106 @@ mod tests {
         result_ids.sort();
         assert_eq!(result_ids, expected_result);
     }
+
+    fn unfold_record(record: Record, keep_empty: bool) -> Vec<Record> {
+        let unfold_opr_pb = pb::Unfold { tag: Some(TAG_A.into()), alias: Some(TAG_B.into()), keep_empty };
+        let unfold = unfold_opr_pb.gen_flat_map().unwrap();
+        unfold.exec(record).unwrap().collect()
+    }
+
+    #[test]
+    fn unfold_map_test() {
+        let mut map = BTreeMap::new();
+        map.insert(object!("name"), object!("marko"));
+        map.insert(object!("scores"), Object::Vector(vec![object!(1), object!(2)]));
+        let record = Record::new(Object::KV(map), Some(TAG_A));
+        let results = unfold_record(record, false);
+        let pairs: Vec<(DynEntry, DynEntry)> = results
+            .iter()
+            .map(|res| {
+                let pair = res
+                    .get(Some(TAG_B))
+                    .unwrap()
+                    .as_any_ref()
+                    .downcast_ref::<PairEntry>()
+                    .unwrap();
+                (pair.get_left().clone(), pair.get_right().clone())
+            })
+            .collect();
+        // the nested vector in the map value is preserved as it is
+        let expected: Vec<(DynEntry, DynEntry)> = vec![
+            (object!("name").into(), object!("marko").into()),
+            (object!("scores").into(), Object::Vector(vec![object!(1), object!(2)]).into()),
+        ];
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn unfold_vector_test() {
+        let record = Record::new(Object::Vector(vec![object!(1), object!(2), object!(3)]), Some(TAG_A));
+        let results: Vec<Object> = unfold_record(record, false)
+            .iter()
+            .map(|res| {
+                res.get(Some(TAG_B))
+                    .unwrap()
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        assert_eq!(results, vec![object!(1), object!(2), object!(3)]);
+    }
+
+    #[test]
+    fn unfold_path_test() {
+        let (v1, v2) = (init_vertex1(), init_vertex2());
+        let e12 = Edge::new(12, None, v1.id(), v2.id(), DynDetails::default());
+        // AllV
+        let mut path = GraphPath::new(
+            v1.clone(),
+            algebra_pb::path_expand::PathOpt::Arbitrary,
+            algebra_pb::path_expand::ResultOpt::AllV,
+        )
+        .unwrap();
+        path.append(v2.clone());
+        let results: Vec<_> = unfold_record(Record::new(path, Some(TAG_A)), false)
+            .iter()
+            .map(|res| res.get(Some(TAG_B)).unwrap().id())
+            .collect();
+        assert_eq!(results, vec![v1.id(), v2.id()]);
+
+        // AllVE
+        let mut path = GraphPath::new(
+            v1.clone(),
+            algebra_pb::path_expand::PathOpt::Arbitrary,
+            algebra_pb::path_expand::ResultOpt::AllVE,
+        )
+        .unwrap();
+        path.append(e12);
+        path.append(v2.clone());
+        let results: Vec<_> = unfold_record(Record::new(path, Some(TAG_A)), false)
+            .iter()
+            .map(|res| {
+                let entry = res.get(Some(TAG_B)).unwrap();
+                (entry.id(), entry.as_vertex().is_some())
+            })
+            .collect();
+        assert_eq!(results, vec![(v1.id(), true), (12, false), (v2.id(), true)]);
+    }
+
+    #[test]
+    fn unfold_empty_test() {
+        let empty_collection: DynEntry = CollectionEntry { inner: vec![] }.into();
+        let empty_map = Object::KV(BTreeMap::new());
+
+        assert!(unfold_record(Record::new(empty_collection.clone(), Some(TAG_A)), false).is_empty());
+        assert!(unfold_record(Record::new(empty_map.clone(), Some(TAG_A)), false).is_empty());
+
+        for record in vec![Record::new(empty_collection, Some(TAG_A)), Record::new(empty_map, Some(TAG_A))]
+        {
+            let results = unfold_record(record, true);
+            assert_eq!(results.len(), 1);
+            assert!(results[0].get(Some(TAG_B)).unwrap().is_none());
+        }
+    }
 }
@@ -77,24 +77,29 @@ impl MapFunction<(RecordKey, Record), Record> for GroupMap {
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     use ahash::HashMap;
-    use dyn_type::Object;
+    use dyn_type::{DateTimeFormats, Object};
     use graph_proxy::apis::GraphElement;
-    use graph_proxy::apis::{DynDetails, Vertex};
+    use graph_proxy::apis::{Details, DynDetails, PropertyValue, Vertex};
     use ir_common::generated::physical as pb;
     use ir_common::generated::{algebra, common as common_pb};
-    use ir_common::NameOrId;
+    use ir_common::{KeyId, NameOrId};
     use pegasus::api::{Dedup, FoldByKey, KeyBy, Map, Sink};
     use pegasus::result::ResultStream;
     use pegasus::JobConf;
+    use pegasus_common::downcast::*;
+    use pegasus_common::impl_as_any;
 
     use crate::process::entry::{CollectionEntry, DynEntry, Entry};
     use crate::process::functions::GroupGen;
     use crate::process::operator::accum::accumulator::Accumulator;
     use crate::process::operator::keyed::KeyFunctionGen;
     use crate::process::operator::tests::{
-        init_source, init_vertex1, init_vertex2, to_var_pb, PERSON_LABEL, TAG_A, TAG_B, TAG_C,
+        init_path_source_with_tag, init_source, init_vertex1, init_vertex2, to_var_pb, PERSON_LABEL, TAG_A,
+        TAG_B, TAG_C, TAG_D,
     };
     use crate::process::record::Record;
 
@@ -458,6 +463,74 @@ mod tests {
         assert_eq!(group_result, expected_result);
     }
 
+    // g.V().group().by(label).by(max('creationDate').as('b'), min('name').as('c')) with key as 'a'
+    #[test]
+    fn group_max_date_min_name_by_label_test() {
+        let date = |millis| Object::DateFormat(DateTimeFormats::from_timestamp_millis(millis).unwrap());
+        let vertex = |id, label, name: &str, created| {
+            let map: HashMap<NameOrId, Object> =
+                vec![("name".into(), object!(name)), ("creationDate".into(), created)]
+                    .into_iter()
+                    .collect();
+            Record::new(Vertex::new(id, Some(label), DynDetails::new(map)), None)
+        };
+        let source = vec![
+            vertex(1, PERSON_LABEL, "marko", date(1267401600000)),
+            vertex(2, PERSON_LABEL, "vadas", date(1327017600000)),
+            vertex(3, PERSON_LABEL, "josh", date(1309824000000)),
+            vertex(4, 1, "ripple", date(1233532800000)),
+            vertex(5, 1, "lop", date(1233532800000)),
+        ];
+        let function = |var: &str, aggregate, alias: KeyId| pb::group_by::AggFunc {
+            vars: vec![common_pb::Variable::from(var.to_string())],
+            aggregate,
+            alias: Some(alias.into()),
+        };
+        let key_alias = pb::group_by::KeyAlias {
+            key: Some(common_pb::Variable::from("@.~label".to_string())),
+            alias: Some(TAG_A.into()),
+        };
+        let group_opr_pb = pb::GroupBy {
+            mappings: vec![key_alias],
+            functions: vec![
+                function("@.creationDate", 2, TAG_B), // max
+                function("@.name", 1, TAG_C),         // min
+            ],
+        };
+        let mut result = group_test(source, group_opr_pb);
+        let mut group_result = vec![];
+        while let Some(Ok(result)) = result.next() {
+            let label = result
+                .get(Some(TAG_A))
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .as_i64()
+                .unwrap();
+            let max_date = result
+                .get(Some(TAG_B))
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .clone();
+            let min_name = result
+                .get(Some(TAG_C))
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .clone();
+            group_result.push((label, max_date, min_name));
+        }
+        group_result.sort_by_key(|(label, _, _)| *label);
+        assert_eq!(
+            group_result,
+            vec![
+                (PERSON_LABEL as i64, date(1327017600000), object!("josh")),
+                (1, date(1233532800000), object!("lop"))
+            ]
+        );
+    }
+
     // g.V().group.by("name").first() with key as 'a', value as 'b'
     #[test]
     fn group_first_test() {
@@ -582,7 +655,8 @@ mod tests {
         let conf = JobConf::new("dedup_test");
         let mut result = pegasus::run(conf, || {
             let source = init_group_source_with_null();
-            let dedup_opr = algebra::Dedup { keys: vec![to_var_pb(None, Some("age".into()))] };
+            let dedup_opr =
+                algebra::Dedup { keys: vec![to_var_pb(None, Some("age".into()))], dedup_key: None };
 
             move |input, output| {
                 let stream = input.input_from(source.into_iter())?;
@@ -614,4 +688,134 @@ mod tests {
         dedup_result.sort();
         assert_eq!(dedup_result, expected_result);
     }
+
+    #[derive(Debug)]
+    struct CountingLazyDetails {
+        props: HashMap<NameOrId, Object>,
+        // the number of times that a single property is fetched
+        fetch_count: Arc<AtomicUsize>,
+    }
+
+    impl_as_any!(CountingLazyDetails);
+
+    impl Details for CountingLazyDetails {
+        fn get_property(&self, key: &NameOrId) -> Option<PropertyValue> {
+            self.fetch_count.fetch_add(1, Ordering::SeqCst);
+            self.props
+                .get(key)
+                .map(|obj| PropertyValue::Owned(obj.clone()))
+        }
+
+        fn get_all_properties(&self) -> Option<HashMap<NameOrId, Object>> {
+            Some(self.props.clone())
+        }
+
+        fn get_property_keys(&self) -> Option<Vec<NameOrId>> {
+            Some(vec![])
+        }
+    }
+
+    // g.V().group().by(values("id", "name", "age", "gender")), where the vertex has lazy details
+    #[test]
+    fn group_by_many_props_of_lazy_vertex_test() {
+        let props: HashMap<NameOrId, Object> = vec![
+            ("id".into(), object!(1)),
+            ("name".into(), object!("marko")),
+            ("age".into(), object!(29)),
+            ("gender".into(), object!("male")),
+            ("city".into(), object!("beijing")),
+        ]
+        .into_iter()
+        .collect();
+        let fetch_count = Arc::new(AtomicUsize::new(0));
+        let details = CountingLazyDetails { props, fetch_count: fetch_count.clone() };
+        let record = Record::new(Vertex::new(1, Some(PERSON_LABEL), DynDetails::lazy(details)), None);
+
+        let mappings = vec![("@.id", TAG_A), ("@.name", TAG_B), ("@.age", TAG_C), ("@.gender", TAG_D)]
+            .into_iter()
+            .map(|(key, alias)| pb::group_by::KeyAlias {
+                key: Some(common_pb::Variable::from(key.to_string())),
+                alias: Some(alias.into()),
+            })
+            .collect();
+        let group_opr_pb = pb::GroupBy { mappings, functions: vec![] };
+        let group_key = group_opr_pb.gen_group_key().unwrap();
+        let (key, record) = group_key.get_kv(record).unwrap();
+
+        let keys: Vec<Object> = key
+            .take()
+            .into_iter()
+            .map(|entry| entry.as_object().unwrap().clone())
+            .collect();
+        assert_eq!(keys, vec![object!(1), object!("marko"), object!(29), object!("male")]);
+        // more than 3 properties are accessed, thus they are fetched into the vertex at once,
+        // and each of them is fetched only once
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 4);
+        let vertex = record.get(None).unwrap().as_vertex().unwrap();
+        assert_eq!(
+            vertex
+                .get_property(&"name".into())
+                .unwrap()
+                .try_to_owned(),
+            Some(object!("marko"))
+        );
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 4);
+        // the property not accessed by the keys is not fetched, but is still accessed lazily
+        assert_eq!(
+            vertex
+                .get_property(&"city".into())
+                .unwrap()
+                .try_to_owned(),
+            Some(object!("beijing"))
+        );
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 5);
+    }
+
+    fn group_count_paths(key: &str) -> HashSet<(DynEntry, DynEntry)> {
+        let function = pb::group_by::AggFunc {
+            vars: vec![common_pb::Variable::from("@0".to_string())],
+            aggregate: 3, // Count
+            alias: Some(TAG_C.into()),
+        };
+        let key_alias = pb::group_by::KeyAlias {
+            key: Some(common_pb::Variable::from(key.to_string())),
+            alias: Some(TAG_B.into()),
+        };
+        let group_opr_pb = pb::GroupBy { mappings: vec![key_alias], functions: vec![function] };
+        let mut result = group_test(init_path_source_with_tag(), group_opr_pb);
+        let mut group_result = HashSet::new();
+        while let Some(Ok(result)) = result.next() {
+            let key = result.get(Some(TAG_B)).unwrap();
+            let val = result.get(Some(TAG_C)).unwrap();
+            group_result.insert((key.clone(), val.clone()));
+        }
+        group_result
+    }
+
+    // g.V().as('a').repeat(out()).times(1..3).path().groupCount().by(path().count(local))
+    #[test]
+    fn group_count_by_path_len_test() {
+        let expected_result: HashSet<(DynEntry, DynEntry)> = [
+            (object!(1usize).into(), object!(1u64).into()),
+            (object!(2usize).into(), object!(2u64).into()),
+            (object!(3usize).into(), object!(1u64).into()),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        assert_eq!(group_count_paths("@0.~len"), expected_result);
+    }
+
+    // group the paths by the name of their last vertices
+    #[test]
+    fn group_count_by_path_last_vertex_test() {
+        let expected_result: HashSet<(DynEntry, DynEntry)> = [
+            (object!("marko").into(), object!(2u64).into()),
+            (object!("vadas").into(), object!(2u64).into()),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        assert_eq!(group_count_paths("@0.~[-1].name"), expected_result);
+    }
 }
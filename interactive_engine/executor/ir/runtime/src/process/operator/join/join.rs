@@ -15,6 +15,8 @@
 
 use ir_common::generated::algebra::join::JoinKind;
 use ir_common::generated::physical as pb;
+use ir_common::generated::physical::physical_opr::operator::OpKind;
+use ir_common::KeyId;
 
 use crate::error::FnGenResult;
 use crate::process::functions::{JoinKeyGen, KeyFunction};
@@ -45,6 +47,101 @@ impl JoinKeyGen<Record, RecordKey, Record> for pb::Join {
         }
         join_kind
     }
+
+    fn is_null_safe(&self) -> bool {
+        self.null_policy == pb::join::NullPolicy::NullSafe as i32
+    }
+
+    fn get_output_tags(&self) -> (Vec<KeyId>, Vec<KeyId>) {
+        let mut left_tags = vec![];
+        if let Some(left_plan) = self.left_plan.as_ref() {
+            collect_aliases(left_plan, &mut left_tags);
+        }
+        let mut right_tags = vec![];
+        if let Some(right_plan) = self.right_plan.as_ref() {
+            collect_aliases(right_plan, &mut right_tags);
+        }
+        if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
+            debug!("Runtime join operator output tags {:?}, {:?}", left_tags, right_tags);
+        }
+        (left_tags, right_tags)
+    }
+}
+
+/// Collect the aliases given by the operators of a plan, i.e., the tags that the plan outputs,
+/// where a `GroupBy` or a non-append `Project` outputs only its own aliases.
+fn collect_aliases(plan: &pb::PhysicalPlan, tags: &mut Vec<KeyId>) {
+    for opr in &plan.plan {
+        let op_kind = if let Some(op_kind) = opr
+            .opr
+            .as_ref()
+            .and_then(|opr| opr.op_kind.as_ref())
+        {
+            op_kind
+        } else {
+            continue;
+        };
+        let mut aliases = vec![];
+        match op_kind {
+            OpKind::Scan(scan) => aliases.push(scan.alias),
+            OpKind::Vertex(get_v) => aliases.push(get_v.alias),
+            OpKind::Edge(edge) => aliases.push(edge.alias),
+            OpKind::Path(path) => aliases.push(path.alias),
+            OpKind::Unfold(unfold) => aliases.push(unfold.alias),
+            OpKind::Apply(apply) => aliases.push(apply.alias),
+            OpKind::Project(project) => {
+                if !project.is_append {
+                    tags.clear();
+                }
+                aliases.extend(
+                    project
+                        .mappings
+                        .iter()
+                        .map(|mapping| mapping.alias),
+                )
+            }
+            OpKind::GroupBy(group) => {
+                tags.clear();
+                aliases.extend(
+                    group
+                        .mappings
+                        .iter()
+                        .map(|mapping| mapping.alias),
+                );
+                aliases.extend(
+                    group
+                        .functions
+                        .iter()
+                        .map(|function| function.alias),
+                );
+            }
+            OpKind::Join(join) => {
+                for sub_plan in join
+                    .left_plan
+                    .iter()
+                    .chain(join.right_plan.iter())
+                {
+                    collect_aliases(sub_plan, tags);
+                }
+            }
+            OpKind::Union(union) => {
+                for sub_plan in &union.sub_plans {
+                    collect_aliases(sub_plan, tags);
+                }
+            }
+            OpKind::Intersect(intersect) => {
+                for sub_plan in &intersect.sub_plans {
+                    collect_aliases(sub_plan, tags);
+                }
+            }
+            _ => {}
+        }
+        for alias in aliases.into_iter().flatten() {
+            if !tags.contains(&alias) {
+                tags.push(alias);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +151,7 @@ mod tests {
     use ir_common::generated::algebra::join::JoinKind;
     use ir_common::generated::common as common_pb;
     use ir_common::generated::physical as pb;
+    use ir_common::generated::physical::physical_opr::operator::OpKind;
     use pegasus::api::{Join, KeyBy, Map, PartitionByKey, Sink};
     use pegasus::JobConf;
 
@@ -89,6 +187,7 @@ mod tests {
                     join_kind,
                     left_plan: None,
                     right_plan: None,
+                    null_policy: 0,
                 };
                 let left_key_selector = join_opr_pb.gen_left_kv_fn()?;
                 let right_key_selector = join_opr_pb.gen_right_kv_fn()?;
@@ -200,4 +299,49 @@ mod tests {
         let expected_ids = vec![2];
         join_test(5, expected_ids);
     }
+
+    fn project_opr(aliases: Vec<i32>, is_append: bool) -> pb::PhysicalOpr {
+        let mappings = aliases
+            .into_iter()
+            .map(|alias| pb::project::ExprAlias { expr: None, alias: Some(alias.into()) })
+            .collect();
+        OpKind::Project(pb::Project { mappings, is_append }).into()
+    }
+
+    #[test]
+    fn join_output_tags_test() {
+        let scan = pb::Scan { alias: Some(0.into()), ..Default::default() };
+        let group = pb::GroupBy {
+            mappings: vec![pb::group_by::KeyAlias { key: None, alias: Some(1.into()) }],
+            functions: vec![pb::group_by::AggFunc { alias: Some(2.into()), ..Default::default() }],
+        };
+        let join_opr_pb = pb::Join {
+            left_plan: Some(pb::PhysicalPlan {
+                plan: vec![OpKind::Scan(scan.clone()).into(), project_opr(vec![1], true)],
+                plan_id: 0,
+                params: Default::default(),
+            }),
+            right_plan: Some(pb::PhysicalPlan {
+                plan: vec![
+                    OpKind::Scan(scan.clone()).into(),
+                    OpKind::GroupBy(group).into(),
+                    project_opr(vec![3], true),
+                ],
+                plan_id: 0,
+                params: Default::default(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(join_opr_pb.get_output_tags(), (vec![0, 1], vec![1, 2, 3]));
+
+        let join_opr_pb = pb::Join {
+            left_plan: Some(pb::PhysicalPlan {
+                plan: vec![OpKind::Scan(scan).into(), project_opr(vec![1], false)],
+                plan_id: 0,
+                params: Default::default(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(join_opr_pb.get_output_tags(), (vec![1], vec![]));
+    }
 }
@@ -15,21 +15,33 @@
 
 use std::convert::TryFrom;
 
+use ahash::{HashMap, HashMapExt};
+use graph_proxy::apis::{DynDetails, GraphElement, PropKey, Vertex};
+use graph_proxy::utils::expr::eval::{Evaluate, Evaluator};
 use ir_common::error::ParsePbError;
 use ir_common::generated::algebra as algebra_pb;
 use ir_common::generated::common as common_pb;
 use ir_common::generated::physical as pb;
+use ir_common::{KeyId, NameOrId};
 use pegasus::api::function::FnResult;
 
-use crate::error::FnGenResult;
+use crate::error::{FnExecError, FnExecResult, FnGenResult};
+use crate::process::entry::{DynEntry, Entry};
 use crate::process::functions::KeyFunction;
 use crate::process::operator::keyed::KeyFunctionGen;
 use crate::process::operator::TagKey;
 use crate::process::record::{Record, RecordKey};
 
+/// If the keys access more than this number of distinct properties of a vertex,
+/// these lazy properties are fetched into the vertex at once, before the keys are got.
+const EAGER_DETAILS_THRESHOLD: usize = 3;
+
 #[derive(Debug)]
 pub struct KeySelector {
     keys: Vec<TagKey>,
+    /// the tags of vertices whose lazy details will be materialized before getting the keys,
+    /// with the properties to materialize, i.e., the ones accessed by the keys
+    eager_tags: Vec<(Option<KeyId>, Vec<NameOrId>)>,
 }
 
 impl KeySelector {
@@ -38,12 +50,57 @@ impl KeySelector {
             .into_iter()
             .map(|tag_key_pb| TagKey::try_from(tag_key_pb))
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(KeySelector { keys })
+        Ok(KeySelector { keys, eager_tags: vec![] })
+    }
+
+    /// Materialize the lazy details of the vertices, of which the keys access
+    /// more than `EAGER_DETAILS_THRESHOLD` distinct properties.
+    fn with_eager_details(mut self) -> Self {
+        let mut tag_props: HashMap<Option<KeyId>, Vec<NameOrId>> = HashMap::new();
+        for tag_key in self.keys.iter() {
+            if let Some(PropKey::Key(key)) = tag_key.key.as_ref() {
+                let props = tag_props.entry(tag_key.tag).or_default();
+                if !props.contains(key) {
+                    props.push(key.clone());
+                }
+            }
+        }
+        let eager_tags = tag_props
+            .into_iter()
+            .filter(|(_, props)| props.len() > EAGER_DETAILS_THRESHOLD)
+            .collect();
+        self.eager_tags = eager_tags;
+        self
     }
 }
 
+fn materialize_details(input: &mut Record, tag: Option<KeyId>, props: &[NameOrId]) -> FnExecResult<()> {
+    let eager_vertex = match input
+        .get(tag)
+        .and_then(|entry| entry.as_vertex())
+    {
+        Some(vertex) if matches!(vertex.get_details(), DynDetails::Lazy(_)) => {
+            let details = vertex.get_details().into_eager_with(props)?;
+            Vertex::new(vertex.id(), vertex.label(), details.into())
+        }
+        _ => return Ok(()),
+    };
+    let entry = DynEntry::new(eager_vertex);
+    if let Some(tag) = tag {
+        input
+            .get_columns_mut()
+            .insert(tag as usize, entry);
+    } else {
+        input.set_curr_entry(Some(entry));
+    }
+    Ok(())
+}
+
 impl KeyFunction<Record, RecordKey, Record> for KeySelector {
     fn get_kv(&self, mut input: Record) -> FnResult<(RecordKey, Record)> {
+        for (tag, props) in self.eager_tags.iter() {
+            materialize_details(&mut input, *tag, props)?;
+        }
         let keys = self
             .keys
             .iter()
@@ -53,6 +110,22 @@ impl KeyFunction<Record, RecordKey, Record> for KeySelector {
     }
 }
 
+/// Select the key of a record as the result of evaluating an expression on it
+#[derive(Debug)]
+struct ExprKeySelector {
+    evaluator: Evaluator,
+}
+
+impl KeyFunction<Record, RecordKey, Record> for ExprKeySelector {
+    fn get_kv(&self, input: Record) -> FnResult<(RecordKey, Record)> {
+        let key = self
+            .evaluator
+            .eval::<DynEntry, Record>(Some(&input))
+            .map_err(|e| FnExecError::from(e))?;
+        Ok((RecordKey::new(vec![DynEntry::new(key)]), input))
+    }
+}
+
 impl KeyFunctionGen for pb::GroupBy {
     fn gen_key(self) -> FnGenResult<Box<dyn KeyFunction<Record, RecordKey, Record>>> {
         let key_selector = KeySelector::with(
@@ -60,7 +133,8 @@ impl KeyFunctionGen for pb::GroupBy {
                 .iter()
                 .map(|mapping| mapping.key.clone().unwrap())
                 .collect::<Vec<_>>(),
-        )?;
+        )?
+        .with_eager_details();
         if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
             debug!("Runtime group operator key_selector: {:?}", key_selector);
         }
@@ -70,6 +144,13 @@ impl KeyFunctionGen for pb::GroupBy {
 
 impl KeyFunctionGen for algebra_pb::Dedup {
     fn gen_key(self) -> FnGenResult<Box<dyn KeyFunction<Record, RecordKey, Record>>> {
+        if let Some(dedup_key) = self.dedup_key {
+            let key_selector = ExprKeySelector { evaluator: Evaluator::try_from(dedup_key)? };
+            if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
+                debug!("Runtime dedup operator key_selector: {:?}", key_selector);
+            }
+            return Ok(Box::new(key_selector));
+        }
         let key_selector = KeySelector::with(self.keys)?;
         if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
             debug!("Runtime dedup operator key_selector: {:?}", key_selector);
@@ -84,6 +165,7 @@ mod tests {
     use dyn_type::Object;
     use graph_proxy::apis::GraphElement;
     use graph_proxy::apis::{DynDetails, Vertex, ID};
+    use ir_common::expr_parse::str_to_expr_pb;
     use ir_common::generated::algebra as pb;
     use ir_common::generated::common as common_pb;
     use ir_common::NameOrId;
@@ -96,16 +178,21 @@ mod tests {
     use crate::process::record::Record;
 
     fn source_gen() -> Box<dyn Iterator<Item = Record> + Send> {
-        let p1: HashMap<NameOrId, Object> = vec![("age".into(), 27.into())]
-            .into_iter()
-            .collect();
-        let p2: HashMap<NameOrId, Object> = vec![("age".into(), 29.into())]
+        let p1: HashMap<NameOrId, Object> =
+            vec![("age".into(), 27.into()), ("name".into(), "marko".into())]
+                .into_iter()
+                .collect();
+        let p2: HashMap<NameOrId, Object> =
+            vec![("age".into(), 29.into()), ("name".into(), "marko".into())]
+                .into_iter()
+                .collect();
+        let p3: HashMap<NameOrId, Object> = vec![("age".into(), 29.into()), ("name".into(), "josh".into())]
             .into_iter()
             .collect();
 
         let v1 = Vertex::new(1, Some(PERSON_LABEL), DynDetails::new(p1));
-        let v2 = Vertex::new(1, Some(PERSON_LABEL), DynDetails::new(p2.clone()));
-        let v3 = Vertex::new(3, Some(PERSON_LABEL), DynDetails::new(p2));
+        let v2 = Vertex::new(1, Some(PERSON_LABEL), DynDetails::new(p2));
+        let v3 = Vertex::new(3, Some(PERSON_LABEL), DynDetails::new(p3));
         let r1 = Record::new(v1, None);
         let r2 = Record::new(v2, None);
         let r3 = Record::new(v3, None);
@@ -113,12 +200,16 @@ mod tests {
     }
 
     fn dedup_test(key_str: String, expected_ids: Vec<ID>) {
+        let dedup_opr_pb = pb::Dedup { keys: vec![common_pb::Variable::from(key_str)], dedup_key: None };
+        dedup_opr_test(dedup_opr_pb, expected_ids)
+    }
+
+    fn dedup_opr_test(dedup_opr_pb: pb::Dedup, expected_ids: Vec<ID>) {
         let conf = JobConf::new("dedup_test");
         let mut result = pegasus::run(conf, || {
-            let key_str = key_str.clone();
+            let dedup_opr_pb = dedup_opr_pb.clone();
             move |input, output| {
                 let mut stream = input.input_from(source_gen())?;
-                let dedup_opr_pb = pb::Dedup { keys: vec![common_pb::Variable::from(key_str.clone())] };
                 let selector = dedup_opr_pb.clone().gen_key().unwrap();
                 stream = stream
                     .key_by(move |record| selector.get_kv(record))?
@@ -154,4 +245,13 @@ mod tests {
         let expected_result = vec![1, 1];
         dedup_test(key_str, expected_result)
     }
+
+    // g.V().dedup().by(values('name'))
+    #[test]
+    fn dedup_by_key_expr_test() {
+        let dedup_opr_pb =
+            pb::Dedup { keys: vec![], dedup_key: Some(str_to_expr_pb("@.name".to_string()).unwrap()) };
+        let expected_result = vec![1, 3];
+        dedup_opr_test(dedup_opr_pb, expected_result)
+    }
 }
@@ -134,11 +134,28 @@ impl FilterMapFunction<Record, Record> for GetVertexOperator {
 /// An Auxilia operator to get extra information for the current entity.
 /// Specifically, we will update the old entity by appending the new extra information,
 /// and rename the entity, if `alias` has been set.
+/// If `is_optional`, a vertex absent from the graph is regarded as a null entry, instead of being filtered out.
 #[derive(Debug)]
 struct AuxiliaOperator {
     tag: Option<KeyId>,
     query_params: QueryParams,
     alias: Option<KeyId>,
+    is_optional: bool,
+}
+
+impl AuxiliaOperator {
+    fn append_null(&self, mut input: Record) -> FnResult<Option<Record>> {
+        if let Some(predicate) = &self.query_params.filter {
+            let res = predicate
+                .eval_bool(Some(&input))
+                .map_err(|e| FnExecError::from(e))?;
+            if !res {
+                return Ok(None);
+            }
+        }
+        input.append(NullEntry, self.alias);
+        Ok(Some(input))
+    }
 }
 
 impl FilterMapFunction<Record, Record> for AuxiliaOperator {
@@ -175,11 +192,19 @@ impl FilterMapFunction<Record, Record> for AuxiliaOperator {
             let graph = get_graph().ok_or_else(|| FnExecError::NullGraphError)?;
             if let Some(v) = entry.as_vertex() {
                 let id = v.id();
-                if let Some(vertex) = graph
-                    .get_vertex(&[id], &self.query_params)?
-                    .next()
-                    .map(|vertex| DynEntry::new(vertex))
-                {
+                let vertex = if self.is_optional {
+                    // fetch the vertex and learn whether it exists in a single round trip
+                    let (mut vertices, misses) = graph.get_vertex_with_misses(&[id], &self.query_params)?;
+                    if !misses.is_empty() {
+                        return self.append_null(input);
+                    }
+                    vertices.next()
+                } else {
+                    graph
+                        .get_vertex(&[id], &self.query_params)?
+                        .next()
+                };
+                if let Some(vertex) = vertex.map(|vertex| DynEntry::new(vertex)) {
                     if let Some(alias) = self.alias {
                         // append without moving head
                         input
@@ -244,20 +269,7 @@ impl FilterMapFunction<Record, Record> for AuxiliaOperator {
                     return Ok(None);
                 }
             } else if entry.is_none() {
-                if let Some(predicate) = &self.query_params.filter {
-                    let res = predicate
-                        .eval_bool(Some(&input))
-                        .map_err(|e| FnExecError::from(e))?;
-                    if res {
-                        input.append(NullEntry, self.alias);
-                        return Ok(Some(input));
-                    } else {
-                        return Ok(None);
-                    }
-                } else {
-                    input.append(NullEntry, self.alias);
-                    return Ok(Some(input));
-                }
+                return self.append_null(input);
             } else {
                 Err(FnExecError::unexpected_data_error(&format!(
                     "neither Vertex nor Edge entry is accessed in `Auxilia` operator, the entry is {:?}",
@@ -304,7 +316,9 @@ impl FilterMapFuncGen for pb::GetV {
             }
             VOpt::Itself => {
                 let query_params: QueryParams = self.params.try_into()?;
-                let auxilia_operator = AuxiliaOperator { tag: self.tag, query_params, alias: self.alias };
+                let is_optional = query_params.is_optional()?;
+                let auxilia_operator =
+                    AuxiliaOperator { tag: self.tag, query_params, alias: self.alias, is_optional };
                 if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
                     debug!("Runtime AuxiliaOperator: {:?}", auxilia_operator);
                 }
@@ -702,6 +702,45 @@ mod tests {
         assert_eq!(object_result, expected_result);
     }
 
+    // g.V().project(toUpper(name), concat(substring(name, 0, 3), '-', name)) with alias of 'b' and 'c'
+    #[test]
+    fn project_string_functions_test() {
+        let project_opr_pb = pb::Project {
+            mappings: vec![
+                pb::project::ExprAlias {
+                    expr: Some(str_to_expr_pb("toUpper(@.name)".to_string()).unwrap()),
+                    alias: Some(TAG_B.into()),
+                },
+                pb::project::ExprAlias {
+                    expr: Some(
+                        str_to_expr_pb("concat(substring(@.name, 0, 3), \"-\", @.name)".to_string())
+                            .unwrap(),
+                    ),
+                    alias: Some(TAG_C.into()),
+                },
+            ],
+            is_append: false,
+        };
+        let mut result = project_test(init_source(), project_opr_pb);
+        let mut object_result = vec![];
+        while let Some(Ok(res)) = result.next() {
+            let upper_val = res
+                .get(Some(TAG_B))
+                .unwrap()
+                .as_object()
+                .unwrap();
+            let concat_val = res
+                .get(Some(TAG_C))
+                .unwrap()
+                .as_object()
+                .unwrap();
+            object_result.push((upper_val.clone(), concat_val.clone()));
+        }
+        let expected_result =
+            vec![(object!("MARKO"), object!("mar-marko")), (object!("VADAS"), object!("vad-vadas"))];
+        assert_eq!(object_result, expected_result);
+    }
+
     // g.V().as('a').select('a').by(valueMap('age', 'name')) with alias of 'age' as 'b' and 'name' as 'c'
     #[test]
     fn project_tag_multi_mapping_test() {
@@ -36,12 +36,14 @@ use ir_common::KeyId;
 use pegasus::codec::{Decode, Encode, ReadExt, WriteExt};
 
 use crate::error::FnExecResult;
-use crate::process::entry::DynEntry;
+use crate::process::entry::{DynEntry, Entry};
 use crate::process::record::Record;
 
 #[derive(Clone, Debug, Default)]
 pub struct TagKey {
     tag: Option<KeyId>,
+    /// The position of the vertex to access, if the tagged entry is a path
+    path_elem: Option<i32>,
     key: Option<PropKey>,
 }
 
@@ -49,9 +51,23 @@ impl TagKey {
     /// This is for key generation, which generate the key of the input Record according to the tag_key field
     pub fn get_arc_entry(&self, input: &Record) -> FnExecResult<DynEntry> {
         if let Some(entry) = input.get(self.tag) {
-            if let Some(prop_key) = self.key.as_ref() {
-                let prop = prop_key.get_key(entry)?;
-                Ok(DynEntry::new(prop))
+            if let Some(index) = self.path_elem {
+                let vertex = entry
+                    .as_graph_path()
+                    .and_then(|path| path.get_vertex(index));
+                match (vertex, self.key.as_ref()) {
+                    (Some(vertex), Some(prop_key)) => Ok(DynEntry::new(prop_key.get_key(vertex)?)),
+                    (Some(vertex), None) => Ok(DynEntry::new(vertex.clone())),
+                    (None, _) => Ok(DynEntry::new(Object::None)),
+                }
+            } else if let Some(prop_key) = self.key.as_ref() {
+                if entry.is_none() {
+                    // the property of a null entry is null, e.g., the entry padded by an outer join
+                    Ok(DynEntry::new(Object::None))
+                } else {
+                    let prop = prop_key.get_key(entry)?;
+                    Ok(DynEntry::new(prop))
+                }
             } else {
                 Ok(entry.clone())
             }
@@ -66,8 +82,18 @@ impl TryFrom<common_pb::Variable> for TagKey {
 
     fn try_from(v: common_pb::Variable) -> Result<Self, Self::Error> {
         let tag = if let Some(tag) = v.tag { Some(KeyId::try_from(tag)?) } else { None };
-        let prop = if let Some(prop) = v.property { Some(PropKey::try_from(prop)?) } else { None };
-        Ok(TagKey { tag, key: prop })
+        match v.property {
+            Some(common_pb::Property { item: Some(common_pb::property::Item::PathElem(path_elem)) }) => {
+                let key = if let Some(prop) = path_elem.property {
+                    Some(PropKey::try_from(*prop)?)
+                } else {
+                    None
+                };
+                Ok(TagKey { tag, path_elem: Some(path_elem.index), key })
+            }
+            Some(prop) => Ok(TagKey { tag, path_elem: None, key: Some(PropKey::try_from(prop)?) }),
+            None => Ok(TagKey { tag, path_elem: None, key: None }),
+        }
     }
 }
 
@@ -91,6 +117,12 @@ impl Encode for TagKey {
                 writer.write_u8(3)?;
             }
         }
+        if let Some(index) = self.path_elem {
+            writer.write_u8(1)?;
+            writer.write_i32(index)?;
+        } else {
+            writer.write_u8(0)?;
+        }
         Ok(())
     }
 }
@@ -98,23 +130,25 @@ impl Encode for TagKey {
 impl Decode for TagKey {
     fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
         let opt = reader.read_u8()?;
-        match opt {
+        let (tag, key) = match opt {
             0 => {
                 let tag = <KeyId>::read_from(reader)?;
                 let key = <PropKey>::read_from(reader)?;
-                Ok(TagKey { tag: Some(tag), key: Some(key) })
+                (Some(tag), Some(key))
             }
             1 => {
                 let tag = <KeyId>::read_from(reader)?;
-                Ok(TagKey { tag: Some(tag), key: None })
+                (Some(tag), None)
             }
             2 => {
                 let key = <PropKey>::read_from(reader)?;
-                Ok(TagKey { tag: None, key: Some(key) })
+                (None, Some(key))
             }
-            3 => Ok(TagKey { tag: None, key: None }),
-            _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "unreachable")),
-        }
+            3 => (None, None),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::Other, "unreachable"))?,
+        };
+        let path_elem = if reader.read_u8()? == 0 { None } else { Some(reader.read_i32()?) };
+        Ok(TagKey { tag, path_elem, key })
     }
 }
 
@@ -122,7 +156,8 @@ impl Decode for TagKey {
 pub(crate) mod tests {
     use ahash::HashMap;
     use dyn_type::Object;
-    use graph_proxy::apis::{DynDetails, GraphElement, Vertex};
+    use graph_proxy::apis::{DynDetails, GraphElement, GraphPath, Vertex};
+    use ir_common::generated::physical as physical_pb;
     use ir_common::{KeyId, LabelId, NameOrId};
 
     use super::*;
@@ -193,6 +228,28 @@ pub(crate) mod tests {
         vec![r1]
     }
 
+    // paths tagged as 'a', i.e., [v1, v2] of 1 hop, [v1, v2, v1] and [v2, v1, v2] of 2 hops,
+    // and [v2, v1, v2, v1] of 3 hops, where v1 is marko and v2 is vadas
+    pub fn init_path_source_with_tag() -> Vec<Record> {
+        let v1 = init_vertex1();
+        let v2 = init_vertex2();
+        vec![vec![&v1, &v2], vec![&v1, &v2, &v1], vec![&v2, &v1, &v2], vec![&v2, &v1, &v2, &v1]]
+            .into_iter()
+            .map(|vertices| {
+                let mut path = GraphPath::new(
+                    vertices[0].clone(),
+                    physical_pb::path_expand::PathOpt::Arbitrary,
+                    physical_pb::path_expand::ResultOpt::AllV,
+                )
+                .unwrap();
+                for vertex in &vertices[1..] {
+                    path.append((*vertex).clone());
+                }
+                Record::new(path, Some(TAG_A.into()))
+            })
+            .collect()
+    }
+
     pub fn to_prop_pb(key: NameOrId) -> common_pb::Property {
         common_pb::Property { item: Some(common_pb::property::Item::Key(key.into())) }
     }
@@ -254,7 +311,7 @@ pub(crate) mod tests {
     #[test]
     // None tag refers to the last appended entry;
     fn test_get_none_tag_entry() {
-        let tag_key = TagKey { tag: None, key: None };
+        let tag_key = TagKey { tag: None, path_elem: None, key: None };
         let record = init_record();
         let expected = object!(10);
         let entry = tag_key.get_arc_entry(&record).unwrap();
@@ -263,7 +320,7 @@ pub(crate) mod tests {
 
     #[test]
     fn test_get_tag_entry() {
-        let tag_key = TagKey { tag: Some((0 as KeyId).into()), key: None };
+        let tag_key = TagKey { tag: Some((0 as KeyId).into()), path_elem: None, key: None };
         let expected = init_vertex2();
         let record = init_record();
         let entry = tag_key.get_arc_entry(&record).unwrap();
@@ -276,7 +333,11 @@ pub(crate) mod tests {
 
     #[test]
     fn test_get_tag_key_entry() {
-        let tag_key = TagKey { tag: Some((0 as KeyId).into()), key: Some(PropKey::Key("age".into())) };
+        let tag_key = TagKey {
+            tag: Some((0 as KeyId).into()),
+            path_elem: None,
+            key: Some(PropKey::Key("age".into())),
+        };
         let expected = 27;
         let record = init_record();
         let entry = tag_key
@@ -288,4 +349,46 @@ pub(crate) mod tests {
 
         assert_eq!(entry, object!(expected));
     }
+
+    #[test]
+    fn test_get_path_elem_entry() {
+        let record = init_path_source_with_tag().pop().unwrap();
+        let get_key = |var: &str| {
+            TagKey::try_from(common_pb::Variable::from(var.to_string()))
+                .unwrap()
+                .get_arc_entry(&record)
+                .unwrap()
+        };
+        assert_eq!(get_key("@0.~len").as_object().unwrap().clone(), object!(3usize));
+        assert_eq!(get_key("@0.~[0]").as_vertex().unwrap().id(), 2);
+        assert_eq!(get_key("@0.~[-1]").as_vertex().unwrap().id(), 1);
+        assert_eq!(
+            get_key("@0.~[1].name")
+                .as_object()
+                .unwrap()
+                .clone(),
+            object!("marko")
+        );
+        assert_eq!(
+            get_key("@0.~[-2].~id")
+                .as_object()
+                .unwrap()
+                .clone(),
+            object!(2)
+        );
+        assert_eq!(
+            get_key("@0.~[4].name")
+                .as_object()
+                .unwrap()
+                .clone(),
+            Object::None
+        );
+        assert_eq!(
+            get_key("@0.~[-5].name")
+                .as_object()
+                .unwrap()
+                .clone(),
+            Object::None
+        );
+    }
 }
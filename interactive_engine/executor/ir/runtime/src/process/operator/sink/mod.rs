@@ -13,6 +13,9 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 mod sink;
+#[cfg(feature = "arrow_sink")]
+mod sink_arrow;
+mod sink_target;
 #[cfg(feature = "with_v6d")]
 mod sink_vineyard;
 
@@ -21,16 +24,26 @@ use std::convert::TryInto;
 use ir_common::error::ParsePbError;
 use ir_common::generated::algebra as algebra_pb;
 use ir_common::generated::physical as pb;
+use ir_common::KeyId;
 
-use crate::error::FnGenResult;
+use crate::error::{FnGenError, FnGenResult};
 use crate::process::operator::sink::sink::{DefaultSinkOp, RecordSinkEncoder};
+#[cfg(feature = "arrow_sink")]
+use crate::process::operator::sink::sink_arrow::{ArrowSinkEncoder, SinkArrowOp};
+pub use crate::process::operator::sink::sink_target::{
+    set_csv_sink_dir, ClientSinkTarget, CsvSinkTarget, MultiTargetSinker, SinkCountsEncoder, SinkTarget,
+    CSV_PATH_KEY,
+};
 #[cfg(feature = "with_v6d")]
 use crate::process::operator::sink::sink_vineyard::{GraphSinkEncoder, SinkVineyardOp};
 
 pub enum Sinker {
     DefaultSinker(RecordSinkEncoder),
+    MultiTargetSinker(MultiTargetSinker),
     #[cfg(feature = "with_v6d")]
     GraphSinker(GraphSinkEncoder),
+    #[cfg(feature = "arrow_sink")]
+    ArrowSinker(ArrowSinkEncoder),
 }
 
 pub trait SinkGen {
@@ -49,6 +62,15 @@ impl SinkGen for pb::PhysicalOpr {
 
 impl SinkGen for pb::Sink {
     fn gen_sink(self) -> FnGenResult<Sinker> {
+        let is_csv_target = matches!(
+            self.sink_target,
+            Some(algebra_pb::sink::SinkTarget {
+                inner: Some(algebra_pb::sink::sink_target::Inner::SinkCsv(_))
+            })
+        );
+        if !self.tagged_targets.is_empty() || is_csv_target {
+            return gen_multi_target_sink(self);
+        }
         if let Some(sink_target) = self.sink_target {
             let inner = sink_target
                 .inner
@@ -64,6 +86,8 @@ impl SinkGen for pb::Sink {
                         DefaultSinkOp { tags, id_name_mappings: sink_default.id_name_mappings };
                     default_sink_op.gen_sink()
                 }
+                // which has been generated as a sinker of multiple targets
+                algebra_pb::sink::sink_target::Inner::SinkCsv(_) => unreachable!(),
                 algebra_pb::sink::sink_target::Inner::SinkVineyard(_sink_vineyard) => {
                     #[cfg(feature = "with_v6d")]
                     {
@@ -71,12 +95,29 @@ impl SinkGen for pb::Sink {
                             tags,
                             graph_name: _sink_vineyard.graph_name,
                             graph_schema: _sink_vineyard.graph_schema,
+                            extra_params: _sink_vineyard.extra,
                         };
                         sink_vineyard_op.gen_sink()
                     }
                     #[cfg(not(feature = "with_v6d"))]
-                    Err(graph_proxy::GraphProxyError::UnSupported(
-                                    "sink_target of Vineyard is not as a feature. Try \'cargo build --features with_v6d\'".to_string()))?
+                    Err(graph_proxy::GraphProxyError::unsupported_error(
+                                    "sink_target of Vineyard is not as a feature. Try \'cargo build --features with_v6d\'"))?
+                }
+                algebra_pb::sink::sink_target::Inner::SinkArrow(_sink_arrow) => {
+                    #[cfg(feature = "arrow_sink")]
+                    {
+                        let sink_arrow_op = SinkArrowOp {
+                            tags,
+                            batch_size: _sink_arrow.batch_size,
+                            id_name_mappings: _sink_arrow.id_name_mappings,
+                            column_types: _sink_arrow.column_types,
+                        };
+                        sink_arrow_op.gen_sink()
+                    }
+                    #[cfg(not(feature = "arrow_sink"))]
+                    Err(FnGenError::unsupported_error(
+                        "sink_target of Arrow is not as a feature. Try \'cargo build --features arrow_sink\'",
+                    ))
                 }
             }
         } else {
@@ -84,3 +125,66 @@ impl SinkGen for pb::Sink {
         }
     }
 }
+
+fn to_sink_tags(tags: Vec<pb::sink::OptTag>) -> Vec<Option<KeyId>> {
+    tags.into_iter().map(|tag| tag.tag).collect()
+}
+
+fn gen_sink_target(
+    tags: Vec<Option<KeyId>>, sink_target: Option<algebra_pb::sink::SinkTarget>,
+) -> FnGenResult<Box<dyn SinkTarget>> {
+    let inner = sink_target
+        .ok_or_else(|| ParsePbError::EmptyFieldError("sink_target is missing".to_string()))?
+        .inner
+        .ok_or_else(|| ParsePbError::EmptyFieldError("sink_target inner is missing".to_string()))?;
+    match inner {
+        algebra_pb::sink::sink_target::Inner::SinkDefault(sink_default) => {
+            let encoder =
+                DefaultSinkOp { tags, id_name_mappings: sink_default.id_name_mappings }.into_encoder();
+            Ok(Box::new(ClientSinkTarget::new(encoder)))
+        }
+        algebra_pb::sink::sink_target::Inner::SinkCsv(sink_csv) => {
+            let path = sink_csv
+                .extra
+                .get(CSV_PATH_KEY)
+                .ok_or_else(|| ParsePbError::EmptyFieldError("path of SinkCsv is missing".to_string()))?;
+            Ok(Box::new(CsvSinkTarget::open(path.clone(), tags)?))
+        }
+        algebra_pb::sink::sink_target::Inner::SinkVineyard(_) => {
+            Err(FnGenError::unsupported_error("sink into Vineyard along with other targets"))
+        }
+        algebra_pb::sink::sink_target::Inner::SinkArrow(_) => {
+            Err(FnGenError::unsupported_error("sink into Arrow along with other targets"))
+        }
+    }
+}
+
+/// Generate the sinker of multiple targets, where at most one of them is the client, as each
+/// record is responded to the client at most once.
+fn gen_multi_target_sink(sink: pb::Sink) -> FnGenResult<Sinker> {
+    let tagged_targets = sink
+        .tagged_targets
+        .into_iter()
+        .map(|target| (target.tags, target.sink_target));
+    let mut targets = vec![];
+    let mut has_client = false;
+    for (tags, sink_target) in std::iter::once((sink.tags, sink.sink_target)).chain(tagged_targets) {
+        if matches!(
+            sink_target,
+            Some(algebra_pb::sink::SinkTarget {
+                inner: Some(algebra_pb::sink::sink_target::Inner::SinkDefault(_))
+            })
+        ) {
+            if has_client {
+                Err(FnGenError::unsupported_error("sink into the client more than once"))?
+            }
+            has_client = true;
+        }
+        targets.push(gen_sink_target(to_sink_tags(tags), sink_target)?);
+    }
+    let sinker = MultiTargetSinker::new(targets, has_client);
+    if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
+        debug!("Runtime sink operator: {:?}", sinker);
+    }
+    Ok(Sinker::MultiTargetSinker(sinker))
+}
@@ -15,6 +15,7 @@
 
 use std::borrow::BorrowMut;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
 use dyn_type::Object;
 use graph_proxy::apis::VertexOrEdge;
@@ -42,8 +43,12 @@ pub struct RecordSinkEncoder {
     schema_map: Option<HashMap<(MetaType, i32), String>>,
 }
 
+fn object_to_value_pb(value: Object) -> FnExecResult<common_pb::Value> {
+    common_pb::Value::try_from(value).map_err(|e| FnExecError::unexpected_data_error(&e.to_string()))
+}
+
 impl RecordSinkEncoder {
-    fn entry_to_pb(&self, e: &DynEntry) -> FnExecResult<result_pb::Entry> {
+    pub(crate) fn entry_to_pb(&self, e: &DynEntry) -> FnExecResult<result_pb::Entry> {
         let inner = match e.get_type() {
             EntryType::Collection => {
                 let collection = e
@@ -68,7 +73,7 @@ impl RecordSinkEncoder {
                 } else {
                     // convert to a collection result
                     for element in &collection.inner {
-                        let element_pb = self.element_to_pb(element);
+                        let element_pb = self.element_to_pb(element)?;
                         collection_pb.push(element_pb);
                     }
                     Some(result_pb::entry::Inner::Collection(result_pb::Collection {
@@ -113,12 +118,12 @@ impl RecordSinkEncoder {
                 }
             }
             _ => {
-                if let Some(map_pb) = self.try_map_to_pb(e) {
+                if let Some(map_pb) = self.try_map_to_pb(e)? {
                     Some(result_pb::entry::Inner::Map(map_pb))
-                } else if let Some(collection_pb) = self.try_collection_to_pb(e) {
+                } else if let Some(collection_pb) = self.try_collection_to_pb(e)? {
                     Some(result_pb::entry::Inner::Collection(collection_pb))
                 } else {
-                    let element_pb = self.element_to_pb(e);
+                    let element_pb = self.element_to_pb(e)?;
                     Some(result_pb::entry::Inner::Element(element_pb))
                 }
             }
@@ -127,37 +132,37 @@ impl RecordSinkEncoder {
     }
 
     // return if the given entry is a collection entry result from PathValueProjector eval, etc.
-    fn try_collection_to_pb(&self, e: &DynEntry) -> Option<result_pb::Collection> {
+    fn try_collection_to_pb(&self, e: &DynEntry) -> FnExecResult<Option<result_pb::Collection>> {
         if let EntryType::Object = e.get_type() {
             if let Object::Vector(vec) = e.as_object().unwrap() {
                 let mut collection_pb = Vec::with_capacity(vec.len());
                 for obj in vec {
-                    let obj_pb = self.object_to_pb(obj.clone());
+                    let obj_pb = self.object_to_pb(obj.clone())?;
                     let element_pb =
                         result_pb::Element { inner: Some(result_pb::element::Inner::Object(obj_pb)) };
                     collection_pb.push(element_pb);
                 }
-                return Some(result_pb::Collection { collection: collection_pb });
+                return Ok(Some(result_pb::Collection { collection: collection_pb }));
             }
         }
 
-        None
+        Ok(None)
     }
 
     // return if the given entry is a map entry result from Map eval.
-    fn try_map_to_pb(&self, e: &DynEntry) -> Option<result_pb::KeyValues> {
+    fn try_map_to_pb(&self, e: &DynEntry) -> FnExecResult<Option<result_pb::KeyValues>> {
         if let EntryType::Object = e.get_type() {
             if let Object::KV(kv) = e.as_object().unwrap() {
                 let mut key_values: Vec<result_pb::key_values::KeyValue> = Vec::with_capacity(kv.len());
                 if let Some(probe) = kv.iter().next() {
                     if let Object::Vector(_) = probe.0 {
                         // the value computed by VarMap.eval(), which will return an Element result. This will be deprecated soon.
-                        return None;
+                        return Ok(None);
                     }
                 }
                 for (key, val) in kv {
-                    let key_pb: common_pb::Value = key.clone().into();
-                    let val_pb: common_pb::Value = val.clone().into();
+                    let key_pb = object_to_value_pb(key.clone())?;
+                    let val_pb = object_to_value_pb(val.clone())?;
                     key_values.push(result_pb::key_values::KeyValue {
                         key: Some(key_pb),
                         value: Some(result_pb::Entry {
@@ -167,11 +172,11 @@ impl RecordSinkEncoder {
                         }),
                     })
                 }
-                return Some(result_pb::KeyValues { key_values });
+                return Ok(Some(result_pb::KeyValues { key_values }));
             }
         }
 
-        None
+        Ok(None)
     }
 
     fn collection_map_to_pb(&self, e: CollectionEntry) -> FnExecResult<result_pb::KeyValues> {
@@ -182,7 +187,7 @@ impl RecordSinkEncoder {
                 .downcast_ref::<PairEntry>()
                 .unwrap();
             if let Some(key_obj) = pair.get_left().as_object() {
-                let key_pb: common_pb::Value = key_obj.clone().into();
+                let key_pb = object_to_value_pb(key_obj.clone())?;
                 let val = pair.get_right();
                 if val.get_type() == EntryType::Collection {
                     let inner_collection = val
@@ -198,7 +203,7 @@ impl RecordSinkEncoder {
                     })
                 } else {
                     let right = pair.get_right();
-                    if let Some(collection) = self.try_collection_to_pb(right) {
+                    if let Some(collection) = self.try_collection_to_pb(right)? {
                         key_values.push(result_pb::key_values::KeyValue {
                             key: Some(key_pb),
                             value: Some(result_pb::Entry {
@@ -206,7 +211,7 @@ impl RecordSinkEncoder {
                             }),
                         });
                     } else {
-                        let val_pb = self.element_to_pb(right);
+                        let val_pb = self.element_to_pb(right)?;
                         key_values.push(result_pb::key_values::KeyValue {
                             key: Some(key_pb),
                             value: Some(result_pb::Entry {
@@ -225,7 +230,7 @@ impl RecordSinkEncoder {
         Ok(result_pb::KeyValues { key_values })
     }
 
-    fn element_to_pb(&self, e: &DynEntry) -> result_pb::Element {
+    fn element_to_pb(&self, e: &DynEntry) -> FnExecResult<result_pb::Element> {
         let inner = match e.get_type() {
             EntryType::Vertex => {
                 let vertex_pb = self.vertex_to_pb(e.as_vertex().unwrap());
@@ -240,7 +245,7 @@ impl RecordSinkEncoder {
                 Some(result_pb::element::Inner::GraphPath(path_pb))
             }
             EntryType::Object => {
-                let obj_pb = self.object_to_pb(e.as_object().unwrap().clone());
+                let obj_pb = self.object_to_pb(e.as_object().unwrap().clone())?;
                 Some(result_pb::element::Inner::Object(obj_pb))
             }
             EntryType::Collection => {
@@ -252,12 +257,12 @@ impl RecordSinkEncoder {
             EntryType::Pair => {
                 unreachable!()
             }
-            EntryType::Null => Some(result_pb::element::Inner::Object(Object::None.into())),
+            EntryType::Null => Some(result_pb::element::Inner::Object(object_to_value_pb(Object::None)?)),
         };
-        result_pb::Element { inner }
+        Ok(result_pb::Element { inner })
     }
 
-    fn object_to_pb(&self, value: Object) -> common_pb::Value {
+    fn object_to_pb(&self, value: Object) -> FnExecResult<common_pb::Value> {
         if let Object::KV(kv) = value {
             let mut pairs: Vec<common_pb::Pair> = Vec::with_capacity(kv.len());
             for (mut key, val) in kv {
@@ -272,14 +277,14 @@ impl RecordSinkEncoder {
                         }
                     }
                 }
-                let key_pb: common_pb::Value = key.into();
-                let val_pb: common_pb::Value = val.into();
+                let key_pb = object_to_value_pb(key)?;
+                let val_pb = object_to_value_pb(val)?;
                 pairs.push(common_pb::Pair { key: Some(key_pb), val: Some(val_pb) })
             }
             let item = common_pb::value::Item::PairArray(common_pb::PairArray { item: pairs });
-            common_pb::Value { item: Some(item) }
+            Ok(common_pb::Value { item: Some(item) })
         } else {
-            common_pb::Value::from(value)
+            object_to_value_pb(value)
         }
     }
 
@@ -371,8 +376,9 @@ impl RecordSinkEncoder {
     }
 }
 
-impl MapFunction<Record, Vec<u8>> for RecordSinkEncoder {
-    fn exec(&self, mut input: Record) -> FnResult<Vec<u8>> {
+impl RecordSinkEncoder {
+    /// Encode the columns to sink in the record into the results sent back to the client
+    pub(crate) fn encode(&self, input: &mut Record) -> FnExecResult<Vec<u8>> {
         let mut sink_columns = Vec::with_capacity(self.sink_keys.len());
         if self.sink_keys.is_empty() {
             // the case of sink all **tagged** columns by default.
@@ -407,22 +413,34 @@ impl MapFunction<Record, Vec<u8>> for RecordSinkEncoder {
     }
 }
 
+impl MapFunction<Record, Vec<u8>> for RecordSinkEncoder {
+    fn exec(&self, mut input: Record) -> FnResult<Vec<u8>> {
+        Ok(self.encode(&mut input)?)
+    }
+}
+
 pub struct DefaultSinkOp {
     pub tags: Vec<Option<KeyId>>,
     pub id_name_mappings: Vec<algebra_pb::sink_default::IdNameMapping>,
 }
 
-impl SinkGen for DefaultSinkOp {
-    fn gen_sink(self) -> FnGenResult<Sinker> {
+impl DefaultSinkOp {
+    pub(crate) fn into_encoder(self) -> RecordSinkEncoder {
         let mut schema_map = HashMap::new();
         for id_name_mappings_pb in self.id_name_mappings {
             let meta_type = unsafe { ::std::mem::transmute(id_name_mappings_pb.meta_type) };
             schema_map.insert((meta_type, id_name_mappings_pb.id), id_name_mappings_pb.name);
         }
-        let record_sinker = RecordSinkEncoder {
+        RecordSinkEncoder {
             sink_keys: self.tags,
             schema_map: if schema_map.is_empty() { None } else { Some(schema_map) },
-        };
+        }
+    }
+}
+
+impl SinkGen for DefaultSinkOp {
+    fn gen_sink(self) -> FnGenResult<Sinker> {
+        let record_sinker = self.into_encoder();
         if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
             debug!("Runtime sink operator: {:?}", record_sinker);
         }
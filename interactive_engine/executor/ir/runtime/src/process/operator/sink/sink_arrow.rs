@@ -0,0 +1,365 @@
+//
+//! Copyright 2024 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BinaryBuilder, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder,
+    TimestampMillisecondBuilder,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::error::ArrowError;
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use dyn_type::{CastError, Object, Primitives};
+use ir_common::error::ParsePbError;
+use ir_common::generated::algebra as algebra_pb;
+use ir_common::generated::common as common_pb;
+use ir_common::KeyId;
+use pegasus::api::function::FnResult;
+use pegasus::api::Unary;
+use pegasus::stream::Stream;
+use pegasus::tag::tools::map::TidyTagMap;
+use pegasus::BuildJobError;
+use prost::Message;
+
+use crate::error::{FnExecError, FnExecResult, FnGenResult};
+use crate::process::entry::{DynEntry, Entry, EntryType};
+use crate::process::operator::sink::sink::{DefaultSinkOp, RecordSinkEncoder};
+use crate::process::operator::sink::{SinkGen, Sinker};
+use crate::process::record::Record;
+
+/// The max number of records in a batch by default
+const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// The arrow type of a column, which is given by the plan. A column of no given type, or of the
+/// types other than the primitives, strings and datetimes, is `Encoded`, i.e., sinked in the
+/// protobuf encoding of the default sink for each value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColumnType {
+    Int64,
+    Float64,
+    Utf8,
+    Boolean,
+    Timestamp,
+    Encoded,
+}
+
+impl ColumnType {
+    fn from_pb(ir_type: &common_pb::IrDataType) -> ColumnType {
+        use common_pb::data_type::Item;
+        use common_pb::temporal::Item as TemporalItem;
+        use common_pb::PrimitiveType;
+
+        let data_type = match ir_type.r#type.as_ref() {
+            Some(common_pb::ir_data_type::Type::DataType(data_type)) => data_type,
+            _ => return ColumnType::Encoded,
+        };
+        match data_type.item.as_ref() {
+            Some(Item::PrimitiveType(t)) => match PrimitiveType::from_i32(*t) {
+                Some(PrimitiveType::DtSignedInt32)
+                | Some(PrimitiveType::DtUnsignedInt32)
+                | Some(PrimitiveType::DtSignedInt64) => ColumnType::Int64,
+                Some(PrimitiveType::DtFloat) | Some(PrimitiveType::DtDouble) => ColumnType::Float64,
+                Some(PrimitiveType::DtBool) => ColumnType::Boolean,
+                _ => ColumnType::Encoded,
+            },
+            Some(Item::String(_)) => ColumnType::Utf8,
+            Some(Item::Temporal(temporal)) => match temporal.item {
+                Some(TemporalItem::Date(_))
+                | Some(TemporalItem::DateTime(_))
+                | Some(TemporalItem::Date32(_))
+                | Some(TemporalItem::Timestamp(_)) => ColumnType::Timestamp,
+                _ => ColumnType::Encoded,
+            },
+            _ => ColumnType::Encoded,
+        }
+    }
+
+    /// Whether the value of a non-null entry can be sinked into a column of this type.
+    fn accepts(&self, entry: &DynEntry) -> bool {
+        match (self, entry.as_object()) {
+            (ColumnType::Encoded, _) => true,
+            // the booleans are represented as bytes in `Object`, which are sinked as booleans
+            // only if the plan says so
+            (ColumnType::Int64, Some(Object::Primitive(Primitives::Byte(_))))
+            | (ColumnType::Int64, Some(Object::Primitive(Primitives::Integer(_))))
+            | (ColumnType::Int64, Some(Object::Primitive(Primitives::UInteger(_))))
+            | (ColumnType::Int64, Some(Object::Primitive(Primitives::Long(_))))
+            | (ColumnType::Boolean, Some(Object::Primitive(Primitives::Byte(_))))
+            | (ColumnType::Float64, Some(Object::Primitive(Primitives::Float(_))))
+            | (ColumnType::Float64, Some(Object::Primitive(Primitives::Double(_))))
+            | (ColumnType::Utf8, Some(Object::String(_))) => true,
+            (ColumnType::Timestamp, Some(Object::DateFormat(date))) => date.timestamp_millis().is_some(),
+            _ => false,
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Utf8 => DataType::Utf8,
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Millisecond, None),
+            ColumnType::Encoded => DataType::Binary,
+        }
+    }
+}
+
+/// Get the non-null value of an entry, where a missing column, or a column of `None`, is a null.
+fn get_value(entry: Option<&DynEntry>) -> Option<&DynEntry> {
+    entry.filter(|entry| match entry.get_type() {
+        EntryType::Null => false,
+        EntryType::Object => !matches!(entry.as_object(), Some(Object::None)),
+        _ => true,
+    })
+}
+
+/// Sink the columns of the given tags as Arrow record batches of at most `batch_size` records,
+/// each of which is serialized as a standalone Arrow IPC stream, and sent back to the client as
+/// soon as it is full. A missing column of a record, or a column of `None`, is sinked as a null.
+///
+/// The schema of the batches follows the column types given by the plan, except that a column of
+/// a batch holding any value of another type falls back to `Encoded` in this batch.
+#[derive(Clone, Debug)]
+pub struct ArrowSinkEncoder {
+    sink_keys: Vec<Option<KeyId>>,
+    column_names: Vec<String>,
+    column_types: Vec<ColumnType>,
+    batch_size: usize,
+    /// To encode the values of the `Encoded` columns
+    encoder: Arc<RecordSinkEncoder>,
+    rows: Vec<Vec<Option<DynEntry>>>,
+}
+
+impl ArrowSinkEncoder {
+    fn build_array(&self, i: usize, column_type: ColumnType) -> FnExecResult<ArrayRef> {
+        let values = self
+            .rows
+            .iter()
+            .map(|row| get_value(row[i].as_ref()));
+        let array: ArrayRef = match column_type {
+            ColumnType::Int64 => {
+                let mut builder = Int64Builder::with_capacity(self.rows.len());
+                for value in values {
+                    match value.and_then(|entry| entry.as_object()) {
+                        Some(obj) => builder.append_value(obj.as_i64().map_err(cast_error)?),
+                        None => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Float64 => {
+                let mut builder = Float64Builder::with_capacity(self.rows.len());
+                for value in values {
+                    match value.and_then(|entry| entry.as_object()) {
+                        Some(obj) => builder.append_value(obj.as_f64().map_err(cast_error)?),
+                        None => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Utf8 => {
+                let mut builder = StringBuilder::new();
+                for value in values {
+                    match value.and_then(|entry| entry.as_object()) {
+                        Some(obj) => builder.append_value(obj.as_str().map_err(cast_error)?),
+                        None => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Boolean => {
+                let mut builder = BooleanBuilder::with_capacity(self.rows.len());
+                for value in values {
+                    match value.and_then(|entry| entry.as_object()) {
+                        Some(obj) => builder.append_value(obj.as_bool().map_err(cast_error)?),
+                        None => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Timestamp => {
+                let mut builder = TimestampMillisecondBuilder::with_capacity(self.rows.len());
+                for value in values {
+                    match value.and_then(|entry| entry.as_object()) {
+                        Some(Object::DateFormat(date)) => builder.append_option(date.timestamp_millis()),
+                        _ => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+            ColumnType::Encoded => {
+                let mut builder = BinaryBuilder::new();
+                for value in values {
+                    match value {
+                        Some(entry) => {
+                            builder.append_value(self.encoder.entry_to_pb(entry)?.encode_to_vec())
+                        }
+                        None => builder.append_null(),
+                    }
+                }
+                Arc::new(builder.finish())
+            }
+        };
+        Ok(array)
+    }
+
+    /// Serialize the buffered records as a record batch.
+    fn flush(&mut self) -> FnExecResult<Vec<u8>> {
+        let mut fields = Vec::with_capacity(self.column_types.len());
+        let mut arrays = Vec::with_capacity(self.column_types.len());
+        for (i, column_type) in self.column_types.iter().enumerate() {
+            let column_type = if self
+                .rows
+                .iter()
+                .filter_map(|row| get_value(row[i].as_ref()))
+                .all(|entry| column_type.accepts(entry))
+            {
+                *column_type
+            } else {
+                ColumnType::Encoded
+            };
+            fields.push(Field::new(&self.column_names[i], column_type.data_type(), true));
+            arrays.push(self.build_array(i, column_type)?);
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(arrow_error)?;
+        let mut writer = StreamWriter::try_new(vec![], &schema).map_err(arrow_error)?;
+        writer.write(&batch).map_err(arrow_error)?;
+        writer.finish().map_err(arrow_error)?;
+        self.rows.clear();
+        writer.into_inner().map_err(arrow_error)
+    }
+
+    /// Buffer a record, and return the serialized batch if the batch is full.
+    fn accum(&mut self, next: Record) -> FnResult<Option<Vec<u8>>> {
+        let row = self
+            .sink_keys
+            .iter()
+            .map(|sink_key| next.get(sink_key.clone()).cloned())
+            .collect();
+        self.rows.push(row);
+        if self.rows.len() >= self.batch_size {
+            Ok(Some(self.flush()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn finalize(mut self) -> FnResult<Option<Vec<u8>>> {
+        if self.rows.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(self.flush()?))
+        }
+    }
+
+    /// Install the encoder to sink the stream, which sends out each batch as soon as it is full,
+    /// and the last batch of a scope at its end.
+    pub fn install(self, stream: Stream<Record>) -> Result<Stream<Vec<u8>>, BuildJobError> {
+        stream.unary("sink_arrow", move |info| {
+            let mut table = TidyTagMap::<ArrowSinkEncoder>::new(info.scope_level);
+            move |input, output| {
+                input.for_each_batch(|batch| {
+                    if !batch.is_empty() {
+                        let mut session = output.new_session(&batch.tag)?;
+                        let encoder = table.get_mut_or_else(&batch.tag, || self.clone());
+                        for record in batch.drain() {
+                            if let Some(bytes) = encoder.accum(record)? {
+                                session.give(bytes)?;
+                            }
+                        }
+                    }
+                    if batch.is_last() {
+                        if let Some(encoder) = table.remove(&batch.tag) {
+                            if let Some(bytes) = encoder.finalize()? {
+                                let mut session = output.new_session(&batch.tag)?;
+                                session.give(bytes)?;
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
+
+fn cast_error(e: CastError) -> FnExecError {
+    FnExecError::unexpected_data_error(&format!("{:?}", e))
+}
+
+fn arrow_error(e: ArrowError) -> FnExecError {
+    FnExecError::unexpected_data_error(&format!("encode arrow record batch error: {}", e))
+}
+
+pub struct SinkArrowOp {
+    pub tags: Vec<Option<KeyId>>,
+    pub batch_size: i32,
+    pub id_name_mappings: Vec<algebra_pb::sink_default::IdNameMapping>,
+    pub column_types: Vec<common_pb::IrDataType>,
+}
+
+impl SinkGen for SinkArrowOp {
+    fn gen_sink(self) -> FnGenResult<Sinker> {
+        if self.tags.is_empty() {
+            Err(ParsePbError::EmptyFieldError("tags of SinkArrow".to_string()))?
+        }
+        let column_types = if self.column_types.is_empty() {
+            vec![ColumnType::Encoded; self.tags.len()]
+        } else if self.column_types.len() == self.tags.len() {
+            self.column_types
+                .iter()
+                .map(ColumnType::from_pb)
+                .collect()
+        } else {
+            Err(ParsePbError::ParseError(format!(
+                "{} column types of SinkArrow for {} tags",
+                self.column_types.len(),
+                self.tags.len()
+            )))?
+        };
+        let column_names = self
+            .tags
+            .iter()
+            .map(|tag| match tag {
+                Some(tag) => self
+                    .id_name_mappings
+                    .iter()
+                    .find(|mapping| mapping.id == *tag)
+                    .map(|mapping| mapping.name.clone())
+                    .unwrap_or_else(|| tag.to_string()),
+                None => "head".to_string(),
+            })
+            .collect();
+        let batch_size = if self.batch_size > 0 { self.batch_size as usize } else { DEFAULT_BATCH_SIZE };
+        let encoder = DefaultSinkOp { tags: self.tags.clone(), id_name_mappings: self.id_name_mappings }
+            .into_encoder();
+        let arrow_sink_encoder = ArrowSinkEncoder {
+            sink_keys: self.tags,
+            column_names,
+            column_types,
+            batch_size,
+            encoder: Arc::new(encoder),
+            rows: vec![],
+        };
+        if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
+            debug!("Runtime sink arrow operator: {:?}", arrow_sink_encoder);
+        }
+        Ok(Sinker::ArrowSinker(arrow_sink_encoder))
+    }
+}
@@ -0,0 +1,276 @@
+//
+//! Copyright 2021 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use dyn_type::Object;
+use graph_proxy::apis::GraphElement;
+use ir_common::error::ParsePbError;
+use ir_common::KeyId;
+use lazy_static::lazy_static;
+use pegasus::api::function::{DynError, FilterMapFunction, FnResult, MapFunction};
+
+use crate::error::{FnExecError, FnGenError, FnGenResult};
+use crate::process::entry::{DynEntry, Entry};
+use crate::process::operator::sink::sink::{DefaultSinkOp, RecordSinkEncoder};
+use crate::process::record::Record;
+
+/// The key in the extra parameters of `SinkCsv` that specifies the path of the csv file,
+/// relative to the directory configured by `set_csv_sink_dir`
+pub const CSV_PATH_KEY: &str = "path";
+
+lazy_static! {
+    static ref CSV_SINK_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+    /// The csv files being opened by the workers of the running jobs, indexed by the job id and the path,
+    /// along with the workers that have opened the file. The file is created, or truncated if it exists,
+    /// by the first of the workers, and is shared with the rest of them.
+    static ref JOB_CSV_FILES: Mutex<HashMap<(u64, PathBuf), (Arc<File>, HashSet<u32>)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Set the directory in which the csv files are sinked into. Sinking into csv files is not
+/// allowed until the directory is set.
+pub fn set_csv_sink_dir<P: AsRef<Path>>(dir: P) {
+    *CSV_SINK_DIR.write().expect("lock poisoned") = Some(dir.as_ref().to_path_buf());
+}
+
+/// A target into which the records are sinked, e.g., the client, or a csv file.
+pub trait SinkTarget: Debug + Send + Sync {
+    /// Sink the record into the target, and return the results to be sent back to the client if any.
+    fn sink(&self, record: &mut Record) -> FnResult<Option<Vec<u8>>>;
+}
+
+/// Sink the records back to the client.
+pub struct ClientSinkTarget {
+    encoder: RecordSinkEncoder,
+}
+
+impl ClientSinkTarget {
+    pub fn new(encoder: RecordSinkEncoder) -> Self {
+        ClientSinkTarget { encoder }
+    }
+}
+
+impl Debug for ClientSinkTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Client")
+    }
+}
+
+impl SinkTarget for ClientSinkTarget {
+    fn sink(&self, record: &mut Record) -> FnResult<Option<Vec<u8>>> {
+        let results = self.encoder.encode(record)?;
+        Ok(Some(results))
+    }
+}
+
+/// Sink the columns of the given tags, as a line of comma-separated values, into a csv file, where
+/// a vertex or an edge is written as its id. The file is rewritten by each job, and is shared by all
+/// the workers of the job on this server in the append mode; each line is thus written by a single
+/// unbuffered `write`, so that the lines of different workers do not interleave.
+pub struct CsvSinkTarget {
+    path: String,
+    tags: Vec<Option<KeyId>>,
+    file: Arc<File>,
+}
+
+impl CsvSinkTarget {
+    /// Open the csv file of the given `path` under the configured directory, where `path` must be
+    /// relative and stay within the directory, after the symbolic links in it are resolved.
+    pub fn open(path: String, tags: Vec<Option<KeyId>>) -> FnGenResult<Self> {
+        if tags.is_empty() {
+            Err(ParsePbError::EmptyFieldError("tags of SinkCsv are empty".to_string()))?
+        }
+        if Path::new(&path)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            Err(FnGenError::unsupported_error(&format!(
+                "sink into csv file {:?} out of the sink directory",
+                path
+            )))?
+        }
+        let sink_dir = CSV_SINK_DIR
+            .read()
+            .expect("lock poisoned")
+            .clone()
+            .ok_or_else(|| FnGenError::unsupported_error("sink into csv files without a sink directory"))?
+            .canonicalize()?;
+        let full_path = resolve_csv_path(&sink_dir, &path)?;
+        if !full_path.starts_with(&sink_dir) {
+            Err(FnGenError::unsupported_error(&format!(
+                "sink into csv file {:?} out of the sink directory via {:?}",
+                path, full_path
+            )))?
+        }
+        let file = open_job_csv_file(full_path.clone())?;
+        Ok(CsvSinkTarget { path: full_path.to_string_lossy().to_string(), tags, file })
+    }
+}
+
+/// Resolve the symbolic links in the path of the csv file under the sink directory, where the file
+/// may not exist yet, but its parent directory must. A dangling link of the file fails to resolve.
+fn resolve_csv_path(sink_dir: &Path, path: &str) -> FnGenResult<PathBuf> {
+    let full_path = sink_dir.join(path);
+    if full_path.symlink_metadata().is_ok() {
+        return Ok(full_path.canonicalize()?);
+    }
+    let parent = full_path
+        .parent()
+        .unwrap_or(sink_dir)
+        .canonicalize()?;
+    let file_name = full_path
+        .file_name()
+        .ok_or_else(|| FnGenError::unsupported_error(&format!("sink into csv file {:?}", path)))?;
+    Ok(parent.join(file_name))
+}
+
+/// Open the csv file shared by the workers of the current job, where the first of them truncates the
+/// file written by a previous job, if any.
+fn open_job_csv_file(full_path: PathBuf) -> FnGenResult<Arc<File>> {
+    let (job_id, worker_index, local_peers) = pegasus::get_current_worker_checked()
+        .map(|worker| (worker.job_id, worker.index, worker.local_peers))
+        .unwrap_or((0, 0, 1));
+    let mut job_csv_files = JOB_CSV_FILES.lock().expect("lock poisoned");
+    let key = (job_id, full_path);
+    let (file, mut opened_by) = match job_csv_files.remove(&key) {
+        Some(entry) => entry,
+        None => {
+            File::create(&key.1)?;
+            (Arc::new(OpenOptions::new().append(true).open(&key.1)?), HashSet::new())
+        }
+    };
+    opened_by.insert(worker_index);
+    // the file is kept until all the workers of the job on this server have opened it
+    if (opened_by.len() as u32) < local_peers {
+        job_csv_files.insert(key, (file.clone(), opened_by));
+    }
+    Ok(file)
+}
+
+impl Debug for CsvSinkTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Csv({})", self.path)
+    }
+}
+
+impl SinkTarget for CsvSinkTarget {
+    fn sink(&self, record: &mut Record) -> FnResult<Option<Vec<u8>>> {
+        let mut fields = Vec::with_capacity(self.tags.len());
+        for tag in self.tags.iter() {
+            let field = match record.get(tag.clone()) {
+                Some(entry) => entry_to_csv_field(entry)?,
+                None => String::new(),
+            };
+            fields.push(field);
+        }
+        let mut line = fields.join(",");
+        line.push('\n');
+        (&*self.file)
+            .write_all(line.as_bytes())
+            .map_err(|e| Box::new(e) as DynError)?;
+        Ok(None)
+    }
+}
+
+fn entry_to_csv_field(entry: &DynEntry) -> FnResult<String> {
+    let field = if let Some(v) = entry.as_vertex() {
+        v.id().to_string()
+    } else if let Some(e) = entry.as_edge() {
+        e.id().to_string()
+    } else if let Some(obj) = entry.as_object() {
+        match obj {
+            Object::None => String::new(),
+            _ => obj.to_string(),
+        }
+    } else {
+        Err(FnExecError::unsupported_error(&format!("sink entry {:?} into csv", entry)))?
+    };
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        Ok(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Ok(field)
+    }
+}
+
+/// Sink each record into multiple targets, each of which sinks the columns of its own tags.
+#[derive(Debug)]
+pub struct MultiTargetSinker {
+    targets: Vec<Box<dyn SinkTarget>>,
+    /// Whether one of the targets is the client, to which the records are responded; otherwise,
+    /// the client is responded with the number of the records sinked into each target
+    sinks_into_client: bool,
+}
+
+impl MultiTargetSinker {
+    pub fn new(targets: Vec<Box<dyn SinkTarget>>, sinks_into_client: bool) -> Self {
+        MultiTargetSinker { targets, sinks_into_client }
+    }
+
+    pub fn sinks_into_client(&self) -> bool {
+        self.sinks_into_client
+    }
+
+    /// The encoder of the number of the records sinked into each of the targets by the whole job
+    pub fn counts_encoder(&self) -> SinkCountsEncoder {
+        let targets = self
+            .targets
+            .iter()
+            .map(|target| format!("{:?}", target))
+            .collect();
+        let encoder = DefaultSinkOp { tags: vec![None], id_name_mappings: vec![] }.into_encoder();
+        SinkCountsEncoder { targets, encoder }
+    }
+}
+
+impl FilterMapFunction<Record, Vec<u8>> for MultiTargetSinker {
+    fn exec(&self, mut input: Record) -> FnResult<Option<Vec<u8>>> {
+        let mut results = None;
+        for target in self.targets.iter() {
+            if let Some(target_results) = target.sink(&mut input)? {
+                results = Some(target_results);
+            }
+        }
+        Ok(results)
+    }
+}
+
+/// Respond to the client with the number of the records sinked into each target, as a map from
+/// the target to the number, which is the same for all the targets, as each of them sinks every record.
+#[derive(Debug)]
+pub struct SinkCountsEncoder {
+    targets: Vec<String>,
+    encoder: RecordSinkEncoder,
+}
+
+impl MapFunction<u64, Vec<u8>> for SinkCountsEncoder {
+    fn exec(&self, count: u64) -> FnResult<Vec<u8>> {
+        let counts: BTreeMap<Object, Object> = self
+            .targets
+            .iter()
+            .map(|target| {
+                info!("sink {} records into {}", count, target);
+                (Object::from(target.clone()), Object::from(count))
+            })
+            .collect();
+        let mut record = Record::new(Object::KV(counts), None);
+        Ok(self.encoder.encode(&mut record)?)
+    }
+}
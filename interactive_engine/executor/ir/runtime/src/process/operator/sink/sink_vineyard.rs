@@ -13,10 +13,11 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
 
-use graph_proxy::apis::{get_graph, GraphElement, WriteGraphProxy};
+use graph_proxy::apis::{get_graph, GraphElement, QueryParams, WriteGraphProxy};
 use graph_proxy::{GraphProxyError, VineyardGraphWriter};
 use ir_common::error::ParsePbError;
 use ir_common::generated::schema as schema_pb;
@@ -32,6 +33,8 @@ use crate::process::record::Record;
 pub struct GraphSinkEncoder {
     graph_writer: Arc<Mutex<VineyardGraphWriter>>,
     sink_keys: Vec<Option<KeyId>>,
+    // the params to read the primary keys of the sunk elements with, e.g., on the snapshot of the query
+    params: QueryParams,
 }
 
 impl Accumulator<Record, Record> for GraphSinkEncoder {
@@ -42,15 +45,20 @@ impl Accumulator<Record, Record> for GraphSinkEncoder {
                 FnExecError::get_tag_error(&format!("tag {:?} in GraphWriter on {:?}", sink_key, next))
             })?;
             if let Some(v) = entry.as_vertex() {
-                let vertex_pk = graph.get_primary_key(&v.id())?.ok_or_else(|| {
-                    GraphProxyError::query_store_error("get_primary_key() returns empty pk")
-                })?;
                 let label = v.label().ok_or_else(|| {
                     FnExecError::unexpected_data_error(&format!(
                         "label of vertex {:?} is None in sink_vineyard",
                         v.id()
                     ))
                 })?;
+                let vertex_pk = graph
+                    .get_primary_key_with_label(&v.id(), label, &self.params)?
+                    .ok_or_else(|| {
+                        GraphProxyError::store_permanent_error(
+                            "sink_vineyard",
+                            &format!("get_primary_key() of vertex {:?} returns empty pk", v.id()),
+                        )
+                    })?;
                 loop {
                     if let Ok(mut graph_writer_guard) = self.graph_writer.try_lock() {
                         graph_writer_guard.add_vertex(label.clone(), vertex_pk, v.get_details().clone())?;
@@ -58,20 +66,6 @@ impl Accumulator<Record, Record> for GraphSinkEncoder {
                     }
                 }
             } else if let Some(e) = entry.as_edge() {
-                let src_vertex_pk = graph
-                    .get_primary_key(&e.src_id)?
-                    .ok_or_else(|| {
-                        GraphProxyError::query_store_error(
-                            "get_primary_key() of src_vertex returns empty pk",
-                        )
-                    })?;
-                let dst_vertex_pk = graph
-                    .get_primary_key(&e.dst_id)?
-                    .ok_or_else(|| {
-                        GraphProxyError::query_store_error(
-                            "get_primary_key() of src_vertex returns empty pk",
-                        )
-                    })?;
                 let label = e.label().ok_or_else(|| {
                     FnExecError::unexpected_data_error(&format!(
                         "label of edge {:?} is None in sink_vineyard",
@@ -90,6 +84,22 @@ impl Accumulator<Record, Record> for GraphSinkEncoder {
                         e.id()
                     ))
                 })?;
+                let src_vertex_pk = graph
+                    .get_primary_key_with_label(&e.src_id, src_label, &self.params)?
+                    .ok_or_else(|| {
+                        GraphProxyError::store_permanent_error(
+                            "sink_vineyard",
+                            &format!("get_primary_key() of src_vertex {:?} returns empty pk", e.src_id),
+                        )
+                    })?;
+                let dst_vertex_pk = graph
+                    .get_primary_key_with_label(&e.dst_id, dst_label, &self.params)?
+                    .ok_or_else(|| {
+                        GraphProxyError::store_permanent_error(
+                            "sink_vineyard",
+                            &format!("get_primary_key() of dst_vertex {:?} returns empty pk", e.dst_id),
+                        )
+                    })?;
                 loop {
                     if let Ok(mut graph_writer_guard) = self.graph_writer.try_lock() {
                         graph_writer_guard.add_edge(
@@ -125,6 +135,7 @@ pub struct SinkVineyardOp {
     pub tags: Vec<Option<KeyId>>,
     pub graph_name: String,
     pub graph_schema: Option<schema_pb::Schema>,
+    pub extra_params: HashMap<String, String>,
 }
 
 impl SinkGen for SinkVineyardOp {
@@ -135,8 +146,13 @@ impl SinkGen for SinkVineyardOp {
                 &graph_schema,
                 pegasus::get_current_worker().index as i32,
             )?;
-            let graph_sink_encoder =
-                GraphSinkEncoder { graph_writer: Arc::new(Mutex::new(graph_writer)), sink_keys: self.tags };
+            let mut params = QueryParams::default();
+            params.extra_params = Some(self.extra_params);
+            let graph_sink_encoder = GraphSinkEncoder {
+                graph_writer: Arc::new(Mutex::new(graph_writer)),
+                sink_keys: self.tags,
+                params,
+            };
             if log_enabled!(log::Level::Debug) && pegasus::get_current_worker().index == 0 {
                 debug!("Runtime sink graph operator: {:?}", graph_sink_encoder,);
             }
@@ -232,7 +248,7 @@ mod tests {
             Ok(Box::new(
                 self.vertices
                     .lock()
-                    .map_err(|_e| GraphProxyError::query_store_error("scan_vertex failed"))?
+                    .map_err(|_e| GraphProxyError::internal_error("vertices are poisoned"))?
                     .clone()
                     .into_iter(),
             ))
@@ -242,7 +258,7 @@ mod tests {
             Ok(Box::new(
                 self.edges
                     .lock()
-                    .map_err(|_e| GraphProxyError::query_store_error("scan_edge failed"))?
+                    .map_err(|_e| GraphProxyError::internal_error("edges are poisoned"))?
                     .clone()
                     .into_iter(),
             ))
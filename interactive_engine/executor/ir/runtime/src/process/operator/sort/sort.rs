@@ -28,24 +28,39 @@ use crate::process::record::Record;
 
 #[derive(Debug)]
 struct RecordCompare {
-    tag_key_order: Vec<(TagKey, Order)>,
+    // the key, the direction, and whether the null keys are placed first
+    tag_key_order: Vec<(TagKey, Order, bool)>,
 }
 
 impl CompareFunction<Record> for RecordCompare {
+    // The same comparison is used to sort the records in each worker and to merge the sorted records
+    // of the workers, so it must be a total order, e.g., the null keys are ordered explicitly,
+    // rather than being incomparable to some types of the others.
     fn compare(&self, left: &Record, right: &Record) -> Ordering {
         let mut result = Ordering::Equal;
-        for (tag_key, order) in self.tag_key_order.iter() {
-            let left_obj = tag_key.get_arc_entry(left).ok();
-            let right_obj = tag_key.get_arc_entry(right).ok();
-            let ordering = left_obj.partial_cmp(&right_obj);
+        for (tag_key, order, nulls_first) in self.tag_key_order.iter() {
+            let left_obj = tag_key
+                .get_arc_entry(left)
+                .ok()
+                .filter(|entry| !entry.is_none());
+            let right_obj = tag_key
+                .get_arc_entry(right)
+                .ok()
+                .filter(|entry| !entry.is_none());
+            let ordering = match (left_obj, right_obj) {
+                (None, None) => Some(Ordering::Equal),
+                (None, Some(_)) => Some(if *nulls_first { Ordering::Less } else { Ordering::Greater }),
+                (Some(_), None) => Some(if *nulls_first { Ordering::Greater } else { Ordering::Less }),
+                (Some(left_obj), Some(right_obj)) => left_obj
+                    .partial_cmp(&right_obj)
+                    .map(|ordering| match order {
+                        Order::Desc => ordering.reverse(),
+                        _ => ordering,
+                    }),
+            };
             if let Some(ordering) = ordering {
                 if Ordering::Equal != ordering {
-                    result = {
-                        match order {
-                            Order::Desc => ordering.reverse(),
-                            _ => ordering,
-                        }
-                    };
+                    result = ordering;
                     break;
                 }
             }
@@ -70,12 +85,13 @@ impl TryFrom<algebra_pb::OrderBy> for RecordCompare {
     fn try_from(order_pb: algebra_pb::OrderBy) -> Result<Self, Self::Error> {
         let mut tag_key_order = Vec::with_capacity(order_pb.pairs.len());
         for order_pair in order_pb.pairs {
+            let nulls_first = order_pair.is_nulls_first()?;
             let key = order_pair
                 .key
                 .ok_or_else(|| ParsePbError::EmptyFieldError("key is empty in order".to_string()))?
                 .try_into()?;
             let order: Order = unsafe { ::std::mem::transmute(order_pair.order) };
-            tag_key_order.push((key, order));
+            tag_key_order.push((key, order, nulls_first));
         }
         Ok(RecordCompare { tag_key_order })
     }
@@ -85,7 +101,7 @@ impl TryFrom<algebra_pb::OrderBy> for RecordCompare {
 mod tests {
     use ahash::HashMap;
     use dyn_type::Object;
-    use graph_proxy::apis::{DynDetails, GraphElement, Vertex};
+    use graph_proxy::apis::{DynDetails, GraphElement, Vertex, ID};
     use ir_common::generated::algebra as pb;
     use ir_common::generated::common as common_pb;
     use ir_common::NameOrId;
@@ -96,7 +112,7 @@ mod tests {
     use crate::process::entry::Entry;
     use crate::process::operator::sort::CompareFunctionGen;
     use crate::process::operator::tests::{
-        init_source, init_source_with_tag, to_var_pb, PERSON_LABEL, TAG_A,
+        init_path_source_with_tag, init_source, init_source_with_tag, to_var_pb, PERSON_LABEL, TAG_A,
     };
     use crate::process::record::Record;
 
@@ -107,8 +123,13 @@ mod tests {
             let sort_opr = sort_opr.clone();
             |input, output| {
                 let mut stream = input.input_from(source.into_iter())?;
+                let unstable = sort_opr.unstable;
                 let sort_func = sort_opr.gen_cmp().unwrap();
-                stream = stream.sort_by(move |a, b| sort_func.compare(a, b))?;
+                if !unstable {
+                    stream = stream.sort_by(move |a, b| sort_func.compare(a, b))?;
+                } else {
+                    stream = stream.sort_unstable_by(move |a, b| sort_func.compare(a, b))?;
+                }
                 stream.sink_into(output)
             }
         })
@@ -123,8 +144,10 @@ mod tests {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(common_pb::Variable { tag: None, property: None, node_type: None }),
                 order: 1, // ascending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut result = sort_test(init_source(), sort_opr);
         let mut result_ids = vec![];
@@ -144,8 +167,10 @@ mod tests {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(common_pb::Variable { tag: None, property: None, node_type: None }),
                 order: 2, // descending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut result = sort_test(init_source(), sort_opr);
         let mut result_ids = vec![];
@@ -165,8 +190,10 @@ mod tests {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(common_pb::Variable::from("@.name".to_string())),
                 order: 2, // descending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut result = sort_test(init_source(), sort_opr);
         let mut result_name = vec![];
@@ -201,13 +228,16 @@ mod tests {
                 pb::order_by::OrderingPair {
                     key: Some(common_pb::Variable::from("@.name".to_string())),
                     order: 1, // ascending
+                    null_order: 0,
                 },
                 pb::order_by::OrderingPair {
                     key: Some(common_pb::Variable::from("@.age".to_string())),
                     order: 2, // descending
+                    null_order: 0,
                 },
             ],
             limit: None,
+            unstable: true,
         };
         let mut result = sort_test(source, sort_opr);
         let mut result_name_ages = vec![];
@@ -235,6 +265,41 @@ mod tests {
         assert_eq!(result_name_ages, expected_name_ages);
     }
 
+    // g.V().order().by(label).by(id)
+    #[test]
+    fn sort_by_label_and_id_test() {
+        let mut source = vec![];
+        for (id, label) in vec![(5, 1), (2, 0), (4, 0), (1, 1), (3, 0)] {
+            let v = Vertex::new(id, Some(label), DynDetails::default());
+            source.push(Record::new(v, None));
+        }
+        let sort_opr = pb::OrderBy {
+            pairs: vec![
+                pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from("@.~label".to_string())),
+                    order: 1, // ascending
+                    null_order: 0,
+                },
+                pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from("@.~id".to_string())),
+                    order: 1, // ascending
+                    null_order: 0,
+                },
+            ],
+            limit: None,
+            unstable: false,
+        };
+        let mut result = sort_test(source, sort_opr);
+        let mut result_label_ids = vec![];
+        while let Some(Ok(record)) = result.next() {
+            if let Some(element) = record.get(None).unwrap().as_vertex() {
+                result_label_ids.push((element.label().unwrap(), element.id()));
+            }
+        }
+        let expected_label_ids = vec![(0, 2), (0, 3), (0, 4), (1, 1), (1, 5)];
+        assert_eq!(result_label_ids, expected_label_ids);
+    }
+
     // g.V().as("a").order().by(select('a'))
     #[test]
     fn sort_by_tag_test() {
@@ -242,8 +307,10 @@ mod tests {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(to_var_pb(Some(TAG_A.into()), None)),
                 order: 2, // descending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut result = sort_test(init_source_with_tag(), sort_opr);
         let mut result_ids = vec![];
@@ -263,8 +330,10 @@ mod tests {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(to_var_pb(Some(TAG_A.into()), Some("age".into()))),
                 order: 2, // descending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut result = sort_test(init_source_with_tag(), sort_opr);
         let mut result_ids = vec![];
@@ -277,15 +346,17 @@ mod tests {
         assert_eq!(result_ids, expected_ids);
     }
 
-    // g.V().order().by('age',desc), where josh's age is null, and josh should be the last one
+    // g.V().order().by('age',desc), where josh's age is null, and josh should be the first one by default
     #[test]
     fn sort_by_null_desc_test() {
         let sort_opr = pb::OrderBy {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(common_pb::Variable::from("@.age".to_string())),
                 order: 2, // descending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut source = init_source();
         // josh's age is null
@@ -308,19 +379,21 @@ mod tests {
                 );
             }
         }
-        let expected_names = vec![object!("marko"), object!("vadas"), object!("josh")];
+        let expected_names = vec![object!("josh"), object!("marko"), object!("vadas")];
         assert_eq!(result_name, expected_names);
     }
 
-    // g.V().order().by('age',asc), where josh's age is null, and josh should be the first one
+    // g.V().order().by('age',asc), where josh's age is null, and josh should be the last one by default
     #[test]
     fn sort_by_null_asc_test() {
         let sort_opr = pb::OrderBy {
             pairs: vec![pb::order_by::OrderingPair {
                 key: Some(common_pb::Variable::from("@.age".to_string())),
                 order: 1, // ascending
+                null_order: 0,
             }],
             limit: None,
+            unstable: true,
         };
         let mut source = init_source();
         // josh's age is null
@@ -343,7 +416,141 @@ mod tests {
                 );
             }
         }
-        let expected_names = vec![object!("josh"), object!("vadas"), object!("marko")];
+        let expected_names = vec![object!("vadas"), object!("marko"), object!("josh")];
         assert_eq!(result_name, expected_names);
     }
+
+    // The persons 1..=8 spread over two workers, where the odd ones have no age, and the others are aged
+    // 20, 20, 30, 10 respectively, sorted by the age in the given direction and null order, then by the id.
+    fn sort_nulls_across_workers(order: i32, null_order: i32) -> Vec<ID> {
+        let sort_opr = pb::OrderBy {
+            pairs: vec![
+                pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from("@.age".to_string())),
+                    order,
+                    null_order,
+                },
+                pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from("@.~id".to_string())),
+                    order: 1, // ascending
+                    null_order: 0,
+                },
+            ],
+            limit: None,
+            unstable: true,
+        };
+        let mut conf = JobConf::new("sort_nulls_across_workers_test");
+        conf.set_workers(2);
+        let mut result = pegasus::run(conf, || {
+            let index = pegasus::get_current_worker().index as ID;
+            let sort_opr = sort_opr.clone();
+            move |input, output| {
+                let source = (1..=8).filter(|id| id % 2 == index).map(|id| {
+                    let mut map: HashMap<NameOrId, Object> = HashMap::default();
+                    match id {
+                        2 | 4 => map.insert("age".into(), object!(20)),
+                        6 => map.insert("age".into(), object!(30)),
+                        8 => map.insert("age".into(), object!(10)),
+                        // a missing or null age
+                        1 | 3 => map.insert("age".into(), Object::None),
+                        _ => None,
+                    };
+                    Record::new(Vertex::new(id, Some(PERSON_LABEL), DynDetails::new(map)), None)
+                });
+                let sort_func = sort_opr.gen_cmp().unwrap();
+                input
+                    .input_from(source)?
+                    .sort_unstable_by(move |a, b| sort_func.compare(a, b))?
+                    .sink_into(output)
+            }
+        })
+        .expect("build job failure");
+        let mut result_ids = vec![];
+        while let Some(Ok(record)) = result.next() {
+            result_ids.push(
+                record
+                    .get(None)
+                    .unwrap()
+                    .as_vertex()
+                    .unwrap()
+                    .id(),
+            );
+        }
+        result_ids
+    }
+
+    #[test]
+    fn sort_nulls_across_workers_test() {
+        use pb::order_by::ordering_pair::NullOrder;
+        // nulls last in ascending order, and first in descending order, by default
+        assert_eq!(sort_nulls_across_workers(1, NullOrder::Default as i32), vec![8, 2, 4, 6, 1, 3, 5, 7]);
+        assert_eq!(sort_nulls_across_workers(2, NullOrder::Default as i32), vec![1, 3, 5, 7, 6, 2, 4, 8]);
+        // the null order is not reversed by the direction
+        assert_eq!(
+            sort_nulls_across_workers(1, NullOrder::NullsFirst as i32),
+            vec![1, 3, 5, 7, 8, 2, 4, 6]
+        );
+        assert_eq!(sort_nulls_across_workers(2, NullOrder::NullsLast as i32), vec![6, 2, 4, 8, 1, 3, 5, 7]);
+        // the same total order in every run
+        for _ in 0..5 {
+            assert_eq!(
+                sort_nulls_across_workers(1, NullOrder::Default as i32),
+                vec![8, 2, 4, 6, 1, 3, 5, 7]
+            );
+        }
+    }
+
+    fn collect_path_ids(mut result: ResultStream<Record>) -> Vec<Vec<ID>> {
+        let mut result_paths = vec![];
+        while let Some(Ok(record)) = result.next() {
+            let path = record
+                .get(Some(TAG_A))
+                .unwrap()
+                .as_graph_path()
+                .unwrap();
+            result_paths.push(path.get_elem_ids());
+        }
+        result_paths
+    }
+
+    // g.V().as('a').repeat(out()).times(1..3).path().order().by(path().count(local))
+    #[test]
+    fn sort_by_path_len_test() {
+        let sort_opr = pb::OrderBy {
+            pairs: vec![pb::order_by::OrderingPair {
+                key: Some(common_pb::Variable::from("@0.~len".to_string())),
+                order: 2, // descending
+                null_order: 0,
+            }],
+            limit: None,
+            unstable: false,
+        };
+        let result = sort_test(init_path_source_with_tag(), sort_opr);
+        let expected_paths = vec![vec![2, 1, 2, 1], vec![1, 2, 1], vec![2, 1, 2], vec![1, 2]];
+        assert_eq!(collect_path_ids(result), expected_paths);
+    }
+
+    // order the paths by the name of their last vertices, and then by the length of the paths
+    #[test]
+    fn sort_by_path_last_vertex_test() {
+        let sort_opr = pb::OrderBy {
+            pairs: vec![
+                pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from("@0.~[-1].name".to_string())),
+                    order: 2, // descending
+                    null_order: 0,
+                },
+                pb::order_by::OrderingPair {
+                    key: Some(common_pb::Variable::from("@0.~len".to_string())),
+                    order: 1, // ascending
+                    null_order: 0,
+                },
+            ],
+            limit: None,
+            unstable: true,
+        };
+        let result = sort_test(init_path_source_with_tag(), sort_opr);
+        let expected_paths = vec![vec![1, 2], vec![2, 1, 2], vec![1, 2, 1], vec![2, 1, 2, 1]];
+        assert_eq!(collect_path_ids(result), expected_paths);
+    }
 }
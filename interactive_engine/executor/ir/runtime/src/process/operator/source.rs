@@ -21,8 +21,10 @@ use dyn_type::{object, Object};
 use graph_proxy::apis::graph::PKV;
 use graph_proxy::apis::partitioner::{PartitionInfo, PartitionedData};
 use graph_proxy::apis::{get_graph, ClusterInfo, Edge, QueryParams, Vertex, ID};
+use graph_proxy::utils::expr::eval_pred::extract_prop_equalities;
 use ir_common::error::{ParsePbError, ParsePbResult};
 use ir_common::generated::algebra as algebra_pb;
+use ir_common::generated::common as common_pb;
 use ir_common::generated::physical as pb;
 use ir_common::{KeyId, NameOrId};
 
@@ -72,6 +74,30 @@ impl SourceOperator {
         match op_kind {
             pb::physical_opr::operator::OpKind::Scan(mut scan) => {
                 if let Some(index_predicate) = scan.idx_predicate.take() {
+                    if index_predicate.is_range() {
+                        // a range, e.g., `between`, cannot be looked up by the index,
+                        // and is thus evaluated as a filter of the scan instead
+                        let range = common_pb::Expression::try_from(index_predicate)?;
+                        let params = scan
+                            .params
+                            .get_or_insert_with(|| algebra_pb::QueryParams {
+                                sample_ratio: 1.0,
+                                ..Default::default()
+                            });
+                        params.predicate = Some(and_predicate(params.predicate.take(), range));
+                        let source_op = SourceOperator::try_from(scan)?;
+                        debug!("Runtime source op of scan with range predicate {:?}", source_op);
+                        return Ok(source_op);
+                    } else if index_predicate
+                        .or_predicates
+                        .iter()
+                        .any(|and_predicate| and_predicate.is_range())
+                    {
+                        Err(FnGenError::unsupported_error(&format!(
+                            "range mixed up with primary keys in indexed scan {:?}",
+                            index_predicate
+                        )))?
+                    }
                     let ip = index_predicate.clone();
                     let ip2 = index_predicate.clone();
                     let mut source_op = SourceOperator::try_from(scan)?;
@@ -105,6 +131,12 @@ impl SourceOperator {
         }
     }
 
+    /// Hint the properties that the scanned vertices are immediately projected to
+    pub fn with_projection(mut self, projection: Option<Vec<NameOrId>>) -> Self {
+        self.query_params.projection = projection;
+        self
+    }
+
     /// Assign source vertex ids for each worker to call get_vertex
     fn set_src<P: PartitionInfo, C: ClusterInfo>(
         &mut self, ids: Vec<ID>, partitioner: Arc<dyn Router<P = P, C = C>>,
@@ -129,6 +161,103 @@ impl SourceOperator {
     }
 }
 
+/// The properties that the vertices of a scan are immediately projected to, if the next operator
+/// is a (non-append) project of the properties of the scanned vertices only, e.g., `@a.name` or `@.age`
+/// of a scan aliased as `a`, with which the storage may drop the other properties after the scan.
+pub fn scan_projection(scan: &pb::Scan, next_op: &pb::PhysicalOpr) -> Option<Vec<NameOrId>> {
+    let scan_opt: algebra_pb::scan::ScanOpt = unsafe { ::std::mem::transmute(scan.scan_opt) };
+    if scan_opt != algebra_pb::scan::ScanOpt::Vertex {
+        return None;
+    }
+    let project = match next_op.opr.as_ref()?.op_kind.as_ref()? {
+        pb::physical_opr::operator::OpKind::Project(project) if !project.is_append => project,
+        _ => return None,
+    };
+    let mut keys = Vec::with_capacity(project.mappings.len());
+    for mapping in &project.mappings {
+        let var = match mapping.expr.as_ref()?.operators.as_slice() {
+            [common_pb::ExprOpr { item: Some(common_pb::expr_opr::Item::Var(var)), .. }] => var,
+            _ => return None,
+        };
+        let is_scanned = match &var.tag {
+            None => true,
+            Some(tag) => match (NameOrId::try_from(tag.clone()).ok()?, scan.alias) {
+                (NameOrId::Id(tag), Some(alias)) => tag == alias,
+                _ => false,
+            },
+        };
+        match var.property.as_ref()?.item.as_ref()? {
+            common_pb::property::Item::Key(key) if is_scanned => keys.push(key.clone().try_into().ok()?),
+            _ => return None,
+        }
+    }
+    Some(keys)
+}
+
+/// The max number of the primary keys hinted by the filter of a scan
+const MAX_PRIMARY_KEY_HINTS: usize = 1024;
+
+/// The candidate primary keys of the vertices to scan, i.e., the combinations of the values that the
+/// properties are restricted to by the filter, e.g., `@.id within [1, 2] && @.name == "a"` gives
+/// `[(id, 1), (name, "a")]` and `[(id, 2), (name, "a")]`. It is up to the storage to pick up the
+/// primary key of a label out of the properties.
+fn primary_key_hints(params: &QueryParams) -> Option<Vec<PKV>> {
+    let equalities = extract_prop_equalities(params.filter.as_ref()?);
+    if equalities.is_empty() {
+        return None;
+    }
+    let mut combinations: Vec<Vec<(NameOrId, Object)>> = vec![vec![]];
+    for (key, values) in equalities {
+        if combinations.len() * values.len() > MAX_PRIMARY_KEY_HINTS {
+            return None;
+        }
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push((key.clone(), value.clone()));
+                    combination
+                })
+            })
+            .collect();
+    }
+    Some(
+        combinations
+            .into_iter()
+            .map(|mut combination| {
+                if combination.len() == 1 {
+                    PKV::from(combination.pop().unwrap())
+                } else {
+                    PKV::from(combination)
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Conjunct the range predicate to the predicate of the scan if any, as `(predicate) && (range)`
+fn and_predicate(
+    predicate: Option<common_pb::Expression>, range: common_pb::Expression,
+) -> common_pb::Expression {
+    if let Some(predicate) = predicate {
+        let brace = |brace: common_pb::expr_opr::Brace| common_pb::ExprOpr {
+            node_type: None,
+            item: Some(common_pb::expr_opr::Item::Brace(brace as i32)),
+        };
+        let mut operators = vec![brace(common_pb::expr_opr::Brace::LeftBrace)];
+        operators.extend(predicate.operators);
+        operators.push(brace(common_pb::expr_opr::Brace::RightBrace));
+        operators.push(common_pb::Logical::And.into());
+        operators.push(brace(common_pb::expr_opr::Brace::LeftBrace));
+        operators.extend(range.operators);
+        operators.push(brace(common_pb::expr_opr::Brace::RightBrace));
+        common_pb::Expression { operators }
+    } else {
+        range
+    }
+}
+
 impl SourceOperator {
     pub fn gen_source(self, worker_index: usize) -> FnGenResult<Box<dyn Iterator<Item = Record> + Send>> {
         let graph = get_graph().ok_or_else(|| FnGenError::NullGraphError)?;
@@ -137,17 +266,22 @@ impl SourceOperator {
             SourceType::Vertex => {
                 let mut v_source = Box::new(std::iter::empty()) as Box<dyn Iterator<Item = Vertex> + Send>;
                 if let Some(seeds) = &self.src {
-                    if let Some(src) = seeds.get(&(worker_index as u64)) {
-                        if !src.is_empty() {
-                            v_source = graph.get_vertex(src, &self.query_params)?;
-                        }
-                    }
+                    let src = seeds
+                        .get(&(worker_index as u64))
+                        .filter(|src| !src.is_empty());
                     if self.is_count_only {
-                        let count = v_source.count() as u64;
+                        // count the existing ids directly, without building the vertices
+                        let count = match src {
+                            Some(src) => graph.count_vertex_by_ids(src, &self.query_params)?,
+                            None => 0,
+                        };
                         return Ok(Box::new(
                             vec![Record::new(object!(count), self.alias.clone())].into_iter(),
                         ));
                     }
+                    if let Some(src) = src {
+                        v_source = graph.get_vertex(src, &self.query_params)?;
+                    }
                 } else if let Some(pkvs) = &self.primary_key_values {
                     if !self.query_params.has_labels() {
                         Err(FnGenError::unsupported_error(
@@ -184,17 +318,21 @@ impl SourceOperator {
             SourceType::Edge => {
                 let mut e_source = Box::new(std::iter::empty()) as Box<dyn Iterator<Item = Edge> + Send>;
                 if let Some(ref seeds) = self.src {
-                    if let Some(src) = seeds.get(&(worker_index as u64)) {
-                        if !src.is_empty() {
-                            e_source = graph.get_edge(src, &self.query_params)?;
-                        }
-                    }
+                    let src = seeds
+                        .get(&(worker_index as u64))
+                        .filter(|src| !src.is_empty());
                     if self.is_count_only {
-                        let count = e_source.count() as u64;
+                        let count = match src {
+                            Some(src) => graph.count_edge_by_ids(src, &self.query_params)?,
+                            None => 0,
+                        };
                         return Ok(Box::new(
                             vec![Record::new(object!(count), self.alias.clone())].into_iter(),
                         ));
                     }
+                    if let Some(src) = src {
+                        e_source = graph.get_edge(src, &self.query_params)?;
+                    }
                 } else {
                     if self.is_count_only {
                         let count = graph.count_edge(&self.query_params)?;
@@ -230,14 +368,131 @@ impl TryFrom<pb::Scan> for SourceOperator {
             algebra_pb::scan::ScanOpt::Edge => SourceType::Edge,
             algebra_pb::scan::ScanOpt::Table => SourceType::Table,
         };
-        let query_params = QueryParams::try_from(scan_pb.params)?;
+        let is_count_only = scan_pb.is_count_only_scan();
+        let mut query_params = QueryParams::try_from(scan_pb.params)?;
+        if let SourceType::Vertex = source_type {
+            // as a hint for the storage to prune the partitions to scan
+            query_params.primary_keys = primary_key_hints(&query_params);
+        }
         Ok(SourceOperator {
             query_params,
             src: None,
             primary_key_values: None,
             alias: scan_pb.alias,
             source_type,
-            is_count_only: scan_pb.is_count_only,
+            is_count_only,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    use dyn_type::Object;
+    use graph_proxy::apis::graph::PKV;
+    use ir_common::expr_parse::str_to_expr_pb;
+    use ir_common::generated::algebra as algebra_pb;
+    use ir_common::generated::physical as pb;
+    use ir_common::NameOrId;
+
+    use super::{scan_projection, SourceOperator};
+    use crate::process::operator::tests::to_expr_var_pb;
+
+    fn scan(scan_opt: i32, predicate: &str) -> SourceOperator {
+        let scan_pb = pb::Scan {
+            scan_opt,
+            alias: None,
+            params: Some(algebra_pb::QueryParams {
+                tables: vec![],
+                columns: vec![],
+                is_all_columns: false,
+                limit: None,
+                predicate: Some(str_to_expr_pb(predicate.to_string()).unwrap()),
+                sample_ratio: 1.0,
+                extra: HashMap::new(),
+            }),
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        SourceOperator::try_from(scan_pb).unwrap()
+    }
+
+    fn primary_keys(source_op: &SourceOperator) -> Option<Vec<Vec<(NameOrId, Object)>>> {
+        source_op
+            .query_params
+            .primary_keys
+            .as_ref()
+            .map(|pkvs| pkvs.iter().map(|pkv| pkv.to_vec()).collect())
+    }
+
+    #[test]
+    fn hint_primary_keys_of_vertex_scan() {
+        let id: NameOrId = "id".into();
+        let name: NameOrId = "name".into();
+        let source_op = scan(0, "@.id == 1");
+        assert_eq!(primary_keys(&source_op), Some(vec![vec![(id.clone(), Object::from(1))]]));
+        assert!(matches!(
+            source_op
+                .query_params
+                .primary_keys
+                .as_ref()
+                .unwrap()[0],
+            PKV::One(_)
+        ));
+
+        let source_op = scan(0, "@.id within [1, 2] && @.name == \"a\" && @.age > 10");
+        assert_eq!(
+            primary_keys(&source_op),
+            Some(vec![
+                vec![(id.clone(), Object::from(1)), (name.clone(), Object::from("a"))],
+                vec![(id.clone(), Object::from(2)), (name.clone(), Object::from("a"))],
+            ])
+        );
+
+        // no hint from a disjunction, or for an edge scan
+        assert!(primary_keys(&scan(0, "@.id == 1 || @.id == 2")).is_none());
+        assert!(primary_keys(&scan(1, "@.id == 1")).is_none());
+    }
+
+    #[test]
+    fn hint_projection_of_vertex_scan() {
+        let scan_pb = |scan_opt: i32| pb::Scan {
+            scan_opt,
+            alias: Some(0),
+            params: None,
+            idx_predicate: None,
+            is_count_only: false,
+            meta_data: None,
+        };
+        let project = |mappings: Vec<(Option<NameOrId>, Option<NameOrId>)>, is_append: bool| {
+            pb::PhysicalOpr::from(pb::physical_opr::operator::OpKind::Project(pb::Project {
+                mappings: mappings
+                    .into_iter()
+                    .map(|(tag, key)| pb::project::ExprAlias {
+                        expr: Some(to_expr_var_pb(tag, key)),
+                        alias: None,
+                    })
+                    .collect(),
+                is_append,
+            }))
+        };
+        let name: NameOrId = "name".into();
+        let age: NameOrId = "age".into();
+
+        // `@.name` and `@0.age` of the scan aliased as 0
+        let next_op = project(vec![(None, Some(name.clone())), (Some(0.into()), Some(age.clone()))], false);
+        assert_eq!(scan_projection(&scan_pb(0), &next_op), Some(vec![name.clone(), age.clone()]));
+
+        // no hint for an edge scan, an appended project, a whole vertex, or a vertex of another tag
+        assert!(scan_projection(&scan_pb(1), &next_op).is_none());
+        let next_op = project(vec![(None, Some(name.clone()))], true);
+        assert!(scan_projection(&scan_pb(0), &next_op).is_none());
+        let next_op = project(vec![(None, None)], false);
+        assert!(scan_projection(&scan_pb(0), &next_op).is_none());
+        let next_op = project(vec![(Some(1.into()), Some(name.clone()))], false);
+        assert!(scan_projection(&scan_pb(0), &next_op).is_none());
+    }
+}
@@ -13,16 +13,23 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use ahash::HashMap;
 use dyn_type::Object;
 use ir_common::generated::algebra::join::JoinKind;
 use ir_common::generated::physical as pb;
+use ir_common::generated::physical::physical_opr::operator::OpKind;
 use ir_common::KeyId;
-use pegasus::api::function::{BinaryFunction, FnResult};
+use pegasus::api::function::{BinaryFunction, FilterFunction, FilterMapFunction, FnResult, MapFunction};
+use pegasus_server::job_pb as server_pb;
 
 use crate::error::{FnExecError, FnGenError, FnGenResult};
-use crate::process::entry::DynEntry;
-use crate::process::functions::ApplyGen;
-use crate::process::record::Record;
+use crate::process::entry::{DynEntry, Entry};
+use crate::process::functions::{ApplyGen, FoldGen, GroupGen, KeyFunction};
+use crate::process::operator::accum::accumulator::Accumulator;
+use crate::process::operator::accum::RecordAccumulator;
+use crate::process::operator::filter::FilterFuncGen;
+use crate::process::operator::map::FilterMapFuncGen;
+use crate::process::record::{Record, RecordKey};
 
 #[derive(Debug)]
 struct ApplyOperator {
@@ -116,3 +123,265 @@ impl ApplyGen<Record, Vec<Record>, Option<Record>> for pb::Apply {
         Ok(Box::new(apply_operator))
     }
 }
+
+/// The name of the worker's counter of the scopes that the subtasks of `Apply` are run in,
+/// i.e., one per parent record, or one per batch of parent records with a `BatchedApply`.
+pub const APPLY_SCOPES_METRIC: &str = "ir.apply_scopes";
+
+/// Count a scope that a subtask of `Apply` is run in, see `APPLY_SCOPES_METRIC`.
+pub fn count_apply_scope() {
+    if let Some(counter) = pegasus::metrics::counter(APPLY_SCOPES_METRIC) {
+        counter.inc();
+    }
+}
+
+/// The `Apply` that runs its subtask once over a batch of parent records, instead of once per parent
+/// record, to save the overhead of a scope per parent record. Each parent record is tagged with its
+/// index in the batch, under a key beyond all the columns of the batch and the aliases of the subtask,
+/// by which the results of the subtask are joined back to their parent records.
+///
+/// It only applies to the subtask whose operators process each record alone and preserve its columns,
+/// e.g., `EdgeExpand`, `GetV` and `Select`, as the others mix the records of different parents, except
+/// that the subtask may end with an aggregation, e.g., the `count()` of `where(out().count().is(gt(3)))`,
+/// followed by `Select`s and `Project`s only. The aggregation, and the operators after it, are not run
+/// in the subtask, but evaluated over the results of the rest of the subtask per parent record instead.
+pub struct BatchedApply {
+    apply: ApplyOperator,
+    tagger: BatchTagger,
+    // the number of the operators of the subtask that are run over the batch of parent records
+    num_batched_oprs: usize,
+    // the aggregation that ends the subtask, with the operators after it
+    aggregate: Option<(BatchedAggregate, Vec<BatchedPostOpr>)>,
+}
+
+enum BatchedAggregate {
+    Count(Box<dyn MapFunction<u64, Record>>),
+    Fold(RecordAccumulator),
+    Group {
+        key: Box<dyn KeyFunction<Record, RecordKey, Record>>,
+        accum: RecordAccumulator,
+        map: Box<dyn MapFunction<(RecordKey, Record), Record>>,
+    },
+}
+
+enum BatchedPostOpr {
+    Filter(Box<dyn FilterFunction<Record>>),
+    Project(Box<dyn FilterMapFunction<Record, Record>>),
+}
+
+impl BatchedApply {
+    /// Returns `None` if the `Apply` cannot be run over a batch of parent records.
+    pub fn new(apply: &pb::Apply) -> FnGenResult<Option<Self>> {
+        let join_kind: JoinKind = unsafe { ::std::mem::transmute(apply.join_kind) };
+        match join_kind {
+            JoinKind::Inner | JoinKind::LeftOuter | JoinKind::Semi | JoinKind::Anti => {}
+            _ => return Ok(None),
+        }
+        let sub_plan = match apply.sub_plan.as_ref() {
+            Some(sub_plan) if apply.keys.is_empty() => &sub_plan.plan,
+            _ => return Ok(None),
+        };
+        let mut min_batch_key = 0;
+        let mut num_batched_oprs = 0;
+        for opr in sub_plan {
+            let alias = match opr
+                .opr
+                .as_ref()
+                .and_then(|opr| opr.op_kind.as_ref())
+            {
+                Some(OpKind::Repartition(_)) | Some(OpKind::Select(_)) => None,
+                Some(OpKind::Edge(edge)) => edge.alias,
+                Some(OpKind::Vertex(getv)) => getv.alias,
+                Some(OpKind::Unfold(unfold)) => unfold.alias,
+                Some(OpKind::Project(project)) if project.is_append => project
+                    .mappings
+                    .iter()
+                    .filter_map(|mapping| mapping.alias)
+                    .max(),
+                Some(OpKind::GroupBy(_)) => break,
+                _ => return Ok(None),
+            };
+            if let Some(alias) = alias {
+                min_batch_key = min_batch_key.max(alias as usize + 1);
+            }
+            num_batched_oprs += 1;
+        }
+        let aggregate = match sub_plan.get(num_batched_oprs..) {
+            Some([group, post_oprs @ ..]) => match Self::gen_aggregate(group, post_oprs)? {
+                Some(aggregate) => Some(aggregate),
+                None => return Ok(None),
+            },
+            _ => None,
+        };
+        Ok(Some(BatchedApply {
+            apply: ApplyOperator { join_kind, alias: apply.alias },
+            tagger: BatchTagger { min_batch_key },
+            num_batched_oprs,
+            aggregate,
+        }))
+    }
+
+    fn gen_aggregate(
+        group: &pb::PhysicalOpr, post_oprs: &[pb::PhysicalOpr],
+    ) -> FnGenResult<Option<(BatchedAggregate, Vec<BatchedPostOpr>)>> {
+        let aggregate = match group
+            .opr
+            .as_ref()
+            .and_then(|opr| opr.op_kind.as_ref())
+        {
+            Some(OpKind::GroupBy(group)) if group.mappings.is_empty() => {
+                if let server_pb::AccumKind::Cnt = group.get_accum_kind() {
+                    BatchedAggregate::Count(group.gen_fold_map()?)
+                } else {
+                    BatchedAggregate::Fold(group.gen_fold_accum()?)
+                }
+            }
+            Some(OpKind::GroupBy(group)) => BatchedAggregate::Group {
+                key: group.gen_group_key()?,
+                accum: group.gen_group_accum()?,
+                map: group.gen_group_map()?,
+            },
+            _ => return Ok(None),
+        };
+        let mut batched_post_oprs = Vec::with_capacity(post_oprs.len());
+        for opr in post_oprs {
+            match opr
+                .opr
+                .as_ref()
+                .and_then(|opr| opr.op_kind.as_ref())
+            {
+                Some(OpKind::Select(select)) => {
+                    batched_post_oprs.push(BatchedPostOpr::Filter(select.clone().gen_filter()?))
+                }
+                Some(OpKind::Project(project)) => {
+                    batched_post_oprs.push(BatchedPostOpr::Project(project.clone().gen_filter_map()?))
+                }
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some((aggregate, batched_post_oprs)))
+    }
+
+    /// The number of the operators of the subtask to run over the batch of parent records, which are
+    /// followed by the aggregation, if any, that is evaluated per parent record by `join()`.
+    pub fn get_num_batched_oprs(&self) -> usize {
+        self.num_batched_oprs
+    }
+
+    /// The tagger of the batches of parent records to run the subtask over
+    pub fn get_tagger(&self) -> BatchTagger {
+        self.tagger
+    }
+
+    /// Evaluate the aggregation, and the operators after it, over the results of a parent record.
+    fn aggregate(
+        &self, aggregate: &BatchedAggregate, post_oprs: &[BatchedPostOpr], subs: Vec<Record>,
+    ) -> FnResult<Vec<Record>> {
+        let aggregated = match aggregate {
+            BatchedAggregate::Count(count_map) => vec![count_map.exec(subs.len() as u64)?],
+            BatchedAggregate::Fold(accum) => {
+                let mut accum = accum.clone();
+                for sub in subs {
+                    accum.accum(sub)?;
+                }
+                vec![accum.finalize()?]
+            }
+            BatchedAggregate::Group { key, accum, map } => {
+                let mut groups: HashMap<RecordKey, RecordAccumulator> = HashMap::default();
+                for sub in subs {
+                    let (group_key, group_value) = key.get_kv(sub)?;
+                    groups
+                        .entry(group_key)
+                        .or_insert_with(|| accum.clone())
+                        .accum(group_value)?;
+                }
+                let mut aggregated = Vec::with_capacity(groups.len());
+                for (group_key, mut accum) in groups {
+                    aggregated.push(map.exec((group_key, accum.finalize()?))?);
+                }
+                aggregated
+            }
+        };
+        let mut results = Vec::with_capacity(aggregated.len());
+        'next_record: for mut record in aggregated {
+            for opr in post_oprs {
+                match opr {
+                    BatchedPostOpr::Filter(filter) => {
+                        if !filter.test(&record)? {
+                            continue 'next_record;
+                        }
+                    }
+                    BatchedPostOpr::Project(project) => match project.exec(record)? {
+                        Some(projected) => record = projected,
+                        None => continue 'next_record,
+                    },
+                }
+            }
+            results.push(record);
+        }
+        Ok(results)
+    }
+
+    /// Join the results of the subtask back to the batch of parent records, in the order of the parents.
+    pub fn join(&self, parents: Vec<Record>, subs: Vec<Record>) -> FnResult<Vec<Record>> {
+        let batch_key = self.tagger.get_batch_key(&parents);
+        let mut grouped_subs: Vec<Vec<Record>> = vec![vec![]; parents.len()];
+        for sub in subs {
+            let group = sub
+                .get(Some(batch_key as KeyId))
+                .and_then(|entry| entry.as_object())
+                .and_then(|idx| idx.as_u64().ok())
+                .and_then(|idx| grouped_subs.get_mut(idx as usize))
+                .ok_or_else(|| {
+                    FnExecError::unexpected_data_error(&format!(
+                        "the sub record {:?} in batched `Apply` is not derived from a parent",
+                        sub
+                    ))
+                })?;
+            group.push(sub);
+        }
+        let mut joined = Vec::with_capacity(parents.len());
+        for (parent, mut sub) in parents.into_iter().zip(grouped_subs) {
+            if let Some((aggregate, post_oprs)) = self.aggregate.as_ref() {
+                sub = self.aggregate(aggregate, post_oprs, sub)?;
+            }
+            let record = match self.apply.join_kind {
+                JoinKind::Semi => Some(parent).filter(|_| !sub.is_empty()),
+                JoinKind::Anti => Some(parent).filter(|_| sub.is_empty()),
+                _ => self.apply.exec(parent, sub)?,
+            };
+            joined.extend(record);
+        }
+        Ok(joined)
+    }
+}
+
+/// Tags each of a batch of parent records with its index in the batch, see `BatchedApply`.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchTagger {
+    // the least key beyond all the aliases defined in the subtask
+    min_batch_key: usize,
+}
+
+impl BatchTagger {
+    fn get_batch_key(&self, parents: &[Record]) -> usize {
+        parents
+            .iter()
+            .filter_map(|parent| parent.get_columns().keys().next_back())
+            .fold(self.min_batch_key, |key, column| key.max(column + 1))
+    }
+
+    /// Tag each of the batch of parent records with its index in the batch.
+    pub fn tag_parents(&self, parents: Vec<Record>) -> impl Iterator<Item = Record> {
+        let batch_key = self.get_batch_key(&parents);
+        parents
+            .into_iter()
+            .enumerate()
+            .map(move |(idx, mut parent)| {
+                parent
+                    .get_columns_mut()
+                    .insert(batch_key, DynEntry::new(Object::from(idx as u64)));
+                parent
+            })
+    }
+}
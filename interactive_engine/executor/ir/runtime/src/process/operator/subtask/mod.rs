@@ -13,6 +13,7 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 mod apply;
+pub use apply::{count_apply_scope, BatchTagger, BatchedApply, APPLY_SCOPES_METRIC};
 
 use crate::error::FnGenResult;
 use crate::process::functions::ApplyGen;
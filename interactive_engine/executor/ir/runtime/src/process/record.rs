@@ -16,6 +16,7 @@
 use std::borrow::BorrowMut;
 use std::hash::Hash;
 
+use dyn_type::Object;
 use graph_proxy::apis::{Edge, GraphPath, Vertex};
 use graph_proxy::utils::expr::eval::Context;
 use ir_common::{KeyId, NameOrId};
@@ -63,6 +64,10 @@ impl Record {
         self.curr = entry;
     }
 
+    pub fn get_columns(&self) -> &VecMap<DynEntry> {
+        &self.columns
+    }
+
     pub fn get_columns_mut(&mut self) -> &mut VecMap<DynEntry> {
         self.columns.borrow_mut()
     }
@@ -97,6 +102,16 @@ impl Record {
         }
     }
 
+    /// Pad the columns of the given `tags` that are absent in the record with null.
+    pub fn pad_none(&mut self, tags: &[KeyId]) {
+        for tag in tags {
+            if !self.columns.contains_key(*tag as usize) {
+                self.columns
+                    .insert(*tag as usize, DynEntry::new(Object::None));
+            }
+        }
+    }
+
     /// To join this record with `other` record. After the join, the columns
     /// from both sides will be merged (and deduplicated). The `curr` entry of the joined
     /// record will be specified according to `is_left_opt`, namely, if
@@ -159,6 +174,11 @@ impl RecordKey {
     pub fn take(self) -> Vec<DynEntry> {
         self.key_fields
     }
+
+    /// Whether any of the key fields is null
+    pub fn has_null(&self) -> bool {
+        self.key_fields.iter().any(|key| key.is_none())
+    }
 }
 
 pub struct RecordExpandIter<E> {
@@ -230,8 +250,21 @@ impl<E: Entry + 'static> Iterator for RecordPathExpandIter<E> {
     }
 }
 
+/// The version of the wire format of `Record`, which is written ahead of each record.
+/// The unversioned format of the last release (version 1) starts with the tag of `curr`,
+/// i.e., 0 or 1, and thus can be told apart from the versioned ones, which start from 2.
+/// The other payloads exchanged among workers carry it in a version header, see `write_version_header()`.
+pub const RECORD_CODEC_VERSION: u8 = 2;
+
 impl Encode for Record {
     fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_u8(RECORD_CODEC_VERSION)?;
+        self.write_body(writer)
+    }
+}
+
+impl Record {
+    fn write_body<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
         match &self.curr {
             None => {
                 writer.write_u8(0)?;
@@ -252,7 +285,24 @@ impl Encode for Record {
 
 impl Decode for Record {
     fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
-        let opt = reader.read_u8()?;
+        let version = reader.read_u8()?;
+        match version {
+            RECORD_CODEC_VERSION => {
+                let opt = reader.read_u8()?;
+                Record::read_body(opt, reader)
+            }
+            // the unversioned format, where the first byte is the tag of `curr`
+            0 | 1 => Record::read_body(version, reader),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown record codec version {}", version),
+            )),
+        }
+    }
+}
+
+impl Record {
+    fn read_body<R: ReadExt>(opt: u8, reader: &mut R) -> std::io::Result<Self> {
         let curr = if opt == 0 { None } else { Some(<DynEntry>::read_from(reader)?) };
         let size = <u64>::read_from(reader)? as usize;
         let mut columns = VecMap::with_capacity(size);
@@ -265,8 +315,36 @@ impl Decode for Record {
     }
 }
 
+/// The flag of the version header of the payloads other than `Record` exchanged among workers, e.g.,
+/// `RecordKey`, whose unversioned format of the last release starts with a u32 length instead.
+/// A length never sets the highest bit, and thus can be told apart from the header.
+const VERSION_HEADER_FLAG: u32 = 1 << 31;
+
+/// Write the version header ahead of a payload whose unversioned format starts with a u32 length.
+pub(crate) fn write_version_header<W: WriteExt>(writer: &mut W) -> std::io::Result<()> {
+    writer.write_u32(VERSION_HEADER_FLAG | RECORD_CODEC_VERSION as u32)
+}
+
+/// Read the leading u32 length of a payload written by `write_version_header()` ahead,
+/// which is also accepted in the unversioned format.
+pub(crate) fn read_versioned_len<R: ReadExt>(reader: &mut R) -> std::io::Result<u32> {
+    let header = reader.read_u32()?;
+    if header & VERSION_HEADER_FLAG == 0 {
+        // the unversioned format, where the header is the length itself
+        Ok(header)
+    } else if header & !VERSION_HEADER_FLAG == RECORD_CODEC_VERSION as u32 {
+        reader.read_u32()
+    } else {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unknown codec version {}", header & !VERSION_HEADER_FLAG),
+        ))
+    }
+}
+
 impl Encode for RecordKey {
     fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_version_header(writer)?;
         writer.write_u32(self.key_fields.len() as u32)?;
         for key in self.key_fields.iter() {
             key.write_to(writer)?
@@ -277,7 +355,7 @@ impl Encode for RecordKey {
 
 impl Decode for RecordKey {
     fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
-        let len = reader.read_u32()?;
+        let len = read_versioned_len(reader)?;
         let mut key_fields = Vec::with_capacity(len as usize);
         for _i in 0..len {
             let entry = <DynEntry>::read_from(reader)?;
@@ -286,3 +364,102 @@ impl Decode for RecordKey {
         Ok(RecordKey { key_fields })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use graph_proxy::apis::DynDetails;
+
+    use super::*;
+    use crate::process::entry::PairEntry;
+
+    fn build_record() -> Record {
+        let mut record = Record::new(Vertex::new(1, Some(0), DynDetails::default()), Some(0));
+        record.append(object!("marko"), Some(1));
+        let pair = PairEntry::new(
+            DynEntry::new(object!(1)),
+            DynEntry::new(Object::Vector(vec![object!(1), object!(2)])),
+        );
+        record.append(pair, Some(2));
+        record
+    }
+
+    fn assert_record_eq(r1: &Record, r2: &Record) {
+        assert_eq!(r1.get(None), r2.get(None));
+        for tag in 0..3 {
+            assert_eq!(r1.get(Some(tag)), r2.get(Some(tag)));
+        }
+    }
+
+    #[test]
+    fn record_codec_test() {
+        let record = build_record();
+        let mut bytes = vec![];
+        record.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes[0], RECORD_CODEC_VERSION);
+        let decoded = Record::read_from(&mut bytes.as_slice()).unwrap();
+        assert_record_eq(&record, &decoded);
+    }
+
+    #[test]
+    fn record_codec_unversioned_test() {
+        // the format of the last release comes without a version byte
+        let record = build_record();
+        let mut bytes = vec![];
+        record.write_body(&mut bytes).unwrap();
+        let decoded = Record::read_from(&mut bytes.as_slice()).unwrap();
+        assert_record_eq(&record, &decoded);
+
+        let empty = Record::default();
+        let mut bytes = vec![];
+        empty.write_body(&mut bytes).unwrap();
+        let decoded = Record::read_from(&mut bytes.as_slice()).unwrap();
+        assert!(decoded.get(None).is_none());
+    }
+
+    #[test]
+    fn record_codec_corrupted_test() {
+        let record = build_record();
+        let mut bytes = vec![];
+        record.write_to(&mut bytes).unwrap();
+
+        // unknown version
+        let mut corrupted = bytes.clone();
+        corrupted[0] = 0xFF;
+        let err = Record::read_from(&mut corrupted.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // unknown entry type of `curr`
+        let mut corrupted = bytes.clone();
+        corrupted[2] = 0xFF;
+        let err = Record::read_from(&mut corrupted.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // truncated
+        let truncated = &bytes[0..bytes.len() / 2];
+        assert!(Record::read_from(&mut &truncated[..]).is_err());
+    }
+
+    #[test]
+    fn record_key_codec_test() {
+        let key = RecordKey::new(vec![DynEntry::new(object!(1)), DynEntry::new(object!("marko"))]);
+        let mut bytes = vec![];
+        key.write_to(&mut bytes).unwrap();
+        assert_eq!(RecordKey::read_from(&mut bytes.as_slice()).unwrap(), key);
+
+        // the format of the last release comes without a version header
+        let mut bytes = vec![];
+        bytes.write_u32(2).unwrap();
+        for field in key.key_fields.iter() {
+            field.write_to(&mut bytes).unwrap();
+        }
+        assert_eq!(RecordKey::read_from(&mut bytes.as_slice()).unwrap(), key);
+
+        // unknown version
+        let mut bytes = vec![];
+        bytes
+            .write_u32(VERSION_HEADER_FLAG | 0xFF)
+            .unwrap();
+        let err = RecordKey::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
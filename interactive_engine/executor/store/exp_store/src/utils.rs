@@ -13,6 +13,7 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::iter::FusedIterator;
 use std::path::Path;
 use std::process::{Command, ExitStatus};
 
@@ -115,6 +116,8 @@ where
                     if let Some(iter_val) = self.iters.pop() {
                         *iter = iter_val;
                     } else {
+                        // drop the exhausted iterator, which may not be fused by itself
+                        self.curr_iter = None;
                         return None;
                     }
                 }
@@ -128,3 +131,62 @@ where
         }
     }
 }
+
+/// Once all the iterators have been exhausted, `IterList` keeps returning `None`,
+/// even if the iterators are not fused by themselves.
+impl<T, I> FusedIterator for IterList<T, I> where T: Iterator<Item = I> {}
+
+#[cfg(test)]
+mod test {
+    use std::iter::FusedIterator;
+
+    use super::IterList;
+
+    /// An iterator that is not fused, as it resumes yielding after returning `None`
+    struct Resumable {
+        count: usize,
+    }
+
+    impl Iterator for Resumable {
+        type Item = usize;
+
+        fn next(&mut self) -> Option<usize> {
+            self.count += 1;
+            if self.count % 3 == 0 {
+                None
+            } else {
+                Some(self.count)
+            }
+        }
+    }
+
+    fn assert_fused<T: FusedIterator>(_: &T) {}
+
+    #[test]
+    fn test_iter_list_fused() {
+        let mut iter_list = IterList::new(vec![Resumable { count: 0 }, Resumable { count: 0 }]);
+        assert_fused(&iter_list);
+        assert_eq!(iter_list.by_ref().collect::<Vec<_>>(), vec![1, 2, 1, 2]);
+        // calling `next()` after exhaustion must keep returning `None`
+        for _ in 0..5 {
+            assert_eq!(iter_list.next(), None);
+        }
+
+        let mut empty = IterList::<Resumable, usize>::new(vec![]);
+        assert_eq!(empty.next(), None);
+        assert_eq!(empty.next(), None);
+    }
+
+    #[test]
+    fn test_iter_list_fused_with_limit() {
+        // e.g., `filter_limit!` that filters and then takes at most `n` elements
+        let mut iter_list = IterList::new(vec![Resumable { count: 0 }, Resumable { count: 3 }]);
+        let limited: Vec<usize> = iter_list
+            .by_ref()
+            .filter(|x| x % 2 == 0)
+            .take(10)
+            .collect();
+        assert_eq!(limited, vec![4, 2]);
+        assert_eq!(iter_list.by_ref().find(|x| x % 2 == 0), None);
+    }
+}
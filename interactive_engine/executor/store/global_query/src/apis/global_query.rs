@@ -22,6 +22,18 @@ use crate::apis::graph_schema::Schema;
 pub type PartitionVertexIds = (PartitionId, Vec<VertexId>);
 pub type PartitionLabeledVertexIds = (PartitionId, Vec<(Option<LabelId>, Vec<VertexId>)>);
 
+/// The capabilities of a store regarding the filters pushed down to it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StoreCaps {
+    /// Whether the store filters the rows by the given `Condition`
+    pub supports_row_filter: bool,
+    /// Whether the store fetches only the properties of the given `output_prop_ids`
+    pub supports_column_filter: bool,
+    /// Whether the store evaluates the `Regex` comparison in the given `Condition`,
+    /// e.g., by a native regex index, which is otherwise evaluated out of the store
+    pub supports_regex: bool,
+}
+
 pub trait GlobalGraphQuery: Send + Sync {
     type V: Vertex;
     type E: Edge;
@@ -61,6 +73,12 @@ pub trait GlobalGraphQuery: Send + Sync {
     fn get_edge_properties(
         &self, si: SnapshotId, ids: Vec<PartitionLabeledVertexIds>, output_prop_ids: Option<&Vec<PropId>>,
     ) -> Self::EI;
+    /// Count the vertices of the given ids that exist in the snapshot, i.e., check the existence of the ids.
+    /// By default, it fetches the vertices without any property and counts them.
+    fn count_vertices_by_ids(&self, si: SnapshotId, ids: Vec<PartitionLabeledVertexIds>) -> u64 {
+        self.get_vertex_properties(si, ids, Some(&vec![]))
+            .count() as u64
+    }
 
     fn get_all_vertices(
         &self, si: SnapshotId, labels: &Vec<LabelId>, condition: Option<&Condition>,
@@ -84,4 +102,12 @@ pub trait GlobalGraphQuery: Send + Sync {
     fn translate_vertex_id(&self, vertex_id: VertexId) -> VertexId;
 
     fn get_schema(&self, si: SnapshotId) -> Option<Arc<dyn Schema>>;
+
+    fn get_store_caps(&self) -> StoreCaps;
+
+    /// The oldest snapshot that the store can still serve, as the older ones may have been compacted away,
+    /// in which case the store may serve the oldest one instead. `None` indicates that it is unknown.
+    fn get_oldest_snapshot_id(&self) -> Option<SnapshotId> {
+        None
+    }
 }
@@ -25,4 +25,13 @@ pub trait GraphPartitionManager: Send + Sync {
         &self, label_id: LabelId, key: &String,
     ) -> Option<(PartitionId, VertexId)>;
     fn get_vertex_id_by_primary_keys(&self, label_id: LabelId, pks: &[Property]) -> Option<VertexId>;
+    /// Look up the vertex id by the pks in the index of the given partition only,
+    /// and return `None` if the vertex does not reside in this partition.
+    /// The default implementation falls back to the global lookup, for the stores without partition-local indices.
+    fn get_vertex_id_by_primary_keys_in_partition(
+        &self, partition_id: PartitionId, label_id: LabelId, pks: &[Property],
+    ) -> Option<VertexId> {
+        self.get_vertex_id_by_primary_keys(label_id, pks)
+            .filter(|vid| self.get_partition_id(*vid) as PartitionId == partition_id)
+    }
 }
@@ -21,5 +21,10 @@ pub trait Schema: Send + Sync {
     fn get_prop_name(&self, prop_id: PropId) -> Option<String>;
     fn get_label_id(&self, name: &str) -> Option<LabelId>;
     fn get_label_name(&self, label: LabelId) -> Option<String>;
+    /// The properties that make up the primary key of the vertices of the label, in the order that they
+    /// are declared, which may be of multiple columns. `None` indicates that the primary key is unknown to the schema.
+    fn get_primary_keys(&self, _label: LabelId) -> Option<Vec<PropId>> {
+        None
+    }
     fn to_proto(&self) -> Vec<u8>;
 }
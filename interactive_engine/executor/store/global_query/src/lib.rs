@@ -22,7 +22,7 @@ extern crate log;
 pub mod apis;
 pub mod store_impl;
 
-pub use apis::global_query::{GlobalGraphQuery, PartitionLabeledVertexIds, PartitionVertexIds};
+pub use apis::global_query::{GlobalGraphQuery, PartitionLabeledVertexIds, PartitionVertexIds, StoreCaps};
 pub use apis::graph_partition::GraphPartitionManager;
 pub use apis::graph_schema::Schema;
 pub use groot_store::api as store_api;
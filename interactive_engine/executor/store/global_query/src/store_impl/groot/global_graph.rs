@@ -30,7 +30,7 @@ use groot_store::db::graph::store::GraphStore;
 use groot_store::db::storage::RawBytes;
 use itertools::Itertools;
 
-use crate::apis::global_query::GlobalGraphQuery;
+use crate::apis::global_query::{GlobalGraphQuery, StoreCaps};
 use crate::apis::global_query::{PartitionLabeledVertexIds, PartitionVertexIds};
 use crate::apis::graph_partition::GraphPartitionManager;
 use crate::apis::graph_schema::Schema;
@@ -75,6 +75,42 @@ impl GlobalGraph {
         }
     }
 
+    /// Count the edges of the given vertices by their degrees in the partitions, which neither
+    /// builds the edges nor decodes their properties.
+    fn get_degrees(
+        &self, si: SnapshotId, vertex_ids: Vec<PartitionVertexIds>, edge_labels: &Vec<LabelId>,
+        is_out: bool,
+    ) -> Vec<(VertexId, usize)> {
+        let labels: Vec<Option<i32>> = if edge_labels.is_empty() {
+            vec![None]
+        } else {
+            edge_labels
+                .iter()
+                .map(|label| Some(*label as i32))
+                .collect()
+        };
+        let mut res = vec![];
+        for (partition_id, vertex_ids) in vertex_ids {
+            if let Some(store) = self.graph_partitions.get(&partition_id) {
+                for vertex_id in vertex_ids {
+                    let degree = labels
+                        .iter()
+                        .map(|label| {
+                            if is_out {
+                                store.get_out_degree(si, vertex_id, *label)
+                            } else {
+                                store.get_in_degree(si, vertex_id, *label)
+                            }
+                            .unwrap()
+                        })
+                        .sum();
+                    res.push((vertex_id, degree));
+                }
+            }
+        }
+        res
+    }
+
     fn parse_property_id(prop_ids: Option<&Vec<PropId>>) -> Option<Vec<PropertyId>> {
         prop_ids.map(|v| {
             v.to_owned()
@@ -229,18 +265,32 @@ impl GlobalGraphQuery for GlobalGraph {
         &self, si: SnapshotId, src_ids: Vec<PartitionVertexIds>, edge_labels: &Vec<LabelId>,
         condition: Option<&Condition>,
     ) -> Box<dyn Iterator<Item = (i64, usize)>> {
+        if condition.is_none() {
+            return Box::new(
+                self.get_degrees(si, src_ids, edge_labels, true)
+                    .into_iter(),
+            );
+        }
+        // the edges are filtered by the condition, without decoding the properties for output
         Box::new(
-            self.get_in_edges(si, src_ids, edge_labels, condition, None, None, 0)
+            self.get_out_edges(si, src_ids, edge_labels, condition, None, Some(&vec![]), 0)
                 .map(|(vertex_id, ei)| (vertex_id, ei.count())),
         )
     }
 
     fn count_in_edges(
-        &self, si: SnapshotId, src_ids: Vec<PartitionVertexIds>, edge_labels: &Vec<LabelId>,
+        &self, si: SnapshotId, dst_ids: Vec<PartitionVertexIds>, edge_labels: &Vec<LabelId>,
         condition: Option<&Condition>,
     ) -> Box<dyn Iterator<Item = (i64, usize)>> {
+        if condition.is_none() {
+            return Box::new(
+                self.get_degrees(si, dst_ids, edge_labels, false)
+                    .into_iter(),
+            );
+        }
+        // the edges are filtered by the condition, without decoding the properties for output
         Box::new(
-            self.get_out_edges(si, src_ids, edge_labels, condition, None, None, 0)
+            self.get_in_edges(si, dst_ids, edge_labels, condition, None, Some(&vec![]), 0)
                 .map(|(vertex_id, ei)| (vertex_id, ei.count())),
         )
     }
@@ -408,6 +458,18 @@ impl GlobalGraphQuery for GlobalGraph {
         let graph_def = partition.get_graph_def().ok()?;
         Some(Arc::new(GlobalGraphSchema::new(graph_def)))
     }
+
+    fn get_store_caps(&self) -> StoreCaps {
+        StoreCaps { supports_row_filter: true, supports_column_filter: true, supports_regex: true }
+    }
+
+    /// A snapshot is served only if it is served by all the local partitions
+    fn get_oldest_snapshot_id(&self) -> Option<SnapshotId> {
+        self.graph_partitions
+            .values()
+            .filter_map(|partition| partition.get_gc_snapshot_id())
+            .max()
+    }
 }
 
 thread_local! {
@@ -544,6 +606,21 @@ impl GraphPartitionManager for GlobalGraph {
             get_vertex_id_by_primary_keys(label_id as i32, pks_bytes) as VertexId
         }))
     }
+
+    /// The vertex id is hashed out of the pks, rather than looked up in an index, and is then
+    /// checked against the given partition, which must be one of the local partitions.
+    fn get_vertex_id_by_primary_keys_in_partition(
+        &self, partition_id: PartitionId, label_id: LabelId, pks: &[Property],
+    ) -> Option<VertexId> {
+        if !self
+            .graph_partitions
+            .contains_key(&partition_id)
+        {
+            return None;
+        }
+        self.get_vertex_id_by_primary_keys(label_id, pks)
+            .filter(|vid| self.get_partition_id(*vid) as PartitionId == partition_id)
+    }
 }
 
 fn floor_div(x: i64, y: i64) -> i64 {
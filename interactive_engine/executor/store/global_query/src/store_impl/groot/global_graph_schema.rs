@@ -81,6 +81,24 @@ impl Schema for GlobalGraphSchema {
         Some(type_def.get_label())
     }
 
+    fn get_primary_keys(&self, label: u32) -> Option<Vec<u32>> {
+        let type_def = self
+            .graph_def
+            .label_to_types
+            .get(&(label as i32))?;
+        let pk_ids = type_def.get_pk_ids();
+        if pk_ids.is_empty() {
+            None
+        } else {
+            Some(
+                pk_ids
+                    .iter()
+                    .map(|pk_id| *pk_id as u32)
+                    .collect(),
+            )
+        }
+    }
+
     fn to_proto(&self) -> Vec<u8> {
         unimplemented!()
     }
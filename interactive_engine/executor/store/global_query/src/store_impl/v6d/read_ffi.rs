@@ -21,7 +21,7 @@ use ir_common::generated::common as common_pb;
 use ir_common::KeyId;
 
 use crate::store_api::prelude::Property;
-use crate::{GlobalGraphQuery, GraphPartitionManager, Schema};
+use crate::{GlobalGraphQuery, GraphPartitionManager, Schema, StoreCaps};
 
 pub type FfiVertexId = u64;
 pub type FfiEdgeId = u64;
@@ -1552,6 +1552,11 @@ impl GlobalGraphQuery for FFIGraphStore {
         let ret = FFISchema::new(schema);
         Some(Arc::new(ret))
     }
+
+    fn get_store_caps(&self) -> StoreCaps {
+        // neither the condition nor the output properties are handled by vineyard
+        StoreCaps { supports_row_filter: false, supports_column_filter: false, supports_regex: false }
+    }
 }
 
 unsafe impl Send for FFIGraphStore {}
@@ -2240,45 +2245,35 @@ impl GraphPartitionManager for VineyardPartitionManager {
     }
 
     fn get_vertex_id_by_primary_keys(&self, label_id: LabelId, pks: &[Property]) -> Option<VertexId> {
-        if pks.len() != 1 {
-            warn!("multiple pks are not supported in Vineyard {:?}", pks);
-            return None;
-        } else {
-            let pk = pks.get(0).unwrap();
-            let key = match pk {
-                // Vineyard only supports `id` as pk.
-                Property::Char(i) => Some(i.to_string()),
-                Property::Short(i) => Some(i.to_string()),
-                Property::Int(i) => Some(i.to_string()),
-                Property::Long(i) => Some(i.to_string()),
-                Property::String(s) => Some(s.clone()),
-                _ => None,
-            };
-            if let Some(key) = key {
-                let mut partition_id = 0;
-                let mut vertex_id = 0;
-                let c_key = CString::new(key.as_str()).unwrap();
-                unsafe {
-                    let ret = v6d_get_vertex_id_from_primary_key(
-                        self.graph,
-                        label_id,
-                        c_key.as_ptr(),
-                        &mut vertex_id,
-                        &mut partition_id,
-                    );
-                    if 0 != ret {
-                        error!(
-                            "get vertex id from primary key {:?} with label id {:?} failed",
-                            key, label_id
-                        );
-                        return None;
-                    }
-                }
-                Some(vertex_id)
-            } else {
-                None
-            }
-        }
+        let key = encode_v6d_primary_key(pks)?;
+        self.get_vertex_id_by_primary_key(label_id, &key)
+            .map(|(_, vertex_id)| vertex_id)
+    }
+
+    /// The partition is told by Vineyard along with the vertex id, which saves the mapping of the vertex id.
+    fn get_vertex_id_by_primary_keys_in_partition(
+        &self, partition_id: PartitionId, label_id: LabelId, pks: &[Property],
+    ) -> Option<VertexId> {
+        let key = encode_v6d_primary_key(pks)?;
+        self.get_vertex_id_by_primary_key(label_id, &key)
+            .filter(|(pid, _)| *pid == partition_id)
+            .map(|(_, vertex_id)| vertex_id)
+    }
+}
+
+/// Encode the pks into the key of Vineyard, which only supports `id` as pk.
+fn encode_v6d_primary_key(pks: &[Property]) -> Option<String> {
+    if pks.len() != 1 {
+        warn!("multiple pks are not supported in Vineyard {:?}", pks);
+        return None;
+    }
+    match &pks[0] {
+        Property::Char(i) => Some(i.to_string()),
+        Property::Short(i) => Some(i.to_string()),
+        Property::Int(i) => Some(i.to_string()),
+        Property::Long(i) => Some(i.to_string()),
+        Property::String(s) => Some(s.clone()),
+        _ => None,
     }
 }
 
@@ -21,8 +21,12 @@ pub use operand::Operand;
 pub use predicate::CmpOperator;
 pub use predicate::PredCondition;
 
+use dyn_type::Object;
+
 use super::filter::ElemFilter;
+use super::property::Property;
 use super::{Edge, Vertex};
+use crate::schema::PropId;
 use crate::GraphResult;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -37,6 +41,17 @@ impl Condition {
     pub fn new(pred: PredCondition) -> Self {
         Condition::Pred(pred)
     }
+
+    /// The condition that the primary key `key` of an element equals `value`, which allows a scan
+    /// by primary key to be pushed down to the storage without an index of the primary keys.
+    pub fn from_pk_equality(key: PropId, value: &Object) -> GraphResult<Self> {
+        let value = Property::from_borrow_object(value.as_borrow())?;
+        Ok(Condition::new(PredCondition::new_predicate(
+            Operand::PropId(key),
+            CmpOperator::Equal,
+            Operand::Const(value),
+        )))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
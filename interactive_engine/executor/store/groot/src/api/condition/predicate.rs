@@ -12,11 +12,13 @@
 //! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 //! See the License for the specific language governing permissions and
 
+use regex::Regex;
+
 use super::operand::Operand;
 use crate::api::filter::ElemFilter;
 use crate::api::{property::*, Edge, Vertex};
 use crate::schema::prelude::*;
-use crate::{unwrap_some_or, GraphResult};
+use crate::{unwrap_some_or, GraphError, GraphResult};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum PredCondition {
@@ -29,7 +31,14 @@ impl PredCondition {
         PredCondition::HasProp(prop_id)
     }
     pub fn new_predicate(left: Operand, op: CmpOperator, right: Operand) -> Self {
-        PredCondition::Cmp(CmpCondition { left, op, right })
+        // a constant pattern of `Regex` is compiled once here, instead of on every row
+        let regex = match (op, &right) {
+            (CmpOperator::Regex, Operand::Const(Property::String(pattern))) => {
+                Some(Regex::new(pattern).map_err(|e| format!("invalid regex {:?}: {}", pattern, e)))
+            }
+            _ => None,
+        };
+        PredCondition::Cmp(CmpCondition { left, op, right, regex })
     }
 }
 
@@ -51,11 +60,30 @@ impl ElemFilter for PredCondition {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct CmpCondition {
     left: Operand,
     op: CmpOperator,
     right: Operand,
+    // the compiled constant pattern of `Regex`, or the error of compiling it
+    regex: Option<Result<Regex, String>>,
+}
+
+impl PartialEq for CmpCondition {
+    fn eq(&self, other: &Self) -> bool {
+        // the compiled regex is determined by the operands
+        self.left == other.left && self.op == other.op && self.right == other.right
+    }
+}
+
+impl CmpCondition {
+    fn compute(&self, left: &Property, right: &Property) -> GraphResult<bool> {
+        match &self.regex {
+            Some(Ok(regex)) => Ok(regex.is_match(left.get_string()?)),
+            Some(Err(msg)) => Err(GraphError::invalid_condition(msg.clone())),
+            None => self.op.compute(left, right),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -70,6 +98,7 @@ pub enum CmpOperator {
     WithOut,
     StartWith,
     EndWith,
+    Regex,
 }
 
 impl CmpOperator {
@@ -85,6 +114,7 @@ impl CmpOperator {
             CmpOperator::WithOut => right.contains(left).map(|ret| !ret),
             CmpOperator::StartWith => left.start_with(right),
             CmpOperator::EndWith => left.end_with(right),
+            CmpOperator::Regex => left.regex_match(right),
         }
     }
 }
@@ -105,7 +135,7 @@ impl ElemFilter for CmpCondition {
             right = owned_right.as_ref();
         }
         let right = unwrap_some_or!(right, return Ok(false));
-        self.op.compute(left, right)
+        self.compute(left, right)
     }
 
     fn filter_edge<E: Edge>(&self, edge: &E) -> GraphResult<bool> {
@@ -123,6 +153,6 @@ impl ElemFilter for CmpCondition {
             right = owned_right.as_ref();
         }
         let right = unwrap_some_or!(right, return Ok(false));
-        self.op.compute(left, right)
+        self.compute(left, right)
     }
 }
@@ -591,3 +591,63 @@ fn test_condition_filter_edge() {
     assert_eq!(vertices[0].get_id(), 3);
     assert_eq!(vertices[1].get_id(), 4);
 }
+
+#[test]
+fn test_condition_from_pk_equality() {
+    let entites = prepare_entites().collect::<Vec<LocalEntity>>();
+    let condition = Condition::from_pk_equality(1, &dyn_type::Object::from(30)).unwrap();
+    let matched = entites
+        .iter()
+        .filter(|v| condition.filter_vertex(*v).unwrap_or(false))
+        .map(|v| v.id)
+        .collect::<Vec<i64>>();
+    assert_eq!(matched, vec![3]);
+
+    let condition =
+        Condition::from_pk_equality(2, &dyn_type::Object::from("Bstr, GraphScope".to_string())).unwrap();
+    let matched = entites
+        .iter()
+        .filter(|v| condition.filter_vertex(*v).unwrap_or(false))
+        .map(|v| v.id)
+        .collect::<Vec<i64>>();
+    assert_eq!(matched, vec![2]);
+}
+
+#[test]
+fn test_condition_regex_operation() {
+    let entites = prepare_entites().collect::<Vec<LocalEntity>>();
+    let predicate = PredCondition::new_predicate(
+        Operand::PropId(2),
+        CmpOperator::Regex,
+        Operand::Const(Property::String("^Astr.*".to_owned())),
+    );
+    let e1 = entites
+        .clone()
+        .into_iter()
+        .filter(|v| predicate.filter_vertex(v).unwrap_or(false))
+        .collect::<Vec<LocalEntity>>();
+    assert_eq!(1, e1.len());
+    assert_eq!(e1[0].get_id(), 1);
+
+    let predicate = PredCondition::new_predicate(
+        Operand::PropId(2),
+        CmpOperator::Regex,
+        Operand::Const(Property::String("engine$".to_owned())),
+    );
+    assert_eq!(
+        2,
+        entites
+            .clone()
+            .into_iter()
+            .filter(|v| predicate.filter_edge(v).unwrap_or(false))
+            .collect::<Vec<LocalEntity>>()
+            .len()
+    );
+
+    let predicate = PredCondition::new_predicate(
+        Operand::PropId(2),
+        CmpOperator::Regex,
+        Operand::Const(Property::String("(".to_owned())),
+    );
+    assert!(predicate.filter_vertex(&entites[0]).is_err());
+}
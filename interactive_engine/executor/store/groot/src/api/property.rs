@@ -203,6 +203,15 @@ impl Property {
         let right = rhs.get_string()?;
         Ok(left.ends_with(right))
     }
+
+    // only work for string property, where the rhs is the regular expression
+    pub(crate) fn regex_match(&self, rhs: &Self) -> GraphResult<bool> {
+        let left = self.get_string()?;
+        let pattern = rhs.get_string()?;
+        let regex = regex::Regex::new(pattern)
+            .map_err(|e| GraphError::invalid_condition(format!("invalid regex {:?}: {}", pattern, e)))?;
+        Ok(regex.is_match(left))
+    }
 }
 
 fn objects_to_list_property(v: &[Object]) -> GraphResult<Property> {
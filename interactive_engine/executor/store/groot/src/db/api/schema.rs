@@ -180,6 +180,8 @@ pub struct TypeDef {
     label: String,
     label_id: LabelId,
     properties: HashMap<PropertyId, PropDef>,
+    // the ids of the properties making up the primary key, in the order that they are declared
+    pk_ids: Vec<PropertyId>,
     type_enum: TypeEnumPb,
 }
 
@@ -196,6 +198,11 @@ impl TypeDef {
         self.properties.get(&prop_id)
     }
 
+    /// The ids of the properties making up the primary key, in the order that they are declared
+    pub fn get_pk_ids(&self) -> &[PropertyId] {
+        &self.pk_ids
+    }
+
     pub fn get_label(&self) -> String {
         self.label.clone()
     }
@@ -205,6 +212,9 @@ impl TypeDef {
     }
 
     pub fn add_property(&mut self, prop: PropDef) {
+        if prop.pk && !self.pk_ids.contains(&prop.id) {
+            self.pk_ids.push(prop.id);
+        }
         self.properties.insert(prop.id, prop);
     }
 
@@ -217,12 +227,16 @@ impl TypeDef {
         let label = proto.get_label();
         let label_id = proto.get_label_id().get_id();
         let mut properties = HashMap::new();
+        let mut pk_ids = vec![];
         for propertydef_pb in proto.get_props() {
             let property_def = PropDef::from_proto(propertydef_pb)?;
+            if property_def.pk {
+                pk_ids.push(property_def.id);
+            }
             properties.insert(property_def.id, property_def);
         }
         let type_enum = proto.get_type_enum();
-        Ok(Self::new(version_id, label.to_string(), label_id, properties, type_enum))
+        Ok(Self::new(version_id, label.to_string(), label_id, properties, pk_ids, type_enum))
     }
 
     pub fn to_proto(&self) -> GraphResult<TypeDefPb> {
@@ -230,10 +244,20 @@ impl TypeDef {
         typedef_pb.set_version_id(self.version);
         typedef_pb.set_label(self.label.clone());
         typedef_pb.mut_label_id().set_id(self.label_id);
+        // the primary key is written first, so as to keep the order that it is declared in
+        for pk_id in &self.pk_ids {
+            if let Some(property_def) = self.properties.get(pk_id) {
+                typedef_pb
+                    .mut_props()
+                    .push(property_def.to_proto()?);
+            }
+        }
         for property_def in self.properties.values() {
-            typedef_pb
-                .mut_props()
-                .push(property_def.to_proto()?);
+            if !property_def.pk {
+                typedef_pb
+                    .mut_props()
+                    .push(property_def.to_proto()?);
+            }
         }
         typedef_pb.set_type_enum(self.type_enum);
         Ok(typedef_pb)
@@ -257,9 +281,9 @@ impl TypeDef {
 
     fn new(
         version: i32, label: String, label_id: LabelId, properties: HashMap<PropertyId, PropDef>,
-        type_enum: TypeEnumPb,
+        pk_ids: Vec<PropertyId>, type_enum: TypeEnumPb,
     ) -> Self {
-        TypeDef { version, label, label_id, properties, type_enum }
+        TypeDef { version, label, label_id, properties, pk_ids, type_enum }
     }
 
     #[cfg(test)]
@@ -305,8 +329,7 @@ impl TypeDefBuilder {
         default_value: Option<Value>, pk: bool, comment: String,
     ) -> &mut Self {
         self.type_def
-            .properties
-            .insert(id, PropDef::new(id, inner_id, name, r#type, default_value, pk, comment));
+            .add_property(PropDef::new(id, inner_id, name, r#type, default_value, pk, comment));
         self
     }
 
@@ -488,4 +511,27 @@ mod tests {
         let type_def2 = TypeDef::from_bytes(&bytes).unwrap();
         assert_eq!(type_def, type_def2);
     }
+
+    #[test]
+    fn test_type_def_pk_order() {
+        let mut builder = TypeDefBuilder::new();
+        // the primary key is declared as (id 3, id 1), regardless of the order of the ids
+        for (id, pk) in vec![(3, true), (2, false), (1, true)] {
+            builder.add_property(
+                id,
+                id,
+                format!("prop-{}", id),
+                ValueType::Long,
+                None,
+                pk,
+                "comment".to_string(),
+            );
+        }
+        let type_def = builder.build();
+        assert_eq!(type_def.get_pk_ids(), &[3, 1]);
+        let bytes = type_def.to_bytes().unwrap();
+        let type_def2 = TypeDef::from_bytes(&bytes).unwrap();
+        assert_eq!(type_def2.get_pk_ids(), &[3, 1]);
+        assert_eq!(type_def, type_def2);
+    }
 }
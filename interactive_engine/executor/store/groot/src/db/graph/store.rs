@@ -39,6 +39,8 @@ pub struct GraphStore {
     data_download_root: String,
     // ensure all modification to graph is in ascending order of snapshot id
     si_guard: AtomicIsize,
+    // the snapshot of the latest garbage collection, below which the snapshots can no longer be served
+    gc_si: AtomicIsize,
     lock: GraphMutexLock<()>,
 }
 
@@ -626,6 +628,8 @@ impl MultiVersionGraph for GraphStore {
             let out_table_prefix = edge_table_prefix(et, EdgeDirection::Out);
             self.delete_table_by_prefix(out_table_prefix, false)?;
         }
+        self.gc_si
+            .fetch_max(si as isize, Ordering::Relaxed);
         Ok(())
     }
 
@@ -765,6 +769,7 @@ impl GraphStore {
             data_root: data_root,
             data_download_root: download_root,
             si_guard: AtomicIsize::new(0),
+            gc_si: AtomicIsize::new(-1),
             lock: GraphMutexLock::new(()),
         };
         Ok(ret)
@@ -864,6 +869,17 @@ impl GraphStore {
             .store(si as isize, Ordering::Relaxed);
     }
 
+    /// The oldest snapshot that can be served, i.e., that of the latest garbage collection,
+    /// or `None` if no garbage has been collected since the store is opened.
+    pub fn get_gc_snapshot_id(&self) -> Option<SnapshotId> {
+        let gc_si = self.gc_si.load(Ordering::Relaxed);
+        if gc_si < 0 {
+            None
+        } else {
+            Some(gc_si as SnapshotId)
+        }
+    }
+
     pub fn ingest(&self, data_path: &str) -> GraphResult<()> {
         let p = [data_path];
         self.storage.load(&p)
@@ -1248,6 +1264,19 @@ mod tests {
         do_test(path, |graph| tests::graph::test_si_guard(graph));
     }
 
+    #[test]
+    fn test_gc_snapshot_id() {
+        let path = "test_gc_snapshot_id";
+        do_test(path, |graph| {
+            assert_eq!(graph.get_gc_snapshot_id(), None);
+            graph.gc(5).unwrap();
+            assert_eq!(graph.get_gc_snapshot_id(), Some(5));
+            // never goes back
+            graph.gc(3).unwrap();
+            assert_eq!(graph.get_gc_snapshot_id(), Some(5));
+        });
+    }
+
     #[test]
     fn test_backup_engine() {
         let test_dir = "store_test/test_backup_engine";